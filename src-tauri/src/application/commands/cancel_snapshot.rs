@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use crate::application::services::transfer::TransferRegistry;
+use crate::domain::entities::snapshot::SnapshotStatus;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+pub struct CancelSnapshotCommand {
+    pub snapshot_id: SnapshotId,
+}
+
+pub struct CancelSnapshotHandler {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    transfers: Arc<TransferRegistry>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+}
+
+impl CancelSnapshotHandler {
+    pub fn new(
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        transfers: Arc<TransferRegistry>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            snapshot_repo,
+            transfers,
+            audit_logger,
+        }
+    }
+
+    /// Cancel the export (or import) running for a snapshot. The running
+    /// handler kills wsl.exe and marks a snapshot being created
+    /// `Failed("cancelled")`. An `InProgress` snapshot with nothing running
+    /// (left over from a crash) is marked directly.
+    pub async fn handle(&self, cmd: CancelSnapshotCommand) -> Result<(), DomainError> {
+        if !self.transfers.cancel(&cmd.snapshot_id) {
+            let mut snapshot = self.snapshot_repo.get_by_id(&cmd.snapshot_id).await?;
+            if !matches!(snapshot.status, SnapshotStatus::InProgress) {
+                return Err(DomainError::SnapshotError(format!(
+                    "Snapshot '{}' has no running operation",
+                    cmd.snapshot_id
+                )));
+            }
+            snapshot.status = SnapshotStatus::Failed("cancelled".into());
+            self.snapshot_repo.save(&snapshot).await?;
+        }
+
+        self.audit_logger
+            .log("snapshot.cancel", &cmd.snapshot_id.to_string())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use chrono::Utc;
+
+    fn make_snapshot(status: SnapshotStatus) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "test".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: "/tmp/snap-001.tar".into(),
+            file_size: MemorySize::zero(),
+            parent_id: None,
+            created_at: Utc::now(),
+            status,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn audit() -> MockAuditLoggerPort {
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, target| action == "snapshot.cancel" && target == "snap-001")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        audit
+    }
+
+    fn cmd() -> CancelSnapshotCommand {
+        CancelSnapshotCommand {
+            snapshot_id: SnapshotId::from_string("snap-001".into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_signals_running_operation() {
+        let transfers = Arc::new(TransferRegistry::default());
+        let guard = transfers.begin(&cmd().snapshot_id).unwrap();
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_save().never();
+
+        let handler = CancelSnapshotHandler::new(Arc::new(repo), transfers, Arc::new(audit()));
+        handler.handle(cmd()).await.unwrap();
+        assert!(matches!(guard.check(), Err(DomainError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_marks_stale_in_progress_snapshot() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id()
+            .returning(|_| Ok(make_snapshot(SnapshotStatus::InProgress)));
+        repo.expect_save()
+            .withf(|s| matches!(&s.status, SnapshotStatus::Failed(r) if r == "cancelled"))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let handler = CancelSnapshotHandler::new(
+            Arc::new(repo),
+            Arc::new(TransferRegistry::default()),
+            Arc::new(audit()),
+        );
+        handler.handle(cmd()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancel_rejects_finished_snapshot() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id()
+            .returning(|_| Ok(make_snapshot(SnapshotStatus::Completed)));
+        repo.expect_save().never();
+
+        let handler = CancelSnapshotHandler::new(
+            Arc::new(repo),
+            Arc::new(TransferRegistry::default()),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler.handle(cmd()).await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+}
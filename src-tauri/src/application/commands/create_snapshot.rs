@@ -1,12 +1,17 @@
 use std::sync::Arc;
 
-use crate::application::path_utils::windows_to_linux_path;
-use crate::domain::entities::snapshot::{ExportFormat, Snapshot, SnapshotStatus, SnapshotType};
+use crate::application::path_utils::{existing_local_path, windows_to_linux_path};
+use crate::application::services::encryption::{self, SnapshotSecret};
+use crate::application::services::transfer::{TransferGuard, TransferPhase, TransferRegistry};
+use crate::application::services::{chunk_store, compression, incremental, integrity, sidecar};
+use crate::domain::entities::snapshot::{
+    ExportFormat, Snapshot, SnapshotChunk, SnapshotStatus, SnapshotStorage, SnapshotType,
+};
 use crate::domain::errors::DomainError;
 use crate::domain::ports::audit_logger::AuditLoggerPort;
 use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
 use crate::domain::ports::wsl_manager::WslManagerPort;
-use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
+use crate::domain::value_objects::{DistroName, EncryptionKey, MemorySize, SnapshotId};
 use chrono::Utc;
 
 pub struct CreateSnapshotCommand {
@@ -15,12 +20,19 @@ pub struct CreateSnapshotCommand {
     pub description: Option<String>,
     pub format: ExportFormat,
     pub output_dir: String,
+    /// When set, store only the delta against this (tar) snapshot.
+    pub parent_id: Option<SnapshotId>,
+    pub storage: SnapshotStorage,
+    /// Encrypt the archive while it is exported, with a key derived from
+    /// this secret. Tar formats only, not for incremental snapshots.
+    pub encryption: Option<SnapshotSecret>,
 }
 
 pub struct CreateSnapshotHandler {
     wsl_manager: Arc<dyn WslManagerPort>,
     snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
     audit_logger: Arc<dyn AuditLoggerPort>,
+    transfers: Option<Arc<TransferRegistry>>,
 }
 
 impl CreateSnapshotHandler {
@@ -33,22 +45,228 @@ impl CreateSnapshotHandler {
             wsl_manager,
             snapshot_repo,
             audit_logger,
+            transfers: None,
         }
     }
 
-    /// Check if a file starts with valid tar data by looking for the "ustar" magic
-    /// at byte offset 257 (per POSIX tar format specification).
-    fn validate_tar_magic(path: &std::path::Path) -> bool {
-        use std::io::Read;
-        let Ok(mut f) = std::fs::File::open(path) else {
-            return false;
+    /// Report export progress to `transfers` and make the snapshot
+    /// cancellable through it while it is being created.
+    pub fn with_transfers(mut self, transfers: Arc<TransferRegistry>) -> Self {
+        self.transfers = Some(transfers);
+        self
+    }
+
+    /// Run one `wsl --export`, watched by `transfer` when progress is tracked.
+    async fn export(
+        &self,
+        transfer: Option<&TransferGuard>,
+        distro_name: &DistroName,
+        export_path: &str,
+        format: &ExportFormat,
+        key: Option<&EncryptionKey>,
+        estimate: Option<u64>,
+    ) -> Result<(), DomainError> {
+        if let Some(t) = transfer {
+            t.check()?;
+        }
+        let export =
+            self.wsl_manager
+                .export_distro(distro_name, export_path, format.clone(), key.cloned());
+        match transfer {
+            Some(t) => {
+                t.run(
+                    TransferPhase::Export,
+                    distro_name,
+                    export_path,
+                    estimate,
+                    export,
+                )
+                .await
+            }
+            None => export.await,
+        }
+    }
+
+    /// Mark `snapshot` failed with `error` (`Failed("cancelled")` on cancellation).
+    async fn fail(&self, snapshot: &mut Snapshot, error: &DomainError) -> Result<(), DomainError> {
+        snapshot.status = match error {
+            DomainError::Cancelled => SnapshotStatus::Failed("cancelled".into()),
+            e => SnapshotStatus::Failed(e.to_string()),
         };
-        let mut buf = [0u8; 263];
-        if f.read_exact(&mut buf).is_err() {
+        self.snapshot_repo.save(snapshot).await
+    }
+
+    /// Check if a file holds valid tar data by looking for the "ustar" magic
+    /// at byte offset 257 (per POSIX tar format specification). Encrypted and
+    /// compressed archives are decrypted and decompressed on the fly.
+    fn validate_tar_magic(
+        path: &std::path::Path,
+        format: &ExportFormat,
+        key: Option<&EncryptionKey>,
+    ) -> bool {
+        let Some(key) = key else {
+            return compression::has_tar_header(path, format);
+        };
+        let Ok(file) = std::fs::File::open(path) else {
             return false;
+        };
+        match encryption::reader(Some(key), std::io::BufReader::new(file)) {
+            Ok(reader) => compression::starts_with_tar_header(format, reader),
+            Err(_) => false,
         }
-        // "ustar" magic at offset 257
-        &buf[257..262] == b"ustar"
+    }
+
+    /// Validate `parent_id` as a base for an incremental snapshot of `cmd.distro_name`
+    /// and resolve the local file of every snapshot in its chain (base first).
+    async fn load_parent_chain(
+        &self,
+        cmd: &CreateSnapshotCommand,
+        parent_id: &SnapshotId,
+    ) -> Result<Vec<std::path::PathBuf>, DomainError> {
+        let parent = self.snapshot_repo.get_by_id(parent_id).await?;
+        if parent.distro_name != cmd.distro_name {
+            return Err(DomainError::SnapshotError(format!(
+                "Parent snapshot '{}' belongs to '{}', not '{}'",
+                parent_id, parent.distro_name, cmd.distro_name
+            )));
+        }
+        if !matches!(parent.status, SnapshotStatus::Completed) {
+            return Err(DomainError::SnapshotError(format!(
+                "Parent snapshot '{}' is not completed",
+                parent_id
+            )));
+        }
+        if !matches!(cmd.format, ExportFormat::Tar) || !matches!(parent.format, ExportFormat::Tar) {
+            return Err(DomainError::SnapshotError(
+                "Incremental snapshots require the tar format".into(),
+            ));
+        }
+
+        let chain = incremental::resolve_chain(self.snapshot_repo.as_ref(), &parent).await?;
+        if chain.iter().any(|s| s.storage == SnapshotStorage::Chunked) {
+            return Err(DomainError::SnapshotError(
+                "Chunked snapshots cannot be the base of an incremental snapshot".into(),
+            ));
+        }
+        if chain.iter().any(|s| s.encryption.is_some()) {
+            return Err(DomainError::SnapshotError(
+                "Encrypted snapshots cannot be the base of an incremental snapshot".into(),
+            ));
+        }
+        chain
+            .iter()
+            .map(|s| {
+                existing_local_path(&s.file_path)
+                    .map(std::path::PathBuf::from)
+                    .ok_or_else(|| {
+                        DomainError::SnapshotError(format!(
+                            "Snapshot file of parent chain member '{}' not found: {}",
+                            s.id, s.file_path
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    /// SHA-256 of the finished archive at `file_path`, recorded for later
+    /// integrity checks.
+    async fn archive_checksum(file_path: &str) -> Result<String, DomainError> {
+        let local = existing_local_path(file_path).ok_or_else(|| {
+            DomainError::SnapshotError(format!("Exported archive not found: {}", file_path))
+        })?;
+        let start = std::time::Instant::now();
+        let checksum = tokio::task::spawn_blocking(move || {
+            integrity::sha256_file(std::path::Path::new(&local))
+        })
+        .await
+        .map_err(|e| DomainError::Internal(format!("checksum task failed: {}", e)))??;
+        tracing::info!(
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "archive checksum: sha256={}",
+            checksum
+        );
+        Ok(checksum)
+    }
+
+    /// Move the archive at `file_path` into the chunk store next to it and
+    /// persist the snapshot's chunk manifest. The archive file is removed.
+    async fn store_chunks(&self, snapshot: &Snapshot) -> Result<Vec<SnapshotChunk>, DomainError> {
+        let local = existing_local_path(&snapshot.file_path).ok_or_else(|| {
+            DomainError::SnapshotError(format!(
+                "Exported archive not found: {}",
+                snapshot.file_path
+            ))
+        })?;
+        let source = std::path::PathBuf::from(&local);
+        let store = source
+            .parent()
+            .unwrap_or(std::path::Path::new("."))
+            .join(chunk_store::CHUNK_DIR);
+
+        let start = std::time::Instant::now();
+        let manifest =
+            tokio::task::spawn_blocking(move || chunk_store::store_file(&source, &store))
+                .await
+                .map_err(|e| DomainError::Internal(format!("chunking task failed: {}", e)))??;
+        self.snapshot_repo
+            .save_chunk_manifest(&snapshot.id, &manifest)
+            .await?;
+        std::fs::remove_file(&local)?;
+        tracing::info!(
+            chunks = manifest.len(),
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "archive moved to chunk store: {}",
+            local
+        );
+        Ok(manifest)
+    }
+
+    /// Replace the full export at `export_path` with a delta against `parent_chain`
+    /// written to `file_path`. The full export is always removed. Returns the delta size.
+    async fn write_delta(
+        parent_chain: Vec<std::path::PathBuf>,
+        export_path: &str,
+        file_path: &str,
+    ) -> Result<u64, DomainError> {
+        let local_export = existing_local_path(export_path).ok_or_else(|| {
+            DomainError::SnapshotError(format!("Exported tar not found: {}", export_path))
+        })?;
+        // Write the delta next to the export, using the same path flavour.
+        let local_out = if local_export == export_path {
+            file_path.to_string()
+        } else {
+            windows_to_linux_path(file_path)
+        };
+
+        let (export, out) = (local_export.clone(), local_out.clone());
+        let result = tokio::task::spawn_blocking(move || {
+            incremental::build_delta(
+                &parent_chain,
+                std::path::Path::new(&export),
+                std::path::Path::new(&out),
+            )
+        })
+        .await
+        .map_err(|e| DomainError::Internal(format!("delta task failed: {}", e)))?;
+        let _ = std::fs::remove_file(&local_export);
+
+        let stats = match result {
+            Ok(stats) => stats,
+            Err(e) => {
+                let _ = std::fs::remove_file(&local_out);
+                return Err(e);
+            }
+        };
+        let size = std::fs::metadata(&local_out)?.len();
+        tracing::info!(
+            changed = stats.changed,
+            deleted = stats.deleted,
+            unchanged = stats.unchanged,
+            delta_bytes = size,
+            "incremental delta written: {}",
+            local_out
+        );
+        Ok(size)
     }
 
     #[tracing::instrument(
@@ -61,6 +279,42 @@ impl CreateSnapshotHandler {
     )]
     pub async fn handle(&self, cmd: CreateSnapshotCommand) -> Result<Snapshot, DomainError> {
         let overall_start = std::time::Instant::now();
+
+        if cmd.parent_id.is_some() && cmd.storage == SnapshotStorage::Chunked {
+            return Err(DomainError::SnapshotError(
+                "Incremental snapshots cannot use chunked storage".into(),
+            ));
+        }
+
+        if cmd.encryption.is_some() {
+            if cmd.parent_id.is_some() {
+                return Err(DomainError::SnapshotError(
+                    "Incremental snapshots cannot be encrypted".into(),
+                ));
+            }
+            if !cmd.format.is_tar() {
+                return Err(DomainError::SnapshotError(
+                    "Only tar snapshots can be encrypted".into(),
+                ));
+            }
+        }
+
+        // Resolve the parent chain before touching the distro: an incremental
+        // snapshot is useless if its base cannot be read back.
+        let parent_chain = match &cmd.parent_id {
+            Some(parent_id) => Some(self.load_parent_chain(&cmd, parent_id).await?),
+            None => None,
+        };
+
+        // Derive the key up front: a missing keyfile fails before the export.
+        let (encryption, key) = match &cmd.encryption {
+            Some(secret) => {
+                let (params, key) = encryption::new_encryption_async(secret).await?;
+                (Some(params), Some(key))
+            }
+            None => (None, None),
+        };
+
         let id = SnapshotId::new();
         let file_path = std::path::PathBuf::from(&cmd.output_dir)
             .join(format!(
@@ -71,6 +325,19 @@ impl CreateSnapshotHandler {
             ))
             .to_string_lossy()
             .to_string();
+        // Incremental snapshots export the full tar to a scratch file first,
+        // then keep only the delta under `file_path`.
+        let export_path = if parent_chain.is_some() {
+            std::path::PathBuf::from(&cmd.output_dir)
+                .join(format!("{}-{}.export.tar", cmd.distro_name, id))
+                .to_string_lossy()
+                .to_string()
+        } else {
+            file_path.clone()
+        };
+
+        // Registered before the InProgress row is visible, so it can be cancelled.
+        let transfer = self.transfers.as_ref().map(|t| t.begin(&id)).transpose()?;
 
         tracing::info!(
             "creating snapshot id={} distro={} name={} format={:?} output_dir={} file_path={}",
@@ -87,14 +354,23 @@ impl CreateSnapshotHandler {
             distro_name: cmd.distro_name.clone(),
             name: cmd.name,
             description: cmd.description,
-            snapshot_type: SnapshotType::Full,
+            snapshot_type: if cmd.parent_id.is_some() {
+                SnapshotType::PseudoIncremental
+            } else {
+                SnapshotType::Full
+            },
             format: cmd.format.clone(),
             file_path: file_path.clone(),
             file_size: MemorySize::zero(),
-            parent_id: None,
+            parent_id: cmd.parent_id.clone(),
             created_at: Utc::now(),
             status: SnapshotStatus::InProgress,
             default_user: None,
+            storage: cmd.storage,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption,
         };
 
         // Capture the default user while the distro is still bootable.
@@ -131,6 +407,18 @@ impl CreateSnapshotHandler {
         tracing::info!(snapshot_id = %id, "saving snapshot with status InProgress");
         self.snapshot_repo.save(&snapshot).await?;
 
+        // Progress is reported against the size of the distro's virtual disk.
+        let estimate = match transfer {
+            Some(_) => self
+                .wsl_manager
+                .get_distro(&cmd.distro_name)
+                .await
+                .ok()
+                .and_then(|d| d.vhdx_size)
+                .map(|size| size.bytes()),
+            None => None,
+        };
+
         // Sync filesystem to flush all pending writes to VHDX before shutdown+export
         match self
             .wsl_manager
@@ -165,7 +453,7 @@ impl CreateSnapshotHandler {
 
         tracing::info!(
             distro = %cmd.distro_name,
-            file_path = %file_path,
+            file_path = %export_path,
             format = ?cmd.format,
             "executing wsl --export"
         );
@@ -173,8 +461,14 @@ impl CreateSnapshotHandler {
         // Try export, retry once after another shutdown if HCS times out
         let export_start = std::time::Instant::now();
         let export_result = match self
-            .wsl_manager
-            .export_distro(&cmd.distro_name, &file_path, cmd.format.clone())
+            .export(
+                transfer.as_ref(),
+                &cmd.distro_name,
+                &export_path,
+                &cmd.format,
+                key.as_ref(),
+                estimate,
+            )
             .await
         {
             Err(e) if e.to_string().contains("TIMEOUT") || e.to_string().contains("SHARING") => {
@@ -188,8 +482,14 @@ impl CreateSnapshotHandler {
                 tokio::time::sleep(std::time::Duration::from_secs(5)).await;
                 let retry_start = std::time::Instant::now();
                 let result = self
-                    .wsl_manager
-                    .export_distro(&cmd.distro_name, &file_path, cmd.format)
+                    .export(
+                        transfer.as_ref(),
+                        &cmd.distro_name,
+                        &export_path,
+                        &cmd.format,
+                        key.as_ref(),
+                        estimate,
+                    )
                     .await;
                 match &result {
                     Ok(()) => tracing::info!(
@@ -227,28 +527,29 @@ impl CreateSnapshotHandler {
             let _ = self.wsl_manager.start_distro(&cmd.distro_name).await;
         }
 
+        let mut chunks = Vec::new();
         match export_result {
             Ok(()) => {
                 // Read file size and reject empty exports.
                 // Try the stored path first; fall back to Windows→Linux conversion
                 // (handles C:\... paths when running from WSL).
-                let meta_result = std::fs::metadata(&file_path);
+                let meta_result = std::fs::metadata(&export_path);
                 tracing::info!(
                     "metadata check for '{}': {}",
-                    file_path,
+                    export_path,
                     match &meta_result {
                         Ok(m) => format!("OK, {} bytes", m.len()),
                         Err(e) => format!("FAILED: {}", e),
                     }
                 );
                 let meta_result = meta_result.or_else(|_| {
-                    let linux = windows_to_linux_path(&file_path);
+                    let linux = windows_to_linux_path(&export_path);
                     tracing::info!(
                         "trying linux path fallback: '{}' (same={})",
                         linux,
-                        linux == file_path
+                        linux == export_path
                     );
-                    if linux != file_path {
+                    if linux != export_path {
                         let r = std::fs::metadata(&linux);
                         tracing::info!(
                             "linux path metadata: {}",
@@ -259,7 +560,7 @@ impl CreateSnapshotHandler {
                         );
                         r
                     } else {
-                        std::fs::metadata(&file_path)
+                        std::fs::metadata(&export_path)
                     }
                 });
 
@@ -268,7 +569,7 @@ impl CreateSnapshotHandler {
                     .unwrap_or_else(|e| {
                         tracing::error!(
                             "failed to read export file metadata: path='{}' error={}",
-                            file_path,
+                            export_path,
                             e
                         );
                         MemorySize::zero()
@@ -277,13 +578,13 @@ impl CreateSnapshotHandler {
                 tracing::info!(
                     "export file size: {} bytes (path={})",
                     snapshot.file_size.bytes(),
-                    file_path
+                    export_path
                 );
 
                 if snapshot.file_size.bytes() == 0 {
                     tracing::error!(
                         "export produced an empty file (0 bytes): path='{}'",
-                        file_path
+                        export_path
                     );
                     snapshot.status =
                         SnapshotStatus::Failed("Export produced an empty file".into());
@@ -296,26 +597,36 @@ impl CreateSnapshotHandler {
                 // For tar exports, verify the file starts with valid tar data.
                 // The "ustar" magic string appears at offset 257 in valid tar archives.
                 // This catches cases where wsl --export returned code 0 but wrote
-                // garbage or an incomplete file.
-                if matches!(snapshot.format, ExportFormat::Tar) {
-                    let tar_path = std::path::Path::new(&file_path);
-                    let linux = windows_to_linux_path(&file_path);
-                    let tar_valid_win = Self::validate_tar_magic(tar_path);
-                    let tar_valid_linux = linux != file_path
-                        && Self::validate_tar_magic(std::path::Path::new(&linux));
+                // garbage or an incomplete file. Compressed archives are checked
+                // after decompression, whatever their size on disk.
+                if snapshot.format.is_tar() {
+                    let tar_path = std::path::Path::new(&export_path);
+                    let linux = windows_to_linux_path(&export_path);
+                    let tar_valid_win =
+                        Self::validate_tar_magic(tar_path, &snapshot.format, key.as_ref());
+                    let tar_valid_linux = linux != export_path
+                        && Self::validate_tar_magic(
+                            std::path::Path::new(&linux),
+                            &snapshot.format,
+                            key.as_ref(),
+                        );
                     let tar_valid = tar_valid_win || tar_valid_linux;
                     tracing::info!(
                         "tar magic check: win_path={} linux_path={} valid_win={} valid_linux={} file_size={}",
-                        file_path,
+                        export_path,
                         linux,
                         tar_valid_win,
                         tar_valid_linux,
                         snapshot.file_size.bytes()
                     );
-                    if !tar_valid && snapshot.file_size.bytes() > 262 {
+                    if !tar_valid
+                        && (snapshot.format.is_compressed()
+                            || key.is_some()
+                            || snapshot.file_size.bytes() > 262)
+                    {
                         tracing::warn!(
                             "exported tar file does not contain valid tar magic: path='{}' size={}",
-                            file_path,
+                            export_path,
                             snapshot.file_size.bytes()
                         );
                         snapshot.status =
@@ -328,12 +639,56 @@ impl CreateSnapshotHandler {
                     tracing::info!("tar magic validation passed");
                 }
 
+                if let Some(chain) = parent_chain {
+                    match Self::write_delta(chain, &export_path, &file_path).await {
+                        Ok(size) => snapshot.file_size = MemorySize::from_bytes(size),
+                        Err(e) => {
+                            tracing::error!("incremental delta failed: {}", e);
+                            snapshot.status = SnapshotStatus::Failed(e.to_string());
+                            self.snapshot_repo.save(&snapshot).await?;
+                            return Err(e);
+                        }
+                    }
+                }
+
+                // Last chance to cancel before the archive is hashed and stored.
+                if let Some(Err(e)) = transfer.as_ref().map(TransferGuard::check) {
+                    remove_local_file(&file_path);
+                    self.fail(&mut snapshot, &e).await?;
+                    return Err(e);
+                }
+
+                match Self::archive_checksum(&file_path).await {
+                    Ok(checksum) => snapshot.checksum = Some(checksum),
+                    Err(e) => {
+                        tracing::error!("archive checksum failed: {}", e);
+                        snapshot.status = SnapshotStatus::Failed(e.to_string());
+                        self.snapshot_repo.save(&snapshot).await?;
+                        return Err(e);
+                    }
+                }
+
+                if snapshot.storage == SnapshotStorage::Chunked {
+                    match self.store_chunks(&snapshot).await {
+                        Ok(manifest) => chunks = manifest,
+                        Err(e) => {
+                            tracing::error!("chunk store failed: {}", e);
+                            snapshot.status = SnapshotStatus::Failed(e.to_string());
+                            self.snapshot_repo.save(&snapshot).await?;
+                            return Err(e);
+                        }
+                    }
+                }
+
                 snapshot.status = SnapshotStatus::Completed;
             }
             Err(e) => {
                 tracing::error!("export failed, saving snapshot as Failed: {}", e);
-                snapshot.status = SnapshotStatus::Failed(e.to_string());
-                self.snapshot_repo.save(&snapshot).await?;
+                if matches!(e, DomainError::Cancelled) {
+                    // wsl.exe was killed mid-export; drop what it wrote.
+                    remove_local_file(&export_path);
+                }
+                self.fail(&mut snapshot, &e).await?;
                 return Err(e);
             }
         }
@@ -349,6 +704,9 @@ impl CreateSnapshotHandler {
             return Err(e);
         }
         tracing::info!("snapshot saved to DB successfully");
+        if let Err(e) = sidecar::write(&snapshot, &chunks) {
+            tracing::warn!("failed to write snapshot sidecar: {}", e);
+        }
         if let Err(e) = self
             .audit_logger
             .log("snapshot.create", &id.to_string())
@@ -363,6 +721,13 @@ impl CreateSnapshotHandler {
     }
 }
 
+/// Best-effort removal of a partial file left by a cancelled snapshot.
+fn remove_local_file(path: &str) {
+    if let Some(local) = existing_local_path(path) {
+        let _ = std::fs::remove_file(local);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -378,6 +743,9 @@ mod tests {
             description: Some("test".into()),
             format: ExportFormat::Tar,
             output_dir: "/tmp".into(),
+            parent_id: None,
+            storage: SnapshotStorage::File,
+            encryption: None,
         }
     }
 
@@ -392,7 +760,7 @@ mod tests {
             m.expect_terminate_distro().returning(|_| Ok(()));
             m.expect_shutdown_all().returning(|| Ok(()));
             m.expect_export_distro()
-                .returning(|_, _, _| Err(DomainError::WslCliError("export failed".into())));
+                .returning(|_, _, _, _| Err(DomainError::WslCliError("export failed".into())));
             m.expect_start_distro().returning(|_| Ok(()));
             m
         };
@@ -434,12 +802,12 @@ mod tests {
         wsl_mock.expect_shutdown_all().returning(|| Ok(()));
         wsl_mock
             .expect_export_distro()
-            .withf(|_, path, _| {
+            .withf(|_, path, _, _| {
                 // On Windows, PathBuf::join uses backslash, so accept both separators
                 (path.starts_with("/tmp/Ubuntu-") || path.starts_with("/tmp\\Ubuntu-"))
                     && path.ends_with(".tar")
             })
-            .returning(|_, _, _| Err(DomainError::WslCliError("abort".into())));
+            .returning(|_, _, _, _| Err(DomainError::WslCliError("abort".into())));
         wsl_mock.expect_start_distro().returning(|_| Ok(()));
 
         let mut repo_mock = MockSnapshotRepositoryPort::new();
@@ -467,7 +835,7 @@ mod tests {
         wsl_mock.expect_shutdown_all().returning(|| Ok(()));
         wsl_mock
             .expect_export_distro()
-            .returning(|_, _, _| Err(DomainError::WslCliError("abort".into())));
+            .returning(|_, _, _, _| Err(DomainError::WslCliError("abort".into())));
         wsl_mock.expect_start_distro().returning(|_| Ok(()));
 
         let statuses = Arc::new(std::sync::Mutex::new(Vec::new()));
@@ -478,6 +846,7 @@ mod tests {
                 SnapshotStatus::InProgress => "in_progress",
                 SnapshotStatus::Completed => "completed",
                 SnapshotStatus::Failed(_) => "failed",
+                SnapshotStatus::Corrupted => "corrupted",
             };
             let mut v = sc.lock().unwrap();
             v.push(status.to_string());
@@ -497,4 +866,410 @@ mod tests {
         assert_eq!(statuses[0], "in_progress");
         assert_eq!(statuses[1], "failed");
     }
+
+    fn make_parent(file_path: &str, format: ExportFormat) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("parent-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "base".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format,
+            file_path: file_path.into(),
+            file_size: MemorySize::from_bytes(1024),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn write_tar(path: &std::path::Path, files: &[(&str, &[u8])]) {
+        let mut builder = tar::Builder::new(std::fs::File::create(path).unwrap());
+        for (name, data) in files {
+            let mut header = tar::Header::new_ustar();
+            header.set_mode(0o644);
+            header.set_mtime(1_700_000_000);
+            header.set_size(data.len() as u64);
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_incremental_rejects_parent_from_other_distro() {
+        let mut parent = make_parent("/tmp/parent.tar", ExportFormat::Tar);
+        parent.distro_name = DistroName::new("Debian").unwrap();
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(parent.clone()));
+        repo_mock.expect_save().never();
+
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let mut cmd = make_cmd();
+        cmd.parent_id = Some(SnapshotId::from_string("parent-001".into()));
+        let result = handler.handle(cmd).await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_rejects_vhdx_parent() {
+        let parent = make_parent("/tmp/parent.vhdx", ExportFormat::Vhd);
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(parent.clone()));
+        repo_mock.expect_save().never();
+
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let mut cmd = make_cmd();
+        cmd.parent_id = Some(SnapshotId::from_string("parent-001".into()));
+        let result = handler.handle(cmd).await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_stores_only_delta() {
+        let dir = std::env::temp_dir().join("create_snapshot_incremental");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let parent_tar = dir.join("parent.tar");
+        let big = vec![7u8; 64 * 1024];
+        write_tar(&parent_tar, &[("etc/big", &big), ("etc/hostname", b"old")]);
+        let parent = make_parent(&parent_tar.to_string_lossy(), ExportFormat::Tar);
+
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_get_default_user().returning(|_| Ok(None));
+        wsl_mock
+            .expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl_mock.expect_terminate_distro().returning(|_| Ok(()));
+        wsl_mock.expect_shutdown_all().returning(|| Ok(()));
+        wsl_mock.expect_start_distro().returning(|_| Ok(()));
+        let big_export = big.clone();
+        wsl_mock
+            .expect_export_distro()
+            .withf(|_, path, _, _| path.ends_with(".export.tar"))
+            .returning(move |_, path, _, _| {
+                write_tar(
+                    std::path::Path::new(path),
+                    &[("etc/big", &big_export), ("etc/hostname", b"new")],
+                );
+                Ok(())
+            });
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(parent.clone()));
+        repo_mock.expect_save().returning(|_| Ok(()));
+        let mut audit_mock = MockAuditLoggerPort::new();
+        audit_mock.expect_log().returning(|_, _| Ok(()));
+
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(audit_mock),
+        );
+        let mut cmd = make_cmd();
+        cmd.output_dir = dir.to_string_lossy().to_string();
+        cmd.parent_id = Some(SnapshotId::from_string("parent-001".into()));
+        let snapshot = handler.handle(cmd).await.unwrap();
+
+        assert!(matches!(
+            snapshot.snapshot_type,
+            SnapshotType::PseudoIncremental
+        ));
+        assert_eq!(snapshot.parent_id.as_ref().unwrap().as_str(), "parent-001");
+        assert!(matches!(snapshot.status, SnapshotStatus::Completed));
+        let delta_len = std::fs::metadata(&snapshot.file_path).unwrap().len();
+        assert_eq!(snapshot.file_size.bytes(), delta_len);
+        assert_eq!(
+            snapshot.checksum.as_deref(),
+            Some(
+                integrity::sha256_file(std::path::Path::new(&snapshot.file_path))
+                    .unwrap()
+                    .as_str()
+            ),
+            "checksum must cover the stored delta"
+        );
+        assert!(
+            delta_len < big.len() as u64,
+            "unchanged file must not be stored"
+        );
+        let leftovers: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".export.tar"))
+            .collect();
+        assert!(leftovers.is_empty(), "scratch export must be removed");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_compressed_export_with_plain_tar_is_rejected() {
+        let dir = std::env::temp_dir().join("create_snapshot_compressed_invalid");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_get_default_user().returning(|_| Ok(None));
+        wsl_mock
+            .expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl_mock.expect_terminate_distro().returning(|_| Ok(()));
+        wsl_mock.expect_shutdown_all().returning(|| Ok(()));
+        wsl_mock.expect_start_distro().returning(|_| Ok(()));
+        // An uncompressed tar under a .tar.zst name must not pass validation.
+        wsl_mock
+            .expect_export_distro()
+            .withf(|_, path, format, _| {
+                path.ends_with(".tar.zst") && matches!(format, ExportFormat::TarZst)
+            })
+            .returning(|_, path, _, _| {
+                write_tar(std::path::Path::new(path), &[("etc/hostname", b"x")]);
+                Ok(())
+            });
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let statuses = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = statuses.clone();
+        repo_mock.expect_save().returning(move |s| {
+            seen.lock().unwrap().push(s.status.clone());
+            Ok(())
+        });
+
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let mut cmd = make_cmd();
+        cmd.format = ExportFormat::TarZst;
+        cmd.output_dir = dir.to_string_lossy().to_string();
+        let err = handler.handle(cmd).await.unwrap_err().to_string();
+
+        assert!(err.contains("invalid tar"), "{}", err);
+        assert!(matches!(
+            statuses.lock().unwrap().last(),
+            Some(SnapshotStatus::Failed(_))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_rejects_chunked_storage() {
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock.expect_save().never();
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let mut cmd = make_cmd();
+        cmd.parent_id = Some(SnapshotId::from_string("parent-001".into()));
+        cmd.storage = SnapshotStorage::Chunked;
+        let result = handler.handle(cmd).await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_snapshot_moves_archive_into_chunk_store() {
+        let dir = std::env::temp_dir().join("create_snapshot_chunked");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_get_default_user().returning(|_| Ok(None));
+        wsl_mock
+            .expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl_mock.expect_terminate_distro().returning(|_| Ok(()));
+        wsl_mock.expect_shutdown_all().returning(|| Ok(()));
+        wsl_mock.expect_start_distro().returning(|_| Ok(()));
+        wsl_mock.expect_export_distro().returning(|_, path, _, _| {
+            write_tar(std::path::Path::new(path), &[("etc/hostname", b"box")]);
+            Ok(())
+        });
+
+        let manifest_len = Arc::new(AtomicU32::new(0));
+        let ml = manifest_len.clone();
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock.expect_save().returning(|_| Ok(()));
+        repo_mock
+            .expect_save_chunk_manifest()
+            .times(1)
+            .returning(move |_, chunks| {
+                ml.store(chunks.len() as u32, Ordering::SeqCst);
+                Ok(())
+            });
+        let mut audit_mock = MockAuditLoggerPort::new();
+        audit_mock.expect_log().returning(|_, _| Ok(()));
+
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(audit_mock),
+        );
+        let mut cmd = make_cmd();
+        cmd.output_dir = dir.to_string_lossy().to_string();
+        cmd.storage = SnapshotStorage::Chunked;
+        let snapshot = handler.handle(cmd).await.unwrap();
+
+        assert_eq!(snapshot.storage, SnapshotStorage::Chunked);
+        assert!(snapshot.file_size.bytes() > 0);
+        assert!(!std::path::Path::new(&snapshot.file_path).exists());
+        assert!(
+            snapshot.checksum.is_some(),
+            "checksum taken before chunking"
+        );
+        assert_eq!(manifest_len.load(Ordering::SeqCst), 1);
+        assert!(dir.join(chunk_store::CHUNK_DIR).is_dir());
+        let sidecar = sidecar::read(std::path::Path::new(&sidecar::sidecar_path(
+            &snapshot.file_path,
+        )))
+        .unwrap();
+        assert_eq!(sidecar.snapshot.id, snapshot.id);
+        assert_eq!(sidecar.chunks.len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_snapshot_exports_with_derived_key() {
+        let dir = std::env::temp_dir().join("create_snapshot_encrypted");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_get_default_user().returning(|_| Ok(None));
+        wsl_mock
+            .expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl_mock.expect_terminate_distro().returning(|_| Ok(()));
+        wsl_mock.expect_shutdown_all().returning(|| Ok(()));
+        wsl_mock.expect_start_distro().returning(|_| Ok(()));
+        wsl_mock
+            .expect_export_distro()
+            .times(1)
+            .returning(|_, path, _, key| {
+                let key = key.expect("encrypted exports receive the key");
+                let plain = format!("{}.plain", path);
+                write_tar(std::path::Path::new(&plain), &[("etc/hostname", b"box")]);
+                let mut writer =
+                    encryption::EncryptWriter::new(&key, std::fs::File::create(path).unwrap())
+                        .unwrap();
+                std::io::copy(&mut std::fs::File::open(&plain).unwrap(), &mut writer).unwrap();
+                writer.finish().unwrap();
+                std::fs::remove_file(&plain).unwrap();
+                Ok(())
+            });
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock.expect_save().returning(|_| Ok(()));
+        let mut audit_mock = MockAuditLoggerPort::new();
+        audit_mock.expect_log().returning(|_, _| Ok(()));
+
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(audit_mock),
+        );
+        let secret = SnapshotSecret::Passphrase("hunter2".into());
+        let mut cmd = make_cmd();
+        cmd.output_dir = dir.to_string_lossy().to_string();
+        cmd.encryption = Some(secret.clone());
+        let snapshot = handler.handle(cmd).await.unwrap();
+
+        let params = snapshot.encryption.clone().expect("parameters are stored");
+        assert!(encryption::has_magic(std::path::Path::new(
+            &snapshot.file_path
+        )));
+        assert!(encryption::unlock(&snapshot.id.to_string(), &params, &secret).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_encryption_rejected_for_incremental_and_vhdx() {
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(MockSnapshotRepositoryPort::new()),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let secret = Some(SnapshotSecret::Passphrase("hunter2".into()));
+
+        let mut incremental = make_cmd();
+        incremental.parent_id = Some(SnapshotId::from_string("base".into()));
+        incremental.encryption = secret.clone();
+        let err = handler.handle(incremental).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Incremental snapshots cannot be encrypted")
+        );
+
+        let mut vhdx = make_cmd();
+        vhdx.format = ExportFormat::Vhd;
+        vhdx.encryption = secret;
+        let err = handler.handle(vhdx).await.unwrap_err();
+        assert!(err.to_string().contains("Only tar snapshots"));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_snapshot_is_marked_failed_cancelled() {
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_get_default_user().returning(|_| Ok(None));
+        wsl_mock
+            .expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl_mock
+            .expect_get_distro()
+            .returning(|_| Err(DomainError::DistroNotFound("Ubuntu".into())));
+        wsl_mock.expect_terminate_distro().returning(|_| Ok(()));
+        wsl_mock.expect_shutdown_all().returning(|| Ok(()));
+        wsl_mock.expect_start_distro().returning(|_| Ok(()));
+        wsl_mock.expect_export_distro().never();
+
+        // Cancel as soon as the InProgress row is visible.
+        let transfers = Arc::new(TransferRegistry::default());
+        let t = transfers.clone();
+        let statuses = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let st = statuses.clone();
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock.expect_save().returning(move |s| {
+            if matches!(s.status, SnapshotStatus::InProgress) {
+                assert!(t.cancel(&s.id));
+            }
+            st.lock().unwrap().push(s.status.clone());
+            Ok(())
+        });
+
+        let handler = CreateSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        )
+        .with_transfers(transfers.clone());
+        let result = handler.handle(make_cmd()).await;
+
+        assert!(matches!(result, Err(DomainError::Cancelled)));
+        let statuses = statuses.lock().unwrap();
+        assert!(matches!(
+            statuses.last(),
+            Some(SnapshotStatus::Failed(reason)) if reason == "cancelled"
+        ));
+        // The registration is released once the handler returns.
+        assert!(transfers.begin(&SnapshotId::new()).is_ok());
+    }
 }
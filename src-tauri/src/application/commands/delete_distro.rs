@@ -1,6 +1,10 @@
 use std::sync::Arc;
 
 use crate::application::path_utils::windows_to_linux_path;
+use crate::application::services::safety_snapshot::{self, SafetySnapshotGuard};
+use crate::application::services::{chunk_store, sidecar};
+use crate::domain::entities::safety_snapshot::DestructiveOperation;
+use crate::domain::entities::snapshot::SnapshotStorage;
 use crate::domain::errors::DomainError;
 use crate::domain::ports::alerting::AlertingPort;
 use crate::domain::ports::audit_logger::AuditLoggerPort;
@@ -22,6 +26,7 @@ pub struct DeleteDistroHandler {
     alerting: Arc<dyn AlertingPort>,
     port_rules_repo: Arc<dyn PortForwardRulesRepository>,
     audit_logger: Arc<dyn AuditLoggerPort>,
+    safety: Option<Arc<SafetySnapshotGuard>>,
 }
 
 impl DeleteDistroHandler {
@@ -40,9 +45,17 @@ impl DeleteDistroHandler {
             alerting,
             port_rules_repo,
             audit_logger,
+            safety: None,
         }
     }
 
+    /// Take a safety snapshot of the distro before deleting it when its
+    /// policy asks for one.
+    pub fn with_safety(mut self, safety: Arc<SafetySnapshotGuard>) -> Self {
+        self.safety = Some(safety);
+        self
+    }
+
     #[tracing::instrument(
         skip(self, cmd),
         fields(distro = %cmd.distro_name, delete_snapshots = cmd.delete_snapshots)
@@ -51,6 +64,17 @@ impl DeleteDistroHandler {
         // 1. Verify the distro exists
         self.wsl_manager.get_distro(&cmd.distro_name).await?;
 
+        // Safety snapshot (pinned, so step 6 keeps it) before anything is
+        // touched; if it fails the distro is left alone.
+        let safety = match &self.safety {
+            Some(guard) => {
+                guard
+                    .protect(&cmd.distro_name, DestructiveOperation::DeleteDistro)
+                    .await?
+            }
+            None => None,
+        };
+
         // 2. Terminate the distro if running
         let _ = self.wsl_manager.terminate_distro(&cmd.distro_name).await;
         let _ = self.wsl_manager.shutdown_all().await;
@@ -94,7 +118,7 @@ impl DeleteDistroHandler {
             }
         }
 
-        // 6. Delete snapshots if requested
+        // 6. Delete snapshots if requested (pinned ones are kept)
         if cmd.delete_snapshots {
             match self.snapshot_repo.delete_by_distro(&cmd.distro_name).await {
                 Ok(snapshots) => {
@@ -104,7 +128,14 @@ impl DeleteDistroHandler {
                         let linux_path = windows_to_linux_path(file_path);
                         let _ = std::fs::remove_file(file_path)
                             .or_else(|_| std::fs::remove_file(&linux_path));
+                        sidecar::remove(file_path);
                     }
+                    let chunked: Vec<String> = snapshots
+                        .iter()
+                        .filter(|s| s.storage == SnapshotStorage::Chunked)
+                        .map(|s| s.file_path.clone())
+                        .collect();
+                    chunk_store::release_stores(self.snapshot_repo.as_ref(), &chunked).await;
                 }
                 Err(e) => tracing::warn!(error = %e, "failed to delete snapshots"),
             }
@@ -130,14 +161,17 @@ impl DeleteDistroHandler {
             .log_with_details(
                 "distro.delete",
                 cmd.distro_name.as_str(),
-                &format!(
-                    "Deleted distro '{}' (snapshots: {})",
-                    cmd.distro_name,
-                    if cmd.delete_snapshots {
-                        "deleted"
-                    } else {
-                        "kept"
-                    }
+                &safety_snapshot::with_safety_note(
+                    format!(
+                        "Deleted distro '{}' (snapshots: {})",
+                        cmd.distro_name,
+                        if cmd.delete_snapshots {
+                            "deleted"
+                        } else {
+                            "kept"
+                        }
+                    ),
+                    safety.as_ref(),
                 ),
             )
             .await?;
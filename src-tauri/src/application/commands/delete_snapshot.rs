@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use crate::application::path_utils::windows_to_linux_path;
+use crate::application::services::{chunk_store, sidecar};
+use crate::domain::entities::snapshot::SnapshotStorage;
 use crate::domain::errors::DomainError;
 use crate::domain::ports::audit_logger::AuditLoggerPort;
 use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
@@ -28,6 +30,28 @@ impl DeleteSnapshotHandler {
 
     pub async fn handle(&self, cmd: DeleteSnapshotCommand) -> Result<(), DomainError> {
         let snapshot = self.snapshot_repo.get_by_id(&cmd.snapshot_id).await?;
+        if snapshot.pinned {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot '{}' is pinned; unpin it before deleting",
+                cmd.snapshot_id
+            )));
+        }
+
+        // Incremental snapshots are rebuilt from their parent chain, so a parent
+        // cannot go while any child still depends on it.
+        let children = self
+            .snapshot_repo
+            .list_by_distro(&snapshot.distro_name)
+            .await?
+            .into_iter()
+            .filter(|s| s.parent_id.as_ref() == Some(&cmd.snapshot_id))
+            .count();
+        if children > 0 {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot '{}' is the parent of {} incremental snapshot(s); delete those first",
+                cmd.snapshot_id, children
+            )));
+        }
 
         // Delete the snapshot file from disk.
         // Try the stored path first; fall back to Windows→Linux conversion
@@ -46,6 +70,14 @@ impl DeleteSnapshotHandler {
         }
 
         self.snapshot_repo.delete(&cmd.snapshot_id).await?;
+        sidecar::remove(&snapshot.file_path);
+        if snapshot.storage == SnapshotStorage::Chunked {
+            chunk_store::release_stores(
+                self.snapshot_repo.as_ref(),
+                std::slice::from_ref(&snapshot.file_path),
+            )
+            .await;
+        }
         self.audit_logger
             .log("snapshot.delete", &cmd.snapshot_id.to_string())
             .await?;
@@ -57,7 +89,9 @@ impl DeleteSnapshotHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::snapshot::{ExportFormat, Snapshot, SnapshotStatus, SnapshotType};
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
     use crate::domain::ports::audit_logger::MockAuditLoggerPort;
     use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
     use crate::domain::value_objects::{DistroName, MemorySize};
@@ -77,6 +111,11 @@ mod tests {
             created_at: Utc::now(),
             status: SnapshotStatus::Completed,
             default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
         }
     }
 
@@ -108,6 +147,7 @@ mod tests {
         repo_mock
             .expect_get_by_id()
             .returning(move |_| Ok(snap.clone()));
+        repo_mock.expect_list_by_distro().returning(|_| Ok(vec![]));
         repo_mock.expect_delete().returning(|_| Ok(()));
 
         let mut audit_mock = MockAuditLoggerPort::new();
@@ -123,4 +163,50 @@ mod tests {
                 .is_ok()
         );
     }
+
+    #[tokio::test]
+    async fn test_delete_refuses_pinned_snapshot() {
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let mut snap = make_snapshot("/nonexistent/path/file.tar");
+        snap.pinned = true;
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+        repo_mock.expect_delete().never();
+
+        let handler =
+            DeleteSnapshotHandler::new(Arc::new(repo_mock), Arc::new(MockAuditLoggerPort::new()));
+        let result = handler
+            .handle(DeleteSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_refuses_parent_of_incremental() {
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let snap = make_snapshot("/nonexistent/path/file.tar");
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+        repo_mock.expect_list_by_distro().returning(|_| {
+            let mut child = make_snapshot("/nonexistent/path/child.tar");
+            child.id = SnapshotId::from_string("snap-002".into());
+            child.snapshot_type = SnapshotType::PseudoIncremental;
+            child.parent_id = Some(SnapshotId::from_string("snap-001".into()));
+            Ok(vec![child])
+        });
+        repo_mock.expect_delete().never();
+
+        let handler =
+            DeleteSnapshotHandler::new(Arc::new(repo_mock), Arc::new(MockAuditLoggerPort::new()));
+        let result = handler
+            .handle(DeleteSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
 }
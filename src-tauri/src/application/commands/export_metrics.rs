@@ -0,0 +1,390 @@
+use std::future::Future;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::application::services::metrics_export::{
+    DistroMetricsPoint, ExportRow, ExportWriter, MetricsExportFormat,
+};
+use crate::domain::entities::metrics_settings::MetricsTier;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::metrics_repository::{MetricsRepositoryPort, RawMetricsRow};
+use crate::domain::value_objects::DistroName;
+
+/// Write the metrics history of some distros over a time range to a file.
+pub struct ExportMetricsCommand {
+    pub distros: Vec<DistroName>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Rollup tier to read, `None` for the raw samples.
+    pub tier: Option<MetricsTier>,
+    pub format: MetricsExportFormat,
+    pub path: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct MetricsExportReport {
+    pub rows: u64,
+    pub bytes: u64,
+}
+
+/// Time range read per query: about 1800 raw samples at the default
+/// collection interval, or a few hundred to 1440 buckets of a tier.
+fn batch_span(tier: Option<MetricsTier>) -> Duration {
+    match tier {
+        None => Duration::hours(1),
+        Some(MetricsTier::Minute) => Duration::days(1),
+        Some(MetricsTier::Hour) => Duration::days(30),
+        Some(MetricsTier::Day) => Duration::days(365),
+    }
+}
+
+/// Partial export deleted when dropped, so a failed export leaves no file
+/// behind and never truncates an existing one.
+struct PartialFile(PathBuf);
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+pub struct ExportMetricsHandler {
+    metrics_repo: Arc<dyn MetricsRepositoryPort>,
+}
+
+impl ExportMetricsHandler {
+    pub fn new(metrics_repo: Arc<dyn MetricsRepositoryPort>) -> Self {
+        Self { metrics_repo }
+    }
+
+    /// Stream the series into the file one batch at a time, distro after
+    /// distro. The file only appears at `path` once it is complete.
+    #[tracing::instrument(skip(self, cmd), fields(format = cmd.format.label()))]
+    pub async fn handle(
+        &self,
+        cmd: ExportMetricsCommand,
+    ) -> Result<MetricsExportReport, DomainError> {
+        if cmd.distros.is_empty() {
+            return Err(DomainError::ConfigError(
+                "Select at least one distro to export".into(),
+            ));
+        }
+        if cmd.from >= cmd.to {
+            return Err(DomainError::ConfigError(
+                "The export range must end after it starts".into(),
+            ));
+        }
+
+        match cmd.tier {
+            None => {
+                let repo = Arc::clone(&self.metrics_repo);
+                write_series(
+                    &cmd,
+                    |row: &RawMetricsRow| row.timestamp,
+                    move |distro, from, to| {
+                        let repo = Arc::clone(&repo);
+                        async move { repo.query_raw(&distro, from, to).await }
+                    },
+                )
+                .await
+            }
+            Some(tier) => {
+                let repo = Arc::clone(&self.metrics_repo);
+                write_series(
+                    &cmd,
+                    |row: &DistroMetricsPoint| row.point.period_start,
+                    move |distro, from, to| {
+                        let repo = Arc::clone(&repo);
+                        async move {
+                            let points = repo.query_aggregated(&distro, tier, from, to).await?;
+                            Ok(points
+                                .into_iter()
+                                .map(|point| DistroMetricsPoint {
+                                    distro_name: distro.to_string(),
+                                    point,
+                                })
+                                .collect())
+                        }
+                    },
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// Read each distro's series in `batch_span` slices and hand them to a
+/// blocking thread that writes the file, one batch ahead of it.
+async fn write_series<R, F, Fut>(
+    cmd: &ExportMetricsCommand,
+    time_of: fn(&R) -> DateTime<Utc>,
+    fetch: F,
+) -> Result<MetricsExportReport, DomainError>
+where
+    R: ExportRow + Send + 'static,
+    F: Fn(DistroName, DateTime<Utc>, DateTime<Utc>) -> Fut,
+    Fut: Future<Output = Result<Vec<R>, DomainError>>,
+{
+    // `None` completes the export; closing the channel without it abandons it
+    let (tx, rx) = tokio::sync::mpsc::channel::<Option<Vec<R>>>(1);
+    let (format, path) = (cmd.format, cmd.path.clone());
+    let writer = tokio::task::spawn_blocking(move || write_file(format, &path, rx));
+
+    let fetched: Result<(), DomainError> = async {
+        let span = batch_span(cmd.tier);
+        for distro in &cmd.distros {
+            let mut start = cmd.from;
+            while start < cmd.to {
+                let end = (start + span).min(cmd.to);
+                let mut rows = fetch(distro.clone(), start, end).await?;
+                // Queries include both bounds: a row at `end` opens the next batch
+                if end < cmd.to {
+                    rows.retain(|row| time_of(row) < end);
+                }
+                if tx.send(Some(rows)).await.is_err() {
+                    // The writer failed; its error is the one reported
+                    return Ok(());
+                }
+                start = end;
+            }
+        }
+        let _ = tx.send(None).await;
+        Ok(())
+    }
+    .await;
+    drop(tx);
+
+    let written = writer
+        .await
+        .map_err(|e| DomainError::Internal(format!("metrics export writer failed: {e}")))?;
+    fetched?;
+    Ok(written?)
+}
+
+/// Write the batches received to `<path>.partial` and move it to `path` once
+/// the export completes. Blocking.
+fn write_file<R: ExportRow>(
+    format: MetricsExportFormat,
+    path: &Path,
+    mut batches: tokio::sync::mpsc::Receiver<Option<Vec<R>>>,
+) -> io::Result<MetricsExportReport> {
+    let mut partial_name = path.as_os_str().to_owned();
+    partial_name.push(".partial");
+    let partial = PartialFile(PathBuf::from(partial_name));
+    let file = BufWriter::new(std::fs::File::create(&partial.0)?);
+
+    let mut writer = ExportWriter::<_, R>::new(format, file)?;
+    loop {
+        match batches.blocking_recv() {
+            Some(Some(rows)) => writer.write_batch(&rows)?,
+            Some(None) => break,
+            None => return Err(io::Error::other("metrics export cancelled")),
+        }
+    }
+    let rows = writer.rows();
+    writer
+        .finish()?
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()?;
+
+    let bytes = std::fs::metadata(&partial.0)?.len();
+    std::fs::rename(&partial.0, path)?;
+    Ok(MetricsExportReport { rows, bytes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::metrics_repository::{
+        AggregatedMetricsPoint, MockMetricsRepositoryPort,
+    };
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_792_324_800 + minutes * 60, 0).unwrap()
+    }
+
+    fn raw_row(distro: &str, timestamp: DateTime<Utc>) -> RawMetricsRow {
+        RawMetricsRow {
+            distro_name: distro.to_string(),
+            timestamp,
+            cpu_usage_percent: 5.0,
+            load_avg_1: 0.0,
+            load_avg_5: 0.0,
+            load_avg_15: 0.0,
+            mem_total_bytes: 0,
+            mem_used_bytes: 0,
+            mem_available_bytes: 0,
+            mem_cached_bytes: 0,
+            swap_total_bytes: 0,
+            swap_used_bytes: 0,
+            disk_total_bytes: 0,
+            disk_used_bytes: 0,
+            disk_available_bytes: 0,
+            disk_usage_percent: 0.0,
+            net_rx_bytes: 0,
+            net_tx_bytes: 0,
+            context_switches: None,
+            disk_io_read_bytes: None,
+            disk_io_write_bytes: None,
+            tcp_established: None,
+            tcp_time_wait: None,
+            tcp_listen: None,
+            gpu_utilization: None,
+            gpu_vram_used: None,
+            gpu_vram_total: None,
+        }
+    }
+
+    fn export_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nexus_export_metrics_{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("metrics.out")
+    }
+
+    fn command(
+        distros: &[&str],
+        tier: Option<MetricsTier>,
+        path: &std::path::Path,
+    ) -> ExportMetricsCommand {
+        ExportMetricsCommand {
+            distros: distros
+                .iter()
+                .map(|d| DistroName::new(d).unwrap())
+                .collect(),
+            from: at(0),
+            to: at(150),
+            tier,
+            format: MetricsExportFormat::Csv,
+            path: path.to_path_buf(),
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_export_is_read_an_hour_at_a_time() {
+        let mut repo = MockMetricsRepositoryPort::new();
+        // Every raw row of the range, as stored: one per 30 minutes
+        repo.expect_query_raw()
+            .times(6)
+            .returning(|distro, from, to| {
+                Ok((0..=150)
+                    .step_by(30)
+                    .map(at)
+                    .filter(|t| *t >= from && *t <= to)
+                    .map(|t| raw_row(distro.as_str(), t))
+                    .collect())
+            });
+        let path = export_path("raw");
+        let handler = ExportMetricsHandler::new(Arc::new(repo));
+
+        let report = handler
+            .handle(command(&["Ubuntu", "Debian"], None, &path))
+            .await
+            .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        // Header, then 6 rows per distro with no duplicate at the batch edges
+        assert_eq!(report.rows, 12);
+        assert_eq!(lines.len(), 13);
+        assert_eq!(report.bytes, text.len() as u64);
+        assert!(lines[1].starts_with("Ubuntu,2026-10-18T12:00:00.000Z,"));
+        assert!(lines[6].starts_with("Ubuntu,2026-10-18T14:30:00.000Z,"));
+        assert!(lines[7].starts_with("Debian,"));
+        let partial = path.with_file_name("metrics.out.partial");
+        assert!(!partial.exists());
+    }
+
+    #[tokio::test]
+    async fn tier_export_labels_each_bucket_with_its_distro() {
+        let mut repo = MockMetricsRepositoryPort::new();
+        repo.expect_query_aggregated()
+            .withf(|_, tier, from, to| {
+                *tier == MetricsTier::Minute && *from == at(0) && *to == at(150)
+            })
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(vec![AggregatedMetricsPoint {
+                    period_start: at(0),
+                    period_end: at(1),
+                    sample_count: 30,
+                    cpu_min: 1.0,
+                    cpu_avg: 2.0,
+                    cpu_max: 3.0,
+                    mem_used_min: 0,
+                    mem_used_avg: 0,
+                    mem_used_max: 0,
+                    mem_total: 0,
+                    disk_min: 0.0,
+                    disk_avg: 0.0,
+                    disk_max: 0.0,
+                    net_rx_total: 0,
+                    net_tx_total: 0,
+                    net_rx_max_rate: 0,
+                    net_tx_max_rate: 0,
+                }])
+            });
+        let path = export_path("tier");
+        let mut cmd = command(&["Ubuntu"], Some(MetricsTier::Minute), &path);
+        cmd.format = MetricsExportFormat::JsonLines;
+
+        let report = ExportMetricsHandler::new(Arc::new(repo))
+            .handle(cmd)
+            .await
+            .unwrap();
+
+        assert_eq!(report.rows, 1);
+        let line: serde_json::Value =
+            serde_json::from_str(std::fs::read_to_string(&path).unwrap().trim()).unwrap();
+        assert_eq!(line["distro_name"], "Ubuntu");
+        assert_eq!(line["cpu_avg"], 2.0);
+    }
+
+    #[tokio::test]
+    async fn failed_export_leaves_no_file() {
+        let mut repo = MockMetricsRepositoryPort::new();
+        repo.expect_query_raw()
+            .returning(|_, _, _| Err(DomainError::DatabaseError("locked".into())));
+        let path = export_path("failed");
+        std::fs::write(&path, b"previous export").unwrap();
+
+        let result = ExportMetricsHandler::new(Arc::new(repo))
+            .handle(command(&["Ubuntu"], None, &path))
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"previous export");
+        assert!(!path.with_file_name("metrics.out.partial").exists());
+    }
+
+    #[tokio::test]
+    async fn unwritable_path_fails_the_export() {
+        let mut repo = MockMetricsRepositoryPort::new();
+        repo.expect_query_raw().returning(|_, _, _| Ok(vec![]));
+        let path = export_path("unwritable")
+            .join("missing")
+            .join("metrics.out");
+
+        let result = ExportMetricsHandler::new(Arc::new(repo))
+            .handle(command(&["Ubuntu"], None, &path))
+            .await;
+
+        assert!(matches!(result, Err(DomainError::IoError(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_an_empty_selection_or_range() {
+        let handler = ExportMetricsHandler::new(Arc::new(MockMetricsRepositoryPort::new()));
+        let path = export_path("invalid");
+
+        assert!(handler.handle(command(&[], None, &path)).await.is_err());
+        let mut backwards = command(&["Ubuntu"], None, &path);
+        backwards.to = backwards.from;
+        assert!(handler.handle(backwards).await.is_err());
+        assert!(!path.exists());
+    }
+}
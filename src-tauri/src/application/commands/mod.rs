@@ -1,4 +1,11 @@
+pub mod cancel_snapshot;
 pub mod create_snapshot;
 pub mod delete_distro;
 pub mod delete_snapshot;
+pub mod export_metrics;
+pub mod provision_from_template;
+pub mod rekey_snapshot;
+pub mod rescan_snapshots;
 pub mod restore_snapshot;
+pub mod update_snapshot_metadata;
+pub mod verify_snapshot;
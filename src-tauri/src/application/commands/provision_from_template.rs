@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::application::commands::restore_snapshot::{
+    RestoreSnapshotCommand, RestoreSnapshotHandler,
+};
+use crate::application::services::encryption::SnapshotSecret;
+use crate::application::services::replication::SnapshotReplicator;
+use crate::application::services::transfer::TransferRegistry;
+use crate::domain::entities::distro::Distro;
+use crate::domain::entities::snapshot::RestoreMode;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::ports::template_repository::TemplateRepositoryPort;
+use crate::domain::ports::wsl_manager::WslManagerPort;
+use crate::domain::value_objects::DistroName;
+
+pub struct ProvisionFromTemplateCommand {
+    pub template_id: String,
+    /// Name of the new distro.
+    pub distro_name: String,
+    pub install_location: String,
+    /// Values of the template's parameters; missing ones take their default.
+    pub parameters: HashMap<String, String>,
+    /// Passphrase or keyfile of an encrypted template snapshot.
+    pub secret: Option<SnapshotSecret>,
+}
+
+/// Step a provisioning run is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisionPhase {
+    Clone,
+    Configure,
+    Script,
+    Done,
+}
+
+/// Emitted when a phase starts (`line` is `None`) and for every line the
+/// bootstrap script prints.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProvisionOutput {
+    pub template_id: String,
+    pub distro_name: String,
+    pub phase: ProvisionPhase,
+    pub line: Option<String>,
+}
+
+pub type ProvisionSink = Arc<dyn Fn(ProvisionOutput) + Send + Sync>;
+
+pub struct ProvisionFromTemplateHandler {
+    wsl_manager: Arc<dyn WslManagerPort>,
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    template_repo: Arc<dyn TemplateRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+    transfers: Option<Arc<TransferRegistry>>,
+    replicator: Option<Arc<SnapshotReplicator>>,
+    output: Option<ProvisionSink>,
+}
+
+impl ProvisionFromTemplateHandler {
+    pub fn new(
+        wsl_manager: Arc<dyn WslManagerPort>,
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        template_repo: Arc<dyn TemplateRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            wsl_manager,
+            snapshot_repo,
+            template_repo,
+            audit_logger,
+            transfers: None,
+            replicator: None,
+            output: None,
+        }
+    }
+
+    /// Report progress of the clone's import and make it cancellable.
+    pub fn with_transfers(mut self, transfers: Arc<TransferRegistry>) -> Self {
+        self.transfers = Some(transfers);
+        self
+    }
+
+    /// Fetch the template snapshot from its replica when it is missing locally.
+    pub fn with_replicator(mut self, replicator: Arc<SnapshotReplicator>) -> Self {
+        self.replicator = Some(replicator);
+        self
+    }
+
+    /// Receive phase changes and the script output as it is printed.
+    pub fn with_output(mut self, output: ProvisionSink) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Clone the template snapshot as `distro_name`, write its wsl.conf
+    /// overlay, then run its bootstrap script. A distro whose script fails
+    /// is kept so it can be inspected.
+    #[tracing::instrument(skip(self, cmd), fields(template = %cmd.template_id, distro = %cmd.distro_name))]
+    pub async fn handle(&self, cmd: ProvisionFromTemplateCommand) -> Result<Distro, DomainError> {
+        let template = self
+            .template_repo
+            .get(&cmd.template_id)
+            .await?
+            .ok_or_else(|| {
+                DomainError::ConfigError(format!("Template '{}' not found", cmd.template_id))
+            })?;
+        let distro_name = DistroName::new(&cmd.distro_name)?;
+
+        // Parameters are checked before anything is created.
+        let values = template.resolve_values(distro_name.as_str(), &cmd.parameters)?;
+        let script = template.render_script(&values);
+        let config = template.render_config(&values);
+        if self.wsl_manager.get_distro(&distro_name).await.is_ok() {
+            return Err(DomainError::ConfigError(format!(
+                "A distro named '{}' already exists",
+                distro_name
+            )));
+        }
+
+        let emit = |phase: ProvisionPhase, line: Option<String>| {
+            if let Some(output) = &self.output {
+                output(ProvisionOutput {
+                    template_id: template.id.clone(),
+                    distro_name: distro_name.to_string(),
+                    phase,
+                    line,
+                });
+            }
+        };
+
+        emit(ProvisionPhase::Clone, None);
+        let mut restore = RestoreSnapshotHandler::new(
+            self.wsl_manager.clone(),
+            self.snapshot_repo.clone(),
+            self.audit_logger.clone(),
+        );
+        if let Some(transfers) = &self.transfers {
+            restore = restore.with_transfers(transfers.clone());
+        }
+        if let Some(replicator) = &self.replicator {
+            restore = restore.with_replicator(replicator.clone());
+        }
+        restore
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: template.snapshot_id.clone(),
+                mode: RestoreMode::Clone {
+                    new_name: distro_name.to_string(),
+                },
+                install_location: cmd.install_location,
+                force: false,
+                secret: cmd.secret,
+            })
+            .await?;
+
+        // wsl.conf is read at boot: stop the clone so the script already runs
+        // with the new hostname, systemd and default user.
+        emit(ProvisionPhase::Configure, None);
+        self.wsl_manager
+            .update_distro_config(&distro_name, config)
+            .await?;
+        let _ = self.wsl_manager.terminate_distro(&distro_name).await;
+
+        if !script.trim().is_empty() {
+            emit(ProvisionPhase::Script, None);
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let run = self.wsl_manager.run_script(&distro_name, &script, tx);
+            let forward = async {
+                while let Some(line) = rx.recv().await {
+                    emit(ProvisionPhase::Script, Some(line));
+                }
+            };
+            let (result, ()) = tokio::join!(run, forward);
+            if let Err(e) = result {
+                self.audit_logger
+                    .log_with_details(
+                        "template.provision.failed",
+                        distro_name.as_str(),
+                        &format!("Template '{}' ({}): {}", template.name, template.id, e),
+                    )
+                    .await?;
+                return Err(DomainError::WslCliError(format!(
+                    "Bootstrap script of template '{}' failed; '{}' was kept for inspection: {}",
+                    template.name, distro_name, e
+                )));
+            }
+        }
+
+        emit(ProvisionPhase::Done, None);
+        self.audit_logger
+            .log_with_details(
+                "template.provision",
+                distro_name.as_str(),
+                &format!(
+                    "Template '{}' ({}), snapshot {}",
+                    template.name, template.id, template.snapshot_id
+                ),
+            )
+            .await?;
+        self.wsl_manager.get_distro(&distro_name).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::distro_template::{DistroTemplate, TemplateParameter};
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::entities::wsl_config::WslDistroConfig;
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::value_objects::{DistroState, MemorySize, SnapshotId, WslVersion};
+    use chrono::Utc;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn make_template() -> DistroTemplate {
+        DistroTemplate {
+            id: "tpl-1".into(),
+            name: "node-dev".into(),
+            description: None,
+            snapshot_id: SnapshotId::from_string("snap-001".into()),
+            script: "useradd -m {{user}}".into(),
+            parameters: vec![TemplateParameter {
+                name: "user".into(),
+                description: None,
+                default: None,
+                required: true,
+            }],
+            wsl_config: WslDistroConfig {
+                network_hostname: Some("{{distro_name}}".into()),
+                user_default: Some("{{user}}".into()),
+                ..Default::default()
+            },
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn make_snapshot(file_path: &str) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "golden".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: file_path.to_string(),
+            file_size: MemorySize::from_bytes(4),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn make_handler(
+        wsl: MockWslManagerPort,
+        snapshots: MockSnapshotRepositoryPort,
+        audit: MockAuditLoggerPort,
+    ) -> ProvisionFromTemplateHandler {
+        let mut templates = MockTemplateRepositoryPort::new();
+        templates
+            .expect_get()
+            .returning(|id| Ok((id == "tpl-1").then(make_template)));
+        ProvisionFromTemplateHandler::new(
+            Arc::new(wsl),
+            Arc::new(snapshots),
+            Arc::new(templates),
+            Arc::new(audit),
+        )
+    }
+
+    fn make_command(parameters: &[(&str, &str)]) -> ProvisionFromTemplateCommand {
+        ProvisionFromTemplateCommand {
+            template_id: "tpl-1".into(),
+            distro_name: "web-1".into(),
+            install_location: "/tmp".into(),
+            parameters: parameters
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            secret: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bad_parameters_are_refused_before_anything_is_created() {
+        // No WSL expectations: any call would panic.
+        let handler = make_handler(
+            MockWslManagerPort::new(),
+            MockSnapshotRepositoryPort::new(),
+            MockAuditLoggerPort::new(),
+        );
+        let err = handler.handle(make_command(&[])).await.unwrap_err();
+        assert!(err.to_string().contains("'user' is required"), "{err}");
+
+        let mut cmd = make_command(&[("user", "dev")]);
+        cmd.template_id = "missing".into();
+        assert!(handler.handle(cmd).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_provision_clones_configures_and_streams_script_output() {
+        let tmp = std::env::temp_dir().join("test_provision_from_template.tar");
+        std::fs::write(&tmp, b"test").unwrap();
+        let mut snapshots = MockSnapshotRepositoryPort::new();
+        let snap = make_snapshot(tmp.to_str().unwrap());
+        snapshots
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+
+        let imported = Arc::new(AtomicBool::new(false));
+        let mut wsl = MockWslManagerPort::new();
+        let seen = imported.clone();
+        wsl.expect_get_distro().returning(move |name| {
+            if seen.load(Ordering::SeqCst) {
+                Ok(Distro::new(
+                    name.clone(),
+                    DistroState::Stopped,
+                    WslVersion::V2,
+                    false,
+                ))
+            } else {
+                Err(DomainError::DistroNotFound(name.to_string()))
+            }
+        });
+        let flag = imported.clone();
+        wsl.expect_import_distro()
+            .withf(|name, _, _, _, _| name.as_str() == "web-1")
+            .times(1)
+            .returning(move |_, _, _, _, _| {
+                flag.store(true, Ordering::SeqCst);
+                Ok(())
+            });
+        wsl.expect_shutdown_all().returning(|| Ok(()));
+        wsl.expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl.expect_get_default_user().returning(|_| Ok(None));
+        wsl.expect_update_distro_config()
+            .withf(|name, config| {
+                name.as_str() == "web-1"
+                    && config.network_hostname.as_deref() == Some("web-1")
+                    && config.user_default.as_deref() == Some("dev")
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        wsl.expect_terminate_distro().returning(|_| Ok(()));
+        wsl.expect_run_script()
+            .withf(|_, script, _| script == "useradd -m 'dev'")
+            .times(1)
+            .returning(|_, _, output| {
+                let _ = output.send("added user".into());
+                Ok(())
+            });
+
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log_with_details()
+            .withf(|action, _, _| action == "snapshot.restore")
+            .returning(|_, _, _| Ok(()));
+        audit
+            .expect_log_with_details()
+            .withf(|action, target, details| {
+                action == "template.provision" && target == "web-1" && details.contains("snap-001")
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let handler = make_handler(wsl, snapshots, audit).with_output(Arc::new(move |out| {
+            sink.lock().unwrap().push((out.phase, out.line));
+        }));
+        let distro = handler
+            .handle(make_command(&[("user", "dev")]))
+            .await
+            .unwrap();
+        let _ = std::fs::remove_file(&tmp);
+
+        assert_eq!(distro.name.as_str(), "web-1");
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (ProvisionPhase::Clone, None),
+                (ProvisionPhase::Configure, None),
+                (ProvisionPhase::Script, None),
+                (ProvisionPhase::Script, Some("added user".to_string())),
+                (ProvisionPhase::Done, None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+use crate::application::path_utils::existing_local_path;
+use crate::application::services::encryption::{
+    self, DecryptReader, EncryptWriter, SnapshotSecret,
+};
+use crate::application::services::replication::SnapshotReplicator;
+use crate::application::services::{chunk_store, integrity, sidecar};
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStatus, SnapshotStorage};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::{EncryptionKey, MemorySize, SnapshotId};
+
+/// Change the key a snapshot archive is encrypted with. `current: None`
+/// encrypts a plain archive, `new: None` decrypts an encrypted one.
+pub struct RekeySnapshotCommand {
+    pub snapshot_id: SnapshotId,
+    pub current: Option<SnapshotSecret>,
+    pub new: Option<SnapshotSecret>,
+}
+
+/// Reader hashing the bytes read through it.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Stream `source` through decryption with `old` and encryption with `new`
+/// into `out`. Returns the checksum of the bytes read from `source`. Blocking.
+fn rewrite_archive(
+    source: impl Read,
+    old: Option<&EncryptionKey>,
+    new: Option<&EncryptionKey>,
+    out: &Path,
+) -> io::Result<String> {
+    let mut hashing = HashingReader {
+        inner: BufReader::new(source),
+        hasher: Sha256::new(),
+    };
+    let mut plain: Box<dyn Read + '_> = match old {
+        Some(key) => Box::new(DecryptReader::new(key, &mut hashing)?),
+        None => Box::new(&mut hashing),
+    };
+    let mut file = BufWriter::new(std::fs::File::create(out)?);
+    match new {
+        Some(key) => {
+            let mut writer = EncryptWriter::new(key, file)?;
+            io::copy(&mut plain, &mut writer)?;
+            writer.finish()?.flush()?;
+        }
+        None => {
+            io::copy(&mut plain, &mut file)?;
+            file.flush()?;
+        }
+    }
+    drop(plain);
+    Ok(chunk_store::hex(&hashing.hasher.finalize()))
+}
+
+/// Scratch file deleted when dropped, so every early return cleans it up.
+struct ScratchFile(PathBuf);
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+pub struct RekeySnapshotHandler {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+    replicator: Option<Arc<SnapshotReplicator>>,
+}
+
+impl RekeySnapshotHandler {
+    pub fn new(
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            snapshot_repo,
+            audit_logger,
+            replicator: None,
+        }
+    }
+
+    /// Mark the replica of a rekeyed archive stale so the old ciphertext is
+    /// never fetched back.
+    pub fn with_replicator(mut self, replicator: Arc<SnapshotReplicator>) -> Self {
+        self.replicator = Some(replicator);
+        self
+    }
+
+    /// Refuse archives that cannot be (re-)encrypted.
+    async fn check_rekeyable(
+        &self,
+        snapshot: &Snapshot,
+        cmd: &RekeySnapshotCommand,
+    ) -> Result<(), DomainError> {
+        if !matches!(snapshot.status, SnapshotStatus::Completed) {
+            return Err(DomainError::SnapshotError(format!(
+                "Only completed snapshots can be rekeyed ('{}' is {:?})",
+                snapshot.id, snapshot.status
+            )));
+        }
+        if snapshot.encryption.is_none() && cmd.new.is_none() {
+            return Err(DomainError::EncryptionError(format!(
+                "Snapshot '{}' is not encrypted",
+                snapshot.id
+            )));
+        }
+        if snapshot.encryption.is_some() || cmd.new.is_none() {
+            return Ok(());
+        }
+        if !snapshot.format.is_tar() {
+            return Err(DomainError::SnapshotError(
+                "Only tar snapshots can be encrypted".into(),
+            ));
+        }
+        if snapshot.parent_id.is_some() {
+            return Err(DomainError::SnapshotError(
+                "Incremental snapshots cannot be encrypted".into(),
+            ));
+        }
+        let children = self
+            .snapshot_repo
+            .list_by_distro(&snapshot.distro_name)
+            .await?
+            .into_iter()
+            .filter(|s| s.parent_id.as_ref() == Some(&snapshot.id))
+            .count();
+        if children > 0 {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot '{}' is the parent of {} incremental snapshot(s) and cannot be encrypted",
+                snapshot.id, children
+            )));
+        }
+        Ok(())
+    }
+
+    /// Re-encrypt the archive of a snapshot under a new key (or encrypt or
+    /// decrypt it), streaming it once. The old archive is verified against
+    /// its checksum while it is read and only replaced once the new one is
+    /// complete.
+    #[tracing::instrument(skip(self, cmd), fields(snapshot_id = %cmd.snapshot_id))]
+    pub async fn handle(&self, cmd: RekeySnapshotCommand) -> Result<Snapshot, DomainError> {
+        let mut snapshot = self.snapshot_repo.get_by_id(&cmd.snapshot_id).await?;
+        self.check_rekeyable(&snapshot, &cmd).await?;
+
+        let old_key = encryption::unlock_snapshot(&snapshot, cmd.current.as_ref()).await?;
+        let (params, new_key) = match &cmd.new {
+            Some(secret) => {
+                let (params, key) = encryption::new_encryption_async(secret).await?;
+                (Some(params), Some(key))
+            }
+            None => (None, None),
+        };
+
+        // The new archive is written next to the old one (or the chunk store).
+        let (source, dir): (Box<dyn Read + Send>, PathBuf) = match snapshot.storage {
+            SnapshotStorage::File => {
+                let local = existing_local_path(&snapshot.file_path).ok_or_else(|| {
+                    DomainError::SnapshotError(format!(
+                        "Snapshot file not found: {}",
+                        snapshot.file_path
+                    ))
+                })?;
+                let local = PathBuf::from(local);
+                let dir = local.parent().unwrap_or(Path::new(".")).to_path_buf();
+                (Box::new(std::fs::File::open(&local)?), dir)
+            }
+            SnapshotStorage::Chunked => {
+                let store = chunk_store::store_dir_for(&snapshot.file_path).ok_or_else(|| {
+                    DomainError::SnapshotError(format!(
+                        "Snapshot directory of '{}' not found",
+                        snapshot.file_path
+                    ))
+                })?;
+                let manifest = self.snapshot_repo.get_chunk_manifest(&snapshot.id).await?;
+                if manifest.is_empty() {
+                    return Err(DomainError::SnapshotError(format!(
+                        "Chunked snapshot '{}' has no chunk manifest",
+                        snapshot.id
+                    )));
+                }
+                let reader = chunk_store::ChunkReader::new(manifest, &store);
+                let dir = store.parent().unwrap_or(&store).to_path_buf();
+                (Box::new(reader), dir)
+            }
+        };
+        let scratch = ScratchFile(dir.join(format!(
+            "{}-{}.rekey.{}",
+            snapshot.distro_name,
+            snapshot.id,
+            snapshot.format.extension()
+        )));
+
+        let out = scratch.0.clone();
+        let start = std::time::Instant::now();
+        let read_checksum = tokio::task::spawn_blocking(move || {
+            rewrite_archive(source, old_key.as_ref(), new_key.as_ref(), &out)
+        })
+        .await
+        .map_err(|e| DomainError::Internal(format!("rekey task failed: {}", e)))?
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => DomainError::SnapshotError(format!(
+                "Archive of snapshot '{}' is corrupted: {}",
+                snapshot.id, e
+            )),
+            _ => DomainError::from(e),
+        })?;
+        if let Some(expected) = &snapshot.checksum
+            && !read_checksum.eq_ignore_ascii_case(expected)
+        {
+            return Err(DomainError::SnapshotError(format!(
+                "Archive of snapshot '{}' does not match its checksum; verify it before rekeying",
+                snapshot.id
+            )));
+        }
+
+        let scratch_path = scratch.0.clone();
+        let checksum = tokio::task::spawn_blocking(move || integrity::sha256_file(&scratch_path))
+            .await
+            .map_err(|e| DomainError::Internal(format!("checksum task failed: {}", e)))??;
+        let size = std::fs::metadata(&scratch.0)?.len();
+        tracing::info!(
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "rewrote archive of {} ({} bytes)",
+            snapshot.id,
+            size
+        );
+
+        let chunks = match snapshot.storage {
+            SnapshotStorage::File => {
+                let local = existing_local_path(&snapshot.file_path).unwrap_or_default();
+                std::fs::rename(&scratch.0, &local).map_err(|e| {
+                    DomainError::SnapshotError(format!(
+                        "Cannot replace '{}' with the rekeyed archive: {}",
+                        local, e
+                    ))
+                })?;
+                Vec::new()
+            }
+            SnapshotStorage::Chunked => {
+                let source = scratch.0.clone();
+                let store = dir.join(chunk_store::CHUNK_DIR);
+                let chunks =
+                    tokio::task::spawn_blocking(move || chunk_store::store_file(&source, &store))
+                        .await
+                        .map_err(|e| {
+                            DomainError::Internal(format!("chunking task failed: {}", e))
+                        })??;
+                self.snapshot_repo
+                    .save_chunk_manifest(&snapshot.id, &chunks)
+                    .await?;
+                chunks
+            }
+        };
+        drop(scratch);
+
+        let from = snapshot
+            .encryption
+            .as_ref()
+            .map_or("none", |e| e.key_source.as_str());
+        let to = params.as_ref().map_or("none", |e| e.key_source.as_str());
+        let details = format!("{} -> {}", from, to);
+        snapshot.encryption = params;
+        snapshot.checksum = Some(checksum);
+        snapshot.file_size = MemorySize::from_bytes(size);
+        self.snapshot_repo.save(&snapshot).await?;
+
+        if existing_local_path(&sidecar::sidecar_path(&snapshot.file_path)).is_some()
+            && let Err(e) = sidecar::write(&snapshot, &chunks)
+        {
+            tracing::warn!("failed to update sidecar of {}: {}", snapshot.id, e);
+        }
+        // The old chunks are collected once no snapshot references them.
+        if snapshot.storage == SnapshotStorage::Chunked {
+            chunk_store::release_stores(
+                self.snapshot_repo.as_ref(),
+                std::slice::from_ref(&snapshot.file_path),
+            )
+            .await;
+        }
+        if let Some(replicator) = &self.replicator {
+            replicator.invalidate(&snapshot.id).await?;
+        }
+
+        self.audit_logger
+            .log_with_details("snapshot.rekey", &snapshot.id.to_string(), &details)
+            .await?;
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotType};
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::DistroName;
+    use chrono::Utc;
+    use std::sync::Mutex;
+
+    fn make_snapshot(file_path: &Path) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "backup".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: MemorySize::from_bytes(0),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: Some(integrity::sha256_file(file_path).unwrap()),
+            tags: vec![],
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    /// Repository holding one snapshot; saves replace it.
+    fn repo_with(snapshot: Arc<Mutex<Snapshot>>) -> MockSnapshotRepositoryPort {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        let current = snapshot.clone();
+        repo.expect_get_by_id()
+            .returning(move |_| Ok(current.lock().unwrap().clone()));
+        repo.expect_save().returning(move |s| {
+            *snapshot.lock().unwrap() = s.clone();
+            Ok(())
+        });
+        repo.expect_list_by_distro().returning(|_| Ok(vec![]));
+        repo
+    }
+
+    fn handler(snapshot: Arc<Mutex<Snapshot>>) -> RekeySnapshotHandler {
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log_with_details().returning(|_, _, _| Ok(()));
+        RekeySnapshotHandler::new(Arc::new(repo_with(snapshot)), Arc::new(audit))
+    }
+
+    fn cmd(current: Option<&str>, new: Option<&str>) -> RekeySnapshotCommand {
+        RekeySnapshotCommand {
+            snapshot_id: SnapshotId::from_string("snap-001".into()),
+            current: current.map(|p| SnapshotSecret::Passphrase(p.into())),
+            new: new.map(|p| SnapshotSecret::Passphrase(p.into())),
+        }
+    }
+
+    fn decrypted(snapshot: &Snapshot, passphrase: &str) -> Vec<u8> {
+        let key = encryption::unlock(
+            snapshot.id.as_str(),
+            snapshot.encryption.as_ref().unwrap(),
+            &SnapshotSecret::Passphrase(passphrase.into()),
+        )
+        .unwrap();
+        let file = std::fs::File::open(&snapshot.file_path).unwrap();
+        let mut out = Vec::new();
+        encryption::reader(Some(&key), file)
+            .unwrap()
+            .read_to_end(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_rekey_and_decrypt_file_snapshot() {
+        let dir = std::env::temp_dir().join("rekey_snapshot_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        let plain: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&archive, &plain).unwrap();
+        let state = Arc::new(Mutex::new(make_snapshot(&archive)));
+        let handler = handler(state.clone());
+
+        let encrypted = handler.handle(cmd(None, Some("first"))).await.unwrap();
+        assert!(encryption::has_magic(&archive));
+        assert_eq!(
+            encrypted.checksum.as_deref(),
+            Some(integrity::sha256_file(&archive).unwrap().as_str())
+        );
+        assert_eq!(
+            encrypted.file_size.bytes(),
+            std::fs::metadata(&archive).unwrap().len()
+        );
+        assert_eq!(decrypted(&encrypted, "first"), plain);
+
+        let wrong = handler.handle(cmd(Some("nope"), Some("second"))).await;
+        assert!(matches!(wrong, Err(DomainError::WrongEncryptionKey(_))));
+
+        let rekeyed = handler
+            .handle(cmd(Some("first"), Some("second")))
+            .await
+            .unwrap();
+        assert_ne!(rekeyed.encryption, encrypted.encryption);
+        assert_eq!(decrypted(&rekeyed, "second"), plain);
+
+        let plain_again = handler.handle(cmd(Some("second"), None)).await.unwrap();
+        assert!(plain_again.encryption.is_none());
+        assert_eq!(std::fs::read(&archive).unwrap(), plain);
+        assert!(!dir.join("Ubuntu-snap-001.rekey.tar").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_rekey_chunked_snapshot_stores_new_manifest() {
+        let dir = std::env::temp_dir().join("rekey_snapshot_chunked");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        std::fs::write(&archive, b"chunked archive").unwrap();
+        let mut snapshot = make_snapshot(&archive);
+        let store = dir.join(chunk_store::CHUNK_DIR);
+        let old_manifest = chunk_store::store_file(&archive, &store).unwrap();
+        std::fs::remove_file(&archive).unwrap();
+        snapshot.storage = SnapshotStorage::Chunked;
+        let state = Arc::new(Mutex::new(snapshot));
+
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let captured = saved.clone();
+        let mut repo = repo_with(state.clone());
+        repo.expect_get_chunk_manifest()
+            .returning(move |_| Ok(old_manifest.clone()));
+        repo.expect_save_chunk_manifest()
+            .returning(move |_, chunks| {
+                *captured.lock().unwrap() = chunks.to_vec();
+                Ok(())
+            });
+        repo.expect_list_referenced_chunks()
+            .returning(|| Ok(vec![]));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log_with_details().returning(|_, _, _| Ok(()));
+        let handler = RekeySnapshotHandler::new(Arc::new(repo), Arc::new(audit));
+
+        let encrypted = handler.handle(cmd(None, Some("pw"))).await.unwrap();
+        let manifest = saved.lock().unwrap().clone();
+        assert!(!manifest.is_empty());
+        assert_eq!(
+            encrypted.checksum,
+            Some(chunk_store::checksum(&manifest, &store).unwrap())
+        );
+        assert!(!archive.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_rekey_rejects_plain_decrypt_and_incremental_parents() {
+        let dir = std::env::temp_dir().join("rekey_snapshot_rejects");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        std::fs::write(&archive, b"plain").unwrap();
+        let snapshot = make_snapshot(&archive);
+
+        let not_encrypted = handler(Arc::new(Mutex::new(snapshot.clone())))
+            .handle(cmd(None, None))
+            .await;
+        assert!(matches!(
+            not_encrypted,
+            Err(DomainError::EncryptionError(_))
+        ));
+
+        let mut child = snapshot.clone();
+        child.id = SnapshotId::from_string("snap-002".into());
+        child.parent_id = Some(snapshot.id.clone());
+        let mut repo = MockSnapshotRepositoryPort::new();
+        let parent = snapshot.clone();
+        repo.expect_get_by_id()
+            .returning(move |_| Ok(parent.clone()));
+        repo.expect_list_by_distro()
+            .returning(move |_| Ok(vec![child.clone()]));
+        repo.expect_save().never();
+        let handler =
+            RekeySnapshotHandler::new(Arc::new(repo), Arc::new(MockAuditLoggerPort::new()));
+        let err = handler.handle(cmd(None, Some("pw"))).await.unwrap_err();
+        assert!(err.to_string().contains("parent of 1 incremental"));
+        assert_eq!(std::fs::read(&archive).unwrap(), b"plain");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::application::path_utils::existing_local_path;
+use crate::application::services::chunk_store;
+use crate::application::services::sidecar::{self, SnapshotSidecar};
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStatus, SnapshotStorage};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+
+/// Archive extensions recognized when looking for orphan files.
+const ARCHIVE_EXTENSIONS: [&str; 4] = [".tar", ".tar.zst", ".tar.gz", ".vhdx"];
+
+/// Intermediate files of running exports and restores, never orphans.
+const TEMP_SUFFIXES: [&str; 2] = [".export.tar", ".restore.tar"];
+
+pub struct RescanSnapshotsCommand {
+    /// Folders to scan in addition to those already holding cataloged snapshots.
+    pub directories: Vec<String>,
+}
+
+/// Outcome of a rescan.
+#[derive(Debug, Default)]
+pub struct RescanReport {
+    /// Snapshots added to the catalog from sidecars.
+    pub imported: Vec<Snapshot>,
+    /// Catalog entries whose archive was found in another scanned folder.
+    pub relocated: Vec<Snapshot>,
+    /// Catalog entries whose archive is missing on this machine.
+    pub missing: Vec<Snapshot>,
+    /// Archives in the scanned folders that no catalog entry describes.
+    pub orphan_files: Vec<String>,
+    /// Sidecars written for cataloged snapshots that had none.
+    pub sidecars_written: u32,
+}
+
+/// Files found in one scanned folder.
+#[derive(Default)]
+struct ScanResult {
+    /// Archive path (as it would be cataloged) with the sidecar describing it.
+    sidecars: Vec<(String, SnapshotSidecar)>,
+    /// Archive path (as it would be cataloged) and its local path.
+    archives: Vec<(String, PathBuf)>,
+}
+
+pub struct RescanSnapshotsHandler {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+}
+
+impl RescanSnapshotsHandler {
+    pub fn new(
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            snapshot_repo,
+            audit_logger,
+        }
+    }
+
+    /// Reconcile the catalog with the snapshot folders: import snapshots
+    /// described by sidecars but not cataloged (e.g. taken by another machine
+    /// sharing the folder), follow archives that moved between scanned folders,
+    /// backfill missing sidecars and report missing archives and orphan files.
+    /// Nothing is deleted.
+    pub async fn handle(&self, cmd: RescanSnapshotsCommand) -> Result<RescanReport, DomainError> {
+        let mut catalog = self.snapshot_repo.list_all().await?;
+        let folders = scan_folders(&cmd.directories, &catalog)?;
+        let scan = tokio::task::spawn_blocking(move || scan(&folders))
+            .await
+            .map_err(|e| DomainError::Internal(format!("rescan task failed: {}", e)))?;
+
+        let mut report = RescanReport::default();
+        let mut index: HashMap<String, usize> = catalog
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.id.as_str().to_string(), i))
+            .collect();
+
+        // Oldest first, so a parent is imported before its incremental children.
+        let mut sidecars = scan.sidecars;
+        sidecars.sort_by_key(|(_, s)| s.snapshot.created_at);
+        for (file_path, found) in sidecars {
+            let storage = found.snapshot.storage;
+            if let Some(&i) = index.get(found.snapshot.id.as_str()) {
+                let known = &mut catalog[i];
+                if known.file_path != file_path
+                    && !archive_present(&known.file_path, known.storage)
+                    && archive_present(&file_path, storage)
+                {
+                    known.file_path = file_path;
+                    self.snapshot_repo.save(known).await?;
+                    tracing::info!("snapshot {} relocated to {}", known.id, known.file_path);
+                    report.relocated.push(known.clone());
+                }
+                continue;
+            }
+
+            if !archive_present(&file_path, storage) {
+                tracing::warn!("sidecar without archive skipped: {}", file_path);
+                continue;
+            }
+            if let Some(parent) = &found.snapshot.parent_id
+                && !index.contains_key(parent.as_str())
+            {
+                tracing::warn!(
+                    "snapshot {} not imported, parent {} is not cataloged",
+                    found.snapshot.id,
+                    parent
+                );
+                continue;
+            }
+            if storage == SnapshotStorage::Chunked && found.chunks.is_empty() {
+                tracing::warn!("chunked sidecar without manifest skipped: {}", file_path);
+                continue;
+            }
+
+            let mut snapshot = found.snapshot;
+            snapshot.file_path = file_path;
+            snapshot.status = SnapshotStatus::Completed;
+            self.snapshot_repo.save(&snapshot).await?;
+            if storage == SnapshotStorage::Chunked {
+                self.snapshot_repo
+                    .save_chunk_manifest(&snapshot.id, &found.chunks)
+                    .await?;
+            }
+            tracing::info!(
+                "snapshot {} imported from {}",
+                snapshot.id,
+                snapshot.file_path
+            );
+            index.insert(snapshot.id.as_str().to_string(), catalog.len());
+            report.imported.push(snapshot.clone());
+            catalog.push(snapshot);
+        }
+
+        let mut cataloged_files = HashSet::new();
+        for snapshot in &catalog {
+            if !matches!(
+                snapshot.status,
+                SnapshotStatus::Completed | SnapshotStatus::Corrupted
+            ) {
+                continue;
+            }
+            if !archive_present(&snapshot.file_path, snapshot.storage) {
+                report.missing.push(snapshot.clone());
+                continue;
+            }
+            if let Some(local) = existing_local_path(&snapshot.file_path) {
+                cataloged_files.insert(canonical(Path::new(&local)));
+            }
+            if matches!(snapshot.status, SnapshotStatus::Completed)
+                && existing_local_path(&sidecar::sidecar_path(&snapshot.file_path)).is_none()
+            {
+                let chunks = match snapshot.storage {
+                    SnapshotStorage::Chunked => {
+                        self.snapshot_repo.get_chunk_manifest(&snapshot.id).await?
+                    }
+                    SnapshotStorage::File => Vec::new(),
+                };
+                match sidecar::write(snapshot, &chunks) {
+                    Ok(()) => report.sidecars_written += 1,
+                    Err(e) => tracing::warn!("failed to write sidecar of {}: {}", snapshot.id, e),
+                }
+            }
+        }
+
+        report.orphan_files = scan
+            .archives
+            .into_iter()
+            .filter(|(_, local)| !cataloged_files.contains(&canonical(local)))
+            .map(|(file_path, _)| file_path)
+            .collect();
+
+        tracing::info!(
+            imported = report.imported.len(),
+            relocated = report.relocated.len(),
+            missing = report.missing.len(),
+            orphans = report.orphan_files.len(),
+            sidecars_written = report.sidecars_written,
+            "snapshot rescan complete"
+        );
+        self.audit_logger
+            .log_with_details(
+                "snapshot.rescan",
+                "snapshots",
+                &format!(
+                    "imported={} relocated={} missing={} orphans={}",
+                    report.imported.len(),
+                    report.relocated.len(),
+                    report.missing.len(),
+                    report.orphan_files.len()
+                ),
+            )
+            .await?;
+
+        Ok(report)
+    }
+}
+
+/// Whether the archive of a snapshot stored at `file_path` exists on this machine.
+fn archive_present(file_path: &str, storage: SnapshotStorage) -> bool {
+    match storage {
+        SnapshotStorage::File => existing_local_path(file_path).is_some(),
+        SnapshotStorage::Chunked => {
+            chunk_store::store_dir_for(file_path).is_some_and(|s| s.is_dir())
+        }
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Folders to scan as (path as cataloged, local path): the requested ones,
+/// which must exist, plus every existing folder holding a cataloged snapshot.
+fn scan_folders(
+    requested: &[String],
+    catalog: &[Snapshot],
+) -> Result<Vec<(String, PathBuf)>, DomainError> {
+    let mut folders = Vec::new();
+    for dir in requested {
+        let local = existing_local_path(dir)
+            .ok_or_else(|| DomainError::SnapshotError(format!("Folder not found: {}", dir)))?;
+        folders.push((dir.clone(), PathBuf::from(local)));
+    }
+    for snapshot in catalog {
+        let Some(parent) = Path::new(&snapshot.file_path).parent() else {
+            continue;
+        };
+        let parent = parent.to_string_lossy().to_string();
+        if let Some(local) = existing_local_path(&parent) {
+            folders.push((parent, PathBuf::from(local)));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    folders.retain(|(_, local)| local.is_dir() && seen.insert(canonical(local)));
+    Ok(folders)
+}
+
+/// List sidecars and archives directly inside each folder (not recursive).
+fn scan(folders: &[(String, PathBuf)]) -> ScanResult {
+    let mut result = ScanResult::default();
+    for (dir, local) in folders {
+        let entries = match std::fs::read_dir(local) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("cannot scan {}: {}", local.display(), e);
+                continue;
+            }
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let cataloged = Path::new(dir).join(&name).to_string_lossy().to_string();
+            if let Some(archive) = sidecar::archive_path(&path) {
+                match sidecar::read(&path) {
+                    Ok(found) => {
+                        let archive_name = archive.file_name().unwrap_or_default();
+                        let archive_path = Path::new(dir).join(archive_name);
+                        result
+                            .sidecars
+                            .push((archive_path.to_string_lossy().to_string(), found));
+                    }
+                    Err(e) => tracing::warn!("unreadable sidecar skipped: {}", e),
+                }
+            } else if ARCHIVE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+                && !TEMP_SUFFIXES.iter().any(|ext| name.ends_with(ext))
+            {
+                result.archives.push((cataloged, path));
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotChunk, SnapshotType};
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
+    use chrono::Utc;
+    use std::sync::Mutex;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rescan_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn make_snapshot(id: &str, file_path: &Path) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: MemorySize::from_bytes(4),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn audit() -> MockAuditLoggerPort {
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log_with_details()
+            .withf(|action, _, _| action == "snapshot.rescan")
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        audit
+    }
+
+    fn handler(repo: MockSnapshotRepositoryPort) -> RescanSnapshotsHandler {
+        RescanSnapshotsHandler::new(Arc::new(repo), Arc::new(audit()))
+    }
+
+    #[tokio::test]
+    async fn test_imports_sidecars_parents_first() {
+        let dir = scratch_dir("import");
+        let parent = make_snapshot("parent", &dir.join("parent.tar"));
+        let mut child = make_snapshot("child", &dir.join("child.tar"));
+        child.snapshot_type = SnapshotType::PseudoIncremental;
+        child.parent_id = Some(parent.id.clone());
+        child.created_at = parent.created_at + chrono::Duration::hours(1);
+        // Another machine wrote these, with its own paths.
+        for snap in [&child, &parent] {
+            std::fs::write(&snap.file_path, b"data").unwrap();
+            sidecar::write(snap, &[]).unwrap();
+        }
+        std::fs::write(dir.join("stray.tar.zst"), b"data").unwrap();
+
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let s = saved.clone();
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_list_all().returning(|| Ok(vec![]));
+        repo.expect_save().returning(move |snap| {
+            s.lock().unwrap().push(snap.id.as_str().to_string());
+            Ok(())
+        });
+
+        let report = handler(repo)
+            .handle(RescanSnapshotsCommand {
+                directories: vec![dir.to_string_lossy().to_string()],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(*saved.lock().unwrap(), vec!["parent", "child"]);
+        assert_eq!(report.imported.len(), 2);
+        assert!(
+            report
+                .imported
+                .iter()
+                .all(|s| s.file_path.starts_with(&*dir.to_string_lossy()))
+        );
+        assert!(report.missing.is_empty());
+        assert_eq!(report.sidecars_written, 0);
+        assert_eq!(report.orphan_files.len(), 1);
+        assert!(report.orphan_files[0].ends_with("stray.tar.zst"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_flags_missing_and_backfills_sidecars() {
+        let dir = scratch_dir("missing");
+        let present = make_snapshot("present", &dir.join("present.tar"));
+        std::fs::write(&present.file_path, b"data").unwrap();
+        let gone = make_snapshot("gone", &dir.join("gone.tar"));
+        let mut failed = make_snapshot("failed", &dir.join("failed.tar"));
+        failed.status = SnapshotStatus::Failed("boom".into());
+        std::fs::write(dir.join("a.export.tar"), b"tmp").unwrap();
+
+        let catalog = vec![present.clone(), gone, failed];
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_list_all()
+            .returning(move || Ok(catalog.clone()));
+        repo.expect_save().never();
+
+        let report = handler(repo)
+            .handle(RescanSnapshotsCommand {
+                directories: vec![],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].id.as_str(), "gone");
+        assert_eq!(report.sidecars_written, 1);
+        assert!(Path::new(&sidecar::sidecar_path(&present.file_path)).exists());
+        assert!(report.orphan_files.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_relocates_moved_archive() {
+        let old_dir = scratch_dir("relocate_old");
+        let new_dir = scratch_dir("relocate_new");
+        let known = make_snapshot("moved", &old_dir.join("moved.tar"));
+        let moved = make_snapshot("moved", &new_dir.join("moved.tar"));
+        std::fs::write(&moved.file_path, b"data").unwrap();
+        sidecar::write(&moved, &[]).unwrap();
+        let _ = std::fs::remove_dir_all(&old_dir);
+
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_list_all()
+            .returning(move || Ok(vec![known.clone()]));
+        let expected = moved.file_path.clone();
+        repo.expect_save()
+            .withf(move |s| s.file_path == expected)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let report = handler(repo)
+            .handle(RescanSnapshotsCommand {
+                directories: vec![new_dir.to_string_lossy().to_string()],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.relocated.len(), 1);
+        assert!(report.imported.is_empty());
+        assert!(report.missing.is_empty());
+        let _ = std::fs::remove_dir_all(&new_dir);
+    }
+
+    #[tokio::test]
+    async fn test_skips_orphan_child_and_chunked_without_store() {
+        let dir = scratch_dir("skip");
+        let mut child = make_snapshot("child", &dir.join("child.tar"));
+        child.parent_id = Some(SnapshotId::from_string("unknown".into()));
+        std::fs::write(&child.file_path, b"data").unwrap();
+        sidecar::write(&child, &[]).unwrap();
+        let mut chunked = make_snapshot("chunked", &dir.join("chunked.tar"));
+        chunked.storage = SnapshotStorage::Chunked;
+        sidecar::write(
+            &chunked,
+            &[SnapshotChunk {
+                hash: "aa".repeat(32),
+                length: 4,
+            }],
+        )
+        .unwrap();
+
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_list_all().returning(|| Ok(vec![]));
+        repo.expect_save().never();
+
+        let report = handler(repo)
+            .handle(RescanSnapshotsCommand {
+                directories: vec![dir.to_string_lossy().to_string()],
+            })
+            .await
+            .unwrap();
+
+        assert!(report.imported.is_empty());
+        assert_eq!(report.orphan_files.len(), 1);
+        assert!(report.orphan_files[0].ends_with("child.tar"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_folder() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_list_all().returning(|| Ok(vec![]));
+        let handler =
+            RescanSnapshotsHandler::new(Arc::new(repo), Arc::new(MockAuditLoggerPort::new()));
+        let result = handler
+            .handle(RescanSnapshotsCommand {
+                directories: vec!["/nonexistent/rescan/folder".into()],
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+}
@@ -1,17 +1,30 @@
 use std::sync::Arc;
 
-use crate::application::path_utils::windows_to_linux_path;
-use crate::domain::entities::snapshot::RestoreMode;
+use crate::application::path_utils::{existing_local_path, windows_to_linux_path};
+use crate::application::services::encryption::{self, SnapshotSecret};
+use crate::application::services::integrity::{self, Verification};
+use crate::application::services::replication::SnapshotReplicator;
+use crate::application::services::safety_snapshot::{self, SafetySnapshotGuard};
+use crate::application::services::transfer::{TransferPhase, TransferRegistry};
+use crate::application::services::{
+    chunk_store, compression, incremental, snapshot_files, snapshot_reader,
+};
+use crate::domain::entities::safety_snapshot::DestructiveOperation;
+use crate::domain::entities::snapshot::{RestoreMode, Snapshot, SnapshotStatus, SnapshotStorage};
 use crate::domain::errors::DomainError;
 use crate::domain::ports::audit_logger::AuditLoggerPort;
 use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
 use crate::domain::ports::wsl_manager::WslManagerPort;
-use crate::domain::value_objects::{DistroName, SnapshotId};
+use crate::domain::value_objects::{DistroName, EncryptionKey, SnapshotId};
 
 pub struct RestoreSnapshotCommand {
     pub snapshot_id: SnapshotId,
     pub mode: RestoreMode,
     pub install_location: String,
+    /// Restore even if the archive fails its checksum verification.
+    pub force: bool,
+    /// Passphrase or keyfile of an encrypted snapshot.
+    pub secret: Option<SnapshotSecret>,
 }
 
 /// Remove `path` (and contents) by polling until it is gone or `timeout` elapses.
@@ -38,10 +51,27 @@ async fn remove_install_dir_until_gone(
     }
 }
 
+/// Quote `s` as a single POSIX shell word.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Scratch file deleted when dropped, so every early return cleans it up.
+struct ScratchFile(std::path::PathBuf);
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
 pub struct RestoreSnapshotHandler {
     wsl_manager: Arc<dyn WslManagerPort>,
     snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
     audit_logger: Arc<dyn AuditLoggerPort>,
+    transfers: Option<Arc<TransferRegistry>>,
+    replicator: Option<Arc<SnapshotReplicator>>,
+    safety: Option<Arc<SafetySnapshotGuard>>,
 }
 
 impl RestoreSnapshotHandler {
@@ -54,7 +84,233 @@ impl RestoreSnapshotHandler {
             wsl_manager,
             snapshot_repo,
             audit_logger,
+            transfers: None,
+            replicator: None,
+            safety: None,
+        }
+    }
+
+    /// Report import progress to `transfers` and make the restore
+    /// cancellable through it.
+    pub fn with_transfers(mut self, transfers: Arc<TransferRegistry>) -> Self {
+        self.transfers = Some(transfers);
+        self
+    }
+
+    /// Pull archives that are missing locally back down from their replicas
+    /// before restoring.
+    pub fn with_replicator(mut self, replicator: Arc<SnapshotReplicator>) -> Self {
+        self.replicator = Some(replicator);
+        self
+    }
+
+    /// Take a safety snapshot of the distro before overwriting it when its
+    /// policy asks for one.
+    pub fn with_safety(mut self, safety: Arc<SafetySnapshotGuard>) -> Self {
+        self.safety = Some(safety);
+        self
+    }
+
+    /// Rebuild the full tar of an incremental snapshot by layering its parent
+    /// chain. The result is written next to the snapshot file.
+    async fn rebuild_from_chain(&self, snapshot: &Snapshot) -> Result<ScratchFile, DomainError> {
+        let chain = incremental::resolve_chain(self.snapshot_repo.as_ref(), snapshot).await?;
+        let layers = chain
+            .iter()
+            .map(|s| {
+                existing_local_path(&s.file_path)
+                    .map(std::path::PathBuf::from)
+                    .ok_or_else(|| {
+                        DomainError::SnapshotError(format!(
+                            "Snapshot file of chain member '{}' not found: {}",
+                            s.id, s.file_path
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let own_file = layers.last().cloned().unwrap_or_default();
+        let out = own_file
+            .parent()
+            .unwrap_or(std::path::Path::new("."))
+            .join(format!(
+                "{}-{}.restore.tar",
+                snapshot.distro_name, snapshot.id
+            ));
+        tracing::info!(
+            "rebuilding full tar from {} layer(s): {}",
+            layers.len(),
+            out.display()
+        );
+
+        let scratch = ScratchFile(out.clone());
+        let entries =
+            tokio::task::spawn_blocking(move || incremental::rebuild_full_tar(&layers, &out))
+                .await
+                .map_err(|e| DomainError::Internal(format!("rebuild task failed: {}", e)))??;
+        tracing::info!("rebuilt full tar with {} entries", entries);
+        Ok(scratch)
+    }
+
+    /// Re-hash the snapshot (every layer of an incremental chain) against its
+    /// recorded checksums. A mismatching layer is marked `Corrupted` and the
+    /// restore aborts. Layers without a checksum (legacy) are not checked.
+    async fn verify_checksums(&self, snapshot: &Snapshot) -> Result<(), DomainError> {
+        if matches!(snapshot.status, SnapshotStatus::Corrupted) {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot '{}' is marked corrupted; verify it again or force the restore",
+                snapshot.id
+            )));
+        }
+        let layers = if snapshot.parent_id.is_some() {
+            incremental::resolve_chain(self.snapshot_repo.as_ref(), snapshot).await?
+        } else {
+            vec![snapshot.clone()]
+        };
+        for mut layer in layers.into_iter().filter(|l| l.checksum.is_some()) {
+            let start = std::time::Instant::now();
+            let verification = integrity::verify(self.snapshot_repo.as_ref(), &layer).await?;
+            tracing::info!(
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "checksum verification of {}: {:?}",
+                layer.id,
+                verification
+            );
+            if let Verification::Corrupted(reason) = verification {
+                layer.status = SnapshotStatus::Corrupted;
+                self.snapshot_repo.save(&layer).await?;
+                self.audit_logger
+                    .log("snapshot.corrupted", &layer.id.to_string())
+                    .await?;
+                return Err(DomainError::SnapshotError(format!(
+                    "Snapshot '{}' failed its integrity check ({}); force the restore to proceed anyway",
+                    layer.id, reason
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reassemble a chunked snapshot's archive from the chunk store next to
+    /// its `file_path`.
+    async fn reassemble_chunks(&self, snapshot: &Snapshot) -> Result<ScratchFile, DomainError> {
+        let manifest = self.snapshot_repo.get_chunk_manifest(&snapshot.id).await?;
+        if manifest.is_empty() {
+            return Err(DomainError::SnapshotError(format!(
+                "Chunked snapshot '{}' has no chunk manifest",
+                snapshot.id
+            )));
+        }
+        let store = chunk_store::store_dir_for(&snapshot.file_path).ok_or_else(|| {
+            DomainError::SnapshotError(format!(
+                "Snapshot directory of '{}' not found",
+                snapshot.file_path
+            ))
+        })?;
+        let out = store.parent().unwrap_or(&store).join(format!(
+            "{}-{}.restore.{}",
+            snapshot.distro_name,
+            snapshot.id,
+            snapshot.format.extension()
+        ));
+        tracing::info!(
+            "reassembling {} chunk(s) from {} into {}",
+            manifest.len(),
+            store.display(),
+            out.display()
+        );
+
+        let scratch = ScratchFile(out.clone());
+        let bytes =
+            tokio::task::spawn_blocking(move || chunk_store::reassemble(&manifest, &store, &out))
+                .await
+                .map_err(|e| DomainError::Internal(format!("reassembly task failed: {}", e)))??;
+        if bytes == 0 {
+            return Err(DomainError::SnapshotError(
+                "Reassembled snapshot is empty (0 bytes)".into(),
+            ));
         }
+        tracing::info!("reassembled {} bytes", bytes);
+        Ok(scratch)
+    }
+
+    /// Copy `paths` from the snapshot back into its (running) distro without
+    /// re-importing it: the selected entries are streamed into a small tar
+    /// staged next to the snapshot, which the distro extracts as root.
+    async fn restore_files(
+        &self,
+        snapshot: &Snapshot,
+        paths: &[String],
+        force: bool,
+        key: Option<&EncryptionKey>,
+    ) -> Result<(), DomainError> {
+        if paths.is_empty() {
+            return Err(DomainError::SnapshotError(
+                "No paths selected for a file restore".into(),
+            ));
+        }
+        let selection = paths
+            .iter()
+            .map(|p| snapshot_files::normalize_path(p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if force {
+            tracing::warn!("restore forced: skipping checksum verification");
+        } else {
+            self.verify_checksums(snapshot).await?;
+        }
+        let layers =
+            snapshot_reader::resolve_layers(self.snapshot_repo.as_ref(), snapshot, key).await?;
+
+        // Staged next to the snapshot so the distro can read it via /mnt/<drive>.
+        let parent = std::path::Path::new(&snapshot.file_path)
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let dir = existing_local_path(&parent).ok_or_else(|| {
+            DomainError::SnapshotError(format!("Snapshot folder not found: {}", parent))
+        })?;
+        let staging = std::path::Path::new(&dir).join(format!(
+            "{}-{}.files.tar",
+            snapshot.distro_name, snapshot.id
+        ));
+        let scratch = ScratchFile(staging.clone());
+
+        let entries = tokio::task::spawn_blocking(move || {
+            snapshot_files::extract_paths(&layers, &selection, &staging)
+        })
+        .await
+        .map_err(|e| DomainError::Internal(format!("extract task failed: {}", e)))??;
+        if entries == 0 {
+            return Err(DomainError::SnapshotError(format!(
+                "None of the selected paths exist in snapshot '{}'",
+                snapshot.id
+            )));
+        }
+        tracing::info!("extracted {} entries to {}", entries, scratch.0.display());
+
+        // Root keeps the archived owners and modes (-p, --numeric-owner).
+        let in_distro = windows_to_linux_path(&scratch.0.to_string_lossy());
+        self.wsl_manager
+            .exec_in_distro_as_root(
+                &snapshot.distro_name,
+                &format!("tar -xpf {} --numeric-owner -C /", shell_quote(&in_distro)),
+            )
+            .await?;
+
+        self.audit_logger
+            .log_with_details(
+                "snapshot.restore",
+                &snapshot.id.to_string(),
+                &format!(
+                    "Restored {} entries into '{}': {}",
+                    entries,
+                    snapshot.distro_name,
+                    paths.join(", ")
+                ),
+            )
+            .await?;
+        Ok(())
     }
 
     #[tracing::instrument(
@@ -67,6 +323,27 @@ impl RestoreSnapshotHandler {
     )]
     pub async fn handle(&self, cmd: RestoreSnapshotCommand) -> Result<(), DomainError> {
         let snapshot = self.snapshot_repo.get_by_id(&cmd.snapshot_id).await?;
+        // A wrong key is refused before anything is fetched or touched.
+        let key = encryption::unlock_snapshot(&snapshot, cmd.secret.as_ref()).await?;
+        let transfer = self
+            .transfers
+            .as_ref()
+            .map(|t| t.begin(&cmd.snapshot_id))
+            .transpose()?;
+
+        // A fetched chunked snapshot comes back as a plain file: reload it.
+        let snapshot = match &self.replicator {
+            Some(r) if r.fetch(&snapshot, transfer.as_ref()).await? => {
+                self.snapshot_repo.get_by_id(&cmd.snapshot_id).await?
+            }
+            _ => snapshot,
+        };
+
+        if let RestoreMode::Files { paths } = &cmd.mode {
+            return self
+                .restore_files(&snapshot, paths, cmd.force, key.as_ref())
+                .await;
+        }
 
         tracing::info!(
             "starting snapshot restore: id={} path={} format={:?} mode={:?} install={}",
@@ -77,51 +354,105 @@ impl RestoreSnapshotHandler {
             cmd.install_location
         );
 
-        // Try the stored path first; if it fails (e.g. Windows path on Linux),
-        // try converting Windows→Linux (/mnt/x/...) as a fallback.
-        let file_meta = std::fs::metadata(&snapshot.file_path)
-            .or_else(|_| {
-                let linux_path = windows_to_linux_path(&snapshot.file_path);
-                if linux_path != snapshot.file_path {
-                    tracing::info!(
-                        "retrying metadata with converted path: original={} converted={}",
-                        snapshot.file_path,
-                        linux_path
-                    );
-                    std::fs::metadata(&linux_path)
-                } else {
-                    // Can't convert, re-trigger the original error
-                    std::fs::metadata(&snapshot.file_path)
-                }
-            })
-            .map_err(|e| {
-                DomainError::SnapshotError(format!(
-                    "Snapshot file not found: {} ({})",
-                    snapshot.file_path, e
-                ))
-            })?;
+        // Chunked snapshots have no archive at `file_path`; their chunks are
+        // verified while reassembling below.
+        if snapshot.storage == SnapshotStorage::File {
+            // Try the stored path first; if it fails (e.g. Windows path on Linux),
+            // try converting Windows→Linux (/mnt/x/...) as a fallback.
+            let file_meta = std::fs::metadata(&snapshot.file_path)
+                .or_else(|_| {
+                    let linux_path = windows_to_linux_path(&snapshot.file_path);
+                    if linux_path != snapshot.file_path {
+                        tracing::info!(
+                            "retrying metadata with converted path: original={} converted={}",
+                            snapshot.file_path,
+                            linux_path
+                        );
+                        std::fs::metadata(&linux_path)
+                    } else {
+                        // Can't convert, re-trigger the original error
+                        std::fs::metadata(&snapshot.file_path)
+                    }
+                })
+                .map_err(|e| {
+                    DomainError::SnapshotError(format!(
+                        "Snapshot file not found: {} ({})",
+                        snapshot.file_path, e
+                    ))
+                })?;
 
-        if file_meta.len() == 0 {
-            return Err(DomainError::SnapshotError(
-                "Snapshot file is empty (0 bytes)".into(),
-            ));
+            if file_meta.len() == 0 {
+                return Err(DomainError::SnapshotError(
+                    "Snapshot file is empty (0 bytes)".into(),
+                ));
+            }
+
+            // Compare whole suffixes: compressed formats have two ("tar.zst").
+            let expected_ext = snapshot.format.extension();
+            let file_name = std::path::Path::new(&snapshot.file_path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_ascii_lowercase();
+            if !file_name.ends_with(&format!(".{}", expected_ext)) {
+                let actual_ext = std::path::Path::new(&snapshot.file_path)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("");
+                return Err(DomainError::SnapshotError(format!(
+                    "File extension '{}' does not match expected format '{}'",
+                    actual_ext, expected_ext
+                )));
+            }
+
+            // Compressed archives must start with their codec's magic bytes
+            // (encrypted ones with the encryption header); anything else would
+            // only fail halfway through the import.
+            let local = existing_local_path(&snapshot.file_path).unwrap_or_default();
+            let local = std::path::Path::new(&local);
+            let valid = if key.is_some() {
+                encryption::has_magic(local)
+            } else {
+                compression::has_magic(local, &snapshot.format)
+            };
+            if !valid {
+                return Err(DomainError::SnapshotError(format!(
+                    "Snapshot file is not a valid {} archive: {}",
+                    expected_ext, snapshot.file_path
+                )));
+            }
         }
 
-        let expected_ext = snapshot.format.extension();
-        let actual_ext = std::path::Path::new(&snapshot.file_path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("");
-        if !actual_ext.eq_ignore_ascii_case(expected_ext) {
-            return Err(DomainError::SnapshotError(format!(
-                "File extension '{}' does not match expected format '{}'",
-                actual_ext, expected_ext
-            )));
+        // Refuse silently corrupted archives before anything destructive happens.
+        if cmd.force {
+            tracing::warn!("restore forced: skipping checksum verification");
+        } else {
+            self.verify_checksums(&snapshot).await?;
+        }
+
+        // Incremental snapshots only hold a delta: rebuild the full tar from the
+        // parent chain before anything destructive happens to the target.
+        let rebuilt_tar = if snapshot.storage == SnapshotStorage::Chunked {
+            Some(self.reassemble_chunks(&snapshot).await?)
+        } else if snapshot.parent_id.is_some() {
+            Some(self.rebuild_from_chain(&snapshot).await?)
+        } else {
+            None
+        };
+        let import_path = rebuilt_tar
+            .as_ref()
+            .map(|f| f.0.to_string_lossy().to_string())
+            .unwrap_or_else(|| snapshot.file_path.clone());
+
+        // Cancelling after this point aborts the import itself.
+        if let Some(t) = &transfer {
+            t.check()?;
         }
 
         let target_name = match &cmd.mode {
             RestoreMode::Clone { new_name } => DistroName::new(new_name)?,
             RestoreMode::Overwrite => snapshot.distro_name.clone(),
+            RestoreMode::Files { .. } => unreachable!("file restores return early"),
         };
 
         // Resolve the distro's real install directory from the WSL registry.
@@ -148,6 +479,17 @@ impl RestoreSnapshotHandler {
             cmd.install_location.clone()
         };
 
+        // A safety snapshot of the current state comes first; if it fails the
+        // distro is left alone.
+        let safety = match (&self.safety, &cmd.mode) {
+            (Some(guard), RestoreMode::Overwrite) => {
+                guard
+                    .protect(&target_name, DestructiveOperation::RestoreOverwrite)
+                    .await?
+            }
+            _ => None,
+        };
+
         // Canary test: write a unique file BEFORE the restore that should NOT
         // survive the import. If it's still present after, the filesystem wasn't replaced.
         let canary_id = uuid::Uuid::new_v4().to_string();
@@ -300,19 +642,38 @@ impl RestoreSnapshotHandler {
             "executing wsl --import: distro={} install={} snapshot={} size={}",
             target_name,
             install_location,
-            snapshot.file_path,
+            import_path,
             snapshot.file_size.bytes()
         );
 
-        let import_result = self
-            .wsl_manager
-            .import_distro(
-                &target_name,
-                &install_location,
-                &snapshot.file_path,
-                snapshot.format.clone(),
-            )
-            .await;
+        let import = self.wsl_manager.import_distro(
+            &target_name,
+            &install_location,
+            &import_path,
+            snapshot.format.clone(),
+            key.clone(),
+        );
+        let import_result = match &transfer {
+            Some(t) => {
+                // The new ext4.vhdx grows to roughly the size of an uncompressed
+                // archive; compressed ones give no usable estimate.
+                let estimate = if snapshot.format.is_compressed() {
+                    None
+                } else {
+                    existing_local_path(&import_path)
+                        .and_then(|p| std::fs::metadata(p).ok())
+                        .map(|m| m.len())
+                };
+                let vhdx = std::path::Path::new(&install_location)
+                    .join("ext4.vhdx")
+                    .to_string_lossy()
+                    .to_string();
+                t.run(TransferPhase::Import, &target_name, &vhdx, estimate, import)
+                    .await
+            }
+            None => import.await,
+        };
+        drop(rebuilt_tar);
 
         // If import failed in overwrite mode, try to restore from safety backup
         if let Err(ref import_err) = import_result
@@ -331,6 +692,7 @@ impl RestoreSnapshotHandler {
                     &install_location,
                     backup_path,
                     crate::domain::entities::snapshot::ExportFormat::Vhd,
+                    None,
                 )
                 .await
             {
@@ -564,7 +926,10 @@ impl RestoreSnapshotHandler {
             .log_with_details(
                 "snapshot.restore",
                 &cmd.snapshot_id.to_string(),
-                &format!("Restored as '{}' ({:?})", target_name, cmd.mode),
+                &safety_snapshot::with_safety_note(
+                    format!("Restored as '{}' ({:?})", target_name, cmd.mode),
+                    safety.as_ref(),
+                ),
             )
             .await?;
 
@@ -575,7 +940,9 @@ impl RestoreSnapshotHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::snapshot::{ExportFormat, Snapshot, SnapshotStatus, SnapshotType};
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
     use crate::domain::ports::audit_logger::MockAuditLoggerPort;
     use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
     use crate::domain::ports::wsl_manager::MockWslManagerPort;
@@ -596,6 +963,11 @@ mod tests {
             created_at: Utc::now(),
             status: SnapshotStatus::Completed,
             default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
         }
     }
 
@@ -622,6 +994,8 @@ mod tests {
                     new_name: "Ubuntu-Clone".into(),
                 },
                 install_location: "/tmp".into(),
+                force: false,
+                secret: None,
             })
             .await;
         assert!(result.is_err());
@@ -655,6 +1029,8 @@ mod tests {
                     new_name: "".into(), // Invalid empty name
                 },
                 install_location: "/tmp".into(),
+                force: false,
+                secret: None,
             })
             .await;
 
@@ -676,7 +1052,7 @@ mod tests {
         let mut wsl_mock = MockWslManagerPort::new();
         wsl_mock
             .expect_import_distro()
-            .returning(|_, _, _, _| Err(DomainError::WslCliError("import failed".into())));
+            .returning(|_, _, _, _, _| Err(DomainError::WslCliError("import failed".into())));
 
         let audit_mock = MockAuditLoggerPort::new();
 
@@ -692,6 +1068,8 @@ mod tests {
                     new_name: "Ubuntu-Clone".into(),
                 },
                 install_location: "/tmp".into(),
+                force: false,
+                secret: None,
             })
             .await;
 
@@ -699,6 +1077,207 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_restore_rejects_compressed_file_without_magic() {
+        let tmp = std::env::temp_dir().join("test_restore_bad_magic.tar.zst");
+        std::fs::write(&tmp, b"not zstd at all").unwrap();
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let mut snap = make_snapshot(tmp.to_str().unwrap());
+        snap.format = ExportFormat::TarZst;
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+
+        // No WSL expectations: the archive must be rejected up front.
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Clone {
+                    new_name: "Ubuntu-Clone".into(),
+                },
+                install_location: "/tmp".into(),
+                force: false,
+                secret: None,
+            })
+            .await;
+
+        let _ = std::fs::remove_file(&tmp);
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("not a valid tar.zst archive"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_restore_compressed_checks_double_extension() {
+        let tmp = std::env::temp_dir().join("test_restore_double_ext.zst");
+        std::fs::write(&tmp, [0x28, 0xB5, 0x2F, 0xFD, 0, 0]).unwrap();
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let mut snap = make_snapshot(tmp.to_str().unwrap());
+        snap.format = ExportFormat::TarZst;
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Clone {
+                    new_name: "Ubuntu-Clone".into(),
+                },
+                install_location: "/tmp".into(),
+                force: false,
+                secret: None,
+            })
+            .await;
+
+        let _ = std::fs::remove_file(&tmp);
+        let err = result.unwrap_err().to_string();
+        assert!(
+            err.contains("does not match expected format 'tar.zst'"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_compressed_passes_format_to_import() {
+        let tmp = std::env::temp_dir().join("test_restore_compressed.tar.gz");
+        std::fs::write(&tmp, [0x1F, 0x8B, 0x08, 0x00]).unwrap();
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let mut snap = make_snapshot(tmp.to_str().unwrap());
+        snap.format = ExportFormat::TarGz;
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+
+        let expected_path = tmp.to_string_lossy().to_string();
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock
+            .expect_import_distro()
+            .withf(move |_, _, path, format, _| {
+                path == expected_path && matches!(format, ExportFormat::TarGz)
+            })
+            .returning(|_, _, _, _, _| Err(DomainError::WslCliError("import failed".into())));
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Clone {
+                    new_name: "Ubuntu-Clone".into(),
+                },
+                install_location: "/tmp".into(),
+                force: false,
+                secret: None,
+            })
+            .await;
+
+        let _ = std::fs::remove_file(&tmp);
+        assert!(result.unwrap_err().to_string().contains("import failed"));
+    }
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[tokio::test]
+    async fn test_restore_refuses_checksum_mismatch() {
+        let tmp = std::env::temp_dir().join("test_restore_checksum_mismatch.tar");
+        std::fs::write(&tmp, b"bit rot").unwrap();
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let mut snap = make_snapshot(tmp.to_str().unwrap());
+        snap.checksum = Some(HELLO_SHA256.into());
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+        repo_mock
+            .expect_save()
+            .withf(|s| matches!(s.status, SnapshotStatus::Corrupted))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit_mock = MockAuditLoggerPort::new();
+        audit_mock
+            .expect_log()
+            .withf(|action, _| action == "snapshot.corrupted")
+            .returning(|_, _| Ok(()));
+
+        // No WSL expectations: nothing may happen to the target distro.
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(repo_mock),
+            Arc::new(audit_mock),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Overwrite,
+                install_location: "/tmp".into(),
+                force: false,
+                secret: None,
+            })
+            .await;
+
+        let _ = std::fs::remove_file(&tmp);
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("failed its integrity check"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_restore_refuses_corrupted_snapshot_unless_forced() {
+        let tmp = std::env::temp_dir().join("test_restore_corrupted_forced.tar");
+        std::fs::write(&tmp, b"bit rot").unwrap();
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        let mut snap = make_snapshot(tmp.to_str().unwrap());
+        snap.checksum = Some(HELLO_SHA256.into());
+        snap.status = SnapshotStatus::Corrupted;
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock
+            .expect_import_distro()
+            .times(1)
+            .returning(|_, _, _, _, _| Err(DomainError::WslCliError("import failed".into())));
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let cmd = |force| RestoreSnapshotCommand {
+            snapshot_id: SnapshotId::from_string("snap-001".into()),
+            mode: RestoreMode::Clone {
+                new_name: "Ubuntu-Clone".into(),
+            },
+            install_location: "/tmp".into(),
+            force,
+            secret: None,
+        };
+
+        let refused = handler.handle(cmd(false)).await.unwrap_err().to_string();
+        assert!(refused.contains("marked corrupted"), "{}", refused);
+        // Forced: verification is skipped and the import is attempted.
+        let forced = handler.handle(cmd(true)).await.unwrap_err().to_string();
+        let _ = std::fs::remove_file(&tmp);
+        assert!(forced.contains("import failed"), "{}", forced);
+    }
+
     #[tokio::test]
     async fn test_overwrite_rejects_stale_marker_mismatch() {
         let dir = std::env::temp_dir().join("restore_stale_marker_dir");
@@ -721,7 +1300,7 @@ mod tests {
         wsl_mock.expect_unregister_distro().returning(|_| Ok(()));
         wsl_mock
             .expect_import_distro()
-            .returning(|_, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _| Ok(()));
         wsl_mock.expect_get_distro().returning(|name| {
             Ok(crate::domain::entities::distro::Distro::new(
                 name.clone(),
@@ -750,6 +1329,8 @@ mod tests {
                 snapshot_id: SnapshotId::from_string("snap-001".into()),
                 mode: RestoreMode::Overwrite,
                 install_location: dir.to_str().unwrap().to_string(),
+                force: false,
+                secret: None,
             })
             .await;
 
@@ -786,7 +1367,7 @@ mod tests {
         wsl_mock.expect_unregister_distro().returning(|_| Ok(()));
         wsl_mock
             .expect_import_distro()
-            .returning(|_, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _| Ok(()));
         wsl_mock.expect_get_distro().returning(|name| {
             Ok(crate::domain::entities::distro::Distro::new(
                 name.clone(),
@@ -817,6 +1398,8 @@ mod tests {
                 snapshot_id: SnapshotId::from_string("snap-001".into()),
                 mode: RestoreMode::Overwrite,
                 install_location: dir.to_str().unwrap().to_string(),
+                force: false,
+                secret: None,
             })
             .await;
 
@@ -854,7 +1437,7 @@ mod tests {
         wsl_mock
             .expect_import_distro()
             .times(1)
-            .returning(move |_, loc, _, _| {
+            .returning(move |_, loc, _, _, _| {
                 assert_eq!(loc, expect_loc, "import must target the registry BasePath");
                 Ok(())
             });
@@ -885,6 +1468,8 @@ mod tests {
                 snapshot_id: SnapshotId::from_string("snap-001".into()),
                 mode: RestoreMode::Overwrite,
                 install_location: "/some/wrong/path/from/frontend".into(),
+                force: false,
+                secret: None,
             })
             .await;
 
@@ -945,6 +1530,8 @@ mod tests {
                 snapshot_id: SnapshotId::from_string("snap-001".into()),
                 mode: RestoreMode::Overwrite,
                 install_location: "/tmp".into(),
+                force: false,
+                secret: None,
             })
             .await;
 
@@ -953,4 +1540,415 @@ mod tests {
         let err_msg = result.unwrap_err().to_string();
         assert!(err_msg.contains("unregister"));
     }
+
+    fn write_tar(path: &std::path::Path, files: &[(&str, &[u8])]) {
+        let mut builder = tar::Builder::new(std::fs::File::create(path).unwrap());
+        for (name, data) in files {
+            let mut header = tar::Header::new_ustar();
+            header.set_mode(0o644);
+            header.set_size(data.len() as u64);
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_incremental_restore_imports_rebuilt_chain() {
+        let dir = std::env::temp_dir().join("restore_incremental_chain");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let base_tar = dir.join("base.tar");
+        let full_v2 = dir.join("v2-full.tar");
+        let delta_tar = dir.join("delta.tar");
+        write_tar(&base_tar, &[("etc/a", b"a1"), ("etc/b", b"b1")]);
+        write_tar(&full_v2, &[("etc/a", b"a2"), ("etc/c", b"c2")]);
+        incremental::build_delta(std::slice::from_ref(&base_tar), &full_v2, &delta_tar).unwrap();
+
+        let mut base = make_snapshot(base_tar.to_str().unwrap());
+        base.id = SnapshotId::from_string("base-001".into());
+        let mut delta = make_snapshot(delta_tar.to_str().unwrap());
+        delta.snapshot_type = SnapshotType::PseudoIncremental;
+        delta.parent_id = Some(base.id.clone());
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock.expect_get_by_id().returning(move |id| {
+            if id.as_str() == "base-001" {
+                Ok(base.clone())
+            } else {
+                Ok(delta.clone())
+            }
+        });
+
+        let imported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = imported.clone();
+        let imported_path = Arc::new(std::sync::Mutex::new(String::new()));
+        let captured_path = imported_path.clone();
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock
+            .expect_import_distro()
+            .returning(move |_, _, path, _, _| {
+                let mut archive = tar::Archive::new(std::fs::File::open(path).unwrap());
+                for entry in archive.entries().unwrap() {
+                    let mut entry = entry.unwrap();
+                    let mut data = String::new();
+                    std::io::Read::read_to_string(&mut entry, &mut data).unwrap();
+                    let name = entry.path().unwrap().to_string_lossy().to_string();
+                    captured.lock().unwrap().push((name, data));
+                }
+                *captured_path.lock().unwrap() = path.to_string();
+                Err(DomainError::WslCliError("stop after import".into()))
+            });
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Clone {
+                    new_name: "Ubuntu-Clone".into(),
+                },
+                install_location: dir.to_string_lossy().to_string(),
+                force: false,
+                secret: None,
+            })
+            .await;
+        assert!(result.is_err());
+
+        let mut got = imported.lock().unwrap().clone();
+        got.sort();
+        assert_eq!(
+            got,
+            vec![
+                ("etc/a".to_string(), "a2".to_string()),
+                ("etc/c".to_string(), "c2".to_string()),
+            ]
+        );
+        let rebuilt = imported_path.lock().unwrap().clone();
+        assert!(rebuilt.ends_with(".restore.tar"));
+        assert!(
+            !std::path::Path::new(&rebuilt).exists(),
+            "rebuilt tar must be removed after import"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_restore_missing_parent_file_fails_before_wsl() {
+        let tmp = std::env::temp_dir().join("restore_incremental_orphan.tar");
+        std::fs::write(&tmp, b"delta").unwrap();
+
+        let mut base = make_snapshot("/nonexistent/base.tar");
+        base.id = SnapshotId::from_string("base-001".into());
+        let mut delta = make_snapshot(tmp.to_str().unwrap());
+        delta.parent_id = Some(base.id.clone());
+
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock.expect_get_by_id().returning(move |id| {
+            if id.as_str() == "base-001" {
+                Ok(base.clone())
+            } else {
+                Ok(delta.clone())
+            }
+        });
+
+        // No WSL expectations: any WSL call would panic.
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Overwrite,
+                install_location: "/tmp".into(),
+                force: false,
+                secret: None,
+            })
+            .await;
+
+        let _ = std::fs::remove_file(&tmp);
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_restore_imports_reassembled_archive() {
+        let dir = std::env::temp_dir().join("restore_chunked_store");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        write_tar(&archive, &[("etc/hostname", b"chunky")]);
+        let original = std::fs::read(&archive).unwrap();
+        let manifest =
+            chunk_store::store_file(&archive, &dir.join(chunk_store::CHUNK_DIR)).unwrap();
+        std::fs::remove_file(&archive).unwrap();
+
+        let mut snap = make_snapshot(archive.to_str().unwrap());
+        snap.storage = SnapshotStorage::Chunked;
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+        repo_mock
+            .expect_get_chunk_manifest()
+            .returning(move |_| Ok(manifest.clone()));
+
+        let imported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = imported.clone();
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock
+            .expect_import_distro()
+            .returning(move |_, _, path, _, _| {
+                *captured.lock().unwrap() = std::fs::read(path).unwrap();
+                Err(DomainError::WslCliError("stop after import".into()))
+            });
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Clone {
+                    new_name: "Ubuntu-Clone".into(),
+                },
+                install_location: dir.to_string_lossy().to_string(),
+                force: false,
+                secret: None,
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(*imported.lock().unwrap(), original);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_files_restore_extracts_selection_into_running_distro() {
+        let dir = std::env::temp_dir().join("restore_files_mode");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        write_tar(
+            &archive,
+            &[
+                ("home/dev/.ssh/id_ed25519", b"key"),
+                ("home/dev/.bashrc", b"rc"),
+                ("etc/hosts", b"h"),
+            ],
+        );
+
+        let snap = make_snapshot(archive.to_str().unwrap());
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+
+        // Capture the staged tar while it exists: it is removed afterwards.
+        let staged = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = staged.clone();
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_import_distro().never();
+        wsl_mock.expect_unregister_distro().never();
+        wsl_mock
+            .expect_exec_in_distro_as_root()
+            .withf(|name, cmd| name.as_str() == "Ubuntu" && cmd.starts_with("tar -xpf '"))
+            .times(1)
+            .returning(move |_, cmd| {
+                let path = cmd.split('\'').nth(1).unwrap();
+                let mut archive = tar::Archive::new(std::fs::File::open(path).unwrap());
+                for entry in archive.entries().unwrap() {
+                    let entry = entry.unwrap();
+                    captured
+                        .lock()
+                        .unwrap()
+                        .push(entry.path().unwrap().to_string_lossy().into_owned());
+                }
+                Ok(String::new())
+            });
+
+        let mut audit_mock = MockAuditLoggerPort::new();
+        audit_mock
+            .expect_log_with_details()
+            .withf(|action, _, details| action == "snapshot.restore" && details.contains(".ssh"))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(audit_mock),
+        );
+        handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Files {
+                    paths: vec!["/home/dev/.ssh".into()],
+                },
+                install_location: String::new(),
+                force: false,
+                secret: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(*staged.lock().unwrap(), vec!["home/dev/.ssh/id_ed25519"]);
+        assert!(!dir.join("Ubuntu-snap-001.files.tar").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_files_restore_rejects_unknown_paths() {
+        let dir = std::env::temp_dir().join("restore_files_unknown");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        write_tar(&archive, &[("etc/hosts", b"h")]);
+
+        let snap = make_snapshot(archive.to_str().unwrap());
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_exec_in_distro_as_root().never();
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let err = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Files {
+                    paths: vec!["/root/.ssh".into()],
+                },
+                install_location: String::new(),
+                force: false,
+                secret: None,
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("None of the selected paths"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// One-file tar encrypted at `path` under a fresh key; returns the
+    /// stored parameters and the plaintext.
+    fn write_encrypted_tar(
+        path: &std::path::Path,
+        secret: &SnapshotSecret,
+    ) -> (
+        crate::domain::entities::snapshot::SnapshotEncryption,
+        Vec<u8>,
+    ) {
+        let plain = path.with_extension("plain");
+        write_tar(&plain, &[("etc/hostname", b"sealed")]);
+        let bytes = std::fs::read(&plain).unwrap();
+        std::fs::remove_file(&plain).unwrap();
+        let (params, key) = encryption::new_encryption(secret).unwrap();
+        let mut writer =
+            encryption::EncryptWriter::new(&key, std::fs::File::create(path).unwrap()).unwrap();
+        std::io::Write::write_all(&mut writer, &bytes).unwrap();
+        writer.finish().unwrap();
+        (params, bytes)
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_restore_imports_with_the_derived_key() {
+        let dir = std::env::temp_dir().join("restore_encrypted");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        let secret = SnapshotSecret::Passphrase("correct horse".into());
+        let (params, plain) = write_encrypted_tar(&archive, &secret);
+
+        let mut snap = make_snapshot(archive.to_str().unwrap());
+        snap.encryption = Some(params);
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+
+        let imported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let captured = imported.clone();
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock
+            .expect_import_distro()
+            .times(1)
+            .returning(move |_, _, path, _, key| {
+                let key = key.expect("encrypted archives are imported with their key");
+                let mut reader =
+                    encryption::reader(Some(&key), std::fs::File::open(path).unwrap()).unwrap();
+                std::io::Read::read_to_end(&mut reader, &mut captured.lock().unwrap()).unwrap();
+                Err(DomainError::WslCliError("stop after import".into()))
+            });
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(RestoreSnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-001".into()),
+                mode: RestoreMode::Clone {
+                    new_name: "Ubuntu-Clone".into(),
+                },
+                install_location: dir.to_string_lossy().to_string(),
+                force: false,
+                secret: Some(secret),
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(*imported.lock().unwrap(), plain);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_restore_refuses_wrong_or_missing_key() {
+        let dir = std::env::temp_dir().join("restore_encrypted_wrong_key");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("Ubuntu-snap-001.tar");
+        let (params, _) =
+            write_encrypted_tar(&archive, &SnapshotSecret::Passphrase("right".into()));
+
+        let mut snap = make_snapshot(archive.to_str().unwrap());
+        snap.encryption = Some(params);
+        let mut repo_mock = MockSnapshotRepositoryPort::new();
+        repo_mock
+            .expect_get_by_id()
+            .returning(move |_| Ok(snap.clone()));
+        let mut wsl_mock = MockWslManagerPort::new();
+        wsl_mock.expect_import_distro().never();
+
+        let handler = RestoreSnapshotHandler::new(
+            Arc::new(wsl_mock),
+            Arc::new(repo_mock),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let cmd = |secret| RestoreSnapshotCommand {
+            snapshot_id: SnapshotId::from_string("snap-001".into()),
+            mode: RestoreMode::Clone {
+                new_name: "Ubuntu-Clone".into(),
+            },
+            install_location: dir.to_string_lossy().to_string(),
+            force: false,
+            secret,
+        };
+
+        let wrong = handler
+            .handle(cmd(Some(SnapshotSecret::Passphrase("wrong".into()))))
+            .await;
+        assert!(matches!(wrong, Err(DomainError::WrongEncryptionKey(_))));
+        let missing = handler.handle(cmd(None)).await;
+        assert!(matches!(missing, Err(DomainError::EncryptionError(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
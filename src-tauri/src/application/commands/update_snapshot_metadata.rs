@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use crate::application::path_utils::existing_local_path;
+use crate::application::services::sidecar;
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStorage, normalize_tags};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+/// Fields left `None` are unchanged.
+pub struct UpdateSnapshotMetadataCommand {
+    pub snapshot_id: SnapshotId,
+    pub name: Option<String>,
+    /// Notes; an empty string clears them.
+    pub description: Option<String>,
+    /// Replaces the whole tag set.
+    pub tags: Option<Vec<String>>,
+    pub pinned: Option<bool>,
+}
+
+pub struct UpdateSnapshotMetadataHandler {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+}
+
+impl UpdateSnapshotMetadataHandler {
+    pub fn new(
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            snapshot_repo,
+            audit_logger,
+        }
+    }
+
+    /// Rename, annotate, tag or (un)pin a snapshot. The archive is untouched;
+    /// an existing sidecar is rewritten so the change survives a rescan.
+    pub async fn handle(
+        &self,
+        cmd: UpdateSnapshotMetadataCommand,
+    ) -> Result<Snapshot, DomainError> {
+        let mut snapshot = self.snapshot_repo.get_by_id(&cmd.snapshot_id).await?;
+
+        if let Some(name) = cmd.name {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(DomainError::SnapshotError(
+                    "Snapshot name cannot be empty".into(),
+                ));
+            }
+            snapshot.name = name.to_string();
+        }
+        if let Some(description) = cmd.description {
+            let description = description.trim();
+            snapshot.description = (!description.is_empty()).then(|| description.to_string());
+        }
+        if let Some(tags) = cmd.tags {
+            snapshot.tags = normalize_tags(&tags)?;
+        }
+        if let Some(pinned) = cmd.pinned {
+            snapshot.pinned = pinned;
+        }
+
+        self.snapshot_repo.save(&snapshot).await?;
+
+        if existing_local_path(&sidecar::sidecar_path(&snapshot.file_path)).is_some() {
+            let chunks = match snapshot.storage {
+                SnapshotStorage::Chunked => {
+                    self.snapshot_repo.get_chunk_manifest(&snapshot.id).await?
+                }
+                SnapshotStorage::File => Vec::new(),
+            };
+            if let Err(e) = sidecar::write(&snapshot, &chunks) {
+                tracing::warn!("failed to update sidecar of {}: {}", snapshot.id, e);
+            }
+        }
+
+        self.audit_logger
+            .log_with_details(
+                "snapshot.update",
+                &cmd.snapshot_id.to_string(),
+                &format!(
+                    "tags=[{}] pinned={}",
+                    snapshot.tags.join(","),
+                    snapshot.pinned
+                ),
+            )
+            .await?;
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotStatus, SnapshotType};
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use chrono::Utc;
+
+    fn make_snapshot() -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "before upgrade".into(),
+            description: Some("old notes".into()),
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: "/nonexistent/snap-001.tar".into(),
+            file_size: MemorySize::from_bytes(1024),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: vec!["old".into()],
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn command() -> UpdateSnapshotMetadataCommand {
+        UpdateSnapshotMetadataCommand {
+            snapshot_id: SnapshotId::from_string("snap-001".into()),
+            name: None,
+            description: None,
+            tags: None,
+            pinned: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_normalizes_tags_and_pins() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id().returning(|_| Ok(make_snapshot()));
+        repo.expect_save()
+            .withf(|s| {
+                s.tags == vec!["golden", "pre-upgrade"]
+                    && s.pinned
+                    && s.name == "before upgrade"
+                    && s.description.is_none()
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log_with_details()
+            .withf(|action, target, details| {
+                action == "snapshot.update"
+                    && target == "snap-001"
+                    && details == "tags=[golden,pre-upgrade] pinned=true"
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let handler = UpdateSnapshotMetadataHandler::new(Arc::new(repo), Arc::new(audit));
+        let snapshot = handler
+            .handle(UpdateSnapshotMetadataCommand {
+                description: Some("  ".into()),
+                tags: Some(vec!["Pre-Upgrade".into(), "golden".into()]),
+                pinned: Some(true),
+                ..command()
+            })
+            .await
+            .unwrap();
+        assert!(snapshot.pinned);
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_invalid_tag_without_saving() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id().returning(|_| Ok(make_snapshot()));
+        repo.expect_save().never();
+
+        let handler = UpdateSnapshotMetadataHandler::new(
+            Arc::new(repo),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        let result = handler
+            .handle(UpdateSnapshotMetadataCommand {
+                tags: Some(vec!["".into()]),
+                ..command()
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+}
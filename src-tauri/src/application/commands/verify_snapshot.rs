@@ -0,0 +1,312 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+
+use crate::application::services::integrity::{self, Verification};
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStatus};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+/// Set while a verify-all pass runs; passes re-hash every archive, so two
+/// at once (manual + scheduled) would only double the disk load.
+static VERIFY_ALL_RUNNING: AtomicBool = AtomicBool::new(false);
+
+pub struct VerifySnapshotCommand {
+    pub snapshot_id: SnapshotId,
+}
+
+/// Snapshot after verification, with the outcome that shaped its status.
+#[derive(Debug)]
+pub struct VerifySnapshotResult {
+    pub snapshot: Snapshot,
+    pub verification: Verification,
+}
+
+/// Tally of a verify-all pass.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct VerifyAllReport {
+    pub intact: u32,
+    pub corrupted: u32,
+    pub unavailable: u32,
+    /// Legacy snapshots that got their first checksum during this pass.
+    pub recorded: u32,
+    pub errors: u32,
+}
+
+pub struct VerifySnapshotHandler {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+}
+
+impl VerifySnapshotHandler {
+    pub fn new(
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            snapshot_repo,
+            audit_logger,
+        }
+    }
+
+    pub async fn handle(
+        &self,
+        cmd: VerifySnapshotCommand,
+    ) -> Result<VerifySnapshotResult, DomainError> {
+        let mut snapshot = self.snapshot_repo.get_by_id(&cmd.snapshot_id).await?;
+        if !matches!(
+            snapshot.status,
+            SnapshotStatus::Completed | SnapshotStatus::Corrupted
+        ) {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot '{}' is not completed",
+                cmd.snapshot_id
+            )));
+        }
+        let verification = self.verify_one(&mut snapshot).await?;
+        self.audit_logger
+            .log("snapshot.verify", &cmd.snapshot_id.to_string())
+            .await?;
+        Ok(VerifySnapshotResult {
+            snapshot,
+            verification,
+        })
+    }
+
+    /// Re-hash every completed (or previously corrupted) snapshot.
+    /// Fails if another pass is already running.
+    pub async fn verify_all(&self) -> Result<VerifyAllReport, DomainError> {
+        if VERIFY_ALL_RUNNING.swap(true, Ordering::SeqCst) {
+            return Err(DomainError::SnapshotError(
+                "Snapshot verification is already running".into(),
+            ));
+        }
+        let result = self.verify_all_inner().await;
+        VERIFY_ALL_RUNNING.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn verify_all_inner(&self) -> Result<VerifyAllReport, DomainError> {
+        let start = std::time::Instant::now();
+        let mut report = VerifyAllReport::default();
+        for mut snapshot in self.snapshot_repo.list_all().await? {
+            if !matches!(
+                snapshot.status,
+                SnapshotStatus::Completed | SnapshotStatus::Corrupted
+            ) {
+                continue;
+            }
+            match self.verify_one(&mut snapshot).await {
+                Ok(Verification::Intact) => report.intact += 1,
+                Ok(Verification::Unrecorded(_)) => report.recorded += 1,
+                Ok(Verification::Unavailable(_)) => report.unavailable += 1,
+                Ok(Verification::Corrupted(_)) => report.corrupted += 1,
+                Err(e) => {
+                    tracing::warn!("verification of snapshot {} failed: {}", snapshot.id, e);
+                    report.errors += 1;
+                }
+            }
+        }
+        tracing::info!(
+            intact = report.intact,
+            corrupted = report.corrupted,
+            unavailable = report.unavailable,
+            recorded = report.recorded,
+            errors = report.errors,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "snapshot verification pass complete"
+        );
+        Ok(report)
+    }
+
+    /// Verify `snapshot` and persist what changed: a corrupted archive flips
+    /// the status to `Corrupted`, a repaired one back to `Completed`, and a
+    /// legacy snapshot gets its first checksum recorded.
+    async fn verify_one(&self, snapshot: &mut Snapshot) -> Result<Verification, DomainError> {
+        let verification = integrity::verify(self.snapshot_repo.as_ref(), snapshot).await?;
+        match &verification {
+            Verification::Intact => {
+                if matches!(snapshot.status, SnapshotStatus::Corrupted) {
+                    tracing::info!("snapshot {} matches its checksum again", snapshot.id);
+                    snapshot.status = SnapshotStatus::Completed;
+                    self.snapshot_repo.save(snapshot).await?;
+                }
+            }
+            Verification::Unrecorded(actual) => {
+                tracing::info!("recording first checksum of snapshot {}", snapshot.id);
+                snapshot.checksum = Some(actual.clone());
+                self.snapshot_repo.save(snapshot).await?;
+            }
+            Verification::Unavailable(reason) => {
+                tracing::warn!("snapshot {} not verifiable: {}", snapshot.id, reason);
+            }
+            Verification::Corrupted(reason) => {
+                tracing::error!("snapshot {} is corrupted: {}", snapshot.id, reason);
+                if !matches!(snapshot.status, SnapshotStatus::Corrupted) {
+                    snapshot.status = SnapshotStatus::Corrupted;
+                    self.snapshot_repo.save(snapshot).await?;
+                    self.audit_logger
+                        .log("snapshot.corrupted", &snapshot.id.to_string())
+                        .await?;
+                }
+            }
+        }
+        Ok(verification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotStorage, SnapshotType};
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use chrono::Utc;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn make_snapshot(id: &str, file_path: &str, checksum: Option<&str>) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: file_path.to_string(),
+            file_size: MemorySize::from_bytes(5),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: checksum.map(str::to_string),
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn scratch_file(name: &str, content: &[u8]) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_mismatch_marks_snapshot_corrupted() {
+        let path = scratch_file("verify_snapshot_mismatch.tar", b"tampered");
+        let snap = make_snapshot("snap-bad", &path, Some(HELLO_SHA256));
+
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id().returning(move |_| Ok(snap.clone()));
+        repo.expect_save()
+            .withf(|s| matches!(s.status, SnapshotStatus::Corrupted))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, _| action == "snapshot.corrupted" || action == "snapshot.verify")
+            .times(2)
+            .returning(|_, _| Ok(()));
+
+        let handler = VerifySnapshotHandler::new(Arc::new(repo), Arc::new(audit));
+        let result = handler
+            .handle(VerifySnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-bad".into()),
+            })
+            .await
+            .unwrap();
+
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(result.verification, Verification::Corrupted(_)));
+        assert!(matches!(result.snapshot.status, SnapshotStatus::Corrupted));
+    }
+
+    #[tokio::test]
+    async fn test_repaired_snapshot_returns_to_completed() {
+        let path = scratch_file("verify_snapshot_repaired.tar", b"hello");
+        let mut snap = make_snapshot("snap-fixed", &path, Some(HELLO_SHA256));
+        snap.status = SnapshotStatus::Corrupted;
+
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id().returning(move |_| Ok(snap.clone()));
+        repo.expect_save()
+            .withf(|s| matches!(s.status, SnapshotStatus::Completed))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log().returning(|_, _| Ok(()));
+
+        let handler = VerifySnapshotHandler::new(Arc::new(repo), Arc::new(audit));
+        let result = handler
+            .handle(VerifySnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-fixed".into()),
+            })
+            .await
+            .unwrap();
+
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(result.verification, Verification::Intact);
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_in_progress_snapshot() {
+        let mut snap = make_snapshot("snap-busy", "/nonexistent.tar", None);
+        snap.status = SnapshotStatus::InProgress;
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id().returning(move |_| Ok(snap.clone()));
+
+        let handler =
+            VerifySnapshotHandler::new(Arc::new(repo), Arc::new(MockAuditLoggerPort::new()));
+        let result = handler
+            .handle(VerifySnapshotCommand {
+                snapshot_id: SnapshotId::from_string("snap-busy".into()),
+            })
+            .await;
+        assert!(result.unwrap_err().to_string().contains("not completed"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_all_tallies_outcomes() {
+        let good = scratch_file("verify_all_good.tar", b"hello");
+        let legacy = scratch_file("verify_all_legacy.tar", b"hello");
+        let mut failed = make_snapshot("failed", "/nonexistent-failed.tar", None);
+        failed.status = SnapshotStatus::Failed("boom".into());
+        let snapshots = vec![
+            make_snapshot("good", &good, Some(HELLO_SHA256)),
+            make_snapshot("legacy", &legacy, None),
+            make_snapshot(
+                "gone",
+                "/nonexistent/verify_all_gone.tar",
+                Some(HELLO_SHA256),
+            ),
+            failed,
+        ];
+
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_list_all()
+            .returning(move || Ok(snapshots.clone()));
+        repo.expect_save()
+            .withf(|s| s.id.as_str() == "legacy" && s.checksum.as_deref() == Some(HELLO_SHA256))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let handler =
+            VerifySnapshotHandler::new(Arc::new(repo), Arc::new(MockAuditLoggerPort::new()));
+        let report = handler.verify_all().await.unwrap();
+
+        let _ = std::fs::remove_file(&good);
+        let _ = std::fs::remove_file(&legacy);
+        assert_eq!(report.intact, 1);
+        assert_eq!(report.recorded, 1);
+        assert_eq!(report.unavailable, 1);
+        assert_eq!(report.corrupted, 0);
+        assert_eq!(report.errors, 0);
+    }
+}
@@ -1,5 +1,16 @@
+use crate::application::commands::rescan_snapshots::RescanReport;
+use crate::application::commands::verify_snapshot::VerifySnapshotResult;
+use crate::application::services::integrity::Verification;
+use crate::application::services::snapshot_files::TreeNode;
+use crate::application::services::tar_diff::{EntryInfo, ModifiedEntry, TarDiff};
 use crate::domain::entities::distro::Distro;
+use crate::domain::entities::distro_template::{DistroTemplate, TemplateParameter};
+use crate::domain::entities::replication::{
+    ReplicationStatus, ReplicationTarget, SnapshotReplication,
+};
+use crate::domain::entities::safety_snapshot::SafetySnapshotPolicy;
 use crate::domain::entities::snapshot::Snapshot;
+use crate::domain::entities::snapshot_schedule::SnapshotSchedule;
 use crate::domain::entities::wsl_config::WslDistroConfig;
 use serde::{Deserialize, Serialize};
 
@@ -53,6 +64,12 @@ pub struct SnapshotResponse {
     pub created_at: String,
     pub status: String,
     pub default_user: Option<String>,
+    pub storage: String,
+    pub checksum: Option<String>,
+    pub tags: Vec<String>,
+    pub pinned: bool,
+    /// "passphrase" or "keyfile" for encrypted snapshots, `None` otherwise.
+    pub encryption: Option<String>,
 }
 
 impl From<Snapshot> for SnapshotResponse {
@@ -65,6 +82,7 @@ impl From<Snapshot> for SnapshotResponse {
             crate::domain::entities::snapshot::SnapshotStatus::Failed(reason) => {
                 format!("failed: {}", reason)
             }
+            crate::domain::entities::snapshot::SnapshotStatus::Corrupted => "corrupted".to_string(),
         };
         let snapshot_type = match s.snapshot_type {
             crate::domain::entities::snapshot::SnapshotType::Full => "full".to_string(),
@@ -85,6 +103,359 @@ impl From<Snapshot> for SnapshotResponse {
             created_at: s.created_at.to_rfc3339(),
             status,
             default_user: s.default_user,
+            storage: s.storage.as_str().to_string(),
+            checksum: s.checksum,
+            tags: s.tags,
+            pinned: s.pinned,
+            encryption: s.encryption.map(|e| e.key_source.as_str().to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotScheduleResponse {
+    pub id: String,
+    pub distro_name: String,
+    /// "hourly", "daily" or "weekly"
+    pub frequency: String,
+    pub format: String,
+    pub storage: String,
+    pub output_dir: String,
+    pub keep_hourly: u32,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+    pub enabled: bool,
+    pub last_run_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<SnapshotSchedule> for SnapshotScheduleResponse {
+    fn from(s: SnapshotSchedule) -> Self {
+        Self {
+            id: s.id,
+            distro_name: s.distro_name.to_string(),
+            frequency: s.frequency.as_str().to_string(),
+            format: s.format.extension().to_string(),
+            storage: s.storage.as_str().to_string(),
+            output_dir: s.output_dir,
+            keep_hourly: s.retention.keep_hourly,
+            keep_daily: s.retention.keep_daily,
+            keep_weekly: s.retention.keep_weekly,
+            enabled: s.enabled,
+            last_run_at: s.last_run_at.map(|t| t.to_rfc3339()),
+            created_at: s.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Safety snapshot policy of one distro.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyPolicyResponse {
+    pub distro_name: String,
+    /// "delete_distro", "restore_overwrite", "resize_vhd" and/or "compact_vhdx"
+    pub operations: Vec<String>,
+    pub format: String,
+    pub output_dir: String,
+    pub keep_last: u32,
+    pub max_age_hours: u32,
+    pub updated_at: String,
+}
+
+impl From<SafetySnapshotPolicy> for SafetyPolicyResponse {
+    fn from(p: SafetySnapshotPolicy) -> Self {
+        Self {
+            distro_name: p.distro_name.to_string(),
+            operations: p
+                .operations
+                .iter()
+                .map(|op| op.as_str().to_string())
+                .collect(),
+            format: p.format.extension().to_string(),
+            output_dir: p.output_dir,
+            keep_last: p.keep_last,
+            max_age_hours: p.max_age_hours,
+            updated_at: p.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Template new distros are provisioned from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistroTemplateResponse {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub snapshot_id: String,
+    pub script: String,
+    pub parameters: Vec<TemplateParameter>,
+    pub wsl_config: WslDistroConfig,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<DistroTemplate> for DistroTemplateResponse {
+    fn from(t: DistroTemplate) -> Self {
+        Self {
+            id: t.id,
+            name: t.name,
+            description: t.description,
+            snapshot_id: t.snapshot_id.to_string(),
+            script: t.script,
+            parameters: t.parameters,
+            wsl_config: t.wsl_config,
+            created_at: t.created_at.to_rfc3339(),
+            updated_at: t.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Replication target as shown to the UI; the secret key never leaves the backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationTargetResponse {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub prefix: String,
+    pub access_key_id: String,
+    pub has_secret: bool,
+    pub path_style: bool,
+    pub auto_replicate: bool,
+}
+
+impl From<ReplicationTarget> for ReplicationTargetResponse {
+    fn from(t: ReplicationTarget) -> Self {
+        Self {
+            has_secret: !t.secret_access_key.is_empty(),
+            endpoint: t.endpoint,
+            region: t.region,
+            bucket: t.bucket,
+            prefix: t.prefix,
+            access_key_id: t.access_key_id,
+            path_style: t.path_style,
+            auto_replicate: t.auto_replicate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotReplicationResponse {
+    pub snapshot_id: String,
+    pub object_key: String,
+    /// "uploading", "replicated" or "failed"
+    pub status: String,
+    pub error: Option<String>,
+    pub bytes_uploaded: u64,
+    pub total_bytes: u64,
+    pub updated_at: String,
+}
+
+impl From<SnapshotReplication> for SnapshotReplicationResponse {
+    fn from(r: SnapshotReplication) -> Self {
+        let (status, error) = match r.status {
+            ReplicationStatus::Uploading => ("uploading", None),
+            ReplicationStatus::Replicated => ("replicated", None),
+            ReplicationStatus::Failed(reason) => ("failed", Some(reason)),
+        };
+        Self {
+            snapshot_id: r.snapshot_id.to_string(),
+            object_key: r.object_key,
+            status: status.to_string(),
+            error,
+            bytes_uploaded: r.bytes_uploaded,
+            total_bytes: r.total_bytes,
+            updated_at: r.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Result of `verify_snapshot`: the (possibly re-flagged) snapshot and what
+/// the re-hash found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifySnapshotResponse {
+    pub snapshot: SnapshotResponse,
+    /// "intact", "recorded", "unavailable" or "corrupted"
+    pub outcome: String,
+    pub detail: Option<String>,
+}
+
+impl From<VerifySnapshotResult> for VerifySnapshotResponse {
+    fn from(r: VerifySnapshotResult) -> Self {
+        let (outcome, detail) = match r.verification {
+            Verification::Intact => ("intact", None),
+            Verification::Unrecorded(_) => ("recorded", None),
+            Verification::Unavailable(reason) => ("unavailable", Some(reason)),
+            Verification::Corrupted(reason) => ("corrupted", Some(reason)),
+        };
+        Self {
+            snapshot: SnapshotResponse::from(r.snapshot),
+            outcome: outcome.to_string(),
+            detail,
+        }
+    }
+}
+
+/// One check of `preflight_restore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightCheckResponse {
+    /// "status", "archive", "encryption", "format", "target",
+    /// "install_location", "running", "disk_space" or "default_user"
+    pub check: String,
+    /// "pass", "warn" or "fail"
+    pub status: String,
+    pub message: String,
+}
+
+/// Result of `preflight_restore`: what a restore with the same arguments
+/// would run into, found without touching anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestorePreflightResponse {
+    pub snapshot_id: String,
+    /// "clone", "overwrite" or "files"
+    pub mode: String,
+    pub target_name: String,
+    /// Where the import would go (the registry path for overwrites).
+    pub install_location: String,
+    /// No check failed.
+    pub ready: bool,
+    pub archive_bytes: u64,
+    /// Estimated space the restore needs at `install_location`.
+    pub required_bytes: Option<u64>,
+    pub available_bytes: Option<u64>,
+    /// User set as default after the import, if known.
+    pub default_user: Option<String>,
+    /// Running distros the restore stops (`wsl --shutdown`).
+    pub stopped_distros: Vec<String>,
+    pub checks: Vec<PreflightCheckResponse>,
+}
+
+/// Result of `rescan_snapshots`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RescanReportResponse {
+    pub imported: Vec<SnapshotResponse>,
+    pub relocated: Vec<SnapshotResponse>,
+    /// Catalog entries whose archive is missing on this machine.
+    pub missing: Vec<SnapshotResponse>,
+    /// Archive files no catalog entry describes.
+    pub orphan_files: Vec<String>,
+    pub sidecars_written: u32,
+}
+
+impl From<RescanReport> for RescanReportResponse {
+    fn from(r: RescanReport) -> Self {
+        let map = |v: Vec<Snapshot>| v.into_iter().map(SnapshotResponse::from).collect();
+        Self {
+            imported: map(r.imported),
+            relocated: map(r.relocated),
+            missing: map(r.missing),
+            orphan_files: r.orphan_files,
+            sidecars_written: r.sidecars_written,
+        }
+    }
+}
+
+/// One path of a snapshot, as shown in diffs and content trees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntryResponse {
+    pub path: String,
+    /// "file", "directory", "symlink", "hardlink" or "other"
+    pub kind: String,
+    pub size_bytes: u64,
+    pub mode: u32,
+    pub uid: u64,
+    pub gid: u64,
+    pub user: Option<String>,
+    pub group: Option<String>,
+    pub link_target: Option<String>,
+}
+
+impl From<EntryInfo> for SnapshotEntryResponse {
+    fn from(e: EntryInfo) -> Self {
+        Self {
+            path: e.path,
+            kind: e.kind.as_str().to_string(),
+            size_bytes: e.size,
+            mode: e.mode,
+            uid: e.uid,
+            gid: e.gid,
+            user: e.user,
+            group: e.group,
+            link_target: e.link_target,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedEntryResponse {
+    pub path: String,
+    pub before: SnapshotEntryResponse,
+    pub after: SnapshotEntryResponse,
+    pub content_changed: bool,
+    pub mode_changed: bool,
+    pub owner_changed: bool,
+}
+
+impl From<ModifiedEntry> for ModifiedEntryResponse {
+    fn from(m: ModifiedEntry) -> Self {
+        Self {
+            path: m.after.path.clone(),
+            before: m.before.into(),
+            after: m.after.into(),
+            content_changed: m.content_changed,
+            mode_changed: m.mode_changed,
+            owner_changed: m.owner_changed,
+        }
+    }
+}
+
+/// Result of `diff_snapshots`: what changed going from `from_id` to `to_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDiffResponse {
+    pub from_id: String,
+    pub to_id: String,
+    pub distro_name: String,
+    pub added: Vec<SnapshotEntryResponse>,
+    pub removed: Vec<SnapshotEntryResponse>,
+    pub modified: Vec<ModifiedEntryResponse>,
+    pub unchanged: u64,
+}
+
+impl SnapshotDiffResponse {
+    pub fn new(from: &Snapshot, to: &Snapshot, diff: TarDiff) -> Self {
+        let map = |v: Vec<EntryInfo>| v.into_iter().map(SnapshotEntryResponse::from).collect();
+        Self {
+            from_id: from.id.to_string(),
+            to_id: to.id.to_string(),
+            distro_name: to.distro_name.to_string(),
+            added: map(diff.added),
+            removed: map(diff.removed),
+            modified: diff
+                .modified
+                .into_iter()
+                .map(ModifiedEntryResponse::from)
+                .collect(),
+            unchanged: diff.unchanged,
+        }
+    }
+}
+
+/// Result of `list_snapshot_contents`: a directory tree of a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotTreeNodeResponse {
+    pub name: String,
+    #[serde(flatten)]
+    pub entry: SnapshotEntryResponse,
+    pub children: Vec<SnapshotTreeNodeResponse>,
+    /// Children were omitted because of the requested depth.
+    pub truncated: bool,
+}
+
+impl From<TreeNode> for SnapshotTreeNodeResponse {
+    fn from(n: TreeNode) -> Self {
+        Self {
+            name: n.name,
+            entry: n.info.into(),
+            children: n.children.into_iter().map(Self::from).collect(),
+            truncated: n.truncated,
         }
     }
 }
@@ -92,7 +463,9 @@ impl From<Snapshot> for SnapshotResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::snapshot::{ExportFormat, Snapshot, SnapshotStatus, SnapshotType};
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
     use crate::domain::value_objects::distro_name::DistroName;
     use crate::domain::value_objects::distro_state::DistroState;
     use crate::domain::value_objects::memory_size::MemorySize;
@@ -125,6 +498,11 @@ mod tests {
             created_at: Utc::now(),
             status,
             default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
         }
     }
 
@@ -246,4 +624,58 @@ mod tests {
         let resp = SnapshotResponse::from(snap);
         assert!(resp.parent_id.is_none());
     }
+
+    #[test]
+    fn test_snapshot_response_storage() {
+        let mut snap = make_test_snapshot(SnapshotStatus::Completed, SnapshotType::Full);
+        assert_eq!(SnapshotResponse::from(snap.clone()).storage, "file");
+        snap.storage = SnapshotStorage::Chunked;
+        assert_eq!(SnapshotResponse::from(snap).storage, "chunked");
+    }
+
+    #[test]
+    fn test_snapshot_response_corrupted_with_checksum() {
+        let mut snap = make_test_snapshot(SnapshotStatus::Corrupted, SnapshotType::Full);
+        snap.checksum = Some("abc123".into());
+        let resp = SnapshotResponse::from(snap);
+        assert_eq!(resp.status, "corrupted");
+        assert_eq!(resp.checksum.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_snapshot_schedule_response() {
+        use crate::domain::entities::snapshot_schedule::{RetentionPolicy, ScheduleFrequency};
+        let resp = SnapshotScheduleResponse::from(SnapshotSchedule {
+            id: "sched-1".into(),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            frequency: ScheduleFrequency::Weekly,
+            format: ExportFormat::TarZst,
+            storage: SnapshotStorage::Chunked,
+            output_dir: "/backups".into(),
+            retention: RetentionPolicy {
+                keep_hourly: 24,
+                keep_daily: 7,
+                keep_weekly: 4,
+            },
+            enabled: true,
+            last_run_at: None,
+            created_at: Utc::now(),
+        });
+        assert_eq!(resp.frequency, "weekly");
+        assert_eq!(resp.format, "tar.zst");
+        assert_eq!(resp.storage, "chunked");
+        assert_eq!(resp.keep_daily, 7);
+        assert!(resp.last_run_at.is_none());
+    }
+
+    #[test]
+    fn test_verify_snapshot_response_outcome() {
+        let resp = VerifySnapshotResponse::from(VerifySnapshotResult {
+            snapshot: make_test_snapshot(SnapshotStatus::Corrupted, SnapshotType::Full),
+            verification: Verification::Corrupted("Checksum mismatch".into()),
+        });
+        assert_eq!(resp.outcome, "corrupted");
+        assert_eq!(resp.detail.as_deref(), Some("Checksum mismatch"));
+        assert_eq!(resp.snapshot.status, "corrupted");
+    }
 }
@@ -13,6 +13,30 @@ pub fn windows_to_linux_path(path: &str) -> String {
     path.to_string()
 }
 
+/// Return the path under which `path` exists on this machine: the stored path
+/// itself, or its Windows→Linux conversion. `None` if neither exists.
+pub fn existing_local_path(path: &str) -> Option<String> {
+    if std::path::Path::new(path).exists() {
+        return Some(path.to_string());
+    }
+    let linux = windows_to_linux_path(path);
+    (linux != path && std::path::Path::new(&linux).exists()).then_some(linux)
+}
+
+/// Free bytes on the volume that holds `path`, read at its nearest existing
+/// ancestor (the install directory of a clone may not exist yet). `None` when
+/// no ancestor exists on this machine.
+pub fn available_space(path: &str) -> Option<u64> {
+    let linux = windows_to_linux_path(path);
+    [path, linux.as_str()].into_iter().find_map(|candidate| {
+        std::path::Path::new(candidate)
+            .ancestors()
+            .filter(|p| !p.as_os_str().is_empty())
+            .find(|p| p.exists())
+            .and_then(|p| fs4::available_space(p).ok())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +69,26 @@ mod tests {
     fn test_passthrough_relative_path() {
         assert_eq!(windows_to_linux_path("snap.tar"), "snap.tar");
     }
+
+    #[test]
+    fn test_existing_local_path_finds_stored_path() {
+        let tmp = std::env::temp_dir().join("path_utils_existing.tar");
+        std::fs::write(&tmp, b"x").unwrap();
+        let p = tmp.to_string_lossy().to_string();
+        assert_eq!(existing_local_path(&p), Some(p.clone()));
+        let _ = std::fs::remove_file(&tmp);
+    }
+
+    #[test]
+    fn test_existing_local_path_missing_returns_none() {
+        assert_eq!(existing_local_path(r"Q:\nonexistent\snap.tar"), None);
+        assert_eq!(existing_local_path("/nonexistent/snap.tar"), None);
+    }
+
+    #[test]
+    fn test_available_space_uses_nearest_existing_ancestor() {
+        let missing = std::env::temp_dir().join("path_utils_space").join("new");
+        let space = available_space(&missing.to_string_lossy());
+        assert!(space.is_some_and(|b| b > 0));
+    }
 }
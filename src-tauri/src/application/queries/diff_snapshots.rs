@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use crate::application::dto::responses::SnapshotDiffResponse;
+use crate::application::services::encryption::{self, SnapshotSecret};
+use crate::application::services::{snapshot_reader, tar_diff};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+pub struct DiffSnapshotsHandler {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+}
+
+impl DiffSnapshotsHandler {
+    pub fn new(snapshot_repo: Arc<dyn SnapshotRepositoryPort>) -> Self {
+        Self { snapshot_repo }
+    }
+
+    /// Compare the filesystems of two tar snapshots of the same distro by
+    /// streaming their archives (incremental chains, chunked and compressed
+    /// snapshots included). `secret` unlocks whichever of them is encrypted.
+    pub async fn handle(
+        &self,
+        from_id: SnapshotId,
+        to_id: SnapshotId,
+        secret: Option<SnapshotSecret>,
+    ) -> Result<SnapshotDiffResponse, DomainError> {
+        let from = self.snapshot_repo.get_by_id(&from_id).await?;
+        let to = self.snapshot_repo.get_by_id(&to_id).await?;
+        if from.distro_name != to.distro_name {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshots '{}' ({}) and '{}' ({}) belong to different distributions",
+                from.id, from.distro_name, to.id, to.distro_name
+            )));
+        }
+
+        let from_key = encryption::unlock_snapshot(&from, secret.as_ref()).await?;
+        let to_key = encryption::unlock_snapshot(&to, secret.as_ref()).await?;
+        let from_layers =
+            snapshot_reader::resolve_layers(self.snapshot_repo.as_ref(), &from, from_key.as_ref())
+                .await?;
+        let to_layers =
+            snapshot_reader::resolve_layers(self.snapshot_repo.as_ref(), &to, to_key.as_ref())
+                .await?;
+        let start = std::time::Instant::now();
+        let diff =
+            tokio::task::spawn_blocking(move || tar_diff::diff_layers(&from_layers, &to_layers))
+                .await
+                .map_err(|e| DomainError::Internal(format!("diff task failed: {}", e)))??;
+        tracing::info!(
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            added = diff.added.len(),
+            removed = diff.removed.len(),
+            modified = diff.modified.len(),
+            "diffed snapshots {} -> {}",
+            from.id,
+            to.id
+        );
+
+        Ok(SnapshotDiffResponse::new(&from, &to, diff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::chunk_store;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use chrono::Utc;
+    use std::path::{Path, PathBuf};
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("diff_snapshots_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_tar(path: &Path, files: &[(&str, &[u8])]) {
+        let mut builder = tar::Builder::new(std::fs::File::create(path).unwrap());
+        for (name, data) in files {
+            let mut header = tar::Header::new_ustar();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap();
+    }
+
+    fn make_snapshot(id: &str, distro: &str, file_path: &Path) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new(distro).unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn repo_with(snapshots: Vec<Snapshot>) -> MockSnapshotRepositoryPort {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        let all = snapshots.clone();
+        repo.expect_get_by_id().returning(move |id| {
+            all.iter()
+                .find(|s| &s.id == id)
+                .cloned()
+                .ok_or_else(|| DomainError::SnapshotNotFound(id.to_string()))
+        });
+        repo
+    }
+
+    #[tokio::test]
+    async fn test_diff_file_and_chunked_snapshots() {
+        let dir = scratch_dir("chunked");
+        let good = dir.join("good.tar");
+        write_tar(&good, &[("etc/fstab", b"ok"), ("etc/old", b"old")]);
+        let broken = dir.join("broken.tar");
+        write_tar(&broken, &[("etc/fstab", b"broken"), ("etc/new", b"new")]);
+        let manifest = chunk_store::store_file(&broken, &dir.join(chunk_store::CHUNK_DIR)).unwrap();
+        std::fs::remove_file(&broken).unwrap();
+
+        let mut chunked = make_snapshot("broken", "Ubuntu", &broken);
+        chunked.storage = SnapshotStorage::Chunked;
+        let mut repo = repo_with(vec![make_snapshot("good", "Ubuntu", &good), chunked]);
+        repo.expect_get_chunk_manifest()
+            .returning(move |_| Ok(manifest.clone()));
+
+        let handler = DiffSnapshotsHandler::new(Arc::new(repo));
+        let diff = handler
+            .handle(
+                SnapshotId::from_string("good".into()),
+                SnapshotId::from_string("broken".into()),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(diff.distro_name, "Ubuntu");
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].path, "etc/new");
+        assert_eq!(diff.removed[0].path, "etc/old");
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].path, "etc/fstab");
+        assert!(diff.modified[0].content_changed);
+        assert_eq!(diff.modified[0].after.size_bytes, 6);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_diff_rejects_different_distros() {
+        let repo = repo_with(vec![
+            make_snapshot("a", "Ubuntu", Path::new("/tmp/a.tar")),
+            make_snapshot("b", "Debian", Path::new("/tmp/b.tar")),
+        ]);
+        let handler = DiffSnapshotsHandler::new(Arc::new(repo));
+        let err = handler
+            .handle(
+                SnapshotId::from_string("a".into()),
+                SnapshotId::from_string("b".into()),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("different distributions"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_rejects_vhdx_snapshot() {
+        let dir = scratch_dir("vhdx");
+        let tar = dir.join("a.tar");
+        write_tar(&tar, &[("etc/hosts", b"x")]);
+        let mut vhdx = make_snapshot("b", "Ubuntu", &dir.join("b.vhdx"));
+        vhdx.format = ExportFormat::Vhd;
+        let repo = repo_with(vec![make_snapshot("a", "Ubuntu", &tar), vhdx]);
+
+        let handler = DiffSnapshotsHandler::new(Arc::new(repo));
+        let err = handler
+            .handle(
+                SnapshotId::from_string("a".into()),
+                SnapshotId::from_string("b".into()),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("VHDX"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use crate::application::dto::responses::SnapshotTreeNodeResponse;
+use crate::application::services::encryption::{self, SnapshotSecret};
+use crate::application::services::{snapshot_files, snapshot_reader};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+pub struct ListSnapshotContentsHandler {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+}
+
+impl ListSnapshotContentsHandler {
+    pub fn new(snapshot_repo: Arc<dyn SnapshotRepositoryPort>) -> Self {
+        Self { snapshot_repo }
+    }
+
+    /// Directory tree of a tar snapshot below `path` (the whole filesystem
+    /// when `None`), limited to `depth` levels when given. `secret` unlocks
+    /// an encrypted snapshot.
+    pub async fn handle(
+        &self,
+        snapshot_id: SnapshotId,
+        path: Option<String>,
+        depth: Option<u32>,
+        secret: Option<SnapshotSecret>,
+    ) -> Result<SnapshotTreeNodeResponse, DomainError> {
+        let root = snapshot_files::normalize_path(path.as_deref().unwrap_or("/"))?;
+        let snapshot = self.snapshot_repo.get_by_id(&snapshot_id).await?;
+        let key = encryption::unlock_snapshot(&snapshot, secret.as_ref()).await?;
+        let layers =
+            snapshot_reader::resolve_layers(self.snapshot_repo.as_ref(), &snapshot, key.as_ref())
+                .await?;
+        let tree =
+            tokio::task::spawn_blocking(move || snapshot_files::list_tree(&layers, &root, depth))
+                .await
+                .map_err(|e| DomainError::Internal(format!("listing task failed: {}", e)))??;
+        Ok(tree.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use chrono::Utc;
+
+    fn make_snapshot(file_path: &std::path::Path) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "test".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::TarGz,
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lists_compressed_snapshot_subtree() {
+        let dir = std::env::temp_dir().join("list_snapshot_contents");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snap.tar.gz");
+        let encoder = crate::application::services::compression::Encoder::new(
+            &ExportFormat::TarGz,
+            std::fs::File::create(&path).unwrap(),
+        )
+        .unwrap();
+        let mut builder = tar::Builder::new(encoder);
+        for (name, data) in [
+            ("home/dev/.ssh/id_ed25519", b"key".as_slice()),
+            ("etc/hosts", b"h"),
+        ] {
+            let mut header = tar::Header::new_ustar();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o600);
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let snapshot = make_snapshot(&path);
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id()
+            .returning(move |_| Ok(snapshot.clone()));
+
+        let handler = ListSnapshotContentsHandler::new(Arc::new(repo));
+        let tree = handler
+            .handle(
+                SnapshotId::from_string("snap-001".into()),
+                Some("/home/dev/".into()),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(tree.name, "dev");
+        assert_eq!(tree.entry.kind, "directory");
+        let ssh = &tree.children[0];
+        assert_eq!(ssh.name, ".ssh");
+        assert_eq!(ssh.children[0].entry.path, "home/dev/.ssh/id_ed25519");
+        assert_eq!(ssh.children[0].entry.mode, 0o600);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_parent_components() {
+        let handler = ListSnapshotContentsHandler::new(Arc::new(MockSnapshotRepositoryPort::new()));
+        let result = handler
+            .handle(
+                SnapshotId::from_string("snap-001".into()),
+                Some("/home/../etc".into()),
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+}
@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
 use crate::application::dto::responses::SnapshotResponse;
+use crate::domain::entities::snapshot::normalize_tags;
 use crate::domain::errors::DomainError;
-use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::ports::snapshot_repository::{SnapshotFilter, SnapshotRepositoryPort};
 use crate::domain::value_objects::DistroName;
 
 pub struct ListSnapshotsHandler {
@@ -24,12 +25,37 @@ impl ListSnapshotsHandler {
         };
         Ok(snapshots.into_iter().map(SnapshotResponse::from).collect())
     }
+
+    /// Snapshots matching `filter`, newest first. Tags are matched
+    /// case-insensitively; a snapshot must carry all of them.
+    pub async fn handle_filtered(
+        &self,
+        mut filter: SnapshotFilter,
+    ) -> Result<Vec<SnapshotResponse>, DomainError> {
+        filter.tags = normalize_tags(&filter.tags)?;
+        if let (Some(after), Some(before)) = (filter.created_after, filter.created_before)
+            && after > before
+        {
+            return Err(DomainError::SnapshotError(
+                "Invalid date range: start is after end".into(),
+            ));
+        }
+        let snapshots = self.snapshot_repo.list_filtered(&filter).await?;
+        Ok(snapshots.into_iter().map(SnapshotResponse::from).collect())
+    }
+
+    /// Every tag in use, sorted (for filter suggestions).
+    pub async fn tags(&self) -> Result<Vec<String>, DomainError> {
+        self.snapshot_repo.list_tags().await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::snapshot::{ExportFormat, Snapshot, SnapshotStatus, SnapshotType};
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
     use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
     use crate::domain::value_objects::{MemorySize, SnapshotId};
     use chrono::Utc;
@@ -48,6 +74,11 @@ mod tests {
             created_at: Utc::now(),
             status: SnapshotStatus::Completed,
             default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
         }
     }
 
@@ -85,4 +116,44 @@ mod tests {
         assert_eq!(result[0].status, "completed");
         assert_eq!(result[0].snapshot_type, "full");
     }
+
+    #[tokio::test]
+    async fn test_handle_filtered_normalizes_tags() {
+        let mut mock = MockSnapshotRepositoryPort::new();
+        mock.expect_list_filtered()
+            .withf(|f| f.tags == vec!["golden"] && f.min_size_bytes == Some(512))
+            .times(1)
+            .returning(|_| {
+                let mut snap = make_snapshot("Ubuntu");
+                snap.tags = vec!["golden".into()];
+                snap.pinned = true;
+                Ok(vec![snap])
+            });
+
+        let handler = ListSnapshotsHandler::new(Arc::new(mock));
+        let result = handler
+            .handle_filtered(SnapshotFilter {
+                tags: vec![" Golden".into()],
+                min_size_bytes: Some(512),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(result[0].tags, vec!["golden"]);
+        assert!(result[0].pinned);
+    }
+
+    #[tokio::test]
+    async fn test_handle_filtered_rejects_inverted_date_range() {
+        let handler = ListSnapshotsHandler::new(Arc::new(MockSnapshotRepositoryPort::new()));
+        let now = Utc::now();
+        let result = handler
+            .handle_filtered(SnapshotFilter {
+                created_after: Some(now),
+                created_before: Some(now - chrono::Duration::days(1)),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
 }
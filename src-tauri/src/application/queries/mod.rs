@@ -1,3 +1,6 @@
+pub mod diff_snapshots;
 pub mod get_distro_details;
 pub mod list_distros;
+pub mod list_snapshot_contents;
 pub mod list_snapshots;
+pub mod preflight_restore;
@@ -0,0 +1,880 @@
+use std::sync::Arc;
+
+use crate::application::dto::responses::{PreflightCheckResponse, RestorePreflightResponse};
+use crate::application::path_utils::{available_space, existing_local_path};
+use crate::application::services::encryption::{self, SnapshotSecret};
+use crate::application::services::replication::SnapshotReplicator;
+use crate::application::services::{chunk_store, compression, incremental};
+use crate::domain::entities::distro::Distro;
+use crate::domain::entities::snapshot::{
+    ExportFormat, RestoreMode, Snapshot, SnapshotStatus, SnapshotStorage,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::ports::wsl_manager::WslManagerPort;
+use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
+
+/// Oldest WSL release whose `wsl --import` accepts `--vhd`.
+const MIN_VHD_IMPORT_VERSION: (u32, u32) = (0, 58);
+
+/// Arguments of the restore to check, as given to `restore_snapshot`.
+pub struct PreflightRestoreQuery {
+    pub snapshot_id: SnapshotId,
+    pub mode: RestoreMode,
+    pub install_location: String,
+    pub force: bool,
+    pub secret: Option<SnapshotSecret>,
+}
+
+/// Checks collected in the order they ran.
+#[derive(Default)]
+struct Checks(Vec<PreflightCheckResponse>);
+
+impl Checks {
+    fn push(&mut self, check: &str, status: &str, message: impl Into<String>) {
+        self.0.push(PreflightCheckResponse {
+            check: check.into(),
+            status: status.into(),
+            message: message.into(),
+        });
+    }
+
+    fn pass(&mut self, check: &str, message: impl Into<String>) {
+        self.push(check, "pass", message);
+    }
+
+    fn warn(&mut self, check: &str, message: impl Into<String>) {
+        self.push(check, "warn", message);
+    }
+
+    fn fail(&mut self, check: &str, message: impl Into<String>) {
+        self.push(check, "fail", message);
+    }
+
+    fn ready(&self) -> bool {
+        self.0.iter().all(|c| c.status != "fail")
+    }
+}
+
+/// Leading "major.minor" of a version string such as "2.0.14.0".
+fn parse_major_minor(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    Some((major, minor))
+}
+
+fn human(bytes: u64) -> String {
+    MemorySize::from_bytes(bytes).to_string()
+}
+
+/// Dry run of `restore_snapshot`: reports what a restore with the same
+/// arguments would run into without fetching, stopping or writing anything.
+pub struct PreflightRestoreHandler {
+    wsl_manager: Arc<dyn WslManagerPort>,
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    replicator: Option<Arc<SnapshotReplicator>>,
+}
+
+impl PreflightRestoreHandler {
+    pub fn new(
+        wsl_manager: Arc<dyn WslManagerPort>,
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    ) -> Self {
+        Self {
+            wsl_manager,
+            snapshot_repo,
+            replicator: None,
+        }
+    }
+
+    /// Accept archives that are missing locally but can be fetched back from
+    /// their replica.
+    pub fn with_replicator(mut self, replicator: Arc<SnapshotReplicator>) -> Self {
+        self.replicator = Some(replicator);
+        self
+    }
+
+    #[tracing::instrument(
+        skip(self, query),
+        fields(snapshot_id = %query.snapshot_id, mode = ?query.mode)
+    )]
+    pub async fn handle(
+        &self,
+        query: PreflightRestoreQuery,
+    ) -> Result<RestorePreflightResponse, DomainError> {
+        let snapshot = self.snapshot_repo.get_by_id(&query.snapshot_id).await?;
+        let distros = self.wsl_manager.list_distros().await?;
+        let mut checks = Checks::default();
+
+        check_status(&snapshot, query.force, &mut checks);
+        let archive_bytes = self.check_archive(&snapshot, &mut checks).await?;
+        check_encryption(&snapshot, query.secret.as_ref(), &mut checks).await;
+        self.check_format(&snapshot, &query.mode, &mut checks).await;
+        let (target_name, target) = check_target(&snapshot, &query.mode, &distros, &mut checks);
+        let install_location = self
+            .check_install_location(&query, target, &mut checks)
+            .await;
+        let stopped_distros = check_running(&query.mode, &target_name, &distros, &mut checks);
+
+        let (required_bytes, available_bytes) = if matches!(query.mode, RestoreMode::Files { .. }) {
+            (None, None)
+        } else {
+            check_disk_space(
+                &snapshot,
+                &query.mode,
+                &install_location,
+                archive_bytes,
+                &mut checks,
+            )
+        };
+
+        if !matches!(query.mode, RestoreMode::Files { .. }) {
+            match &snapshot.default_user {
+                Some(user) => checks.pass(
+                    "default_user",
+                    format!("'{}' is set as the default user after the import", user),
+                ),
+                None => checks.warn(
+                    "default_user",
+                    "No default user recorded; it is detected after the import and falls back to root",
+                ),
+            }
+        }
+
+        Ok(RestorePreflightResponse {
+            snapshot_id: snapshot.id.to_string(),
+            mode: match query.mode {
+                RestoreMode::Clone { .. } => "clone",
+                RestoreMode::Overwrite => "overwrite",
+                RestoreMode::Files { .. } => "files",
+            }
+            .into(),
+            target_name,
+            install_location,
+            ready: checks.ready(),
+            archive_bytes,
+            required_bytes,
+            available_bytes,
+            default_user: snapshot.default_user.clone(),
+            stopped_distros,
+            checks: checks.0,
+        })
+    }
+
+    /// Every archive the restore reads (each layer of an incremental chain)
+    /// is present and well-formed, or can be fetched back from its replica.
+    /// Returns the total archive size found locally.
+    async fn check_archive(
+        &self,
+        snapshot: &Snapshot,
+        checks: &mut Checks,
+    ) -> Result<u64, DomainError> {
+        let layers = if snapshot.parent_id.is_some() {
+            match incremental::resolve_chain(self.snapshot_repo.as_ref(), snapshot).await {
+                Ok(chain) => chain,
+                Err(e) => {
+                    checks.fail("archive", e.to_string());
+                    return Ok(0);
+                }
+            }
+        } else {
+            vec![snapshot.clone()]
+        };
+
+        let mut bytes = 0;
+        let mut problems = Vec::new();
+        let mut fetched = Vec::new();
+        for layer in &layers {
+            let problem = match layer.storage {
+                SnapshotStorage::File => match existing_local_path(&layer.file_path) {
+                    Some(local) => {
+                        let local = std::path::Path::new(&local);
+                        let len = std::fs::metadata(local).map(|m| m.len()).unwrap_or(0);
+                        bytes += len;
+                        let ext = layer.format.extension();
+                        let valid = if layer.encryption.is_some() {
+                            encryption::has_magic(local)
+                        } else {
+                            compression::has_magic(local, &layer.format)
+                        };
+                        if len == 0 {
+                            Some(format!("Snapshot file is empty: {}", layer.file_path))
+                        } else if !layer
+                            .file_path
+                            .to_ascii_lowercase()
+                            .ends_with(&format!(".{}", ext))
+                        {
+                            Some(format!(
+                                "File extension of {} does not match format '{}'",
+                                layer.file_path, ext
+                            ))
+                        } else if !valid {
+                            Some(format!(
+                                "Snapshot file is not a valid {} archive: {}",
+                                ext, layer.file_path
+                            ))
+                        } else {
+                            None
+                        }
+                    }
+                    None => Some(format!("Snapshot file not found: {}", layer.file_path)),
+                },
+                SnapshotStorage::Chunked => {
+                    let manifest = self.snapshot_repo.get_chunk_manifest(&layer.id).await?;
+                    bytes += manifest.iter().map(|c| c.length).sum::<u64>();
+                    if manifest.is_empty() {
+                        Some(format!(
+                            "Chunked snapshot '{}' has no chunk manifest",
+                            layer.id
+                        ))
+                    } else if chunk_store::store_dir_for(&layer.file_path).is_none() {
+                        Some(format!(
+                            "Chunk store of '{}' not found next to {}",
+                            layer.id, layer.file_path
+                        ))
+                    } else {
+                        None
+                    }
+                }
+            };
+            let Some(problem) = problem else { continue };
+            match &self.replicator {
+                Some(r) if r.has_replica(&layer.id).await? => fetched.push(layer.id.to_string()),
+                _ => problems.push(problem),
+            }
+        }
+
+        if !problems.is_empty() {
+            checks.fail("archive", problems.join("; "));
+        } else if !fetched.is_empty() {
+            checks.warn(
+                "archive",
+                format!(
+                    "Archive of {} is missing or damaged locally and is downloaded from its replica first",
+                    fetched.join(", ")
+                ),
+            );
+        } else if layers.len() > 1 {
+            checks.pass(
+                "archive",
+                format!("All {} layers of the chain are present", layers.len()),
+            );
+        } else {
+            checks.pass("archive", "Archive is present and well-formed");
+        }
+        Ok(bytes)
+    }
+
+    /// The archive format can be imported by the installed WSL and suits the
+    /// restore mode.
+    async fn check_format(&self, snapshot: &Snapshot, mode: &RestoreMode, checks: &mut Checks) {
+        let ext = snapshot.format.extension();
+        if matches!(mode, RestoreMode::Files { .. }) {
+            if snapshot.format.is_tar() {
+                checks.pass("format", format!("Files are read from the {} archive", ext));
+            } else {
+                checks.fail(
+                    "format",
+                    "File restores need a tar snapshot; VHDX snapshots can only be restored whole",
+                );
+            }
+            return;
+        }
+        match snapshot.format {
+            ExportFormat::Vhd => {
+                let version = self
+                    .wsl_manager
+                    .get_version_info()
+                    .await
+                    .ok()
+                    .and_then(|info| info.wsl_version);
+                match version.as_deref().and_then(parse_major_minor) {
+                    Some(v) if v >= MIN_VHD_IMPORT_VERSION => checks.pass(
+                        "format",
+                        format!("WSL {} imports VHDX snapshots", version.unwrap_or_default()),
+                    ),
+                    Some(_) => checks.fail(
+                        "format",
+                        format!(
+                            "WSL {} cannot import VHDX snapshots; update WSL to 0.58 or later",
+                            version.unwrap_or_default()
+                        ),
+                    ),
+                    None => checks.warn(
+                        "format",
+                        "Could not determine the WSL version; VHDX imports need WSL 0.58 or later",
+                    ),
+                }
+            }
+            ExportFormat::TarZst | ExportFormat::TarGz => checks.pass(
+                "format",
+                format!("{} archive is decompressed while it is imported", ext),
+            ),
+            ExportFormat::Tar => checks.pass("format", "Tar archive is imported as-is"),
+        }
+    }
+
+    /// Where the import goes: the registry path of the distro being
+    /// overwritten (as the restore itself resolves it) or the caller's
+    /// location for clones.
+    async fn check_install_location(
+        &self,
+        query: &PreflightRestoreQuery,
+        target: Option<DistroName>,
+        checks: &mut Checks,
+    ) -> String {
+        match &query.mode {
+            RestoreMode::Files { .. } => String::new(),
+            RestoreMode::Overwrite => {
+                let registry = match &target {
+                    Some(name) => self.wsl_manager.get_distro_install_path(name).await.ok(),
+                    None => None,
+                };
+                match registry.filter(|p| !p.trim().is_empty()) {
+                    Some(p)
+                        if !query.install_location.is_empty() && p != query.install_location =>
+                    {
+                        checks.warn(
+                            "install_location",
+                            format!(
+                                "The registry path {} is used instead of {}",
+                                p, query.install_location
+                            ),
+                        );
+                        p
+                    }
+                    Some(p) => {
+                        checks.pass("install_location", format!("Imports into {}", p));
+                        p
+                    }
+                    None if query.install_location.trim().is_empty() => {
+                        checks.fail(
+                            "install_location",
+                            "The install path could not be read from the registry and none was given",
+                        );
+                        String::new()
+                    }
+                    None => {
+                        checks.warn(
+                            "install_location",
+                            format!(
+                                "The install path could not be read from the registry; {} is used",
+                                query.install_location
+                            ),
+                        );
+                        query.install_location.clone()
+                    }
+                }
+            }
+            RestoreMode::Clone { .. } => {
+                let location = query.install_location.clone();
+                if location.trim().is_empty() {
+                    checks.fail("install_location", "A clone needs an install location");
+                    return location;
+                }
+                match existing_local_path(&location) {
+                    Some(dir) if std::path::Path::new(&dir).join("ext4.vhdx").exists() => {
+                        checks.fail(
+                            "install_location",
+                            format!("{} already holds an ext4.vhdx", location),
+                        );
+                    }
+                    Some(dir) if std::fs::read_dir(&dir).is_ok_and(|mut d| d.next().is_some()) => {
+                        checks.warn("install_location", format!("{} is not empty", location));
+                    }
+                    Some(_) => {
+                        checks.pass("install_location", format!("Imports into {}", location))
+                    }
+                    None => checks.pass(
+                        "install_location",
+                        format!("{} is created for the import", location),
+                    ),
+                }
+                location
+            }
+        }
+    }
+}
+
+/// The snapshot finished and passes (or may skip) checksum verification.
+fn check_status(snapshot: &Snapshot, force: bool, checks: &mut Checks) {
+    match &snapshot.status {
+        SnapshotStatus::Completed => {}
+        SnapshotStatus::Corrupted if force => {
+            checks.warn(
+                "status",
+                "Snapshot is marked corrupted; the forced restore skips verification",
+            );
+            return;
+        }
+        SnapshotStatus::Corrupted => {
+            checks.fail(
+                "status",
+                "Snapshot is marked corrupted; verify it again or force the restore",
+            );
+            return;
+        }
+        SnapshotStatus::InProgress => {
+            checks.fail("status", "Snapshot is still being created");
+            return;
+        }
+        SnapshotStatus::Failed(reason) => {
+            checks.fail("status", format!("Snapshot failed: {}", reason));
+            return;
+        }
+    }
+    if force {
+        checks.warn("status", "Checksum verification is skipped (forced)");
+    } else if snapshot.checksum.is_none() {
+        checks.warn(
+            "status",
+            "No checksum recorded; the archive cannot be verified before the import",
+        );
+    } else {
+        checks.pass(
+            "status",
+            "Snapshot is complete and verified before the import",
+        );
+    }
+}
+
+/// An encrypted snapshot comes with the secret it was encrypted with.
+async fn check_encryption(
+    snapshot: &Snapshot,
+    secret: Option<&SnapshotSecret>,
+    checks: &mut Checks,
+) {
+    if snapshot.encryption.is_none() {
+        return;
+    }
+    match encryption::unlock_snapshot(snapshot, secret).await {
+        Ok(_) => checks.pass("encryption", "The given key unlocks the snapshot"),
+        Err(e) => checks.fail("encryption", e.to_string()),
+    }
+}
+
+/// The distro the restore writes to: a free, valid name for clones, an
+/// existing distro otherwise. Returns its name and, when it exists or is
+/// valid, the parsed [`DistroName`].
+fn check_target(
+    snapshot: &Snapshot,
+    mode: &RestoreMode,
+    distros: &[Distro],
+    checks: &mut Checks,
+) -> (String, Option<DistroName>) {
+    let exists = |name: &str| {
+        distros
+            .iter()
+            .any(|d| d.name.as_str().eq_ignore_ascii_case(name))
+    };
+    match mode {
+        RestoreMode::Clone { new_name } => {
+            match DistroName::new(new_name) {
+                Err(e) => checks.fail("target", e.to_string()),
+                Ok(_) if exists(new_name) => checks.fail(
+                    "target",
+                    format!(
+                        "A distribution named '{}' already exists; pick another name",
+                        new_name
+                    ),
+                ),
+                Ok(_) => checks.pass(
+                    "target",
+                    format!("'{}' is registered as a new distribution", new_name),
+                ),
+            }
+            (new_name.clone(), None)
+        }
+        RestoreMode::Overwrite | RestoreMode::Files { .. } => {
+            let name = snapshot.distro_name.clone();
+            if !exists(name.as_str()) {
+                checks.fail(
+                    "target",
+                    format!(
+                        "'{}' is not registered; restore the snapshot as a clone instead",
+                        name
+                    ),
+                );
+                return (name.to_string(), None);
+            }
+            if matches!(mode, RestoreMode::Overwrite) {
+                checks.warn(
+                    "target",
+                    format!(
+                        "'{}' is replaced by the snapshot; changes since it was taken are lost",
+                        name
+                    ),
+                );
+            } else {
+                checks.pass(
+                    "target",
+                    format!("The selected paths are copied back into '{}'", name),
+                );
+            }
+            (name.to_string(), Some(name))
+        }
+    }
+}
+
+/// Which running distros the restore stops. Whole restores end with
+/// `wsl --shutdown`; file restores start the target if it is stopped.
+fn check_running(
+    mode: &RestoreMode,
+    target_name: &str,
+    distros: &[Distro],
+    checks: &mut Checks,
+) -> Vec<String> {
+    let target_running = distros
+        .iter()
+        .any(|d| d.name.as_str() == target_name && d.state.is_running());
+    if matches!(mode, RestoreMode::Files { .. }) {
+        if target_running {
+            checks.pass("running", format!("'{}' keeps running", target_name));
+        } else {
+            checks.pass(
+                "running",
+                format!("'{}' is started to extract the files", target_name),
+            );
+        }
+        return Vec::new();
+    }
+
+    let stopped: Vec<String> = distros
+        .iter()
+        .filter(|d| d.state.is_running())
+        .map(|d| d.name.to_string())
+        .collect();
+    if stopped.is_empty() {
+        checks.pass("running", "No running distribution is affected");
+        return stopped;
+    }
+    let mut message = format!("WSL is shut down, stopping {}", stopped.join(", "));
+    if matches!(mode, RestoreMode::Overwrite) && target_running {
+        message = format!(
+            "'{}' is terminated and its terminal sessions are closed; {}",
+            target_name, message
+        );
+    }
+    checks.warn("running", message);
+    stopped
+}
+
+/// Free space at the install location against what the import writes: the
+/// new ext4.vhdx (about the size of an uncompressed archive) plus, for
+/// overwrites, the safety backup copy of the current one.
+fn check_disk_space(
+    snapshot: &Snapshot,
+    mode: &RestoreMode,
+    install_location: &str,
+    archive_bytes: u64,
+    checks: &mut Checks,
+) -> (Option<u64>, Option<u64>) {
+    let backup_bytes = if matches!(mode, RestoreMode::Overwrite) {
+        existing_local_path(install_location)
+            .and_then(|dir| std::fs::metadata(std::path::Path::new(&dir).join("ext4.vhdx")).ok())
+            .map(|m| m.len())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let required = archive_bytes + backup_bytes;
+    let available = if install_location.trim().is_empty() {
+        None
+    } else {
+        available_space(install_location)
+    };
+
+    let Some(free) = available else {
+        checks.warn(
+            "disk_space",
+            format!(
+                "Could not determine the free space at '{}'; about {} is needed",
+                install_location,
+                human(required)
+            ),
+        );
+        return (Some(required), None);
+    };
+    if free < required {
+        checks.fail(
+            "disk_space",
+            format!(
+                "About {} is needed but only {} is free at {}",
+                human(required),
+                human(free),
+                install_location
+            ),
+        );
+    } else if snapshot.format.is_compressed() || snapshot.encryption.is_some() {
+        checks.warn(
+            "disk_space",
+            format!(
+                "At least {} is needed (the imported filesystem is larger than the packed archive); {} is free",
+                human(required),
+                human(free)
+            ),
+        );
+    } else {
+        checks.pass(
+            "disk_space",
+            format!(
+                "About {} is needed; {} is free",
+                human(required),
+                human(free)
+            ),
+        );
+    }
+    (Some(required), Some(free))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{KeySource, SnapshotEncryption, SnapshotType};
+    use crate::domain::entities::wsl_version::WslVersionInfo;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::value_objects::{DistroState, WslVersion};
+    use chrono::Utc;
+
+    fn make_snapshot(file_path: &std::path::Path, format: ExportFormat) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "test".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format,
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: Some("dev".into()),
+            storage: SnapshotStorage::File,
+            checksum: Some("abc".into()),
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn distro(name: &str, state: DistroState) -> Distro {
+        Distro::new(DistroName::new(name).unwrap(), state, WslVersion::V2, false)
+    }
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn handler(
+        snapshot: Snapshot,
+        distros: Vec<Distro>,
+        mut wsl: MockWslManagerPort,
+    ) -> PreflightRestoreHandler {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id()
+            .returning(move |_| Ok(snapshot.clone()));
+        wsl.expect_list_distros()
+            .returning(move || Ok(distros.clone()));
+        PreflightRestoreHandler::new(Arc::new(wsl), Arc::new(repo))
+    }
+
+    fn status_of<'a>(report: &'a RestorePreflightResponse, check: &str) -> &'a str {
+        report
+            .checks
+            .iter()
+            .find(|c| c.check == check)
+            .map(|c| c.status.as_str())
+            .unwrap_or("missing")
+    }
+
+    fn query(mode: RestoreMode, install_location: &str) -> PreflightRestoreQuery {
+        PreflightRestoreQuery {
+            snapshot_id: SnapshotId::from_string("snap-001".into()),
+            mode,
+            install_location: install_location.into(),
+            force: false,
+            secret: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_major_minor() {
+        assert_eq!(parse_major_minor("2.0.14.0"), Some((2, 0)));
+        assert_eq!(parse_major_minor("0.58"), Some((0, 58)));
+        assert_eq!(parse_major_minor("1"), Some((1, 0)));
+        assert_eq!(parse_major_minor("unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn test_clone_preflight_is_ready_and_touches_nothing() {
+        let dir = scratch_dir("preflight_clone_ready");
+        let archive = dir.join("snap.tar");
+        std::fs::write(&archive, vec![0u8; 1024]).unwrap();
+        let install = dir.join("Ubuntu-Copy");
+
+        // Only read-only port calls are expected: any start, shutdown or
+        // import would panic on the mock.
+        let handler = handler(
+            make_snapshot(&archive, ExportFormat::Tar),
+            vec![
+                distro("Ubuntu", DistroState::Running),
+                distro("Debian", DistroState::Stopped),
+            ],
+            MockWslManagerPort::new(),
+        );
+        let report = handler
+            .handle(query(
+                RestoreMode::Clone {
+                    new_name: "Ubuntu-Copy".into(),
+                },
+                &install.to_string_lossy(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(report.ready, "{:?}", report.checks);
+        assert_eq!(report.mode, "clone");
+        assert_eq!(report.target_name, "Ubuntu-Copy");
+        assert_eq!(report.archive_bytes, 1024);
+        assert_eq!(report.required_bytes, Some(1024));
+        assert!(report.available_bytes.is_some());
+        assert_eq!(report.stopped_distros, vec!["Ubuntu".to_string()]);
+        assert_eq!(report.default_user.as_deref(), Some("dev"));
+        assert_eq!(status_of(&report, "target"), "pass");
+        assert_eq!(status_of(&report, "disk_space"), "pass");
+        assert_eq!(status_of(&report, "running"), "warn");
+        assert!(!install.exists());
+    }
+
+    #[tokio::test]
+    async fn test_clone_preflight_reports_name_conflict_and_missing_archive() {
+        let dir = scratch_dir("preflight_clone_conflict");
+        let handler = handler(
+            make_snapshot(&dir.join("gone.tar"), ExportFormat::Tar),
+            vec![distro("Ubuntu", DistroState::Stopped)],
+            MockWslManagerPort::new(),
+        );
+        let report = handler
+            .handle(query(
+                RestoreMode::Clone {
+                    new_name: "ubuntu".into(),
+                },
+                &dir.join("clone").to_string_lossy(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(!report.ready);
+        assert_eq!(status_of(&report, "target"), "fail");
+        assert_eq!(status_of(&report, "archive"), "fail");
+        assert_eq!(status_of(&report, "running"), "pass");
+        assert!(report.stopped_distros.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_overwrite_preflight_counts_backup_and_stopped_distros() {
+        let dir = scratch_dir("preflight_overwrite");
+        let archive = dir.join("snap.tar");
+        std::fs::write(&archive, vec![0u8; 1000]).unwrap();
+        let install = dir.join("Ubuntu");
+        std::fs::create_dir_all(&install).unwrap();
+        std::fs::write(install.join("ext4.vhdx"), vec![0u8; 500]).unwrap();
+        let registry = install.to_string_lossy().to_string();
+
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_distro_install_path()
+            .returning(move |_| Ok(registry.clone()));
+        let mut snapshot = make_snapshot(&archive, ExportFormat::Tar);
+        snapshot.default_user = None;
+        let handler = handler(
+            snapshot,
+            vec![
+                distro("Ubuntu", DistroState::Running),
+                distro("Debian", DistroState::Running),
+            ],
+            wsl,
+        );
+        let report = handler
+            .handle(query(RestoreMode::Overwrite, "C:\\elsewhere"))
+            .await
+            .unwrap();
+
+        assert!(report.ready, "{:?}", report.checks);
+        assert_eq!(report.install_location, install.to_string_lossy());
+        assert_eq!(report.required_bytes, Some(1500));
+        assert_eq!(report.stopped_distros, vec!["Ubuntu", "Debian"]);
+        assert_eq!(status_of(&report, "install_location"), "warn");
+        assert_eq!(status_of(&report, "default_user"), "warn");
+        let running = report.checks.iter().find(|c| c.check == "running").unwrap();
+        assert!(running.message.contains("terminal sessions are closed"));
+    }
+
+    #[tokio::test]
+    async fn test_file_preflight_rejects_vhdx_and_missing_secret() {
+        let dir = scratch_dir("preflight_files");
+        let archive = dir.join("snap.vhdx");
+        std::fs::write(&archive, b"vhdxfile").unwrap();
+        let mut snapshot = make_snapshot(&archive, ExportFormat::Vhd);
+        snapshot.encryption = Some(SnapshotEncryption {
+            key_source: KeySource::Passphrase,
+            salt: "00".into(),
+            memory_kib: 8,
+            iterations: 1,
+            parallelism: 1,
+            key_check: "00".into(),
+        });
+        let handler = handler(
+            snapshot,
+            vec![distro("Ubuntu", DistroState::Stopped)],
+            MockWslManagerPort::new(),
+        );
+        let report = handler
+            .handle(query(
+                RestoreMode::Files {
+                    paths: vec!["/etc/hosts".into()],
+                },
+                "",
+            ))
+            .await
+            .unwrap();
+
+        assert!(!report.ready);
+        assert_eq!(status_of(&report, "format"), "fail");
+        assert_eq!(status_of(&report, "encryption"), "fail");
+        assert_eq!(status_of(&report, "disk_space"), "missing");
+        assert!(report.stopped_distros.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_vhdx_preflight_needs_recent_wsl() {
+        let dir = scratch_dir("preflight_vhdx");
+        let archive = dir.join("snap.vhdx");
+        std::fs::write(&archive, b"vhdxfile").unwrap();
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_version_info().returning(|| {
+            Ok(WslVersionInfo {
+                wsl_version: Some("0.51.2".into()),
+                ..Default::default()
+            })
+        });
+        let handler = handler(make_snapshot(&archive, ExportFormat::Vhd), Vec::new(), wsl);
+        let report = handler
+            .handle(query(
+                RestoreMode::Clone {
+                    new_name: "Fresh".into(),
+                },
+                &dir.join("fresh").to_string_lossy(),
+            ))
+            .await
+            .unwrap();
+
+        assert!(!report.ready);
+        assert_eq!(status_of(&report, "format"), "fail");
+    }
+}
@@ -0,0 +1,422 @@
+//! Content-addressed, deduplicated storage for snapshot archives.
+//!
+//! Exported archives are split into content-defined chunks (FastCDC), each
+//! stored once under `<snapshot dir>/chunks/<aa>/<sha256>`. Snapshots sharing a
+//! base image (even across distros) share most of their chunks. The ordered
+//! list of chunks is the snapshot's manifest, kept in SQLite and in the
+//! snapshot's sidecar.
+//!
+//! All file functions here do blocking I/O and must be run through
+//! `tokio::task::spawn_blocking` from async handlers.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use fastcdc::v2020::StreamCDC;
+use sha2::{Digest, Sha256};
+
+use crate::application::path_utils::existing_local_path;
+use crate::application::services::sidecar;
+use crate::domain::entities::snapshot::SnapshotChunk;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+
+/// Directory name of the chunk store, next to the snapshot files.
+pub const CHUNK_DIR: &str = "chunks";
+
+const MIN_CHUNK_SIZE: u32 = 512 * 1024;
+const AVG_CHUNK_SIZE: u32 = 2 * 1024 * 1024;
+const MAX_CHUNK_SIZE: u32 = 8 * 1024 * 1024;
+
+/// Chunks touched more recently than this are never collected, so a snapshot
+/// still being written (chunks on disk, manifest not yet saved) is safe.
+pub const GC_GRACE_PERIOD: Duration = Duration::from_secs(6 * 3600);
+
+/// Resolve the chunk store that serves the snapshot stored at `file_path`.
+/// Returns `None` when the snapshot directory does not exist on this machine.
+pub fn store_dir_for(file_path: &str) -> Option<PathBuf> {
+    let parent = Path::new(file_path).parent()?.to_string_lossy().to_string();
+    existing_local_path(&parent).map(|dir| PathBuf::from(dir).join(CHUNK_DIR))
+}
+
+/// Split `source` into content-defined chunks and store the ones not already
+/// present in `store`. Returns the ordered manifest.
+pub fn store_file(source: &Path, store: &Path) -> Result<Vec<SnapshotChunk>, DomainError> {
+    store_file_io(source, store).map_err(|e| {
+        DomainError::SnapshotError(format!(
+            "Failed to store '{}' in chunk store '{}': {}",
+            source.display(),
+            store.display(),
+            e
+        ))
+    })
+}
+
+/// Reassemble the archive described by `manifest` from `store` into `out`,
+/// verifying every chunk's length and hash. Returns the number of bytes written.
+pub fn reassemble(
+    manifest: &[SnapshotChunk],
+    store: &Path,
+    out: &Path,
+) -> Result<u64, DomainError> {
+    reassemble_io(manifest, store, out).map_err(|e| {
+        DomainError::SnapshotError(format!(
+            "Failed to reassemble '{}' from chunk store '{}': {}",
+            out.display(),
+            store.display(),
+            e
+        ))
+    })
+}
+
+/// Hex SHA-256 of the archive described by `manifest`, computed from the
+/// chunks in `store` without writing the archive out.
+pub fn checksum(manifest: &[SnapshotChunk], store: &Path) -> Result<String, DomainError> {
+    let mut hasher = Sha256::new();
+    copy_chunks(manifest, store, &mut hasher)
+        .map(|_| hex(&hasher.finalize()))
+        .map_err(|e| {
+            DomainError::SnapshotError(format!(
+                "Failed to read chunks from chunk store '{}': {}",
+                store.display(),
+                e
+            ))
+        })
+}
+
+/// Remove chunks in `store` that are not in `referenced` and were not touched
+/// within `grace`. Returns the number of chunks removed.
+pub fn collect_garbage(
+    store: &Path,
+    referenced: &HashSet<String>,
+    grace: Duration,
+) -> Result<u64, DomainError> {
+    collect_garbage_io(store, referenced, grace).map_err(|e| {
+        DomainError::SnapshotError(format!(
+            "Failed to collect chunk store '{}': {}",
+            store.display(),
+            e
+        ))
+    })
+}
+
+/// Best-effort cleanup after chunked snapshots stored at `file_paths` were
+/// deleted: drop every chunk of their stores that no snapshot references anymore.
+/// Chunks listed by sidecars next to a store count as referenced, so snapshots
+/// cataloged only by another machine sharing the folder keep their chunks.
+pub async fn release_stores(snapshot_repo: &dyn SnapshotRepositoryPort, file_paths: &[String]) {
+    let stores: HashSet<PathBuf> = file_paths.iter().filter_map(|p| store_dir_for(p)).collect();
+    if stores.is_empty() {
+        return;
+    }
+    let referenced: HashSet<String> = match snapshot_repo.list_referenced_chunks().await {
+        Ok(hashes) => hashes.into_iter().collect(),
+        Err(e) => {
+            tracing::warn!("chunk store GC skipped, cannot list references: {}", e);
+            return;
+        }
+    };
+    let result = tokio::task::spawn_blocking(move || {
+        stores
+            .iter()
+            .map(|store| {
+                let mut referenced = referenced.clone();
+                if let Some(dir) = store.parent() {
+                    referenced.extend(sidecar::referenced_chunks(dir));
+                }
+                collect_garbage(store, &referenced, GC_GRACE_PERIOD).map(|n| (store.clone(), n))
+            })
+            .collect::<Vec<_>>()
+    })
+    .await;
+    match result {
+        Ok(results) => {
+            for r in results {
+                match r {
+                    Ok((store, removed)) => {
+                        tracing::info!(removed, "chunk store GC: {}", store.display())
+                    }
+                    Err(e) => tracing::warn!("chunk store GC failed: {}", e),
+                }
+            }
+        }
+        Err(e) => tracing::warn!("chunk store GC task failed: {}", e),
+    }
+}
+
+fn chunk_path(store: &Path, hash: &str) -> PathBuf {
+    store.join(&hash[..2]).join(hash)
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn store_file_io(source: &Path, store: &Path) -> io::Result<Vec<SnapshotChunk>> {
+    let reader = BufReader::new(File::open(source)?);
+    let mut manifest = Vec::new();
+    for chunk in StreamCDC::new(reader, MIN_CHUNK_SIZE, AVG_CHUNK_SIZE, MAX_CHUNK_SIZE) {
+        let chunk = chunk?;
+        let hash = hex(&Sha256::digest(&chunk.data));
+        let path = chunk_path(store, &hash);
+        if path.exists() {
+            // Refresh the mtime so a concurrent GC keeps this chunk alive
+            // until the manifest referencing it is saved.
+            File::options()
+                .write(true)
+                .open(&path)?
+                .set_modified(SystemTime::now())?;
+        } else {
+            std::fs::create_dir_all(path.parent().unwrap_or(store))?;
+            // Write under a temporary name so a crash never leaves a truncated
+            // chunk behind a valid hash.
+            let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+            std::fs::write(&tmp, &chunk.data)?;
+            std::fs::rename(&tmp, &path)?;
+        }
+        manifest.push(SnapshotChunk {
+            hash,
+            length: chunk.length as u64,
+        });
+    }
+    Ok(manifest)
+}
+
+fn reassemble_io(manifest: &[SnapshotChunk], store: &Path, out: &Path) -> io::Result<u64> {
+    let mut writer = BufWriter::new(File::create(out)?);
+    let written = copy_chunks(manifest, store, &mut writer)?;
+    writer.flush()?;
+    Ok(written)
+}
+
+/// Write the verified chunks of `manifest` to `writer`, in order.
+fn copy_chunks(
+    manifest: &[SnapshotChunk],
+    store: &Path,
+    writer: &mut impl Write,
+) -> io::Result<u64> {
+    io::copy(&mut ChunkReader::new(manifest.to_vec(), store), writer)
+}
+
+/// Reader over the archive described by a chunk manifest, loading and
+/// verifying one chunk at a time. A corrupted chunk is an `InvalidData` error.
+pub struct ChunkReader {
+    manifest: std::vec::IntoIter<SnapshotChunk>,
+    store: PathBuf,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl ChunkReader {
+    pub fn new(manifest: Vec<SnapshotChunk>, store: &Path) -> Self {
+        Self {
+            manifest: manifest.into_iter(),
+            store: store.to_path_buf(),
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Load the next chunk into `buf`; `false` at the end of the manifest.
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        let Some(chunk) = self.manifest.next() else {
+            return Ok(false);
+        };
+        self.buf.clear();
+        self.pos = 0;
+        File::open(chunk_path(&self.store, &chunk.hash))
+            .map_err(|e| io::Error::new(e.kind(), format!("chunk {}: {}", chunk.hash, e)))?
+            .read_to_end(&mut self.buf)?;
+        if self.buf.len() as u64 != chunk.length || hex(&Sha256::digest(&self.buf)) != chunk.hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("chunk {} is corrupted", chunk.hash),
+            ));
+        }
+        Ok(true)
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() {
+            if !self.next_chunk()? {
+                return Ok(0);
+            }
+        }
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn collect_garbage_io(
+    store: &Path,
+    referenced: &HashSet<String>,
+    grace: Duration,
+) -> io::Result<u64> {
+    if !store.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0u64;
+    for prefix in std::fs::read_dir(store)? {
+        let prefix = prefix?;
+        if !prefix.file_type()?.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(prefix.path())? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let age = entry
+                .metadata()?
+                .modified()?
+                .elapsed()
+                .unwrap_or(Duration::ZERO);
+            if referenced.contains(&name) || age < grace {
+                continue;
+            }
+            std::fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+        // Drop the prefix directory once it is empty; ignore if it is not.
+        let _ = std::fs::remove_dir(prefix.path());
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nexus_chunk_store_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Deterministic pseudo-random bytes so chunk boundaries are content-defined.
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut x = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect()
+    }
+
+    fn chunk_files(store: &Path) -> usize {
+        std::fs::read_dir(store)
+            .map(|prefixes| {
+                prefixes
+                    .filter_map(|p| p.ok())
+                    .map(|p| std::fs::read_dir(p.path()).unwrap().count())
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_store_and_reassemble_round_trip() {
+        let dir = scratch_dir("roundtrip");
+        let store = dir.join(CHUNK_DIR);
+        let source = dir.join("a.tar");
+        let data = noise(1, 5 * 1024 * 1024);
+        std::fs::write(&source, &data).unwrap();
+
+        let manifest = store_file(&source, &store).unwrap();
+        assert!(manifest.len() > 1, "5 MiB should span several chunks");
+        assert_eq!(
+            manifest.iter().map(|c| c.length).sum::<u64>(),
+            data.len() as u64
+        );
+
+        let out = dir.join("out.tar");
+        assert_eq!(
+            reassemble(&manifest, &store, &out).unwrap(),
+            data.len() as u64
+        );
+        assert_eq!(std::fs::read(&out).unwrap(), data);
+        assert_eq!(
+            checksum(&manifest, &store).unwrap(),
+            hex(&Sha256::digest(&data))
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_shared_content_is_stored_once() {
+        let dir = scratch_dir("dedup");
+        let store = dir.join(CHUNK_DIR);
+        let base = noise(2, 6 * 1024 * 1024);
+        let mut variant = base.clone();
+        variant.extend_from_slice(&noise(3, 1024 * 1024));
+        std::fs::write(dir.join("a.tar"), &base).unwrap();
+        std::fs::write(dir.join("b.tar"), &variant).unwrap();
+
+        let a = store_file(&dir.join("a.tar"), &store).unwrap();
+        let after_a = chunk_files(&store);
+        let b = store_file(&dir.join("b.tar"), &store).unwrap();
+        let after_b = chunk_files(&store);
+
+        let shared = b.iter().filter(|c| a.contains(c)).count();
+        assert!(shared >= a.len() - 1, "base chunks must be reused");
+        assert!(after_b - after_a <= b.len() - shared);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_reassemble_detects_corrupted_chunk() {
+        let dir = scratch_dir("corrupt");
+        let store = dir.join(CHUNK_DIR);
+        let source = dir.join("a.tar");
+        std::fs::write(&source, noise(4, 1024 * 1024)).unwrap();
+        let manifest = store_file(&source, &store).unwrap();
+
+        let victim = chunk_path(&store, &manifest[0].hash);
+        let mut bytes = std::fs::read(&victim).unwrap();
+        bytes[0] ^= 0xFF;
+        std::fs::write(&victim, bytes).unwrap();
+
+        let result = reassemble(&manifest, &store, &dir.join("out.tar"));
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_collect_garbage_keeps_referenced_and_recent_chunks() {
+        let dir = scratch_dir("gc");
+        let store = dir.join(CHUNK_DIR);
+        let source = dir.join("a.tar");
+        std::fs::write(&source, noise(5, 3 * 1024 * 1024)).unwrap();
+        let manifest = store_file(&source, &store).unwrap();
+        let total = chunk_files(&store);
+        let keep: HashSet<String> = std::iter::once(manifest[0].hash.clone()).collect();
+
+        // Fresh chunks are protected by the grace period.
+        assert_eq!(collect_garbage(&store, &keep, GC_GRACE_PERIOD).unwrap(), 0);
+        assert_eq!(chunk_files(&store), total);
+
+        let removed = collect_garbage(&store, &keep, Duration::ZERO).unwrap();
+        assert_eq!(removed as usize, total - 1);
+        assert!(chunk_path(&store, &manifest[0].hash).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_collect_garbage_missing_store_is_noop() {
+        let store = std::env::temp_dir().join("nexus_chunk_store_missing");
+        let _ = std::fs::remove_dir_all(&store);
+        assert_eq!(
+            collect_garbage(&store, &HashSet::new(), Duration::ZERO).unwrap(),
+            0
+        );
+    }
+}
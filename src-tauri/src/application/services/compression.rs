@@ -0,0 +1,190 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::domain::entities::snapshot::ExportFormat;
+
+/// zstd level used for exports: fast enough to keep up with `wsl --export`.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Writer compressing into `W` according to an [`ExportFormat`].
+/// Must be [`finish`](Encoder::finish)ed to write the trailing frame.
+pub enum Encoder<W: Write> {
+    Plain(W),
+    Zstd(zstd::Encoder<'static, W>),
+    Gzip(flate2::write::GzEncoder<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(format: &ExportFormat, inner: W) -> std::io::Result<Self> {
+        Ok(match format {
+            ExportFormat::TarZst => Self::Zstd(zstd::Encoder::new(inner, ZSTD_LEVEL)?),
+            ExportFormat::TarGz => Self::Gzip(flate2::write::GzEncoder::new(
+                inner,
+                flate2::Compression::default(),
+            )),
+            ExportFormat::Tar | ExportFormat::Vhd => Self::Plain(inner),
+        })
+    }
+
+    /// Flush the compressed stream and return the inner writer.
+    pub fn finish(self) -> std::io::Result<W> {
+        match self {
+            Self::Plain(mut w) => {
+                w.flush()?;
+                Ok(w)
+            }
+            Self::Zstd(e) => e.finish(),
+            Self::Gzip(e) => e.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Zstd(e) => e.write(buf),
+            Self::Gzip(e) => e.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Zstd(e) => e.flush(),
+            Self::Gzip(e) => e.flush(),
+        }
+    }
+}
+
+/// Reader yielding the uncompressed content of an archive in `format`.
+pub fn decoder<R: Read + Send + 'static>(
+    format: &ExportFormat,
+    inner: R,
+) -> std::io::Result<Box<dyn Read + Send>> {
+    Ok(match format {
+        ExportFormat::TarZst => Box::new(zstd::Decoder::new(inner)?),
+        ExportFormat::TarGz => Box::new(flate2::read::MultiGzDecoder::new(inner)),
+        ExportFormat::Tar | ExportFormat::Vhd => Box::new(inner),
+    })
+}
+
+/// Leading magic bytes of a compressed archive, `None` for uncompressed formats.
+pub fn magic(format: &ExportFormat) -> Option<&'static [u8]> {
+    match format {
+        ExportFormat::TarZst => Some(&[0x28, 0xB5, 0x2F, 0xFD]),
+        ExportFormat::TarGz => Some(&[0x1F, 0x8B]),
+        ExportFormat::Tar | ExportFormat::Vhd => None,
+    }
+}
+
+/// Check that the file starts with the compression magic of `format`.
+/// Always true for uncompressed formats.
+pub fn has_magic(path: &Path, format: &ExportFormat) -> bool {
+    let Some(expected) = magic(format) else {
+        return true;
+    };
+    let Ok(mut f) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut buf = vec![0u8; expected.len()];
+    f.read_exact(&mut buf).is_ok() && buf == expected
+}
+
+/// Check that the (decompressed) archive starts with a tar header by looking
+/// for the "ustar" magic at byte offset 257 (per POSIX tar format specification).
+pub fn has_tar_header(path: &Path, format: &ExportFormat) -> bool {
+    let Ok(f) = std::fs::File::open(path) else {
+        return false;
+    };
+    starts_with_tar_header(format, f)
+}
+
+/// [`has_tar_header`] for an archive read from `inner` (e.g. while decrypting).
+pub fn starts_with_tar_header<R: Read + Send + 'static>(format: &ExportFormat, inner: R) -> bool {
+    let Ok(mut reader) = decoder(format, inner) else {
+        return false;
+    };
+    let mut buf = [0u8; 263];
+    if reader.read_exact(&mut buf).is_err() {
+        return false;
+    }
+    &buf[257..262] == b"ustar"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_bytes() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let data = b"hello";
+        let mut header = tar::Header::new_ustar();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "etc/hostname", &data[..])
+            .unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    fn compress(format: &ExportFormat, data: &[u8]) -> Vec<u8> {
+        let mut enc = Encoder::new(format, Vec::new()).unwrap();
+        enc.write_all(data).unwrap();
+        enc.finish().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_all_formats() {
+        let data = tar_bytes();
+        for format in [ExportFormat::Tar, ExportFormat::TarZst, ExportFormat::TarGz] {
+            let compressed = compress(&format, &data);
+            let mut out = Vec::new();
+            decoder(&format, std::io::Cursor::new(compressed))
+                .unwrap()
+                .read_to_end(&mut out)
+                .unwrap();
+            assert_eq!(out, data, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_compressed_output_is_smaller() {
+        let data = vec![0u8; 1 << 20];
+        assert!(compress(&ExportFormat::TarZst, &data).len() < 1024);
+        assert!(compress(&ExportFormat::TarGz, &data).len() < 4096);
+    }
+
+    #[test]
+    fn test_magic_and_tar_header_checks() {
+        let dir = std::env::temp_dir().join("wsl_nexus_compression_magic");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = tar_bytes();
+
+        let zst = dir.join("a.tar.zst");
+        std::fs::write(&zst, compress(&ExportFormat::TarZst, &data)).unwrap();
+        assert!(has_magic(&zst, &ExportFormat::TarZst));
+        assert!(!has_magic(&zst, &ExportFormat::TarGz));
+        assert!(has_tar_header(&zst, &ExportFormat::TarZst));
+        assert!(!has_tar_header(&zst, &ExportFormat::Tar));
+
+        let gz = dir.join("a.tar.gz");
+        std::fs::write(&gz, compress(&ExportFormat::TarGz, &data)).unwrap();
+        assert!(has_magic(&gz, &ExportFormat::TarGz));
+        assert!(has_tar_header(&gz, &ExportFormat::TarGz));
+
+        let plain = dir.join("a.tar");
+        std::fs::write(&plain, &data).unwrap();
+        assert!(has_magic(&plain, &ExportFormat::Tar));
+        assert!(has_tar_header(&plain, &ExportFormat::Tar));
+
+        let garbage = dir.join("garbage.tar.zst");
+        std::fs::write(&garbage, vec![0xAB; 600]).unwrap();
+        assert!(!has_magic(&garbage, &ExportFormat::TarZst));
+        assert!(!has_tar_header(&garbage, &ExportFormat::TarZst));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,543 @@
+//! Authenticated encryption of snapshot archives at rest.
+//!
+//! The key is derived from a passphrase or the content of a keyfile with
+//! Argon2id; the derivation parameters and a key check are stored with the
+//! snapshot (see `SnapshotEncryption`), the secret never is.
+//!
+//! Archives are encrypted after compression with XChaCha20-Poly1305 in the
+//! STREAM construction: a header (`MAGIC` + random nonce prefix) followed by
+//! frames of `FRAME_LEN` plaintext bytes, each with its own tag. The nonce of
+//! a frame is the prefix, its big-endian counter and a final-frame flag, so
+//! reordered, dropped or truncated frames fail to authenticate. Only the
+//! final frame is shorter than a full one (it may be empty).
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::application::path_utils::existing_local_path;
+use crate::application::services::chunk_store;
+use crate::domain::entities::snapshot::{KeySource, Snapshot, SnapshotEncryption};
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::EncryptionKey;
+use crate::domain::value_objects::encryption_key::KEY_LEN;
+
+/// First bytes of every encrypted archive; also the frames' associated data.
+pub const MAGIC: &[u8; 8] = b"WSLDENC1";
+
+/// Plaintext bytes per frame.
+pub const FRAME_LEN: usize = 64 * 1024;
+
+const TAG_LEN: usize = 16;
+const NONCE_PREFIX_LEN: usize = 19;
+const SALT_LEN: usize = 16;
+
+/// Argon2id cost for new keys: 64 MiB, 3 passes.
+const MEMORY_KIB: u32 = 64 * 1024;
+const ITERATIONS: u32 = 3;
+const PARALLELISM: u32 = 1;
+
+/// Upper bounds accepted from stored parameters, so a tampered sidecar
+/// cannot make unlocking exhaust memory.
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// Largest accepted keyfile.
+const MAX_KEYFILE_BYTES: u64 = 1024 * 1024;
+
+/// Domain separation for the stored key check.
+const KEY_CHECK_CONTEXT: &[u8] = b"wsl-dashboard snapshot key check v1";
+
+/// What the user unlocks an encrypted snapshot with.
+#[derive(Clone)]
+pub enum SnapshotSecret {
+    Passphrase(String),
+    /// Path of a keyfile; its content is the secret.
+    Keyfile(String),
+}
+
+impl SnapshotSecret {
+    /// Secret from the optional passphrase/keyfile arguments of a command.
+    /// Blank values count as absent; giving both is an error.
+    pub fn from_args(
+        passphrase: Option<String>,
+        keyfile: Option<String>,
+    ) -> Result<Option<Self>, DomainError> {
+        let passphrase = passphrase.filter(|p| !p.is_empty());
+        let keyfile = keyfile.filter(|k| !k.trim().is_empty());
+        match (passphrase, keyfile) {
+            (Some(_), Some(_)) => Err(DomainError::EncryptionError(
+                "Give either a passphrase or a keyfile, not both".into(),
+            )),
+            (Some(p), None) => Ok(Some(Self::Passphrase(p))),
+            (None, Some(k)) => Ok(Some(Self::Keyfile(k.trim().to_string()))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    pub fn key_source(&self) -> KeySource {
+        match self {
+            Self::Passphrase(_) => KeySource::Passphrase,
+            Self::Keyfile(_) => KeySource::Keyfile,
+        }
+    }
+
+    /// Bytes the key is derived from. Blocking for keyfiles.
+    fn material(&self) -> Result<Zeroizing<Vec<u8>>, DomainError> {
+        match self {
+            Self::Passphrase(p) => Ok(Zeroizing::new(p.as_bytes().to_vec())),
+            Self::Keyfile(path) => {
+                let local = existing_local_path(path).ok_or_else(|| {
+                    DomainError::EncryptionError(format!("Keyfile not found: {}", path))
+                })?;
+                let len = std::fs::metadata(&local)?.len();
+                if len == 0 || len > MAX_KEYFILE_BYTES {
+                    return Err(DomainError::EncryptionError(format!(
+                        "Keyfile must hold 1 byte to {} KiB: {}",
+                        MAX_KEYFILE_BYTES / 1024,
+                        path
+                    )));
+                }
+                Ok(Zeroizing::new(std::fs::read(&local)?))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SnapshotSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Passphrase(_) => f.write_str("Passphrase(<redacted>)"),
+            Self::Keyfile(path) => f.debug_tuple("Keyfile").field(path).finish(),
+        }
+    }
+}
+
+/// Fresh parameters and key for a new encrypted archive. Blocking (Argon2).
+pub fn new_encryption(
+    secret: &SnapshotSecret,
+) -> Result<(SnapshotEncryption, EncryptionKey), DomainError> {
+    let mut salt = [0u8; SALT_LEN];
+    fill_random(&mut salt)?;
+    let mut encryption = SnapshotEncryption {
+        key_source: secret.key_source(),
+        salt: chunk_store::hex(&salt),
+        memory_kib: MEMORY_KIB,
+        iterations: ITERATIONS,
+        parallelism: PARALLELISM,
+        key_check: String::new(),
+    };
+    let key = derive_key(&encryption, &secret.material()?)?;
+    encryption.key_check = key_check(&key);
+    Ok((encryption, key))
+}
+
+/// Re-derive the key of an archive encrypted with `encryption`.
+/// [`DomainError::WrongEncryptionKey`] if `secret` is not the one it was
+/// encrypted with. Blocking (Argon2).
+pub fn unlock(
+    snapshot_id: &str,
+    encryption: &SnapshotEncryption,
+    secret: &SnapshotSecret,
+) -> Result<EncryptionKey, DomainError> {
+    let key = derive_key(encryption, &secret.material()?)?;
+    if key_check(&key) != encryption.key_check {
+        return Err(DomainError::WrongEncryptionKey(snapshot_id.to_string()));
+    }
+    Ok(key)
+}
+
+/// Key of `snapshot`, `None` for a plain archive. An encrypted snapshot
+/// without `secret` is refused before anything is read.
+pub async fn unlock_snapshot(
+    snapshot: &Snapshot,
+    secret: Option<&SnapshotSecret>,
+) -> Result<Option<EncryptionKey>, DomainError> {
+    let Some(encryption) = snapshot.encryption.clone() else {
+        return Ok(None);
+    };
+    let secret = secret.cloned().ok_or_else(|| {
+        DomainError::EncryptionError(format!(
+            "Snapshot '{}' is encrypted; its {} is required",
+            snapshot.id,
+            encryption.key_source.as_str()
+        ))
+    })?;
+    let id = snapshot.id.to_string();
+    let key = tokio::task::spawn_blocking(move || unlock(&id, &encryption, &secret))
+        .await
+        .map_err(|e| DomainError::Internal(format!("key derivation task failed: {}", e)))??;
+    Ok(Some(key))
+}
+
+/// Async wrapper of [`new_encryption`].
+pub async fn new_encryption_async(
+    secret: &SnapshotSecret,
+) -> Result<(SnapshotEncryption, EncryptionKey), DomainError> {
+    let secret = secret.clone();
+    tokio::task::spawn_blocking(move || new_encryption(&secret))
+        .await
+        .map_err(|e| DomainError::Internal(format!("key derivation task failed: {}", e)))?
+}
+
+fn derive_key(
+    encryption: &SnapshotEncryption,
+    material: &[u8],
+) -> Result<EncryptionKey, DomainError> {
+    if encryption.memory_kib > MAX_MEMORY_KIB
+        || encryption.iterations > MAX_ITERATIONS
+        || encryption.parallelism > MAX_PARALLELISM
+    {
+        return Err(DomainError::EncryptionError(
+            "Key derivation parameters are out of range".into(),
+        ));
+    }
+    let salt = unhex(&encryption.salt)
+        .ok_or_else(|| DomainError::EncryptionError("Invalid key derivation salt".into()))?;
+    let params = Params::new(
+        encryption.memory_kib,
+        encryption.iterations,
+        encryption.parallelism,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| DomainError::EncryptionError(format!("Invalid key derivation parameters: {e}")))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(material, &salt, key.as_mut())
+        .map_err(|e| DomainError::EncryptionError(format!("Key derivation failed: {e}")))?;
+    Ok(EncryptionKey::from_bytes(*key))
+}
+
+fn key_check(key: &EncryptionKey) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_CHECK_CONTEXT);
+    hasher.update(key.as_bytes());
+    chunk_store::hex(&hasher.finalize())
+}
+
+fn fill_random(buf: &mut [u8]) -> Result<(), DomainError> {
+    getrandom::getrandom(buf)
+        .map_err(|e| DomainError::EncryptionError(format!("No system randomness: {e}")))
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn frame_nonce(prefix: &[u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> XNonce {
+    let mut nonce = [0u8; 24];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..NONCE_PREFIX_LEN + 4].copy_from_slice(&counter.to_be_bytes());
+    nonce[23] = last as u8;
+    XNonce::from(nonce)
+}
+
+/// Writer encrypting into `W`. Must be [`finish`](EncryptWriter::finish)ed to
+/// write the final frame; an unfinished archive fails to decrypt.
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    cipher: XChaCha20Poly1305,
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    buf: Zeroizing<Vec<u8>>,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    pub fn new(key: &EncryptionKey, mut inner: W) -> io::Result<Self> {
+        let mut prefix = [0u8; NONCE_PREFIX_LEN];
+        fill_random(&mut prefix).map_err(io::Error::other)?;
+        inner.write_all(MAGIC)?;
+        inner.write_all(&prefix)?;
+        Ok(Self {
+            inner,
+            cipher: XChaCha20Poly1305::new(key.as_bytes().into()),
+            prefix,
+            counter: 0,
+            buf: Zeroizing::new(Vec::with_capacity(FRAME_LEN)),
+        })
+    }
+
+    fn seal_frame(&mut self, last: bool) -> io::Result<()> {
+        let nonce = frame_nonce(&self.prefix, self.counter, last);
+        let frame = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &self.buf,
+                    aad: MAGIC,
+                },
+            )
+            .map_err(|_| io::Error::other("archive encryption failed"))?;
+        self.inner.write_all(&frame)?;
+        self.buf.clear();
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| io::Error::other("archive too large to encrypt"))?;
+        Ok(())
+    }
+
+    /// Write the final frame and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.seal_frame(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len().min(FRAME_LEN - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        // A full frame is never the last one: finish() always adds a short one.
+        if self.buf.len() == FRAME_LEN {
+            self.seal_frame(false)?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader yielding the plaintext of an archive encrypted by [`EncryptWriter`].
+pub struct DecryptReader<R: Read> {
+    inner: R,
+    cipher: XChaCha20Poly1305,
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    frame: Vec<u8>,
+    plain: Zeroizing<Vec<u8>>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> DecryptReader<R> {
+    pub fn new(key: &EncryptionKey, mut inner: R) -> io::Result<Self> {
+        let mut header = [0u8; MAGIC.len() + NONCE_PREFIX_LEN];
+        inner.read_exact(&mut header).map_err(|_| not_encrypted())?;
+        if &header[..MAGIC.len()] != MAGIC {
+            return Err(not_encrypted());
+        }
+        let mut prefix = [0u8; NONCE_PREFIX_LEN];
+        prefix.copy_from_slice(&header[MAGIC.len()..]);
+        Ok(Self {
+            inner,
+            cipher: XChaCha20Poly1305::new(key.as_bytes().into()),
+            prefix,
+            counter: 0,
+            frame: vec![0u8; FRAME_LEN + TAG_LEN],
+            plain: Zeroizing::new(Vec::new()),
+            pos: 0,
+            done: false,
+        })
+    }
+
+    fn open_frame(&mut self) -> io::Result<()> {
+        let mut len = 0;
+        while len < self.frame.len() {
+            match self.inner.read(&mut self.frame[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let last = len < self.frame.len();
+        if len < TAG_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "encrypted archive is truncated",
+            ));
+        }
+        let nonce = frame_nonce(&self.prefix, self.counter, last);
+        self.plain = Zeroizing::new(
+            self.cipher
+                .decrypt(
+                    &nonce,
+                    Payload {
+                        msg: &self.frame[..len],
+                        aad: MAGIC,
+                    },
+                )
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "encrypted archive failed authentication (corrupted or truncated)",
+                    )
+                })?,
+        );
+        self.pos = 0;
+        self.done = last;
+        self.counter = self.counter.wrapping_add(1);
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.plain.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.open_frame()?;
+        }
+        let n = out.len().min(self.plain.len() - self.pos);
+        out[..n].copy_from_slice(&self.plain[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn not_encrypted() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "not an encrypted snapshot archive",
+    )
+}
+
+/// `inner` decrypted with `key`, or `inner` as is without a key.
+pub fn reader<R: Read + Send + 'static>(
+    key: Option<&EncryptionKey>,
+    inner: R,
+) -> io::Result<Box<dyn Read + Send>> {
+    Ok(match key {
+        Some(key) => Box::new(DecryptReader::new(key, inner)?),
+        None => Box::new(inner),
+    })
+}
+
+/// Check that the file starts with the encrypted archive header.
+pub fn has_magic(path: &Path) -> bool {
+    let Ok(mut f) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut buf = [0u8; MAGIC.len()];
+    f.read_exact(&mut buf).is_ok() && &buf == MAGIC
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passphrase(p: &str) -> SnapshotSecret {
+        SnapshotSecret::Passphrase(p.into())
+    }
+
+    fn encrypt(key: &EncryptionKey, data: &[u8]) -> Vec<u8> {
+        let mut w = EncryptWriter::new(key, Vec::new()).unwrap();
+        w.write_all(data).unwrap();
+        w.finish().unwrap()
+    }
+
+    fn decrypt(key: &EncryptionKey, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        DecryptReader::new(key, io::Cursor::new(data))?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_roundtrip_across_frame_boundaries() {
+        let key = EncryptionKey::from_bytes([7; KEY_LEN]);
+        for len in [0, 1, FRAME_LEN - 1, FRAME_LEN, FRAME_LEN + 1, 3 * FRAME_LEN] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let sealed = encrypt(&key, &data);
+            assert!(sealed.starts_with(MAGIC));
+            assert_eq!(decrypt(&key, sealed).unwrap(), data, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_tampering_truncation_and_wrong_key_are_detected() {
+        let key = EncryptionKey::from_bytes([7; KEY_LEN]);
+        let data = vec![42u8; 2 * FRAME_LEN + 10];
+        let sealed = encrypt(&key, &data);
+
+        let mut flipped = sealed.clone();
+        flipped[MAGIC.len() + NONCE_PREFIX_LEN + 5] ^= 1;
+        assert!(decrypt(&key, flipped).is_err());
+
+        // Dropping the final frame leaves only full frames: still an error.
+        let frame = FRAME_LEN + TAG_LEN;
+        let truncated = sealed[..MAGIC.len() + NONCE_PREFIX_LEN + 2 * frame].to_vec();
+        assert!(decrypt(&key, truncated).is_err());
+
+        let other = EncryptionKey::from_bytes([8; KEY_LEN]);
+        assert!(decrypt(&other, sealed).is_err());
+        assert!(decrypt(&key, b"ustar plain archive.....................".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_unlock_checks_the_secret() {
+        let (encryption, key) = new_encryption(&passphrase("correct horse")).unwrap();
+        assert_eq!(encryption.key_source, KeySource::Passphrase);
+        assert_eq!(encryption.salt.len(), 2 * SALT_LEN);
+
+        let again = unlock("s1", &encryption, &passphrase("correct horse")).unwrap();
+        assert_eq!(again, key);
+        assert!(matches!(
+            unlock("s1", &encryption, &passphrase("wrong")),
+            Err(DomainError::WrongEncryptionKey(id)) if id == "s1"
+        ));
+
+        let mut tampered = encryption.clone();
+        tampered.memory_kib = MAX_MEMORY_KIB + 1;
+        assert!(matches!(
+            unlock("s1", &tampered, &passphrase("correct horse")),
+            Err(DomainError::EncryptionError(_))
+        ));
+    }
+
+    #[test]
+    fn test_keyfile_secret() {
+        let dir = std::env::temp_dir().join("encryption_keyfile_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let keyfile = dir.join("backup.key");
+        std::fs::write(&keyfile, [1u8, 2, 3, 4]).unwrap();
+        let secret = SnapshotSecret::Keyfile(keyfile.to_string_lossy().to_string());
+
+        let (encryption, key) = new_encryption(&secret).unwrap();
+        assert_eq!(encryption.key_source, KeySource::Keyfile);
+        assert_eq!(unlock("s1", &encryption, &secret).unwrap(), key);
+
+        std::fs::write(&keyfile, []).unwrap();
+        assert!(matches!(
+            unlock("s1", &encryption, &secret),
+            Err(DomainError::EncryptionError(_))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_secret_from_args() {
+        assert!(
+            SnapshotSecret::from_args(None, Some("  ".into()))
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            SnapshotSecret::from_args(Some("p".into()), None).unwrap(),
+            Some(SnapshotSecret::Passphrase(_))
+        ));
+        assert!(SnapshotSecret::from_args(Some("p".into()), Some("/k".into())).is_err());
+        assert_eq!(
+            format!("{:?}", passphrase("hunter2")),
+            "Passphrase(<redacted>)"
+        );
+    }
+}
@@ -0,0 +1,625 @@
+//! Tar-level deltas backing incremental snapshots.
+//!
+//! An incremental snapshot is stored as a regular tar holding only the entries
+//! that were added or changed relative to its parent, followed by a reserved
+//! entry listing the paths deleted since the parent. A full tar is rebuilt by
+//! layering every delta of the chain on top of the base (full) snapshot.
+//!
+//! All tar functions here do blocking file I/O and must be run through
+//! `tokio::task::spawn_blocking` from async handlers.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tar::{Archive, Builder, Entry, EntryType, Header};
+
+use crate::domain::entities::snapshot::Snapshot;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+
+/// Reserved entry name holding the NUL-separated list of deleted paths.
+pub const DELETIONS_ENTRY: &str = ".wsl-nexus-deletions";
+
+/// Upper bound on the parent chain length, guarding against cycles in corrupt metadata.
+const MAX_CHAIN_DEPTH: usize = 1024;
+
+/// PAX keys that are either re-emitted by the tar builder itself or change on
+/// every read of the source filesystem and must not influence entry digests.
+const VOLATILE_PAX_KEYS: &[&str] = &["path", "linkpath", "size", "atime", "ctime"];
+
+type EntryDigest = [u8; 32];
+
+/// Summary of a delta produced by [`build_delta`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeltaStats {
+    /// Entries added or modified since the parent (stored in the delta).
+    pub changed: u64,
+    /// Entries present in the parent but gone from the new export.
+    pub deleted: u64,
+    /// Entries identical to the parent (omitted from the delta).
+    pub unchanged: u64,
+}
+
+/// Walk `parent_id` links from `snapshot` back to its full base snapshot.
+/// Returns the chain ordered base first, ending with `snapshot` itself.
+pub async fn resolve_chain(
+    snapshot_repo: &dyn SnapshotRepositoryPort,
+    snapshot: &Snapshot,
+) -> Result<Vec<Snapshot>, DomainError> {
+    let mut chain = vec![snapshot.clone()];
+    let mut seen = HashSet::from([snapshot.id.clone()]);
+    let mut current = snapshot.clone();
+    while let Some(parent_id) = current.parent_id.clone() {
+        if !seen.insert(parent_id.clone()) || chain.len() >= MAX_CHAIN_DEPTH {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot chain of '{}' is cyclic or too deep",
+                snapshot.id
+            )));
+        }
+        let parent = snapshot_repo.get_by_id(&parent_id).await.map_err(|e| {
+            DomainError::SnapshotError(format!(
+                "Parent snapshot '{}' of '{}' is unavailable: {}",
+                parent_id, current.id, e
+            ))
+        })?;
+        chain.push(parent.clone());
+        current = parent;
+    }
+    chain.reverse();
+    Ok(chain)
+}
+
+/// Diff the full export `full_tar` against the state described by `parent_chain`
+/// (base first) and write the changed/added entries plus a deletion manifest
+/// to `delta_out`.
+pub fn build_delta(
+    parent_chain: &[PathBuf],
+    full_tar: &Path,
+    delta_out: &Path,
+) -> Result<DeltaStats, DomainError> {
+    build_delta_io(parent_chain, full_tar, delta_out).map_err(|e| {
+        DomainError::SnapshotError(format!(
+            "Failed to build incremental delta '{}': {}",
+            delta_out.display(),
+            e
+        ))
+    })
+}
+
+/// Rebuild a full, importable tar at `out` from `chain` (base first).
+/// Returns the number of entries written.
+pub fn rebuild_full_tar(chain: &[PathBuf], out: &Path) -> Result<u64, DomainError> {
+    rebuild_full_tar_io(chain, out).map_err(|e| {
+        DomainError::SnapshotError(format!(
+            "Failed to rebuild full tar '{}' from snapshot chain: {}",
+            out.display(),
+            e
+        ))
+    })
+}
+
+fn build_delta_io(
+    parent_chain: &[PathBuf],
+    full_tar: &Path,
+    delta_out: &Path,
+) -> io::Result<DeltaStats> {
+    let parent_index = index_chain(parent_chain)?;
+
+    // Pass 1: hash every entry of the new export and decide what changed.
+    let mut stats = DeltaStats::default();
+    let mut seen: HashSet<Vec<u8>> = HashSet::new();
+    let mut changed: HashSet<Vec<u8>> = HashSet::new();
+    let mut archive = Archive::new(BufReader::new(File::open(full_tar)?));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let meta = read_meta(&mut entry)?;
+        let digest = digest_entry(&meta, &mut entry)?;
+        // A hard link whose target changed is re-emitted after that target so
+        // the rebuilt tar never references a file that appears later.
+        let link_target_changed = meta.header.entry_type() == EntryType::Link
+            && meta
+                .link_key
+                .as_ref()
+                .is_some_and(|target| changed.contains(target));
+        if link_target_changed || parent_index.get(&meta.key) != Some(&digest) {
+            changed.insert(meta.key.clone());
+            stats.changed += 1;
+        } else {
+            stats.unchanged += 1;
+        }
+        seen.insert(meta.key);
+    }
+
+    let mut deleted: Vec<&Vec<u8>> = parent_index
+        .keys()
+        .filter(|key| !seen.contains(*key))
+        .collect();
+    deleted.sort();
+    stats.deleted = deleted.len() as u64;
+
+    // Pass 2: copy the changed entries; unchanged payloads are skipped via seek.
+    let mut builder = Builder::new(BufWriter::new(File::create(delta_out)?));
+    let mut archive = Archive::new(File::open(full_tar)?);
+    for entry in archive.entries_with_seek()? {
+        let mut entry = entry?;
+        let meta = read_meta(&mut entry)?;
+        if changed.contains(&meta.key) {
+            write_entry(&mut builder, &meta, &mut entry)?;
+        }
+    }
+
+    let manifest = deleted.iter().fold(Vec::new(), |mut acc, key| {
+        acc.extend_from_slice(key);
+        acc.push(0);
+        acc
+    });
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Regular);
+    header.set_mode(0o600);
+    header.set_size(manifest.len() as u64);
+    builder.append_data(&mut header, DELETIONS_ENTRY, manifest.as_slice())?;
+
+    builder.into_inner()?.flush()?;
+    Ok(stats)
+}
+
+fn rebuild_full_tar_io(chain: &[PathBuf], out: &Path) -> io::Result<u64> {
+    // Pass 1: find which layer provides the final version of every path.
+    let mut winners: HashMap<Vec<u8>, usize> = HashMap::new();
+    for (layer, path) in chain.iter().enumerate() {
+        let mut archive = Archive::new(File::open(path)?);
+        for entry in archive.entries_with_seek()? {
+            let mut entry = entry?;
+            let key = normalize_key(&entry.path_bytes());
+            if key == DELETIONS_ENTRY.as_bytes() {
+                for deleted in read_deletions(&mut entry)? {
+                    winners.remove(&deleted);
+                }
+            } else {
+                winners.insert(key, layer);
+            }
+        }
+    }
+
+    // Pass 2: emit layers oldest first so directories and hard-link targets
+    // precede the entries that depend on them.
+    let mut written = 0u64;
+    let mut builder = Builder::new(BufWriter::new(File::create(out)?));
+    for (layer, path) in chain.iter().enumerate() {
+        let mut archive = Archive::new(File::open(path)?);
+        for entry in archive.entries_with_seek()? {
+            let mut entry = entry?;
+            let meta = read_meta(&mut entry)?;
+            if winners.get(&meta.key) == Some(&layer) {
+                write_entry(&mut builder, &meta, &mut entry)?;
+                written += 1;
+            }
+        }
+    }
+    builder.into_inner()?.flush()?;
+    Ok(written)
+}
+
+/// Digest every path of the state described by `chain` (base first).
+fn index_chain(chain: &[PathBuf]) -> io::Result<HashMap<Vec<u8>, EntryDigest>> {
+    let mut index = HashMap::new();
+    for path in chain {
+        let mut archive = Archive::new(BufReader::new(File::open(path)?));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let meta = read_meta(&mut entry)?;
+            if meta.key == DELETIONS_ENTRY.as_bytes() {
+                for deleted in read_deletions(&mut entry)? {
+                    index.remove(&deleted);
+                }
+                continue;
+            }
+            let digest = digest_entry(&meta, &mut entry)?;
+            index.insert(meta.key, digest);
+        }
+    }
+    Ok(index)
+}
+
+pub(crate) fn read_deletions<R: Read>(entry: &mut Entry<'_, R>) -> io::Result<Vec<Vec<u8>>> {
+    let mut buf = Vec::new();
+    entry.read_to_end(&mut buf)?;
+    Ok(buf
+        .split(|b| *b == 0)
+        .filter(|p| !p.is_empty())
+        .map(<[u8]>::to_vec)
+        .collect())
+}
+
+/// Everything needed to digest an entry and re-emit it into another archive.
+pub(crate) struct EntryMeta {
+    header: Header,
+    path: PathBuf,
+    pub(crate) key: Vec<u8>,
+    link_name: Option<PathBuf>,
+    link_key: Option<Vec<u8>>,
+    pax: Vec<(String, Vec<u8>)>,
+    size: u64,
+}
+
+pub(crate) fn read_meta<R: Read>(entry: &mut Entry<'_, R>) -> io::Result<EntryMeta> {
+    let mut pax = Vec::new();
+    if let Some(extensions) = entry.pax_extensions()? {
+        for ext in extensions {
+            let ext = ext?;
+            let Ok(key) = ext.key() else { continue };
+            if VOLATILE_PAX_KEYS.contains(&key) || key.starts_with("GNU.sparse") {
+                continue;
+            }
+            pax.push((key.to_string(), ext.value_bytes().to_vec()));
+        }
+    }
+    Ok(EntryMeta {
+        header: entry.header().clone(),
+        path: entry.path()?.into_owned(),
+        key: normalize_key(&entry.path_bytes()),
+        link_name: entry.link_name()?.map(|p| p.into_owned()),
+        link_key: entry.link_name_bytes().map(|b| normalize_key(&b)),
+        pax,
+        size: entry.size(),
+    })
+}
+
+/// Hash the metadata that matters for a restore together with the content.
+fn digest_entry<R: Read>(meta: &EntryMeta, content: &mut R) -> io::Result<EntryDigest> {
+    let header = &meta.header;
+    let mut hasher = Sha256::new();
+    hasher.update([header.entry_type().as_byte()]);
+    // Blank numeric fields are tolerated (some writers leave them empty).
+    hasher.update(header.mode().unwrap_or_default().to_le_bytes());
+    hasher.update(header.uid().unwrap_or_default().to_le_bytes());
+    hasher.update(header.gid().unwrap_or_default().to_le_bytes());
+    hasher.update(header.mtime().unwrap_or_default().to_le_bytes());
+    hasher.update(meta.size.to_le_bytes());
+    if let Some(link) = &meta.link_key {
+        hasher.update(link);
+    }
+    hasher.update([0]);
+    for (key, value) in &meta.pax {
+        hasher.update(key.as_bytes());
+        hasher.update([0]);
+        hasher.update(value);
+        hasher.update([0]);
+    }
+    io::copy(content, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+pub(crate) fn write_entry<W: Write, R: Read>(
+    builder: &mut Builder<W>,
+    meta: &EntryMeta,
+    data: &mut R,
+) -> io::Result<()> {
+    let mut header = meta.header.clone();
+    // Sparse entries are expanded by the reader, so re-emit them as regular files.
+    if header.entry_type().is_gnu_sparse() {
+        header.set_entry_type(EntryType::Regular);
+    }
+    header.set_size(meta.size);
+    if !meta.pax.is_empty() {
+        builder.append_pax_extensions(
+            meta.pax
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_slice())),
+        )?;
+    }
+    match (&meta.link_name, header.entry_type()) {
+        (Some(target), EntryType::Link | EntryType::Symlink) => {
+            builder.append_link(&mut header, &meta.path, target)
+        }
+        _ => builder.append_data(&mut header, &meta.path, data),
+    }
+}
+
+/// Canonical form of an archive path: `./etc/` and `etc` are the same entry.
+pub(crate) fn normalize_key(raw: &[u8]) -> Vec<u8> {
+    let mut path = raw;
+    while let Some(rest) = path.strip_prefix(b"./".as_slice()) {
+        path = rest;
+    }
+    while path.len() > 1 && path.ends_with(b"/") {
+        path = &path[..path.len() - 1];
+    }
+    if path.is_empty() {
+        b".".to_vec()
+    } else {
+        path.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
+    use chrono::Utc;
+
+    enum Item<'a> {
+        Dir(&'a str),
+        File(&'a str, &'a [u8]),
+        HardLink(&'a str, &'a str),
+    }
+
+    fn write_tar(path: &Path, items: &[Item<'_>]) {
+        let mut builder = Builder::new(File::create(path).unwrap());
+        for item in items {
+            let mut header = Header::new_gnu();
+            header.set_mtime(1_700_000_000);
+            header.set_uid(0);
+            header.set_gid(0);
+            match item {
+                Item::Dir(p) => {
+                    header.set_entry_type(EntryType::Directory);
+                    header.set_mode(0o755);
+                    header.set_size(0);
+                    builder.append_data(&mut header, p, io::empty()).unwrap();
+                }
+                Item::File(p, data) => {
+                    header.set_entry_type(EntryType::Regular);
+                    header.set_mode(0o644);
+                    header.set_size(data.len() as u64);
+                    builder.append_data(&mut header, p, *data).unwrap();
+                }
+                Item::HardLink(p, target) => {
+                    header.set_entry_type(EntryType::Link);
+                    header.set_mode(0o644);
+                    header.set_size(0);
+                    builder.append_link(&mut header, p, target).unwrap();
+                }
+            }
+        }
+        builder.finish().unwrap();
+    }
+
+    /// Read an archive into (normalized path, content) pairs in archive order.
+    fn read_tar(path: &Path) -> Vec<(String, Vec<u8>)> {
+        let mut archive = Archive::new(File::open(path).unwrap());
+        archive
+            .entries()
+            .unwrap()
+            .map(|e| {
+                let mut e = e.unwrap();
+                let key = String::from_utf8(normalize_key(&e.path_bytes())).unwrap();
+                let mut data = Vec::new();
+                e.read_to_end(&mut data).unwrap();
+                (key, data)
+            })
+            .collect()
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nexus_incremental_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_normalize_key_strips_dot_prefix_and_trailing_slash() {
+        assert_eq!(normalize_key(b"./etc/"), b"etc".to_vec());
+        assert_eq!(normalize_key(b"././usr/bin/ls"), b"usr/bin/ls".to_vec());
+        assert_eq!(normalize_key(b"./"), b".".to_vec());
+        assert_eq!(normalize_key(b"etc"), b"etc".to_vec());
+    }
+
+    #[test]
+    fn test_build_delta_stores_only_changes_and_deletions() {
+        let dir = scratch_dir("delta");
+        let base = dir.join("base.tar");
+        let next = dir.join("next.tar");
+        let delta = dir.join("delta.tar");
+        write_tar(
+            &base,
+            &[
+                Item::Dir("./etc/"),
+                Item::File("./etc/hostname", b"old-host"),
+                Item::File("./etc/keep", b"same"),
+                Item::File("./etc/gone", b"bye"),
+            ],
+        );
+        write_tar(
+            &next,
+            &[
+                Item::Dir("./etc/"),
+                Item::File("./etc/hostname", b"new-host"),
+                Item::File("./etc/keep", b"same"),
+                Item::File("./etc/added", b"hi"),
+            ],
+        );
+
+        let stats = build_delta(std::slice::from_ref(&base), &next, &delta).unwrap();
+        assert_eq!(
+            stats,
+            DeltaStats {
+                changed: 2,
+                deleted: 1,
+                unchanged: 2
+            }
+        );
+
+        let entries = read_tar(&delta);
+        let names: Vec<&str> = entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["etc/hostname", "etc/added", DELETIONS_ENTRY]);
+        assert_eq!(entries[2].1, b"etc/gone\0".to_vec());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rebuild_matches_latest_export_across_chain() {
+        let dir = scratch_dir("rebuild");
+        let base = dir.join("base.tar");
+        let v2 = dir.join("v2.tar");
+        let v3 = dir.join("v3.tar");
+        let d2 = dir.join("d2.tar");
+        let d3 = dir.join("d3.tar");
+        let rebuilt = dir.join("rebuilt.tar");
+        write_tar(
+            &base,
+            &[
+                Item::Dir("./home/"),
+                Item::File("./home/a", b"a1"),
+                Item::File("./home/b", b"b1"),
+            ],
+        );
+        // v2 deletes b and adds c
+        write_tar(
+            &v2,
+            &[
+                Item::Dir("./home/"),
+                Item::File("./home/a", b"a1"),
+                Item::File("./home/c", b"c1"),
+            ],
+        );
+        // v3 brings b back with new content and modifies a
+        write_tar(
+            &v3,
+            &[
+                Item::Dir("./home/"),
+                Item::File("./home/a", b"a3"),
+                Item::File("./home/b", b"b3"),
+                Item::File("./home/c", b"c1"),
+            ],
+        );
+
+        build_delta(std::slice::from_ref(&base), &v2, &d2).unwrap();
+        let stats = build_delta(&[base.clone(), d2.clone()], &v3, &d3).unwrap();
+        assert_eq!(stats.changed, 2);
+        assert_eq!(stats.deleted, 0);
+
+        let written = rebuild_full_tar(&[base, d2, d3], &rebuilt).unwrap();
+        assert_eq!(written, 4);
+
+        let mut got = read_tar(&rebuilt);
+        got.sort();
+        let mut want = read_tar(&v3);
+        want.sort();
+        assert_eq!(got, want);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hard_link_follows_changed_target() {
+        let dir = scratch_dir("hardlink");
+        let base = dir.join("base.tar");
+        let next = dir.join("next.tar");
+        let delta = dir.join("delta.tar");
+        write_tar(
+            &base,
+            &[
+                Item::File("bin/tool", b"v1"),
+                Item::HardLink("bin/tool-alias", "bin/tool"),
+            ],
+        );
+        write_tar(
+            &next,
+            &[
+                Item::File("bin/tool", b"v2"),
+                Item::HardLink("bin/tool-alias", "bin/tool"),
+            ],
+        );
+
+        let stats = build_delta(&[base], &next, &delta).unwrap();
+        assert_eq!(stats.changed, 2);
+        let names: Vec<String> = read_tar(&delta).into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, vec!["bin/tool", "bin/tool-alias", DELETIONS_ENTRY]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_metadata_change_counts_as_modified() {
+        let dir = scratch_dir("metadata");
+        let base = dir.join("base.tar");
+        let next = dir.join("next.tar");
+        let delta = dir.join("delta.tar");
+        write_tar(&base, &[Item::File("etc/shadow", b"x")]);
+
+        let mut builder = Builder::new(File::create(&next).unwrap());
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Regular);
+        header.set_mtime(1_700_000_000);
+        header.set_mode(0o600);
+        header.set_size(1);
+        builder
+            .append_data(&mut header, "etc/shadow", b"x".as_slice())
+            .unwrap();
+        builder.finish().unwrap();
+
+        let stats = build_delta(&[base], &next, &delta).unwrap();
+        assert_eq!(stats.changed, 1);
+        assert_eq!(stats.unchanged, 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn make_snapshot(id: &str, parent: Option<&str>) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: if parent.is_some() {
+                SnapshotType::PseudoIncremental
+            } else {
+                SnapshotType::Full
+            },
+            format: ExportFormat::Tar,
+            file_path: format!("/snaps/{}.tar", id),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: parent.map(|p| SnapshotId::from_string(p.into())),
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_chain_orders_base_first() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id().returning(|id| match id.as_str() {
+            "s2" => Ok(make_snapshot("s2", Some("s1"))),
+            "s1" => Ok(make_snapshot("s1", None)),
+            other => Err(DomainError::SnapshotNotFound(other.into())),
+        });
+
+        let chain = resolve_chain(&repo, &make_snapshot("s3", Some("s2")))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = chain.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["s1", "s2", "s3"]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_chain_detects_cycle() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id()
+            .returning(|_| Ok(make_snapshot("s1", Some("s2"))));
+
+        let result = resolve_chain(&repo, &make_snapshot("s2", Some("s1"))).await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_chain_missing_parent_errors() {
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_by_id()
+            .returning(|id| Err(DomainError::SnapshotNotFound(id.to_string())));
+
+        let result = resolve_chain(&repo, &make_snapshot("s2", Some("s1"))).await;
+        assert!(matches!(result, Err(DomainError::SnapshotError(_))));
+    }
+}
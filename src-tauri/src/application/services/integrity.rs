@@ -0,0 +1,175 @@
+//! SHA-256 checksums of snapshot archives.
+//!
+//! The checksum covers the archive bytes exactly as restore reads them: the
+//! file at `file_path` for file storage, the reassembled chunk stream for
+//! chunked storage. Incremental snapshots are checksummed per layer.
+
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::application::path_utils::existing_local_path;
+use crate::application::services::chunk_store;
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStorage};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+
+/// Outcome of re-hashing a snapshot archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    /// The archive matches its recorded checksum.
+    Intact,
+    /// No checksum was recorded (legacy snapshot); carries the current one.
+    Unrecorded(String),
+    /// The archive is not reachable on this machine (e.g. unplugged drive).
+    Unavailable(String),
+    /// The archive differs from its recorded checksum or cannot be read back.
+    Corrupted(String),
+}
+
+/// Hex SHA-256 of the file at `path`. Blocking.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut BufReader::new(std::fs::File::open(path)?), &mut hasher)?;
+    Ok(chunk_store::hex(&hasher.finalize()))
+}
+
+/// Re-hash the archive of `snapshot` and compare it with its recorded checksum.
+pub async fn verify(
+    snapshot_repo: &dyn SnapshotRepositoryPort,
+    snapshot: &Snapshot,
+) -> Result<Verification, DomainError> {
+    let actual = match snapshot.storage {
+        SnapshotStorage::File => {
+            let Some(local) = existing_local_path(&snapshot.file_path) else {
+                return Ok(Verification::Unavailable(format!(
+                    "Snapshot file not found: {}",
+                    snapshot.file_path
+                )));
+            };
+            tokio::task::spawn_blocking(move || sha256_file(Path::new(&local)))
+                .await
+                .map_err(|e| DomainError::Internal(format!("checksum task failed: {}", e)))?
+                .map_err(|e| format!("Cannot read snapshot file: {}", e))
+        }
+        SnapshotStorage::Chunked => {
+            let Some(store) = chunk_store::store_dir_for(&snapshot.file_path) else {
+                return Ok(Verification::Unavailable(format!(
+                    "Snapshot directory of '{}' not found",
+                    snapshot.file_path
+                )));
+            };
+            let manifest = snapshot_repo.get_chunk_manifest(&snapshot.id).await?;
+            if manifest.is_empty() {
+                return Ok(Verification::Corrupted(
+                    "Chunked snapshot has no chunk manifest".into(),
+                ));
+            }
+            tokio::task::spawn_blocking(move || chunk_store::checksum(&manifest, &store))
+                .await
+                .map_err(|e| DomainError::Internal(format!("checksum task failed: {}", e)))?
+                .map_err(|e| e.to_string())
+        }
+    };
+
+    Ok(match (actual, &snapshot.checksum) {
+        (Err(reason), _) => Verification::Corrupted(reason),
+        (Ok(actual), None) => Verification::Unrecorded(actual),
+        (Ok(actual), Some(expected)) if actual.eq_ignore_ascii_case(expected) => {
+            Verification::Intact
+        }
+        (Ok(actual), Some(expected)) => Verification::Corrupted(format!(
+            "Checksum mismatch: expected {}, found {}",
+            expected, actual
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, SnapshotChunk, SnapshotStatus, SnapshotType,
+    };
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn make_snapshot(file_path: &str, checksum: Option<&str>) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-sum".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "s".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: file_path.to_string(),
+            file_size: MemorySize::from_bytes(5),
+            parent_id: None,
+            created_at: chrono::Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: checksum.map(str::to_string),
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_file_outcomes() {
+        let dir = std::env::temp_dir().join("integrity_verify_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("a.tar");
+        std::fs::write(&file, b"hello").unwrap();
+        let path = file.to_string_lossy().to_string();
+        let repo = MockSnapshotRepositoryPort::new();
+
+        let intact = make_snapshot(&path, Some(HELLO_SHA256));
+        assert_eq!(verify(&repo, &intact).await.unwrap(), Verification::Intact);
+        assert_eq!(
+            verify(&repo, &make_snapshot(&path, None)).await.unwrap(),
+            Verification::Unrecorded(HELLO_SHA256.into())
+        );
+
+        std::fs::write(&file, b"hellO").unwrap();
+        assert!(matches!(
+            verify(&repo, &intact).await.unwrap(),
+            Verification::Corrupted(reason) if reason.contains("mismatch")
+        ));
+
+        std::fs::remove_file(&file).unwrap();
+        assert!(matches!(
+            verify(&repo, &intact).await.unwrap(),
+            Verification::Unavailable(_)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_verify_chunked_hashes_reassembled_stream() {
+        let dir = std::env::temp_dir().join("integrity_verify_chunked");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("export.tar");
+        std::fs::write(&source, b"hello").unwrap();
+        let manifest: Vec<SnapshotChunk> =
+            chunk_store::store_file(&source, &dir.join(chunk_store::CHUNK_DIR)).unwrap();
+
+        let mut repo = MockSnapshotRepositoryPort::new();
+        repo.expect_get_chunk_manifest()
+            .returning(move |_| Ok(manifest.clone()));
+        let mut snap = make_snapshot(
+            &dir.join("Ubuntu-snap.tar").to_string_lossy(),
+            Some(HELLO_SHA256),
+        );
+        snap.storage = SnapshotStorage::Chunked;
+
+        assert_eq!(verify(&repo, &snap).await.unwrap(), Verification::Intact);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,502 @@
+//! Writers for exported metrics history: CSV, JSON Lines and Arrow IPC.
+//!
+//! Rows are written in batches as they are read from the database, so an
+//! export never holds more than one batch in memory. All three formats share
+//! one column layout per row type, taken from [`ExportRow`].
+
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray, UInt64Array,
+};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::ports::metrics_repository::{AggregatedMetricsPoint, RawMetricsRow};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricsExportFormat {
+    #[serde(rename = "csv")]
+    Csv,
+    #[serde(rename = "jsonl")]
+    JsonLines,
+    /// Arrow IPC file (Feather v2), readable by pandas, polars and pyarrow
+    /// and convertible to Parquet without a schema.
+    #[serde(rename = "arrow")]
+    ArrowIpc,
+}
+
+impl MetricsExportFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            MetricsExportFormat::Csv => "csv",
+            MetricsExportFormat::JsonLines => "jsonl",
+            MetricsExportFormat::ArrowIpc => "arrow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ColumnKind {
+    Text,
+    Timestamp,
+    Float,
+    UInt,
+}
+
+/// One value of a row, typed for the columnar format.
+pub enum Cell<'a> {
+    Text(&'a str),
+    Timestamp(DateTime<Utc>),
+    Float(Option<f64>),
+    UInt(Option<u64>),
+}
+
+/// A row type that can be exported: its columns, in order, and its values.
+/// JSON Lines uses the `Serialize` form, which has the same field names.
+pub trait ExportRow: Serialize {
+    fn columns() -> &'static [(&'static str, ColumnKind)];
+    fn cells(&self) -> Vec<Cell<'_>>;
+}
+
+impl ExportRow for RawMetricsRow {
+    fn columns() -> &'static [(&'static str, ColumnKind)] {
+        use ColumnKind::*;
+        &[
+            ("distro_name", Text),
+            ("timestamp", Timestamp),
+            ("cpu_usage_percent", Float),
+            ("load_avg_1", Float),
+            ("load_avg_5", Float),
+            ("load_avg_15", Float),
+            ("mem_total_bytes", UInt),
+            ("mem_used_bytes", UInt),
+            ("mem_available_bytes", UInt),
+            ("mem_cached_bytes", UInt),
+            ("swap_total_bytes", UInt),
+            ("swap_used_bytes", UInt),
+            ("disk_total_bytes", UInt),
+            ("disk_used_bytes", UInt),
+            ("disk_available_bytes", UInt),
+            ("disk_usage_percent", Float),
+            ("net_rx_bytes", UInt),
+            ("net_tx_bytes", UInt),
+            ("context_switches", UInt),
+            ("disk_io_read_bytes", UInt),
+            ("disk_io_write_bytes", UInt),
+            ("tcp_established", UInt),
+            ("tcp_time_wait", UInt),
+            ("tcp_listen", UInt),
+            ("gpu_utilization", Float),
+            ("gpu_vram_used", UInt),
+            ("gpu_vram_total", UInt),
+        ]
+    }
+
+    fn cells(&self) -> Vec<Cell<'_>> {
+        let count = |value: Option<u32>| Cell::UInt(value.map(u64::from));
+        vec![
+            Cell::Text(&self.distro_name),
+            Cell::Timestamp(self.timestamp),
+            Cell::Float(Some(self.cpu_usage_percent)),
+            Cell::Float(Some(self.load_avg_1)),
+            Cell::Float(Some(self.load_avg_5)),
+            Cell::Float(Some(self.load_avg_15)),
+            Cell::UInt(Some(self.mem_total_bytes)),
+            Cell::UInt(Some(self.mem_used_bytes)),
+            Cell::UInt(Some(self.mem_available_bytes)),
+            Cell::UInt(Some(self.mem_cached_bytes)),
+            Cell::UInt(Some(self.swap_total_bytes)),
+            Cell::UInt(Some(self.swap_used_bytes)),
+            Cell::UInt(Some(self.disk_total_bytes)),
+            Cell::UInt(Some(self.disk_used_bytes)),
+            Cell::UInt(Some(self.disk_available_bytes)),
+            Cell::Float(Some(self.disk_usage_percent)),
+            Cell::UInt(Some(self.net_rx_bytes)),
+            Cell::UInt(Some(self.net_tx_bytes)),
+            Cell::UInt(self.context_switches),
+            Cell::UInt(self.disk_io_read_bytes),
+            Cell::UInt(self.disk_io_write_bytes),
+            count(self.tcp_established),
+            count(self.tcp_time_wait),
+            count(self.tcp_listen),
+            Cell::Float(self.gpu_utilization),
+            Cell::UInt(self.gpu_vram_used),
+            Cell::UInt(self.gpu_vram_total),
+        ]
+    }
+}
+
+/// A bucket of a rollup tier with the distro it belongs to, which
+/// [`AggregatedMetricsPoint`] leaves to the query.
+#[derive(Debug, Clone, Serialize)]
+pub struct DistroMetricsPoint {
+    pub distro_name: String,
+    #[serde(flatten)]
+    pub point: AggregatedMetricsPoint,
+}
+
+impl ExportRow for DistroMetricsPoint {
+    fn columns() -> &'static [(&'static str, ColumnKind)] {
+        use ColumnKind::*;
+        &[
+            ("distro_name", Text),
+            ("period_start", Timestamp),
+            ("period_end", Timestamp),
+            ("sample_count", UInt),
+            ("cpu_min", Float),
+            ("cpu_avg", Float),
+            ("cpu_max", Float),
+            ("mem_used_min", UInt),
+            ("mem_used_avg", UInt),
+            ("mem_used_max", UInt),
+            ("mem_total", UInt),
+            ("disk_min", Float),
+            ("disk_avg", Float),
+            ("disk_max", Float),
+            ("net_rx_total", UInt),
+            ("net_tx_total", UInt),
+            ("net_rx_max_rate", UInt),
+            ("net_tx_max_rate", UInt),
+        ]
+    }
+
+    fn cells(&self) -> Vec<Cell<'_>> {
+        let p = &self.point;
+        vec![
+            Cell::Text(&self.distro_name),
+            Cell::Timestamp(p.period_start),
+            Cell::Timestamp(p.period_end),
+            Cell::UInt(Some(p.sample_count.into())),
+            Cell::Float(Some(p.cpu_min)),
+            Cell::Float(Some(p.cpu_avg)),
+            Cell::Float(Some(p.cpu_max)),
+            Cell::UInt(Some(p.mem_used_min)),
+            Cell::UInt(Some(p.mem_used_avg)),
+            Cell::UInt(Some(p.mem_used_max)),
+            Cell::UInt(Some(p.mem_total)),
+            Cell::Float(Some(p.disk_min)),
+            Cell::Float(Some(p.disk_avg)),
+            Cell::Float(Some(p.disk_max)),
+            Cell::UInt(Some(p.net_rx_total)),
+            Cell::UInt(Some(p.net_tx_total)),
+            Cell::UInt(Some(p.net_rx_max_rate)),
+            Cell::UInt(Some(p.net_tx_max_rate)),
+        ]
+    }
+}
+
+fn arrow_schema<R: ExportRow>() -> Schema {
+    let fields: Vec<Field> = R::columns()
+        .iter()
+        .map(|&(name, kind)| match kind {
+            ColumnKind::Text => Field::new(name, DataType::Utf8, false),
+            ColumnKind::Timestamp => Field::new(
+                name,
+                DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+                false,
+            ),
+            ColumnKind::Float => Field::new(name, DataType::Float64, true),
+            ColumnKind::UInt => Field::new(name, DataType::UInt64, true),
+        })
+        .collect();
+    Schema::new(fields)
+}
+
+fn arrow_error(e: arrow_schema::ArrowError) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// `value` as a CSV field, quoted when it holds a separator, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_cell(cell: &Cell<'_>) -> String {
+    match cell {
+        Cell::Text(text) => csv_field(text),
+        Cell::Timestamp(at) => at.to_rfc3339_opts(SecondsFormat::Millis, true),
+        Cell::Float(value) => value.map(|v| v.to_string()).unwrap_or_default(),
+        Cell::UInt(value) => value.map(|v| v.to_string()).unwrap_or_default(),
+    }
+}
+
+enum Sink<W: Write + Send> {
+    Csv(W),
+    JsonLines(W),
+    ArrowIpc(Box<FileWriter<W>>),
+}
+
+/// Writes batches of rows of type `R` in one of the export formats.
+pub struct ExportWriter<W: Write + Send, R: ExportRow> {
+    sink: Sink<W>,
+    rows: u64,
+    _row: std::marker::PhantomData<fn(&R)>,
+}
+
+impl<W: Write + Send, R: ExportRow> ExportWriter<W, R> {
+    /// Start the file: the CSV header or the Arrow schema.
+    pub fn new(format: MetricsExportFormat, mut out: W) -> io::Result<Self> {
+        let sink = match format {
+            MetricsExportFormat::Csv => {
+                let header: Vec<&str> = R::columns().iter().map(|(name, _)| *name).collect();
+                writeln!(out, "{}", header.join(","))?;
+                Sink::Csv(out)
+            }
+            MetricsExportFormat::JsonLines => Sink::JsonLines(out),
+            MetricsExportFormat::ArrowIpc => Sink::ArrowIpc(Box::new(
+                FileWriter::try_new(out, &arrow_schema::<R>()).map_err(arrow_error)?,
+            )),
+        };
+        Ok(Self {
+            sink,
+            rows: 0,
+            _row: std::marker::PhantomData,
+        })
+    }
+
+    /// Append a batch of rows; each Arrow batch becomes one record batch.
+    pub fn write_batch(&mut self, rows: &[R]) -> io::Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        match &mut self.sink {
+            Sink::Csv(out) => {
+                for row in rows {
+                    let fields: Vec<String> = row.cells().iter().map(csv_cell).collect();
+                    writeln!(out, "{}", fields.join(","))?;
+                }
+            }
+            Sink::JsonLines(out) => {
+                for row in rows {
+                    serde_json::to_writer(&mut *out, row)?;
+                    out.write_all(b"\n")?;
+                }
+            }
+            Sink::ArrowIpc(writer) => {
+                let batch = record_batch(rows)?;
+                writer.write(&batch).map_err(arrow_error)?;
+            }
+        }
+        self.rows += rows.len() as u64;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// End the file (the Arrow footer) and hand back the output.
+    pub fn finish(self) -> io::Result<W> {
+        match self.sink {
+            Sink::Csv(mut out) | Sink::JsonLines(mut out) => {
+                out.flush()?;
+                Ok(out)
+            }
+            Sink::ArrowIpc(mut writer) => {
+                writer.finish().map_err(arrow_error)?;
+                let mut out = writer.into_inner().map_err(arrow_error)?;
+                out.flush()?;
+                Ok(out)
+            }
+        }
+    }
+}
+
+fn record_batch<R: ExportRow>(rows: &[R]) -> io::Result<RecordBatch> {
+    let cells: Vec<Vec<Cell<'_>>> = rows.iter().map(|row| row.cells()).collect();
+    let columns: Vec<ArrayRef> = R::columns()
+        .iter()
+        .enumerate()
+        .map(|(index, &(_, kind))| -> ArrayRef {
+            let values = cells.iter().map(|row| &row[index]);
+            match kind {
+                ColumnKind::Text => {
+                    Arc::new(StringArray::from_iter_values(values.map(
+                        |cell| match cell {
+                            Cell::Text(text) => *text,
+                            _ => "",
+                        },
+                    )))
+                }
+                ColumnKind::Timestamp => Arc::new(
+                    TimestampMillisecondArray::from_iter_values(values.map(|cell| match cell {
+                        Cell::Timestamp(at) => at.timestamp_millis(),
+                        _ => 0,
+                    }))
+                    .with_timezone("UTC"),
+                ),
+                ColumnKind::Float => {
+                    Arc::new(Float64Array::from_iter(values.map(|cell| match cell {
+                        Cell::Float(value) => *value,
+                        _ => None,
+                    })))
+                }
+                ColumnKind::UInt => {
+                    Arc::new(UInt64Array::from_iter(values.map(|cell| match cell {
+                        Cell::UInt(value) => *value,
+                        _ => None,
+                    })))
+                }
+            }
+        })
+        .collect();
+    RecordBatch::try_new(Arc::new(arrow_schema::<R>()), columns).map_err(arrow_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+
+    fn make_raw_row(distro: &str, timestamp: DateTime<Utc>) -> RawMetricsRow {
+        RawMetricsRow {
+            distro_name: distro.to_string(),
+            timestamp,
+            cpu_usage_percent: 12.5,
+            load_avg_1: 0.5,
+            load_avg_5: 0.25,
+            load_avg_15: 0.1,
+            mem_total_bytes: 8_000,
+            mem_used_bytes: 2_000,
+            mem_available_bytes: 6_000,
+            mem_cached_bytes: 500,
+            swap_total_bytes: 0,
+            swap_used_bytes: 0,
+            disk_total_bytes: 100,
+            disk_used_bytes: 40,
+            disk_available_bytes: 60,
+            disk_usage_percent: 40.0,
+            net_rx_bytes: 1_000,
+            net_tx_bytes: 2_000,
+            context_switches: Some(42),
+            disk_io_read_bytes: None,
+            disk_io_write_bytes: None,
+            tcp_established: Some(3),
+            tcp_time_wait: None,
+            tcp_listen: None,
+            gpu_utilization: None,
+            gpu_vram_used: None,
+            gpu_vram_total: None,
+        }
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_792_324_800 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn csv_has_a_header_and_empty_missing_values() {
+        let mut writer =
+            ExportWriter::<Vec<u8>, RawMetricsRow>::new(MetricsExportFormat::Csv, Vec::new())
+                .unwrap();
+        writer
+            .write_batch(&[make_raw_row("Ubuntu, LTS", at(0))])
+            .unwrap();
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("distro_name,timestamp,cpu_usage_percent,"));
+        assert_eq!(lines[0].split(',').count(), RawMetricsRow::columns().len());
+        assert!(lines[1].starts_with("\"Ubuntu, LTS\",2026-10-18T12:00:00.000Z,12.5,"));
+        assert!(lines[1].ends_with(",42,,,3,,,,,"));
+    }
+
+    #[test]
+    fn json_lines_write_one_object_per_row() {
+        let point = DistroMetricsPoint {
+            distro_name: "Ubuntu".to_string(),
+            point: AggregatedMetricsPoint {
+                period_start: at(0),
+                period_end: at(60),
+                sample_count: 30,
+                cpu_min: 1.0,
+                cpu_avg: 2.0,
+                cpu_max: 3.0,
+                mem_used_min: 1,
+                mem_used_avg: 2,
+                mem_used_max: 3,
+                mem_total: 10,
+                disk_min: 1.0,
+                disk_avg: 1.0,
+                disk_max: 1.0,
+                net_rx_total: 5,
+                net_tx_total: 6,
+                net_rx_max_rate: 7,
+                net_tx_max_rate: 8,
+            },
+        };
+        let mut writer = ExportWriter::<Vec<u8>, DistroMetricsPoint>::new(
+            MetricsExportFormat::JsonLines,
+            Vec::new(),
+        )
+        .unwrap();
+        writer.write_batch(&[point.clone(), point.clone()]).unwrap();
+        writer.write_batch(&[point]).unwrap();
+        assert_eq!(writer.rows(), 3);
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["distro_name"], "Ubuntu");
+        assert_eq!(lines[0]["sample_count"], 30);
+        assert_eq!(lines[0]["net_tx_max_rate"], 8);
+    }
+
+    #[test]
+    fn arrow_file_reads_back_batch_by_batch() {
+        let mut writer =
+            ExportWriter::<Vec<u8>, RawMetricsRow>::new(MetricsExportFormat::ArrowIpc, Vec::new())
+                .unwrap();
+        writer
+            .write_batch(&[make_raw_row("Ubuntu", at(0)), make_raw_row("Ubuntu", at(2))])
+            .unwrap();
+        writer
+            .write_batch(&[make_raw_row("Debian", at(0))])
+            .unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let reader =
+            arrow_ipc::reader::FileReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+        assert_eq!(
+            reader.schema().fields().len(),
+            RawMetricsRow::columns().len()
+        );
+        let batches: Vec<RecordBatch> = reader.map(|batch| batch.unwrap()).collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].num_rows(), 2);
+
+        let timestamps = batches[0]
+            .column_by_name("timestamp")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(timestamps.value(1), at(2).timestamp_millis());
+        let distros = batches[1]
+            .column_by_name("distro_name")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(distros.value(0), "Debian");
+        let tcp_time_wait = batches[1]
+            .column_by_name("tcp_time_wait")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert!(tcp_time_wait.is_null(0));
+    }
+}
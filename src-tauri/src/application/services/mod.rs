@@ -1 +1,14 @@
-
+pub mod chunk_store;
+pub mod compression;
+pub mod encryption;
+pub mod incremental;
+pub mod integrity;
+pub mod metrics_export;
+pub mod replication;
+pub mod safety_snapshot;
+pub mod sidecar;
+pub mod snapshot_files;
+pub mod snapshot_reader;
+pub mod snapshot_scheduler;
+pub mod tar_diff;
+pub mod transfer;
@@ -0,0 +1,1014 @@
+//! Offsite replication of snapshot archives to object storage.
+//!
+//! A completed snapshot is uploaded as one object holding exactly the bytes
+//! restore reads (the file, or the reassembled chunk stream), so its recorded
+//! checksum covers the remote copy too. Uploads are multipart: the upload id
+//! and progress are persisted after every part, and an interrupted upload is
+//! resumed from the parts the service still has. A restore whose archive is
+//! gone locally pulls it back down first (see [`SnapshotReplicator::fetch`]).
+
+use std::collections::HashSet;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::application::path_utils::{existing_local_path, windows_to_linux_path};
+use crate::application::services::transfer::{TransferGuard, TransferPhase, TransferRegistry};
+use crate::application::services::{chunk_store, incremental, integrity};
+use crate::domain::entities::replication::{
+    ReplicationStatus, ReplicationTarget, SnapshotReplication, part_size_for,
+};
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStatus, SnapshotStorage};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::replication_repository::ReplicationRepositoryPort;
+use crate::domain::ports::snapshot_replication::{SnapshotReplicationPort, UploadedPart};
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+/// How often the background loop looks for snapshots to replicate.
+const AUTO_REPLICATE_INTERVAL: Duration = Duration::from_secs(300);
+/// Size of the ranges a download is fetched in.
+const DOWNLOAD_RANGE: u64 = 8 * 1024 * 1024;
+
+/// Builds the object store client for a target (the S3 adapter in the app).
+pub type StoreFactory = Arc<
+    dyn Fn(&ReplicationTarget) -> Result<Arc<dyn SnapshotReplicationPort>, DomainError>
+        + Send
+        + Sync,
+>;
+
+pub struct SnapshotReplicator {
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    replication_repo: Arc<dyn ReplicationRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+    factory: StoreFactory,
+    store: RwLock<Option<(ReplicationTarget, Arc<dyn SnapshotReplicationPort>)>>,
+    transfers: Option<Arc<TransferRegistry>>,
+}
+
+/// The archive bytes of a snapshot, read sequentially.
+enum ArchiveSource {
+    File(BufReader<std::fs::File>),
+    Chunks(chunk_store::ChunkReader),
+}
+
+impl ArchiveSource {
+    fn skip(&mut self, n: u64) -> io::Result<()> {
+        match self {
+            Self::File(f) => f.seek(SeekFrom::Current(n as i64)).map(|_| ()),
+            Self::Chunks(c) => io::copy(&mut c.take(n), &mut io::sink()).map(|_| ()),
+        }
+    }
+
+    fn read_part(&mut self, len: u64) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(len as usize);
+        match self {
+            Self::File(f) => f.take(len).read_to_end(&mut buf)?,
+            Self::Chunks(c) => c.take(len).read_to_end(&mut buf)?,
+        };
+        if (buf.len() as u64) < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "archive is shorter than expected",
+            ));
+        }
+        Ok(buf)
+    }
+}
+
+impl SnapshotReplicator {
+    pub fn new(
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        replication_repo: Arc<dyn ReplicationRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+        factory: StoreFactory,
+    ) -> Self {
+        Self {
+            snapshot_repo,
+            replication_repo,
+            audit_logger,
+            factory,
+            store: RwLock::new(None),
+            transfers: None,
+        }
+    }
+
+    /// Report upload progress to `transfers` and make uploads cancellable.
+    pub fn with_transfers(mut self, transfers: Arc<TransferRegistry>) -> Self {
+        self.transfers = Some(transfers);
+        self
+    }
+
+    /// Connect to the persisted target, if any (called once at startup).
+    pub async fn load(&self) -> Result<(), DomainError> {
+        let target = self.replication_repo.get_target().await?;
+        self.connect(target)
+    }
+
+    /// The configured target, if any.
+    pub fn target(&self) -> Option<ReplicationTarget> {
+        self.store
+            .read()
+            .expect("replication store poisoned")
+            .as_ref()
+            .map(|(target, _)| target.clone())
+    }
+
+    /// Replace the target (`None` turns replication off). Existing replicas
+    /// stay where they are.
+    pub async fn configure(&self, target: Option<ReplicationTarget>) -> Result<(), DomainError> {
+        if let Some(t) = &target {
+            for (field, value) in [
+                ("Endpoint", &t.endpoint),
+                ("Region", &t.region),
+                ("Bucket", &t.bucket),
+                ("Access key id", &t.access_key_id),
+                ("Secret access key", &t.secret_access_key),
+            ] {
+                if value.trim().is_empty() {
+                    return Err(DomainError::ConfigError(format!("{} is required", field)));
+                }
+            }
+            // Fails on an unusable endpoint before anything is saved.
+            (self.factory)(t)?;
+        }
+        self.replication_repo.save_target(target.clone()).await?;
+        let details = target
+            .as_ref()
+            .map(|t| format!("{} bucket={} prefix={}", t.endpoint, t.bucket, t.prefix))
+            .unwrap_or_else(|| "disabled".to_string());
+        self.connect(target)?;
+        self.audit_logger
+            .log_with_details("replication.configure", "replication", &details)
+            .await
+    }
+
+    fn connect(&self, target: Option<ReplicationTarget>) -> Result<(), DomainError> {
+        let store = target
+            .map(|t| (self.factory)(&t).map(|store| (t, store)))
+            .transpose()?;
+        *self.store.write().expect("replication store poisoned") = store;
+        Ok(())
+    }
+
+    fn store(&self) -> Result<(ReplicationTarget, Arc<dyn SnapshotReplicationPort>), DomainError> {
+        self.store
+            .read()
+            .expect("replication store poisoned")
+            .clone()
+            .ok_or_else(|| DomainError::ReplicationError("Replication is not configured".into()))
+    }
+
+    /// Replication state of every snapshot that has one.
+    pub async fn list(&self) -> Result<Vec<SnapshotReplication>, DomainError> {
+        self.replication_repo.list().await
+    }
+
+    /// Upload the archive of a completed snapshot, resuming an interrupted
+    /// upload when the service still has it. A no-op for a snapshot already
+    /// replicated to the current target.
+    pub async fn replicate(&self, id: &SnapshotId) -> Result<SnapshotReplication, DomainError> {
+        let (target, store) = self.store()?;
+        let snapshot = self.snapshot_repo.get_by_id(id).await?;
+        if !matches!(snapshot.status, SnapshotStatus::Completed) {
+            return Err(DomainError::SnapshotError(format!(
+                "Only completed snapshots can be replicated ('{}' is {:?})",
+                snapshot.id, snapshot.status
+            )));
+        }
+        let guard = self.transfers.as_ref().map(|t| t.begin(id)).transpose()?;
+
+        let (source, total) = self.open_archive(&snapshot).await?;
+        let key = target.object_key(snapshot.distro_name.as_str(), &file_name(&snapshot));
+        let previous = self
+            .replication_repo
+            .get(id)
+            .await?
+            .filter(|r| r.object_key == key && r.total_bytes == total);
+
+        if let Some(r) = &previous
+            && r.status == ReplicationStatus::Replicated
+            && store.object_size(&key).await? == Some(total)
+        {
+            return Ok(r.clone());
+        }
+
+        // Resume the previous upload if the service still knows it.
+        let mut resumed = None;
+        if let Some(r) = &previous
+            && let Some(upload_id) = &r.upload_id
+        {
+            match store.list_parts(&key, upload_id).await {
+                Ok(parts) => resumed = Some((upload_id.clone(), r.part_size, parts)),
+                Err(e) => {
+                    tracing::info!("upload {} of {} is gone, restarting: {}", upload_id, id, e)
+                }
+            }
+        }
+        let (upload_id, part_size, listed) = match resumed {
+            Some(r) => r,
+            None => (
+                store.create_multipart_upload(&key).await?,
+                part_size_for(total),
+                Vec::new(),
+            ),
+        };
+        let part_count = total.div_ceil(part_size).max(1) as u32;
+        let expected_size = |n: u32| part_size.min(total - (n as u64 - 1) * part_size);
+        let done: Vec<UploadedPart> = listed
+            .into_iter()
+            .filter(|p| {
+                p.number >= 1 && p.number <= part_count && p.size == expected_size(p.number)
+            })
+            .collect();
+
+        let mut state = SnapshotReplication {
+            snapshot_id: id.clone(),
+            object_key: key.clone(),
+            status: ReplicationStatus::Uploading,
+            upload_id: Some(upload_id.clone()),
+            part_size,
+            bytes_uploaded: done.iter().map(|p| p.size).sum(),
+            total_bytes: total,
+            updated_at: Utc::now(),
+        };
+        self.replication_repo.save(&state).await?;
+        tracing::info!(
+            "replicating {} to {} ({} bytes, {} of {} parts already uploaded)",
+            id,
+            key,
+            total,
+            done.len(),
+            part_count
+        );
+
+        let result = self
+            .upload_parts(
+                &snapshot,
+                store.as_ref(),
+                guard.as_ref(),
+                source,
+                &mut state,
+                done,
+                part_count,
+            )
+            .await;
+        state.updated_at = Utc::now();
+        match result {
+            Ok(()) => {
+                state.status = ReplicationStatus::Replicated;
+                state.upload_id = None;
+                self.replication_repo.save(&state).await?;
+                self.audit_logger
+                    .log_with_details("snapshot.replicate", id.as_str(), &key)
+                    .await?;
+                Ok(state)
+            }
+            Err(e) => {
+                // Keep the upload id: the next attempt resumes from it.
+                state.status = ReplicationStatus::Failed(e.to_string());
+                self.replication_repo.save(&state).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Stream the archive part by part, skipping the parts in `done`, then
+    /// complete the upload.
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_parts(
+        &self,
+        snapshot: &Snapshot,
+        store: &dyn SnapshotReplicationPort,
+        guard: Option<&TransferGuard>,
+        mut source: ArchiveSource,
+        state: &mut SnapshotReplication,
+        mut done: Vec<UploadedPart>,
+        part_count: u32,
+    ) -> Result<(), DomainError> {
+        let upload_id = state.upload_id.clone().unwrap_or_default();
+        let (part_size, total) = (state.part_size, state.total_bytes);
+        let skip: HashSet<u32> = done.iter().map(|p| p.number).collect();
+
+        // Parts are read on a blocking thread, one ahead of the upload.
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<io::Result<(u32, Vec<u8>)>>(1);
+        let reader = tokio::task::spawn_blocking(move || {
+            for number in 1..=part_count {
+                let len = part_size.min(total - (number as u64 - 1) * part_size);
+                let part = if skip.contains(&number) {
+                    source.skip(len).map(|_| None)
+                } else {
+                    source.read_part(len).map(|data| Some((number, data)))
+                };
+                let message = match part {
+                    Ok(None) => continue,
+                    Ok(Some(part)) => Ok(part),
+                    Err(e) => Err(e),
+                };
+                let failed = message.is_err();
+                if tx.blocking_send(message).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        while let Some(part) = rx.recv().await {
+            if let Some(g) = guard {
+                g.check()?;
+            }
+            let (number, data) = part.map_err(|e| {
+                DomainError::SnapshotError(format!(
+                    "Cannot read archive of '{}': {}",
+                    snapshot.id, e
+                ))
+            })?;
+            let size = data.len() as u64;
+            let etag = store
+                .upload_part(&state.object_key, &upload_id, number, data)
+                .await?;
+            done.push(UploadedPart { number, etag, size });
+
+            state.bytes_uploaded += size;
+            state.updated_at = Utc::now();
+            self.replication_repo.save(state).await?;
+            if let Some(g) = guard {
+                g.report(
+                    TransferPhase::Upload,
+                    &snapshot.distro_name,
+                    state.bytes_uploaded,
+                    Some(total),
+                );
+            }
+        }
+        reader
+            .await
+            .map_err(|e| DomainError::Internal(format!("archive reader failed: {}", e)))?;
+
+        done.sort_by_key(|p| p.number);
+        if done.len() != part_count as usize {
+            return Err(DomainError::ReplicationError(format!(
+                "Upload of '{}' is incomplete ({} of {} parts)",
+                snapshot.id,
+                done.len(),
+                part_count
+            )));
+        }
+        store
+            .complete_multipart_upload(&state.object_key, &upload_id, &done)
+            .await
+    }
+
+    /// Open the archive bytes restore would read, and their length.
+    async fn open_archive(&self, snapshot: &Snapshot) -> Result<(ArchiveSource, u64), DomainError> {
+        match snapshot.storage {
+            SnapshotStorage::File => {
+                let local = existing_local_path(&snapshot.file_path).ok_or_else(|| {
+                    DomainError::SnapshotError(format!(
+                        "Snapshot file not found: {}",
+                        snapshot.file_path
+                    ))
+                })?;
+                let file = std::fs::File::open(&local)?;
+                let total = file.metadata()?.len();
+                Ok((ArchiveSource::File(BufReader::new(file)), total))
+            }
+            SnapshotStorage::Chunked => {
+                let manifest = self.snapshot_repo.get_chunk_manifest(&snapshot.id).await?;
+                let store = chunk_store::store_dir_for(&snapshot.file_path)
+                    .filter(|s| s.is_dir())
+                    .ok_or_else(|| {
+                        DomainError::SnapshotError(format!(
+                            "Chunk store of '{}' not found",
+                            snapshot.file_path
+                        ))
+                    })?;
+                let total = manifest.iter().map(|c| c.length).sum();
+                Ok((
+                    ArchiveSource::Chunks(chunk_store::ChunkReader::new(manifest, &store)),
+                    total,
+                ))
+            }
+        }
+    }
+
+    /// Whether `id` has a complete replica on the configured target, i.e.
+    /// whether [`fetch`](Self::fetch) can bring its archive back.
+    pub async fn has_replica(&self, id: &SnapshotId) -> Result<bool, DomainError> {
+        if self.target().is_none() {
+            return Ok(false);
+        }
+        Ok(self
+            .replication_repo
+            .get(id)
+            .await?
+            .is_some_and(|r| r.status == ReplicationStatus::Replicated))
+    }
+
+    /// Mark the replica of `id` stale after its archive was rewritten (e.g.
+    /// re-encrypted): it is never fetched back and the next replication
+    /// uploads the new archive from scratch.
+    pub async fn invalidate(&self, id: &SnapshotId) -> Result<(), DomainError> {
+        let Some(mut replica) = self.replication_repo.get(id).await? else {
+            return Ok(());
+        };
+        replica.status =
+            ReplicationStatus::Failed("Archive changed since it was replicated".into());
+        replica.upload_id = None;
+        replica.bytes_uploaded = 0;
+        replica.updated_at = Utc::now();
+        self.replication_repo.save(&replica).await
+    }
+
+    /// Download every archive of `snapshot`'s chain that is missing locally
+    /// from its replica. Returns whether anything was fetched; a snapshot whose
+    /// archives are all present is left alone. Downloads resume from a
+    /// previous partial file and are verified against the recorded checksum.
+    pub async fn fetch(
+        &self,
+        snapshot: &Snapshot,
+        guard: Option<&TransferGuard>,
+    ) -> Result<bool, DomainError> {
+        let chain = if snapshot.parent_id.is_some() {
+            incremental::resolve_chain(self.snapshot_repo.as_ref(), snapshot).await?
+        } else {
+            vec![snapshot.clone()]
+        };
+        let mut fetched = false;
+        for layer in chain {
+            if is_available(&layer) {
+                continue;
+            }
+            self.fetch_layer(layer, guard).await?;
+            fetched = true;
+        }
+        Ok(fetched)
+    }
+
+    async fn fetch_layer(
+        &self,
+        mut snapshot: Snapshot,
+        guard: Option<&TransferGuard>,
+    ) -> Result<(), DomainError> {
+        let replica = self
+            .replication_repo
+            .get(&snapshot.id)
+            .await?
+            .filter(|r| r.status == ReplicationStatus::Replicated)
+            .ok_or_else(|| {
+                DomainError::SnapshotError(format!(
+                    "Archive of snapshot '{}' is missing and it has no replica: {}",
+                    snapshot.id, snapshot.file_path
+                ))
+            })?;
+        let (_, store) = self.store()?;
+        let size = store
+            .object_size(&replica.object_key)
+            .await?
+            .ok_or_else(|| {
+                DomainError::ReplicationError(format!(
+                    "Replica of '{}' not found: {}",
+                    snapshot.id, replica.object_key
+                ))
+            })?;
+
+        let dest = local_destination(&snapshot.file_path);
+        if let Some(dir) = dest.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = PathBuf::from(format!("{}.part", dest.display()));
+        let mut offset = std::fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+        if offset > size {
+            offset = 0;
+        }
+        tracing::info!(
+            "fetching {} ({} bytes, resuming at {}) into {}",
+            replica.object_key,
+            size,
+            offset,
+            dest.display()
+        );
+
+        let mut out = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&partial)?;
+        out.set_len(offset)?;
+        while offset < size {
+            if let Some(g) = guard {
+                g.check()?;
+            }
+            let data = store
+                .read_range(
+                    &replica.object_key,
+                    offset,
+                    DOWNLOAD_RANGE.min(size - offset),
+                )
+                .await?;
+            if data.is_empty() {
+                return Err(DomainError::ReplicationError(format!(
+                    "Replica of '{}' ended early at {} of {} bytes",
+                    snapshot.id, offset, size
+                )));
+            }
+            io::Write::write_all(&mut out, &data)?;
+            offset += data.len() as u64;
+            if let Some(g) = guard {
+                g.report(
+                    TransferPhase::Download,
+                    &snapshot.distro_name,
+                    offset,
+                    Some(size),
+                );
+            }
+        }
+        out.sync_all()?;
+        drop(out);
+
+        if let Some(expected) = snapshot.checksum.clone() {
+            let path = partial.clone();
+            let actual = tokio::task::spawn_blocking(move || integrity::sha256_file(&path))
+                .await
+                .map_err(|e| DomainError::Internal(format!("checksum task failed: {}", e)))??;
+            if actual != expected {
+                let _ = std::fs::remove_file(&partial);
+                return Err(DomainError::ReplicationError(format!(
+                    "Replica of '{}' does not match its checksum",
+                    snapshot.id
+                )));
+            }
+        }
+        std::fs::rename(&partial, &dest)?;
+
+        // The chunk store is gone: the snapshot is a plain file from now on.
+        if snapshot.storage == SnapshotStorage::Chunked {
+            self.snapshot_repo
+                .save_chunk_manifest(&snapshot.id, &[])
+                .await?;
+            snapshot.storage = SnapshotStorage::File;
+            self.snapshot_repo.save(&snapshot).await?;
+        }
+        self.audit_logger
+            .log_with_details("snapshot.fetch", snapshot.id.as_str(), &replica.object_key)
+            .await
+    }
+
+    /// Background loop: while the target has `auto_replicate` on, upload
+    /// completed snapshots that have no finished replica yet.
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(AUTO_REPLICATE_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.replicate_pending().await {
+                tracing::warn!("Automatic replication error: {e}");
+            }
+        }
+    }
+
+    async fn replicate_pending(&self) -> Result<(), DomainError> {
+        if !self.target().is_some_and(|t| t.auto_replicate) {
+            return Ok(());
+        }
+        let replicated: HashSet<SnapshotId> = self
+            .replication_repo
+            .list()
+            .await?
+            .into_iter()
+            .filter(|r| r.status == ReplicationStatus::Replicated)
+            .map(|r| r.snapshot_id)
+            .collect();
+        for snapshot in self.snapshot_repo.list_all().await? {
+            if !matches!(snapshot.status, SnapshotStatus::Completed)
+                || replicated.contains(&snapshot.id)
+                || !is_available(&snapshot)
+            {
+                continue;
+            }
+            if let Err(e) = self.replicate(&snapshot.id).await {
+                tracing::warn!("replication of {} failed: {e}", snapshot.id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the archive restore reads is present on this machine.
+fn is_available(snapshot: &Snapshot) -> bool {
+    match snapshot.storage {
+        SnapshotStorage::File => existing_local_path(&snapshot.file_path).is_some(),
+        SnapshotStorage::Chunked => {
+            chunk_store::store_dir_for(&snapshot.file_path).is_some_and(|s| s.is_dir())
+        }
+    }
+}
+
+/// Name of the archive of `snapshot` (its object key ends with it).
+fn file_name(snapshot: &Snapshot) -> String {
+    match file_name_of(&snapshot.file_path) {
+        "" => format!("{}.{}", snapshot.id, snapshot.format.extension()),
+        name => name.to_string(),
+    }
+}
+
+/// Where `file_path` lives on this machine, even if its folder is gone.
+fn local_destination(file_path: &str) -> PathBuf {
+    if let Some(local) = existing_local_path(file_path) {
+        return PathBuf::from(local);
+    }
+    let parent = file_path
+        .rsplit_once(['/', '\\'])
+        .map(|(dir, _)| dir)
+        .unwrap_or(".");
+    let dir = existing_local_path(parent).unwrap_or_else(|| {
+        if Path::new(parent).is_absolute() {
+            parent.to_string()
+        } else {
+            windows_to_linux_path(parent)
+        }
+    });
+    PathBuf::from(dir).join(file_name_of(file_path))
+}
+
+/// Last component of `file_path`, whichever separator it uses.
+fn file_name_of(file_path: &str) -> &str {
+    file_path.rsplit(['/', '\\']).next().unwrap_or(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::replication::MIN_PART_SIZE;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotType};
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use async_trait::async_trait;
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::Mutex;
+
+    /// Object storage in memory; `fail_part` makes that part's upload fail once.
+    #[derive(Default)]
+    struct MemoryStore {
+        objects: Mutex<HashMap<String, Vec<u8>>>,
+        uploads: Mutex<HashMap<String, BTreeMap<u32, Vec<u8>>>>,
+        uploaded_parts: Mutex<Vec<u32>>,
+        fail_part: Mutex<Option<u32>>,
+    }
+
+    #[async_trait]
+    impl SnapshotReplicationPort for MemoryStore {
+        async fn create_multipart_upload(&self, key: &str) -> Result<String, DomainError> {
+            let id = format!("{}#{}", key, self.uploads.lock().unwrap().len());
+            self.uploads
+                .lock()
+                .unwrap()
+                .insert(id.clone(), BTreeMap::new());
+            Ok(id)
+        }
+
+        async fn list_parts(
+            &self,
+            _key: &str,
+            upload_id: &str,
+        ) -> Result<Vec<UploadedPart>, DomainError> {
+            let uploads = self.uploads.lock().unwrap();
+            let parts = uploads
+                .get(upload_id)
+                .ok_or_else(|| DomainError::ReplicationError("NoSuchUpload".into()))?;
+            Ok(parts
+                .iter()
+                .map(|(n, d)| UploadedPart {
+                    number: *n,
+                    etag: format!("etag-{}", n),
+                    size: d.len() as u64,
+                })
+                .collect())
+        }
+
+        async fn upload_part(
+            &self,
+            _key: &str,
+            upload_id: &str,
+            number: u32,
+            data: Vec<u8>,
+        ) -> Result<String, DomainError> {
+            if self
+                .fail_part
+                .lock()
+                .unwrap()
+                .take_if(|n| *n == number)
+                .is_some()
+            {
+                return Err(DomainError::ReplicationError("connection reset".into()));
+            }
+            self.uploaded_parts.lock().unwrap().push(number);
+            self.uploads
+                .lock()
+                .unwrap()
+                .get_mut(upload_id)
+                .unwrap()
+                .insert(number, data);
+            Ok(format!("etag-{}", number))
+        }
+
+        async fn complete_multipart_upload(
+            &self,
+            key: &str,
+            upload_id: &str,
+            parts: &[UploadedPart],
+        ) -> Result<(), DomainError> {
+            let stored = self.uploads.lock().unwrap().remove(upload_id).unwrap();
+            let object = parts
+                .iter()
+                .flat_map(|p| stored[&p.number].clone())
+                .collect();
+            self.objects.lock().unwrap().insert(key.to_string(), object);
+            Ok(())
+        }
+
+        async fn abort_multipart_upload(
+            &self,
+            _key: &str,
+            upload_id: &str,
+        ) -> Result<(), DomainError> {
+            self.uploads.lock().unwrap().remove(upload_id);
+            Ok(())
+        }
+
+        async fn object_size(&self, key: &str) -> Result<Option<u64>, DomainError> {
+            Ok(self
+                .objects
+                .lock()
+                .unwrap()
+                .get(key)
+                .map(|o| o.len() as u64))
+        }
+
+        async fn read_range(
+            &self,
+            key: &str,
+            offset: u64,
+            len: u64,
+        ) -> Result<Vec<u8>, DomainError> {
+            let objects = self.objects.lock().unwrap();
+            let object = &objects[key];
+            let start = (offset as usize).min(object.len());
+            let end = (offset + len).min(object.len() as u64) as usize;
+            Ok(object[start..end].to_vec())
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("replication_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn target() -> ReplicationTarget {
+        ReplicationTarget {
+            endpoint: "http://localhost:9000".into(),
+            region: "us-east-1".into(),
+            bucket: "snapshots".into(),
+            prefix: "laptop".into(),
+            access_key_id: "ak".into(),
+            secret_access_key: "sk".into(),
+            path_style: true,
+            auto_replicate: false,
+        }
+    }
+
+    fn make_snapshot(file_path: &Path, checksum: Option<String>) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "nightly".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: MemorySize::from_bytes(0),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    /// Replication repository mock backed by one shared state slot.
+    fn replication_repo(
+        state: Arc<Mutex<Option<SnapshotReplication>>>,
+    ) -> MockReplicationRepositoryPort {
+        let mut repo = MockReplicationRepositoryPort::new();
+        let s = state.clone();
+        repo.expect_save().returning(move |r| {
+            *s.lock().unwrap() = Some(r.clone());
+            Ok(())
+        });
+        let s = state.clone();
+        repo.expect_get()
+            .returning(move |_| Ok(s.lock().unwrap().clone()));
+        repo.expect_save_target().returning(|_| Ok(()));
+        repo
+    }
+
+    fn replicator(
+        snapshot: Snapshot,
+        state: Arc<Mutex<Option<SnapshotReplication>>>,
+        store: Arc<MemoryStore>,
+    ) -> SnapshotReplicator {
+        let mut snapshots = MockSnapshotRepositoryPort::new();
+        snapshots
+            .expect_get_by_id()
+            .returning(move |_| Ok(snapshot.clone()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log_with_details().returning(|_, _, _| Ok(()));
+        let factory: StoreFactory =
+            Arc::new(move |_| Ok(store.clone() as Arc<dyn SnapshotReplicationPort>));
+        SnapshotReplicator::new(
+            Arc::new(snapshots),
+            Arc::new(replication_repo(state)),
+            Arc::new(audit),
+            factory,
+        )
+    }
+
+    /// Two and a half parts of distinct bytes.
+    fn archive() -> Vec<u8> {
+        (0..MIN_PART_SIZE * 5 / 2)
+            .map(|i| (i % 251) as u8)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_replicate_resumes_interrupted_upload() {
+        let dir = temp_dir("resume");
+        let file = dir.join("snap.tar");
+        let data = archive();
+        std::fs::write(&file, &data).unwrap();
+
+        let store = Arc::new(MemoryStore::default());
+        *store.fail_part.lock().unwrap() = Some(2);
+        let state = Arc::new(Mutex::new(None));
+        let replicator = replicator(make_snapshot(&file, None), state.clone(), store.clone());
+        replicator.configure(Some(target())).await.unwrap();
+        let id = SnapshotId::from_string("snap-001".into());
+
+        let err = replicator.replicate(&id).await.unwrap_err();
+        assert!(err.to_string().contains("connection reset"));
+        let failed = state.lock().unwrap().clone().unwrap();
+        assert!(matches!(failed.status, ReplicationStatus::Failed(_)));
+        assert_eq!(failed.bytes_uploaded, MIN_PART_SIZE);
+        assert!(failed.upload_id.is_some());
+
+        // The retry only sends the parts the service does not have yet.
+        let done = replicator.replicate(&id).await.unwrap();
+        assert_eq!(done.status, ReplicationStatus::Replicated);
+        assert_eq!(done.upload_id, None);
+        assert_eq!(done.bytes_uploaded, data.len() as u64);
+        assert_eq!(*store.uploaded_parts.lock().unwrap(), [1, 2, 3]);
+        assert_eq!(
+            store.objects.lock().unwrap()["laptop/Ubuntu/snap.tar"],
+            data
+        );
+
+        // Already replicated: nothing is uploaded again.
+        replicator.replicate(&id).await.unwrap();
+        assert_eq!(store.uploaded_parts.lock().unwrap().len(), 3);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_replicate_requires_target_and_completed_snapshot() {
+        let dir = temp_dir("requires");
+        let file = dir.join("snap.tar");
+        std::fs::write(&file, b"data").unwrap();
+        let mut snapshot = make_snapshot(&file, None);
+        snapshot.status = SnapshotStatus::InProgress;
+        let id = snapshot.id.clone();
+
+        let replicator = replicator(
+            snapshot,
+            Arc::new(Mutex::new(None)),
+            Arc::new(MemoryStore::default()),
+        );
+        assert!(matches!(
+            replicator.replicate(&id).await,
+            Err(DomainError::ReplicationError(_))
+        ));
+        replicator.configure(Some(target())).await.unwrap();
+        assert!(matches!(
+            replicator.replicate(&id).await,
+            Err(DomainError::SnapshotError(_))
+        ));
+        assert!(matches!(
+            replicator
+                .configure(Some(ReplicationTarget {
+                    bucket: " ".into(),
+                    ..target()
+                }))
+                .await,
+            Err(DomainError::ConfigError(_))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_restores_missing_archive_and_verifies_it() {
+        let dir = temp_dir("fetch");
+        let file = dir.join("snap.tar");
+        let data = archive();
+        std::fs::write(&file, &data).unwrap();
+        let checksum = integrity::sha256_file(&file).unwrap();
+        let snapshot = make_snapshot(&file, Some(checksum));
+
+        let store = Arc::new(MemoryStore::default());
+        let state = Arc::new(Mutex::new(None));
+        let replicator = replicator(snapshot.clone(), state, store.clone());
+        replicator.configure(Some(target())).await.unwrap();
+        replicator.replicate(&snapshot.id).await.unwrap();
+
+        // Present locally: nothing to do.
+        assert!(!replicator.fetch(&snapshot, None).await.unwrap());
+
+        // Lost locally, with a partial download left from an earlier attempt.
+        std::fs::remove_file(&file).unwrap();
+        std::fs::write(dir.join("snap.tar.part"), &data[..1000]).unwrap();
+        assert!(replicator.fetch(&snapshot, None).await.unwrap());
+        assert_eq!(std::fs::read(&file).unwrap(), data);
+        assert!(!dir.join("snap.tar.part").exists());
+
+        // A tampered replica is rejected and leaves nothing behind.
+        std::fs::remove_file(&file).unwrap();
+        store
+            .objects
+            .lock()
+            .unwrap()
+            .get_mut("laptop/Ubuntu/snap.tar")
+            .unwrap()[0] ^= 0xff;
+        assert!(replicator.fetch(&snapshot, None).await.is_err());
+        assert!(!file.exists());
+        assert!(!dir.join("snap.tar.part").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_invalidated_replica_is_uploaded_again() {
+        let dir = temp_dir("invalidate");
+        let file = dir.join("snap.tar");
+        std::fs::write(&file, b"old ciphertext").unwrap();
+        let state = Arc::new(Mutex::new(None));
+        let store = Arc::new(MemoryStore::default());
+        let replicator = replicator(make_snapshot(&file, None), state.clone(), store.clone());
+        replicator.configure(Some(target())).await.unwrap();
+        let id = SnapshotId::from_string("snap-001".into());
+        replicator.replicate(&id).await.unwrap();
+
+        // Rewritten in place with the same size: only the invalidation tells.
+        std::fs::write(&file, b"new ciphertext").unwrap();
+        replicator.invalidate(&id).await.unwrap();
+        let stale = state.lock().unwrap().clone().unwrap();
+        assert!(matches!(stale.status, ReplicationStatus::Failed(_)));
+        assert_eq!(stale.upload_id, None);
+
+        replicator.replicate(&id).await.unwrap();
+        assert_eq!(
+            store.objects.lock().unwrap()["laptop/Ubuntu/snap.tar"],
+            b"new ciphertext"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_without_replica_fails() {
+        let dir = temp_dir("no_replica");
+        let snapshot = make_snapshot(&dir.join("gone.tar"), None);
+        let replicator = replicator(
+            snapshot.clone(),
+            Arc::new(Mutex::new(None)),
+            Arc::new(MemoryStore::default()),
+        );
+        replicator.configure(Some(target())).await.unwrap();
+
+        let err = replicator.fetch(&snapshot, None).await.unwrap_err();
+        assert!(err.to_string().contains("no replica"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_local_destination_keeps_file_name() {
+        let dir = temp_dir("dest");
+        let path = dir.join("missing").join("a.tar");
+        assert_eq!(local_destination(&path.to_string_lossy()), path);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
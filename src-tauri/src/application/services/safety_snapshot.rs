@@ -0,0 +1,435 @@
+//! Safety snapshots taken before destructive operations.
+//!
+//! When a distro's [`SafetySnapshotPolicy`] guards an operation (deleting the
+//! distro, overwriting it with a restore, resizing or compacting its VHDX),
+//! the operation first exports a snapshot tagged `safety` and
+//! `safety:<operation>` and logs it as `snapshot.safety`, naming the audit
+//! action of the operation it protects. The distro's older safety snapshots
+//! are then pruned by the policy's own short retention. If the safety
+//! snapshot fails, the operation is not run.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::application::commands::create_snapshot::{CreateSnapshotCommand, CreateSnapshotHandler};
+use crate::application::commands::delete_snapshot::{DeleteSnapshotCommand, DeleteSnapshotHandler};
+use crate::application::commands::update_snapshot_metadata::{
+    UpdateSnapshotMetadataCommand, UpdateSnapshotMetadataHandler,
+};
+use crate::application::services::transfer::TransferRegistry;
+use crate::domain::entities::safety_snapshot::{
+    DestructiveOperation, SAFETY_TAG, SafetySnapshotPolicy,
+};
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStorage};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::safety_policy_repository::SafetyPolicyRepositoryPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::ports::wsl_manager::WslManagerPort;
+use crate::domain::services::snapshot_retention;
+use crate::domain::value_objects::DistroName;
+
+pub struct SafetySnapshotGuard {
+    wsl_manager: Arc<dyn WslManagerPort>,
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    policy_repo: Arc<dyn SafetyPolicyRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+    transfers: Option<Arc<TransferRegistry>>,
+}
+
+impl SafetySnapshotGuard {
+    pub fn new(
+        wsl_manager: Arc<dyn WslManagerPort>,
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        policy_repo: Arc<dyn SafetyPolicyRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            wsl_manager,
+            snapshot_repo,
+            policy_repo,
+            audit_logger,
+            transfers: None,
+        }
+    }
+
+    /// Report progress of safety exports and make them cancellable.
+    pub fn with_transfers(mut self, transfers: Arc<TransferRegistry>) -> Self {
+        self.transfers = Some(transfers);
+        self
+    }
+
+    /// Take a safety snapshot of `distro` before `operation` if its policy
+    /// guards that operation. Returns the snapshot, `None` when the
+    /// operation is not guarded. An error means the operation must not run.
+    #[tracing::instrument(skip(self), fields(distro = %distro, operation = operation.as_str()))]
+    pub async fn protect(
+        &self,
+        distro: &DistroName,
+        operation: DestructiveOperation,
+    ) -> Result<Option<Snapshot>, DomainError> {
+        let Some(policy) = self.policy_repo.get(distro.as_str()).await? else {
+            return Ok(None);
+        };
+        if !policy.guards(operation) {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let mut create = CreateSnapshotHandler::new(
+            self.wsl_manager.clone(),
+            self.snapshot_repo.clone(),
+            self.audit_logger.clone(),
+        );
+        if let Some(transfers) = &self.transfers {
+            create = create.with_transfers(transfers.clone());
+        }
+        let snapshot = create
+            .handle(CreateSnapshotCommand {
+                distro_name: distro.clone(),
+                name: format!(
+                    "safety {} {}",
+                    operation.as_str(),
+                    now.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                ),
+                description: Some(format!(
+                    "Automatic safety snapshot before {}",
+                    operation.audit_action()
+                )),
+                format: policy.format.clone(),
+                output_dir: policy.output_dir.clone(),
+                parent_id: None,
+                storage: SnapshotStorage::File,
+                encryption: None,
+            })
+            .await
+            .map_err(|e| {
+                DomainError::SnapshotError(format!(
+                    "Safety snapshot of '{}' before {} failed, so it was not run: {}",
+                    distro,
+                    operation.as_str(),
+                    e
+                ))
+            })?;
+
+        // Deleting a distro may delete its snapshots too: pinning keeps the
+        // safety snapshot of a deleted distro (retention skips pinned ones).
+        let snapshot = UpdateSnapshotMetadataHandler::new(
+            self.snapshot_repo.clone(),
+            self.audit_logger.clone(),
+        )
+        .handle(UpdateSnapshotMetadataCommand {
+            snapshot_id: snapshot.id.clone(),
+            name: None,
+            description: None,
+            tags: Some(vec![SAFETY_TAG.to_string(), operation.tag()]),
+            pinned: (operation == DestructiveOperation::DeleteDistro).then_some(true),
+        })
+        .await?;
+
+        self.audit_logger
+            .log_with_details(
+                "snapshot.safety",
+                &snapshot.id.to_string(),
+                &format!("Before {} of '{}'", operation.audit_action(), distro),
+            )
+            .await?;
+        tracing::info!("safety snapshot {} taken", snapshot.id);
+
+        if let Err(e) = self.apply_retention(&policy, now).await {
+            tracing::warn!("safety snapshot retention of '{}' failed: {e}", distro);
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Delete the safety snapshots of the policy's distro that it no longer
+    /// keeps. Returns how many were deleted.
+    async fn apply_retention(
+        &self,
+        policy: &SafetySnapshotPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<u32, DomainError> {
+        let snapshots = self
+            .snapshot_repo
+            .list_by_distro(&policy.distro_name)
+            .await?;
+        let delete =
+            DeleteSnapshotHandler::new(self.snapshot_repo.clone(), self.audit_logger.clone());
+        let mut deleted = 0;
+        for snapshot_id in snapshot_retention::expired_safety_snapshots(&snapshots, policy, now) {
+            match delete
+                .handle(DeleteSnapshotCommand {
+                    snapshot_id: snapshot_id.clone(),
+                })
+                .await
+            {
+                Ok(()) => deleted += 1,
+                Err(e) => tracing::warn!("safety retention kept snapshot {snapshot_id}: {e}"),
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+/// Audit details of a guarded operation, naming its safety snapshot if one
+/// was taken.
+pub fn with_safety_note(details: String, safety: Option<&Snapshot>) -> String {
+    match safety {
+        Some(snapshot) => format!("{} (safety snapshot {})", details, snapshot.id),
+        None => details,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotStatus, SnapshotType};
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::value_objects::{MemorySize, SnapshotId};
+    use std::sync::Mutex;
+
+    fn make_policy(
+        operations: Vec<DestructiveOperation>,
+        output_dir: &str,
+    ) -> SafetySnapshotPolicy {
+        SafetySnapshotPolicy {
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            operations,
+            format: ExportFormat::Tar,
+            output_dir: output_dir.into(),
+            keep_last: 1,
+            max_age_hours: 0,
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn old_safety_snapshot() -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("old-safety".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "safety resize_vhd".into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: "/nonexistent/old-safety.tar".into(),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at: Utc::now() - chrono::Duration::hours(5),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: vec![SAFETY_TAG.into(), DestructiveOperation::ResizeVhd.tag()],
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn write_tar(path: &str) {
+        let mut builder = tar::Builder::new(std::fs::File::create(path).unwrap());
+        let mut header = tar::Header::new_ustar();
+        header.set_size(3);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "etc/hostname", b"box".as_slice())
+            .unwrap();
+        builder.into_inner().unwrap();
+    }
+
+    fn exporting_wsl() -> MockWslManagerPort {
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_default_user().returning(|_| Ok(None));
+        wsl.expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl.expect_terminate_distro().returning(|_| Ok(()));
+        wsl.expect_shutdown_all().returning(|| Ok(()));
+        wsl.expect_start_distro().returning(|_| Ok(()));
+        wsl.expect_export_distro().returning(|_, path, _, _| {
+            write_tar(path);
+            Ok(())
+        });
+        wsl
+    }
+
+    fn guard(
+        wsl: MockWslManagerPort,
+        snapshots: MockSnapshotRepositoryPort,
+        policy: Option<SafetySnapshotPolicy>,
+        audit: MockAuditLoggerPort,
+    ) -> SafetySnapshotGuard {
+        let mut policies = MockSafetyPolicyRepositoryPort::new();
+        policies.expect_get().returning(move |_| Ok(policy.clone()));
+        SafetySnapshotGuard::new(
+            Arc::new(wsl),
+            Arc::new(snapshots),
+            Arc::new(policies),
+            Arc::new(audit),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_unguarded_operations_take_no_snapshot() {
+        let ubuntu = DistroName::new("Ubuntu").unwrap();
+        // Any export or save would panic on these mocks.
+        let without_policy = guard(
+            MockWslManagerPort::new(),
+            MockSnapshotRepositoryPort::new(),
+            None,
+            MockAuditLoggerPort::new(),
+        );
+        assert!(
+            without_policy
+                .protect(&ubuntu, DestructiveOperation::DeleteDistro)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let other_operation = guard(
+            MockWslManagerPort::new(),
+            MockSnapshotRepositoryPort::new(),
+            Some(make_policy(vec![DestructiveOperation::ResizeVhd], "/tmp")),
+            MockAuditLoggerPort::new(),
+        );
+        assert!(
+            other_operation
+                .protect(&ubuntu, DestructiveOperation::CompactVhdx)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_safety_snapshot_is_tagged_audited_and_pruned() {
+        let dir = std::env::temp_dir().join("safety_snapshot_guard");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let saved: Arc<Mutex<Option<Snapshot>>> = Arc::default();
+        let mut snapshots = MockSnapshotRepositoryPort::new();
+        let s = saved.clone();
+        snapshots.expect_save().returning(move |snap| {
+            *s.lock().unwrap() = Some(snap.clone());
+            Ok(())
+        });
+        let s = saved.clone();
+        snapshots.expect_get_by_id().returning(move |id| {
+            if id.as_str() == "old-safety" {
+                return Ok(old_safety_snapshot());
+            }
+            Ok(s.lock().unwrap().clone().unwrap())
+        });
+        let s = saved.clone();
+        snapshots.expect_list_by_distro().returning(move |_| {
+            Ok(vec![
+                old_safety_snapshot(),
+                s.lock().unwrap().clone().unwrap(),
+            ])
+        });
+        snapshots
+            .expect_delete()
+            .withf(|id| id.as_str() == "old-safety")
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let actions: Arc<Mutex<Vec<(String, String)>>> = Arc::default();
+        let mut audit = MockAuditLoggerPort::new();
+        let a = actions.clone();
+        audit.expect_log().returning(move |action, _| {
+            a.lock().unwrap().push((action.into(), String::new()));
+            Ok(())
+        });
+        let a = actions.clone();
+        audit
+            .expect_log_with_details()
+            .returning(move |action, _, details| {
+                a.lock().unwrap().push((action.into(), details.into()));
+                Ok(())
+            });
+
+        let guard = guard(
+            exporting_wsl(),
+            snapshots,
+            Some(make_policy(
+                vec![DestructiveOperation::ResizeVhd],
+                &dir.to_string_lossy(),
+            )),
+            audit,
+        );
+        let snapshot = guard
+            .protect(
+                &DistroName::new("Ubuntu").unwrap(),
+                DestructiveOperation::ResizeVhd,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(snapshot.tags, vec!["safety", "safety:resize_vhd"]);
+        assert!(!snapshot.pinned);
+        let actions = actions.lock().unwrap();
+        let safety = actions
+            .iter()
+            .find(|(action, _)| action == "snapshot.safety")
+            .unwrap();
+        assert_eq!(safety.1, "Before vhdx.resize of 'Ubuntu'");
+        assert!(
+            actions
+                .iter()
+                .any(|(action, _)| action == "snapshot.delete")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_failed_safety_snapshot_blocks_the_operation() {
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_default_user().returning(|_| Ok(None));
+        wsl.expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl.expect_terminate_distro().returning(|_| Ok(()));
+        wsl.expect_shutdown_all().returning(|| Ok(()));
+        wsl.expect_start_distro().returning(|_| Ok(()));
+        wsl.expect_export_distro()
+            .returning(|_, _, _, _| Err(DomainError::WslCliError("disk full".into())));
+        let mut snapshots = MockSnapshotRepositoryPort::new();
+        snapshots.expect_save().returning(|_| Ok(()));
+        snapshots.expect_delete().never();
+
+        let guard = guard(
+            wsl,
+            snapshots,
+            Some(make_policy(
+                vec![DestructiveOperation::DeleteDistro],
+                "/tmp",
+            )),
+            MockAuditLoggerPort::new(),
+        );
+        let err = guard
+            .protect(
+                &DistroName::new("Ubuntu").unwrap(),
+                DestructiveOperation::DeleteDistro,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("was not run"), "{err}");
+    }
+
+    #[test]
+    fn test_safety_note_names_the_snapshot() {
+        assert_eq!(
+            with_safety_note("Resized to 20GB".into(), None),
+            "Resized to 20GB"
+        );
+        let snapshot = old_safety_snapshot();
+        assert_eq!(
+            with_safety_note("Resized to 20GB".into(), Some(&snapshot)),
+            "Resized to 20GB (safety snapshot old-safety)"
+        );
+    }
+}
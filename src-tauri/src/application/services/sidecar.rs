@@ -0,0 +1,227 @@
+//! Sidecar manifests: a JSON copy of a snapshot's catalog entry kept next to
+//! its archive as `<file><SIDECAR_SUFFIX>`.
+//!
+//! They make a snapshot folder self-describing: `rescan_snapshots` rebuilds
+//! the catalog from them, and a folder shared between machines can be
+//! imported by each machine's catalog. Chunked snapshots carry their chunk
+//! manifest, which also keeps their chunks alive during another catalog's GC.
+//!
+//! Sidecars are small and written synchronously; callers treat failures as
+//! warnings, the SQLite catalog stays the source of truth.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::application::path_utils::existing_local_path;
+use crate::domain::entities::snapshot::{Snapshot, SnapshotChunk};
+use crate::domain::errors::DomainError;
+
+/// Appended to the archive file name to form its sidecar's name.
+pub const SIDECAR_SUFFIX: &str = ".snapshot.json";
+
+/// Bumped when the sidecar layout changes incompatibly.
+pub const SIDECAR_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSidecar {
+    pub version: u32,
+    /// Catalog entry at the time of writing. `file_path` is informational:
+    /// on import the archive next to the sidecar wins.
+    pub snapshot: Snapshot,
+    /// Chunk manifest of a chunked snapshot; empty for file storage.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<SnapshotChunk>,
+}
+
+/// Sidecar path for the archive stored at `file_path`.
+pub fn sidecar_path(file_path: &str) -> String {
+    format!("{}{}", file_path, SIDECAR_SUFFIX)
+}
+
+/// Archive described by the sidecar at `path`, or `None` if `path` is not a sidecar.
+pub fn archive_path(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let archive = name.strip_suffix(SIDECAR_SUFFIX)?;
+    (!archive.is_empty()).then(|| path.with_file_name(archive))
+}
+
+/// Write (or replace) the sidecar of `snapshot`. The snapshot folder must exist.
+pub fn write(snapshot: &Snapshot, chunks: &[SnapshotChunk]) -> Result<(), DomainError> {
+    let file_path = Path::new(&snapshot.file_path);
+    let file_name = file_path
+        .file_name()
+        .ok_or_else(|| {
+            DomainError::SnapshotError(format!("Invalid snapshot path: {}", snapshot.file_path))
+        })?
+        .to_string_lossy()
+        .to_string();
+    let parent = file_path
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dir = existing_local_path(&parent).ok_or_else(|| {
+        DomainError::SnapshotError(format!("Snapshot folder not found: {}", parent))
+    })?;
+
+    let sidecar = SnapshotSidecar {
+        version: SIDECAR_VERSION,
+        snapshot: snapshot.clone(),
+        chunks: chunks.to_vec(),
+    };
+    let json = serde_json::to_vec_pretty(&sidecar)
+        .map_err(|e| DomainError::Internal(format!("sidecar serialization failed: {}", e)))?;
+
+    // Write then rename, so a reader on another machine never sees half a file.
+    let target = Path::new(&dir).join(format!("{}{}", file_name, SIDECAR_SUFFIX));
+    let tmp = target.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, &target)?;
+    Ok(())
+}
+
+/// Read the sidecar at `path`.
+pub fn read(path: &Path) -> Result<SnapshotSidecar, DomainError> {
+    let bytes = std::fs::read(path)?;
+    let sidecar: SnapshotSidecar = serde_json::from_slice(&bytes).map_err(|e| {
+        DomainError::SnapshotError(format!("Invalid sidecar '{}': {}", path.display(), e))
+    })?;
+    if sidecar.version > SIDECAR_VERSION {
+        return Err(DomainError::SnapshotError(format!(
+            "Sidecar '{}' has unsupported version {}",
+            path.display(),
+            sidecar.version
+        )));
+    }
+    Ok(sidecar)
+}
+
+/// Best-effort removal of the sidecar of the archive stored at `file_path`.
+pub fn remove(file_path: &str) {
+    if let Some(path) = existing_local_path(&sidecar_path(file_path))
+        && let Err(e) = std::fs::remove_file(&path)
+    {
+        tracing::warn!("failed to remove sidecar {}: {}", path, e);
+    }
+}
+
+/// Chunk hashes listed by the sidecars in `dir`; unreadable sidecars are skipped.
+pub fn referenced_chunks(dir: &Path) -> HashSet<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return HashSet::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| archive_path(p).is_some())
+        .filter_map(|p| read(&p).ok())
+        .flat_map(|s| s.chunks.into_iter().map(|c| c.hash))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
+    use chrono::Utc;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sidecar_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn make_snapshot(file_path: &Path, storage: SnapshotStorage) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string("snap-001".into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: "nightly".into(),
+            description: Some("before upgrade".into()),
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::TarZst,
+            file_path: file_path.to_string_lossy().to_string(),
+            file_size: MemorySize::from_bytes(42),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: Some("dev".into()),
+            storage,
+            checksum: Some("ab".repeat(32)),
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    #[test]
+    fn test_archive_path_strips_suffix() {
+        assert_eq!(
+            archive_path(Path::new("/snaps/a.tar.zst.snapshot.json")),
+            Some(PathBuf::from("/snaps/a.tar.zst"))
+        );
+        assert_eq!(archive_path(Path::new("/snaps/a.tar")), None);
+        assert_eq!(archive_path(Path::new("/snaps/.snapshot.json")), None);
+    }
+
+    #[test]
+    fn test_write_read_round_trip() {
+        let dir = scratch_dir("round_trip");
+        let archive = dir.join("Ubuntu-snap-001.tar.zst");
+        let snapshot = make_snapshot(&archive, SnapshotStorage::File);
+
+        write(&snapshot, &[]).unwrap();
+        let path = PathBuf::from(sidecar_path(&snapshot.file_path));
+        assert_eq!(archive_path(&path), Some(archive));
+
+        let sidecar = read(&path).unwrap();
+        assert_eq!(sidecar.version, SIDECAR_VERSION);
+        assert_eq!(sidecar.snapshot.id, snapshot.id);
+        assert_eq!(sidecar.snapshot.checksum, snapshot.checksum);
+        assert_eq!(sidecar.snapshot.default_user.as_deref(), Some("dev"));
+        assert!(sidecar.chunks.is_empty());
+
+        remove(&snapshot.file_path);
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_referenced_chunks_reads_chunked_sidecars() {
+        let dir = scratch_dir("chunks");
+        let snapshot = make_snapshot(&dir.join("a.tar"), SnapshotStorage::Chunked);
+        let chunks = vec![
+            SnapshotChunk {
+                hash: "11".repeat(32),
+                length: 10,
+            },
+            SnapshotChunk {
+                hash: "22".repeat(32),
+                length: 20,
+            },
+        ];
+        write(&snapshot, &chunks).unwrap();
+        std::fs::write(dir.join("broken.tar.snapshot.json"), b"{").unwrap();
+
+        let referenced = referenced_chunks(&dir);
+        assert_eq!(referenced.len(), 2);
+        assert!(referenced.contains(&"11".repeat(32)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_rejects_newer_version() {
+        let dir = scratch_dir("version");
+        let snapshot = make_snapshot(&dir.join("a.tar"), SnapshotStorage::File);
+        let json = serde_json::json!({ "version": SIDECAR_VERSION + 1, "snapshot": snapshot });
+        let path = dir.join("a.tar.snapshot.json");
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        assert!(read(&path).unwrap_err().to_string().contains("version"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,345 @@
+//! Browsing and extracting individual paths of a snapshot.
+//!
+//! Both walk the tar streams of a snapshot's layers (see `snapshot_reader`),
+//! applying each delta's deletion manifest, so the result reflects the
+//! snapshot's final filesystem without rebuilding or extracting the archive.
+//!
+//! Blocking; run through `tokio::task::spawn_blocking` from async handlers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use tar::{Archive, Builder};
+
+use crate::application::services::incremental::{self, DELETIONS_ENTRY};
+use crate::application::services::snapshot_reader::LayerSource;
+use crate::application::services::tar_diff::{self, EntryInfo, EntryKind};
+use crate::domain::errors::DomainError;
+
+/// A path of a snapshot and, for directories, what is below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeNode {
+    pub name: String,
+    pub info: EntryInfo,
+    pub children: Vec<TreeNode>,
+    /// Children were left out because of the depth limit.
+    pub truncated: bool,
+}
+
+/// Archive key of a user-supplied path: `/home/dev/.ssh/` becomes
+/// `home/dev/.ssh`, `/` becomes `.`. Parent components are rejected.
+pub fn normalize_path(path: &str) -> Result<Vec<u8>, DomainError> {
+    let trimmed = path.trim();
+    if trimmed.split('/').any(|c| c == "..") {
+        return Err(DomainError::SnapshotError(format!(
+            "Invalid path '{}': '..' is not allowed",
+            path
+        )));
+    }
+    Ok(incremental::normalize_key(
+        trimmed.trim_start_matches('/').as_bytes(),
+    ))
+}
+
+/// Whether `key` is `root` or below it.
+fn is_under(key: &[u8], root: &[u8]) -> bool {
+    root == b"." || key == root || (key.starts_with(root) && key.get(root.len()) == Some(&b'/'))
+}
+
+/// List the snapshot's filesystem below `root` (an archive key, see
+/// [`normalize_path`]). With `depth`, only that many levels below `root` are
+/// included; directories cut off are marked `truncated`.
+pub fn list_tree(
+    layers: &[LayerSource],
+    root: &[u8],
+    depth: Option<u32>,
+) -> Result<TreeNode, DomainError> {
+    let entries = list_entries(layers, root).map_err(|e| {
+        DomainError::SnapshotError(format!("Failed to read snapshot archive: {}", e))
+    })?;
+    if root != b"." && entries.is_empty() {
+        return Err(DomainError::SnapshotError(format!(
+            "Path '/{}' does not exist in the snapshot",
+            String::from_utf8_lossy(root)
+        )));
+    }
+
+    let root_path = String::from_utf8_lossy(root).into_owned();
+    let mut tree = TreeNode {
+        name: root_path.rsplit('/').next().unwrap_or(".").to_string(),
+        info: entries
+            .get(root)
+            .cloned()
+            .unwrap_or_else(|| implicit_dir(&root_path)),
+        children: Vec::new(),
+        truncated: false,
+    };
+    let prefix_len = if root == b"." { 0 } else { root.len() + 1 };
+    for (key, info) in entries {
+        if key == root {
+            continue;
+        }
+        let relative = String::from_utf8_lossy(&key[prefix_len..]).into_owned();
+        let components: Vec<&str> = relative.split('/').collect();
+        insert(&mut tree, &root_path, &components, info, depth);
+    }
+    Ok(tree)
+}
+
+/// Final state of every path below `root`, sorted by path.
+fn list_entries(layers: &[LayerSource], root: &[u8]) -> io::Result<BTreeMap<Vec<u8>, EntryInfo>> {
+    let mut entries = BTreeMap::new();
+    for layer in layers {
+        let mut archive = Archive::new(layer.open()?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let key = incremental::normalize_key(&entry.path_bytes());
+            if key == DELETIONS_ENTRY.as_bytes() {
+                for deleted in incremental::read_deletions(&mut entry)? {
+                    entries.remove(&deleted);
+                }
+                continue;
+            }
+            if is_under(&key, root) {
+                let info = tar_diff::entry_info(&key, &entry);
+                entries.insert(key, info);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Place `info` at `components` below `node`, creating directories the
+/// archive did not list explicitly.
+fn insert(
+    node: &mut TreeNode,
+    node_path: &str,
+    components: &[&str],
+    info: EntryInfo,
+    depth: Option<u32>,
+) {
+    let Some((name, rest)) = components.split_first() else {
+        return;
+    };
+    if depth == Some(0) {
+        node.truncated = true;
+        return;
+    }
+    let child_path = if node_path == "." {
+        name.to_string()
+    } else {
+        format!("{}/{}", node_path, name)
+    };
+    // Entries arrive sorted, so an existing child is almost always the last one.
+    let index = match node.children.iter().rposition(|c| c.name == *name) {
+        Some(i) => i,
+        None => {
+            node.children.push(TreeNode {
+                name: name.to_string(),
+                info: implicit_dir(&child_path),
+                children: Vec::new(),
+                truncated: false,
+            });
+            node.children.len() - 1
+        }
+    };
+    let child = &mut node.children[index];
+    if rest.is_empty() {
+        child.info = info;
+    } else {
+        insert(child, &child_path, rest, info, depth.map(|d| d - 1));
+    }
+}
+
+fn implicit_dir(path: &str) -> EntryInfo {
+    EntryInfo {
+        path: path.to_string(),
+        kind: EntryKind::Directory,
+        size: 0,
+        mode: 0o755,
+        uid: 0,
+        gid: 0,
+        user: None,
+        group: None,
+        link_target: None,
+    }
+}
+
+/// Write the final version of every entry at or below one of `selection`
+/// (archive keys) to a plain tar at `out`. Returns the number of entries written.
+pub fn extract_paths(
+    layers: &[LayerSource],
+    selection: &[Vec<u8>],
+    out: &Path,
+) -> Result<u64, DomainError> {
+    extract_paths_io(layers, selection, out).map_err(|e| {
+        DomainError::SnapshotError(format!(
+            "Failed to extract files to '{}': {}",
+            out.display(),
+            e
+        ))
+    })
+}
+
+fn extract_paths_io(layers: &[LayerSource], selection: &[Vec<u8>], out: &Path) -> io::Result<u64> {
+    let selected = |key: &[u8]| selection.iter().any(|root| is_under(key, root));
+
+    // First pass: the layer holding the final version of each selected path.
+    let mut owner: HashMap<Vec<u8>, usize> = HashMap::new();
+    for (i, layer) in layers.iter().enumerate() {
+        let mut archive = Archive::new(layer.open()?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let key = incremental::normalize_key(&entry.path_bytes());
+            if key == DELETIONS_ENTRY.as_bytes() {
+                for deleted in incremental::read_deletions(&mut entry)? {
+                    owner.remove(&deleted);
+                }
+            } else if selected(&key) {
+                owner.insert(key, i);
+            }
+        }
+    }
+
+    let mut builder = Builder::new(BufWriter::new(File::create(out)?));
+    let mut written = 0u64;
+    if !owner.is_empty() {
+        for (i, layer) in layers.iter().enumerate() {
+            let mut archive = Archive::new(layer.open()?);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let meta = incremental::read_meta(&mut entry)?;
+                if owner.get(&meta.key) == Some(&i) {
+                    incremental::write_entry(&mut builder, &meta, &mut entry)?;
+                    written += 1;
+                }
+            }
+        }
+    }
+    builder.into_inner()?.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::ExportFormat;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("snapshot_files_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_tar(path: &Path, files: &[(&str, &[u8])]) -> LayerSource {
+        let mut builder = Builder::new(File::create(path).unwrap());
+        for (name, data) in files {
+            let mut header = tar::Header::new_ustar();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o600);
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap();
+        LayerSource::File {
+            path: path.to_path_buf(),
+            format: ExportFormat::Tar,
+            key: None,
+        }
+    }
+
+    fn read_tar(path: &Path) -> Vec<(String, String)> {
+        let mut archive = Archive::new(File::open(path).unwrap());
+        archive
+            .entries()
+            .unwrap()
+            .map(|e| {
+                let mut e = e.unwrap();
+                let mut data = String::new();
+                e.read_to_string(&mut data).unwrap();
+                (e.path().unwrap().to_string_lossy().into_owned(), data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("/home/dev/.ssh/").unwrap(), b"home/dev/.ssh");
+        assert_eq!(normalize_path("/").unwrap(), b".");
+        assert!(normalize_path("/home/../etc").is_err());
+        assert!(is_under(b"home/dev/.ssh/id_rsa", b"home/dev/.ssh"));
+        assert!(!is_under(b"home/dev/.sshx", b"home/dev/.ssh"));
+    }
+
+    #[test]
+    fn test_list_tree_with_depth() {
+        let dir = scratch_dir("tree");
+        let layer = write_tar(
+            &dir.join("a.tar"),
+            &[
+                ("./home/dev/.ssh/id_rsa", b"key"),
+                ("./home/dev/.bashrc", b"rc"),
+                ("./etc/hosts", b"h"),
+            ],
+        );
+
+        let tree = list_tree(std::slice::from_ref(&layer), b".", Some(1)).unwrap();
+        let names: Vec<_> = tree.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["etc", "home"]);
+        assert!(
+            tree.children
+                .iter()
+                .all(|c| c.truncated && c.children.is_empty())
+        );
+
+        let home = list_tree(std::slice::from_ref(&layer), b"home/dev", None).unwrap();
+        assert_eq!(home.name, "dev");
+        let ssh = home.children.iter().find(|c| c.name == ".ssh").unwrap();
+        assert_eq!(ssh.info.kind, EntryKind::Directory);
+        assert_eq!(ssh.children[0].info.path, "home/dev/.ssh/id_rsa");
+        assert_eq!(ssh.children[0].info.size, 3);
+
+        assert!(list_tree(&[layer], b"srv", None).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_paths_uses_latest_layer_and_deletions() {
+        let dir = scratch_dir("extract");
+        let base_path = dir.join("base.tar");
+        let base = write_tar(
+            &base_path,
+            &[
+                ("./home/dev/.ssh/id_rsa", b"old"),
+                ("./home/dev/.ssh/known_hosts", b"kh"),
+                ("./etc/hosts", b"h"),
+            ],
+        );
+        let full_path = dir.join("full.tar");
+        write_tar(
+            &full_path,
+            &[("./home/dev/.ssh/id_rsa", b"new"), ("./etc/hosts", b"h")],
+        );
+        let delta_path = dir.join("delta.tar");
+        incremental::build_delta(std::slice::from_ref(&base_path), &full_path, &delta_path)
+            .unwrap();
+        let delta = LayerSource::File {
+            path: delta_path,
+            format: ExportFormat::Tar,
+            key: None,
+        };
+
+        let out = dir.join("out.tar");
+        let written = extract_paths(&[base, delta], &[b"home/dev/.ssh".to_vec()], &out).unwrap();
+        assert_eq!(written, 1);
+        assert_eq!(
+            read_tar(&out),
+            vec![("home/dev/.ssh/id_rsa".to_string(), "new".to_string())]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
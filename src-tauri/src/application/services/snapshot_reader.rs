@@ -0,0 +1,147 @@
+//! Streaming access to the tar content of a snapshot, whatever its storage.
+//!
+//! A snapshot's filesystem is the tar stream of every layer of its chain
+//! (base first, see `incremental`), each read from its file or reassembled
+//! from the chunk store on the fly, decrypted and decompressed while reading.
+//! Nothing is written to disk.
+//!
+//! [`resolve_layers`] does the async catalog lookups; [`LayerSource::open`]
+//! does blocking I/O and must be run through `tokio::task::spawn_blocking`.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::PathBuf;
+
+use crate::application::path_utils::existing_local_path;
+use crate::application::services::{chunk_store, compression, encryption, incremental};
+use crate::domain::entities::snapshot::{
+    ExportFormat, Snapshot, SnapshotChunk, SnapshotStatus, SnapshotStorage,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::value_objects::EncryptionKey;
+
+/// Where one layer's archive is read from.
+#[derive(Debug, Clone)]
+pub enum LayerSource {
+    File {
+        path: PathBuf,
+        format: ExportFormat,
+        /// Set for encrypted archives.
+        key: Option<EncryptionKey>,
+    },
+    Chunked {
+        store: PathBuf,
+        manifest: Vec<SnapshotChunk>,
+        format: ExportFormat,
+        key: Option<EncryptionKey>,
+    },
+}
+
+impl LayerSource {
+    /// Open the uncompressed tar stream of this layer. Blocking.
+    pub fn open(&self) -> io::Result<Box<dyn Read + Send>> {
+        match self {
+            Self::File { path, format, key } => compression::decoder(
+                format,
+                encryption::reader(key.as_ref(), BufReader::new(File::open(path)?))?,
+            ),
+            Self::Chunked {
+                store,
+                manifest,
+                format,
+                key,
+            } => compression::decoder(
+                format,
+                encryption::reader(
+                    key.as_ref(),
+                    chunk_store::ChunkReader::new(manifest.clone(), store),
+                )?,
+            ),
+        }
+    }
+}
+
+/// Resolve the layers making up the filesystem of `snapshot`, base first.
+/// Fails for VHDX snapshots, unfinished snapshots, archives that are not
+/// reachable on this machine and encrypted layers when `key` is `None`.
+/// `key` must be the one unlocking `snapshot` (see `encryption::unlock_snapshot`).
+pub async fn resolve_layers(
+    snapshot_repo: &dyn SnapshotRepositoryPort,
+    snapshot: &Snapshot,
+    key: Option<&EncryptionKey>,
+) -> Result<Vec<LayerSource>, DomainError> {
+    let chain = if snapshot.parent_id.is_some() {
+        incremental::resolve_chain(snapshot_repo, snapshot).await?
+    } else {
+        vec![snapshot.clone()]
+    };
+
+    let mut layers = Vec::with_capacity(chain.len());
+    for layer in chain {
+        if matches!(layer.format, ExportFormat::Vhd) {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot '{}' is a VHDX disk image; only tar snapshots can be read",
+                layer.id
+            )));
+        }
+        if matches!(
+            layer.status,
+            SnapshotStatus::InProgress | SnapshotStatus::Failed(_)
+        ) {
+            return Err(DomainError::SnapshotError(format!(
+                "Snapshot '{}' is not completed",
+                layer.id
+            )));
+        }
+        // Encrypted snapshots have no incremental chain: the key is the layer's.
+        let key = match (&layer.encryption, key) {
+            (None, _) => None,
+            (Some(_), Some(key)) => Some(key.clone()),
+            (Some(e), None) => {
+                return Err(DomainError::EncryptionError(format!(
+                    "Snapshot '{}' is encrypted; its {} is required",
+                    layer.id,
+                    e.key_source.as_str()
+                )));
+            }
+        };
+        layers.push(match layer.storage {
+            SnapshotStorage::File => {
+                let path = existing_local_path(&layer.file_path).ok_or_else(|| {
+                    DomainError::SnapshotError(format!(
+                        "Snapshot file not found: {}",
+                        layer.file_path
+                    ))
+                })?;
+                LayerSource::File {
+                    path: PathBuf::from(path),
+                    format: layer.format,
+                    key,
+                }
+            }
+            SnapshotStorage::Chunked => {
+                let store = chunk_store::store_dir_for(&layer.file_path).ok_or_else(|| {
+                    DomainError::SnapshotError(format!(
+                        "Snapshot directory of '{}' not found",
+                        layer.file_path
+                    ))
+                })?;
+                let manifest = snapshot_repo.get_chunk_manifest(&layer.id).await?;
+                if manifest.is_empty() {
+                    return Err(DomainError::SnapshotError(format!(
+                        "Chunked snapshot '{}' has no chunk manifest",
+                        layer.id
+                    )));
+                }
+                LayerSource::Chunked {
+                    store,
+                    manifest,
+                    format: layer.format,
+                    key,
+                }
+            }
+        });
+    }
+    Ok(layers)
+}
@@ -0,0 +1,366 @@
+//! Background service that takes scheduled snapshots and prunes them.
+//!
+//! Every minute, each enabled schedule whose current period (hour, day or
+//! ISO week, local time) has no run yet gets a snapshot through
+//! `CreateSnapshotHandler`. Afterwards the schedule's retention policy is
+//! applied to the snapshots it created, deleting expired ones through
+//! `DeleteSnapshotHandler`. Manually created snapshots are never pruned.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::application::commands::create_snapshot::{CreateSnapshotCommand, CreateSnapshotHandler};
+use crate::application::commands::delete_snapshot::{DeleteSnapshotCommand, DeleteSnapshotHandler};
+use crate::application::services::transfer::TransferRegistry;
+use crate::domain::entities::snapshot::Snapshot;
+use crate::domain::entities::snapshot_schedule::SnapshotSchedule;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::schedule_repository::ScheduleRepositoryPort;
+use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::ports::wsl_manager::WslManagerPort;
+use crate::domain::services::snapshot_retention;
+
+const TICK_INTERVAL_SECS: u64 = 60;
+
+/// Serializes scheduled runs: the background loop and `run_snapshot_schedule`
+/// must not export (and shut down WSL) concurrently.
+static RUN_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+pub struct SnapshotScheduler {
+    wsl_manager: Arc<dyn WslManagerPort>,
+    snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+    schedule_repo: Arc<dyn ScheduleRepositoryPort>,
+    audit_logger: Arc<dyn AuditLoggerPort>,
+    transfers: Option<Arc<TransferRegistry>>,
+}
+
+impl SnapshotScheduler {
+    pub fn new(
+        wsl_manager: Arc<dyn WslManagerPort>,
+        snapshot_repo: Arc<dyn SnapshotRepositoryPort>,
+        schedule_repo: Arc<dyn ScheduleRepositoryPort>,
+        audit_logger: Arc<dyn AuditLoggerPort>,
+    ) -> Self {
+        Self {
+            wsl_manager,
+            snapshot_repo,
+            schedule_repo,
+            audit_logger,
+            transfers: None,
+        }
+    }
+
+    /// Report progress of scheduled exports and make them cancellable.
+    pub fn with_transfers(mut self, transfers: Arc<TransferRegistry>) -> Self {
+        self.transfers = Some(transfers);
+        self
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(TICK_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.tick(Utc::now()).await {
+                tracing::warn!("Snapshot scheduler error: {e}");
+            }
+        }
+    }
+
+    /// Run every schedule that is due at `now`, one after another.
+    async fn tick(&self, now: DateTime<Utc>) -> Result<(), DomainError> {
+        for schedule in self.schedule_repo.list(None).await? {
+            if !schedule.is_due(now) {
+                continue;
+            }
+            if let Err(e) = self.run_schedule(&schedule, now).await {
+                tracing::warn!(
+                    "scheduled snapshot of '{}' ({}) failed: {e}",
+                    schedule.distro_name,
+                    schedule.frequency.as_str()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the snapshot of `schedule` now, then apply its retention policy.
+    /// The run is recorded up front, so a failed export is retried in the
+    /// next period rather than every minute.
+    pub async fn run_schedule(
+        &self,
+        schedule: &SnapshotSchedule,
+        now: DateTime<Utc>,
+    ) -> Result<Snapshot, DomainError> {
+        let _guard = RUN_LOCK.lock().await;
+        self.schedule_repo.mark_run(&schedule.id, now).await?;
+
+        let frequency = schedule.frequency.as_str();
+        let mut create = CreateSnapshotHandler::new(
+            self.wsl_manager.clone(),
+            self.snapshot_repo.clone(),
+            self.audit_logger.clone(),
+        );
+        if let Some(transfers) = &self.transfers {
+            create = create.with_transfers(transfers.clone());
+        }
+        let snapshot = create
+            .handle(CreateSnapshotCommand {
+                distro_name: schedule.distro_name.clone(),
+                name: format!(
+                    "{} {}",
+                    frequency,
+                    now.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                ),
+                description: Some(format!("Scheduled {} snapshot", frequency)),
+                format: schedule.format.clone(),
+                output_dir: schedule.output_dir.clone(),
+                parent_id: None,
+                storage: schedule.storage,
+                encryption: None,
+            })
+            .await?;
+        self.schedule_repo
+            .add_snapshot(&schedule.id, &snapshot.id)
+            .await?;
+
+        if let Err(e) = self.apply_retention(schedule).await {
+            tracing::warn!("retention of schedule {} failed: {e}", schedule.id);
+        }
+        Ok(snapshot)
+    }
+
+    /// Delete the snapshots of `schedule` that fall outside its retention
+    /// policy. Returns how many were deleted; a snapshot that cannot be
+    /// deleted (e.g. it is the parent of an incremental snapshot) is skipped.
+    async fn apply_retention(&self, schedule: &SnapshotSchedule) -> Result<u32, DomainError> {
+        if schedule.retention.keeps_everything() {
+            return Ok(0);
+        }
+
+        let mut snapshots = Vec::new();
+        for id in self.schedule_repo.list_snapshots(&schedule.id).await? {
+            match self.snapshot_repo.get_by_id(&id).await {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(DomainError::SnapshotNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let delete =
+            DeleteSnapshotHandler::new(self.snapshot_repo.clone(), self.audit_logger.clone());
+        let mut deleted = 0;
+        for snapshot_id in snapshot_retention::expired_snapshots(&snapshots, &schedule.retention) {
+            match delete
+                .handle(DeleteSnapshotCommand {
+                    snapshot_id: snapshot_id.clone(),
+                })
+                .await
+            {
+                Ok(()) => deleted += 1,
+                Err(e) => tracing::warn!("retention kept snapshot {snapshot_id}: {e}"),
+            }
+        }
+        if deleted > 0 {
+            tracing::info!(
+                "retention of schedule {} deleted {deleted} snapshot(s)",
+                schedule.id
+            );
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::entities::snapshot_schedule::{RetentionPolicy, ScheduleFrequency};
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
+
+    fn make_schedule(
+        retention: RetentionPolicy,
+        last_run_at: Option<DateTime<Utc>>,
+    ) -> SnapshotSchedule {
+        SnapshotSchedule {
+            id: "sched-1".into(),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            frequency: ScheduleFrequency::Hourly,
+            format: ExportFormat::Tar,
+            storage: SnapshotStorage::File,
+            output_dir: "/nonexistent/backups".into(),
+            retention,
+            enabled: true,
+            last_run_at,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn make_snapshot(id: &str, hours_ago: i64) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: format!("/nonexistent/backups/{id}.tar"),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at: Utc::now() - chrono::Duration::hours(hours_ago),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn make_scheduler(
+        snapshot_repo: MockSnapshotRepositoryPort,
+        schedule_repo: MockScheduleRepositoryPort,
+        audit: MockAuditLoggerPort,
+    ) -> SnapshotScheduler {
+        SnapshotScheduler::new(
+            Arc::new(MockWslManagerPort::new()),
+            Arc::new(snapshot_repo),
+            Arc::new(schedule_repo),
+            Arc::new(audit),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_retention_deletes_expired_snapshots_only() {
+        let snapshots = vec![
+            make_snapshot("new", 0),
+            make_snapshot("mid", 1),
+            make_snapshot("old", 2),
+        ];
+        let by_id = snapshots.clone();
+
+        let mut schedule_repo = MockScheduleRepositoryPort::new();
+        schedule_repo
+            .expect_list_snapshots()
+            .returning(move |_| Ok(snapshots.iter().map(|s| s.id.clone()).collect()));
+        let mut snapshot_repo = MockSnapshotRepositoryPort::new();
+        snapshot_repo.expect_get_by_id().returning(move |id| {
+            by_id
+                .iter()
+                .find(|s| &s.id == id)
+                .cloned()
+                .ok_or_else(|| DomainError::SnapshotNotFound(id.to_string()))
+        });
+        snapshot_repo
+            .expect_list_by_distro()
+            .returning(|_| Ok(vec![]));
+        snapshot_repo
+            .expect_delete()
+            .withf(|id| id.as_str() == "old")
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| action == "snapshot.delete" && detail == "old")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let scheduler = make_scheduler(snapshot_repo, schedule_repo, audit);
+        let schedule = make_schedule(
+            RetentionPolicy {
+                keep_hourly: 2,
+                ..Default::default()
+            },
+            None,
+        );
+        assert_eq!(scheduler.apply_retention(&schedule).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retention_skips_snapshots_that_cannot_be_deleted() {
+        // "parent" is expired but a manual incremental snapshot depends on it.
+        let new = make_snapshot("new", 0);
+        let parent = make_snapshot("parent", 1);
+        let mut child = make_snapshot("child", 0);
+        child.parent_id = Some(parent.id.clone());
+        let all = vec![new.clone(), parent.clone(), child];
+        let by_id = all.clone();
+
+        let mut schedule_repo = MockScheduleRepositoryPort::new();
+        schedule_repo
+            .expect_list_snapshots()
+            .returning(move |_| Ok(vec![new.id.clone(), parent.id.clone()]));
+        let mut snapshot_repo = MockSnapshotRepositoryPort::new();
+        snapshot_repo.expect_get_by_id().returning(move |id| {
+            by_id
+                .iter()
+                .find(|s| &s.id == id)
+                .cloned()
+                .ok_or_else(|| DomainError::SnapshotNotFound(id.to_string()))
+        });
+        snapshot_repo
+            .expect_list_by_distro()
+            .returning(move |_| Ok(all.clone()));
+        snapshot_repo.expect_delete().never();
+
+        let scheduler = make_scheduler(snapshot_repo, schedule_repo, MockAuditLoggerPort::new());
+        let schedule = make_schedule(
+            RetentionPolicy {
+                keep_hourly: 1,
+                ..Default::default()
+            },
+            None,
+        );
+        assert_eq!(scheduler.apply_retention(&schedule).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tick_runs_due_schedules_and_records_failed_runs() {
+        let now = Utc::now();
+        let due = make_schedule(RetentionPolicy::default(), None);
+        let mut done = make_schedule(RetentionPolicy::default(), Some(now));
+        done.id = "sched-done".into();
+
+        let mut schedule_repo = MockScheduleRepositoryPort::new();
+        schedule_repo
+            .expect_list()
+            .returning(move |_| Ok(vec![due.clone(), done.clone()]));
+        schedule_repo
+            .expect_mark_run()
+            .withf(|id, _| id == "sched-1")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        schedule_repo.expect_add_snapshot().never();
+        let mut snapshot_repo = MockSnapshotRepositoryPort::new();
+        // The InProgress save fails, so the export never starts.
+        snapshot_repo
+            .expect_save()
+            .times(1)
+            .returning(|_| Err(DomainError::DatabaseError("disk full".into())));
+
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_default_user().returning(|_| Ok(None));
+        wsl.expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+
+        let scheduler = SnapshotScheduler::new(
+            Arc::new(wsl),
+            Arc::new(snapshot_repo),
+            Arc::new(schedule_repo),
+            Arc::new(MockAuditLoggerPort::new()),
+        );
+        assert!(scheduler.tick(now).await.is_ok());
+    }
+}
@@ -0,0 +1,381 @@
+//! Filesystem diff between two snapshots, computed from their tar streams.
+//!
+//! Each side is indexed by streaming its layers (see `snapshot_reader`) and
+//! hashing file contents; nothing is extracted. Entries are compared on kind,
+//! content (SHA-256 and size), link target, permission bits and ownership.
+//! Timestamps are ignored: a file that was only touched is unchanged.
+//!
+//! Blocking; run through `tokio::task::spawn_blocking` from async handlers.
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+use sha2::{Digest, Sha256};
+use tar::{Archive, EntryType};
+
+use crate::application::services::incremental::{self, DELETIONS_ENTRY};
+use crate::application::services::snapshot_reader::LayerSource;
+use crate::domain::errors::DomainError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+    Hardlink,
+    Other,
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::File => "file",
+            Self::Directory => "directory",
+            Self::Symlink => "symlink",
+            Self::Hardlink => "hardlink",
+            Self::Other => "other",
+        }
+    }
+
+    fn of(entry_type: EntryType) -> Self {
+        match entry_type {
+            EntryType::Regular | EntryType::Continuous | EntryType::GNUSparse => Self::File,
+            EntryType::Directory => Self::Directory,
+            EntryType::Symlink => Self::Symlink,
+            EntryType::Link => Self::Hardlink,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Metadata of one path as recorded in a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryInfo {
+    pub path: String,
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Permission bits (`0o7777` mask).
+    pub mode: u32,
+    pub uid: u64,
+    pub gid: u64,
+    pub user: Option<String>,
+    pub group: Option<String>,
+    pub link_target: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedEntry {
+    pub before: EntryInfo,
+    pub after: EntryInfo,
+    /// Kind, size, content or link target differ.
+    pub content_changed: bool,
+    pub mode_changed: bool,
+    pub owner_changed: bool,
+}
+
+/// Paths added, removed and modified going from one snapshot to another,
+/// each sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TarDiff {
+    pub added: Vec<EntryInfo>,
+    pub removed: Vec<EntryInfo>,
+    pub modified: Vec<ModifiedEntry>,
+    pub unchanged: u64,
+}
+
+/// Diff the filesystem described by `from` against the one described by `to`
+/// (both base layer first).
+pub fn diff_layers(from: &[LayerSource], to: &[LayerSource]) -> Result<TarDiff, DomainError> {
+    let index = |layers| {
+        index_layers(layers).map_err(|e| {
+            DomainError::SnapshotError(format!("Failed to read snapshot archive: {}", e))
+        })
+    };
+    Ok(compare(index(from)?, index(to)?))
+}
+
+/// An entry and the SHA-256 of its content (`None` for non-files).
+type Indexed = (EntryInfo, Option<[u8; 32]>);
+
+fn index_layers(layers: &[LayerSource]) -> io::Result<HashMap<Vec<u8>, Indexed>> {
+    let mut index = HashMap::new();
+    for layer in layers {
+        let mut archive = Archive::new(layer.open()?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let key = incremental::normalize_key(&entry.path_bytes());
+            if key == DELETIONS_ENTRY.as_bytes() {
+                for deleted in incremental::read_deletions(&mut entry)? {
+                    index.remove(&deleted);
+                }
+                continue;
+            }
+            let info = entry_info(&key, &entry);
+            let digest = match info.kind {
+                EntryKind::File => Some(sha256(&mut entry)?),
+                _ => None,
+            };
+            index.insert(key, (info, digest));
+        }
+    }
+    Ok(index)
+}
+
+/// Metadata of `entry`, stored under the normalized path `key`.
+pub(crate) fn entry_info<R: Read>(key: &[u8], entry: &tar::Entry<'_, R>) -> EntryInfo {
+    let header = entry.header();
+    EntryInfo {
+        path: String::from_utf8_lossy(key).into_owned(),
+        kind: EntryKind::of(header.entry_type()),
+        size: entry.size(),
+        mode: header.mode().unwrap_or_default() & 0o7777,
+        uid: header.uid().unwrap_or_default(),
+        gid: header.gid().unwrap_or_default(),
+        user: header.username().ok().flatten().map(str::to_string),
+        group: header.groupname().ok().flatten().map(str::to_string),
+        link_target: entry
+            .link_name_bytes()
+            .map(|b| String::from_utf8_lossy(&incremental::normalize_key(&b)).into_owned()),
+    }
+}
+
+fn sha256(content: &mut impl Read) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(content, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+fn compare(mut from: HashMap<Vec<u8>, Indexed>, to: HashMap<Vec<u8>, Indexed>) -> TarDiff {
+    let mut diff = TarDiff::default();
+    for (key, (after, after_digest)) in to {
+        let Some((before, before_digest)) = from.remove(&key) else {
+            diff.added.push(after);
+            continue;
+        };
+        let content_changed = before.kind != after.kind
+            || before.size != after.size
+            || before_digest != after_digest
+            || before.link_target != after.link_target;
+        let mode_changed = before.mode != after.mode;
+        let owner_changed = before.uid != after.uid
+            || before.gid != after.gid
+            || before.user != after.user
+            || before.group != after.group;
+        if content_changed || mode_changed || owner_changed {
+            diff.modified.push(ModifiedEntry {
+                before,
+                after,
+                content_changed,
+                mode_changed,
+                owner_changed,
+            });
+        } else {
+            diff.unchanged += 1;
+        }
+    }
+    diff.removed = from.into_values().map(|(info, _)| info).collect();
+
+    diff.added.sort_by(|a, b| a.path.cmp(&b.path));
+    diff.removed.sort_by(|a, b| a.path.cmp(&b.path));
+    diff.modified
+        .sort_by(|a, b| a.after.path.cmp(&b.after.path));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::compression::Encoder;
+    use crate::domain::entities::snapshot::ExportFormat;
+    use std::path::{Path, PathBuf};
+    use tar::{Builder, Header};
+
+    struct Entry<'a> {
+        path: &'a str,
+        data: &'a [u8],
+        mode: u32,
+        uid: u64,
+    }
+
+    fn file<'a>(path: &'a str, data: &'a [u8]) -> Entry<'a> {
+        Entry {
+            path,
+            data,
+            mode: 0o644,
+            uid: 0,
+        }
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tar_diff_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_tar(path: &Path, format: &ExportFormat, entries: &[Entry]) -> LayerSource {
+        let encoder = Encoder::new(format, std::fs::File::create(path).unwrap()).unwrap();
+        let mut builder = Builder::new(encoder);
+        let mut dir = Header::new_ustar();
+        dir.set_entry_type(EntryType::Directory);
+        dir.set_mode(0o755);
+        dir.set_size(0);
+        builder
+            .append_data(&mut dir, "./etc/", io::empty())
+            .unwrap();
+        for e in entries {
+            let mut header = Header::new_ustar();
+            header.set_size(e.data.len() as u64);
+            header.set_mode(e.mode);
+            header.set_uid(e.uid);
+            header.set_mtime(e.data.len() as u64); // differs between versions
+            builder.append_data(&mut header, e.path, e.data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        LayerSource::File {
+            path: path.to_path_buf(),
+            format: format.clone(),
+            key: None,
+        }
+    }
+
+    fn paths(entries: &[EntryInfo]) -> Vec<&str> {
+        entries.iter().map(|e| e.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_and_modified() {
+        let dir = scratch_dir("basic");
+        let from = write_tar(
+            &dir.join("from.tar"),
+            &ExportFormat::Tar,
+            &[
+                file("./etc/hosts", b"127.0.0.1"),
+                file("./etc/gone", b"bye"),
+                file("./etc/same", b"same"),
+                file("./etc/chmod", b"x"),
+                file("./etc/chown", b"y"),
+            ],
+        );
+        let to = write_tar(
+            &dir.join("to.tar.zst"),
+            &ExportFormat::TarZst,
+            &[
+                file("etc/hosts", b"127.0.0.1 broken"),
+                file("./etc/new", b"hi"),
+                file("./etc/same", b"same"),
+                Entry {
+                    mode: 0o600,
+                    ..file("./etc/chmod", b"x")
+                },
+                Entry {
+                    uid: 1000,
+                    ..file("./etc/chown", b"y")
+                },
+            ],
+        );
+
+        let diff = diff_layers(&[from], &[to]).unwrap();
+        assert_eq!(paths(&diff.added), vec!["etc/new"]);
+        assert_eq!(paths(&diff.removed), vec!["etc/gone"]);
+        assert_eq!(diff.unchanged, 2); // etc/ and etc/same
+
+        let modified: Vec<_> = diff
+            .modified
+            .iter()
+            .map(|m| {
+                (
+                    m.after.path.as_str(),
+                    m.content_changed,
+                    m.mode_changed,
+                    m.owner_changed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            modified,
+            vec![
+                ("etc/chmod", false, true, false),
+                ("etc/chown", false, false, true),
+                ("etc/hosts", true, false, false),
+            ]
+        );
+        assert_eq!(diff.modified[0].before.mode, 0o644);
+        assert_eq!(diff.modified[0].after.mode, 0o600);
+        assert_eq!(diff.modified[2].after.size, 16);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_diff_layers_incremental_chain() {
+        let dir = scratch_dir("chain");
+        let base = write_tar(
+            &dir.join("base.tar"),
+            &ExportFormat::Tar,
+            &[file("./etc/a", b"a"), file("./etc/b", b"b")],
+        );
+        let full = write_tar(
+            &dir.join("full.tar"),
+            &ExportFormat::Tar,
+            &[file("./etc/a", b"a2"), file("./etc/c", b"c")],
+        );
+        let LayerSource::File {
+            path: base_path, ..
+        } = &base
+        else {
+            unreachable!()
+        };
+        let LayerSource::File {
+            path: full_path, ..
+        } = &full
+        else {
+            unreachable!()
+        };
+        let delta_path = dir.join("delta.tar");
+        incremental::build_delta(std::slice::from_ref(base_path), full_path, &delta_path).unwrap();
+        let delta = LayerSource::File {
+            path: delta_path,
+            format: ExportFormat::Tar,
+            key: None,
+        };
+
+        // The chain base+delta describes the same filesystem as the full export.
+        let diff = diff_layers(&[base.clone(), delta], std::slice::from_ref(&full)).unwrap();
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty());
+
+        let diff = diff_layers(&[base], &[full]).unwrap();
+        assert_eq!(paths(&diff.removed), vec!["etc/b"]);
+        assert_eq!(paths(&diff.added), vec!["etc/c"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_diff_reads_encrypted_layers() {
+        use crate::application::services::encryption::EncryptWriter;
+        use crate::domain::value_objects::EncryptionKey;
+        use std::io::Write;
+
+        let dir = scratch_dir("encrypted");
+        let entries = [file("./etc/hosts", b"127.0.0.1")];
+        let plain = write_tar(&dir.join("plain.tar.zst"), &ExportFormat::TarZst, &entries);
+        let LayerSource::File { path, .. } = &plain else {
+            unreachable!()
+        };
+
+        let key = EncryptionKey::from_bytes([3; 32]);
+        let sealed = dir.join("sealed.tar.zst");
+        let mut writer = EncryptWriter::new(&key, std::fs::File::create(&sealed).unwrap()).unwrap();
+        writer.write_all(&std::fs::read(path).unwrap()).unwrap();
+        writer.finish().unwrap();
+        let encrypted = LayerSource::File {
+            path: sealed,
+            format: ExportFormat::TarZst,
+            key: Some(key),
+        };
+
+        let diff = diff_layers(&[plain], &[encrypted]).unwrap();
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.modified.is_empty());
+        assert_eq!(diff.unchanged, 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
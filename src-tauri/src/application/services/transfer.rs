@@ -0,0 +1,306 @@
+//! Progress reporting and cancellation for snapshot exports, imports and
+//! replication transfers.
+//!
+//! `wsl --export` and `wsl --import` report nothing while they run, so a
+//! transfer is watched from the outside: the file being written is polled and
+//! its size compared with an estimate. Cancelling drops the port future, which
+//! kills the wsl.exe child (see `WslManagerPort::export_distro`). Uploads and
+//! downloads advance part by part and report each step themselves.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::application::path_utils::existing_local_path;
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::{DistroName, SnapshotId};
+
+/// How often the growing file is polled.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferPhase {
+    Export,
+    Import,
+    /// Replicating the archive to object storage.
+    Upload,
+    /// Pulling a replicated archive back down.
+    Download,
+}
+
+/// One progress sample of a running transfer.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferProgress {
+    pub snapshot_id: String,
+    pub distro_name: String,
+    pub phase: TransferPhase,
+    /// Current size of the file being written (bytes sent for uploads).
+    pub bytes_done: u64,
+    /// Estimated final size; `None` when there is no usable estimate.
+    pub bytes_total: Option<u64>,
+}
+
+/// Receives progress samples (the presentation layer emits them as events).
+pub type ProgressSink = Arc<dyn Fn(TransferProgress) + Send + Sync>;
+
+#[derive(Default)]
+struct CancelSignal {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelSignal {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    async fn cancelled(&self) {
+        loop {
+            // Register before checking the flag so a cancel in between is not lost.
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Snapshot operations currently running, keyed by snapshot id.
+#[derive(Default)]
+pub struct TransferRegistry {
+    sink: Option<ProgressSink>,
+    running: Mutex<HashMap<String, Arc<CancelSignal>>>,
+}
+
+impl TransferRegistry {
+    pub fn new(sink: ProgressSink) -> Self {
+        Self {
+            sink: Some(sink),
+            running: Mutex::default(),
+        }
+    }
+
+    /// Track an operation on `id`. It can be cancelled until the returned
+    /// guard is dropped. Fails if an operation on `id` is already running.
+    pub fn begin(self: &Arc<Self>, id: &SnapshotId) -> Result<TransferGuard, DomainError> {
+        let signal = Arc::new(CancelSignal::default());
+        let mut running = self.running.lock().expect("transfer registry poisoned");
+        if running.contains_key(id.as_str()) {
+            return Err(DomainError::SnapshotError(format!(
+                "An operation on snapshot '{}' is already running",
+                id
+            )));
+        }
+        running.insert(id.as_str().to_string(), signal.clone());
+        Ok(TransferGuard {
+            registry: self.clone(),
+            id: id.clone(),
+            signal,
+        })
+    }
+
+    /// Request cancellation of the operation on `id`. Returns `false` when
+    /// nothing is running for it.
+    pub fn cancel(&self, id: &SnapshotId) -> bool {
+        let running = self.running.lock().expect("transfer registry poisoned");
+        match running.get(id.as_str()) {
+            Some(signal) => {
+                signal.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn report(&self, progress: TransferProgress) {
+        if let Some(sink) = &self.sink {
+            sink(progress);
+        }
+    }
+}
+
+/// A registered operation; unregisters itself when dropped.
+pub struct TransferGuard {
+    registry: Arc<TransferRegistry>,
+    id: SnapshotId,
+    signal: Arc<CancelSignal>,
+}
+
+impl TransferGuard {
+    /// `Err(Cancelled)` once cancellation was requested.
+    pub fn check(&self) -> Result<(), DomainError> {
+        if self.signal.is_cancelled() {
+            return Err(DomainError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Report one progress sample of a transfer driven step by step.
+    pub fn report(
+        &self,
+        phase: TransferPhase,
+        distro_name: &DistroName,
+        bytes_done: u64,
+        bytes_total: Option<u64>,
+    ) {
+        self.registry.report(TransferProgress {
+            snapshot_id: self.id.as_str().to_string(),
+            distro_name: distro_name.as_str().to_string(),
+            phase,
+            bytes_done,
+            bytes_total,
+        });
+    }
+
+    /// Drive `transfer` while reporting the size of `watched` against `total`
+    /// every [`PROGRESS_INTERVAL`]. On cancellation `transfer` is dropped and
+    /// `Err(Cancelled)` returned.
+    pub async fn run<T, F>(
+        &self,
+        phase: TransferPhase,
+        distro_name: &DistroName,
+        watched: &str,
+        total: Option<u64>,
+        transfer: F,
+    ) -> Result<T, DomainError>
+    where
+        F: Future<Output = Result<T, DomainError>>,
+    {
+        self.check()?;
+        let sample = |bytes_done| TransferProgress {
+            snapshot_id: self.id.as_str().to_string(),
+            distro_name: distro_name.as_str().to_string(),
+            phase,
+            bytes_done,
+            bytes_total: total,
+        };
+        let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+        tokio::pin!(transfer);
+        loop {
+            tokio::select! {
+                result = &mut transfer => {
+                    if result.is_ok() {
+                        self.registry.report(sample(file_size(watched)));
+                    }
+                    return result;
+                }
+                _ = self.signal.cancelled() => {
+                    tracing::warn!(snapshot = %self.id, ?phase, "transfer cancelled");
+                    return Err(DomainError::Cancelled);
+                }
+                _ = ticker.tick() => self.registry.report(sample(file_size(watched))),
+            }
+        }
+    }
+}
+
+impl Drop for TransferGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.registry.running.lock() {
+            running.remove(self.id.as_str());
+        }
+    }
+}
+
+fn file_size(path: &str) -> u64 {
+    existing_local_path(path)
+        .and_then(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording_registry() -> (Arc<TransferRegistry>, Arc<Mutex<Vec<TransferProgress>>>) {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let s = samples.clone();
+        let registry = TransferRegistry::new(Arc::new(move |p| s.lock().unwrap().push(p)));
+        (Arc::new(registry), samples)
+    }
+
+    fn distro() -> DistroName {
+        DistroName::new("Ubuntu").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_file_growth() {
+        let dir = std::env::temp_dir().join("transfer_progress");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("out.tar");
+        let watched = out.to_string_lossy().to_string();
+
+        let (registry, samples) = recording_registry();
+        let id = SnapshotId::new();
+        let guard = registry.begin(&id).unwrap();
+        let result = guard
+            .run(TransferPhase::Export, &distro(), &watched, Some(8), async {
+                std::fs::write(&out, b"1234").unwrap();
+                tokio::time::sleep(Duration::from_millis(1500)).await;
+                std::fs::write(&out, b"12345678").unwrap();
+                Ok(42)
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 42);
+        let samples = samples.lock().unwrap();
+        assert!(samples.iter().any(|p| p.bytes_done == 4));
+        let last = samples.last().unwrap();
+        assert_eq!(last.bytes_done, 8);
+        assert_eq!(last.bytes_total, Some(8));
+        assert_eq!(last.snapshot_id, id.as_str());
+        assert_eq!(last.phase, TransferPhase::Export);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_drops_transfer() {
+        let (registry, _) = recording_registry();
+        let id = SnapshotId::new();
+        let guard = registry.begin(&id).unwrap();
+
+        let r = registry.clone();
+        let cancel_id = id.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(r.cancel(&cancel_id));
+        });
+        let result: Result<(), DomainError> = guard
+            .run(
+                TransferPhase::Import,
+                &distro(),
+                "/nonexistent/ext4.vhdx",
+                None,
+                std::future::pending(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(DomainError::Cancelled)));
+        assert!(matches!(guard.check(), Err(DomainError::Cancelled)));
+    }
+
+    #[test]
+    fn test_guard_unregisters_on_drop() {
+        let registry = Arc::new(TransferRegistry::default());
+        let id = SnapshotId::new();
+        let guard = registry.begin(&id).unwrap();
+        assert!(registry.begin(&id).is_err());
+        drop(guard);
+        assert!(!registry.cancel(&id));
+        assert!(registry.begin(&id).is_ok());
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::errors::DomainError;
+use crate::domain::ports::alerting::AlertType;
+
+/// Longest maintenance window; a window never spans more than two local days.
+pub const MAX_WINDOW_MINUTES: u32 = 24 * 60;
+
+/// Which alerts a silence or maintenance window covers. A field left empty
+/// matches everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertMatcher {
+    #[serde(default)]
+    pub distro_name: Option<String>,
+    #[serde(default)]
+    pub alert_type: Option<AlertType>,
+}
+
+impl AlertMatcher {
+    pub fn matches(&self, distro: &str, alert_type: AlertType) -> bool {
+        self.distro_name.as_deref().is_none_or(|d| d == distro)
+            && self.alert_type.is_none_or(|t| t == alert_type)
+    }
+}
+
+/// One-off silence: alerts it matches that fire between `starts_at` and
+/// `ends_at` are recorded as suppressed and send no notification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertSilence {
+    pub id: String,
+    #[serde(flatten)]
+    pub matcher: AlertMatcher,
+    pub reason: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AlertSilence {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+/// Recurring maintenance window in local time, e.g. every night from 02:00
+/// for 90 minutes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    /// Assigned on first save.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub matcher: AlertMatcher,
+    /// Days the window starts on; empty for every day.
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Local start time, `HH:MM`.
+    pub start_time: String,
+    pub duration_minutes: u32,
+    pub enabled: bool,
+}
+
+impl MaintenanceWindow {
+    fn start(&self) -> Result<NaiveTime, DomainError> {
+        NaiveTime::parse_from_str(&self.start_time, "%H:%M").map_err(|_| {
+            DomainError::ConfigError(format!(
+                "Invalid maintenance window start time '{}' (expected HH:MM)",
+                self.start_time
+            ))
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), DomainError> {
+        if self.name.trim().is_empty() {
+            return Err(DomainError::ConfigError(
+                "Maintenance window name must not be empty".into(),
+            ));
+        }
+        self.start()?;
+        if !(1..=MAX_WINDOW_MINUTES).contains(&self.duration_minutes) {
+            return Err(DomainError::ConfigError(format!(
+                "Maintenance window must last between 1 and {MAX_WINDOW_MINUTES} minutes"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether `now` falls in an occurrence of the window, including one that
+    /// started the day before and runs past midnight.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        let Ok(start) = self.start() else {
+            return false;
+        };
+        if !self.enabled {
+            return false;
+        }
+        let now = now.with_timezone(&Local).naive_local();
+        let duration = Duration::minutes(self.duration_minutes as i64);
+        [now.date(), now.date() - Duration::days(1)]
+            .into_iter()
+            .filter(|day| self.days.is_empty() || self.days.contains(&day.weekday()))
+            .any(|day| {
+                let opens = day.and_time(start);
+                opens <= now && now < opens + duration
+            })
+    }
+}
+
+/// Silences and maintenance windows, as read by the metrics collector on
+/// every tick.
+#[derive(Debug, Clone, Default)]
+pub struct AlertSuppressions {
+    pub silences: Vec<AlertSilence>,
+    pub windows: Vec<MaintenanceWindow>,
+}
+
+impl AlertSuppressions {
+    /// What suppresses an alert of `alert_type` on `distro` firing at `now`,
+    /// as shown with the recorded alert; `None` when nothing does.
+    pub fn suppressed_by(
+        &self,
+        distro: &str,
+        alert_type: AlertType,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        if let Some(silence) = self
+            .silences
+            .iter()
+            .find(|s| s.is_active(now) && s.matcher.matches(distro, alert_type))
+        {
+            return Some(format!("Silence: {}", silence.reason));
+        }
+        self.windows
+            .iter()
+            .find(|w| w.is_active(now) && w.matcher.matches(distro, alert_type))
+            .map(|w| format!("Maintenance window: {}", w.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn window(days: Vec<Weekday>, start_time: &str, duration_minutes: u32) -> MaintenanceWindow {
+        MaintenanceWindow {
+            id: "w".into(),
+            name: "Nightly build".into(),
+            matcher: AlertMatcher::default(),
+            days,
+            start_time: start_time.into(),
+            duration_minutes,
+            enabled: true,
+        }
+    }
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn window_runs_past_midnight_on_its_days() {
+        // 2026-03-10 is a Tuesday
+        let nightly = window(vec![Weekday::Tue], "23:00", 120);
+        assert!(!nightly.is_active(local(2026, 3, 10, 22, 59)));
+        assert!(nightly.is_active(local(2026, 3, 10, 23, 30)));
+        assert!(nightly.is_active(local(2026, 3, 11, 0, 59)));
+        assert!(!nightly.is_active(local(2026, 3, 11, 1, 0)));
+        // Wednesday's occurrence doesn't exist
+        assert!(!nightly.is_active(local(2026, 3, 11, 23, 30)));
+
+        let daily = window(vec![], "12:00", 30);
+        assert!(daily.is_active(local(2026, 3, 14, 12, 10)));
+
+        let mut disabled = daily.clone();
+        disabled.enabled = false;
+        assert!(!disabled.is_active(local(2026, 3, 14, 12, 10)));
+    }
+
+    #[test]
+    fn validate_rejects_bad_windows() {
+        assert!(window(vec![], "02:00", 90).validate().is_ok());
+        assert!(window(vec![], "2am", 90).validate().is_err());
+        assert!(window(vec![], "02:00", 0).validate().is_err());
+        assert!(
+            window(vec![], "02:00", MAX_WINDOW_MINUTES + 1)
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn suppressed_by_names_the_matching_silence_or_window() {
+        let now = Utc::now();
+        let silence = AlertSilence {
+            id: "s".into(),
+            matcher: AlertMatcher {
+                distro_name: Some("Ubuntu".into()),
+                alert_type: Some(AlertType::Cpu),
+            },
+            reason: "Kernel build".into(),
+            starts_at: now - Duration::minutes(5),
+            ends_at: now + Duration::minutes(55),
+            created_at: now,
+        };
+        let suppressions = AlertSuppressions {
+            silences: vec![silence],
+            windows: vec![MaintenanceWindow {
+                matcher: AlertMatcher {
+                    distro_name: Some("Debian".into()),
+                    alert_type: None,
+                },
+                ..window(vec![], "00:00", MAX_WINDOW_MINUTES)
+            }],
+        };
+
+        assert_eq!(
+            suppressions.suppressed_by("Ubuntu", AlertType::Cpu, now),
+            Some("Silence: Kernel build".into())
+        );
+        assert_eq!(
+            suppressions.suppressed_by("Ubuntu", AlertType::Memory, now),
+            None
+        );
+        assert_eq!(
+            suppressions.suppressed_by("Ubuntu", AlertType::Cpu, now + Duration::hours(1)),
+            None
+        );
+        assert_eq!(
+            suppressions.suppressed_by("Debian", AlertType::Disk, now),
+            Some("Maintenance window: Nightly build".into())
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use crate::domain::entities::wsl_config::WslDistroConfig;
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::SnapshotId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Placeholder every template can use: the name of the distro being provisioned.
+pub const DISTRO_NAME_PARAM: &str = "distro_name";
+
+/// A snapshot new distros are cloned from, plus the bootstrap script and
+/// `/etc/wsl.conf` overlay applied to each clone.
+///
+/// The script and the string fields of the overlay may contain `{{param}}`
+/// placeholders. In the script they expand to a single shell-quoted word, in
+/// the overlay to the raw value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistroTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub snapshot_id: SnapshotId,
+    /// Run as root with `sh` inside the new distro; empty for none.
+    pub script: String,
+    pub parameters: Vec<TemplateParameter>,
+    /// Only the fields that are set are written over the clone's wsl.conf.
+    pub wsl_config: WslDistroConfig,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateParameter {
+    pub name: String,
+    pub description: Option<String>,
+    pub default: Option<String>,
+    /// Provisioning fails when a required parameter has no value or default.
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl DistroTemplate {
+    /// Check parameter names and that every placeholder refers to a declared
+    /// parameter (or `distro_name`).
+    pub fn validate(&self) -> Result<(), DomainError> {
+        if self.name.trim().is_empty() {
+            return Err(DomainError::ConfigError("A template needs a name".into()));
+        }
+        let mut declared = vec![DISTRO_NAME_PARAM];
+        for param in &self.parameters {
+            if !is_identifier(&param.name) {
+                return Err(DomainError::ConfigError(format!(
+                    "Invalid template parameter name '{}'",
+                    param.name
+                )));
+            }
+            if declared.contains(&param.name.as_str()) {
+                return Err(DomainError::ConfigError(format!(
+                    "Template parameter '{}' is declared twice",
+                    param.name
+                )));
+            }
+            declared.push(&param.name);
+        }
+        for text in std::iter::once(self.script.as_str()).chain(config_strings(&self.wsl_config)) {
+            for name in placeholders(text) {
+                if !declared.contains(&name) {
+                    return Err(DomainError::ConfigError(format!(
+                        "Placeholder '{{{{{}}}}}' has no matching template parameter",
+                        name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Values of every parameter for provisioning `distro_name`: the given
+    /// ones, else defaults. Unknown names, missing required values and
+    /// multi-line values are refused.
+    pub fn resolve_values(
+        &self,
+        distro_name: &str,
+        values: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, DomainError> {
+        if let Some(unknown) = values
+            .keys()
+            .find(|k| !self.parameters.iter().any(|p| &p.name == *k))
+        {
+            return Err(DomainError::ConfigError(format!(
+                "Template '{}' has no parameter '{}'",
+                self.name, unknown
+            )));
+        }
+
+        let mut resolved = HashMap::new();
+        for param in &self.parameters {
+            let value = values.get(&param.name).or(param.default.as_ref()).cloned();
+            match value {
+                Some(v) if v.contains(['\n', '\r', '\0']) => {
+                    return Err(DomainError::ConfigError(format!(
+                        "Value of template parameter '{}' must be a single line",
+                        param.name
+                    )));
+                }
+                Some(v) => {
+                    resolved.insert(param.name.clone(), v);
+                }
+                None if param.required => {
+                    return Err(DomainError::ConfigError(format!(
+                        "Template parameter '{}' is required",
+                        param.name
+                    )));
+                }
+                None => {
+                    resolved.insert(param.name.clone(), String::new());
+                }
+            }
+        }
+        resolved.insert(DISTRO_NAME_PARAM.to_string(), distro_name.to_string());
+        Ok(resolved)
+    }
+
+    /// The bootstrap script with each placeholder replaced by its shell-quoted value.
+    pub fn render_script(&self, values: &HashMap<String, String>) -> String {
+        substitute(&self.script, |name| {
+            values
+                .get(name)
+                .map(|v| format!("'{}'", v.replace('\'', "'\\''")))
+        })
+    }
+
+    /// The wsl.conf overlay with placeholders replaced by their raw values.
+    pub fn render_config(&self, values: &HashMap<String, String>) -> WslDistroConfig {
+        let render = |field: &Option<String>| {
+            field
+                .as_deref()
+                .map(|text| substitute(text, |name| values.get(name).cloned()))
+        };
+        WslDistroConfig {
+            automount_root: render(&self.wsl_config.automount_root),
+            network_hostname: render(&self.wsl_config.network_hostname),
+            user_default: render(&self.wsl_config.user_default),
+            boot_command: render(&self.wsl_config.boot_command),
+            ..self.wsl_config.clone()
+        }
+    }
+}
+
+fn config_strings(config: &WslDistroConfig) -> impl Iterator<Item = &str> {
+    [
+        &config.automount_root,
+        &config.network_hostname,
+        &config.user_default,
+        &config.boot_command,
+    ]
+    .into_iter()
+    .filter_map(|field| field.as_deref())
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Names of the `{{name}}` placeholders in `text`, in order.
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim();
+        if is_identifier(name) {
+            names.push(name);
+            rest = &after[end + 2..];
+        } else {
+            rest = after;
+        }
+    }
+    names
+}
+
+/// Replace each `{{name}}` placeholder in `text` by `value(name)`; unknown
+/// names are left as they are.
+fn substitute(text: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim();
+        match value(name).filter(|_| is_identifier(name)) {
+            Some(v) => {
+                out.push_str(&rest[..start]);
+                out.push_str(&v);
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_template(script: &str) -> DistroTemplate {
+        DistroTemplate {
+            id: "tpl-1".into(),
+            name: "dev".into(),
+            description: None,
+            snapshot_id: SnapshotId::from_string("snap-1".into()),
+            script: script.into(),
+            parameters: vec![
+                TemplateParameter {
+                    name: "user".into(),
+                    description: None,
+                    default: None,
+                    required: true,
+                },
+                TemplateParameter {
+                    name: "packages".into(),
+                    description: None,
+                    default: Some("git curl".into()),
+                    required: false,
+                },
+            ],
+            wsl_config: WslDistroConfig {
+                network_hostname: Some("{{ distro_name }}-dev".into()),
+                boot_systemd: Some(true),
+                ..Default::default()
+            },
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_quotes_script_values_and_fills_config() {
+        let template = make_template("useradd -m {{user}} && apt-get install -y {{packages}}");
+        template.validate().unwrap();
+
+        let values = template
+            .resolve_values(
+                "web-1",
+                &HashMap::from([("user".to_string(), "o'neil".to_string())]),
+            )
+            .unwrap();
+        assert_eq!(
+            template.render_script(&values),
+            "useradd -m 'o'\\''neil' && apt-get install -y 'git curl'"
+        );
+        let config = template.render_config(&values);
+        assert_eq!(config.network_hostname.as_deref(), Some("web-1-dev"));
+        assert_eq!(config.boot_systemd, Some(true));
+    }
+
+    #[test]
+    fn test_validate_and_resolve_refuse_bad_input() {
+        assert!(make_template("echo {{ missing }}").validate().is_err());
+        // Not an identifier, so not a placeholder (e.g. Go templates in heredocs)
+        assert!(make_template("echo '{{ .Name }}'").validate().is_ok());
+
+        let template = make_template("echo {{user}}");
+        assert!(template.resolve_values("web-1", &HashMap::new()).is_err());
+        assert!(
+            template
+                .resolve_values(
+                    "web-1",
+                    &HashMap::from([("user".to_string(), "a\nb".to_string())])
+                )
+                .is_err()
+        );
+        assert!(
+            template
+                .resolve_values(
+                    "web-1",
+                    &HashMap::from([
+                        ("user".to_string(), "dev".to_string()),
+                        ("shell".to_string(), "zsh".to_string()),
+                    ])
+                )
+                .is_err()
+        );
+    }
+}
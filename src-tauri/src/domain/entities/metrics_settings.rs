@@ -0,0 +1,253 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::errors::DomainError;
+
+/// The UI computes `from` with its own clock a moment before the query runs,
+/// so a range reaching back exactly as far as a tier is kept still uses it.
+const RETENTION_SLACK_MINUTES: i64 = 5;
+
+/// Rollup tiers above the raw samples, each built from the one below.
+/// Buckets are aligned on UTC minutes, hours and days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricsTier {
+    #[serde(rename = "1m")]
+    Minute,
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl MetricsTier {
+    pub const ALL: [MetricsTier; 3] = [MetricsTier::Minute, MetricsTier::Hour, MetricsTier::Day];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MetricsTier::Minute => "1m",
+            MetricsTier::Hour => "1h",
+            MetricsTier::Day => "1d",
+        }
+    }
+
+    /// Longest range read from this tier, which keeps a chart to at most a
+    /// few hundred to 1440 points.
+    fn max_range(self) -> Option<Duration> {
+        match self {
+            MetricsTier::Minute => Some(Duration::hours(24)),
+            MetricsTier::Hour => Some(Duration::days(31)),
+            MetricsTier::Day => None,
+        }
+    }
+}
+
+/// Runtime settings of the metrics pipeline: how often the collector samples
+/// the running distros, how long each tier of the history is kept, and
+/// whether the latest metrics are served to scrapers in OpenMetrics format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSettings {
+    pub collection_interval_secs: u32,
+    pub raw_retention_hours: u32,
+    pub minute_retention_hours: u32,
+    pub hour_retention_days: u32,
+    pub day_retention_days: u32,
+    /// Serve `http://127.0.0.1:<port>/metrics`. Off unless enabled.
+    pub openmetrics_enabled: bool,
+    pub openmetrics_port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            collection_interval_secs: 2,
+            raw_retention_hours: 1,
+            minute_retention_hours: 24,
+            hour_retention_days: 30,
+            day_retention_days: 365,
+            openmetrics_enabled: false,
+            openmetrics_port: 9469,
+        }
+    }
+}
+
+impl MetricsSettings {
+    pub fn validate(&self) -> Result<(), DomainError> {
+        let checks = [
+            (
+                "Collection interval",
+                self.collection_interval_secs,
+                1..=60,
+                "seconds",
+            ),
+            ("Raw retention", self.raw_retention_hours, 1..=168, "hours"),
+            (
+                "1-minute retention",
+                self.minute_retention_hours,
+                2..=720,
+                "hours",
+            ),
+            (
+                "1-hour retention",
+                self.hour_retention_days,
+                2..=365,
+                "days",
+            ),
+            ("1-day retention", self.day_retention_days, 1..=3650, "days"),
+        ];
+        for (label, value, range, unit) in checks {
+            if !range.contains(&value) {
+                return Err(DomainError::ConfigError(format!(
+                    "{label} must be between {} and {} {unit}",
+                    range.start(),
+                    range.end()
+                )));
+            }
+        }
+        // Each tier is rolled up from the one below, so no tier may be kept
+        // for less time than the one it is built from
+        let mut source = ("raw retention", self.raw_retention());
+        for (tier, label) in MetricsTier::ALL.into_iter().zip([
+            "1-minute retention",
+            "1-hour retention",
+            "1-day retention",
+        ]) {
+            let retention = self.retention(tier);
+            if retention < source.1 {
+                return Err(DomainError::ConfigError(format!(
+                    "{label} must be at least as long as the {}",
+                    source.0
+                )));
+            }
+            source = (label, retention);
+        }
+        // Only the loopback interface is bound, but privileged ports still
+        // need elevation on some hosts
+        if self.openmetrics_port < 1024 {
+            return Err(DomainError::ConfigError(
+                "OpenMetrics port must be between 1024 and 65535".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn raw_retention(&self) -> Duration {
+        Duration::hours(self.raw_retention_hours.into())
+    }
+
+    pub fn retention(&self, tier: MetricsTier) -> Duration {
+        match tier {
+            MetricsTier::Minute => Duration::hours(self.minute_retention_hours.into()),
+            MetricsTier::Hour => Duration::days(self.hour_retention_days.into()),
+            MetricsTier::Day => Duration::days(self.day_retention_days.into()),
+        }
+    }
+
+    /// Where a history range is read from: the raw samples (`None`) for up
+    /// to an hour, else the finest tier whose range limit and retention
+    /// cover it.
+    pub fn history_tier(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<MetricsTier> {
+        let range = to - from;
+        let covers = |retention: Duration| {
+            from + Duration::minutes(RETENTION_SLACK_MINUTES) >= now - retention
+        };
+        if range <= Duration::hours(1) && covers(self.raw_retention()) {
+            return None;
+        }
+        MetricsTier::ALL
+            .into_iter()
+            .find(|&tier| {
+                tier.max_range().is_none_or(|max| range <= max) && covers(self.retention(tier))
+            })
+            .or(Some(MetricsTier::Day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_checks_each_range() {
+        assert!(MetricsSettings::default().validate().is_ok());
+
+        let too_fast = MetricsSettings {
+            collection_interval_secs: 0,
+            ..Default::default()
+        };
+        let err = too_fast.validate().unwrap_err().to_string();
+        assert!(err.contains("Collection interval"));
+
+        let short_minutes = MetricsSettings {
+            minute_retention_hours: 1,
+            ..Default::default()
+        };
+        assert!(short_minutes.validate().is_err());
+
+        let raw_outlives_minutes = MetricsSettings {
+            raw_retention_hours: 48,
+            ..Default::default()
+        };
+        let err = raw_outlives_minutes.validate().unwrap_err().to_string();
+        assert!(err.contains("1-minute retention must be at least as long as the raw retention"));
+
+        let days_before_hours = MetricsSettings {
+            hour_retention_days: 365,
+            day_retention_days: 1,
+            ..Default::default()
+        };
+        let err = days_before_hours.validate().unwrap_err().to_string();
+        assert!(err.contains("1-day retention"));
+
+        // Equal retention is fine
+        let flat = MetricsSettings {
+            raw_retention_hours: 24,
+            minute_retention_hours: 24,
+            hour_retention_days: 30,
+            day_retention_days: 30,
+            ..Default::default()
+        };
+        assert!(flat.validate().is_ok());
+
+        let privileged_port = MetricsSettings {
+            openmetrics_port: 80,
+            ..Default::default()
+        };
+        let err = privileged_port.validate().unwrap_err().to_string();
+        assert!(err.contains("OpenMetrics port"));
+    }
+
+    #[test]
+    fn history_tier_follows_range_and_retention() {
+        let settings = MetricsSettings::default();
+        let now = Utc::now();
+        let tier = |range: Duration| settings.history_tier(now - range, now, now);
+
+        assert_eq!(tier(Duration::hours(1)), None);
+        assert_eq!(tier(Duration::hours(6)), Some(MetricsTier::Minute));
+        assert_eq!(tier(Duration::hours(24)), Some(MetricsTier::Minute));
+        assert_eq!(tier(Duration::days(7)), Some(MetricsTier::Hour));
+        assert_eq!(tier(Duration::days(30)), Some(MetricsTier::Hour));
+        assert_eq!(tier(Duration::days(90)), Some(MetricsTier::Day));
+
+        // A short range older than the raw samples reads the 1-minute tier
+        let old = now - Duration::hours(3);
+        assert_eq!(
+            settings.history_tier(old, old + Duration::minutes(30), now),
+            Some(MetricsTier::Minute)
+        );
+        // Longer raw retention serves older short ranges from the samples
+        let long_raw = MetricsSettings {
+            raw_retention_hours: 6,
+            ..Default::default()
+        };
+        assert_eq!(
+            long_raw.history_tier(old, old + Duration::minutes(30), now),
+            None
+        );
+    }
+}
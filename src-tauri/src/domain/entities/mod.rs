@@ -1,6 +1,13 @@
+pub mod alert_silence;
 pub mod distro;
+pub mod distro_template;
+pub mod metrics_settings;
 pub mod monitoring;
+pub mod notification_channel;
 pub mod port_forward;
+pub mod replication;
+pub mod safety_snapshot;
 pub mod snapshot;
+pub mod snapshot_schedule;
 pub mod wsl_config;
 pub mod wsl_version;
@@ -88,4 +88,46 @@ pub struct ProcessInfo {
     pub rss_bytes: u64,
     pub command: String,
     pub state: String,
+    /// Cumulative CPU time (the `TIME` column of `ps`).
+    #[serde(default)]
+    pub cpu_time_secs: u64,
+}
+
+/// CPU and memory of one process over the last sampling interval, as
+/// persisted in the per-process history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub user: String,
+    pub command: String,
+    /// CPU used since the previous sample, in percent of one core.
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+}
+
+/// Cumulative counters of one systemd unit, read from its cgroup v2
+/// directory under `/sys/fs/cgroup/system.slice`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceCgroupStats {
+    /// Unit name, e.g. `postgresql@16-main.service`.
+    pub unit: String,
+    /// `usage_usec` of `cpu.stat`.
+    pub cpu_usage_usec: u64,
+    /// `memory.current`.
+    pub memory_bytes: u64,
+    /// `rbytes` and `wbytes` of `io.stat`, summed over devices.
+    pub io_read_bytes: u64,
+    pub io_write_bytes: u64,
+}
+
+/// CPU, memory and I/O of one systemd unit over the last sampling interval,
+/// as persisted in the per-service history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceMetrics {
+    pub unit: String,
+    /// CPU used since the previous sample, in percent of one core.
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+    pub io_read_bytes_per_sec: u64,
+    pub io_write_bytes_per_sec: u64,
 }
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::errors::DomainError;
+
+/// Where alert notifications are delivered. Alert rules route to channels by id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationChannel {
+    /// Assigned on first save.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub config: ChannelConfig,
+}
+
+/// Id of the built-in desktop channel, used by rules that route nowhere.
+pub const DESKTOP_CHANNEL_ID: &str = "desktop";
+
+impl NotificationChannel {
+    /// The desktop notification every alert rule without channels goes to.
+    pub fn desktop() -> Self {
+        Self {
+            id: DESKTOP_CHANNEL_ID.into(),
+            name: "Desktop".into(),
+            enabled: true,
+            config: ChannelConfig::Desktop,
+        }
+    }
+
+    /// Check the settings a delivery can't work without.
+    pub fn validate(&self) -> Result<(), DomainError> {
+        if self.name.trim().is_empty() {
+            return Err(DomainError::ConfigError(
+                "Notification channel name must not be empty".into(),
+            ));
+        }
+        match &self.config {
+            ChannelConfig::Desktop => Ok(()),
+            ChannelConfig::Webhook { url } => {
+                if url.starts_with("https://") || url.starts_with("http://") {
+                    Ok(())
+                } else {
+                    Err(DomainError::ConfigError(format!(
+                        "Webhook URL must be an http(s) URL: {url}"
+                    )))
+                }
+            }
+            ChannelConfig::Email {
+                smtp_host,
+                from,
+                to,
+                ..
+            } => {
+                if smtp_host.trim().is_empty() {
+                    return Err(DomainError::ConfigError(
+                        "SMTP host must not be empty".into(),
+                    ));
+                }
+                if to.is_empty() {
+                    return Err(DomainError::ConfigError(
+                        "Email channel needs at least one recipient".into(),
+                    ));
+                }
+                match std::iter::once(from).chain(to).find(|a| !a.contains('@')) {
+                    Some(address) => Err(DomainError::ConfigError(format!(
+                        "Invalid email address: {address}"
+                    ))),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChannelConfig {
+    /// Desktop notification through the notification plugin.
+    Desktop,
+    /// JSON `POST` with a `text` field (Slack and Teams incoming webhooks)
+    /// plus the structured alert.
+    Webhook { url: String },
+    Email {
+        smtp_host: String,
+        smtp_port: u16,
+        #[serde(default)]
+        security: SmtpSecurity,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+impl ChannelConfig {
+    pub fn kind(&self) -> ChannelKind {
+        match self {
+            ChannelConfig::Desktop => ChannelKind::Desktop,
+            ChannelConfig::Webhook { .. } => ChannelKind::Webhook,
+            ChannelConfig::Email { .. } => ChannelKind::Email,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    Desktop,
+    Webhook,
+    Email,
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with `STARTTLS` (usually port 587).
+    #[default]
+    StartTls,
+    /// TLS from the start (usually port 465).
+    Tls,
+    /// No encryption; only for local relays.
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,
+}
+
+impl std::fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryStatus::Delivered => write!(f, "delivered"),
+            DeliveryStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl std::str::FromStr for DeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "failed" => Ok(DeliveryStatus::Failed),
+            _ => Err(format!("Unknown delivery status: {s}")),
+        }
+    }
+}
+
+/// One alert notification sent (or given up on) through one channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDelivery {
+    /// Assigned when the delivery is logged.
+    pub id: i64,
+    pub channel_id: String,
+    pub channel_name: String,
+    /// `None` for test notifications and alerts that failed to record.
+    pub alert_id: Option<i64>,
+    pub distro_name: String,
+    pub title: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// Error of the last attempt, when it failed.
+    pub error: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(from: &str, to: &[&str]) -> NotificationChannel {
+        NotificationChannel {
+            id: String::new(),
+            name: "Ops".into(),
+            enabled: true,
+            config: ChannelConfig::Email {
+                smtp_host: "smtp.example.com".into(),
+                smtp_port: 587,
+                security: SmtpSecurity::StartTls,
+                username: None,
+                password: None,
+                from: from.into(),
+                to: to.iter().map(|s| s.to_string()).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn validate_rejects_incomplete_channels() {
+        assert!(
+            email("alerts@example.com", &["ops@example.com"])
+                .validate()
+                .is_ok()
+        );
+        assert!(email("alerts@example.com", &[]).validate().is_err());
+        assert!(email("alerts", &["ops@example.com"]).validate().is_err());
+
+        let webhook = |url: &str| NotificationChannel {
+            config: ChannelConfig::Webhook { url: url.into() },
+            ..NotificationChannel::desktop()
+        };
+        assert!(
+            webhook("https://hooks.slack.com/services/T/B/X")
+                .validate()
+                .is_ok()
+        );
+        assert!(webhook("ftp://example.com").validate().is_err());
+    }
+
+    #[test]
+    fn channel_config_serde_is_tagged_by_kind() {
+        let json = serde_json::to_value(ChannelConfig::Webhook {
+            url: "http://localhost/hook".into(),
+        })
+        .unwrap();
+        assert_eq!(json["kind"], "webhook");
+
+        let parsed: ChannelConfig = serde_json::from_str(
+            r#"{"kind":"email","smtp_host":"localhost","smtp_port":25,"from":"a@b","to":["c@d"]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.kind(), ChannelKind::Email);
+        assert!(matches!(
+            parsed,
+            ChannelConfig::Email {
+                security: SmtpSecurity::StartTls,
+                ..
+            }
+        ));
+    }
+}
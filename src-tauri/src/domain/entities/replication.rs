@@ -0,0 +1,104 @@
+use crate::domain::value_objects::SnapshotId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// S3-compatible bucket snapshots are replicated to (AWS S3, MinIO, ...).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicationTarget {
+    /// Base URL of the service, e.g. `https://s3.eu-west-1.amazonaws.com`
+    /// or `http://localhost:9000`.
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Key prefix inside the bucket; objects go to `<prefix><distro>/<file>`.
+    pub prefix: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// `endpoint/bucket/key` URLs instead of `bucket.endpoint/key`
+    /// (MinIO and most self-hosted services need this).
+    pub path_style: bool,
+    /// Upload completed snapshots in the background without being asked.
+    pub auto_replicate: bool,
+}
+
+impl ReplicationTarget {
+    /// Object key of the archive of a snapshot of `distro` stored as `file_name`.
+    pub fn object_key(&self, distro: &str, file_name: &str) -> String {
+        let prefix = self.prefix.trim_matches('/');
+        if prefix.is_empty() {
+            format!("{}/{}", distro, file_name)
+        } else {
+            format!("{}/{}/{}", prefix, distro, file_name)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplicationStatus {
+    /// A multipart upload is under way (or was interrupted and will resume).
+    Uploading,
+    /// The remote object is complete.
+    Replicated,
+    /// The last attempt failed; an unfinished upload is resumed by the next one.
+    Failed(String),
+}
+
+/// Offsite copy of one snapshot archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotReplication {
+    pub snapshot_id: SnapshotId,
+    pub object_key: String,
+    pub status: ReplicationStatus,
+    /// Multipart upload to resume; `None` once completed.
+    pub upload_id: Option<String>,
+    /// Part size the upload was started with (resumed parts must match).
+    pub part_size: u64,
+    pub bytes_uploaded: u64,
+    pub total_bytes: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Parts are at least 8 MiB (S3 requires 5 MiB for all but the last) and
+/// grow for archives that would need more than the 10 000 parts S3 allows.
+pub const MIN_PART_SIZE: u64 = 8 * 1024 * 1024;
+pub const MAX_PARTS: u64 = 10_000;
+
+/// Part size for an archive of `total_bytes`.
+pub fn part_size_for(total_bytes: u64) -> u64 {
+    MIN_PART_SIZE.max(total_bytes.div_ceil(MAX_PARTS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(prefix: &str) -> ReplicationTarget {
+        ReplicationTarget {
+            endpoint: "http://localhost:9000".into(),
+            region: "us-east-1".into(),
+            bucket: "snapshots".into(),
+            prefix: prefix.into(),
+            access_key_id: "ak".into(),
+            secret_access_key: "sk".into(),
+            path_style: true,
+            auto_replicate: false,
+        }
+    }
+
+    #[test]
+    fn test_object_key_with_and_without_prefix() {
+        assert_eq!(target("").object_key("Ubuntu", "a.tar"), "Ubuntu/a.tar");
+        assert_eq!(
+            target("/laptop/").object_key("Ubuntu", "a.tar"),
+            "laptop/Ubuntu/a.tar"
+        );
+    }
+
+    #[test]
+    fn test_part_size_stays_within_part_limit() {
+        assert_eq!(part_size_for(1024), MIN_PART_SIZE);
+        let huge = 200 * 1024 * 1024 * 1024u64;
+        assert!(part_size_for(huge) * MAX_PARTS >= huge);
+        assert!(part_size_for(huge) > MIN_PART_SIZE);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::domain::entities::snapshot::ExportFormat;
+use crate::domain::value_objects::DistroName;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tag carried by every safety snapshot.
+pub const SAFETY_TAG: &str = "safety";
+
+/// Operation that can destroy a distro's data and may be guarded by a
+/// safety snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DestructiveOperation {
+    DeleteDistro,
+    RestoreOverwrite,
+    ResizeVhd,
+    CompactVhdx,
+}
+
+impl DestructiveOperation {
+    pub const ALL: [Self; 4] = [
+        Self::DeleteDistro,
+        Self::RestoreOverwrite,
+        Self::ResizeVhd,
+        Self::CompactVhdx,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::DeleteDistro => "delete_distro",
+            Self::RestoreOverwrite => "restore_overwrite",
+            Self::ResizeVhd => "resize_vhd",
+            Self::CompactVhdx => "compact_vhdx",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|op| op.as_str() == s)
+    }
+
+    /// Audit action the operation itself is logged under.
+    pub fn audit_action(&self) -> &str {
+        match self {
+            Self::DeleteDistro => "distro.delete",
+            Self::RestoreOverwrite => "snapshot.restore",
+            Self::ResizeVhd => "vhdx.resize",
+            Self::CompactVhdx => "vhdx.compact",
+        }
+    }
+
+    /// Tag naming the operation a safety snapshot was taken for,
+    /// e.g. "safety:resize_vhd".
+    pub fn tag(&self) -> String {
+        format!("{}:{}", SAFETY_TAG, self.as_str())
+    }
+}
+
+/// Opt-in policy of one distro: which destructive operations take a
+/// safety snapshot first, and how long those snapshots are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetySnapshotPolicy {
+    pub distro_name: DistroName,
+    /// Guarded operations; an empty list turns the policy off.
+    pub operations: Vec<DestructiveOperation>,
+    pub format: ExportFormat,
+    pub output_dir: String,
+    /// Newest safety snapshots of the distro kept; 0 keeps any number.
+    pub keep_last: u32,
+    /// Safety snapshots older than this many hours are deleted; 0 keeps
+    /// them regardless of age.
+    pub max_age_hours: u32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SafetySnapshotPolicy {
+    pub fn guards(&self, operation: DestructiveOperation) -> bool {
+        self.operations.contains(&operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_round_trip() {
+        for op in DestructiveOperation::ALL {
+            assert_eq!(DestructiveOperation::parse(op.as_str()), Some(op));
+        }
+        assert_eq!(DestructiveOperation::parse("format_disk"), None);
+        assert_eq!(DestructiveOperation::ResizeVhd.tag(), "safety:resize_vhd");
+    }
+
+    #[test]
+    fn test_policy_guards_listed_operations_only() {
+        let policy = SafetySnapshotPolicy {
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            operations: vec![DestructiveOperation::DeleteDistro],
+            format: ExportFormat::TarZst,
+            output_dir: "/backups".into(),
+            keep_last: 3,
+            max_age_hours: 0,
+            updated_at: Utc::now(),
+        };
+        assert!(policy.guards(DestructiveOperation::DeleteDistro));
+        assert!(!policy.guards(DestructiveOperation::CompactVhdx));
+    }
+}
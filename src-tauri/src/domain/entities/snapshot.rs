@@ -1,3 +1,4 @@
+use crate::domain::errors::DomainError;
 use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -16,6 +17,42 @@ pub struct Snapshot {
     pub created_at: DateTime<Utc>,
     pub status: SnapshotStatus,
     pub default_user: Option<String>,
+    pub storage: SnapshotStorage,
+    /// Hex SHA-256 of the archive bytes, recorded at creation time.
+    /// `None` for snapshots created before checksums existed.
+    pub checksum: Option<String>,
+    /// User-defined labels such as "golden" or "pre-upgrade", sorted and unique.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Pinned snapshots are never removed by retention or deletion sweeps.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Set when the archive is encrypted; `checksum` then covers the ciphertext.
+    #[serde(default)]
+    pub encryption: Option<SnapshotEncryption>,
+}
+
+/// Longest accepted snapshot tag, in characters.
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Normalize user-supplied tags: trimmed, lowercased, sorted and deduplicated.
+/// Empty tags, overlong tags and tags containing control characters are rejected.
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, DomainError> {
+    let mut normalized = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LEN || tag.chars().any(char::is_control)
+        {
+            return Err(DomainError::SnapshotError(format!(
+                "Invalid tag '{}': tags must be 1 to {} printable characters",
+                tag, MAX_TAG_LEN
+            )));
+        }
+        normalized.push(tag);
+    }
+    normalized.sort();
+    normalized.dedup();
+    Ok(normalized)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +65,10 @@ pub enum SnapshotType {
 pub enum ExportFormat {
     Tar,
     Vhd,
+    /// Tar stream compressed with zstd while it is exported.
+    TarZst,
+    /// Tar stream compressed with gzip while it is exported.
+    TarGz,
 }
 
 impl ExportFormat {
@@ -35,34 +76,131 @@ impl ExportFormat {
         match self {
             Self::Tar => "tar",
             Self::Vhd => "vhdx",
+            Self::TarZst => "tar.zst",
+            Self::TarGz => "tar.gz",
         }
     }
 
     pub fn wsl_flag(&self) -> Option<&str> {
         match self {
             Self::Vhd => Some("--vhd"),
-            Self::Tar => None,
+            Self::Tar | Self::TarZst | Self::TarGz => None,
+        }
+    }
+
+    /// Whether the archive holds a tar stream (plain or compressed).
+    pub fn is_tar(&self) -> bool {
+        !matches!(self, Self::Vhd)
+    }
+
+    /// Whether the archive is compressed on disk.
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, Self::TarZst | Self::TarGz)
+    }
+}
+
+/// Where the archive bytes of a snapshot live.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotStorage {
+    /// A standalone archive at `file_path`.
+    File,
+    /// Content-defined chunks in the shared chunk store next to `file_path`,
+    /// listed by the snapshot's chunk manifest.
+    Chunked,
+}
+
+impl SnapshotStorage {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::File => "file",
+            Self::Chunked => "chunked",
+        }
+    }
+}
+
+/// One entry of a chunked snapshot's manifest, in archive order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotChunk {
+    /// Hex SHA-256 of the chunk content; also its file name in the chunk store.
+    pub hash: String,
+    pub length: u64,
+}
+
+/// What the key of an encrypted snapshot is derived from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    Passphrase,
+    /// The content of a key file chosen by the user.
+    Keyfile,
+}
+
+impl KeySource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Passphrase => "passphrase",
+            Self::Keyfile => "keyfile",
         }
     }
 }
 
+/// Key-derivation parameters of an encrypted snapshot (Argon2id). Holds no
+/// secret: the key is re-derived from the passphrase or keyfile on use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEncryption {
+    pub key_source: KeySource,
+    /// Hex-encoded random salt.
+    pub salt: String,
+    /// Argon2 memory cost in KiB.
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Hex digest proving a derived key is the right one before any
+    /// ciphertext is read.
+    pub key_check: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SnapshotStatus {
     InProgress,
     Completed,
     Failed(String),
+    /// The archive no longer matches its recorded checksum (or is gone).
+    Corrupted,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RestoreMode {
-    Clone { new_name: String },
+    Clone {
+        new_name: String,
+    },
     Overwrite,
+    /// Copy the given files or directories back into the running distro.
+    Files {
+        paths: Vec<String>,
+    },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_normalize_tags_sorts_and_dedups() {
+        let tags = vec![
+            " Golden ".to_string(),
+            "pre-upgrade".into(),
+            "golden".into(),
+        ];
+        assert_eq!(
+            normalize_tags(&tags).unwrap(),
+            vec!["golden", "pre-upgrade"]
+        );
+        assert!(normalize_tags(&["  ".to_string()]).is_err());
+        assert!(normalize_tags(&["a\u{1f}b".to_string()]).is_err());
+        assert!(normalize_tags(&["x".repeat(MAX_TAG_LEN + 1)]).is_err());
+    }
+
     #[test]
     fn test_export_format_tar_extension() {
         assert_eq!(ExportFormat::Tar.extension(), "tar");
@@ -82,4 +220,21 @@ mod tests {
     fn test_wsl_flag_tar_returns_none() {
         assert_eq!(ExportFormat::Tar.wsl_flag(), None);
     }
+
+    #[test]
+    fn test_compressed_formats() {
+        assert_eq!(ExportFormat::TarZst.extension(), "tar.zst");
+        assert_eq!(ExportFormat::TarGz.extension(), "tar.gz");
+        assert_eq!(ExportFormat::TarZst.wsl_flag(), None);
+        assert!(ExportFormat::TarGz.is_tar());
+        assert!(ExportFormat::TarZst.is_compressed());
+        assert!(!ExportFormat::Tar.is_compressed());
+        assert!(!ExportFormat::Vhd.is_tar());
+    }
+
+    #[test]
+    fn test_snapshot_storage_as_str() {
+        assert_eq!(SnapshotStorage::File.as_str(), "file");
+        assert_eq!(SnapshotStorage::Chunked.as_str(), "chunked");
+    }
 }
@@ -0,0 +1,155 @@
+use crate::domain::entities::snapshot::{ExportFormat, SnapshotStorage};
+use crate::domain::value_objects::DistroName;
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A recurring automatic snapshot of one distro.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSchedule {
+    pub id: String,
+    pub distro_name: DistroName,
+    pub frequency: ScheduleFrequency,
+    pub format: ExportFormat,
+    pub storage: SnapshotStorage,
+    pub output_dir: String,
+    pub retention: RetentionPolicy,
+    pub enabled: bool,
+    /// Start of the last scheduled run, successful or not.
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SnapshotSchedule {
+    /// Whether the current period (local hour, day or ISO week) has no run yet.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.enabled
+            && self.last_run_at.is_none_or(|last| {
+                self.frequency.period_key(last) != self.frequency.period_key(now)
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleFrequency {
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+impl ScheduleFrequency {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "hourly" => Some(Self::Hourly),
+            "daily" => Some(Self::Daily),
+            "weekly" => Some(Self::Weekly),
+            _ => None,
+        }
+    }
+
+    /// Identifies the local-time period `at` falls in; two instants share a
+    /// key iff they are in the same hour, day or ISO week.
+    pub fn period_key(&self, at: DateTime<Utc>) -> i64 {
+        let local = at.with_timezone(&Local);
+        let day = local.date_naive().num_days_from_ce() as i64;
+        match self {
+            Self::Hourly => day * 24 + local.hour() as i64,
+            Self::Daily => day,
+            Self::Weekly => {
+                let week = local.iso_week();
+                week.year() as i64 * 100 + week.week() as i64
+            }
+        }
+    }
+}
+
+/// Grandfather-father-son retention: the newest snapshot of each of the last
+/// `keep_hourly` hours, `keep_daily` days and `keep_weekly` weeks survives.
+/// All zeros keeps everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub keep_hourly: u32,
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+}
+
+impl RetentionPolicy {
+    pub fn keeps_everything(&self) -> bool {
+        self.keep_hourly == 0 && self.keep_daily == 0 && self.keep_weekly == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn schedule(
+        frequency: ScheduleFrequency,
+        last_run_at: Option<DateTime<Utc>>,
+    ) -> SnapshotSchedule {
+        SnapshotSchedule {
+            id: "sched-1".into(),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            frequency,
+            format: ExportFormat::Tar,
+            storage: SnapshotStorage::File,
+            output_dir: "/tmp".into(),
+            retention: RetentionPolicy::default(),
+            enabled: true,
+            last_run_at,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_frequency_round_trip() {
+        for f in [
+            ScheduleFrequency::Hourly,
+            ScheduleFrequency::Daily,
+            ScheduleFrequency::Weekly,
+        ] {
+            assert_eq!(ScheduleFrequency::parse(f.as_str()), Some(f));
+        }
+        assert_eq!(ScheduleFrequency::parse("monthly"), None);
+    }
+
+    #[test]
+    fn test_is_due_once_per_period() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 11, 12, 0, 0).unwrap();
+        assert!(schedule(ScheduleFrequency::Hourly, None).is_due(now));
+        assert!(!schedule(ScheduleFrequency::Hourly, Some(now)).is_due(now));
+        assert!(
+            schedule(
+                ScheduleFrequency::Hourly,
+                Some(now - chrono::Duration::hours(1))
+            )
+            .is_due(now)
+        );
+        assert!(
+            !schedule(
+                ScheduleFrequency::Weekly,
+                Some(now - chrono::Duration::minutes(1))
+            )
+            .is_due(now)
+        );
+        assert!(
+            schedule(
+                ScheduleFrequency::Weekly,
+                Some(now - chrono::Duration::days(8))
+            )
+            .is_due(now)
+        );
+
+        let mut disabled = schedule(ScheduleFrequency::Daily, None);
+        disabled.enabled = false;
+        assert!(!disabled.is_due(now));
+    }
+}
@@ -39,8 +39,24 @@ pub enum DomainError {
     #[error("Terminal error: {0}")]
     TerminalError(String),
 
+    #[error("Replication error: {0}")]
+    ReplicationError(String),
+
+    #[error("Notification error: {0}")]
+    NotificationError(String),
+
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    /// The passphrase or keyfile does not unlock the snapshot with this id.
+    #[error("Wrong passphrase or keyfile for snapshot {0}")]
+    WrongEncryptionKey(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 impl Serialize for DomainError {
@@ -121,5 +137,18 @@ mod tests {
             DomainError::Internal("x".into()).to_string(),
             "Internal error: x"
         );
+        assert_eq!(
+            DomainError::ReplicationError("x".into()).to_string(),
+            "Replication error: x"
+        );
+        assert_eq!(
+            DomainError::EncryptionError("x".into()).to_string(),
+            "Encryption error: x"
+        );
+        assert_eq!(
+            DomainError::WrongEncryptionKey("abc".into()).to_string(),
+            "Wrong passphrase or keyfile for snapshot abc"
+        );
+        assert_eq!(DomainError::Cancelled.to_string(), "Operation cancelled");
     }
 }
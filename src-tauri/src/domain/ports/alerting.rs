@@ -1,16 +1,65 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::domain::entities::alert_silence::{AlertSilence, MaintenanceWindow};
 use crate::domain::errors::DomainError;
 use crate::domain::value_objects::DistroName;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AlertType {
     Cpu,
     Memory,
     Disk,
+    Swap,
+    /// 1-minute load average divided by the number of cores.
+    LoadPerCore,
+    /// Sockets in TIME_WAIT.
+    TcpTimeWait,
+    /// Disk write throughput, in bytes per second.
+    DiskWrite,
+    GpuVram,
+}
+
+impl AlertType {
+    pub const ALL: [AlertType; 8] = [
+        AlertType::Cpu,
+        AlertType::Memory,
+        AlertType::Disk,
+        AlertType::Swap,
+        AlertType::LoadPerCore,
+        AlertType::TcpTimeWait,
+        AlertType::DiskWrite,
+        AlertType::GpuVram,
+    ];
+
+    /// Human-readable name, used in notifications.
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertType::Cpu => "CPU",
+            AlertType::Memory => "Memory",
+            AlertType::Disk => "Disk",
+            AlertType::Swap => "Swap",
+            AlertType::LoadPerCore => "Load per core",
+            AlertType::TcpTimeWait => "TCP TIME_WAIT",
+            AlertType::DiskWrite => "Disk write",
+            AlertType::GpuVram => "GPU VRAM",
+        }
+    }
+
+    /// Whether a threshold on this type may be expressed in `unit`.
+    pub fn supports(&self, unit: AlertUnit) -> bool {
+        match self {
+            AlertType::Cpu => unit == AlertUnit::Percent,
+            AlertType::LoadPerCore | AlertType::TcpTimeWait | AlertType::DiskWrite => {
+                unit == AlertUnit::Absolute
+            }
+            AlertType::Memory | AlertType::Disk | AlertType::Swap | AlertType::GpuVram => true,
+        }
+    }
 }
 
 impl std::fmt::Display for AlertType {
@@ -19,6 +68,11 @@ impl std::fmt::Display for AlertType {
             AlertType::Cpu => write!(f, "cpu"),
             AlertType::Memory => write!(f, "memory"),
             AlertType::Disk => write!(f, "disk"),
+            AlertType::Swap => write!(f, "swap"),
+            AlertType::LoadPerCore => write!(f, "load_per_core"),
+            AlertType::TcpTimeWait => write!(f, "tcp_time_wait"),
+            AlertType::DiskWrite => write!(f, "disk_write"),
+            AlertType::GpuVram => write!(f, "gpu_vram"),
         }
     }
 }
@@ -31,16 +85,187 @@ impl std::str::FromStr for AlertType {
             "cpu" => Ok(AlertType::Cpu),
             "memory" => Ok(AlertType::Memory),
             "disk" => Ok(AlertType::Disk),
+            "swap" => Ok(AlertType::Swap),
+            "load_per_core" => Ok(AlertType::LoadPerCore),
+            "tcp_time_wait" => Ok(AlertType::TcpTimeWait),
+            "disk_write" => Ok(AlertType::DiskWrite),
+            "gpu_vram" => Ok(AlertType::GpuVram),
             _ => Err(format!("Unknown alert type: {s}")),
         }
     }
 }
 
+/// Unit a threshold and its alert values are expressed in.
+///
+/// `Absolute` is the metric's natural unit: bytes for memory, disk, swap and
+/// VRAM, bytes per second for disk writes, a socket count for TIME_WAIT and
+/// the raw ratio for load per core.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertUnit {
+    #[default]
+    Percent,
+    Absolute,
+}
+
+impl std::fmt::Display for AlertUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertUnit::Percent => write!(f, "percent"),
+            AlertUnit::Absolute => write!(f, "absolute"),
+        }
+    }
+}
+
+impl std::str::FromStr for AlertUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "percent" => Ok(AlertUnit::Percent),
+            "absolute" => Ok(AlertUnit::Absolute),
+            _ => Err(format!("Unknown alert unit: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertThreshold {
     pub alert_type: AlertType,
+    /// Threshold value, in `unit`.
     pub threshold_percent: f64,
     pub enabled: bool,
+    #[serde(default)]
+    pub unit: AlertUnit,
+    /// Distro this threshold overrides the global default for; `None` for a default.
+    #[serde(default)]
+    pub distro_name: Option<String>,
+    /// How long the value must stay at or above the threshold before the
+    /// alert fires; 0 fires on the first sample.
+    #[serde(default)]
+    pub sustained_secs: u64,
+    /// A firing alert resolves once the value drops below this (hysteresis);
+    /// `None` uses `threshold_percent`.
+    #[serde(default)]
+    pub clear_percent: Option<f64>,
+    /// Notification channels the alert is sent to; none sends a desktop notification.
+    #[serde(default)]
+    pub channels: Vec<String>,
+}
+
+impl AlertThreshold {
+    /// Defaults used until the user saves their own: every alert type, disabled.
+    pub fn builtin_defaults() -> Vec<AlertThreshold> {
+        [
+            (AlertType::Cpu, 90.0, AlertUnit::Percent),
+            (AlertType::Memory, 85.0, AlertUnit::Percent),
+            (AlertType::Disk, 90.0, AlertUnit::Percent),
+            (AlertType::Swap, 50.0, AlertUnit::Percent),
+            (AlertType::LoadPerCore, 2.0, AlertUnit::Absolute),
+            (AlertType::TcpTimeWait, 5000.0, AlertUnit::Absolute),
+            (
+                AlertType::DiskWrite,
+                100.0 * 1024.0 * 1024.0,
+                AlertUnit::Absolute,
+            ),
+            (AlertType::GpuVram, 90.0, AlertUnit::Percent),
+        ]
+        .into_iter()
+        .map(|(alert_type, threshold_percent, unit)| AlertThreshold {
+            alert_type,
+            threshold_percent,
+            enabled: false,
+            unit,
+            distro_name: None,
+            sustained_secs: 0,
+            clear_percent: None,
+            channels: Vec::new(),
+        })
+        .collect()
+    }
+
+    /// Value below which a firing alert resolves.
+    pub fn clear_threshold(&self) -> f64 {
+        self.clear_percent.map_or(self.threshold_percent, |clear| {
+            clear.min(self.threshold_percent)
+        })
+    }
+}
+
+/// Global default thresholds plus per-distro overrides, as read by the
+/// metrics collector on every tick.
+#[derive(Debug, Clone)]
+pub struct AlertThresholdSet {
+    defaults: Vec<AlertThreshold>,
+    overrides: HashMap<String, Vec<AlertThreshold>>,
+}
+
+impl Default for AlertThresholdSet {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl AlertThresholdSet {
+    /// Build the set from stored thresholds; alert types without a stored
+    /// default fall back to [`AlertThreshold::builtin_defaults`].
+    pub fn new(stored: Vec<AlertThreshold>) -> Self {
+        let mut set = Self {
+            defaults: AlertThreshold::builtin_defaults(),
+            overrides: HashMap::new(),
+        };
+        let (defaults, overrides): (Vec<_>, Vec<_>) =
+            stored.into_iter().partition(|t| t.distro_name.is_none());
+        set.set_defaults(defaults);
+        for threshold in overrides {
+            if let Some(distro) = threshold.distro_name.clone() {
+                set.overrides.entry(distro).or_default().push(threshold);
+            }
+        }
+        set
+    }
+
+    pub fn defaults(&self) -> &[AlertThreshold] {
+        &self.defaults
+    }
+
+    /// Update the global defaults of the given alert types; the others keep
+    /// theirs.
+    pub fn set_defaults(&mut self, thresholds: Vec<AlertThreshold>) {
+        for threshold in thresholds {
+            if let Some(default) = self
+                .defaults
+                .iter_mut()
+                .find(|d| d.alert_type == threshold.alert_type)
+            {
+                *default = threshold;
+            }
+        }
+    }
+
+    /// Replace the overrides of one distro; an empty list removes them.
+    pub fn set_overrides(&mut self, distro: &str, thresholds: Vec<AlertThreshold>) {
+        if thresholds.is_empty() {
+            self.overrides.remove(distro);
+        } else {
+            self.overrides.insert(distro.to_string(), thresholds);
+        }
+    }
+
+    /// Thresholds in force for `distro`: its override for each alert type,
+    /// else the global default.
+    pub fn effective(&self, distro: &str) -> Vec<AlertThreshold> {
+        let overrides = self.overrides.get(distro);
+        self.defaults
+            .iter()
+            .map(|default| {
+                overrides
+                    .and_then(|o| o.iter().find(|t| t.alert_type == default.alert_type))
+                    .unwrap_or(default)
+                    .clone()
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,22 +275,47 @@ pub struct AlertRecord {
     pub alert_type: AlertType,
     pub threshold: f64,
     pub actual_value: f64,
+    /// Unit of `threshold`, `actual_value` and `resolved_value`.
+    #[serde(default)]
+    pub unit: AlertUnit,
     pub timestamp: DateTime<Utc>,
     pub acknowledged: bool,
+    /// When the value dropped below the clear threshold; `None` while firing.
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Value that resolved the alert; `None` while firing or when the distro stopped.
+    #[serde(default)]
+    pub resolved_value: Option<f64>,
+    /// Silence or maintenance window the alert fired during; suppressed
+    /// alerts send no notification.
+    #[serde(default)]
+    pub suppressed_by: Option<String>,
 }
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait AlertingPort: Send + Sync {
-    /// Record a triggered alert.
+    /// Record a triggered alert, firing until resolved. Returns its ID.
     async fn record_alert(
         &self,
         distro: &DistroName,
         alert_type: AlertType,
+        unit: AlertUnit,
         threshold: f64,
         actual_value: f64,
+        suppressed_by: Option<String>,
+    ) -> Result<i64, DomainError>;
+
+    /// Mark a firing alert resolved, with the value that cleared it.
+    async fn resolve_alert(
+        &self,
+        alert_id: i64,
+        resolved_value: Option<f64>,
     ) -> Result<(), DomainError>;
 
+    /// Alerts still firing, across all distros.
+    async fn list_firing_alerts(&self) -> Result<Vec<AlertRecord>, DomainError>;
+
     /// Fetch recent alerts for a distro.
     async fn get_recent_alerts(
         &self,
@@ -76,11 +326,38 @@ pub trait AlertingPort: Send + Sync {
     /// Acknowledge an alert by ID.
     async fn acknowledge_alert(&self, alert_id: i64) -> Result<(), DomainError>;
 
-    /// Purge old alerts. Returns rows deleted.
+    /// Purge alerts resolved before `before`. Returns rows deleted.
     async fn purge_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
 
     /// Delete all alerts for a distro.
     async fn delete_by_distro(&self, distro: &DistroName) -> Result<(), DomainError>;
+
+    /// Every stored threshold: global defaults and per-distro overrides.
+    async fn list_thresholds(&self) -> Result<Vec<AlertThreshold>, DomainError>;
+
+    /// Save global defaults (`distro` = `None`), leaving the alert types not
+    /// given as they are, or replace one distro's overrides.
+    async fn save_thresholds(
+        &self,
+        distro: Option<String>,
+        thresholds: Vec<AlertThreshold>,
+    ) -> Result<(), DomainError>;
+
+    /// Every stored silence, ended ones included until purged.
+    async fn list_silences(&self) -> Result<Vec<AlertSilence>, DomainError>;
+
+    /// Insert or update a silence.
+    async fn save_silence(&self, silence: &AlertSilence) -> Result<(), DomainError>;
+
+    /// Purge silences that ended before `before`. Returns rows deleted.
+    async fn purge_silences_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
+
+    async fn list_maintenance_windows(&self) -> Result<Vec<MaintenanceWindow>, DomainError>;
+
+    /// Insert or update a maintenance window.
+    async fn save_maintenance_window(&self, window: &MaintenanceWindow) -> Result<(), DomainError>;
+
+    async fn delete_maintenance_window(&self, window_id: &str) -> Result<(), DomainError>;
 }
 
 #[cfg(test)]
@@ -92,6 +369,8 @@ mod tests {
         assert_eq!(AlertType::Cpu.to_string(), "cpu");
         assert_eq!(AlertType::Memory.to_string(), "memory");
         assert_eq!(AlertType::Disk.to_string(), "disk");
+        assert_eq!(AlertType::LoadPerCore.to_string(), "load_per_core");
+        assert_eq!(AlertType::TcpTimeWait.to_string(), "tcp_time_wait");
     }
 
     #[test]
@@ -111,7 +390,7 @@ mod tests {
 
     #[test]
     fn alert_type_roundtrip() {
-        for alert_type in [AlertType::Cpu, AlertType::Memory, AlertType::Disk] {
+        for alert_type in AlertType::ALL {
             let s = alert_type.to_string();
             let parsed: AlertType = s.parse().unwrap();
             assert_eq!(parsed, alert_type);
@@ -120,19 +399,52 @@ mod tests {
 
     #[test]
     fn alert_type_serde_roundtrip() {
-        for alert_type in [AlertType::Cpu, AlertType::Memory, AlertType::Disk] {
+        for alert_type in AlertType::ALL {
             let json = serde_json::to_string(&alert_type).unwrap();
             let parsed: AlertType = serde_json::from_str(&json).unwrap();
             assert_eq!(parsed, alert_type);
         }
     }
 
+    #[test]
+    fn alert_type_serde_matches_display() {
+        for alert_type in AlertType::ALL {
+            let json = serde_json::to_string(&alert_type).unwrap();
+            assert_eq!(json, format!("\"{alert_type}\""));
+        }
+    }
+
+    #[test]
+    fn alert_type_supported_units() {
+        assert!(AlertType::Cpu.supports(AlertUnit::Percent));
+        assert!(!AlertType::Cpu.supports(AlertUnit::Absolute));
+        assert!(AlertType::Swap.supports(AlertUnit::Absolute));
+        assert!(!AlertType::TcpTimeWait.supports(AlertUnit::Percent));
+        for threshold in AlertThreshold::builtin_defaults() {
+            assert!(threshold.alert_type.supports(threshold.unit));
+        }
+    }
+
+    #[test]
+    fn alert_threshold_unit_defaults_to_percent() {
+        let parsed: AlertThreshold = serde_json::from_str(
+            r#"{"alert_type":"memory","threshold_percent":85.0,"enabled":true}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.unit, AlertUnit::Percent);
+    }
+
     #[test]
     fn alert_threshold_serde() {
         let threshold = AlertThreshold {
             alert_type: AlertType::Cpu,
             threshold_percent: 90.0,
             enabled: true,
+            unit: AlertUnit::Percent,
+            distro_name: None,
+            sustained_secs: 0,
+            clear_percent: None,
+            channels: Vec::new(),
         };
         let json = serde_json::to_string(&threshold).unwrap();
         let parsed: AlertThreshold = serde_json::from_str(&json).unwrap();
@@ -141,6 +453,41 @@ mod tests {
         assert!(parsed.enabled);
     }
 
+    #[test]
+    fn threshold_set_resolves_overrides_over_defaults() {
+        let threshold = |alert_type, percent, distro: Option<&str>| AlertThreshold {
+            alert_type,
+            threshold_percent: percent,
+            enabled: true,
+            unit: AlertUnit::Percent,
+            distro_name: distro.map(String::from),
+            sustained_secs: 0,
+            clear_percent: None,
+            channels: Vec::new(),
+        };
+        let mut set = AlertThresholdSet::new(vec![
+            threshold(AlertType::Cpu, 80.0, None),
+            threshold(AlertType::Cpu, 95.0, Some("Ubuntu")),
+        ]);
+
+        let ubuntu = set.effective("Ubuntu");
+        assert_eq!(ubuntu.len(), AlertType::ALL.len());
+        assert!((ubuntu[0].threshold_percent - 95.0).abs() < f64::EPSILON);
+        assert_eq!(ubuntu[0].distro_name.as_deref(), Some("Ubuntu"));
+        // Memory has no stored default, so the built-in (disabled) one applies
+        assert!(!ubuntu[1].enabled);
+        assert!((set.effective("Debian")[0].threshold_percent - 80.0).abs() < f64::EPSILON);
+
+        set.set_overrides("Ubuntu", vec![]);
+        assert!(set.effective("Ubuntu")[0].distro_name.is_none());
+
+        // Updating one default keeps the others
+        set.set_defaults(vec![threshold(AlertType::Memory, 70.0, None)]);
+        let debian = set.effective("Debian");
+        assert!((debian[0].threshold_percent - 80.0).abs() < f64::EPSILON);
+        assert!(debian[1].enabled);
+    }
+
     #[test]
     fn alert_record_serde() {
         let record = AlertRecord {
@@ -149,8 +496,12 @@ mod tests {
             alert_type: AlertType::Memory,
             threshold: 85.0,
             actual_value: 91.5,
+            unit: AlertUnit::Percent,
             timestamp: Utc::now(),
             acknowledged: false,
+            resolved_at: None,
+            resolved_value: None,
+            suppressed_by: Some("Silence: Kernel build".into()),
         };
         let json = serde_json::to_string(&record).unwrap();
         let parsed: AlertRecord = serde_json::from_str(&json).unwrap();
@@ -158,6 +509,10 @@ mod tests {
         assert_eq!(parsed.distro_name, "Ubuntu");
         assert_eq!(parsed.alert_type, AlertType::Memory);
         assert!(!parsed.acknowledged);
+        assert_eq!(
+            parsed.suppressed_by.as_deref(),
+            Some("Silence: Kernel build")
+        );
     }
 
     mod proptests {
@@ -171,13 +526,11 @@ mod tests {
             }
 
             #[test]
-            fn alert_type_from_str_only_accepts_known(s in "[a-z]{1,10}") {
+            fn alert_type_from_str_only_accepts_known(s in "[a-z_]{1,14}") {
+                let known = AlertType::ALL.iter().any(|t| t.to_string() == s);
                 match s.parse::<AlertType>() {
-                    Ok(at) => prop_assert!(
-                        s == "cpu" || s == "memory" || s == "disk",
-                        "Unexpected success for {:?} -> {:?}", s, at
-                    ),
-                    Err(_) => prop_assert!(s != "cpu" && s != "memory" && s != "disk"),
+                    Ok(at) => prop_assert!(known, "Unexpected success for {:?} -> {:?}", s, at),
+                    Err(_) => prop_assert!(!known),
                 }
             }
         }
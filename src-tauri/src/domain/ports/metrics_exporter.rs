@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::domain::entities::metrics_settings::MetricsSettings;
+use crate::domain::errors::DomainError;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait MetricsExporterPort: Send + Sync {
+    /// Start, move or stop the scrape endpoint to match the settings.
+    /// Fails when the endpoint is enabled and its port cannot be bound.
+    async fn apply(&self, settings: &MetricsSettings) -> Result<(), DomainError>;
+}
@@ -2,11 +2,12 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::domain::entities::monitoring::SystemMetrics;
+use crate::domain::entities::metrics_settings::{MetricsSettings, MetricsTier};
+use crate::domain::entities::monitoring::{ProcessSample, ServiceMetrics, SystemMetrics};
 use crate::domain::errors::DomainError;
 use crate::domain::value_objects::DistroName;
 
-/// An aggregated data point (min/avg/max over a time bucket).
+/// An aggregated data point (min/avg/max over a bucket of a rollup tier).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedMetricsPoint {
     pub period_start: DateTime<Utc>,
@@ -60,6 +61,38 @@ pub struct RawMetricsRow {
     pub gpu_vram_total: Option<u64>,
 }
 
+/// Resource usage of one process over a time range, from the per-process
+/// history (samples or 1-minute buckets).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub user: String,
+    pub command: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub sample_count: u32,
+    pub cpu_avg: f64,
+    pub cpu_max: f64,
+    pub rss_avg: u64,
+    pub rss_max: u64,
+}
+
+/// Resource usage of one systemd unit over a time range, from the
+/// per-service history (samples or 1-minute buckets).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceUsage {
+    pub unit: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub sample_count: u32,
+    pub cpu_avg: f64,
+    pub cpu_max: f64,
+    pub memory_avg: u64,
+    pub memory_max: u64,
+    pub io_read_avg: u64,
+    pub io_write_avg: u64,
+}
+
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait MetricsRepositoryPort: Send + Sync {
@@ -74,10 +107,11 @@ pub trait MetricsRepositoryPort: Send + Sync {
         to: DateTime<Utc>,
     ) -> Result<Vec<RawMetricsRow>, DomainError>;
 
-    /// Query aggregated metrics for a time range.
+    /// Query the buckets of a rollup tier starting in a time range.
     async fn query_aggregated(
         &self,
         distro: &DistroName,
+        tier: MetricsTier,
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<AggregatedMetricsPoint>, DomainError>;
@@ -91,12 +125,120 @@ pub trait MetricsRepositoryPort: Send + Sync {
         bucket_end: DateTime<Utc>,
     ) -> Result<u64, DomainError>;
 
+    /// Roll the buckets of the tier below `tier` up into `tier` buckets for a
+    /// given time window; buckets that already exist are left alone.
+    /// `MetricsTier::Minute` aggregates the raw metrics like
+    /// `aggregate_raw_buckets`. Returns the number of buckets created.
+    async fn rollup_buckets(
+        &self,
+        tier: MetricsTier,
+        bucket_start: DateTime<Utc>,
+        bucket_end: DateTime<Utc>,
+    ) -> Result<u64, DomainError>;
+
     /// Delete raw metrics older than the given timestamp. Returns rows deleted.
     async fn purge_raw_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
 
-    /// Delete aggregated metrics older than the given timestamp. Returns rows deleted.
-    async fn purge_aggregated_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
+    /// Delete the buckets of a tier older than the given timestamp. Returns rows deleted.
+    async fn purge_aggregated_before(
+        &self,
+        tier: MetricsTier,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DomainError>;
+
+    /// Store the process samples of a distro taken at `timestamp`.
+    async fn store_process_samples(
+        &self,
+        distro: &DistroName,
+        timestamp: DateTime<Utc>,
+        samples: &[ProcessSample],
+    ) -> Result<(), DomainError>;
+
+    /// Processes of a distro sampled in a time range, by peak CPU.
+    async fn query_top_processes_raw(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<ProcessUsage>, DomainError>;
+
+    /// Processes of a distro over the 1-minute buckets starting in a time
+    /// range, by peak CPU.
+    async fn query_top_processes_aggregated(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<ProcessUsage>, DomainError>;
+
+    /// Aggregate process samples into per-process 1-minute buckets for a
+    /// given time window. Returns the number of buckets created.
+    async fn aggregate_process_buckets(
+        &self,
+        bucket_start: DateTime<Utc>,
+        bucket_end: DateTime<Utc>,
+    ) -> Result<u64, DomainError>;
+
+    /// Delete process samples older than the given timestamp. Returns rows deleted.
+    async fn purge_process_raw_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
+
+    /// Delete process buckets older than the given timestamp. Returns rows deleted.
+    async fn purge_process_aggregated_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DomainError>;
+
+    /// Store the service samples of a distro taken at `timestamp`.
+    async fn store_service_metrics(
+        &self,
+        distro: &DistroName,
+        timestamp: DateTime<Utc>,
+        services: &[ServiceMetrics],
+    ) -> Result<(), DomainError>;
+
+    /// Units of a distro sampled in a time range, by peak memory.
+    async fn query_service_usage_raw(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ServiceUsage>, DomainError>;
+
+    /// Units of a distro over the 1-minute buckets starting in a time range,
+    /// by peak memory.
+    async fn query_service_usage_aggregated(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ServiceUsage>, DomainError>;
+
+    /// Aggregate service samples into per-unit 1-minute buckets for a given
+    /// time window. Returns the number of buckets created.
+    async fn aggregate_service_buckets(
+        &self,
+        bucket_start: DateTime<Utc>,
+        bucket_end: DateTime<Utc>,
+    ) -> Result<u64, DomainError>;
+
+    /// Delete service samples older than the given timestamp. Returns rows deleted.
+    async fn purge_service_raw_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
+
+    /// Delete service buckets older than the given timestamp. Returns rows deleted.
+    async fn purge_service_aggregated_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DomainError>;
+
+    /// Stored metrics settings, `None` until they are first saved.
+    async fn load_settings(&self) -> Result<Option<MetricsSettings>, DomainError>;
+
+    /// Save the metrics settings.
+    async fn save_settings(&self, settings: &MetricsSettings) -> Result<(), DomainError>;
 
-    /// Delete all metrics (raw + aggregated) for a distro.
+    /// Delete all metrics (raw + every tier, system, per-process and per-service)
+    /// for a distro.
     async fn delete_by_distro(&self, distro: &DistroName) -> Result<(), DomainError>;
 }
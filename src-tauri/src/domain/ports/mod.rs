@@ -1,15 +1,31 @@
 pub mod alerting;
 pub mod audit_logger;
+pub mod metrics_exporter;
 pub mod metrics_repository;
 pub mod monitoring_provider;
+pub mod notification_channel;
+pub mod notification_repository;
 pub mod port_forwarding;
+pub mod replication_repository;
+pub mod safety_policy_repository;
+pub mod schedule_repository;
+pub mod snapshot_replication;
 pub mod snapshot_repository;
+pub mod template_repository;
 pub mod wsl_manager;
 
 pub use alerting::AlertingPort;
 pub use audit_logger::AuditLoggerPort;
+pub use metrics_exporter::MetricsExporterPort;
 pub use metrics_repository::MetricsRepositoryPort;
 pub use monitoring_provider::MonitoringProviderPort;
+pub use notification_channel::NotificationChannelPort;
+pub use notification_repository::NotificationRepositoryPort;
 pub use port_forwarding::{PortForwardRulesRepository, PortForwardingPort};
+pub use replication_repository::ReplicationRepositoryPort;
+pub use safety_policy_repository::SafetyPolicyRepositoryPort;
+pub use schedule_repository::ScheduleRepositoryPort;
+pub use snapshot_replication::SnapshotReplicationPort;
 pub use snapshot_repository::SnapshotRepositoryPort;
+pub use template_repository::TemplateRepositoryPort;
 pub use wsl_manager::WslManagerPort;
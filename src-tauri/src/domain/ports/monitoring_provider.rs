@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 
 use crate::domain::entities::monitoring::{
-    CpuMetrics, DiskMetrics, MemoryMetrics, NetworkMetrics, ProcessInfo, SystemMetrics,
+    CpuMetrics, DiskMetrics, MemoryMetrics, NetworkMetrics, ProcessInfo, ServiceCgroupStats,
+    SystemMetrics,
 };
 use crate::domain::errors::DomainError;
 use crate::domain::value_objects::DistroName;
@@ -26,4 +27,11 @@ pub trait MonitoringProviderPort: Send + Sync {
 
     /// Get all system metrics in a single batched call.
     async fn get_all_metrics(&self, distro: &DistroName) -> Result<SystemMetrics, DomainError>;
+
+    /// Get the cgroup v2 counters of the systemd units of a distro; empty
+    /// when systemd does not manage its cgroups.
+    async fn get_service_stats(
+        &self,
+        distro: &DistroName,
+    ) -> Result<Vec<ServiceCgroupStats>, DomainError>;
 }
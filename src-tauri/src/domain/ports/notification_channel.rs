@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::entities::notification_channel::NotificationChannel;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::alerting::{AlertType, AlertUnit};
+
+/// An alert that fired or resolved, ready to be sent through any channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertNotification {
+    /// `None` for test notifications and alerts that failed to record.
+    pub alert_id: Option<i64>,
+    pub distro_name: String,
+    pub alert_type: AlertType,
+    pub unit: AlertUnit,
+    pub threshold: f64,
+    /// `None` when a stopped distro resolved the alert.
+    pub actual_value: Option<f64>,
+    pub resolved: bool,
+    pub title: String,
+    pub body: String,
+}
+
+/// Sends alert notifications through one kind of channel (desktop, webhook, email).
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait NotificationChannelPort: Send + Sync {
+    /// Deliver `notification` with the settings of `channel`. A single
+    /// attempt; retries are up to the caller.
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notification: &AlertNotification,
+    ) -> Result<(), DomainError>;
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+use crate::domain::entities::notification_channel::{NotificationChannel, NotificationDelivery};
+use crate::domain::errors::DomainError;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait NotificationRepositoryPort: Send + Sync {
+    /// All configured channels, ordered by name
+    async fn list_channels(&self) -> Result<Vec<NotificationChannel>, DomainError>;
+
+    /// Save or update a channel
+    async fn save_channel(&self, channel: &NotificationChannel) -> Result<(), DomainError>;
+
+    /// Delete a channel; rules still routed to it skip it
+    async fn delete_channel(&self, id: &str) -> Result<(), DomainError>;
+
+    /// Log a delivery. Returns its ID; only the most recent deliveries are kept.
+    async fn record_delivery(&self, delivery: &NotificationDelivery) -> Result<i64, DomainError>;
+
+    /// Most recent deliveries first
+    async fn list_deliveries(&self, limit: u32) -> Result<Vec<NotificationDelivery>, DomainError>;
+}
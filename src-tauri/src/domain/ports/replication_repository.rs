@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use crate::domain::entities::replication::{ReplicationTarget, SnapshotReplication};
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::SnapshotId;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ReplicationRepositoryPort: Send + Sync {
+    /// The configured replication target, if any
+    async fn get_target(&self) -> Result<Option<ReplicationTarget>, DomainError>;
+
+    /// Replace the replication target; `None` turns replication off
+    async fn save_target(&self, target: Option<ReplicationTarget>) -> Result<(), DomainError>;
+
+    /// Save or update the replication state of a snapshot
+    async fn save(&self, replication: &SnapshotReplication) -> Result<(), DomainError>;
+
+    /// Replication state of a snapshot, `None` if it was never replicated
+    async fn get(&self, id: &SnapshotId) -> Result<Option<SnapshotReplication>, DomainError>;
+
+    /// Replication state of every snapshot that has one
+    async fn list(&self) -> Result<Vec<SnapshotReplication>, DomainError>;
+}
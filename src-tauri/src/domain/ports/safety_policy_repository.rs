@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::domain::entities::safety_snapshot::SafetySnapshotPolicy;
+use crate::domain::errors::DomainError;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait SafetyPolicyRepositoryPort: Send + Sync {
+    /// Save or replace the policy of a distro
+    async fn save(&self, policy: &SafetySnapshotPolicy) -> Result<(), DomainError>;
+
+    /// Policy of a distro, `None` when it has none (no safety snapshots)
+    async fn get(&self, distro_name: &str) -> Result<Option<SafetySnapshotPolicy>, DomainError>;
+
+    /// All policies, ordered by distro
+    async fn list(&self) -> Result<Vec<SafetySnapshotPolicy>, DomainError>;
+
+    /// Remove the policy of a distro; its safety snapshots are kept
+    async fn delete(&self, distro_name: &str) -> Result<(), DomainError>;
+}
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::entities::snapshot_schedule::SnapshotSchedule;
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::SnapshotId;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ScheduleRepositoryPort: Send + Sync {
+    /// Save or update a schedule
+    async fn save(&self, schedule: &SnapshotSchedule) -> Result<(), DomainError>;
+
+    /// List all schedules, optionally filtered by distro
+    async fn list(&self, distro_name: Option<String>)
+    -> Result<Vec<SnapshotSchedule>, DomainError>;
+
+    /// Get a schedule by its ID
+    async fn get(&self, id: &str) -> Result<Option<SnapshotSchedule>, DomainError>;
+
+    /// Delete a schedule; the snapshots it created are kept
+    async fn delete(&self, id: &str) -> Result<(), DomainError>;
+
+    /// Delete all schedules for a distro
+    async fn delete_by_distro(&self, distro_name: &str) -> Result<(), DomainError>;
+
+    /// Record the start of a scheduled run
+    async fn mark_run(&self, id: &str, at: DateTime<Utc>) -> Result<(), DomainError>;
+
+    /// Link a snapshot to the schedule that created it (retention only prunes linked snapshots)
+    async fn add_snapshot(&self, id: &str, snapshot_id: &SnapshotId) -> Result<(), DomainError>;
+
+    /// Snapshots created by a schedule that still exist
+    async fn list_snapshots(&self, id: &str) -> Result<Vec<SnapshotId>, DomainError>;
+}
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+
+use crate::domain::errors::DomainError;
+
+/// One part of a multipart upload, as stored remotely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedPart {
+    /// 1-based part number.
+    pub number: u32,
+    pub etag: String,
+    pub size: u64,
+}
+
+/// Object storage snapshot archives are replicated to. Keys are relative to
+/// the configured bucket.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait SnapshotReplicationPort: Send + Sync {
+    /// Start a multipart upload and return its upload id
+    async fn create_multipart_upload(&self, key: &str) -> Result<String, DomainError>;
+
+    /// Parts already stored for an unfinished upload, by part number.
+    /// Fails if the upload no longer exists (completed, aborted or expired).
+    async fn list_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<Vec<UploadedPart>, DomainError>;
+
+    /// Upload one part and return its ETag
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        number: u32,
+        data: Vec<u8>,
+    ) -> Result<String, DomainError>;
+
+    /// Assemble the object from `parts` (sorted by number)
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[UploadedPart],
+    ) -> Result<(), DomainError>;
+
+    /// Discard an unfinished upload and its parts
+    async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<(), DomainError>;
+
+    /// Size of the object, `None` if it does not exist
+    async fn object_size(&self, key: &str) -> Result<Option<u64>, DomainError>;
+
+    /// Read `len` bytes of the object starting at `offset` (fewer at its end)
+    async fn read_range(&self, key: &str, offset: u64, len: u64) -> Result<Vec<u8>, DomainError>;
+}
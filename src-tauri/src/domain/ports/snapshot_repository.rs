@@ -1,9 +1,23 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
-use crate::domain::entities::snapshot::Snapshot;
+use crate::domain::entities::snapshot::{Snapshot, SnapshotChunk};
 use crate::domain::errors::DomainError;
 use crate::domain::value_objects::{DistroName, SnapshotId};
 
+/// Criteria of a filtered snapshot listing; `None`/empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotFilter {
+    pub distro_name: Option<DistroName>,
+    /// Snapshots must carry every one of these (normalized) tags.
+    pub tags: Vec<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub min_size_bytes: Option<u64>,
+    pub max_size_bytes: Option<u64>,
+    pub pinned: Option<bool>,
+}
+
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait SnapshotRepositoryPort: Send + Sync {
@@ -16,12 +30,32 @@ pub trait SnapshotRepositoryPort: Send + Sync {
     /// List all snapshots across all distros
     async fn list_all(&self) -> Result<Vec<Snapshot>, DomainError>;
 
+    /// List the snapshots matching `filter`, newest first
+    async fn list_filtered(&self, filter: &SnapshotFilter) -> Result<Vec<Snapshot>, DomainError>;
+
+    /// Distinct tags in use, sorted
+    async fn list_tags(&self) -> Result<Vec<String>, DomainError>;
+
     /// Get a snapshot by its ID
     async fn get_by_id(&self, id: &SnapshotId) -> Result<Snapshot, DomainError>;
 
-    /// Delete a snapshot's metadata
+    /// Delete a snapshot's metadata (including its chunk manifest and tags)
     async fn delete(&self, id: &SnapshotId) -> Result<(), DomainError>;
 
-    /// Delete all snapshots for a distro and return them (for file cleanup)
+    /// Delete all unpinned snapshots for a distro and return them (for file cleanup).
+    /// Pinned snapshots are kept.
     async fn delete_by_distro(&self, distro: &DistroName) -> Result<Vec<Snapshot>, DomainError>;
+
+    /// Replace the ordered chunk manifest of a chunked snapshot
+    async fn save_chunk_manifest(
+        &self,
+        id: &SnapshotId,
+        chunks: &[SnapshotChunk],
+    ) -> Result<(), DomainError>;
+
+    /// Get the ordered chunk manifest of a snapshot (empty for file snapshots)
+    async fn get_chunk_manifest(&self, id: &SnapshotId) -> Result<Vec<SnapshotChunk>, DomainError>;
+
+    /// Distinct chunk hashes still referenced by any snapshot (for chunk store GC)
+    async fn list_referenced_chunks(&self) -> Result<Vec<String>, DomainError>;
 }
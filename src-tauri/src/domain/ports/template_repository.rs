@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::domain::entities::distro_template::DistroTemplate;
+use crate::domain::errors::DomainError;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait TemplateRepositoryPort: Send + Sync {
+    /// Save or update a template
+    async fn save(&self, template: &DistroTemplate) -> Result<(), DomainError>;
+
+    /// Get a template by its ID
+    async fn get(&self, id: &str) -> Result<Option<DistroTemplate>, DomainError>;
+
+    /// All templates, ordered by name
+    async fn list(&self) -> Result<Vec<DistroTemplate>, DomainError>;
+
+    /// Delete a template; its snapshot and the distros provisioned from it are kept
+    async fn delete(&self, id: &str) -> Result<(), DomainError>;
+}
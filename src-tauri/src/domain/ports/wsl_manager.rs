@@ -1,11 +1,12 @@
 use async_trait::async_trait;
+use tokio::sync::mpsc::UnboundedSender;
 
 use crate::domain::entities::distro::Distro;
 use crate::domain::entities::snapshot::ExportFormat;
 use crate::domain::entities::wsl_config::{WslDistroConfig, WslGlobalConfig};
 use crate::domain::entities::wsl_version::WslVersionInfo;
 use crate::domain::errors::DomainError;
-use crate::domain::value_objects::DistroName;
+use crate::domain::value_objects::{DistroName, EncryptionKey};
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
@@ -25,21 +26,26 @@ pub trait WslManagerPort: Send + Sync {
     /// Unregister a distribution (removes it and its filesystem)
     async fn unregister_distro(&self, name: &DistroName) -> Result<(), DomainError>;
 
-    /// Export a distribution to a file
+    /// Export a distribution to a file, encrypted with `key` when given
+    /// (tar formats only).
+    /// Dropping the returned future aborts the export (wsl.exe is killed).
     async fn export_distro(
         &self,
         name: &DistroName,
         path: &str,
         format: ExportFormat,
+        key: Option<EncryptionKey>,
     ) -> Result<(), DomainError>;
 
-    /// Import a distribution from a file
+    /// Import a distribution from a file, decrypted with `key` when given.
+    /// Dropping the returned future aborts the import (wsl.exe is killed).
     async fn import_distro(
         &self,
         name: &DistroName,
         install_location: &str,
         file_path: &str,
         format: ExportFormat,
+        key: Option<EncryptionKey>,
     ) -> Result<(), DomainError>;
 
     /// Shutdown all WSL instances
@@ -49,12 +55,36 @@ pub trait WslManagerPort: Send + Sync {
     async fn exec_in_distro(&self, name: &DistroName, command: &str)
     -> Result<String, DomainError>;
 
+    /// Execute a command inside a distribution as root
+    async fn exec_in_distro_as_root(
+        &self,
+        name: &DistroName,
+        command: &str,
+    ) -> Result<String, DomainError>;
+
+    /// Run a shell script as root inside a distribution, sending each line it
+    /// prints (stdout and stderr) to `output` as it is printed. Fails when the
+    /// script exits non-zero.
+    async fn run_script(
+        &self,
+        name: &DistroName,
+        script: &str,
+        output: UnboundedSender<String>,
+    ) -> Result<(), DomainError>;
+
     /// Read the global .wslconfig
     async fn get_global_config(&self) -> Result<WslGlobalConfig, DomainError>;
 
     /// Read a distro's /etc/wsl.conf
     async fn get_distro_config(&self, name: &DistroName) -> Result<WslDistroConfig, DomainError>;
 
+    /// Write a distro's /etc/wsl.conf. Sections it does not model are kept.
+    async fn update_distro_config(
+        &self,
+        name: &DistroName,
+        config: WslDistroConfig,
+    ) -> Result<(), DomainError>;
+
     /// Write the global .wslconfig
     async fn update_global_config(&self, config: WslGlobalConfig) -> Result<(), DomainError>;
 
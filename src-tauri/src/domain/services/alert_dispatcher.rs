@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::domain::entities::notification_channel::{
+    ChannelKind, DESKTOP_CHANNEL_ID, DeliveryStatus, NotificationChannel, NotificationDelivery,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::notification_channel::{AlertNotification, NotificationChannelPort};
+use crate::domain::ports::notification_repository::NotificationRepositoryPort;
+
+const DELIVERY_ATTEMPTS: u32 = 3;
+/// Wait before the second attempt; doubled before each further one.
+const RETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Routes alert notifications to the channels of their rule, retrying failed
+/// sends and logging every delivery.
+pub struct AlertDispatcher {
+    repo: Arc<dyn NotificationRepositoryPort>,
+    senders: HashMap<ChannelKind, Arc<dyn NotificationChannelPort>>,
+    attempts: u32,
+    backoff: Duration,
+}
+
+impl AlertDispatcher {
+    pub fn new(repo: Arc<dyn NotificationRepositoryPort>) -> Self {
+        Self {
+            repo,
+            senders: HashMap::new(),
+            attempts: DELIVERY_ATTEMPTS,
+            backoff: RETRY_BACKOFF,
+        }
+    }
+
+    /// Send the channels of `kind` through `sender`.
+    pub fn with_sender(
+        mut self,
+        kind: ChannelKind,
+        sender: Arc<dyn NotificationChannelPort>,
+    ) -> Self {
+        self.senders.insert(kind, sender);
+        self
+    }
+
+    pub fn with_retry(mut self, attempts: u32, backoff: Duration) -> Self {
+        self.attempts = attempts.max(1);
+        self.backoff = backoff;
+        self
+    }
+
+    /// Send `notification` to the channels `channel_ids` name (the desktop
+    /// when there are none). Disabled and deleted channels are skipped.
+    pub async fn dispatch(
+        &self,
+        notification: &AlertNotification,
+        channel_ids: &[String],
+    ) -> Vec<NotificationDelivery> {
+        let channels = self.resolve(channel_ids).await;
+        futures::future::join_all(
+            channels
+                .iter()
+                .map(|channel| self.deliver(channel, notification)),
+        )
+        .await
+    }
+
+    /// Send `notification` through `channel`, retrying with backoff, and log
+    /// the outcome.
+    pub async fn deliver(
+        &self,
+        channel: &NotificationChannel,
+        notification: &AlertNotification,
+    ) -> NotificationDelivery {
+        let (attempts, result) = match self.senders.get(&channel.config.kind()) {
+            Some(sender) => {
+                let mut attempt = 1;
+                let mut backoff = self.backoff;
+                loop {
+                    let result = sender.send(channel, notification).await;
+                    if result.is_ok() || attempt == self.attempts {
+                        break (attempt, result);
+                    }
+                    tracing::debug!(
+                        "Notification to channel '{}' failed (attempt {attempt}), retrying",
+                        channel.name
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+            None => (
+                0,
+                Err(DomainError::NotificationError(format!(
+                    "No sender for {:?} channels",
+                    channel.config.kind()
+                ))),
+            ),
+        };
+
+        let mut delivery = NotificationDelivery {
+            id: 0,
+            channel_id: channel.id.clone(),
+            channel_name: channel.name.clone(),
+            alert_id: notification.alert_id,
+            distro_name: notification.distro_name.clone(),
+            title: notification.title.clone(),
+            status: if result.is_ok() {
+                DeliveryStatus::Delivered
+            } else {
+                DeliveryStatus::Failed
+            },
+            attempts,
+            error: result.err().map(|e| e.to_string()),
+            timestamp: chrono::Utc::now(),
+        };
+        if let Some(error) = &delivery.error {
+            tracing::warn!("Notification to channel '{}' failed: {error}", channel.name);
+        }
+        match self.repo.record_delivery(&delivery).await {
+            Ok(id) => delivery.id = id,
+            Err(e) => tracing::warn!("Failed to log notification delivery: {e}"),
+        }
+        delivery
+    }
+
+    async fn resolve(&self, channel_ids: &[String]) -> Vec<NotificationChannel> {
+        if channel_ids.is_empty() {
+            return vec![NotificationChannel::desktop()];
+        }
+        let stored = self.repo.list_channels().await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load notification channels: {e}");
+            Vec::new()
+        });
+        channel_ids
+            .iter()
+            .filter_map(|id| {
+                if id == DESKTOP_CHANNEL_ID {
+                    return Some(NotificationChannel::desktop());
+                }
+                let channel = stored.iter().find(|c| &c.id == id);
+                if channel.is_none() {
+                    tracing::warn!("Alert routed to unknown notification channel {id}");
+                }
+                channel.filter(|c| c.enabled).cloned()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use crate::domain::entities::notification_channel::ChannelConfig;
+    use crate::domain::ports::alerting::{AlertType, AlertUnit};
+    use crate::domain::ports::notification_channel::MockNotificationChannelPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
+
+    fn notification() -> AlertNotification {
+        AlertNotification {
+            alert_id: Some(7),
+            distro_name: "Ubuntu".into(),
+            alert_type: AlertType::Cpu,
+            unit: AlertUnit::Percent,
+            threshold: 90.0,
+            actual_value: Some(95.0),
+            resolved: false,
+            title: "CPU Alert - Ubuntu".into(),
+            body: "CPU at 95.0% (threshold: 90.0%)".into(),
+        }
+    }
+
+    fn webhook(id: &str, enabled: bool) -> NotificationChannel {
+        NotificationChannel {
+            id: id.into(),
+            name: format!("hook {id}"),
+            enabled,
+            config: ChannelConfig::Webhook {
+                url: "http://localhost/hook".into(),
+            },
+        }
+    }
+
+    fn logging_repo(channels: Vec<NotificationChannel>) -> MockNotificationRepositoryPort {
+        let mut repo = MockNotificationRepositoryPort::new();
+        repo.expect_list_channels()
+            .returning(move || Ok(channels.clone()));
+        repo.expect_record_delivery().returning(|_| Ok(1));
+        repo
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_channel_accepts() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let mut sender = MockNotificationChannelPort::new();
+        sender.expect_send().returning(move |_, _| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(DomainError::NotificationError("503".into()))
+            } else {
+                Ok(())
+            }
+        });
+        let dispatcher = AlertDispatcher::new(Arc::new(logging_repo(vec![webhook("a", true)])))
+            .with_sender(ChannelKind::Webhook, Arc::new(sender))
+            .with_retry(3, Duration::ZERO);
+
+        let deliveries = dispatcher.dispatch(&notification(), &["a".into()]).await;
+
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempts, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_last_attempt_and_logs_the_failure() {
+        let mut sender = MockNotificationChannelPort::new();
+        sender
+            .expect_send()
+            .times(3)
+            .returning(|_, _| Err(DomainError::NotificationError("connection refused".into())));
+        let mut repo = MockNotificationRepositoryPort::new();
+        repo.expect_list_channels()
+            .returning(|| Ok(vec![webhook("a", true)]));
+        repo.expect_record_delivery()
+            .withf(|d| {
+                d.status == DeliveryStatus::Failed && d.attempts == 3 && d.alert_id == Some(7)
+            })
+            .times(1)
+            .returning(|_| Ok(42));
+        let dispatcher = AlertDispatcher::new(Arc::new(repo))
+            .with_sender(ChannelKind::Webhook, Arc::new(sender))
+            .with_retry(3, Duration::ZERO);
+
+        let deliveries = dispatcher.dispatch(&notification(), &["a".into()]).await;
+
+        assert_eq!(deliveries[0].id, 42);
+        assert!(
+            deliveries[0]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("connection refused")
+        );
+    }
+
+    #[tokio::test]
+    async fn routes_to_the_rule_channels_or_the_desktop() {
+        let mut desktop = MockNotificationChannelPort::new();
+        desktop.expect_send().times(2).returning(|_, _| Ok(()));
+        let mut hooks = MockNotificationChannelPort::new();
+        hooks
+            .expect_send()
+            .withf(|channel, _| channel.id == "a")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let dispatcher = AlertDispatcher::new(Arc::new(logging_repo(vec![
+            webhook("a", true),
+            webhook("off", false),
+        ])))
+        .with_sender(ChannelKind::Desktop, Arc::new(desktop))
+        .with_sender(ChannelKind::Webhook, Arc::new(hooks));
+
+        // No channels: the desktop notification, as before routing existed
+        let deliveries = dispatcher.dispatch(&notification(), &[]).await;
+        assert_eq!(deliveries[0].channel_id, DESKTOP_CHANNEL_ID);
+
+        // Disabled and deleted channels are skipped
+        let routed = ["a", "off", "deleted", DESKTOP_CHANNEL_ID].map(String::from);
+        let deliveries = dispatcher.dispatch(&notification(), &routed).await;
+        assert_eq!(deliveries.len(), 2);
+        assert!(
+            deliveries
+                .iter()
+                .all(|d| d.status == DeliveryStatus::Delivered)
+        );
+    }
+}
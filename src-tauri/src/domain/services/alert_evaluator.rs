@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::domain::ports::alerting::{AlertRecord, AlertThreshold, AlertType};
+
+/// What a sample changed in an alert's lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertTransition {
+    /// The condition held long enough: record a new alert.
+    Fire,
+    /// The value dropped below the clear threshold. `alert_id` is `None`
+    /// when recording the alert failed; `suppressed` when it fired during a
+    /// silence or maintenance window.
+    Resolve {
+        alert_id: Option<i64>,
+        suppressed: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RuleState {
+    /// At or above the threshold since `since`, not for long enough yet.
+    Pending { since: Instant },
+    Firing {
+        alert_id: Option<i64>,
+        suppressed: bool,
+    },
+}
+
+/// Per (distro, alert type) state of the alert rules: a value must stay at or
+/// above the threshold for `sustained_secs` before the alert fires, and a
+/// firing alert resolves only once the value drops below the clear threshold.
+#[derive(Debug, Default)]
+pub struct AlertEvaluator {
+    states: HashMap<(String, AlertType), RuleState>,
+}
+
+impl AlertEvaluator {
+    const FIRED: RuleState = RuleState::Firing {
+        alert_id: None,
+        suppressed: false,
+    };
+
+    /// Resume the lifecycle of alerts left firing by a previous run.
+    pub fn with_firing(alerts: &[AlertRecord]) -> Self {
+        let states = alerts
+            .iter()
+            .map(|a| {
+                (
+                    (a.distro_name.clone(), a.alert_type),
+                    RuleState::Firing {
+                        alert_id: Some(a.id),
+                        suppressed: a.suppressed_by.is_some(),
+                    },
+                )
+            })
+            .collect();
+        Self { states }
+    }
+
+    /// Feed one sample of `threshold.alert_type` for `distro`.
+    pub fn evaluate(
+        &mut self,
+        distro: &str,
+        threshold: &AlertThreshold,
+        value: f64,
+        now: Instant,
+    ) -> Option<AlertTransition> {
+        let key = (distro.to_string(), threshold.alert_type);
+        let above = value >= threshold.threshold_percent;
+        let sustained = Duration::from_secs(threshold.sustained_secs);
+
+        match self.states.get(&key).copied() {
+            None if above && sustained.is_zero() => {
+                self.states.insert(key, Self::FIRED);
+                Some(AlertTransition::Fire)
+            }
+            None if above => {
+                self.states.insert(key, RuleState::Pending { since: now });
+                None
+            }
+            None => None,
+            Some(RuleState::Pending { since }) if above => {
+                if now.duration_since(since) >= sustained {
+                    self.states.insert(key, Self::FIRED);
+                    Some(AlertTransition::Fire)
+                } else {
+                    None
+                }
+            }
+            Some(RuleState::Pending { .. }) => {
+                self.states.remove(&key);
+                None
+            }
+            Some(RuleState::Firing {
+                alert_id,
+                suppressed,
+            }) if value < threshold.clear_threshold() => {
+                self.states.remove(&key);
+                Some(AlertTransition::Resolve {
+                    alert_id,
+                    suppressed,
+                })
+            }
+            Some(RuleState::Firing { .. }) => None,
+        }
+    }
+
+    /// Remember the ID the alert that just fired was recorded under.
+    pub fn set_alert_id(&mut self, distro: &str, alert_type: AlertType, alert_id: i64) {
+        if let Some(RuleState::Firing { alert_id: id, .. }) =
+            self.states.get_mut(&(distro.to_string(), alert_type))
+        {
+            *id = Some(alert_id);
+        }
+    }
+
+    /// Mark the alert that just fired as suppressed: it stays so until it
+    /// resolves, even if the silence ends first.
+    pub fn set_suppressed(&mut self, distro: &str, alert_type: AlertType) {
+        if let Some(RuleState::Firing { suppressed, .. }) =
+            self.states.get_mut(&(distro.to_string(), alert_type))
+        {
+            *suppressed = true;
+        }
+    }
+
+    /// Drop the state of a rule that no longer applies (disabled threshold,
+    /// stopped distro). Returns the alert to resolve if it was firing.
+    pub fn clear(&mut self, distro: &str, alert_type: AlertType) -> Option<AlertTransition> {
+        match self.states.remove(&(distro.to_string(), alert_type)) {
+            Some(RuleState::Firing {
+                alert_id,
+                suppressed,
+            }) => Some(AlertTransition::Resolve {
+                alert_id,
+                suppressed,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Distros with a pending or firing rule.
+    pub fn distros(&self) -> Vec<String> {
+        let mut distros: Vec<String> = self.states.keys().map(|(d, _)| d.clone()).collect();
+        distros.sort();
+        distros.dedup();
+        distros
+    }
+
+    pub fn is_firing(&self, distro: &str, alert_type: AlertType) -> bool {
+        matches!(
+            self.states.get(&(distro.to_string(), alert_type)),
+            Some(RuleState::Firing { .. })
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::alerting::AlertUnit;
+
+    fn cpu_rule(sustained_secs: u64, clear_percent: Option<f64>) -> AlertThreshold {
+        AlertThreshold {
+            alert_type: AlertType::Cpu,
+            threshold_percent: 90.0,
+            enabled: true,
+            unit: AlertUnit::Percent,
+            distro_name: None,
+            sustained_secs,
+            clear_percent,
+            channels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fires_only_after_the_condition_is_sustained() {
+        let rule = cpu_rule(120, None);
+        let mut evaluator = AlertEvaluator::default();
+        let t0 = Instant::now();
+
+        assert_eq!(evaluator.evaluate("Ubuntu", &rule, 95.0, t0), None);
+        assert_eq!(
+            evaluator.evaluate("Ubuntu", &rule, 95.0, t0 + Duration::from_secs(60)),
+            None
+        );
+        // A dip below the threshold restarts the clock
+        assert_eq!(
+            evaluator.evaluate("Ubuntu", &rule, 50.0, t0 + Duration::from_secs(70)),
+            None
+        );
+        assert_eq!(
+            evaluator.evaluate("Ubuntu", &rule, 95.0, t0 + Duration::from_secs(80)),
+            None
+        );
+        assert_eq!(
+            evaluator.evaluate("Ubuntu", &rule, 95.0, t0 + Duration::from_secs(200)),
+            Some(AlertTransition::Fire)
+        );
+        // Still firing: no second record
+        assert_eq!(
+            evaluator.evaluate("Ubuntu", &rule, 99.0, t0 + Duration::from_secs(202)),
+            None
+        );
+    }
+
+    #[test]
+    fn resolves_only_below_the_clear_threshold() {
+        let rule = cpu_rule(0, Some(75.0));
+        let mut evaluator = AlertEvaluator::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            evaluator.evaluate("Ubuntu", &rule, 92.0, now),
+            Some(AlertTransition::Fire)
+        );
+        evaluator.set_alert_id("Ubuntu", AlertType::Cpu, 7);
+        // Between the clear and firing thresholds: keeps firing
+        assert_eq!(evaluator.evaluate("Ubuntu", &rule, 80.0, now), None);
+        assert_eq!(
+            evaluator.evaluate("Ubuntu", &rule, 70.0, now),
+            Some(AlertTransition::Resolve {
+                alert_id: Some(7),
+                suppressed: false
+            })
+        );
+        assert!(!evaluator.is_firing("Ubuntu", AlertType::Cpu));
+    }
+
+    #[test]
+    fn clear_resolves_a_firing_alert_resumed_from_storage() {
+        let mut evaluator = AlertEvaluator::with_firing(&[AlertRecord {
+            id: 3,
+            distro_name: "Debian".into(),
+            alert_type: AlertType::Disk,
+            threshold: 90.0,
+            actual_value: 95.0,
+            unit: AlertUnit::Percent,
+            timestamp: chrono::Utc::now(),
+            acknowledged: false,
+            resolved_at: None,
+            resolved_value: None,
+            suppressed_by: Some("Silence: upgrade".into()),
+        }]);
+
+        assert_eq!(evaluator.distros(), vec!["Debian".to_string()]);
+        assert_eq!(
+            evaluator.clear("Debian", AlertType::Disk),
+            Some(AlertTransition::Resolve {
+                alert_id: Some(3),
+                suppressed: true
+            })
+        );
+        assert_eq!(evaluator.clear("Debian", AlertType::Disk), None);
+    }
+}
@@ -1,21 +1,22 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use chrono::{Timelike, Utc};
+use chrono::{DateTime, Timelike, Utc};
 
+use crate::domain::entities::metrics_settings::{MetricsSettings, MetricsTier};
 use crate::domain::ports::alerting::AlertingPort;
 use crate::domain::ports::metrics_repository::MetricsRepositoryPort;
 
 const AGGREGATION_INTERVAL_SECS: u64 = 60;
-const RAW_RETENTION_HOURS: i64 = 1;
-const AGGREGATED_RETENTION_HOURS: i64 = 24;
 const ALERT_RETENTION_HOURS: i64 = 24;
 
-/// Background service that aggregates raw metrics into 1-minute buckets
-/// and purges expired data.
+/// Background service that aggregates raw system, per-process and
+/// per-service metrics into 1-minute buckets, rolls those up into 1-hour and 1-day buckets, and
+/// purges each tier past the retention of the metrics settings.
 pub struct MetricsAggregator {
     metrics_repo: Arc<dyn MetricsRepositoryPort>,
     alerting: Arc<dyn AlertingPort>,
+    settings: Arc<tokio::sync::RwLock<MetricsSettings>>,
 }
 
 impl MetricsAggregator {
@@ -26,9 +27,16 @@ impl MetricsAggregator {
         Self {
             metrics_repo,
             alerting,
+            settings: Default::default(),
         }
     }
 
+    /// Share the metrics settings edited from the UI.
+    pub fn with_settings(mut self, settings: Arc<tokio::sync::RwLock<MetricsSettings>>) -> Self {
+        self.settings = settings;
+        self
+    }
+
     pub async fn run(self) {
         let mut interval = tokio::time::interval(Duration::from_secs(AGGREGATION_INTERVAL_SECS));
 
@@ -43,6 +51,7 @@ impl MetricsAggregator {
 
     async fn aggregate_and_purge(&self) -> Result<(), String> {
         let now = Utc::now();
+        let settings = self.settings.read().await.clone();
 
         // Aggregate raw metrics that are at least 2 minutes old (to ensure complete buckets).
         // Window: from 62 minutes ago to 2 minutes ago (covers the full raw retention window).
@@ -67,20 +76,112 @@ impl MetricsAggregator {
             Err(e) => tracing::debug!("Aggregation failed: {e}"),
         }
 
-        // Purge expired raw data (> 1 hour old)
-        let raw_cutoff = now - chrono::Duration::hours(RAW_RETENTION_HOURS);
+        match self
+            .metrics_repo
+            .aggregate_process_buckets(agg_start, agg_end)
+            .await
+        {
+            Ok(count) => {
+                if count > 0 {
+                    tracing::debug!("Aggregated {count} process buckets");
+                }
+            }
+            Err(e) => tracing::debug!("Process aggregation failed: {e}"),
+        }
+
+        match self
+            .metrics_repo
+            .aggregate_service_buckets(agg_start, agg_end)
+            .await
+        {
+            Ok(count) => {
+                if count > 0 {
+                    tracing::debug!("Aggregated {count} service buckets");
+                }
+            }
+            Err(e) => tracing::debug!("Service aggregation failed: {e}"),
+        }
+
+        // Roll complete hours of 1-minute buckets into 1-hour buckets, and
+        // complete days of those into 1-day buckets. The windows reach back
+        // over the whole retention of the source tier so hours and days
+        // missed while the app was closed are caught up; existing buckets
+        // are skipped.
+        let hour_end = truncate_to_hour(agg_end);
+        let day_end = hour_end.with_hour(0).unwrap_or(hour_end);
+        let rollups = [
+            (
+                MetricsTier::Hour,
+                hour_end - settings.retention(MetricsTier::Minute),
+                hour_end,
+            ),
+            (
+                MetricsTier::Day,
+                day_end - settings.retention(MetricsTier::Hour),
+                day_end,
+            ),
+        ];
+        for (tier, start, end) in rollups {
+            match self.metrics_repo.rollup_buckets(tier, start, end).await {
+                Ok(count) => {
+                    if count > 0 {
+                        tracing::debug!("Rolled up {count} {} metric buckets", tier.label());
+                    }
+                }
+                Err(e) => tracing::debug!("{} rollup failed: {e}", tier.label()),
+            }
+        }
+
+        // Purge expired raw data
+        let raw_cutoff = now - settings.raw_retention();
         if let Ok(deleted) = self.metrics_repo.purge_raw_before(raw_cutoff).await
             && deleted > 0
         {
             tracing::debug!("Purged {deleted} raw metrics rows");
         }
 
-        // Purge expired aggregated data (> 24 hours old)
-        let agg_cutoff = now - chrono::Duration::hours(AGGREGATED_RETENTION_HOURS);
-        if let Ok(deleted) = self.metrics_repo.purge_aggregated_before(agg_cutoff).await
+        // Purge the expired buckets of each tier
+        for tier in MetricsTier::ALL {
+            let cutoff = now - settings.retention(tier);
+            if let Ok(deleted) = self
+                .metrics_repo
+                .purge_aggregated_before(tier, cutoff)
+                .await
+                && deleted > 0
+            {
+                tracing::debug!("Purged {deleted} {} metrics rows", tier.label());
+            }
+        }
+
+        // Process and service samples and 1-minute buckets share the system
+        // metrics retention
+        if let Ok(deleted) = self.metrics_repo.purge_process_raw_before(raw_cutoff).await
+            && deleted > 0
+        {
+            tracing::debug!("Purged {deleted} process sample rows");
+        }
+        let minute_cutoff = now - settings.retention(MetricsTier::Minute);
+        if let Ok(deleted) = self
+            .metrics_repo
+            .purge_process_aggregated_before(minute_cutoff)
+            .await
             && deleted > 0
         {
-            tracing::debug!("Purged {deleted} aggregated metrics rows");
+            tracing::debug!("Purged {deleted} process bucket rows");
+        }
+
+        if let Ok(deleted) = self.metrics_repo.purge_service_raw_before(raw_cutoff).await
+            && deleted > 0
+        {
+            tracing::debug!("Purged {deleted} service sample rows");
+        }
+        if let Ok(deleted) = self
+            .metrics_repo
+            .purge_service_aggregated_before(minute_cutoff)
+            .await
+            && deleted > 0
+        {
+            tracing::debug!("Purged {deleted} service bucket rows");
         }
 
         // Purge expired alerts (> 24 hours old)
@@ -91,10 +192,24 @@ impl MetricsAggregator {
             tracing::debug!("Purged {deleted} alert log rows");
         }
 
+        // Purge silences that ended more than 24 hours ago
+        if let Ok(deleted) = self.alerting.purge_silences_before(alert_cutoff).await
+            && deleted > 0
+        {
+            tracing::debug!("Purged {deleted} expired alert silences");
+        }
+
         Ok(())
     }
 }
 
+fn truncate_to_hour(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,6 +217,44 @@ mod tests {
     use crate::domain::ports::alerting::MockAlertingPort;
     use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
 
+    /// The per-process and per-service histories go through the same
+    /// aggregate-then-purge cycle, and each pass rolls up the 1-hour and
+    /// 1-day tiers.
+    fn expect_process_history(metrics_repo: &mut MockMetricsRepositoryPort) {
+        metrics_repo
+            .expect_rollup_buckets()
+            .times(2)
+            .returning(|_, _, _| Ok(0));
+        metrics_repo
+            .expect_aggregate_process_buckets()
+            .times(1)
+            .returning(|_, _| Ok(0));
+        metrics_repo
+            .expect_purge_process_raw_before()
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_purge_process_aggregated_before()
+            .times(1)
+            .returning(|_| Ok(0));
+        expect_service_history(metrics_repo);
+    }
+
+    fn expect_service_history(metrics_repo: &mut MockMetricsRepositoryPort) {
+        metrics_repo
+            .expect_aggregate_service_buckets()
+            .times(1)
+            .returning(|_, _| Ok(0));
+        metrics_repo
+            .expect_purge_service_raw_before()
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_purge_service_aggregated_before()
+            .times(1)
+            .returning(|_| Ok(0));
+    }
+
     fn make_aggregator(
         metrics_repo: MockMetricsRepositoryPort,
         alerting: MockAlertingPort,
@@ -113,6 +266,7 @@ mod tests {
     async fn aggregate_and_purge_calls_aggregate_raw_buckets() {
         let mut metrics_repo = MockMetricsRepositoryPort::new();
         let mut alerting = MockAlertingPort::new();
+        expect_process_history(&mut metrics_repo);
 
         metrics_repo
             .expect_aggregate_raw_buckets()
@@ -124,9 +278,13 @@ mod tests {
             .returning(|_| Ok(0));
         metrics_repo
             .expect_purge_aggregated_before()
+            .times(3)
+            .returning(|_, _| Ok(0));
+        alerting.expect_purge_before().times(1).returning(|_| Ok(0));
+        alerting
+            .expect_purge_silences_before()
             .times(1)
             .returning(|_| Ok(0));
-        alerting.expect_purge_before().times(1).returning(|_| Ok(0));
 
         let aggregator = make_aggregator(metrics_repo, alerting);
         let result = aggregator.aggregate_and_purge().await;
@@ -137,6 +295,7 @@ mod tests {
     async fn aggregate_and_purge_calls_all_purge_functions() {
         let mut metrics_repo = MockMetricsRepositoryPort::new();
         let mut alerting = MockAlertingPort::new();
+        expect_process_history(&mut metrics_repo);
 
         metrics_repo
             .expect_aggregate_raw_buckets()
@@ -147,9 +306,13 @@ mod tests {
             .returning(|_| Ok(10));
         metrics_repo
             .expect_purge_aggregated_before()
-            .times(1)
-            .returning(|_| Ok(3));
+            .times(3)
+            .returning(|_, _| Ok(3));
         alerting.expect_purge_before().times(1).returning(|_| Ok(7));
+        alerting
+            .expect_purge_silences_before()
+            .times(1)
+            .returning(|_| Ok(2));
 
         let aggregator = make_aggregator(metrics_repo, alerting);
         let result = aggregator.aggregate_and_purge().await;
@@ -160,6 +323,7 @@ mod tests {
     async fn aggregate_and_purge_continues_when_aggregation_fails() {
         let mut metrics_repo = MockMetricsRepositoryPort::new();
         let mut alerting = MockAlertingPort::new();
+        expect_process_history(&mut metrics_repo);
 
         metrics_repo
             .expect_aggregate_raw_buckets()
@@ -171,9 +335,13 @@ mod tests {
             .returning(|_| Ok(0));
         metrics_repo
             .expect_purge_aggregated_before()
+            .times(3)
+            .returning(|_, _| Ok(0));
+        alerting.expect_purge_before().times(1).returning(|_| Ok(0));
+        alerting
+            .expect_purge_silences_before()
             .times(1)
             .returning(|_| Ok(0));
-        alerting.expect_purge_before().times(1).returning(|_| Ok(0));
 
         let aggregator = make_aggregator(metrics_repo, alerting);
         let result = aggregator.aggregate_and_purge().await;
@@ -184,6 +352,7 @@ mod tests {
     async fn aggregate_and_purge_continues_when_purge_raw_fails() {
         let mut metrics_repo = MockMetricsRepositoryPort::new();
         let mut alerting = MockAlertingPort::new();
+        expect_process_history(&mut metrics_repo);
 
         metrics_repo
             .expect_aggregate_raw_buckets()
@@ -193,9 +362,13 @@ mod tests {
             .returning(|_| Err(DomainError::Internal("purge raw failed".into())));
         metrics_repo
             .expect_purge_aggregated_before()
+            .times(3)
+            .returning(|_, _| Ok(0));
+        alerting.expect_purge_before().times(1).returning(|_| Ok(0));
+        alerting
+            .expect_purge_silences_before()
             .times(1)
             .returning(|_| Ok(0));
-        alerting.expect_purge_before().times(1).returning(|_| Ok(0));
 
         let aggregator = make_aggregator(metrics_repo, alerting);
         let result = aggregator.aggregate_and_purge().await;
@@ -206,6 +379,7 @@ mod tests {
     async fn aggregate_and_purge_continues_when_purge_aggregated_fails() {
         let mut metrics_repo = MockMetricsRepositoryPort::new();
         let mut alerting = MockAlertingPort::new();
+        expect_process_history(&mut metrics_repo);
 
         metrics_repo
             .expect_aggregate_raw_buckets()
@@ -213,8 +387,12 @@ mod tests {
         metrics_repo.expect_purge_raw_before().returning(|_| Ok(0));
         metrics_repo
             .expect_purge_aggregated_before()
-            .returning(|_| Err(DomainError::Internal("purge agg failed".into())));
+            .returning(|_, _| Err(DomainError::Internal("purge agg failed".into())));
         alerting.expect_purge_before().times(1).returning(|_| Ok(0));
+        alerting
+            .expect_purge_silences_before()
+            .times(1)
+            .returning(|_| Ok(0));
 
         let aggregator = make_aggregator(metrics_repo, alerting);
         let result = aggregator.aggregate_and_purge().await;
@@ -225,6 +403,7 @@ mod tests {
     async fn aggregate_and_purge_continues_when_alert_purge_fails() {
         let mut metrics_repo = MockMetricsRepositoryPort::new();
         let mut alerting = MockAlertingPort::new();
+        expect_process_history(&mut metrics_repo);
 
         metrics_repo
             .expect_aggregate_raw_buckets()
@@ -232,13 +411,149 @@ mod tests {
         metrics_repo.expect_purge_raw_before().returning(|_| Ok(0));
         metrics_repo
             .expect_purge_aggregated_before()
-            .returning(|_| Ok(0));
+            .returning(|_, _| Ok(0));
         alerting
             .expect_purge_before()
             .returning(|_| Err(DomainError::Internal("alert purge failed".into())));
+        alerting
+            .expect_purge_silences_before()
+            .returning(|_| Err(DomainError::Internal("silence purge failed".into())));
+
+        let aggregator = make_aggregator(metrics_repo, alerting);
+        let result = aggregator.aggregate_and_purge().await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn aggregate_and_purge_continues_when_process_history_fails() {
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        let mut alerting = MockAlertingPort::new();
+        expect_service_history(&mut metrics_repo);
+
+        metrics_repo
+            .expect_aggregate_raw_buckets()
+            .returning(|_, _| Ok(0));
+        metrics_repo
+            .expect_aggregate_process_buckets()
+            .returning(|_, _| Err(DomainError::Internal("process agg failed".into())));
+        metrics_repo
+            .expect_rollup_buckets()
+            .times(2)
+            .returning(|_, _, _| Err(DomainError::Internal("rollup failed".into())));
+        metrics_repo
+            .expect_purge_process_raw_before()
+            .returning(|_| Err(DomainError::Internal("process purge failed".into())));
+        metrics_repo
+            .expect_purge_process_aggregated_before()
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_purge_raw_before()
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_purge_aggregated_before()
+            .times(3)
+            .returning(|_, _| Ok(0));
+        alerting.expect_purge_before().times(1).returning(|_| Ok(0));
+        alerting
+            .expect_purge_silences_before()
+            .times(1)
+            .returning(|_| Ok(0));
 
         let aggregator = make_aggregator(metrics_repo, alerting);
         let result = aggregator.aggregate_and_purge().await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn aggregate_and_purge_uses_the_retention_of_each_tier() {
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        let mut alerting = MockAlertingPort::new();
+        let settings = MetricsSettings {
+            raw_retention_hours: 6,
+            minute_retention_hours: 48,
+            hour_retention_days: 90,
+            day_retention_days: 730,
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let near = move |at: DateTime<Utc>, ago: chrono::Duration| {
+            (now - ago - at).num_seconds().abs() < 60
+        };
+
+        metrics_repo
+            .expect_aggregate_raw_buckets()
+            .returning(|_, _| Ok(0));
+        metrics_repo
+            .expect_aggregate_process_buckets()
+            .returning(|_, _| Ok(0));
+        metrics_repo
+            .expect_purge_process_raw_before()
+            .withf(move |before| near(*before, chrono::Duration::hours(6)))
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_purge_process_aggregated_before()
+            .withf(move |before| near(*before, chrono::Duration::hours(48)))
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_aggregate_service_buckets()
+            .returning(|_, _| Ok(0));
+        metrics_repo
+            .expect_purge_service_raw_before()
+            .withf(move |before| near(*before, chrono::Duration::hours(6)))
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_purge_service_aggregated_before()
+            .withf(move |before| near(*before, chrono::Duration::hours(48)))
+            .times(1)
+            .returning(|_| Ok(0));
+        // Hours are rolled up from the last 48h of 1-minute buckets, whole
+        // UTC hours only
+        metrics_repo
+            .expect_rollup_buckets()
+            .withf(move |tier, start, end| {
+                *tier == MetricsTier::Hour
+                    && *end - *start == chrono::Duration::hours(48)
+                    && end.minute() == 0
+                    && *end <= now
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(1));
+        metrics_repo
+            .expect_rollup_buckets()
+            .withf(|tier, start, end| {
+                *tier == MetricsTier::Day
+                    && *end - *start == chrono::Duration::days(90)
+                    && end.hour() == 0
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(0));
+        metrics_repo
+            .expect_purge_raw_before()
+            .withf(move |before| near(*before, chrono::Duration::hours(6)))
+            .times(1)
+            .returning(|_| Ok(0));
+        metrics_repo
+            .expect_purge_aggregated_before()
+            .withf(move |tier, before| {
+                let retention = match tier {
+                    MetricsTier::Minute => chrono::Duration::hours(48),
+                    MetricsTier::Hour => chrono::Duration::days(90),
+                    MetricsTier::Day => chrono::Duration::days(730),
+                };
+                near(*before, retention)
+            })
+            .times(3)
+            .returning(|_, _| Ok(0));
+        alerting.expect_purge_before().returning(|_| Ok(0));
+        alerting.expect_purge_silences_before().returning(|_| Ok(0));
+
+        let aggregator = make_aggregator(metrics_repo, alerting)
+            .with_settings(Arc::new(tokio::sync::RwLock::new(settings)));
+        assert!(aggregator.aggregate_and_purge().await.is_ok());
+    }
 }
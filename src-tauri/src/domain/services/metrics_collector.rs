@@ -1,32 +1,79 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use tauri::{AppHandle, Emitter};
-use tauri_plugin_notification::NotificationExt;
 
+use crate::domain::entities::alert_silence::AlertSuppressions;
 use crate::domain::entities::distro::Distro;
+use crate::domain::entities::metrics_settings::MetricsSettings;
 use crate::domain::entities::monitoring::SystemMetrics;
 use crate::domain::errors::DomainError;
-use crate::domain::ports::alerting::{AlertThreshold, AlertType, AlertingPort};
+use crate::domain::ports::alerting::{AlertThresholdSet, AlertType, AlertUnit, AlertingPort};
 use crate::domain::ports::metrics_repository::MetricsRepositoryPort;
 use crate::domain::ports::monitoring_provider::MonitoringProviderPort;
+use crate::domain::ports::notification_channel::AlertNotification;
 use crate::domain::ports::wsl_manager::WslManagerPort;
-use crate::domain::value_objects::DistroName;
-use crate::presentation::events::{EVENT_ALERT_TRIGGERED, EVENT_SYSTEM_METRICS};
+use crate::domain::services::alert_dispatcher::AlertDispatcher;
+use crate::domain::services::alert_evaluator::{AlertEvaluator, AlertTransition};
+use crate::domain::services::process_tracker::ProcessTracker;
+use crate::domain::services::service_tracker::ServiceTracker;
+use crate::domain::value_objects::{DistroName, MemorySize};
+use crate::presentation::events::{
+    EVENT_ALERT_RESOLVED, EVENT_ALERT_TRIGGERED, EVENT_SYSTEM_METRICS,
+};
 
-const COLLECTION_INTERVAL_SECS: u64 = 2;
-const ALERT_COOLDOWN_SECS: u64 = 300; // 5 minutes between same alert type/distro
 const DISTRO_CACHE_TTL_SECS: u64 = 2;
+/// Listing processes is another `wsl.exe` call per distro, so the
+/// per-process history is sampled less often than the system metrics. The
+/// per-service history is sampled along with it.
+const PROCESS_SAMPLE_INTERVAL_SECS: u64 = 10;
+
+/// The last metrics collected from each running distro, shared with readers
+/// that must not run `wsl.exe` themselves, such as the OpenMetrics endpoint.
+#[derive(Debug, Default)]
+pub struct LatestMetrics {
+    by_distro: tokio::sync::RwLock<BTreeMap<String, SystemMetrics>>,
+}
+
+impl LatestMetrics {
+    pub async fn record(&self, metrics: &SystemMetrics) {
+        self.by_distro
+            .write()
+            .await
+            .insert(metrics.distro_name.clone(), metrics.clone());
+    }
+
+    /// Forget the distros that are no longer running.
+    pub async fn retain(&self, running: &[String]) {
+        self.by_distro
+            .write()
+            .await
+            .retain(|distro, _| running.contains(distro));
+    }
+
+    /// The latest metrics of each distro, ordered by distro name.
+    pub async fn snapshot(&self) -> Vec<SystemMetrics> {
+        self.by_distro.read().await.values().cloned().collect()
+    }
+}
 
 /// Background service that collects metrics from all running distros,
-/// persists them, emits Tauri events, and checks alert thresholds.
+/// persists them, emits Tauri events, and evaluates the alert rules, sending
+/// their notifications through the dispatcher unless a silence or maintenance
+/// window suppresses them. Every few collections it also samples the top
+/// processes of each distro into the per-process history, and the systemd
+/// units of distros booted with systemd into the per-service history.
 pub struct MetricsCollector {
     monitoring: Arc<dyn MonitoringProviderPort>,
     metrics_repo: Arc<dyn MetricsRepositoryPort>,
     alerting: Arc<dyn AlertingPort>,
     wsl_manager: Arc<dyn WslManagerPort>,
-    alert_thresholds: Arc<tokio::sync::RwLock<Vec<AlertThreshold>>>,
+    alert_thresholds: Arc<tokio::sync::RwLock<AlertThresholdSet>>,
+    alert_suppressions: Arc<tokio::sync::RwLock<AlertSuppressions>>,
+    dispatcher: Arc<AlertDispatcher>,
+    settings: Arc<tokio::sync::RwLock<MetricsSettings>>,
+    latest: Arc<LatestMetrics>,
 }
 
 impl MetricsCollector {
@@ -35,7 +82,9 @@ impl MetricsCollector {
         metrics_repo: Arc<dyn MetricsRepositoryPort>,
         alerting: Arc<dyn AlertingPort>,
         wsl_manager: Arc<dyn WslManagerPort>,
-        alert_thresholds: Arc<tokio::sync::RwLock<Vec<AlertThreshold>>>,
+        alert_thresholds: Arc<tokio::sync::RwLock<AlertThresholdSet>>,
+        alert_suppressions: Arc<tokio::sync::RwLock<AlertSuppressions>>,
+        dispatcher: Arc<AlertDispatcher>,
     ) -> Self {
         Self {
             monitoring,
@@ -43,18 +92,57 @@ impl MetricsCollector {
             alerting,
             wsl_manager,
             alert_thresholds,
+            alert_suppressions,
+            dispatcher,
+            settings: Default::default(),
+            latest: Default::default(),
         }
     }
 
+    /// Share the metrics settings edited from the UI (collection interval).
+    pub fn with_settings(mut self, settings: Arc<tokio::sync::RwLock<MetricsSettings>>) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Share the latest metrics of each distro with other readers.
+    pub fn with_latest_metrics(mut self, latest: Arc<LatestMetrics>) -> Self {
+        self.latest = latest;
+        self
+    }
+
+    async fn collection_interval(&self) -> Duration {
+        Duration::from_secs(self.settings.read().await.collection_interval_secs.into())
+    }
+
     pub async fn run(self, app_handle: AppHandle) {
-        let mut interval = tokio::time::interval(Duration::from_secs(COLLECTION_INTERVAL_SECS));
-        let mut alert_cooldowns: HashMap<(String, String), Instant> = HashMap::new();
+        let mut interval = tokio::time::interval(self.collection_interval().await);
         let mut cached_distros: Option<(Instant, Vec<Distro>)> = None;
+        // Alerts left firing by the previous run carry on where they were
+        let mut evaluator = match self.alerting.list_firing_alerts().await {
+            Ok(firing) => AlertEvaluator::with_firing(&firing),
+            Err(e) => {
+                tracing::warn!("Failed to load firing alerts: {e}");
+                AlertEvaluator::default()
+            }
+        };
+        let mut process_tracker = ProcessTracker::default();
+        let mut service_tracker = ServiceTracker::default();
+        // Whether each running distro booted with systemd; wsl.conf only
+        // applies on the next start
+        let mut boots_systemd: HashMap<String, bool> = HashMap::new();
+        let mut last_process_sample: Option<tokio::time::Instant> = None;
 
         loop {
-            interval.tick().await;
+            let tick = interval.tick().await;
 
-            // Use cached distro list if fresh enough (avoids wsl.exe --list every 2s)
+            // A changed collection interval applies from the next tick
+            let period = self.collection_interval().await;
+            if period != interval.period() {
+                interval = tokio::time::interval_at(tick + period, period);
+            }
+
+            // Use cached distro list if fresh enough (avoids wsl.exe --list on every tick)
             let distros = match Self::get_distros(&self.wsl_manager, &mut cached_distros).await {
                 Some(d) => d,
                 None => continue,
@@ -65,6 +153,8 @@ impl MetricsCollector {
                 .filter(|d| d.state.is_running())
                 .map(|d| d.name.clone())
                 .collect();
+            let running_names: Vec<String> =
+                running.iter().map(|n| n.as_str().to_string()).collect();
 
             // Collect metrics from all running distros in parallel
             let results: Vec<(DistroName, Result<SystemMetrics, DomainError>)> =
@@ -80,12 +170,24 @@ impl MetricsCollector {
                 }))
                 .await;
 
-            // Check alerts sequentially (needs mutable cooldowns)
+            // Evaluate alert rules sequentially (needs the mutable evaluator)
+            let now = Instant::now();
+            let mut collected = Vec::new();
             for (name, result) in results {
                 match result {
                     Ok(metrics) => {
-                        self.check_alerts(&metrics, &app_handle, &mut alert_cooldowns)
-                            .await;
+                        collected.push(name);
+                        self.latest.record(&metrics).await;
+                        let changes = Self::evaluate_alerts(
+                            &self.alerting,
+                            &self.alert_thresholds,
+                            &self.alert_suppressions,
+                            &metrics,
+                            &mut evaluator,
+                            now,
+                        )
+                        .await;
+                        Self::notify(&app_handle, &self.dispatcher, changes);
                     }
                     Err(e) => {
                         tracing::debug!("Metrics collection failed for {}: {e}", name.as_str());
@@ -93,9 +195,42 @@ impl MetricsCollector {
                 }
             }
 
-            // Purge expired cooldown entries to prevent unbounded growth
-            alert_cooldowns
-                .retain(|_, last_fired| last_fired.elapsed().as_secs() < ALERT_COOLDOWN_SECS);
+            // Alerts of distros that stopped running resolve
+            let changes = Self::resolve_stopped(
+                &self.alerting,
+                &self.alert_thresholds,
+                &running_names,
+                &mut evaluator,
+            )
+            .await;
+            Self::notify(&app_handle, &self.dispatcher, changes);
+            self.latest.retain(&running_names).await;
+
+            process_tracker.retain(&running_names);
+            service_tracker.retain(&running_names);
+            boots_systemd.retain(|distro, _| running_names.contains(distro));
+            let sample_due = last_process_sample.is_none_or(|last| {
+                tick.duration_since(last) >= Duration::from_secs(PROCESS_SAMPLE_INTERVAL_SECS)
+            });
+            if sample_due {
+                last_process_sample = Some(tick);
+                Self::sample_processes(
+                    &self.monitoring,
+                    &self.metrics_repo,
+                    &mut process_tracker,
+                    &collected,
+                )
+                .await;
+                let systemd_distros =
+                    Self::systemd_distros(&self.wsl_manager, &mut boots_systemd, &collected).await;
+                Self::sample_services(
+                    &self.monitoring,
+                    &self.metrics_repo,
+                    &mut service_tracker,
+                    &systemd_distros,
+                )
+                .await;
+            }
         }
     }
 
@@ -142,140 +277,396 @@ impl MetricsCollector {
         Ok(metrics)
     }
 
-    #[cfg(test)]
-    async fn check_alerts_headless(
-        alerting: &Arc<dyn AlertingPort>,
-        alert_thresholds: &Arc<tokio::sync::RwLock<Vec<AlertThreshold>>>,
-        metrics: &SystemMetrics,
-        cooldowns: &mut HashMap<(String, String), Instant>,
+    /// List the processes of each distro in parallel and persist their top
+    /// offenders.
+    async fn sample_processes(
+        monitoring: &Arc<dyn MonitoringProviderPort>,
+        metrics_repo: &Arc<dyn MetricsRepositoryPort>,
+        tracker: &mut ProcessTracker,
+        distros: &[DistroName],
     ) {
-        let thresholds = alert_thresholds.read().await;
+        let listings = futures::future::join_all(
+            distros
+                .iter()
+                .map(|name| async move { (name, monitoring.get_processes(name).await) }),
+        )
+        .await;
+
         let now = Instant::now();
+        let timestamp = chrono::Utc::now();
+        for (name, result) in listings {
+            let processes = match result {
+                Ok(processes) => processes,
+                Err(e) => {
+                    tracing::debug!("Process listing failed for {}: {e}", name.as_str());
+                    continue;
+                }
+            };
+            if let Some(samples) = tracker.sample(name.as_str(), &processes, now)
+                && let Err(e) = metrics_repo
+                    .store_process_samples(name, timestamp, &samples)
+                    .await
+            {
+                tracing::warn!("Failed to persist processes for {}: {e}", name.as_str());
+            }
+        }
+    }
 
-        for threshold in thresholds.iter().filter(|t| t.enabled) {
-            let actual_value = match threshold.alert_type {
-                AlertType::Cpu => metrics.cpu.usage_percent,
-                AlertType::Memory => {
-                    if metrics.memory.total_bytes == 0 {
-                        continue;
+    /// The distros among `distros` whose wsl.conf sets `boot_systemd`, reading
+    /// the config of those not seen since they started.
+    async fn systemd_distros(
+        wsl_manager: &Arc<dyn WslManagerPort>,
+        boots_systemd: &mut HashMap<String, bool>,
+        distros: &[DistroName],
+    ) -> Vec<DistroName> {
+        let mut systemd = Vec::new();
+        for name in distros {
+            let boots = match boots_systemd.get(name.as_str()) {
+                Some(&boots) => boots,
+                None => match wsl_manager.get_distro_config(name).await {
+                    Ok(config) => {
+                        let boots = config.boot_systemd == Some(true);
+                        boots_systemd.insert(name.as_str().to_string(), boots);
+                        boots
                     }
-                    (metrics.memory.used_bytes as f64 / metrics.memory.total_bytes as f64) * 100.0
-                }
-                AlertType::Disk => metrics.disk.usage_percent,
+                    Err(e) => {
+                        tracing::debug!("Failed to read wsl.conf of {}: {e}", name.as_str());
+                        false
+                    }
+                },
             };
+            if boots {
+                systemd.push(name.clone());
+            }
+        }
+        systemd
+    }
 
-            if actual_value >= threshold.threshold_percent {
-                let key = (
-                    metrics.distro_name.clone(),
-                    threshold.alert_type.to_string(),
-                );
+    /// Read the cgroups of the systemd units of each distro in parallel and
+    /// persist their usage.
+    async fn sample_services(
+        monitoring: &Arc<dyn MonitoringProviderPort>,
+        metrics_repo: &Arc<dyn MetricsRepositoryPort>,
+        tracker: &mut ServiceTracker,
+        distros: &[DistroName],
+    ) {
+        let readings = futures::future::join_all(
+            distros
+                .iter()
+                .map(|name| async move { (name, monitoring.get_service_stats(name).await) }),
+        )
+        .await;
 
-                if let Some(last_fired) = cooldowns.get(&key)
-                    && now.duration_since(*last_fired).as_secs() < ALERT_COOLDOWN_SECS
-                {
+        let now = Instant::now();
+        let timestamp = chrono::Utc::now();
+        for (name, result) in readings {
+            let stats = match result {
+                Ok(stats) => stats,
+                Err(e) => {
+                    tracing::debug!("Service cgroup reading failed for {}: {e}", name.as_str());
                     continue;
                 }
-
-                cooldowns.insert(key, now);
-
-                if let Ok(name) = DistroName::new(&metrics.distro_name) {
-                    let _ = alerting
-                        .record_alert(
-                            &name,
-                            threshold.alert_type,
-                            threshold.threshold_percent,
-                            actual_value,
-                        )
-                        .await;
-                }
+            };
+            if let Some(services) = tracker.sample(name.as_str(), &stats, now)
+                && !services.is_empty()
+                && let Err(e) = metrics_repo
+                    .store_service_metrics(name, timestamp, &services)
+                    .await
+            {
+                tracing::warn!("Failed to persist services for {}: {e}", name.as_str());
             }
         }
     }
 
-    async fn check_alerts(
-        &self,
+    /// Feed the metrics of one distro to its alert rules, recording alerts
+    /// that fire and resolving those that clear. Alerts that fire during a
+    /// silence or maintenance window are recorded as suppressed and, like
+    /// their resolution, left out of the returned changes.
+    async fn evaluate_alerts(
+        alerting: &Arc<dyn AlertingPort>,
+        alert_thresholds: &Arc<tokio::sync::RwLock<AlertThresholdSet>>,
+        alert_suppressions: &Arc<tokio::sync::RwLock<AlertSuppressions>>,
         metrics: &SystemMetrics,
-        app_handle: &AppHandle,
-        cooldowns: &mut HashMap<(String, String), Instant>,
-    ) {
-        let thresholds = self.alert_thresholds.read().await;
-        let now = Instant::now();
+        evaluator: &mut AlertEvaluator,
+        now: Instant,
+    ) -> Vec<AlertChange> {
+        // Per-distro overrides on top of the global defaults
+        let thresholds = alert_thresholds
+            .read()
+            .await
+            .effective(&metrics.distro_name);
+        let distro = metrics.distro_name.as_str();
+        let mut changes = Vec::new();
+
+        for threshold in &thresholds {
+            let value = alert_value(metrics, threshold.alert_type, threshold.unit);
+
+            let transition = if !threshold.enabled {
+                // Disabling a rule ends its alert
+                evaluator.clear(distro, threshold.alert_type)
+            } else if let Some(value) = value {
+                evaluator.evaluate(distro, threshold, value, now)
+            } else {
+                None
+            };
 
-        for threshold in thresholds.iter().filter(|t| t.enabled) {
-            let (actual_value, label) = match threshold.alert_type {
-                AlertType::Cpu => (metrics.cpu.usage_percent, "CPU"),
-                AlertType::Memory => {
-                    if metrics.memory.total_bytes == 0 {
-                        continue;
+            let mut change = AlertChange {
+                alert_id: None,
+                distro_name: distro.to_string(),
+                alert_type: threshold.alert_type,
+                unit: threshold.unit,
+                threshold: threshold.threshold_percent,
+                actual_value: value,
+                resolved: false,
+                channels: threshold.channels.clone(),
+            };
+            match transition {
+                Some(AlertTransition::Fire) => {
+                    let suppressed_by = alert_suppressions.read().await.suppressed_by(
+                        distro,
+                        threshold.alert_type,
+                        chrono::Utc::now(),
+                    );
+                    if let Ok(name) = DistroName::new(distro) {
+                        match alerting
+                            .record_alert(
+                                &name,
+                                threshold.alert_type,
+                                threshold.unit,
+                                threshold.threshold_percent,
+                                value.unwrap_or_default(),
+                                suppressed_by.clone(),
+                            )
+                            .await
+                        {
+                            Ok(id) => {
+                                evaluator.set_alert_id(distro, threshold.alert_type, id);
+                                change.alert_id = Some(id);
+                            }
+                            Err(e) => tracing::warn!("Failed to record alert for {distro}: {e}"),
+                        }
+                    }
+                    match suppressed_by {
+                        Some(by) => {
+                            tracing::debug!(
+                                "{} alert for {distro} suppressed ({by})",
+                                threshold.alert_type
+                            );
+                            evaluator.set_suppressed(distro, threshold.alert_type);
+                        }
+                        None => changes.push(change),
                     }
-                    let pct = (metrics.memory.used_bytes as f64
-                        / metrics.memory.total_bytes as f64)
-                        * 100.0;
-                    (pct, "Memory")
                 }
-                AlertType::Disk => (metrics.disk.usage_percent, "Disk"),
-            };
-
-            if actual_value >= threshold.threshold_percent {
-                let key = (
-                    metrics.distro_name.clone(),
-                    threshold.alert_type.to_string(),
-                );
+                Some(AlertTransition::Resolve {
+                    alert_id,
+                    suppressed,
+                }) => {
+                    if let Some(id) = alert_id
+                        && let Err(e) = alerting.resolve_alert(id, value).await
+                    {
+                        tracing::warn!("Failed to resolve alert {id}: {e}");
+                    }
+                    change.alert_id = alert_id;
+                    change.resolved = true;
+                    if !suppressed {
+                        changes.push(change);
+                    }
+                }
+                None => {}
+            }
+        }
+        changes
+    }
 
-                // Check cooldown
-                if let Some(last_fired) = cooldowns.get(&key)
-                    && now.duration_since(*last_fired).as_secs() < ALERT_COOLDOWN_SECS
-                {
+    /// Resolve the alerts of distros that are no longer running.
+    async fn resolve_stopped(
+        alerting: &Arc<dyn AlertingPort>,
+        alert_thresholds: &Arc<tokio::sync::RwLock<AlertThresholdSet>>,
+        running: &[String],
+        evaluator: &mut AlertEvaluator,
+    ) -> Vec<AlertChange> {
+        let mut changes = Vec::new();
+        for distro in evaluator.distros() {
+            if running.contains(&distro) {
+                continue;
+            }
+            let thresholds = alert_thresholds.read().await.effective(&distro);
+            for threshold in &thresholds {
+                let Some(AlertTransition::Resolve {
+                    alert_id,
+                    suppressed,
+                }) = evaluator.clear(&distro, threshold.alert_type)
+                else {
                     continue;
+                };
+                if let Some(id) = alert_id
+                    && let Err(e) = alerting.resolve_alert(id, None).await
+                {
+                    tracing::warn!("Failed to resolve alert {id}: {e}");
                 }
-
-                cooldowns.insert(key, now);
-
-                // Record in database
-                if let Ok(name) = DistroName::new(&metrics.distro_name) {
-                    let _ = self
-                        .alerting
-                        .record_alert(
-                            &name,
-                            threshold.alert_type,
-                            threshold.threshold_percent,
-                            actual_value,
-                        )
-                        .await;
+                if suppressed {
+                    continue;
                 }
-
-                // Emit event
-                let alert_event = serde_json::json!({
-                    "distro_name": metrics.distro_name,
-                    "alert_type": threshold.alert_type,
-                    "threshold": threshold.threshold_percent,
-                    "actual_value": actual_value,
+                changes.push(AlertChange {
+                    alert_id,
+                    distro_name: distro.clone(),
+                    alert_type: threshold.alert_type,
+                    unit: threshold.unit,
+                    threshold: threshold.threshold_percent,
+                    actual_value: None,
+                    resolved: true,
+                    channels: threshold.channels.clone(),
                 });
-                let _ = app_handle.emit(EVENT_ALERT_TRIGGERED, &alert_event);
-
-                // Desktop notification
-                let _ = app_handle
-                    .notification()
-                    .builder()
-                    .title(format!("{} Alert - {}", label, metrics.distro_name))
-                    .body(format!(
-                        "{} usage at {:.1}% (threshold: {:.0}%)",
-                        label, actual_value, threshold.threshold_percent
-                    ))
-                    .show();
             }
         }
+        changes
+    }
+
+    /// Emit an event for each alert that fired or resolved, and send its
+    /// notification to the channels of its rule in the background (retries
+    /// must not hold up collection).
+    fn notify(
+        app_handle: &AppHandle,
+        dispatcher: &Arc<AlertDispatcher>,
+        changes: Vec<AlertChange>,
+    ) {
+        for change in changes {
+            let event = if change.resolved {
+                EVENT_ALERT_RESOLVED
+            } else {
+                EVENT_ALERT_TRIGGERED
+            };
+            let _ = app_handle.emit(event, &change);
+
+            let dispatcher = dispatcher.clone();
+            tokio::spawn(async move {
+                dispatcher
+                    .dispatch(&alert_notification(&change), &change.channels)
+                    .await;
+            });
+        }
+    }
+}
+
+/// Title and body of the notification of an alert that fired or resolved.
+fn alert_notification(change: &AlertChange) -> AlertNotification {
+    let label = change.alert_type.label();
+    let (title, body) = match (change.resolved, change.actual_value) {
+        (false, value) => (
+            format!("{} Alert - {}", label, change.distro_name),
+            format!(
+                "{} at {} (threshold: {})",
+                label,
+                format_value(change.alert_type, change.unit, value.unwrap_or_default()),
+                format_value(change.alert_type, change.unit, change.threshold)
+            ),
+        ),
+        (true, Some(value)) => (
+            format!("{} Alert Resolved - {}", label, change.distro_name),
+            format!(
+                "{} back to {}",
+                label,
+                format_value(change.alert_type, change.unit, value)
+            ),
+        ),
+        (true, None) => (
+            format!("{} Alert Resolved - {}", label, change.distro_name),
+            format!("{} is no longer monitored", change.distro_name),
+        ),
+    };
+    AlertNotification {
+        alert_id: change.alert_id,
+        distro_name: change.distro_name.clone(),
+        alert_type: change.alert_type,
+        unit: change.unit,
+        threshold: change.threshold,
+        actual_value: change.actual_value,
+        resolved: change.resolved,
+        title,
+        body,
+    }
+}
+
+/// Current value of `alert_type` in `unit`; `None` when the metric wasn't
+/// collected (no GPU, no swap, ...) or the unit doesn't apply.
+fn alert_value(metrics: &SystemMetrics, alert_type: AlertType, unit: AlertUnit) -> Option<f64> {
+    let percent = |used: u64, total: u64| (total > 0).then(|| used as f64 / total as f64 * 100.0);
+    let memory = &metrics.memory;
+    match (alert_type, unit) {
+        (AlertType::Cpu, AlertUnit::Percent) => Some(metrics.cpu.usage_percent),
+        (AlertType::Memory, AlertUnit::Percent) => percent(memory.used_bytes, memory.total_bytes),
+        (AlertType::Memory, AlertUnit::Absolute) => Some(memory.used_bytes as f64),
+        (AlertType::Disk, AlertUnit::Percent) => Some(metrics.disk.usage_percent),
+        (AlertType::Disk, AlertUnit::Absolute) => Some(metrics.disk.used_bytes as f64),
+        (AlertType::Swap, AlertUnit::Percent) => {
+            percent(memory.swap_used_bytes, memory.swap_total_bytes)
+        }
+        (AlertType::Swap, AlertUnit::Absolute) => Some(memory.swap_used_bytes as f64),
+        (AlertType::LoadPerCore, AlertUnit::Absolute) => {
+            let cores = metrics.cpu.per_core.len();
+            (cores > 0).then(|| metrics.cpu.load_average[0] / cores as f64)
+        }
+        (AlertType::TcpTimeWait, AlertUnit::Absolute) => {
+            metrics.tcp_connections.as_ref().map(|t| t.time_wait as f64)
+        }
+        (AlertType::DiskWrite, AlertUnit::Absolute) => metrics
+            .disk_io
+            .as_ref()
+            .map(|io| io.write_bytes_per_sec as f64),
+        (AlertType::GpuVram, AlertUnit::Percent) => {
+            let gpu = metrics.gpu.as_ref()?;
+            percent(gpu.vram_used_bytes?, gpu.vram_total_bytes?)
+        }
+        (AlertType::GpuVram, AlertUnit::Absolute) => {
+            metrics.gpu.as_ref()?.vram_used_bytes.map(|b| b as f64)
+        }
+        _ => None,
+    }
+}
+
+/// Render an alert value for a notification.
+fn format_value(alert_type: AlertType, unit: AlertUnit, value: f64) -> String {
+    match (alert_type, unit) {
+        (_, AlertUnit::Percent) => format!("{value:.1}%"),
+        (AlertType::LoadPerCore, _) => format!("{value:.2}"),
+        (AlertType::TcpTimeWait, _) => format!("{value:.0} sockets"),
+        (AlertType::DiskWrite, _) => format!("{}/s", MemorySize::from_bytes(value as u64)),
+        _ => MemorySize::from_bytes(value as u64).to_string(),
     }
 }
 
+/// Payload of the `alert-triggered` / `alert-resolved` events.
+#[derive(Debug, Clone, serde::Serialize)]
+struct AlertChange {
+    /// `None` when recording the alert failed.
+    alert_id: Option<i64>,
+    distro_name: String,
+    alert_type: AlertType,
+    /// Unit of `threshold` and `actual_value`.
+    unit: AlertUnit,
+    threshold: f64,
+    /// `None` when a stopped distro resolved the alert.
+    actual_value: Option<f64>,
+    #[serde(skip)]
+    resolved: bool,
+    /// Notification channels of the rule.
+    #[serde(skip)]
+    channels: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::entities::alert_silence::{AlertMatcher, AlertSilence};
     use crate::domain::entities::distro::Distro;
     use crate::domain::entities::monitoring::{
-        CpuMetrics, DiskMetrics, MemoryMetrics, NetworkMetrics,
+        CpuMetrics, DiskIoMetrics, DiskMetrics, GpuMetrics, MemoryMetrics, NetworkMetrics,
+        ProcessInfo, ServiceCgroupStats, TcpConnectionMetrics,
     };
-    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::entities::wsl_config::WslDistroConfig;
+    use crate::domain::ports::alerting::{AlertThreshold, MockAlertingPort};
+    use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
+    use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
     use crate::domain::ports::wsl_manager::MockWslManagerPort;
     use crate::domain::value_objects::{DistroState, WslVersion};
 
@@ -320,6 +711,28 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn latest_metrics_keep_the_last_collection_of_running_distros() {
+        let latest = LatestMetrics::default();
+        latest
+            .record(&make_metrics("Ubuntu", 10.0, 1, 2, 0.0))
+            .await;
+        latest
+            .record(&make_metrics("Debian", 20.0, 1, 2, 0.0))
+            .await;
+        latest
+            .record(&make_metrics("Ubuntu", 30.0, 1, 2, 0.0))
+            .await;
+
+        let snapshot = latest.snapshot().await;
+        let names: Vec<&str> = snapshot.iter().map(|m| m.distro_name.as_str()).collect();
+        assert_eq!(names, vec!["Debian", "Ubuntu"]);
+        assert_eq!(snapshot[1].cpu.usage_percent, 30.0);
+
+        latest.retain(&["Ubuntu".to_string()]).await;
+        assert_eq!(latest.snapshot().await.len(), 1);
+    }
+
     // --- get_distros cache tests ---
 
     #[tokio::test]
@@ -397,7 +810,7 @@ mod tests {
         assert!(result.is_none());
     }
 
-    // --- check_alerts_headless tests ---
+    // --- evaluate_alerts tests ---
 
     #[tokio::test]
     async fn alert_triggered_when_cpu_exceeds_threshold() {
@@ -405,23 +818,37 @@ mod tests {
         alerting
             .expect_record_alert()
             .times(1)
-            .returning(|_, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _| Ok(1));
 
         let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
-        let thresholds = Arc::new(tokio::sync::RwLock::new(vec![AlertThreshold {
-            alert_type: AlertType::Cpu,
-            threshold_percent: 80.0,
-            enabled: true,
-        }]));
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Cpu,
+                threshold_percent: 80.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
 
         let metrics = make_metrics("Ubuntu", 95.0, 0, 1, 0.0);
-        let mut cooldowns: HashMap<(String, String), Instant> = HashMap::new();
-
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
-            .await;
+        let mut evaluator = AlertEvaluator::default();
+
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
 
         // Cooldown entry should be recorded
-        assert!(cooldowns.contains_key(&("Ubuntu".to_string(), "cpu".to_string())));
+        assert!(evaluator.is_firing("Ubuntu", AlertType::Cpu));
     }
 
     #[tokio::test]
@@ -431,47 +858,82 @@ mod tests {
         alerting.expect_record_alert().times(0);
 
         let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
-        let thresholds = Arc::new(tokio::sync::RwLock::new(vec![AlertThreshold {
-            alert_type: AlertType::Cpu,
-            threshold_percent: 80.0,
-            enabled: true,
-        }]));
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Cpu,
+                threshold_percent: 80.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
 
         let metrics = make_metrics("Ubuntu", 50.0, 0, 1, 0.0);
-        let mut cooldowns: HashMap<(String, String), Instant> = HashMap::new();
-
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
-            .await;
-
-        assert!(cooldowns.is_empty());
+        let mut evaluator = AlertEvaluator::default();
+
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
+
+        assert!(evaluator.distros().is_empty());
     }
 
     #[tokio::test]
-    async fn cooldown_prevents_duplicate_alerts() {
+    async fn firing_alert_is_recorded_once() {
         let mut alerting = MockAlertingPort::new();
         // record_alert should only be called once (first invocation)
         alerting
             .expect_record_alert()
             .times(1)
-            .returning(|_, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _| Ok(1));
 
         let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
-        let thresholds = Arc::new(tokio::sync::RwLock::new(vec![AlertThreshold {
-            alert_type: AlertType::Cpu,
-            threshold_percent: 80.0,
-            enabled: true,
-        }]));
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Cpu,
+                threshold_percent: 80.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
 
         let metrics = make_metrics("Ubuntu", 95.0, 0, 1, 0.0);
-        let mut cooldowns: HashMap<(String, String), Instant> = HashMap::new();
+        let mut evaluator = AlertEvaluator::default();
 
         // First call: should trigger alert
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
-            .await;
-
-        // Second call: should be suppressed by cooldown
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
-            .await;
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
+
+        // Second call: already firing, nothing new to record
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
     }
 
     #[tokio::test]
@@ -480,19 +942,33 @@ mod tests {
         alerting.expect_record_alert().times(0);
 
         let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
-        let thresholds = Arc::new(tokio::sync::RwLock::new(vec![AlertThreshold {
-            alert_type: AlertType::Cpu,
-            threshold_percent: 80.0,
-            enabled: false,
-        }]));
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Cpu,
+                threshold_percent: 80.0,
+                enabled: false,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
 
         let metrics = make_metrics("Ubuntu", 95.0, 0, 1, 0.0);
-        let mut cooldowns: HashMap<(String, String), Instant> = HashMap::new();
-
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
-            .await;
-
-        assert!(cooldowns.is_empty());
+        let mut evaluator = AlertEvaluator::default();
+
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
+
+        assert!(evaluator.distros().is_empty());
     }
 
     #[tokio::test]
@@ -501,23 +977,37 @@ mod tests {
         alerting
             .expect_record_alert()
             .times(1)
-            .returning(|_, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _| Ok(1));
 
         let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
-        let thresholds = Arc::new(tokio::sync::RwLock::new(vec![AlertThreshold {
-            alert_type: AlertType::Memory,
-            threshold_percent: 90.0,
-            enabled: true,
-        }]));
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Memory,
+                threshold_percent: 90.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
 
         // 95% memory usage (950 / 1000)
         let metrics = make_metrics("Ubuntu", 0.0, 950, 1000, 0.0);
-        let mut cooldowns: HashMap<(String, String), Instant> = HashMap::new();
-
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
-            .await;
-
-        assert!(cooldowns.contains_key(&("Ubuntu".to_string(), "memory".to_string())));
+        let mut evaluator = AlertEvaluator::default();
+
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
+
+        assert!(evaluator.is_firing("Ubuntu", AlertType::Memory));
     }
 
     #[tokio::test]
@@ -526,20 +1016,34 @@ mod tests {
         alerting.expect_record_alert().times(0);
 
         let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
-        let thresholds = Arc::new(tokio::sync::RwLock::new(vec![AlertThreshold {
-            alert_type: AlertType::Memory,
-            threshold_percent: 90.0,
-            enabled: true,
-        }]));
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Memory,
+                threshold_percent: 90.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
 
         // total_bytes = 0 should cause a skip
         let metrics = make_metrics("Ubuntu", 0.0, 0, 0, 0.0);
-        let mut cooldowns: HashMap<(String, String), Instant> = HashMap::new();
-
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
-            .await;
-
-        assert!(cooldowns.is_empty());
+        let mut evaluator = AlertEvaluator::default();
+
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
+
+        assert!(evaluator.distros().is_empty());
     }
 
     #[tokio::test]
@@ -549,28 +1053,462 @@ mod tests {
         alerting
             .expect_record_alert()
             .times(2)
-            .returning(|_, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _| Ok(1));
 
         let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
-        let thresholds = Arc::new(tokio::sync::RwLock::new(vec![
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
             AlertThreshold {
                 alert_type: AlertType::Cpu,
                 threshold_percent: 80.0,
                 enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
             },
             AlertThreshold {
                 alert_type: AlertType::Disk,
                 threshold_percent: 70.0,
                 enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
             },
-        ]));
+        ])));
 
         let metrics = make_metrics("Ubuntu", 95.0, 0, 1, 85.0);
-        let mut cooldowns: HashMap<(String, String), Instant> = HashMap::new();
+        let mut evaluator = AlertEvaluator::default();
+
+        MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
+
+        assert!(evaluator.is_firing("Ubuntu", AlertType::Cpu));
+        assert!(evaluator.is_firing("Ubuntu", AlertType::Disk));
+    }
+
+    #[tokio::test]
+    async fn distro_override_replaces_global_threshold() {
+        let mut alerting = MockAlertingPort::new();
+        // Only Debian alerts: Ubuntu's override raises the CPU threshold to 99%
+        alerting
+            .expect_record_alert()
+            .withf(|name, _, _, threshold, _, _| name.as_str() == "Debian" && *threshold == 80.0)
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(1));
+
+        let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Cpu,
+                threshold_percent: 80.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+            AlertThreshold {
+                alert_type: AlertType::Cpu,
+                threshold_percent: 99.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: Some("Ubuntu".into()),
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
+        let mut evaluator = AlertEvaluator::default();
+
+        for distro in ["Ubuntu", "Debian"] {
+            let metrics = make_metrics(distro, 95.0, 0, 1, 0.0);
+            MetricsCollector::evaluate_alerts(
+                &alerting,
+                &thresholds,
+                &Default::default(),
+                &metrics,
+                &mut evaluator,
+                Instant::now(),
+            )
+            .await;
+        }
+
+        assert_eq!(evaluator.distros(), vec!["Debian".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn alert_resolves_below_clear_threshold_and_when_distro_stops() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_record_alert()
+            .times(2)
+            .returning(|_, alert_type, _, _, _, _| {
+                Ok(if alert_type == AlertType::Cpu { 1 } else { 2 })
+            });
+        alerting
+            .expect_resolve_alert()
+            .withf(|id, value| *id == 1 && *value == Some(60.0))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        alerting
+            .expect_resolve_alert()
+            .withf(|id, value| *id == 2 && value.is_none())
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
+        let rule = |alert_type| AlertThreshold {
+            alert_type,
+            threshold_percent: 80.0,
+            enabled: true,
+            unit: AlertUnit::Percent,
+            distro_name: None,
+            sustained_secs: 0,
+            clear_percent: Some(70.0),
+            channels: Vec::new(),
+        };
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            rule(AlertType::Cpu),
+            rule(AlertType::Disk),
+        ])));
+        let mut evaluator = AlertEvaluator::default();
+
+        for (cpu, disk) in [(95.0, 95.0), (75.0, 95.0), (60.0, 95.0)] {
+            let metrics = make_metrics("Ubuntu", cpu, 0, 1, disk);
+            MetricsCollector::evaluate_alerts(
+                &alerting,
+                &thresholds,
+                &Default::default(),
+                &metrics,
+                &mut evaluator,
+                Instant::now(),
+            )
+            .await;
+        }
+        assert!(!evaluator.is_firing("Ubuntu", AlertType::Cpu));
+        assert!(evaluator.is_firing("Ubuntu", AlertType::Disk));
+
+        let changes =
+            MetricsCollector::resolve_stopped(&alerting, &thresholds, &[], &mut evaluator).await;
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].resolved);
+        assert!(evaluator.distros().is_empty());
+    }
+
+    #[test]
+    fn alert_value_covers_extended_types() {
+        let mut metrics = make_metrics("Ubuntu", 10.0, 2048, 4096, 0.0);
+        metrics.cpu.per_core = vec![10.0; 4];
+        metrics.cpu.load_average = [6.0, 4.0, 2.0];
+        metrics.memory.swap_total_bytes = 1000;
+        metrics.memory.swap_used_bytes = 250;
+
+        let value = |alert_type, unit| alert_value(&metrics, alert_type, unit);
+        assert_eq!(value(AlertType::Memory, AlertUnit::Absolute), Some(2048.0));
+        assert_eq!(value(AlertType::Swap, AlertUnit::Percent), Some(25.0));
+        assert_eq!(value(AlertType::Swap, AlertUnit::Absolute), Some(250.0));
+        assert_eq!(
+            value(AlertType::LoadPerCore, AlertUnit::Absolute),
+            Some(1.5)
+        );
+        // Not collected yet
+        assert_eq!(value(AlertType::TcpTimeWait, AlertUnit::Absolute), None);
+        assert_eq!(value(AlertType::DiskWrite, AlertUnit::Absolute), None);
+        assert_eq!(value(AlertType::GpuVram, AlertUnit::Percent), None);
+        // Unit doesn't apply
+        assert_eq!(value(AlertType::Cpu, AlertUnit::Absolute), None);
+
+        metrics.tcp_connections = Some(TcpConnectionMetrics {
+            established: 10,
+            time_wait: 4200,
+            listen: 3,
+        });
+        metrics.disk_io = Some(DiskIoMetrics {
+            read_bytes_per_sec: 0,
+            write_bytes_per_sec: 5_000_000,
+        });
+        metrics.gpu = Some(GpuMetrics {
+            utilization_percent: None,
+            vram_used_bytes: Some(3 << 30),
+            vram_total_bytes: Some(4 << 30),
+        });
+        let value = |alert_type, unit| alert_value(&metrics, alert_type, unit);
+        assert_eq!(
+            value(AlertType::TcpTimeWait, AlertUnit::Absolute),
+            Some(4200.0)
+        );
+        assert_eq!(value(AlertType::DiskWrite, AlertUnit::Absolute), Some(5e6));
+        assert_eq!(value(AlertType::GpuVram, AlertUnit::Percent), Some(75.0));
+        assert_eq!(
+            value(AlertType::GpuVram, AlertUnit::Absolute),
+            Some((3u64 << 30) as f64)
+        );
+    }
+
+    #[test]
+    fn format_value_uses_the_alert_unit() {
+        assert_eq!(
+            format_value(AlertType::Swap, AlertUnit::Percent, 42.25),
+            "42.2%"
+        );
+        assert_eq!(
+            format_value(AlertType::LoadPerCore, AlertUnit::Absolute, 1.5),
+            "1.50"
+        );
+        assert_eq!(
+            format_value(AlertType::TcpTimeWait, AlertUnit::Absolute, 4200.0),
+            "4200 sockets"
+        );
+        assert_eq!(
+            format_value(AlertType::DiskWrite, AlertUnit::Absolute, 2048.0),
+            "2.00 KB/s"
+        );
+    }
+
+    #[tokio::test]
+    async fn absolute_alert_records_its_unit() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_record_alert()
+            .withf(|_, alert_type, unit, threshold, value, suppressed_by| {
+                *alert_type == AlertType::TcpTimeWait
+                    && *unit == AlertUnit::Absolute
+                    && *threshold == 1000.0
+                    && *value == 4200.0
+                    && suppressed_by.is_none()
+            })
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(1));
+
+        let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::TcpTimeWait,
+                threshold_percent: 1000.0,
+                enabled: true,
+                unit: AlertUnit::Absolute,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
+        let mut metrics = make_metrics("Ubuntu", 10.0, 0, 1, 0.0);
+        metrics.tcp_connections = Some(TcpConnectionMetrics {
+            established: 10,
+            time_wait: 4200,
+            listen: 3,
+        });
+        let mut evaluator = AlertEvaluator::default();
+
+        let changes = MetricsCollector::evaluate_alerts(
+            &alerting,
+            &thresholds,
+            &Default::default(),
+            &metrics,
+            &mut evaluator,
+            Instant::now(),
+        )
+        .await;
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].unit, AlertUnit::Absolute);
+    }
 
-        MetricsCollector::check_alerts_headless(&alerting, &thresholds, &metrics, &mut cooldowns)
+    #[tokio::test]
+    async fn silenced_alert_is_recorded_as_suppressed_without_notifying() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_record_alert()
+            .withf(|_, _, _, _, _, suppressed_by| {
+                suppressed_by.as_deref() == Some("Silence: Kernel build")
+            })
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(5));
+        alerting
+            .expect_resolve_alert()
+            .withf(|id, _| *id == 5)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let alerting: Arc<dyn AlertingPort> = Arc::new(alerting);
+        let thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(vec![
+            AlertThreshold {
+                alert_type: AlertType::Cpu,
+                threshold_percent: 80.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            },
+        ])));
+        let now = chrono::Utc::now();
+        let suppressions = Arc::new(tokio::sync::RwLock::new(AlertSuppressions {
+            silences: vec![AlertSilence {
+                id: "s".into(),
+                matcher: AlertMatcher {
+                    distro_name: Some("Ubuntu".into()),
+                    alert_type: None,
+                },
+                reason: "Kernel build".into(),
+                starts_at: now - chrono::Duration::minutes(1),
+                ends_at: now + chrono::Duration::hours(1),
+                created_at: now,
+            }],
+            windows: Vec::new(),
+        }));
+        let mut evaluator = AlertEvaluator::default();
+
+        for cpu in [95.0, 40.0] {
+            let changes = MetricsCollector::evaluate_alerts(
+                &alerting,
+                &thresholds,
+                &suppressions,
+                &make_metrics("Ubuntu", cpu, 0, 1, 0.0),
+                &mut evaluator,
+                Instant::now(),
+            )
             .await;
+            assert!(changes.is_empty());
+        }
+        assert!(!evaluator.is_firing("Ubuntu", AlertType::Cpu));
+    }
+
+    #[tokio::test]
+    async fn process_samples_are_stored_from_the_second_listing() {
+        let mut monitoring = MockMonitoringProviderPort::new();
+        monitoring.expect_get_processes().returning(|name| {
+            if name.as_str() == "Debian" {
+                return Err(DomainError::Internal("ps failed".into()));
+            }
+            Ok(vec![ProcessInfo {
+                pid: 42,
+                user: "root".to_string(),
+                cpu_percent: 0.0,
+                mem_percent: 1.0,
+                vsz_bytes: 0,
+                rss_bytes: 1_000,
+                command: "cargo build".to_string(),
+                state: "R".to_string(),
+                cpu_time_secs: 0,
+            }])
+        });
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_store_process_samples()
+            .withf(|name, _, samples| {
+                name.as_str() == "Ubuntu" && samples.len() == 1 && samples[0].pid == 42
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let monitoring: Arc<dyn MonitoringProviderPort> = Arc::new(monitoring);
+        let metrics_repo: Arc<dyn MetricsRepositoryPort> = Arc::new(metrics_repo);
+        let distros = [
+            DistroName::new("Ubuntu").unwrap(),
+            DistroName::new("Debian").unwrap(),
+        ];
+        let mut tracker = ProcessTracker::default();
+
+        // The first listing only sets the CPU baseline
+        MetricsCollector::sample_processes(&monitoring, &metrics_repo, &mut tracker, &distros)
+            .await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        MetricsCollector::sample_processes(&monitoring, &metrics_repo, &mut tracker, &distros)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn only_distros_booted_with_systemd_are_sampled_for_services() {
+        let mut wsl_manager = MockWslManagerPort::new();
+        // Read once per distro while it keeps running
+        wsl_manager
+            .expect_get_distro_config()
+            .times(2)
+            .returning(|name| {
+                Ok(WslDistroConfig {
+                    boot_systemd: Some(name.as_str() == "Ubuntu"),
+                    ..Default::default()
+                })
+            });
+        let mut monitoring = MockMonitoringProviderPort::new();
+        monitoring
+            .expect_get_service_stats()
+            .withf(|name| name.as_str() == "Ubuntu")
+            .times(2)
+            .returning(|_| {
+                Ok(vec![ServiceCgroupStats {
+                    unit: "docker.service".to_string(),
+                    memory_bytes: 700_000_000,
+                    ..Default::default()
+                }])
+            });
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_store_service_metrics()
+            .withf(|name, _, services| {
+                name.as_str() == "Ubuntu"
+                    && services.len() == 1
+                    && services[0].memory_bytes == 700_000_000
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let wsl_manager: Arc<dyn WslManagerPort> = Arc::new(wsl_manager);
+        let monitoring: Arc<dyn MonitoringProviderPort> = Arc::new(monitoring);
+        let metrics_repo: Arc<dyn MetricsRepositoryPort> = Arc::new(metrics_repo);
+        let running = [
+            DistroName::new("Ubuntu").unwrap(),
+            DistroName::new("Debian").unwrap(),
+        ];
+        let mut boots_systemd = HashMap::new();
+        let mut tracker = ServiceTracker::default();
+
+        // The first reading only sets the CPU and I/O baseline
+        for _ in 0..2 {
+            let systemd =
+                MetricsCollector::systemd_distros(&wsl_manager, &mut boots_systemd, &running).await;
+            assert_eq!(systemd, vec![DistroName::new("Ubuntu").unwrap()]);
+            MetricsCollector::sample_services(&monitoring, &metrics_repo, &mut tracker, &systemd)
+                .await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
 
-        assert_eq!(cooldowns.len(), 2);
+    #[test]
+    fn alert_notification_describes_the_change() {
+        let mut change = AlertChange {
+            alert_id: Some(4),
+            distro_name: "Ubuntu".into(),
+            alert_type: AlertType::DiskWrite,
+            unit: AlertUnit::Absolute,
+            threshold: 1024.0 * 1024.0,
+            actual_value: Some(3.0 * 1024.0 * 1024.0),
+            resolved: false,
+            channels: vec!["hook".into()],
+        };
+        let fired = alert_notification(&change);
+        assert_eq!(fired.title, "Disk write Alert - Ubuntu");
+        assert_eq!(fired.body, "Disk write at 3.00 MB/s (threshold: 1.00 MB/s)");
+        assert_eq!(fired.alert_id, Some(4));
+
+        change.resolved = true;
+        change.actual_value = None;
+        let resolved = alert_notification(&change);
+        assert_eq!(resolved.title, "Disk write Alert Resolved - Ubuntu");
+        assert_eq!(resolved.body, "Ubuntu is no longer monitored");
     }
 }
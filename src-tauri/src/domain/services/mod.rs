@@ -1,3 +1,8 @@
+pub mod alert_dispatcher;
+pub mod alert_evaluator;
 pub mod distro_service;
 pub mod metrics_aggregator;
 pub mod metrics_collector;
+pub mod process_tracker;
+pub mod service_tracker;
+pub mod snapshot_retention;
@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use crate::domain::entities::monitoring::{ProcessInfo, ProcessSample};
+
+/// Processes kept per distro and sample: the top N by CPU plus the top N by
+/// resident memory.
+pub const TOP_PROCESSES: usize = 10;
+/// Longer command lines are truncated before being stored.
+const MAX_COMMAND_CHARS: usize = 256;
+
+/// Turns successive process listings into per-process samples.
+///
+/// The `%CPU` column of `ps` is averaged over the lifetime of a process, so a
+/// daemon that idled for a day and then spins hides behind a low figure.
+/// Instead, the CPU of a sample is the CPU time a process used since the
+/// previous listing of its distro, divided by the wall time in between.
+#[derive(Debug, Default)]
+pub struct ProcessTracker {
+    /// Per distro: when it was last listed and the CPU time of each pid then.
+    previous: HashMap<String, (Instant, HashMap<u32, u64>)>,
+}
+
+impl ProcessTracker {
+    /// Sample the processes of `distro`, keeping the top offenders. `None`
+    /// for the first listing of a distro, which only sets the baseline.
+    pub fn sample(
+        &mut self,
+        distro: &str,
+        processes: &[ProcessInfo],
+        now: Instant,
+    ) -> Option<Vec<ProcessSample>> {
+        let cpu_times: HashMap<u32, u64> =
+            processes.iter().map(|p| (p.pid, p.cpu_time_secs)).collect();
+        let (since, previous) = self.previous.insert(distro.to_string(), (now, cpu_times))?;
+
+        let elapsed = now.duration_since(since).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        let mut samples: Vec<ProcessSample> = processes
+            .iter()
+            .map(|p| {
+                // A pid that is new, or was reused by a younger process, used
+                // all of its CPU time since the previous listing
+                let used = match previous.get(&p.pid) {
+                    Some(&before) if before <= p.cpu_time_secs => p.cpu_time_secs - before,
+                    _ => p.cpu_time_secs.min(elapsed.ceil() as u64),
+                };
+                ProcessSample {
+                    pid: p.pid,
+                    user: p.user.clone(),
+                    command: p.command.chars().take(MAX_COMMAND_CHARS).collect(),
+                    cpu_percent: used as f64 / elapsed * 100.0,
+                    rss_bytes: p.rss_bytes,
+                }
+            })
+            .collect();
+
+        samples.sort_by_key(|s| std::cmp::Reverse(s.rss_bytes));
+        let top_memory: HashSet<u32> = samples.iter().take(TOP_PROCESSES).map(|s| s.pid).collect();
+        samples.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        let top = samples
+            .into_iter()
+            .enumerate()
+            .filter(|(rank, s)| *rank < TOP_PROCESSES || top_memory.contains(&s.pid))
+            .map(|(_, s)| s)
+            .collect();
+        Some(top)
+    }
+
+    /// Forget the distros that are no longer running.
+    pub fn retain(&mut self, running: &[String]) {
+        self.previous.retain(|distro, _| running.contains(distro));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn process(pid: u32, cpu_time_secs: u64, rss_bytes: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            user: "root".to_string(),
+            cpu_percent: 0.0,
+            mem_percent: 0.0,
+            vsz_bytes: 0,
+            rss_bytes,
+            command: format!("proc-{pid}"),
+            state: "S".to_string(),
+            cpu_time_secs,
+        }
+    }
+
+    #[test]
+    fn cpu_is_measured_between_listings() {
+        let mut tracker = ProcessTracker::default();
+        let start = Instant::now();
+        let first = [process(1, 3_600, 1_000), process(2, 0, 1_000)];
+        assert!(tracker.sample("Ubuntu", &first, start).is_none());
+
+        // pid 1 idles, pid 2 spins for the whole 10s, pid 3 started since
+        let second = [
+            process(1, 3_600, 1_000),
+            process(2, 10, 1_000),
+            process(3, 5, 1_000),
+        ];
+        let samples = tracker
+            .sample("Ubuntu", &second, start + Duration::from_secs(10))
+            .unwrap();
+
+        let pids: Vec<u32> = samples.iter().map(|s| s.pid).collect();
+        assert_eq!(pids, vec![2, 3, 1]);
+        assert!((samples[0].cpu_percent - 100.0).abs() < 0.01);
+        assert!((samples[1].cpu_percent - 50.0).abs() < 0.01);
+        assert_eq!(samples[2].cpu_percent, 0.0);
+    }
+
+    #[test]
+    fn keeps_top_cpu_and_top_memory_processes() {
+        let mut tracker = ProcessTracker::default();
+        let start = Instant::now();
+        // 12 busy processes with little memory and one idle memory hog
+        let mut processes: Vec<ProcessInfo> = (1..=12)
+            .map(|pid| process(pid, 0, 1_000 * u64::from(pid)))
+            .collect();
+        processes.push(process(99, 0, 8_000_000_000));
+        tracker.sample("Ubuntu", &processes, start);
+
+        for p in processes.iter_mut().filter(|p| p.pid != 99) {
+            p.cpu_time_secs = u64::from(p.pid);
+        }
+        let samples = tracker
+            .sample("Ubuntu", &processes, start + Duration::from_secs(10))
+            .unwrap();
+
+        assert_eq!(samples.len(), TOP_PROCESSES + 1);
+        assert_eq!(samples[0].pid, 12);
+        assert!(samples.iter().any(|s| s.pid == 99));
+        assert!(!samples.iter().any(|s| s.pid == 1 || s.pid == 2));
+    }
+
+    #[test]
+    fn stopped_distro_starts_a_new_baseline() {
+        let mut tracker = ProcessTracker::default();
+        let start = Instant::now();
+        tracker.sample("Ubuntu", &[process(1, 0, 1_000)], start);
+        tracker.retain(&[]);
+
+        let later = start + Duration::from_secs(10);
+        assert!(
+            tracker
+                .sample("Ubuntu", &[process(1, 5, 1_000)], later)
+                .is_none()
+        );
+    }
+}
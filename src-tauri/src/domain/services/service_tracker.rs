@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::domain::entities::monitoring::{ServiceCgroupStats, ServiceMetrics};
+
+/// Turns successive cgroup readings into per-service samples.
+///
+/// `cpu.stat` and `io.stat` count from the creation of a unit's cgroup, so
+/// the CPU and I/O of a sample are the difference with the previous reading
+/// of its distro, divided by the wall time in between. Memory is the current
+/// usage and needs no baseline.
+#[derive(Debug, Default)]
+pub struct ServiceTracker {
+    /// Per distro: when it was last read and the counters of each unit then.
+    previous: HashMap<String, (Instant, HashMap<String, ServiceCgroupStats>)>,
+}
+
+impl ServiceTracker {
+    /// Sample the units of `distro`. `None` for the first reading of a
+    /// distro, which only sets the baseline.
+    pub fn sample(
+        &mut self,
+        distro: &str,
+        services: &[ServiceCgroupStats],
+        now: Instant,
+    ) -> Option<Vec<ServiceMetrics>> {
+        let counters: HashMap<String, ServiceCgroupStats> = services
+            .iter()
+            .map(|s| (s.unit.clone(), s.clone()))
+            .collect();
+        let (since, previous) = self.previous.insert(distro.to_string(), (now, counters))?;
+
+        let elapsed = now.duration_since(since).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        let samples = services
+            .iter()
+            .map(|s| {
+                // A unit that started, or restarted in a new cgroup, since the
+                // previous reading counts from zero
+                let before = previous
+                    .get(&s.unit)
+                    .filter(|b| {
+                        b.cpu_usage_usec <= s.cpu_usage_usec
+                            && b.io_read_bytes <= s.io_read_bytes
+                            && b.io_write_bytes <= s.io_write_bytes
+                    })
+                    .cloned()
+                    .unwrap_or_default();
+                let rate = |used: u64| (used as f64 / elapsed).round() as u64;
+                ServiceMetrics {
+                    unit: s.unit.clone(),
+                    cpu_percent: (s.cpu_usage_usec - before.cpu_usage_usec) as f64
+                        / 1_000_000.0
+                        / elapsed
+                        * 100.0,
+                    memory_bytes: s.memory_bytes,
+                    io_read_bytes_per_sec: rate(s.io_read_bytes - before.io_read_bytes),
+                    io_write_bytes_per_sec: rate(s.io_write_bytes - before.io_write_bytes),
+                }
+            })
+            .collect();
+        Some(samples)
+    }
+
+    /// Forget the distros that are no longer running.
+    pub fn retain(&mut self, running: &[String]) {
+        self.previous.retain(|distro, _| running.contains(distro));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn unit(name: &str, cpu_usage_usec: u64, memory_bytes: u64, io: u64) -> ServiceCgroupStats {
+        ServiceCgroupStats {
+            unit: name.to_string(),
+            cpu_usage_usec,
+            memory_bytes,
+            io_read_bytes: io,
+            io_write_bytes: io / 2,
+        }
+    }
+
+    #[test]
+    fn usage_is_measured_between_readings() {
+        let mut tracker = ServiceTracker::default();
+        let start = Instant::now();
+        let first = [
+            unit("postgresql.service", 60_000_000, 1_000, 0),
+            unit("redis-server.service", 0, 1_000, 0),
+        ];
+        assert!(tracker.sample("Ubuntu", &first, start).is_none());
+
+        // postgres idles, redis spins for the whole 10s and reads 10 MB,
+        // docker started since
+        let second = [
+            unit("postgresql.service", 60_000_000, 2_000, 0),
+            unit("redis-server.service", 10_000_000, 1_000, 10_000_000),
+            unit("docker.service", 5_000_000, 500_000_000, 0),
+        ];
+        let samples = tracker
+            .sample("Ubuntu", &second, start + Duration::from_secs(10))
+            .unwrap();
+
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0].cpu_percent, 0.0);
+        assert_eq!(samples[0].memory_bytes, 2_000);
+        assert!((samples[1].cpu_percent - 100.0).abs() < 0.01);
+        assert_eq!(samples[1].io_read_bytes_per_sec, 1_000_000);
+        assert_eq!(samples[1].io_write_bytes_per_sec, 500_000);
+        assert!((samples[2].cpu_percent - 50.0).abs() < 0.01);
+        assert_eq!(samples[2].memory_bytes, 500_000_000);
+    }
+
+    #[test]
+    fn restarted_unit_counts_from_zero() {
+        let mut tracker = ServiceTracker::default();
+        let start = Instant::now();
+        tracker.sample("Ubuntu", &[unit("docker.service", 90_000_000, 0, 0)], start);
+
+        let samples = tracker
+            .sample(
+                "Ubuntu",
+                &[unit("docker.service", 2_000_000, 0, 0)],
+                start + Duration::from_secs(10),
+            )
+            .unwrap();
+        assert!((samples[0].cpu_percent - 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn stopped_distro_starts_a_new_baseline() {
+        let mut tracker = ServiceTracker::default();
+        let start = Instant::now();
+        tracker.sample("Ubuntu", &[unit("docker.service", 0, 0, 0)], start);
+        tracker.retain(&[]);
+
+        let later = start + Duration::from_secs(10);
+        assert!(
+            tracker
+                .sample("Ubuntu", &[unit("docker.service", 5, 0, 0)], later)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::entities::safety_snapshot::{SAFETY_TAG, SafetySnapshotPolicy};
+use crate::domain::entities::snapshot::{Snapshot, SnapshotStatus};
+use crate::domain::entities::snapshot_schedule::{RetentionPolicy, ScheduleFrequency};
+use crate::domain::value_objects::SnapshotId;
+
+/// Snapshots that fall outside `policy` (grandfather-father-son).
+///
+/// Walking from newest to oldest, a snapshot is kept when it is the newest of
+/// an hour, day or week that still has a slot left in its tier. Only completed
+/// snapshots take part: in-progress, failed, corrupted and pinned ones are
+/// neither kept in a slot nor expired.
+pub fn expired_snapshots(snapshots: &[Snapshot], policy: &RetentionPolicy) -> Vec<SnapshotId> {
+    if policy.keeps_everything() {
+        return Vec::new();
+    }
+
+    let mut completed: Vec<&Snapshot> = snapshots
+        .iter()
+        .filter(|s| matches!(s.status, SnapshotStatus::Completed) && !s.pinned)
+        .collect();
+    completed.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+
+    let mut tiers = [
+        (ScheduleFrequency::Hourly, policy.keep_hourly, None),
+        (ScheduleFrequency::Daily, policy.keep_daily, None),
+        (ScheduleFrequency::Weekly, policy.keep_weekly, None),
+    ];
+
+    let mut expired = Vec::new();
+    for snapshot in completed {
+        let mut keep = false;
+        for (frequency, slots, last_key) in tiers.iter_mut() {
+            let key = frequency.period_key(snapshot.created_at);
+            if *slots > 0 && *last_key != Some(key) {
+                *slots -= 1;
+                *last_key = Some(key);
+                keep = true;
+            }
+        }
+        if !keep {
+            expired.push(snapshot.id.clone());
+        }
+    }
+    expired
+}
+
+/// Safety snapshots (tagged [`SAFETY_TAG`]) that `policy` no longer keeps at
+/// `now`: those beyond the newest `keep_last` and those older than
+/// `max_age_hours`. Other snapshots, pinned ones and ones that did not
+/// complete are never expired.
+pub fn expired_safety_snapshots(
+    snapshots: &[Snapshot],
+    policy: &SafetySnapshotPolicy,
+    now: DateTime<Utc>,
+) -> Vec<SnapshotId> {
+    let mut safety: Vec<&Snapshot> = snapshots
+        .iter()
+        .filter(|s| {
+            matches!(s.status, SnapshotStatus::Completed)
+                && !s.pinned
+                && s.tags.iter().any(|t| t == SAFETY_TAG)
+        })
+        .collect();
+    safety.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+
+    let max_age = chrono::Duration::hours(policy.max_age_hours as i64);
+    safety
+        .into_iter()
+        .enumerate()
+        .filter(|(i, s)| {
+            (policy.keep_last > 0 && *i >= policy.keep_last as usize)
+                || (policy.max_age_hours > 0 && now - s.created_at > max_age)
+        })
+        .map(|(_, s)| s.id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotStorage, SnapshotType};
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn snap(id: &str, created_at: DateTime<Utc>) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: format!("/tmp/{id}.tar"),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at,
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn ids(ids: &[SnapshotId]) -> Vec<&str> {
+        let mut v: Vec<&str> = ids.iter().map(|id| id.as_str()).collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn test_keeps_everything_with_empty_policy() {
+        let snaps = vec![snap("a", Utc::now()), snap("b", Utc::now())];
+        assert!(expired_snapshots(&snaps, &RetentionPolicy::default()).is_empty());
+    }
+
+    #[test]
+    fn test_hourly_keeps_newest_per_hour() {
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 3, 11, h, m, 0).unwrap();
+        let snaps = vec![
+            snap("h10a", at(10, 5)),
+            snap("h10b", at(10, 10)),
+            snap("h11", at(11, 5)),
+            snap("h12", at(12, 5)),
+            snap("h13", at(13, 5)),
+        ];
+        let policy = RetentionPolicy {
+            keep_hourly: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&expired_snapshots(&snaps, &policy)),
+            vec!["h10a", "h10b"]
+        );
+    }
+
+    #[test]
+    fn test_daily_and_weekly_tiers_combine() {
+        // Newest is Sunday 2026-03-15: the 7 daily slots cover ISO week 11,
+        // the weekly slots add the Sundays of weeks 10, 9 and 8.
+        let newest = Utc.with_ymd_and_hms(2026, 3, 15, 12, 0, 0).unwrap();
+        let snaps: Vec<Snapshot> = (0..60)
+            .map(|d| snap(&format!("d{d:02}"), newest - chrono::Duration::days(d)))
+            .collect();
+        let policy = RetentionPolicy {
+            keep_hourly: 0,
+            keep_daily: 7,
+            keep_weekly: 4,
+        };
+
+        let expired = expired_snapshots(&snaps, &policy);
+        assert_eq!(expired.len(), 60 - 10);
+        for kept in ["d00", "d06", "d07", "d14", "d21"] {
+            assert!(!expired.iter().any(|id| id.as_str() == kept), "{kept}");
+        }
+        assert!(expired.iter().any(|id| id.as_str() == "d08"));
+    }
+
+    #[test]
+    fn test_ignores_snapshots_that_are_not_completed() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 11, 12, 0, 0).unwrap();
+        let mut failed = snap("failed", now);
+        failed.status = SnapshotStatus::Failed("boom".into());
+        let snaps = vec![
+            failed,
+            snap("new", now - chrono::Duration::hours(1)),
+            snap("old", now - chrono::Duration::hours(2)),
+        ];
+        let policy = RetentionPolicy {
+            keep_hourly: 1,
+            ..Default::default()
+        };
+        assert_eq!(ids(&expired_snapshots(&snaps, &policy)), vec!["old"]);
+    }
+
+    #[test]
+    fn test_pinned_snapshots_never_expire_nor_take_a_slot() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 11, 12, 0, 0).unwrap();
+        let mut golden = snap("golden", now - chrono::Duration::hours(5));
+        golden.pinned = true;
+        let mut pinned_new = snap("pinned-new", now);
+        pinned_new.pinned = true;
+        let snaps = vec![
+            pinned_new,
+            snap("new", now - chrono::Duration::hours(1)),
+            snap("old", now - chrono::Duration::hours(2)),
+            golden,
+        ];
+        let policy = RetentionPolicy {
+            keep_hourly: 1,
+            ..Default::default()
+        };
+        assert_eq!(ids(&expired_snapshots(&snaps, &policy)), vec!["old"]);
+    }
+
+    #[test]
+    fn test_safety_snapshots_expire_by_count_and_age() {
+        use crate::domain::entities::safety_snapshot::DestructiveOperation;
+
+        let now = Utc.with_ymd_and_hms(2026, 3, 11, 12, 0, 0).unwrap();
+        let safety = |id: &str, hours_ago: i64| {
+            let mut s = snap(id, now - chrono::Duration::hours(hours_ago));
+            s.tags = vec![SAFETY_TAG.into(), DestructiveOperation::ResizeVhd.tag()];
+            s
+        };
+        let mut pinned = safety("pinned", 100);
+        pinned.pinned = true;
+        let snaps = vec![
+            safety("s0", 0),
+            safety("s1", 1),
+            safety("s2", 30),
+            safety("s3", 40),
+            pinned,
+            snap("manual", now - chrono::Duration::hours(200)),
+        ];
+        let mut policy = SafetySnapshotPolicy {
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            operations: vec![DestructiveOperation::ResizeVhd],
+            format: ExportFormat::Tar,
+            output_dir: "/tmp".into(),
+            keep_last: 3,
+            max_age_hours: 24,
+            updated_at: now,
+        };
+        assert_eq!(
+            ids(&expired_safety_snapshots(&snaps, &policy, now)),
+            vec!["s2", "s3"]
+        );
+
+        policy.max_age_hours = 0;
+        assert_eq!(
+            ids(&expired_safety_snapshots(&snaps, &policy, now)),
+            vec!["s3"]
+        );
+        policy.keep_last = 0;
+        assert!(expired_safety_snapshots(&snaps, &policy, now).is_empty());
+    }
+}
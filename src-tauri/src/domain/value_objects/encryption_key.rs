@@ -0,0 +1,40 @@
+use std::fmt;
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Length of a snapshot encryption key, in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// Symmetric key of an encrypted snapshot archive, derived from the user's
+/// passphrase or keyfile. Wiped from memory when dropped and never printed.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_does_not_leak_key() {
+        let key = EncryptionKey::from_bytes([0xAB; KEY_LEN]);
+        let printed = format!("{:?}", key);
+        assert_eq!(printed, "EncryptionKey(<redacted>)");
+        assert!(!printed.to_lowercase().contains("ab"));
+    }
+}
@@ -1,11 +1,13 @@
 pub mod distro_name;
 pub mod distro_state;
+pub mod encryption_key;
 pub mod memory_size;
 pub mod snapshot_id;
 pub mod wsl_version;
 
 pub use distro_name::DistroName;
 pub use distro_state::DistroState;
+pub use encryption_key::EncryptionKey;
 pub use memory_size::MemorySize;
 pub use snapshot_id::SnapshotId;
 pub use wsl_version::WslVersion;
@@ -1,7 +1,10 @@
 pub mod audit;
 pub mod debug_log;
 pub mod monitoring;
+pub mod notifications;
+pub mod openmetrics;
 pub mod port_forwarding;
+pub mod s3;
 pub mod sqlite;
 pub mod terminal;
 pub mod wsl_cli;
@@ -2,8 +2,8 @@ use async_trait::async_trait;
 use std::sync::Arc;
 
 use crate::domain::entities::monitoring::{
-    CpuMetrics, DiskMetrics, GpuMetrics, MemoryMetrics, NetworkMetrics, ProcessInfo, SystemMetrics,
-    TcpConnectionMetrics,
+    CpuMetrics, DiskMetrics, GpuMetrics, MemoryMetrics, NetworkMetrics, ProcessInfo,
+    ServiceCgroupStats, SystemMetrics, TcpConnectionMetrics,
 };
 use crate::domain::errors::DomainError;
 use crate::domain::ports::monitoring_provider::MonitoringProviderPort;
@@ -110,12 +110,26 @@ pub fn parse_ps_aux(text: &str) -> Vec<ProcessInfo> {
                 rss_bytes: parts[5].parse::<u64>().unwrap_or(0) * 1024,
                 state: parts[7].to_string(),
                 command: parts[10..].join(" "),
+                cpu_time_secs: parse_cpu_time(parts[9]).unwrap_or(0),
             });
         }
     }
     processes
 }
 
+/// Parse a `ps` CPU time (`M:SS`, `HH:MM:SS` or `D-HH:MM:SS`) into seconds.
+pub fn parse_cpu_time(text: &str) -> Option<u64> {
+    let (days, clock) = match text.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, text),
+    };
+    let mut secs = 0u64;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 86_400 + secs)
+}
+
 /// Parse the "ctxt" line from /proc/stat to get total context switches.
 pub fn parse_context_switches(stat_text: &str) -> Option<u64> {
     for line in stat_text.lines() {
@@ -151,6 +165,50 @@ pub fn parse_diskstats(text: &str) -> (u64, u64) {
     (read_bytes, write_bytes)
 }
 
+/// Marks the start of a unit in the output of the cgroup listing.
+const UNIT_MARKER: &str = "__NEXUS_UNIT__ ";
+
+/// Parse the cgroup listing of the systemd units: a marker line with the unit
+/// name, then its `cpu.stat`, `memory.current` and `io.stat` files.
+pub fn parse_service_cgroups(text: &str) -> Vec<ServiceCgroupStats> {
+    let mut services: Vec<ServiceCgroupStats> = Vec::new();
+    for line in text.lines() {
+        if let Some(unit) = line.strip_prefix(UNIT_MARKER) {
+            services.push(ServiceCgroupStats {
+                unit: unit.trim().to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(service) = services.last_mut() else {
+            continue;
+        };
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["usage_usec", usec] => service.cpu_usage_usec = usec.parse().unwrap_or(0),
+            [bytes] => {
+                if let Ok(bytes) = bytes.parse() {
+                    service.memory_bytes = bytes;
+                }
+            }
+            // io.stat: "MAJ:MIN rbytes=N wbytes=N rios=N wios=N dbytes=N dios=N"
+            [_device, stats @ ..] => {
+                for stat in stats {
+                    let bytes = |value: &str| value.parse::<u64>().unwrap_or(0);
+                    if let Some(value) = stat.strip_prefix("rbytes=") {
+                        service.io_read_bytes = service.io_read_bytes.saturating_add(bytes(value));
+                    } else if let Some(value) = stat.strip_prefix("wbytes=") {
+                        service.io_write_bytes =
+                            service.io_write_bytes.saturating_add(bytes(value));
+                    }
+                }
+            }
+            [] => {}
+        }
+    }
+    services
+}
+
 /// Parse TCP connection state counts from awk output: "established time_wait listen".
 pub fn parse_tcp_connections(text: &str) -> TcpConnectionMetrics {
     let parts: Vec<&str> = text.split_whitespace().collect();
@@ -472,6 +530,29 @@ impl MonitoringProviderPort for ProcFsMonitoringAdapter {
             gpu: None, // GPU probe is separate
         })
     }
+
+    async fn get_service_stats(
+        &self,
+        distro: &DistroName,
+    ) -> Result<Vec<ServiceCgroupStats>, DomainError> {
+        // Services and scopes (e.g. Docker containers) are direct children of
+        // system.slice, which only exists when systemd manages the cgroups
+        let output = self
+            .wsl_manager
+            .exec_in_distro(
+                distro,
+                concat!(
+                    "cd /sys/fs/cgroup/system.slice 2>/dev/null || exit 0;",
+                    " for d in *.service *.scope; do",
+                    " [ -d \"$d\" ] || continue;",
+                    " echo \"__NEXUS_UNIT__ $d\";",
+                    " cat \"$d/cpu.stat\" \"$d/memory.current\" \"$d/io.stat\" 2>/dev/null;",
+                    " done",
+                ),
+            )
+            .await?;
+        Ok(parse_service_cgroups(&output))
+    }
 }
 
 #[cfg(test)]
@@ -617,6 +698,17 @@ Inter-|   Receive                                                |  Transmit
         assert_eq!(procs[0].command, "/usr/lib/systemd/systemd");
         assert_eq!(procs[1].pid, 100);
         assert_eq!(procs[1].command, "nginx: worker process");
+        assert_eq!(procs[0].cpu_time_secs, 30);
+        assert_eq!(procs[1].cpu_time_secs, 60);
+    }
+
+    #[test]
+    fn test_parse_cpu_time_formats() {
+        assert_eq!(parse_cpu_time("0:30"), Some(30));
+        assert_eq!(parse_cpu_time("125:10"), Some(7510));
+        assert_eq!(parse_cpu_time("01:02:03"), Some(3723));
+        assert_eq!(parse_cpu_time("2-01:00:00"), Some(176_400));
+        assert_eq!(parse_cpu_time("n/a"), None);
     }
 
     #[tokio::test]
@@ -633,6 +725,48 @@ Inter-|   Receive                                                |  Transmit
         assert!(procs.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_get_service_stats_parses_cgroups() {
+        use crate::domain::ports::wsl_manager::MockWslManagerPort;
+
+        let output = "__NEXUS_UNIT__ docker.service\n\
+            usage_usec 5000000\nuser_usec 4000000\nsystem_usec 1000000\nnr_periods 0\n\
+            734003200\n\
+            8:16 rbytes=1048576 wbytes=2048 rios=10 wios=1 dbytes=0 dios=0\n\
+            8:32 rbytes=1024 wbytes=0 rios=1 wios=0 dbytes=0 dios=0\n\
+            __NEXUS_UNIT__ redis-server.service\n\
+            usage_usec 120\n\
+            4096\n";
+        let mut mock = MockWslManagerPort::new();
+        mock.expect_exec_in_distro()
+            .withf(|_, cmd| cmd.contains("/sys/fs/cgroup/system.slice"))
+            .returning(move |_, _| Ok(output.to_string()));
+
+        let adapter = ProcFsMonitoringAdapter::new(Arc::new(mock));
+        let name = DistroName::new("Ubuntu").unwrap();
+        let services = adapter.get_service_stats(&name).await.unwrap();
+
+        assert_eq!(
+            services,
+            vec![
+                ServiceCgroupStats {
+                    unit: "docker.service".to_string(),
+                    cpu_usage_usec: 5_000_000,
+                    memory_bytes: 734_003_200,
+                    io_read_bytes: 1_049_600,
+                    io_write_bytes: 2048,
+                },
+                ServiceCgroupStats {
+                    unit: "redis-server.service".to_string(),
+                    cpu_usage_usec: 120,
+                    memory_bytes: 4096,
+                    io_read_bytes: 0,
+                    io_write_bytes: 0,
+                },
+            ]
+        );
+    }
+
     mod proptests {
         use super::*;
         use proptest::prelude::*;
@@ -683,6 +817,11 @@ Inter-|   Receive                                                |  Transmit
             fn parse_ps_aux_never_panics(s in "\\PC{0,500}") {
                 let _ = parse_ps_aux(&s);
             }
+
+            #[test]
+            fn parse_service_cgroups_never_panics(s in "\\PC{0,500}") {
+                let _ = parse_service_cgroups(&s);
+            }
         }
     }
 }
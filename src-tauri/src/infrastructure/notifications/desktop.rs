@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::domain::entities::notification_channel::NotificationChannel;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::notification_channel::{AlertNotification, NotificationChannelPort};
+
+/// Desktop notifications through the notification plugin.
+pub struct DesktopNotifier {
+    app_handle: AppHandle,
+}
+
+impl DesktopNotifier {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+#[async_trait]
+impl NotificationChannelPort for DesktopNotifier {
+    async fn send(
+        &self,
+        _channel: &NotificationChannel,
+        notification: &AlertNotification,
+    ) -> Result<(), DomainError> {
+        self.app_handle
+            .notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show()
+            .map_err(|e| DomainError::NotificationError(e.to_string()))
+    }
+}
@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+
+use crate::domain::entities::notification_channel::{
+    ChannelConfig, NotificationChannel, SmtpSecurity,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::notification_channel::{AlertNotification, NotificationChannelPort};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Plain-text alert emails over SMTP (STARTTLS, implicit TLS or, for local
+/// relays, unencrypted).
+#[derive(Default)]
+pub struct EmailNotifier;
+
+impl EmailNotifier {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn notification_err(e: impl std::fmt::Display) -> DomainError {
+    DomainError::NotificationError(e.to_string())
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, DomainError> {
+    address
+        .parse()
+        .map_err(|e| DomainError::ConfigError(format!("Invalid email address '{address}': {e}")))
+}
+
+#[async_trait]
+impl NotificationChannelPort for EmailNotifier {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notification: &AlertNotification,
+    ) -> Result<(), DomainError> {
+        let ChannelConfig::Email {
+            smtp_host,
+            smtp_port,
+            security,
+            username,
+            password,
+            from,
+            to,
+        } = &channel.config
+        else {
+            return Err(DomainError::NotificationError(format!(
+                "'{}' is not an email channel",
+                channel.name
+            )));
+        };
+
+        let mut message = Message::builder()
+            .from(mailbox(from)?)
+            .subject(&notification.title)
+            .header(ContentType::TEXT_PLAIN);
+        for recipient in to {
+            message = message.to(mailbox(recipient)?);
+        }
+        let message = message
+            .body(format!("{}\n", notification.body))
+            .map_err(notification_err)?;
+
+        let mut transport = match security {
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(smtp_host)
+                    .map_err(notification_err)?
+            }
+            SmtpSecurity::Tls => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(smtp_host).map_err(notification_err)?
+            }
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(smtp_host)
+            }
+        }
+        .port(*smtp_port)
+        .timeout(Some(SMTP_TIMEOUT));
+        if let Some(username) = username {
+            transport = transport.credentials(Credentials::new(
+                username.clone(),
+                password.clone().unwrap_or_default(),
+            ));
+        }
+
+        transport
+            .build()
+            .send(message)
+            .await
+            .map_err(|e| DomainError::NotificationError(format!("SMTP delivery failed: {e}")))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::alerting::{AlertType, AlertUnit};
+    use crate::infrastructure::notifications::test_server::FakeSmtp;
+
+    fn channel(port: u16, to: &[&str]) -> NotificationChannel {
+        NotificationChannel {
+            id: "mail".into(),
+            name: "Ops mail".into(),
+            enabled: true,
+            config: ChannelConfig::Email {
+                smtp_host: "127.0.0.1".into(),
+                smtp_port: port,
+                security: SmtpSecurity::None,
+                username: None,
+                password: None,
+                from: "WSL Nexus <alerts@example.com>".into(),
+                to: to.iter().map(|s| s.to_string()).collect(),
+            },
+        }
+    }
+
+    fn notification() -> AlertNotification {
+        AlertNotification {
+            alert_id: Some(9),
+            distro_name: "Debian".into(),
+            alert_type: AlertType::Disk,
+            unit: AlertUnit::Percent,
+            threshold: 90.0,
+            actual_value: Some(96.5),
+            resolved: false,
+            title: "Disk Alert - Debian".into(),
+            body: "Disk at 96.5% (threshold: 90.0%)".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_the_alert_to_every_recipient() {
+        let server = FakeSmtp::start();
+        EmailNotifier::new()
+            .send(
+                &channel(server.port(), &["ops@example.com", "oncall@example.com"]),
+                &notification(),
+            )
+            .await
+            .unwrap();
+
+        let mails = server.mails();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(mails[0].from, "alerts@example.com");
+        assert_eq!(mails[0].to, vec!["ops@example.com", "oncall@example.com"]);
+        assert!(mails[0].data.contains("Subject: Disk Alert - Debian"));
+        assert!(mails[0].data.contains("Disk at 96.5% (threshold: 90.0%)"));
+    }
+
+    #[tokio::test]
+    async fn invalid_recipient_is_a_config_error() {
+        let err = EmailNotifier::new()
+            .send(&channel(1, &["not an address"]), &notification())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DomainError::ConfigError(_)));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod desktop;
+pub mod email;
+pub mod webhook;
+
+#[cfg(test)]
+pub(crate) mod test_server;
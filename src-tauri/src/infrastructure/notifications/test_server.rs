@@ -0,0 +1,187 @@
+//! In-process stand-ins for a webhook receiver and an SMTP relay, enough to
+//! exercise the notifiers end to end.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+/// HTTP endpoint recording the body of every request. Answers with the
+/// queued statuses first, then 200.
+pub(crate) struct FakeWebhook {
+    port: u16,
+    bodies: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl FakeWebhook {
+    pub(crate) fn start(statuses: Vec<u16>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let statuses = Arc::new(Mutex::new(VecDeque::from(statuses)));
+        let shared = bodies.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let bodies = shared.clone();
+                let statuses = statuses.clone();
+                std::thread::spawn(move || serve_http(stream, bodies, statuses));
+            }
+        });
+        Self { port, bodies }
+    }
+
+    pub(crate) fn url(&self) -> String {
+        format!("http://127.0.0.1:{}/hook", self.port)
+    }
+
+    pub(crate) fn bodies(&self) -> Vec<Vec<u8>> {
+        self.bodies.lock().unwrap().clone()
+    }
+}
+
+fn serve_http(
+    stream: TcpStream,
+    bodies: Arc<Mutex<Vec<Vec<u8>>>>,
+    statuses: Arc<Mutex<VecDeque<u16>>>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).unwrap_or(0) == 0 {
+                return;
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+
+        let status = statuses.lock().unwrap().pop_front().unwrap_or(200);
+        if status == 200 {
+            bodies.lock().unwrap().push(body);
+        }
+        let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n", status);
+        if writer.write_all(response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+/// A mail accepted by [`FakeSmtp`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReceivedMail {
+    pub(crate) from: String,
+    pub(crate) to: Vec<String>,
+    /// Headers and body, as sent after `DATA`.
+    pub(crate) data: String,
+}
+
+/// Unencrypted SMTP relay without authentication that accepts every mail.
+pub(crate) struct FakeSmtp {
+    port: u16,
+    mails: Arc<Mutex<Vec<ReceivedMail>>>,
+}
+
+impl FakeSmtp {
+    pub(crate) fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mails = Arc::new(Mutex::new(Vec::new()));
+        let shared = mails.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mails = shared.clone();
+                std::thread::spawn(move || serve_smtp(stream, mails));
+            }
+        });
+        Self { port, mails }
+    }
+
+    pub(crate) fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub(crate) fn mails(&self) -> Vec<ReceivedMail> {
+        self.mails.lock().unwrap().clone()
+    }
+}
+
+/// Address inside the `<...>` of a `MAIL FROM:` / `RCPT TO:` command.
+fn angle_address(line: &str) -> String {
+    line.split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(address, _)| address.to_string())
+        .unwrap_or_default()
+}
+
+fn reply(writer: &mut TcpStream, text: &str) -> bool {
+    writer.write_all(format!("{text}\r\n").as_bytes()).is_ok()
+}
+
+fn serve_smtp(stream: TcpStream, mails: Arc<Mutex<Vec<ReceivedMail>>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    if !reply(&mut writer, "220 localhost ESMTP") {
+        return;
+    }
+    let mut mail = ReceivedMail::default();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let command = line.trim_end().to_ascii_uppercase();
+        let ok = if command.starts_with("EHLO") || command.starts_with("HELO") {
+            reply(&mut writer, "250 localhost")
+        } else if command.starts_with("MAIL FROM") {
+            mail = ReceivedMail {
+                from: angle_address(&line),
+                ..Default::default()
+            };
+            reply(&mut writer, "250 OK")
+        } else if command.starts_with("RCPT TO") {
+            mail.to.push(angle_address(&line));
+            reply(&mut writer, "250 OK")
+        } else if command == "DATA" {
+            if !reply(&mut writer, "354 End data with <CR><LF>.<CR><LF>") {
+                return;
+            }
+            loop {
+                let mut data = String::new();
+                if reader.read_line(&mut data).unwrap_or(0) == 0 {
+                    return;
+                }
+                if data == ".\r\n" {
+                    break;
+                }
+                mail.data.push_str(&data);
+            }
+            mails.lock().unwrap().push(std::mem::take(&mut mail));
+            reply(&mut writer, "250 OK: queued")
+        } else if command == "QUIT" {
+            let _ = reply(&mut writer, "221 Bye");
+            return;
+        } else {
+            reply(&mut writer, "250 OK")
+        };
+        if !ok {
+            return;
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::domain::entities::notification_channel::{ChannelConfig, NotificationChannel};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::notification_channel::{AlertNotification, NotificationChannelPort};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Generic HTTP webhook: `POST`s a JSON body whose `text` field is what Slack
+/// and Teams incoming webhooks display, plus the structured alert for other
+/// receivers.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+}
+
+impl Default for WebhookNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebhookNotifier {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn payload(notification: &AlertNotification) -> serde_json::Value {
+    json!({
+        "text": format!("{}\n{}", notification.title, notification.body),
+        "title": notification.title,
+        "alert": notification,
+    })
+}
+
+#[async_trait]
+impl NotificationChannelPort for WebhookNotifier {
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        notification: &AlertNotification,
+    ) -> Result<(), DomainError> {
+        let ChannelConfig::Webhook { url } = &channel.config else {
+            return Err(DomainError::NotificationError(format!(
+                "'{}' is not a webhook channel",
+                channel.name
+            )));
+        };
+        let response = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload(notification).to_string())
+            .send()
+            .await
+            .map_err(|e| DomainError::NotificationError(format!("Webhook request failed: {e}")))?;
+        if !response.status().is_success() {
+            return Err(DomainError::NotificationError(format!(
+                "Webhook answered {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ports::alerting::{AlertType, AlertUnit};
+    use crate::infrastructure::notifications::test_server::FakeWebhook;
+
+    fn notification() -> AlertNotification {
+        AlertNotification {
+            alert_id: Some(5),
+            distro_name: "Ubuntu".into(),
+            alert_type: AlertType::Memory,
+            unit: AlertUnit::Percent,
+            threshold: 85.0,
+            actual_value: Some(91.0),
+            resolved: false,
+            title: "Memory Alert - Ubuntu".into(),
+            body: "Memory at 91.0% (threshold: 85.0%)".into(),
+        }
+    }
+
+    fn channel(url: String) -> NotificationChannel {
+        NotificationChannel {
+            id: "hook".into(),
+            name: "Slack".into(),
+            enabled: true,
+            config: ChannelConfig::Webhook { url },
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_slack_compatible_json() {
+        let server = FakeWebhook::start(vec![]);
+        WebhookNotifier::new()
+            .send(&channel(server.url()), &notification())
+            .await
+            .unwrap();
+
+        let bodies = server.bodies();
+        assert_eq!(bodies.len(), 1);
+        let body: serde_json::Value = serde_json::from_slice(&bodies[0]).unwrap();
+        assert_eq!(
+            body["text"],
+            "Memory Alert - Ubuntu\nMemory at 91.0% (threshold: 85.0%)"
+        );
+        assert_eq!(body["alert"]["alert_type"], "memory");
+        assert_eq!(body["alert"]["alert_id"], 5);
+    }
+
+    #[tokio::test]
+    async fn error_status_fails_the_send() {
+        let server = FakeWebhook::start(vec![503]);
+        let err = WebhookNotifier::new()
+            .send(&channel(server.url()), &notification())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("503"));
+    }
+}
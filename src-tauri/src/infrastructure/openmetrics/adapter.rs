@@ -0,0 +1,264 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use super::exposition;
+use crate::domain::entities::metrics_settings::MetricsSettings;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::metrics_exporter::MetricsExporterPort;
+use crate::domain::services::metrics_collector::LatestMetrics;
+
+/// Scrapers send a short request line and a few headers.
+const MAX_REQUEST_HEAD_BYTES: u64 = 8 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Minimal HTTP/1.1 server answering `GET /metrics` on the loopback
+/// interface with the latest metrics kept by the collector, so a scrape
+/// never runs `wsl.exe`.
+pub struct OpenMetricsExporter {
+    latest: Arc<LatestMetrics>,
+    endpoint: tokio::sync::Mutex<Option<Endpoint>>,
+}
+
+/// The accept loop of the listening port, stopped when dropped.
+struct Endpoint {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl OpenMetricsExporter {
+    pub fn new(latest: Arc<LatestMetrics>) -> Self {
+        Self {
+            latest,
+            endpoint: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl MetricsExporterPort for OpenMetricsExporter {
+    async fn apply(&self, settings: &MetricsSettings) -> Result<(), DomainError> {
+        let mut endpoint = self.endpoint.lock().await;
+        let wanted = settings
+            .openmetrics_enabled
+            .then_some(settings.openmetrics_port);
+        if endpoint.as_ref().map(|e| e.port) == wanted {
+            return Ok(());
+        }
+        let Some(port) = wanted else {
+            *endpoint = None;
+            tracing::info!("OpenMetrics endpoint stopped");
+            return Ok(());
+        };
+
+        // Bind before stopping the current endpoint, which keeps serving if
+        // the new port is taken
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .await
+            .map_err(|e| {
+                DomainError::ConfigError(format!(
+                    "Cannot serve OpenMetrics on 127.0.0.1:{port}: {e}"
+                ))
+            })?;
+        let latest = Arc::clone(&self.latest);
+        *endpoint = Some(Endpoint {
+            port,
+            task: tokio::spawn(accept_loop(listener, latest)),
+        });
+        tracing::info!("OpenMetrics endpoint listening on http://127.0.0.1:{port}/metrics");
+        Ok(())
+    }
+}
+
+async fn accept_loop(listener: TcpListener, latest: Arc<LatestMetrics>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let latest = Arc::clone(&latest);
+                tokio::spawn(async move {
+                    if tokio::time::timeout(REQUEST_TIMEOUT, respond(stream, &latest))
+                        .await
+                        .is_err()
+                    {
+                        tracing::debug!("OpenMetrics request timed out");
+                    }
+                });
+            }
+            Err(e) => {
+                tracing::debug!("OpenMetrics accept failed: {e}");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+/// Answer one request, then close the connection.
+async fn respond(stream: TcpStream, latest: &LatestMetrics) {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader.take(MAX_REQUEST_HEAD_BYTES));
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+        return;
+    }
+    // Drain the headers; none of them change the response
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) if header.trim_end().is_empty() => break,
+            Ok(_) => {}
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+    let response = match (method, path) {
+        ("GET" | "HEAD", "/metrics") => {
+            let body = exposition::render(&latest.snapshot().await);
+            http_response("200 OK", exposition::CONTENT_TYPE, &body, method == "HEAD")
+        }
+        (_, "/metrics") => http_response(
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Only GET and HEAD are supported\n",
+            false,
+        ),
+        _ => http_response(
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Metrics are served at /metrics\n",
+            false,
+        ),
+    };
+    let _ = writer.write_all(response.as_bytes()).await;
+    let _ = writer.shutdown().await;
+}
+
+fn http_response(status: &str, content_type: &str, body: &str, head_only: bool) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        if head_only { "" } else { body }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::monitoring::{
+        CpuMetrics, DiskMetrics, MemoryMetrics, NetworkMetrics, SystemMetrics,
+    };
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn settings(enabled: bool, port: u16) -> MetricsSettings {
+        MetricsSettings {
+            openmetrics_enabled: enabled,
+            openmetrics_port: port,
+            ..Default::default()
+        }
+    }
+
+    fn make_metrics(distro_name: &str) -> SystemMetrics {
+        SystemMetrics {
+            distro_name: distro_name.to_string(),
+            timestamp: chrono::Utc::now(),
+            cpu: CpuMetrics {
+                usage_percent: 50.0,
+                per_core: vec![],
+                load_average: [0.0, 0.0, 0.0],
+            },
+            memory: MemoryMetrics {
+                total_bytes: 0,
+                used_bytes: 0,
+                available_bytes: 0,
+                cached_bytes: 0,
+                swap_total_bytes: 0,
+                swap_used_bytes: 0,
+            },
+            disk: DiskMetrics {
+                total_bytes: 0,
+                used_bytes: 0,
+                available_bytes: 0,
+                usage_percent: 0.0,
+            },
+            network: NetworkMetrics { interfaces: vec![] },
+            context_switches: None,
+            disk_io: None,
+            tcp_connections: None,
+            gpu: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_the_latest_metrics_until_disabled() {
+        let latest = Arc::new(LatestMetrics::default());
+        latest.record(&make_metrics("Ubuntu")).await;
+        let exporter = OpenMetricsExporter::new(Arc::clone(&latest));
+        let port = free_port();
+        exporter.apply(&settings(true, port)).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let url = format!("http://127.0.0.1:{port}/metrics");
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()[reqwest::header::CONTENT_TYPE],
+            exposition::CONTENT_TYPE
+        );
+        let body = response.text().await.unwrap();
+        assert!(body.contains("wsl_cpu_usage_ratio{distro=\"Ubuntu\"} 0.5\n"));
+        assert!(body.ends_with("# EOF\n"));
+
+        let other = client
+            .get(format!("http://127.0.0.1:{port}/"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(other.status(), 404);
+        let post = client.post(&url).send().await.unwrap();
+        assert_eq!(post.status(), 405);
+
+        exporter.apply(&settings(false, port)).await.unwrap();
+        assert!(client.get(&url).send().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn taken_port_keeps_the_current_endpoint() {
+        let exporter = OpenMetricsExporter::new(Arc::new(LatestMetrics::default()));
+        let port = free_port();
+        exporter.apply(&settings(true, port)).await.unwrap();
+
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let taken_port = taken.local_addr().unwrap().port();
+        let err = exporter
+            .apply(&settings(true, taken_port))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(&taken_port.to_string()));
+
+        let response = reqwest::get(format!("http://127.0.0.1:{port}/metrics"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+}
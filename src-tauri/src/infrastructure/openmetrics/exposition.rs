@@ -0,0 +1,438 @@
+//! Rendering of [`SystemMetrics`] in the OpenMetrics text format.
+//!
+//! Names follow the Prometheus conventions: a `wsl_` prefix, base units in
+//! the name (`_bytes`, `_seconds`, `_ratio` from 0 to 1) and `_total` on
+//! counters. Every sample carries a `distro` label.
+
+use std::fmt::Write;
+
+use crate::domain::entities::monitoring::{InterfaceStats, SystemMetrics};
+
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Reads one value of a distro, or of one of its network interfaces.
+type DistroValue = fn(&SystemMetrics) -> u64;
+type InterfaceValue = fn(&InterfaceStats) -> u64;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Gauge,
+    Counter,
+}
+
+/// One metric family: its metadata, then one sample per label set.
+struct Family<'a> {
+    name: &'a str,
+    kind: Kind,
+    unit: Option<&'a str>,
+    help: &'a str,
+}
+
+impl Family<'_> {
+    fn gauge<'a>(name: &'a str, unit: Option<&'a str>, help: &'a str) -> Family<'a> {
+        Family {
+            name,
+            kind: Kind::Gauge,
+            unit,
+            help,
+        }
+    }
+
+    fn counter<'a>(name: &'a str, unit: Option<&'a str>, help: &'a str) -> Family<'a> {
+        Family {
+            name,
+            kind: Kind::Counter,
+            unit,
+            help,
+        }
+    }
+
+    /// Families without samples (e.g. no distro has a GPU) are left out.
+    fn write(&self, out: &mut String, samples: &[(String, String)]) {
+        if samples.is_empty() {
+            return;
+        }
+        let (kind, suffix) = match self.kind {
+            Kind::Gauge => ("gauge", ""),
+            Kind::Counter => ("counter", "_total"),
+        };
+        let _ = writeln!(out, "# TYPE {} {kind}", self.name);
+        if let Some(unit) = self.unit {
+            let _ = writeln!(out, "# UNIT {} {unit}", self.name);
+        }
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{}{suffix}{{{labels}}} {value}", self.name);
+        }
+    }
+}
+
+/// `name="value"` pairs with the value escaped as the format requires.
+fn labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{escaped}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render the latest metrics of each distro, ending with the mandatory
+/// `# EOF` line.
+pub fn render(metrics: &[SystemMetrics]) -> String {
+    let mut out = String::new();
+    // One sample per distro, for the distros that have the value
+    let per_distro = |value: &dyn Fn(&SystemMetrics) -> Option<String>| {
+        metrics
+            .iter()
+            .filter_map(|m| Some((labels(&[("distro", &m.distro_name)]), value(m)?)))
+            .collect::<Vec<_>>()
+    };
+
+    Family::gauge(
+        "wsl_collection_timestamp_seconds",
+        Some("seconds"),
+        "When the metrics of the distro were last collected.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(float(m.timestamp.timestamp_millis() as f64 / 1000.0))),
+    );
+
+    Family::gauge(
+        "wsl_cpu_usage_ratio",
+        Some("ratio"),
+        "CPU usage of the distro across all cores.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(float(m.cpu.usage_percent / 100.0))),
+    );
+
+    let cores: Vec<(String, String)> = metrics
+        .iter()
+        .flat_map(|m| {
+            m.cpu.per_core.iter().enumerate().map(|(core, usage)| {
+                (
+                    labels(&[("distro", &m.distro_name), ("core", &core.to_string())]),
+                    float(usage / 100.0),
+                )
+            })
+        })
+        .collect();
+    Family::gauge(
+        "wsl_cpu_core_usage_ratio",
+        Some("ratio"),
+        "CPU usage of each core.",
+    )
+    .write(&mut out, &cores);
+
+    for (index, (name, help)) in [
+        ("wsl_load1", "1-minute load average."),
+        ("wsl_load5", "5-minute load average."),
+        ("wsl_load15", "15-minute load average."),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        Family::gauge(name, None, help).write(
+            &mut out,
+            &per_distro(&|m| Some(float(m.cpu.load_average[index]))),
+        );
+    }
+
+    let bytes: [(&str, &str, DistroValue); 9] = [
+        ("wsl_memory_total_bytes", "Total memory.", |m| {
+            m.memory.total_bytes
+        }),
+        ("wsl_memory_used_bytes", "Memory in use.", |m| {
+            m.memory.used_bytes
+        }),
+        (
+            "wsl_memory_available_bytes",
+            "Memory available for new allocations.",
+            |m| m.memory.available_bytes,
+        ),
+        ("wsl_memory_cached_bytes", "Page cache.", |m| {
+            m.memory.cached_bytes
+        }),
+        ("wsl_swap_total_bytes", "Total swap.", |m| {
+            m.memory.swap_total_bytes
+        }),
+        ("wsl_swap_used_bytes", "Swap in use.", |m| {
+            m.memory.swap_used_bytes
+        }),
+        (
+            "wsl_filesystem_size_bytes",
+            "Size of the root filesystem.",
+            |m| m.disk.total_bytes,
+        ),
+        (
+            "wsl_filesystem_used_bytes",
+            "Space used on the root filesystem.",
+            |m| m.disk.used_bytes,
+        ),
+        (
+            "wsl_filesystem_available_bytes",
+            "Space available on the root filesystem.",
+            |m| m.disk.available_bytes,
+        ),
+    ];
+    for (name, help, value) in bytes {
+        Family::gauge(name, Some("bytes"), help)
+            .write(&mut out, &per_distro(&|m| Some(value(m).to_string())));
+    }
+
+    Family::gauge(
+        "wsl_disk_read_bytes_per_second",
+        None,
+        "Disk read rate over the last collection.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(m.disk_io.as_ref()?.read_bytes_per_sec.to_string())),
+    );
+    Family::gauge(
+        "wsl_disk_written_bytes_per_second",
+        None,
+        "Disk write rate over the last collection.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(m.disk_io.as_ref()?.write_bytes_per_sec.to_string())),
+    );
+
+    let interface_counters: [(&str, Option<&str>, &str, InterfaceValue); 4] = [
+        (
+            "wsl_network_receive_bytes",
+            Some("bytes"),
+            "Bytes received by the interface.",
+            |i| i.rx_bytes,
+        ),
+        (
+            "wsl_network_transmit_bytes",
+            Some("bytes"),
+            "Bytes sent by the interface.",
+            |i| i.tx_bytes,
+        ),
+        (
+            "wsl_network_receive_packets",
+            None,
+            "Packets received by the interface.",
+            |i| i.rx_packets,
+        ),
+        (
+            "wsl_network_transmit_packets",
+            None,
+            "Packets sent by the interface.",
+            |i| i.tx_packets,
+        ),
+    ];
+    for (name, unit, help, value) in interface_counters {
+        let samples: Vec<(String, String)> = metrics
+            .iter()
+            .flat_map(|m| {
+                m.network.interfaces.iter().map(|interface| {
+                    (
+                        labels(&[("distro", &m.distro_name), ("interface", &interface.name)]),
+                        value(interface).to_string(),
+                    )
+                })
+            })
+            .collect();
+        Family::counter(name, unit, help).write(&mut out, &samples);
+    }
+
+    Family::counter(
+        "wsl_context_switches",
+        None,
+        "Context switches since the distro booted.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(m.context_switches?.to_string())),
+    );
+
+    let connections: Vec<(String, String)> = metrics
+        .iter()
+        .filter_map(|m| Some((m, m.tcp_connections.as_ref()?)))
+        .flat_map(|(m, tcp)| {
+            [
+                ("established", tcp.established),
+                ("time_wait", tcp.time_wait),
+                ("listen", tcp.listen),
+            ]
+            .map(|(state, count)| {
+                (
+                    labels(&[("distro", &m.distro_name), ("state", state)]),
+                    count.to_string(),
+                )
+            })
+        })
+        .collect();
+    Family::gauge("wsl_tcp_connections", None, "TCP sockets by state.")
+        .write(&mut out, &connections);
+
+    Family::gauge(
+        "wsl_gpu_utilization_ratio",
+        Some("ratio"),
+        "GPU utilization.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(float(m.gpu.as_ref()?.utilization_percent? / 100.0))),
+    );
+    Family::gauge(
+        "wsl_gpu_memory_used_bytes",
+        Some("bytes"),
+        "Video memory in use.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(m.gpu.as_ref()?.vram_used_bytes?.to_string())),
+    );
+    Family::gauge(
+        "wsl_gpu_memory_total_bytes",
+        Some("bytes"),
+        "Total video memory.",
+    )
+    .write(
+        &mut out,
+        &per_distro(&|m| Some(m.gpu.as_ref()?.vram_total_bytes?.to_string())),
+    );
+
+    out.push_str("# EOF\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::monitoring::{
+        CpuMetrics, DiskMetrics, MemoryMetrics, NetworkMetrics, TcpConnectionMetrics,
+    };
+
+    fn make_metrics(distro_name: &str) -> SystemMetrics {
+        SystemMetrics {
+            distro_name: distro_name.to_string(),
+            timestamp: "2026-10-18T12:00:00.500Z".parse().unwrap(),
+            cpu: CpuMetrics {
+                usage_percent: 25.0,
+                per_core: vec![10.0, 40.0],
+                load_average: [0.5, 0.25, 0.125],
+            },
+            memory: MemoryMetrics {
+                total_bytes: 8_000_000_000,
+                used_bytes: 2_000_000_000,
+                available_bytes: 6_000_000_000,
+                cached_bytes: 0,
+                swap_total_bytes: 0,
+                swap_used_bytes: 0,
+            },
+            disk: DiskMetrics {
+                total_bytes: 100,
+                used_bytes: 40,
+                available_bytes: 60,
+                usage_percent: 40.0,
+            },
+            network: NetworkMetrics {
+                interfaces: vec![
+                    InterfaceStats {
+                        name: "eth0".to_string(),
+                        rx_bytes: 1_000,
+                        tx_bytes: 2_000,
+                        rx_packets: 10,
+                        tx_packets: 20,
+                    },
+                    InterfaceStats {
+                        name: "lo".to_string(),
+                        rx_bytes: 5,
+                        tx_bytes: 5,
+                        rx_packets: 1,
+                        tx_packets: 1,
+                    },
+                ],
+            },
+            context_switches: Some(12_345),
+            disk_io: None,
+            tcp_connections: Some(TcpConnectionMetrics {
+                established: 3,
+                time_wait: 1,
+                listen: 2,
+            }),
+            gpu: None,
+        }
+    }
+
+    #[test]
+    fn renders_families_with_distro_labels() {
+        let text = render(&[make_metrics("Debian"), make_metrics("Ubuntu")]);
+
+        assert!(text.ends_with("# EOF\n"));
+        assert!(text.contains("# TYPE wsl_cpu_usage_ratio gauge\n"));
+        assert!(text.contains("wsl_cpu_usage_ratio{distro=\"Ubuntu\"} 0.25\n"));
+        assert!(text.contains("wsl_cpu_core_usage_ratio{distro=\"Debian\",core=\"1\"} 0.4\n"));
+        assert!(
+            text.contains("wsl_collection_timestamp_seconds{distro=\"Debian\"} 1792324800.5\n")
+        );
+        assert!(text.contains("# UNIT wsl_memory_used_bytes bytes\n"));
+        assert!(text.contains("wsl_memory_used_bytes{distro=\"Debian\"} 2000000000\n"));
+        assert!(text.contains("wsl_tcp_connections{distro=\"Ubuntu\",state=\"time_wait\"} 1\n"));
+
+        // Counters are declared without, and sampled with, the _total suffix
+        assert!(text.contains("# TYPE wsl_network_receive_bytes counter\n"));
+        assert!(text.contains(
+            "wsl_network_receive_bytes_total{distro=\"Ubuntu\",interface=\"eth0\"} 1000\n"
+        ));
+        assert!(text.contains(
+            "wsl_network_transmit_packets_total{distro=\"Debian\",interface=\"lo\"} 1\n"
+        ));
+        assert!(text.contains("wsl_context_switches_total{distro=\"Debian\"} 12345\n"));
+
+        // Values no distro has are left out rather than reported as zero
+        assert!(!text.contains("wsl_gpu_"));
+        assert!(!text.contains("wsl_disk_read_bytes_per_second"));
+    }
+
+    #[test]
+    fn samples_of_a_family_are_grouped() {
+        let text = render(&[make_metrics("Debian"), make_metrics("Ubuntu")]);
+        let lines: Vec<&str> = text.lines().collect();
+        let start = lines
+            .iter()
+            .position(|l| *l == "# TYPE wsl_load1 gauge")
+            .unwrap();
+        assert_eq!(
+            &lines[start + 2..start + 4],
+            [
+                "wsl_load1{distro=\"Debian\"} 0.5",
+                "wsl_load1{distro=\"Ubuntu\"} 0.5"
+            ]
+        );
+        assert_eq!(lines[start + 4], "# TYPE wsl_load5 gauge");
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(
+            labels(&[("distro", "a\"b\\c\nd")]),
+            "distro=\"a\\\"b\\\\c\\nd\""
+        );
+        assert_eq!(render(&[]), "# EOF\n");
+    }
+}
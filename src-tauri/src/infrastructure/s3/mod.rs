@@ -0,0 +1,5 @@
+pub mod adapter;
+mod sigv4;
+
+#[cfg(test)]
+pub(crate) mod test_server;
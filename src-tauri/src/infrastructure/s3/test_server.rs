@@ -0,0 +1,388 @@
+//! In-process stand-in for MinIO: enough of the S3 REST API (path-style,
+//! SigV4-checked) to exercise [`super::adapter::S3ObjectStore`] end to end.
+
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use super::sigv4::{self, CanonicalRequest, Credentials};
+
+const BUCKET: &str = "snapshots";
+const ACCESS_KEY: &str = "minioadmin";
+const SECRET_KEY: &str = "minioadmin";
+/// Tiny on purpose so clients have to follow `NextPartNumberMarker`.
+const LIST_PARTS_PAGE: usize = 2;
+
+/// Key of an unfinished upload and its parts: number -> (etag, data).
+type Upload = (String, BTreeMap<u32, (String, Vec<u8>)>);
+
+#[derive(Default)]
+struct Bucket {
+    objects: HashMap<String, Vec<u8>>,
+    uploads: HashMap<String, Upload>,
+    next_upload: u32,
+}
+
+pub(crate) struct FakeS3 {
+    port: u16,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// `Content-Length` without a body (HEAD).
+    head_length: Option<usize>,
+}
+
+impl Response {
+    fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            head_length: None,
+        }
+    }
+
+    fn error(status: u16, code: &str) -> Self {
+        Self::new(
+            status,
+            format!(
+                "<Error><Code>{}</Code><Message>{}</Message></Error>",
+                code, code
+            ),
+        )
+    }
+}
+
+impl FakeS3 {
+    /// Listen on an ephemeral localhost port, bucket `snapshots`, credentials
+    /// `minioadmin`/`minioadmin`.
+    pub(crate) fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let bucket = Arc::new(Mutex::new(Bucket::default()));
+        let shared = bucket.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let bucket = shared.clone();
+                std::thread::spawn(move || serve(stream, port, bucket));
+            }
+        });
+        Self { port, bucket }
+    }
+
+    pub(crate) fn endpoint(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    pub(crate) fn object(&self, key: &str) -> Option<Vec<u8>> {
+        self.bucket.lock().unwrap().objects.get(key).cloned()
+    }
+}
+
+fn serve(stream: TcpStream, port: u16, bucket: Arc<Mutex<Bucket>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    while let Some(request) = read_request(&mut reader) {
+        let response = if verify_signature(&request, port) {
+            handle(&request, &mut bucket.lock().unwrap())
+        } else {
+            Response::error(403, "SignatureDoesNotMatch")
+        };
+
+        let mut head = format!("HTTP/1.1 {} X\r\n", response.status);
+        let length = response.head_length.unwrap_or(response.body.len());
+        head.push_str(&format!("Content-Length: {}\r\n", length));
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        if writer.write_all(head.as_bytes()).is_err() || writer.write_all(&response.body).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).ok()? == 0 {
+        return None;
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?.to_string();
+    let (path, query) = match target.split_once('?') {
+        Some((p, q)) => (p.to_string(), q.to_string()),
+        None => (target, String::new()),
+    };
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    })
+}
+
+/// Recompute the signature from the request as received.
+fn verify_signature(request: &Request, port: u16) -> bool {
+    let Some(authorization) = request.headers.get("authorization") else {
+        return false;
+    };
+    let field = |name: &str| {
+        authorization
+            .split([' ', ','])
+            .find_map(|f| f.strip_prefix(name))
+            .unwrap_or_default()
+    };
+    let (signed_headers, signature) = (field("SignedHeaders="), field("Signature="));
+    if !field("Credential=").starts_with(ACCESS_KEY) {
+        return false;
+    }
+    let payload_hash = request
+        .headers
+        .get("x-amz-content-sha256")
+        .cloned()
+        .unwrap_or_default();
+    if payload_hash != sigv4::sha256_hex(&request.body) {
+        return false;
+    }
+    let Some(now) = request
+        .headers
+        .get("x-amz-date")
+        .and_then(|d| NaiveDateTime::parse_from_str(d, "%Y%m%dT%H%M%SZ").ok())
+        .map(|d| Utc.from_utc_datetime(&d))
+    else {
+        return false;
+    };
+
+    let mut query: Vec<&str> = request.query.split('&').filter(|p| !p.is_empty()).collect();
+    query.sort();
+    let query: Vec<String> = query
+        .iter()
+        .map(|p| {
+            if p.contains('=') {
+                p.to_string()
+            } else {
+                format!("{}=", p)
+            }
+        })
+        .collect();
+    let host = format!("127.0.0.1:{}", port);
+    let headers: Vec<(&str, &str)> = signed_headers
+        .split(';')
+        .map(|name| {
+            let value = if name == "host" {
+                request.headers.get("host").map_or(host.as_str(), |h| h)
+            } else {
+                request.headers.get(name).map_or("", |v| v)
+            };
+            (name, value)
+        })
+        .collect();
+
+    let (_, expected) = sigv4::signature(
+        &Credentials {
+            access_key_id: ACCESS_KEY,
+            secret_access_key: SECRET_KEY,
+            region: "us-east-1",
+        },
+        &CanonicalRequest {
+            method: &request.method,
+            uri: &request.path,
+            query: &query.join("&"),
+            headers: &headers,
+            payload_hash: &payload_hash,
+        },
+        now,
+    );
+    expected == signature
+}
+
+fn handle(request: &Request, bucket: &mut Bucket) -> Response {
+    let Some(key) = request
+        .path
+        .strip_prefix(&format!("/{}/", BUCKET))
+        .map(percent_decode)
+    else {
+        return Response::error(404, "NoSuchBucket");
+    };
+    let params: HashMap<&str, &str> = request
+        .query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.split_once('=').unwrap_or((p, "")))
+        .collect();
+    let upload_id = params.get("uploadId").map(|id| percent_decode(id));
+
+    match (request.method.as_str(), upload_id) {
+        ("POST", None) if params.contains_key("uploads") => {
+            bucket.next_upload += 1;
+            let id = format!("upload-{}", bucket.next_upload);
+            bucket
+                .uploads
+                .insert(id.clone(), (key.clone(), BTreeMap::new()));
+            Response::new(
+                200,
+                format!(
+                    "<InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key>\
+                     <UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                    BUCKET, key, id
+                ),
+            )
+        }
+        ("PUT", Some(id)) => {
+            let Some((_, parts)) = bucket.uploads.get_mut(&id) else {
+                return Response::error(404, "NoSuchUpload");
+            };
+            let number: u32 = params
+                .get("partNumber")
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0);
+            let etag = format!("\"{}\"", &sigv4::sha256_hex(&request.body)[..32]);
+            parts.insert(number, (etag.clone(), request.body.clone()));
+            let mut response = Response::new(200, Vec::new());
+            response.headers.push(("ETag".into(), etag));
+            response
+        }
+        ("GET", Some(id)) => {
+            let Some((_, parts)) = bucket.uploads.get(&id) else {
+                return Response::error(404, "NoSuchUpload");
+            };
+            let marker: u32 = params
+                .get("part-number-marker")
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0);
+            let page: Vec<_> = parts.range(marker + 1..).take(LIST_PARTS_PAGE).collect();
+            let truncated = parts.range(marker + 1..).count() > page.len();
+            let mut xml = format!(
+                "<ListPartsResult><UploadId>{}</UploadId><IsTruncated>{}</IsTruncated>",
+                id, truncated
+            );
+            if let Some((last, _)) = page.last() {
+                xml.push_str(&format!(
+                    "<NextPartNumberMarker>{}</NextPartNumberMarker>",
+                    last
+                ));
+            }
+            for (number, (etag, data)) in &page {
+                xml.push_str(&format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag><Size>{}</Size></Part>",
+                    number,
+                    etag.replace('"', "&quot;"),
+                    data.len()
+                ));
+            }
+            xml.push_str("</ListPartsResult>");
+            Response::new(200, xml)
+        }
+        ("POST", Some(id)) => {
+            let Some((upload_key, parts)) = bucket.uploads.remove(&id) else {
+                return Response::error(404, "NoSuchUpload");
+            };
+            let body = String::from_utf8_lossy(&request.body).replace("&quot;", "\"");
+            let mut object = Vec::new();
+            for part in body.split("<Part>").skip(1) {
+                let number: u32 = between(part, "<PartNumber>", "</PartNumber>")
+                    .parse()
+                    .unwrap_or(0);
+                let etag = between(part, "<ETag>", "</ETag>");
+                match parts.get(&number) {
+                    Some((stored, data)) if stored == etag => object.extend_from_slice(data),
+                    _ => return Response::new(200, "<Error><Code>InvalidPart</Code></Error>"),
+                }
+            }
+            bucket.objects.insert(upload_key, object);
+            Response::new(200, "<CompleteMultipartUploadResult/>")
+        }
+        ("DELETE", Some(id)) => match bucket.uploads.remove(&id) {
+            Some(_) => Response::new(204, Vec::new()),
+            None => Response::error(404, "NoSuchUpload"),
+        },
+        ("HEAD", None) => match bucket.objects.get(&key) {
+            Some(object) => Response {
+                head_length: Some(object.len()),
+                ..Response::new(200, Vec::new())
+            },
+            None => Response::new(404, Vec::new()),
+        },
+        ("GET", None) => {
+            let Some(object) = bucket.objects.get(&key) else {
+                return Response::error(404, "NoSuchKey");
+            };
+            let Some(range) = request.headers.get("range") else {
+                return Response::new(200, object.clone());
+            };
+            let (start, end) = range
+                .trim_start_matches("bytes=")
+                .split_once('-')
+                .unwrap_or(("0", ""));
+            let start: usize = start.parse().unwrap_or(0);
+            let end: usize = end.parse().unwrap_or(usize::MAX);
+            if start >= object.len() {
+                return Response::error(416, "InvalidRange");
+            }
+            let end = end.min(object.len() - 1);
+            Response::new(206, object[start..=end].to_vec())
+        }
+        _ => Response::error(400, "NotImplemented"),
+    }
+}
+
+fn between<'a>(s: &'a str, open: &str, close: &str) -> &'a str {
+    s.split_once(open)
+        .and_then(|(_, rest)| rest.split_once(close))
+        .map_or("", |(inner, _)| inner)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && let Ok(b) = u8::from_str_radix(&s[i + 1..i + 3], 16)
+        {
+            out.push(b);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
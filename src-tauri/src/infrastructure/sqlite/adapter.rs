@@ -4,12 +4,26 @@ use sqlx::{Row, SqlitePool};
 use std::str::FromStr;
 
 use super::SqlxResultExt;
-use crate::domain::entities::snapshot::{ExportFormat, Snapshot, SnapshotStatus, SnapshotType};
+use crate::domain::entities::snapshot::{
+    ExportFormat, Snapshot, SnapshotChunk, SnapshotStatus, SnapshotStorage, SnapshotType,
+};
 use crate::domain::errors::DomainError;
 use crate::domain::ports::audit_logger::{AuditEntry, AuditLoggerPort, AuditQuery};
-use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::ports::snapshot_repository::{SnapshotFilter, SnapshotRepositoryPort};
 use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
 
+/// `format` column values of compressed exports. Distinct from the extensions
+/// because legacy rows used "tar.gz" for what was always a plain tar.
+const FORMAT_TAR_ZST: &str = "tar+zstd";
+const FORMAT_TAR_GZ: &str = "tar+gzip";
+
+/// Snapshot columns plus its tags joined with `TAG_SEPARATOR` (tags never
+/// contain control characters). Append `WHERE`/`ORDER BY` clauses on `s`.
+const SNAPSHOT_SELECT: &str = "SELECT s.*, \
+     (SELECT group_concat(tag, char(31)) FROM snapshot_tags t WHERE t.snapshot_id = s.id) AS tags \
+     FROM snapshots s";
+const TAG_SEPARATOR: char = '\u{1f}';
+
 /// Shared SQLite connection pool.
 #[derive(Clone)]
 pub struct SqliteDb {
@@ -64,6 +78,130 @@ impl SqliteDb {
             let _ = sqlx::query(stmt).execute(&pool).await;
         }
 
+        // Migration 006: best-effort ADD COLUMN (no-op if already applied)
+        let _ = sqlx::query(include_str!("migrations/006_snapshot_storage.sql"))
+            .execute(&pool)
+            .await;
+
+        sqlx::query(include_str!("migrations/007_snapshot_chunks.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        // Migration 008: best-effort ADD COLUMN (no-op if already applied)
+        let _ = sqlx::query(include_str!("migrations/008_snapshot_checksum.sql"))
+            .execute(&pool)
+            .await;
+
+        sqlx::query(include_str!("migrations/009_snapshot_schedules.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        // Migration 010: best-effort ADD COLUMN (no-op if already applied)
+        let _ = sqlx::query(include_str!("migrations/010_snapshot_pinned.sql"))
+            .execute(&pool)
+            .await;
+
+        sqlx::query(include_str!("migrations/011_snapshot_tags.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        sqlx::query(include_str!("migrations/012_snapshot_replication.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        // Migration 013: best-effort ADD COLUMN (no-op if already applied)
+        let _ = sqlx::query(include_str!("migrations/013_snapshot_encryption.sql"))
+            .execute(&pool)
+            .await;
+
+        sqlx::query(include_str!("migrations/014_safety_snapshot_policies.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        sqlx::query(include_str!("migrations/015_distro_templates.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        sqlx::query(include_str!("migrations/016_alert_thresholds.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        // Migration 017: best-effort ADD COLUMN (alert lifecycle + rule settings).
+        // The backfill only runs along with the `resolved_at` column, so later
+        // startups don't resolve alerts that are still firing.
+        let mut lifecycle_added = false;
+        for stmt in include_str!("migrations/017_alert_lifecycle.sql")
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.trim().starts_with("--"))
+        {
+            if stmt.starts_with("UPDATE") {
+                if lifecycle_added {
+                    sqlx::query(stmt).execute(&pool).await.db_err()?;
+                }
+                continue;
+            }
+            let added = sqlx::query(stmt).execute(&pool).await.is_ok();
+            lifecycle_added |= added && stmt.contains("resolved_at");
+        }
+
+        // Migration 018: best-effort ADD COLUMN (alert units)
+        for stmt in include_str!("migrations/018_alert_units.sql")
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.trim().starts_with("--"))
+        {
+            let _ = sqlx::query(stmt).execute(&pool).await;
+        }
+
+        sqlx::query(include_str!("migrations/019_notification_channels.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        // Migration 020: best-effort ADD COLUMN (no-op if already applied)
+        let _ = sqlx::query(include_str!("migrations/020_alert_threshold_channels.sql"))
+            .execute(&pool)
+            .await;
+
+        sqlx::query(include_str!("migrations/021_alert_silences.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        // Migration 022: best-effort ADD COLUMN (no-op if already applied)
+        let _ = sqlx::query(include_str!("migrations/022_alert_log_suppressed.sql"))
+            .execute(&pool)
+            .await;
+
+        sqlx::query(include_str!("migrations/023_process_metrics.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        sqlx::query(include_str!("migrations/024_metrics_tiers.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
+        // Migration 025: best-effort ADD COLUMN (OpenMetrics endpoint)
+        for stmt in include_str!("migrations/025_openmetrics_settings.sql")
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.trim().starts_with("--"))
+        {
+            let _ = sqlx::query(stmt).execute(&pool).await;
+        }
+
+        sqlx::query(include_str!("migrations/026_service_metrics.sql"))
+            .execute(&pool)
+            .await
+            .db_err()?;
+
         Ok(Self { pool })
     }
 }
@@ -85,6 +223,8 @@ impl SqliteSnapshotRepository {
             SnapshotStatus::InProgress
         } else if status_str == "completed" {
             SnapshotStatus::Completed
+        } else if status_str == "corrupted" {
+            SnapshotStatus::Corrupted
         } else if let Some(reason) = status_str.strip_prefix("failed:") {
             SnapshotStatus::Failed(reason.trim().to_string())
         } else {
@@ -101,11 +241,31 @@ impl SqliteSnapshotRepository {
         let format_str: String = row.get("format");
         let format = match format_str.as_str() {
             "vhdx" => ExportFormat::Vhd,
+            FORMAT_TAR_ZST => ExportFormat::TarZst,
+            FORMAT_TAR_GZ => ExportFormat::TarGz,
             _ => ExportFormat::Tar, // "tar" + legacy "tar.gz"/"tar.xz" (always plain tar)
         };
 
+        let storage_str: String = row.get("storage");
+        let storage = match storage_str.as_str() {
+            "chunked" => SnapshotStorage::Chunked,
+            _ => SnapshotStorage::File,
+        };
+
         let parent_id: Option<String> = row.get("parent_id");
         let created_str: String = row.get("created_at");
+        let mut tags: Vec<String> = row
+            .get::<Option<String>, _>("tags")
+            .map(|t| t.split(TAG_SEPARATOR).map(str::to_string).collect())
+            .unwrap_or_default();
+        tags.sort();
+
+        // Unreadable parameters must not turn an encrypted snapshot into a plain one.
+        let encryption = row
+            .get::<Option<String>, _>("encryption")
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| DomainError::DatabaseError(format!("invalid encryption params: {}", e)))?;
 
         Ok(Snapshot {
             id: SnapshotId::from_string(row.get("id")),
@@ -122,6 +282,11 @@ impl SqliteSnapshotRepository {
                 .unwrap_or_else(|_| chrono::Utc::now()),
             status,
             default_user: row.get("default_user"),
+            storage,
+            checksum: row.get("checksum"),
+            tags,
+            pinned: row.get::<i64, _>("pinned") != 0,
+            encryption,
         })
     }
 }
@@ -132,6 +297,7 @@ impl SnapshotRepositoryPort for SqliteSnapshotRepository {
         let status_str = match &snapshot.status {
             SnapshotStatus::InProgress => "in_progress".to_string(),
             SnapshotStatus::Completed => "completed".to_string(),
+            SnapshotStatus::Corrupted => "corrupted".to_string(),
             SnapshotStatus::Failed(reason) => format!("failed: {}", reason),
         };
 
@@ -140,42 +306,74 @@ impl SnapshotRepositoryPort for SqliteSnapshotRepository {
             SnapshotType::PseudoIncremental => "incremental",
         };
 
+        let encryption = snapshot
+            .encryption
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| DomainError::Internal(format!("encryption serialization: {}", e)))?;
+
+        let mut tx = self.db.pool.begin().await.db_err()?;
         sqlx::query(
-            "INSERT OR REPLACE INTO snapshots (id, distro_name, name, description, snapshot_type, format, file_path, file_size, parent_id, created_at, status, default_user)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT OR REPLACE INTO snapshots (id, distro_name, name, description, snapshot_type, format, file_path, file_size, parent_id, created_at, status, default_user, storage, checksum, pinned, encryption)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(snapshot.id.as_str())
         .bind(snapshot.distro_name.as_str())
         .bind(&snapshot.name)
         .bind(&snapshot.description)
         .bind(snapshot_type)
-        .bind(snapshot.format.extension())
+        .bind(match snapshot.format {
+            ExportFormat::TarZst => FORMAT_TAR_ZST,
+            ExportFormat::TarGz => FORMAT_TAR_GZ,
+            ExportFormat::Tar | ExportFormat::Vhd => snapshot.format.extension(),
+        })
         .bind(&snapshot.file_path)
         .bind(snapshot.file_size.bytes() as i64)
         .bind(snapshot.parent_id.as_ref().map(|id| id.as_str().to_string()))
         .bind(snapshot.created_at.to_rfc3339())
         .bind(&status_str)
         .bind(&snapshot.default_user)
-        .execute(&self.db.pool)
+        .bind(snapshot.storage.as_str())
+        .bind(&snapshot.checksum)
+        .bind(snapshot.pinned)
+        .bind(encryption)
+        .execute(&mut *tx)
         .await
         .db_err()?;
 
+        sqlx::query("DELETE FROM snapshot_tags WHERE snapshot_id = ?")
+            .bind(snapshot.id.as_str())
+            .execute(&mut *tx)
+            .await
+            .db_err()?;
+        for tag in &snapshot.tags {
+            sqlx::query("INSERT OR IGNORE INTO snapshot_tags (snapshot_id, tag) VALUES (?, ?)")
+                .bind(snapshot.id.as_str())
+                .bind(tag)
+                .execute(&mut *tx)
+                .await
+                .db_err()?;
+        }
+        tx.commit().await.db_err()?;
+
         Ok(())
     }
 
     async fn list_by_distro(&self, distro: &DistroName) -> Result<Vec<Snapshot>, DomainError> {
-        let rows =
-            sqlx::query("SELECT * FROM snapshots WHERE distro_name = ? ORDER BY created_at DESC")
-                .bind(distro.as_str())
-                .fetch_all(&self.db.pool)
-                .await
-                .db_err()?;
+        let rows = sqlx::query(&format!(
+            "{SNAPSHOT_SELECT} WHERE s.distro_name = ? ORDER BY s.created_at DESC"
+        ))
+        .bind(distro.as_str())
+        .fetch_all(&self.db.pool)
+        .await
+        .db_err()?;
 
         rows.iter().map(|r| self.row_to_snapshot(r)).collect()
     }
 
     async fn list_all(&self) -> Result<Vec<Snapshot>, DomainError> {
-        let rows = sqlx::query("SELECT * FROM snapshots ORDER BY created_at DESC")
+        let rows = sqlx::query(&format!("{SNAPSHOT_SELECT} ORDER BY s.created_at DESC"))
             .fetch_all(&self.db.pool)
             .await
             .db_err()?;
@@ -183,8 +381,58 @@ impl SnapshotRepositoryPort for SqliteSnapshotRepository {
         rows.iter().map(|r| self.row_to_snapshot(r)).collect()
     }
 
+    async fn list_filtered(&self, filter: &SnapshotFilter) -> Result<Vec<Snapshot>, DomainError> {
+        let mut sql = format!("{SNAPSHOT_SELECT} WHERE 1=1");
+        let mut binds: Vec<String> = Vec::new();
+
+        if let Some(ref distro) = filter.distro_name {
+            sql.push_str(" AND s.distro_name = ?");
+            binds.push(distro.to_string());
+        }
+        for tag in &filter.tags {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM snapshot_tags t WHERE t.snapshot_id = s.id AND t.tag = ?)",
+            );
+            binds.push(tag.clone());
+        }
+        if let Some(after) = filter.created_after {
+            sql.push_str(" AND s.created_at >= ?");
+            binds.push(after.to_rfc3339());
+        }
+        if let Some(before) = filter.created_before {
+            sql.push_str(" AND s.created_at <= ?");
+            binds.push(before.to_rfc3339());
+        }
+        if let Some(min) = filter.min_size_bytes {
+            sql.push_str(&format!(" AND s.file_size >= {}", min as i64));
+        }
+        if let Some(max) = filter.max_size_bytes {
+            sql.push_str(&format!(" AND s.file_size <= {}", max as i64));
+        }
+        if let Some(pinned) = filter.pinned {
+            sql.push_str(&format!(" AND s.pinned = {}", pinned as i64));
+        }
+        sql.push_str(" ORDER BY s.created_at DESC");
+
+        let mut q = sqlx::query(&sql);
+        for b in &binds {
+            q = q.bind(b);
+        }
+        let rows = q.fetch_all(&self.db.pool).await.db_err()?;
+
+        rows.iter().map(|r| self.row_to_snapshot(r)).collect()
+    }
+
+    async fn list_tags(&self) -> Result<Vec<String>, DomainError> {
+        let rows = sqlx::query("SELECT DISTINCT tag FROM snapshot_tags ORDER BY tag")
+            .fetch_all(&self.db.pool)
+            .await
+            .db_err()?;
+        Ok(rows.iter().map(|r| r.get("tag")).collect())
+    }
+
     async fn get_by_id(&self, id: &SnapshotId) -> Result<Snapshot, DomainError> {
-        let row = sqlx::query("SELECT * FROM snapshots WHERE id = ?")
+        let row = sqlx::query(&format!("{SNAPSHOT_SELECT} WHERE s.id = ?"))
             .bind(id.as_str())
             .fetch_optional(&self.db.pool)
             .await
@@ -195,6 +443,18 @@ impl SnapshotRepositoryPort for SqliteSnapshotRepository {
     }
 
     async fn delete(&self, id: &SnapshotId) -> Result<(), DomainError> {
+        for table in [
+            "snapshot_chunks",
+            "schedule_snapshots",
+            "snapshot_tags",
+            "snapshot_replications",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE snapshot_id = ?"))
+                .bind(id.as_str())
+                .execute(&self.db.pool)
+                .await
+                .db_err()?;
+        }
         sqlx::query("DELETE FROM snapshots WHERE id = ?")
             .bind(id.as_str())
             .execute(&self.db.pool)
@@ -204,14 +464,87 @@ impl SnapshotRepositoryPort for SqliteSnapshotRepository {
     }
 
     async fn delete_by_distro(&self, distro: &DistroName) -> Result<Vec<Snapshot>, DomainError> {
-        let snapshots = self.list_by_distro(distro).await?;
-        sqlx::query("DELETE FROM snapshots WHERE distro_name = ?")
+        let snapshots: Vec<Snapshot> = self
+            .list_by_distro(distro)
+            .await?
+            .into_iter()
+            .filter(|s| !s.pinned)
+            .collect();
+        for table in [
+            "snapshot_chunks",
+            "schedule_snapshots",
+            "snapshot_tags",
+            "snapshot_replications",
+        ] {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE snapshot_id IN \
+                 (SELECT id FROM snapshots WHERE distro_name = ? AND pinned = 0)"
+            ))
+            .bind(distro.as_str())
+            .execute(&self.db.pool)
+            .await
+            .db_err()?;
+        }
+        sqlx::query("DELETE FROM snapshots WHERE distro_name = ? AND pinned = 0")
             .bind(distro.as_str())
             .execute(&self.db.pool)
             .await
             .db_err()?;
         Ok(snapshots)
     }
+
+    async fn save_chunk_manifest(
+        &self,
+        id: &SnapshotId,
+        chunks: &[SnapshotChunk],
+    ) -> Result<(), DomainError> {
+        let mut tx = self.db.pool.begin().await.db_err()?;
+        sqlx::query("DELETE FROM snapshot_chunks WHERE snapshot_id = ?")
+            .bind(id.as_str())
+            .execute(&mut *tx)
+            .await
+            .db_err()?;
+        for (seq, chunk) in chunks.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO snapshot_chunks (snapshot_id, seq, chunk_hash, length) VALUES (?, ?, ?, ?)",
+            )
+            .bind(id.as_str())
+            .bind(seq as i64)
+            .bind(&chunk.hash)
+            .bind(chunk.length as i64)
+            .execute(&mut *tx)
+            .await
+            .db_err()?;
+        }
+        tx.commit().await.db_err()?;
+        Ok(())
+    }
+
+    async fn get_chunk_manifest(&self, id: &SnapshotId) -> Result<Vec<SnapshotChunk>, DomainError> {
+        let rows = sqlx::query(
+            "SELECT chunk_hash, length FROM snapshot_chunks WHERE snapshot_id = ? ORDER BY seq",
+        )
+        .bind(id.as_str())
+        .fetch_all(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(rows
+            .iter()
+            .map(|r| SnapshotChunk {
+                hash: r.get("chunk_hash"),
+                length: r.get::<i64, _>("length") as u64,
+            })
+            .collect())
+    }
+
+    async fn list_referenced_chunks(&self) -> Result<Vec<String>, DomainError> {
+        let rows = sqlx::query("SELECT DISTINCT chunk_hash FROM snapshot_chunks")
+            .fetch_all(&self.db.pool)
+            .await
+            .db_err()?;
+        Ok(rows.iter().map(|r| r.get("chunk_hash")).collect())
+    }
 }
 
 // --- Audit Logger backed by SQLite ---
@@ -317,9 +650,11 @@ impl AuditLoggerPort for SqliteAuditLogger {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::entities::snapshot::{ExportFormat, Snapshot, SnapshotStatus, SnapshotType};
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotChunk, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
     use crate::domain::ports::audit_logger::{AuditLoggerPort, AuditQuery};
-    use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+    use crate::domain::ports::snapshot_repository::{SnapshotFilter, SnapshotRepositoryPort};
     use crate::domain::value_objects::{DistroName, MemorySize, SnapshotId};
 
     /// Helper: create an in-memory SqliteDb for testing.
@@ -347,6 +682,11 @@ mod tests {
             created_at,
             status: SnapshotStatus::Completed,
             default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
         }
     }
 
@@ -545,6 +885,11 @@ mod tests {
             created_at: now,
             status: SnapshotStatus::InProgress,
             default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
         };
         repo.save(&snapshot).await.unwrap();
 
@@ -631,6 +976,235 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    // ---- Chunk manifest tests ----
+
+    fn chunk(hash: &str, length: u64) -> SnapshotChunk {
+        SnapshotChunk {
+            hash: hash.to_string(),
+            length,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checksum_and_corrupted_status_round_trip() {
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let mut snap = make_snapshot("sum-1", "Ubuntu", "s", chrono::Utc::now());
+        repo.save(&snap).await.unwrap();
+        assert_eq!(repo.get_by_id(&snap.id).await.unwrap().checksum, None);
+
+        snap.checksum = Some("ab".repeat(32));
+        snap.status = SnapshotStatus::Corrupted;
+        repo.save(&snap).await.unwrap();
+
+        let retrieved = repo.get_by_id(&snap.id).await.unwrap();
+        assert_eq!(retrieved.checksum, snap.checksum);
+        assert!(matches!(retrieved.status, SnapshotStatus::Corrupted));
+    }
+
+    #[tokio::test]
+    async fn test_storage_round_trips() {
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let mut snap = make_snapshot("chunked-1", "Ubuntu", "c", chrono::Utc::now());
+        snap.storage = SnapshotStorage::Chunked;
+        repo.save(&snap).await.unwrap();
+
+        let retrieved = repo.get_by_id(&snap.id).await.unwrap();
+        assert_eq!(retrieved.storage, SnapshotStorage::Chunked);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_manifest_preserves_order_and_replaces() {
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let snap = make_snapshot("chunked-2", "Ubuntu", "c", chrono::Utc::now());
+        repo.save(&snap).await.unwrap();
+        let id = snap.id.clone();
+
+        repo.save_chunk_manifest(&id, &[chunk("aa", 1)])
+            .await
+            .unwrap();
+        let chunks = vec![
+            chunk("cc", 3),
+            chunk("aa", 1),
+            chunk("bb", 2),
+            chunk("aa", 1),
+        ];
+        repo.save_chunk_manifest(&id, &chunks).await.unwrap();
+
+        assert_eq!(repo.get_chunk_manifest(&id).await.unwrap(), chunks);
+        let empty = repo
+            .get_chunk_manifest(&SnapshotId::from_string("file-snap".to_string()))
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_releases_chunk_references() {
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let a = make_snapshot("chunk-a", "Ubuntu", "a", chrono::Utc::now());
+        let b = make_snapshot("chunk-b", "Debian", "b", chrono::Utc::now());
+        repo.save(&a).await.unwrap();
+        repo.save(&b).await.unwrap();
+        repo.save_chunk_manifest(&a.id, &[chunk("shared", 1), chunk("only-a", 1)])
+            .await
+            .unwrap();
+        repo.save_chunk_manifest(&b.id, &[chunk("shared", 1)])
+            .await
+            .unwrap();
+
+        repo.delete(&a.id).await.unwrap();
+        let mut referenced = repo.list_referenced_chunks().await.unwrap();
+        referenced.sort();
+        assert_eq!(referenced, vec!["shared".to_string()]);
+
+        repo.delete_by_distro(&DistroName::new("Debian").unwrap())
+            .await
+            .unwrap();
+        assert!(repo.list_referenced_chunks().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_encryption_params_round_trip() {
+        use crate::domain::entities::snapshot::{KeySource, SnapshotEncryption};
+
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let mut snap = make_snapshot("enc-1", "Ubuntu", "e", chrono::Utc::now());
+        repo.save(&snap).await.unwrap();
+        assert!(repo.get_by_id(&snap.id).await.unwrap().encryption.is_none());
+
+        snap.encryption = Some(SnapshotEncryption {
+            key_source: KeySource::Keyfile,
+            salt: "00".repeat(16),
+            memory_kib: 65536,
+            iterations: 3,
+            parallelism: 1,
+            key_check: "ff".repeat(32),
+        });
+        repo.save(&snap).await.unwrap();
+        assert_eq!(
+            repo.get_by_id(&snap.id).await.unwrap().encryption,
+            snap.encryption
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tags_and_pinned_round_trip() {
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let mut snap = make_snapshot("tag-1", "Ubuntu", "t", chrono::Utc::now());
+        snap.tags = vec!["golden".into(), "pre-upgrade".into()];
+        snap.pinned = true;
+        repo.save(&snap).await.unwrap();
+
+        let retrieved = repo.get_by_id(&snap.id).await.unwrap();
+        assert_eq!(retrieved.tags, vec!["golden", "pre-upgrade"]);
+        assert!(retrieved.pinned);
+
+        snap.tags = vec!["golden".into()];
+        snap.pinned = false;
+        repo.save(&snap).await.unwrap();
+        let retrieved = repo.get_by_id(&snap.id).await.unwrap();
+        assert_eq!(retrieved.tags, vec!["golden"]);
+        assert!(!retrieved.pinned);
+        assert_eq!(repo.list_tags().await.unwrap(), vec!["golden"]);
+
+        repo.delete(&snap.id).await.unwrap();
+        assert!(repo.list_tags().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_filtered_by_tags_dates_and_size() {
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let now = chrono::Utc::now();
+        let mut a = make_snapshot("f-a", "Ubuntu", "a", now - chrono::Duration::days(10));
+        a.tags = vec!["golden".into()];
+        let mut b = make_snapshot("f-b", "Ubuntu", "b", now - chrono::Duration::days(2));
+        b.tags = vec!["golden".into(), "pre-upgrade".into()];
+        b.file_size = MemorySize::from_bytes(10_000);
+        b.pinned = true;
+        let c = make_snapshot("f-c", "Debian", "c", now);
+        for s in [&a, &b, &c] {
+            repo.save(s).await.unwrap();
+        }
+        let ids = |snaps: Vec<Snapshot>| -> Vec<String> {
+            snaps.into_iter().map(|s| s.id.to_string()).collect()
+        };
+
+        let all = repo
+            .list_filtered(&SnapshotFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(ids(all), vec!["f-c", "f-b", "f-a"]);
+
+        let golden = SnapshotFilter {
+            tags: vec!["golden".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(repo.list_filtered(&golden).await.unwrap()),
+            vec!["f-b", "f-a"]
+        );
+
+        let both = SnapshotFilter {
+            tags: vec!["golden".into(), "pre-upgrade".into()],
+            ..Default::default()
+        };
+        assert_eq!(ids(repo.list_filtered(&both).await.unwrap()), vec!["f-b"]);
+
+        let recent = SnapshotFilter {
+            created_after: Some(now - chrono::Duration::days(5)),
+            created_before: Some(now - chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert_eq!(ids(repo.list_filtered(&recent).await.unwrap()), vec!["f-b"]);
+
+        let small_ubuntu = SnapshotFilter {
+            distro_name: Some(DistroName::new("Ubuntu").unwrap()),
+            max_size_bytes: Some(5_000),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(repo.list_filtered(&small_ubuntu).await.unwrap()),
+            vec!["f-a"]
+        );
+
+        let pinned = SnapshotFilter {
+            pinned: Some(true),
+            min_size_bytes: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(ids(repo.list_filtered(&pinned).await.unwrap()), vec!["f-b"]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_distro_keeps_pinned_snapshots() {
+        let db = test_db().await;
+        let repo = SqliteSnapshotRepository::new(db);
+        let mut golden = make_snapshot("keep", "Ubuntu", "golden", chrono::Utc::now());
+        golden.pinned = true;
+        golden.tags = vec!["golden".into()];
+        let scratch = make_snapshot("drop", "Ubuntu", "scratch", chrono::Utc::now());
+        repo.save(&golden).await.unwrap();
+        repo.save(&scratch).await.unwrap();
+
+        let deleted = repo
+            .delete_by_distro(&DistroName::new("Ubuntu").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].id.as_str(), "drop");
+
+        let remaining = repo.list_all().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].tags, vec!["golden"]);
+    }
+
     // ---- Snapshot round-trip: format and status variants ----
 
     #[tokio::test]
@@ -642,6 +1216,8 @@ mod tests {
         let formats = vec![
             ("fmt-tar", ExportFormat::Tar),
             ("fmt-vhd", ExportFormat::Vhd),
+            ("fmt-zst", ExportFormat::TarZst),
+            ("fmt-gz", ExportFormat::TarGz),
         ];
 
         for (id, format) in &formats {
@@ -658,6 +1234,11 @@ mod tests {
                 created_at: now,
                 status: SnapshotStatus::Completed,
                 default_user: None,
+                storage: SnapshotStorage::File,
+                checksum: None,
+                tags: Vec::new(),
+                pinned: false,
+                encryption: None,
             };
             repo.save(&snap).await.unwrap();
         }
@@ -675,6 +1256,18 @@ mod tests {
             .unwrap();
         assert_eq!(retrieved.format.extension(), "vhdx");
 
+        let retrieved = repo
+            .get_by_id(&SnapshotId::from_string("fmt-zst".to_string()))
+            .await
+            .unwrap();
+        assert!(matches!(retrieved.format, ExportFormat::TarZst));
+
+        let retrieved = repo
+            .get_by_id(&SnapshotId::from_string("fmt-gz".to_string()))
+            .await
+            .unwrap();
+        assert!(matches!(retrieved.format, ExportFormat::TarGz));
+
         // Legacy "tar.gz"/"tar.xz" values in DB should deserialize as Tar
         // (wsl --export always produced plain tar regardless of extension)
         sqlx::query("INSERT INTO snapshots (id, distro_name, name, description, snapshot_type, format, file_path, file_size, parent_id, created_at, status) VALUES (?, ?, ?, NULL, 'full', 'tar.gz', '/tmp/legacy', 100, NULL, ?, 'completed')")
@@ -4,8 +4,11 @@ use sqlx::Row;
 
 use super::SqlxResultExt;
 use super::adapter::SqliteDb;
+use crate::domain::entities::alert_silence::{AlertMatcher, AlertSilence, MaintenanceWindow};
 use crate::domain::errors::DomainError;
-use crate::domain::ports::alerting::{AlertRecord, AlertType, AlertingPort};
+use crate::domain::ports::alerting::{
+    AlertRecord, AlertThreshold, AlertType, AlertUnit, AlertingPort,
+};
 use crate::domain::value_objects::DistroName;
 
 pub struct SqliteAlertRepository {
@@ -18,24 +21,114 @@ impl SqliteAlertRepository {
     }
 }
 
+fn parse_timestamp(ts: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn parse_unit(row: &sqlx::sqlite::SqliteRow) -> AlertUnit {
+    row.get::<&str, _>("unit").parse().unwrap_or_default()
+}
+
+fn row_to_alert(row: &sqlx::sqlite::SqliteRow) -> AlertRecord {
+    let alert_type_str: String = row.get("alert_type");
+    AlertRecord {
+        id: row.get("id"),
+        distro_name: row.get("distro_name"),
+        alert_type: alert_type_str
+            .parse::<AlertType>()
+            .unwrap_or(AlertType::Cpu),
+        threshold: row.get("threshold"),
+        actual_value: row.get("actual_value"),
+        unit: parse_unit(row),
+        timestamp: parse_timestamp(row.get("timestamp")),
+        acknowledged: row.get::<i32, _>("acknowledged") != 0,
+        resolved_at: row
+            .get::<Option<&str>, _>("resolved_at")
+            .map(parse_timestamp),
+        resolved_value: row.get("resolved_value"),
+        suppressed_by: row.get("suppressed_by"),
+    }
+}
+
+fn row_to_matcher(row: &sqlx::sqlite::SqliteRow) -> AlertMatcher {
+    AlertMatcher {
+        distro_name: row.get("distro_name"),
+        alert_type: row
+            .get::<Option<&str>, _>("alert_type")
+            .and_then(|t| t.parse().ok()),
+    }
+}
+
+fn row_to_silence(row: &sqlx::sqlite::SqliteRow) -> AlertSilence {
+    AlertSilence {
+        id: row.get("id"),
+        matcher: row_to_matcher(row),
+        reason: row.get("reason"),
+        starts_at: parse_timestamp(row.get("starts_at")),
+        ends_at: parse_timestamp(row.get("ends_at")),
+        created_at: parse_timestamp(row.get("created_at")),
+    }
+}
+
+fn row_to_window(row: &sqlx::sqlite::SqliteRow) -> MaintenanceWindow {
+    MaintenanceWindow {
+        id: row.get("id"),
+        name: row.get("name"),
+        matcher: row_to_matcher(row),
+        days: row
+            .get::<&str, _>("days")
+            .split(',')
+            .filter_map(|d| d.parse().ok())
+            .collect(),
+        start_time: row.get("start_time"),
+        duration_minutes: row.get::<i64, _>("duration_minutes") as u32,
+        enabled: row.get::<i32, _>("enabled") != 0,
+    }
+}
+
 #[async_trait]
 impl AlertingPort for SqliteAlertRepository {
     async fn record_alert(
         &self,
         distro: &DistroName,
         alert_type: AlertType,
+        unit: AlertUnit,
         threshold: f64,
         actual_value: f64,
-    ) -> Result<(), DomainError> {
-        sqlx::query(
-            "INSERT INTO alert_log (distro_name, alert_type, threshold, actual_value, timestamp)
-             VALUES (?, ?, ?, ?, ?)",
+        suppressed_by: Option<String>,
+    ) -> Result<i64, DomainError> {
+        let result = sqlx::query(
+            "INSERT INTO alert_log (distro_name, alert_type, unit, threshold, actual_value, timestamp, suppressed_by)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(distro.as_str())
         .bind(alert_type.to_string())
+        .bind(unit.to_string())
         .bind(threshold)
         .bind(actual_value)
         .bind(Utc::now().to_rfc3339())
+        .bind(suppressed_by)
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn resolve_alert(
+        &self,
+        alert_id: i64,
+        resolved_value: Option<f64>,
+    ) -> Result<(), DomainError> {
+        sqlx::query(
+            "UPDATE alert_log SET resolved_at = ?, resolved_value = ?
+             WHERE id = ? AND resolved_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(resolved_value)
+        .bind(alert_id)
         .execute(&self.db.pool)
         .await
         .db_err()?;
@@ -43,6 +136,15 @@ impl AlertingPort for SqliteAlertRepository {
         Ok(())
     }
 
+    async fn list_firing_alerts(&self) -> Result<Vec<AlertRecord>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM alert_log WHERE resolved_at IS NULL ORDER BY id")
+            .fetch_all(&self.db.pool)
+            .await
+            .db_err()?;
+
+        Ok(rows.iter().map(row_to_alert).collect())
+    }
+
     async fn get_recent_alerts(
         &self,
         distro: &DistroName,
@@ -60,26 +162,7 @@ impl AlertingPort for SqliteAlertRepository {
         .await
         .db_err()?;
 
-        rows.iter()
-            .map(|row| {
-                let ts_str: String = row.get("timestamp");
-                let alert_type_str: String = row.get("alert_type");
-
-                Ok(AlertRecord {
-                    id: row.get("id"),
-                    distro_name: row.get("distro_name"),
-                    alert_type: alert_type_str
-                        .parse::<AlertType>()
-                        .unwrap_or(AlertType::Cpu),
-                    threshold: row.get("threshold"),
-                    actual_value: row.get("actual_value"),
-                    timestamp: chrono::DateTime::parse_from_rfc3339(&ts_str)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    acknowledged: row.get::<i32, _>("acknowledged") != 0,
-                })
-            })
-            .collect()
+        Ok(rows.iter().map(row_to_alert).collect())
     }
 
     async fn acknowledge_alert(&self, alert_id: i64) -> Result<(), DomainError> {
@@ -93,7 +176,8 @@ impl AlertingPort for SqliteAlertRepository {
     }
 
     async fn purge_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
-        let result = sqlx::query("DELETE FROM alert_log WHERE timestamp < ?")
+        // Firing alerts are kept however old they are
+        let result = sqlx::query("DELETE FROM alert_log WHERE resolved_at < ?")
             .bind(before.to_rfc3339())
             .execute(&self.db.pool)
             .await
@@ -110,12 +194,150 @@ impl AlertingPort for SqliteAlertRepository {
             .db_err()?;
         Ok(())
     }
+
+    async fn list_thresholds(&self) -> Result<Vec<AlertThreshold>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM alert_thresholds ORDER BY distro_name, alert_type")
+            .fetch_all(&self.db.pool)
+            .await
+            .db_err()?;
+
+        // Rows of alert types this version doesn't know are skipped.
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let alert_type = row.get::<&str, _>("alert_type").parse().ok()?;
+                let distro_name: String = row.get("distro_name");
+                Some(AlertThreshold {
+                    alert_type,
+                    threshold_percent: row.get("threshold_percent"),
+                    enabled: row.get::<i32, _>("enabled") != 0,
+                    unit: parse_unit(row),
+                    distro_name: (!distro_name.is_empty()).then_some(distro_name),
+                    sustained_secs: row.get::<i64, _>("sustained_secs") as u64,
+                    clear_percent: row.get("clear_percent"),
+                    channels: serde_json::from_str(row.get("channels")).unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+
+    async fn save_thresholds(
+        &self,
+        distro: Option<String>,
+        thresholds: Vec<AlertThreshold>,
+    ) -> Result<(), DomainError> {
+        let mut tx = self.db.pool.begin().await.db_err()?;
+        // Defaults are upserted per alert type, overrides replaced as a whole
+        if let Some(distro) = &distro {
+            sqlx::query("DELETE FROM alert_thresholds WHERE distro_name = ?")
+                .bind(distro)
+                .execute(&mut *tx)
+                .await
+                .db_err()?;
+        }
+        let scope = distro.unwrap_or_default();
+        for threshold in &thresholds {
+            sqlx::query(
+                "INSERT OR REPLACE INTO alert_thresholds (distro_name, alert_type, threshold_percent, enabled, unit, sustained_secs, clear_percent, channels)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&scope)
+            .bind(threshold.alert_type.to_string())
+            .bind(threshold.threshold_percent)
+            .bind(threshold.enabled as i32)
+            .bind(threshold.unit.to_string())
+            .bind(threshold.sustained_secs as i64)
+            .bind(threshold.clear_percent)
+            .bind(serde_json::to_string(&threshold.channels).unwrap_or_else(|_| "[]".into()))
+            .execute(&mut *tx)
+            .await
+            .db_err()?;
+        }
+        tx.commit().await.db_err()?;
+        Ok(())
+    }
+
+    async fn list_silences(&self) -> Result<Vec<AlertSilence>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM alert_silences ORDER BY starts_at DESC")
+            .fetch_all(&self.db.pool)
+            .await
+            .db_err()?;
+
+        Ok(rows.iter().map(row_to_silence).collect())
+    }
+
+    async fn save_silence(&self, silence: &AlertSilence) -> Result<(), DomainError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO alert_silences (id, distro_name, alert_type, reason, starts_at, ends_at, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&silence.id)
+        .bind(&silence.matcher.distro_name)
+        .bind(silence.matcher.alert_type.map(|t| t.to_string()))
+        .bind(&silence.reason)
+        .bind(silence.starts_at.to_rfc3339())
+        .bind(silence.ends_at.to_rfc3339())
+        .bind(silence.created_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+        Ok(())
+    }
+
+    async fn purge_silences_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let result = sqlx::query("DELETE FROM alert_silences WHERE ends_at < ?")
+            .bind(before.to_rfc3339())
+            .execute(&self.db.pool)
+            .await
+            .db_err()?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn list_maintenance_windows(&self) -> Result<Vec<MaintenanceWindow>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM maintenance_windows ORDER BY name")
+            .fetch_all(&self.db.pool)
+            .await
+            .db_err()?;
+
+        Ok(rows.iter().map(row_to_window).collect())
+    }
+
+    async fn save_maintenance_window(&self, window: &MaintenanceWindow) -> Result<(), DomainError> {
+        let days: Vec<String> = window.days.iter().map(|d| d.to_string()).collect();
+        sqlx::query(
+            "INSERT OR REPLACE INTO maintenance_windows (id, name, distro_name, alert_type, days, start_time, duration_minutes, enabled)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&window.id)
+        .bind(&window.name)
+        .bind(&window.matcher.distro_name)
+        .bind(window.matcher.alert_type.map(|t| t.to_string()))
+        .bind(days.join(","))
+        .bind(&window.start_time)
+        .bind(window.duration_minutes as i64)
+        .bind(window.enabled as i32)
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+        Ok(())
+    }
+
+    async fn delete_maintenance_window(&self, window_id: &str) -> Result<(), DomainError> {
+        sqlx::query("DELETE FROM maintenance_windows WHERE id = ?")
+            .bind(window_id)
+            .execute(&self.db.pool)
+            .await
+            .db_err()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::infrastructure::sqlite::adapter::SqliteDb;
+    use std::str::FromStr;
 
     async fn test_db() -> SqliteDb {
         SqliteDb::new("sqlite::memory:").await.unwrap()
@@ -127,12 +349,26 @@ mod tests {
         let repo = SqliteAlertRepository::new(db);
 
         let distro = DistroName::new("Ubuntu").unwrap();
-        repo.record_alert(&distro, AlertType::Cpu, 90.0, 95.5)
-            .await
-            .unwrap();
-        repo.record_alert(&distro, AlertType::Memory, 85.0, 88.0)
-            .await
-            .unwrap();
+        repo.record_alert(
+            &distro,
+            AlertType::Cpu,
+            AlertUnit::Percent,
+            90.0,
+            95.5,
+            None,
+        )
+        .await
+        .unwrap();
+        repo.record_alert(
+            &distro,
+            AlertType::Memory,
+            AlertUnit::Percent,
+            85.0,
+            88.0,
+            None,
+        )
+        .await
+        .unwrap();
 
         let alerts = repo.get_recent_alerts(&distro, 10).await.unwrap();
         assert_eq!(alerts.len(), 2);
@@ -148,9 +384,16 @@ mod tests {
         let repo = SqliteAlertRepository::new(db);
 
         let distro = DistroName::new("Ubuntu").unwrap();
-        repo.record_alert(&distro, AlertType::Disk, 90.0, 92.0)
-            .await
-            .unwrap();
+        repo.record_alert(
+            &distro,
+            AlertType::Disk,
+            AlertUnit::Percent,
+            90.0,
+            92.0,
+            None,
+        )
+        .await
+        .unwrap();
 
         let alerts = repo.get_recent_alerts(&distro, 10).await.unwrap();
         assert!(!alerts[0].acknowledged);
@@ -167,7 +410,27 @@ mod tests {
         let repo = SqliteAlertRepository::new(db);
 
         let distro = DistroName::new("Ubuntu").unwrap();
-        repo.record_alert(&distro, AlertType::Cpu, 90.0, 95.0)
+        let resolved = repo
+            .record_alert(
+                &distro,
+                AlertType::Cpu,
+                AlertUnit::Percent,
+                90.0,
+                95.0,
+                None,
+            )
+            .await
+            .unwrap();
+        repo.resolve_alert(resolved, Some(70.0)).await.unwrap();
+        let firing = repo
+            .record_alert(
+                &distro,
+                AlertType::Disk,
+                AlertUnit::Percent,
+                90.0,
+                95.0,
+                None,
+            )
             .await
             .unwrap();
 
@@ -177,8 +440,101 @@ mod tests {
             .unwrap();
         assert_eq!(deleted, 1);
 
+        // The firing alert survives the purge
         let alerts = repo.get_recent_alerts(&distro, 10).await.unwrap();
-        assert!(alerts.is_empty());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].id, firing);
+    }
+
+    #[tokio::test]
+    async fn test_alerts_logged_before_lifecycles_are_resolved() {
+        let path = std::env::temp_dir().join("nexus_alert_lifecycle_migration.db");
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+        let url = format!("sqlite:{}", path.display());
+
+        // An alert log from before migration 017, one row past retention
+        let options = sqlx::sqlite::SqliteConnectOptions::from_str(&url)
+            .unwrap()
+            .create_if_missing(true);
+        let pool = sqlx::SqlitePool::connect_with(options).await.unwrap();
+        sqlx::query(include_str!("migrations/002_metrics.sql"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        for days_ago in [3, 0] {
+            sqlx::query(
+                "INSERT INTO alert_log (distro_name, alert_type, threshold, actual_value, timestamp)
+                 VALUES ('Ubuntu', 'cpu', 90.0, 95.0, ?)",
+            )
+            .bind((Utc::now() - chrono::Duration::days(days_ago)).to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        pool.close().await;
+
+        let repo = SqliteAlertRepository::new(SqliteDb::new(&url).await.unwrap());
+        assert!(repo.list_firing_alerts().await.unwrap().is_empty());
+        let deleted = repo
+            .purge_before(Utc::now() - chrono::Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+
+        // Later startups leave alerts fired since then firing
+        let distro = DistroName::new("Ubuntu").unwrap();
+        let firing = repo
+            .record_alert(
+                &distro,
+                AlertType::Disk,
+                AlertUnit::Percent,
+                90.0,
+                95.0,
+                None,
+            )
+            .await
+            .unwrap();
+        repo.db.pool.close().await;
+        let repo = SqliteAlertRepository::new(SqliteDb::new(&url).await.unwrap());
+        let alerts = repo.list_firing_alerts().await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].id, firing);
+
+        repo.db.pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_alert_ends_its_lifecycle() {
+        let db = test_db().await;
+        let repo = SqliteAlertRepository::new(db);
+
+        let distro = DistroName::new("Ubuntu").unwrap();
+        let id = repo
+            .record_alert(
+                &distro,
+                AlertType::Memory,
+                AlertUnit::Percent,
+                85.0,
+                91.0,
+                None,
+            )
+            .await
+            .unwrap();
+        let firing = repo.list_firing_alerts().await.unwrap();
+        assert_eq!(firing.len(), 1);
+        assert!(firing[0].resolved_at.is_none());
+
+        repo.resolve_alert(id, Some(60.5)).await.unwrap();
+
+        assert!(repo.list_firing_alerts().await.unwrap().is_empty());
+        let alerts = repo.get_recent_alerts(&distro, 10).await.unwrap();
+        assert!(alerts[0].resolved_at.is_some());
+        assert_eq!(alerts[0].resolved_value, Some(60.5));
     }
 
     #[tokio::test]
@@ -188,15 +544,244 @@ mod tests {
 
         let ubuntu = DistroName::new("Ubuntu").unwrap();
         let debian = DistroName::new("Debian").unwrap();
-        repo.record_alert(&ubuntu, AlertType::Cpu, 90.0, 95.0)
+        repo.record_alert(
+            &ubuntu,
+            AlertType::Cpu,
+            AlertUnit::Percent,
+            90.0,
+            95.0,
+            None,
+        )
+        .await
+        .unwrap();
+        repo.record_alert(
+            &debian,
+            AlertType::Memory,
+            AlertUnit::Percent,
+            85.0,
+            88.0,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let ubuntu_alerts = repo.get_recent_alerts(&ubuntu, 10).await.unwrap();
+        assert_eq!(ubuntu_alerts.len(), 1);
+        assert_eq!(ubuntu_alerts[0].distro_name, "Ubuntu");
+    }
+
+    #[tokio::test]
+    async fn test_save_thresholds_per_scope() {
+        let db = test_db().await;
+        let repo = SqliteAlertRepository::new(db);
+
+        let threshold = |alert_type, percent| AlertThreshold {
+            alert_type,
+            threshold_percent: percent,
+            enabled: true,
+            unit: AlertUnit::Percent,
+            distro_name: None,
+            sustained_secs: 0,
+            clear_percent: None,
+            channels: Vec::new(),
+        };
+        repo.save_thresholds(
+            None,
+            vec![
+                threshold(AlertType::Cpu, 80.0),
+                threshold(AlertType::Disk, 90.0),
+            ],
+        )
+        .await
+        .unwrap();
+        repo.save_thresholds(Some("Ubuntu".into()), vec![threshold(AlertType::Cpu, 95.0)])
             .await
             .unwrap();
-        repo.record_alert(&debian, AlertType::Memory, 85.0, 88.0)
+        // Saving a default adds to the others and leaves the overrides alone
+        repo.save_thresholds(None, vec![threshold(AlertType::Memory, 70.0)])
             .await
             .unwrap();
 
-        let ubuntu_alerts = repo.get_recent_alerts(&ubuntu, 10).await.unwrap();
-        assert_eq!(ubuntu_alerts.len(), 1);
-        assert_eq!(ubuntu_alerts[0].distro_name, "Ubuntu");
+        let stored = repo.list_thresholds().await.unwrap();
+        let types: Vec<AlertType> = stored.iter().map(|t| t.alert_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                AlertType::Cpu,
+                AlertType::Disk,
+                AlertType::Memory,
+                AlertType::Cpu
+            ]
+        );
+        assert!(stored[..3].iter().all(|t| t.distro_name.is_none()));
+        assert_eq!(stored[3].distro_name.as_deref(), Some("Ubuntu"));
+        assert!((stored[3].threshold_percent - 95.0).abs() < f64::EPSILON);
+
+        // Overrides are replaced as a whole; an empty list removes them
+        repo.save_thresholds(Some("Ubuntu".into()), vec![])
+            .await
+            .unwrap();
+        assert_eq!(repo.list_thresholds().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_partial_default_update_keeps_other_types_and_lifecycle() {
+        let db = test_db().await;
+        let repo = SqliteAlertRepository::new(db);
+
+        let cpu = AlertThreshold {
+            alert_type: AlertType::Cpu,
+            threshold_percent: 90.0,
+            enabled: true,
+            unit: AlertUnit::Percent,
+            distro_name: None,
+            sustained_secs: 120,
+            clear_percent: Some(75.0),
+            channels: vec!["ops-webhook".to_string()],
+        };
+        let swap = AlertThreshold {
+            alert_type: AlertType::Swap,
+            threshold_percent: 2.0 * 1024.0 * 1024.0 * 1024.0,
+            enabled: true,
+            unit: AlertUnit::Absolute,
+            distro_name: None,
+            sustained_secs: 30,
+            clear_percent: None,
+            channels: Vec::new(),
+        };
+        repo.save_thresholds(None, vec![cpu.clone(), swap.clone()])
+            .await
+            .unwrap();
+
+        // A client that only knows the memory threshold edits it
+        repo.save_thresholds(
+            None,
+            vec![AlertThreshold {
+                alert_type: AlertType::Memory,
+                threshold_percent: 85.0,
+                enabled: true,
+                unit: AlertUnit::Percent,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            }],
+        )
+        .await
+        .unwrap();
+
+        let stored = repo.list_thresholds().await.unwrap();
+        assert_eq!(stored.len(), 3);
+        let find = |alert_type| stored.iter().find(|t| t.alert_type == alert_type).unwrap();
+        let stored_cpu = find(AlertType::Cpu);
+        assert_eq!(stored_cpu.sustained_secs, 120);
+        assert_eq!(stored_cpu.clear_percent, Some(75.0));
+        assert_eq!(stored_cpu.channels, cpu.channels);
+        let stored_swap = find(AlertType::Swap);
+        assert_eq!(stored_swap.unit, AlertUnit::Absolute);
+        assert_eq!(stored_swap.sustained_secs, 30);
+        assert!((find(AlertType::Memory).threshold_percent - 85.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_units_roundtrip() {
+        let db = test_db().await;
+        let repo = SqliteAlertRepository::new(db);
+
+        let distro = DistroName::new("Ubuntu").unwrap();
+        repo.record_alert(
+            &distro,
+            AlertType::TcpTimeWait,
+            AlertUnit::Absolute,
+            5000.0,
+            7200.0,
+            None,
+        )
+        .await
+        .unwrap();
+        let alerts = repo.get_recent_alerts(&distro, 10).await.unwrap();
+        assert_eq!(alerts[0].alert_type, AlertType::TcpTimeWait);
+        assert_eq!(alerts[0].unit, AlertUnit::Absolute);
+
+        repo.save_thresholds(
+            None,
+            vec![AlertThreshold {
+                alert_type: AlertType::Swap,
+                threshold_percent: 2.0 * 1024.0 * 1024.0 * 1024.0,
+                enabled: true,
+                unit: AlertUnit::Absolute,
+                distro_name: None,
+                sustained_secs: 0,
+                clear_percent: None,
+                channels: Vec::new(),
+            }],
+        )
+        .await
+        .unwrap();
+        let stored = repo.list_thresholds().await.unwrap();
+        assert_eq!(stored[0].alert_type, AlertType::Swap);
+        assert_eq!(stored[0].unit, AlertUnit::Absolute);
+    }
+
+    #[tokio::test]
+    async fn test_silences_and_maintenance_windows() {
+        let db = test_db().await;
+        let repo = SqliteAlertRepository::new(db);
+
+        let now = Utc::now();
+        let silence = |id: &str, ends_at| AlertSilence {
+            id: id.into(),
+            matcher: AlertMatcher {
+                distro_name: Some("Ubuntu".into()),
+                alert_type: Some(AlertType::Cpu),
+            },
+            reason: "Kernel build".into(),
+            starts_at: now - chrono::Duration::hours(2),
+            ends_at,
+            created_at: now,
+        };
+        repo.save_silence(&silence("old", now - chrono::Duration::hours(1)))
+            .await
+            .unwrap();
+        repo.save_silence(&silence("current", now + chrono::Duration::hours(1)))
+            .await
+            .unwrap();
+        assert_eq!(repo.purge_silences_before(now).await.unwrap(), 1);
+        let silences = repo.list_silences().await.unwrap();
+        assert_eq!(silences.len(), 1);
+        assert_eq!(silences[0].id, "current");
+        assert_eq!(silences[0].matcher.alert_type, Some(AlertType::Cpu));
+
+        let window = MaintenanceWindow {
+            id: "w".into(),
+            name: "Backups".into(),
+            matcher: AlertMatcher::default(),
+            days: vec![chrono::Weekday::Sat, chrono::Weekday::Sun],
+            start_time: "02:00".into(),
+            duration_minutes: 90,
+            enabled: true,
+        };
+        repo.save_maintenance_window(&window).await.unwrap();
+        assert_eq!(repo.list_maintenance_windows().await.unwrap(), vec![window]);
+        repo.delete_maintenance_window("w").await.unwrap();
+        assert!(repo.list_maintenance_windows().await.unwrap().is_empty());
+
+        // Suppressed alerts say what suppressed them
+        let distro = DistroName::new("Ubuntu").unwrap();
+        repo.record_alert(
+            &distro,
+            AlertType::Cpu,
+            AlertUnit::Percent,
+            90.0,
+            99.0,
+            Some("Silence: Kernel build".into()),
+        )
+        .await
+        .unwrap();
+        let alerts = repo.get_recent_alerts(&distro, 10).await.unwrap();
+        assert_eq!(
+            alerts[0].suppressed_by.as_deref(),
+            Some("Silence: Kernel build")
+        );
     }
 }
@@ -4,10 +4,11 @@ use sqlx::Row;
 
 use super::SqlxResultExt;
 use super::adapter::SqliteDb;
-use crate::domain::entities::monitoring::SystemMetrics;
+use crate::domain::entities::metrics_settings::{MetricsSettings, MetricsTier};
+use crate::domain::entities::monitoring::{ProcessSample, ServiceMetrics, SystemMetrics};
 use crate::domain::errors::DomainError;
 use crate::domain::ports::metrics_repository::{
-    AggregatedMetricsPoint, MetricsRepositoryPort, RawMetricsRow,
+    AggregatedMetricsPoint, MetricsRepositoryPort, ProcessUsage, RawMetricsRow, ServiceUsage,
 };
 use crate::domain::value_objects::DistroName;
 
@@ -21,6 +22,51 @@ impl SqliteMetricsRepository {
     }
 }
 
+/// Table holding the buckets of a tier.
+fn tier_table(tier: MetricsTier) -> &'static str {
+    match tier {
+        MetricsTier::Minute => "metrics_aggregated",
+        MetricsTier::Hour => "metrics_hourly",
+        MetricsTier::Day => "metrics_daily",
+    }
+}
+
+fn parse_timestamp(ts: &str) -> DateTime<Utc> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn row_to_process_usage(row: &sqlx::sqlite::SqliteRow) -> ProcessUsage {
+    ProcessUsage {
+        pid: row.get::<i64, _>("pid") as u32,
+        user: row.get("user"),
+        command: row.get("command"),
+        first_seen: parse_timestamp(&row.get::<String, _>("first_seen")),
+        last_seen: parse_timestamp(&row.get::<String, _>("last_seen")),
+        sample_count: row.get::<i64, _>("sample_count") as u32,
+        cpu_avg: row.get("cpu_avg"),
+        cpu_max: row.get("cpu_max"),
+        rss_avg: row.get::<i64, _>("rss_avg") as u64,
+        rss_max: row.get::<i64, _>("rss_max") as u64,
+    }
+}
+
+fn row_to_service_usage(row: &sqlx::sqlite::SqliteRow) -> ServiceUsage {
+    ServiceUsage {
+        unit: row.get("unit"),
+        first_seen: parse_timestamp(&row.get::<String, _>("first_seen")),
+        last_seen: parse_timestamp(&row.get::<String, _>("last_seen")),
+        sample_count: row.get::<i64, _>("sample_count") as u32,
+        cpu_avg: row.get("cpu_avg"),
+        cpu_max: row.get("cpu_max"),
+        memory_avg: row.get::<i64, _>("memory_avg") as u64,
+        memory_max: row.get::<i64, _>("memory_max") as u64,
+        io_read_avg: row.get::<i64, _>("io_read_avg") as u64,
+        io_write_avg: row.get::<i64, _>("io_write_avg") as u64,
+    }
+}
+
 #[async_trait]
 impl MetricsRepositoryPort for SqliteMetricsRepository {
     async fn store_raw(&self, metrics: &SystemMetrics) -> Result<(), DomainError> {
@@ -170,14 +216,16 @@ impl MetricsRepositoryPort for SqliteMetricsRepository {
     async fn query_aggregated(
         &self,
         distro: &DistroName,
+        tier: MetricsTier,
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<AggregatedMetricsPoint>, DomainError> {
-        let rows = sqlx::query(
-            "SELECT * FROM metrics_aggregated
+        let rows = sqlx::query(&format!(
+            "SELECT * FROM {}
              WHERE distro_name = ? AND period_start >= ? AND period_start <= ?
              ORDER BY period_start ASC",
-        )
+            tier_table(tier)
+        ))
         .bind(distro.as_str())
         .bind(from.to_rfc3339())
         .bind(to.to_rfc3339())
@@ -261,6 +309,58 @@ impl MetricsRepositoryPort for SqliteMetricsRepository {
         Ok(result.rows_affected())
     }
 
+    async fn rollup_buckets(
+        &self,
+        tier: MetricsTier,
+        bucket_start: DateTime<Utc>,
+        bucket_end: DateTime<Utc>,
+    ) -> Result<u64, DomainError> {
+        let (source, truncate, step) = match tier {
+            MetricsTier::Minute => {
+                return self.aggregate_raw_buckets(bucket_start, bucket_end).await;
+            }
+            MetricsTier::Hour => (MetricsTier::Minute, "%Y-%m-%dT%H:00:00+00:00", "+1 hour"),
+            MetricsTier::Day => (MetricsTier::Hour, "%Y-%m-%dT00:00:00+00:00", "+1 day"),
+        };
+
+        // Averages are weighted by the samples of each lower-tier bucket;
+        // the unique (distro, period_start) index makes existing buckets a no-op.
+        let result = sqlx::query(&format!(
+            "INSERT OR IGNORE INTO {target} (
+                distro_name, period_start, period_end, sample_count,
+                cpu_min, cpu_avg, cpu_max,
+                mem_used_min, mem_used_avg, mem_used_max, mem_total,
+                disk_min, disk_avg, disk_max,
+                net_rx_total, net_tx_total, net_rx_max_rate, net_tx_max_rate
+            )
+            SELECT
+                distro_name,
+                strftime('{truncate}', period_start) as bucket_start,
+                strftime('{truncate}', period_start, '{step}'),
+                SUM(sample_count),
+                MIN(cpu_min), SUM(cpu_avg * sample_count) / SUM(sample_count), MAX(cpu_max),
+                MIN(mem_used_min),
+                CAST(SUM(mem_used_avg * sample_count) / SUM(sample_count) AS INTEGER),
+                MAX(mem_used_max),
+                MAX(mem_total),
+                MIN(disk_min), SUM(disk_avg * sample_count) / SUM(sample_count), MAX(disk_max),
+                SUM(net_rx_total), SUM(net_tx_total),
+                MAX(net_rx_max_rate), MAX(net_tx_max_rate)
+            FROM {source}
+            WHERE period_start >= ? AND period_start < ?
+            GROUP BY distro_name, bucket_start",
+            target = tier_table(tier),
+            source = tier_table(source),
+        ))
+        .bind(bucket_start.to_rfc3339())
+        .bind(bucket_end.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(result.rows_affected())
+    }
+
     async fn purge_raw_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
         let result = sqlx::query("DELETE FROM metrics_raw WHERE timestamp < ?")
             .bind(before.to_rfc3339())
@@ -271,8 +371,150 @@ impl MetricsRepositoryPort for SqliteMetricsRepository {
         Ok(result.rows_affected())
     }
 
-    async fn purge_aggregated_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
-        let result = sqlx::query("DELETE FROM metrics_aggregated WHERE period_start < ?")
+    async fn purge_aggregated_before(
+        &self,
+        tier: MetricsTier,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DomainError> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM {} WHERE period_start < ?",
+            tier_table(tier)
+        ))
+        .bind(before.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn store_process_samples(
+        &self,
+        distro: &DistroName,
+        timestamp: DateTime<Utc>,
+        samples: &[ProcessSample],
+    ) -> Result<(), DomainError> {
+        let timestamp = timestamp.to_rfc3339();
+        let mut tx = self.db.pool.begin().await.db_err()?;
+        for sample in samples {
+            sqlx::query(
+                "INSERT INTO process_metrics_raw (
+                    distro_name, timestamp, pid, user, command, cpu_percent, rss_bytes
+                ) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(distro.as_str())
+            .bind(&timestamp)
+            .bind(sample.pid as i64)
+            .bind(&sample.user)
+            .bind(&sample.command)
+            .bind(sample.cpu_percent)
+            .bind(sample.rss_bytes as i64)
+            .execute(&mut *tx)
+            .await
+            .db_err()?;
+        }
+        tx.commit().await.db_err()?;
+        Ok(())
+    }
+
+    async fn query_top_processes_raw(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<ProcessUsage>, DomainError> {
+        let rows = sqlx::query(
+            "SELECT pid, MAX(user) AS user, command,
+                MIN(timestamp) AS first_seen, MAX(timestamp) AS last_seen,
+                COUNT(*) AS sample_count,
+                AVG(cpu_percent) AS cpu_avg, MAX(cpu_percent) AS cpu_max,
+                CAST(AVG(rss_bytes) AS INTEGER) AS rss_avg, MAX(rss_bytes) AS rss_max
+             FROM process_metrics_raw
+             WHERE distro_name = ? AND timestamp >= ? AND timestamp <= ?
+             GROUP BY pid, command
+             ORDER BY cpu_max DESC, cpu_avg DESC
+             LIMIT ?",
+        )
+        .bind(distro.as_str())
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(rows.iter().map(row_to_process_usage).collect())
+    }
+
+    async fn query_top_processes_aggregated(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<ProcessUsage>, DomainError> {
+        // Averages are weighted by the samples of each bucket
+        let rows = sqlx::query(
+            "SELECT pid, MAX(user) AS user, command,
+                MIN(period_start) AS first_seen, MAX(period_end) AS last_seen,
+                SUM(sample_count) AS sample_count,
+                SUM(cpu_avg * sample_count) / SUM(sample_count) AS cpu_avg,
+                MAX(cpu_max) AS cpu_max,
+                CAST(SUM(rss_avg * sample_count) / SUM(sample_count) AS INTEGER) AS rss_avg,
+                MAX(rss_max) AS rss_max
+             FROM process_metrics_aggregated
+             WHERE distro_name = ? AND period_start >= ? AND period_start <= ?
+             GROUP BY pid, command
+             ORDER BY cpu_max DESC, cpu_avg DESC
+             LIMIT ?",
+        )
+        .bind(distro.as_str())
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(rows.iter().map(row_to_process_usage).collect())
+    }
+
+    async fn aggregate_process_buckets(
+        &self,
+        bucket_start: DateTime<Utc>,
+        bucket_end: DateTime<Utc>,
+    ) -> Result<u64, DomainError> {
+        // Same 1-minute truncation as aggregate_raw_buckets, one bucket per
+        // process; the unique index makes buckets already created a no-op.
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO process_metrics_aggregated (
+                distro_name, period_start, period_end, pid, user, command,
+                sample_count, cpu_avg, cpu_max, rss_avg, rss_max
+            )
+            SELECT
+                distro_name,
+                strftime('%Y-%m-%dT%H:%M:00+00:00', timestamp) as period_start,
+                strftime('%Y-%m-%dT%H:%M:00+00:00', timestamp, '+1 minute') as period_end,
+                pid, MAX(user), command,
+                COUNT(*),
+                AVG(cpu_percent), MAX(cpu_percent),
+                CAST(AVG(rss_bytes) AS INTEGER), MAX(rss_bytes)
+            FROM process_metrics_raw
+            WHERE timestamp >= ? AND timestamp < ?
+            GROUP BY distro_name, strftime('%Y-%m-%dT%H:%M', timestamp), pid, command",
+        )
+        .bind(bucket_start.to_rfc3339())
+        .bind(bucket_end.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn purge_process_raw_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let result = sqlx::query("DELETE FROM process_metrics_raw WHERE timestamp < ?")
             .bind(before.to_rfc3339())
             .execute(&self.db.pool)
             .await
@@ -281,17 +523,225 @@ impl MetricsRepositoryPort for SqliteMetricsRepository {
         Ok(result.rows_affected())
     }
 
-    async fn delete_by_distro(&self, distro: &DistroName) -> Result<(), DomainError> {
-        sqlx::query("DELETE FROM metrics_raw WHERE distro_name = ?")
-            .bind(distro.as_str())
+    async fn purge_process_aggregated_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DomainError> {
+        let result = sqlx::query("DELETE FROM process_metrics_aggregated WHERE period_start < ?")
+            .bind(before.to_rfc3339())
             .execute(&self.db.pool)
             .await
             .db_err()?;
-        sqlx::query("DELETE FROM metrics_aggregated WHERE distro_name = ?")
+
+        Ok(result.rows_affected())
+    }
+
+    async fn store_service_metrics(
+        &self,
+        distro: &DistroName,
+        timestamp: DateTime<Utc>,
+        services: &[ServiceMetrics],
+    ) -> Result<(), DomainError> {
+        let timestamp = timestamp.to_rfc3339();
+        let mut tx = self.db.pool.begin().await.db_err()?;
+        for service in services {
+            sqlx::query(
+                "INSERT INTO service_metrics_raw (
+                    distro_name, timestamp, unit, cpu_percent, memory_bytes,
+                    io_read_bytes_per_sec, io_write_bytes_per_sec
+                ) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
             .bind(distro.as_str())
+            .bind(&timestamp)
+            .bind(&service.unit)
+            .bind(service.cpu_percent)
+            .bind(service.memory_bytes as i64)
+            .bind(service.io_read_bytes_per_sec as i64)
+            .bind(service.io_write_bytes_per_sec as i64)
+            .execute(&mut *tx)
+            .await
+            .db_err()?;
+        }
+        tx.commit().await.db_err()?;
+        Ok(())
+    }
+
+    async fn query_service_usage_raw(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ServiceUsage>, DomainError> {
+        let rows = sqlx::query(
+            "SELECT unit,
+                MIN(timestamp) AS first_seen, MAX(timestamp) AS last_seen,
+                COUNT(*) AS sample_count,
+                AVG(cpu_percent) AS cpu_avg, MAX(cpu_percent) AS cpu_max,
+                CAST(AVG(memory_bytes) AS INTEGER) AS memory_avg,
+                MAX(memory_bytes) AS memory_max,
+                CAST(AVG(io_read_bytes_per_sec) AS INTEGER) AS io_read_avg,
+                CAST(AVG(io_write_bytes_per_sec) AS INTEGER) AS io_write_avg
+             FROM service_metrics_raw
+             WHERE distro_name = ? AND timestamp >= ? AND timestamp <= ?
+             GROUP BY unit
+             ORDER BY memory_max DESC, cpu_max DESC",
+        )
+        .bind(distro.as_str())
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(rows.iter().map(row_to_service_usage).collect())
+    }
+
+    async fn query_service_usage_aggregated(
+        &self,
+        distro: &DistroName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ServiceUsage>, DomainError> {
+        // Averages are weighted by the samples of each bucket
+        let rows = sqlx::query(
+            "SELECT unit,
+                MIN(period_start) AS first_seen, MAX(period_end) AS last_seen,
+                SUM(sample_count) AS sample_count,
+                SUM(cpu_avg * sample_count) / SUM(sample_count) AS cpu_avg,
+                MAX(cpu_max) AS cpu_max,
+                CAST(SUM(memory_avg * sample_count) / SUM(sample_count) AS INTEGER) AS memory_avg,
+                MAX(memory_max) AS memory_max,
+                CAST(SUM(io_read_avg * sample_count) / SUM(sample_count) AS INTEGER) AS io_read_avg,
+                CAST(SUM(io_write_avg * sample_count) / SUM(sample_count) AS INTEGER) AS io_write_avg
+             FROM service_metrics_aggregated
+             WHERE distro_name = ? AND period_start >= ? AND period_start <= ?
+             GROUP BY unit
+             ORDER BY memory_max DESC, cpu_max DESC",
+        )
+        .bind(distro.as_str())
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(rows.iter().map(row_to_service_usage).collect())
+    }
+
+    async fn aggregate_service_buckets(
+        &self,
+        bucket_start: DateTime<Utc>,
+        bucket_end: DateTime<Utc>,
+    ) -> Result<u64, DomainError> {
+        // Same 1-minute truncation as aggregate_raw_buckets, one bucket per
+        // unit; the unique index makes buckets already created a no-op.
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO service_metrics_aggregated (
+                distro_name, period_start, period_end, unit, sample_count,
+                cpu_avg, cpu_max, memory_avg, memory_max, io_read_avg, io_write_avg
+            )
+            SELECT
+                distro_name,
+                strftime('%Y-%m-%dT%H:%M:00+00:00', timestamp) as period_start,
+                strftime('%Y-%m-%dT%H:%M:00+00:00', timestamp, '+1 minute') as period_end,
+                unit,
+                COUNT(*),
+                AVG(cpu_percent), MAX(cpu_percent),
+                CAST(AVG(memory_bytes) AS INTEGER), MAX(memory_bytes),
+                CAST(AVG(io_read_bytes_per_sec) AS INTEGER),
+                CAST(AVG(io_write_bytes_per_sec) AS INTEGER)
+            FROM service_metrics_raw
+            WHERE timestamp >= ? AND timestamp < ?
+            GROUP BY distro_name, strftime('%Y-%m-%dT%H:%M', timestamp), unit",
+        )
+        .bind(bucket_start.to_rfc3339())
+        .bind(bucket_end.to_rfc3339())
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn purge_service_raw_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let result = sqlx::query("DELETE FROM service_metrics_raw WHERE timestamp < ?")
+            .bind(before.to_rfc3339())
+            .execute(&self.db.pool)
+            .await
+            .db_err()?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn purge_service_aggregated_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, DomainError> {
+        let result = sqlx::query("DELETE FROM service_metrics_aggregated WHERE period_start < ?")
+            .bind(before.to_rfc3339())
             .execute(&self.db.pool)
             .await
             .db_err()?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn load_settings(&self) -> Result<Option<MetricsSettings>, DomainError> {
+        let row = sqlx::query("SELECT * FROM metrics_settings WHERE id = 1")
+            .fetch_optional(&self.db.pool)
+            .await
+            .db_err()?;
+
+        Ok(row.map(|row| MetricsSettings {
+            collection_interval_secs: row.get::<i64, _>("collection_interval_secs") as u32,
+            raw_retention_hours: row.get::<i64, _>("raw_retention_hours") as u32,
+            minute_retention_hours: row.get::<i64, _>("minute_retention_hours") as u32,
+            hour_retention_days: row.get::<i64, _>("hour_retention_days") as u32,
+            day_retention_days: row.get::<i64, _>("day_retention_days") as u32,
+            openmetrics_enabled: row.get::<i64, _>("openmetrics_enabled") != 0,
+            openmetrics_port: row.get::<i64, _>("openmetrics_port") as u16,
+        }))
+    }
+
+    async fn save_settings(&self, settings: &MetricsSettings) -> Result<(), DomainError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO metrics_settings (
+                id, collection_interval_secs, raw_retention_hours,
+                minute_retention_hours, hour_retention_days, day_retention_days,
+                openmetrics_enabled, openmetrics_port
+            ) VALUES (1, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(settings.collection_interval_secs as i64)
+        .bind(settings.raw_retention_hours as i64)
+        .bind(settings.minute_retention_hours as i64)
+        .bind(settings.hour_retention_days as i64)
+        .bind(settings.day_retention_days as i64)
+        .bind(settings.openmetrics_enabled as i64)
+        .bind(settings.openmetrics_port as i64)
+        .execute(&self.db.pool)
+        .await
+        .db_err()?;
+
+        Ok(())
+    }
+
+    async fn delete_by_distro(&self, distro: &DistroName) -> Result<(), DomainError> {
+        for table in [
+            "metrics_raw",
+            "metrics_aggregated",
+            "metrics_hourly",
+            "metrics_daily",
+            "process_metrics_raw",
+            "process_metrics_aggregated",
+            "service_metrics_raw",
+            "service_metrics_aggregated",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE distro_name = ?"))
+                .bind(distro.as_str())
+                .execute(&self.db.pool)
+                .await
+                .db_err()?;
+        }
         Ok(())
     }
 }
@@ -301,6 +751,7 @@ mod tests {
     use super::*;
     use crate::domain::entities::monitoring::*;
     use crate::infrastructure::sqlite::adapter::SqliteDb;
+    use chrono::Timelike;
 
     async fn test_db() -> SqliteDb {
         SqliteDb::new("sqlite::memory:").await.unwrap()
@@ -414,4 +865,255 @@ mod tests {
             .unwrap();
         assert!(rows.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_rollup_tiers_weight_averages_and_skip_existing_buckets() {
+        let db = test_db().await;
+        let repo = SqliteMetricsRepository::new(db);
+        let distro = DistroName::new("Ubuntu").unwrap();
+
+        // Three samples in one minute and one in the next, two hours ago
+        let hour = (Utc::now() - chrono::Duration::hours(2))
+            .with_minute(0)
+            .and_then(|t| t.with_second(0))
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap();
+        for (offset, cpu) in [(0, 10.0), (10, 20.0), (20, 30.0), (70, 80.0)] {
+            let mut metrics = make_metrics("Ubuntu", cpu, 4_000_000_000, 50.0);
+            metrics.timestamp = hour + chrono::Duration::seconds(offset);
+            repo.store_raw(&metrics).await.unwrap();
+        }
+        let end = hour + chrono::Duration::hours(1);
+        assert_eq!(repo.aggregate_raw_buckets(hour, end).await.unwrap(), 2);
+        // Buckets are only created once
+        assert_eq!(
+            repo.rollup_buckets(MetricsTier::Minute, hour, end)
+                .await
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(
+            repo.rollup_buckets(MetricsTier::Hour, hour, end)
+                .await
+                .unwrap(),
+            1
+        );
+        let hourly = repo
+            .query_aggregated(&distro, MetricsTier::Hour, hour, end)
+            .await
+            .unwrap();
+        assert_eq!(hourly.len(), 1);
+        assert_eq!(hourly[0].period_start, hour);
+        assert_eq!(hourly[0].period_end, end);
+        assert_eq!(hourly[0].sample_count, 4);
+        assert!((hourly[0].cpu_avg - 35.0).abs() < 0.01);
+        assert!((hourly[0].cpu_min - 10.0).abs() < 0.01);
+        assert!((hourly[0].cpu_max - 80.0).abs() < 0.01);
+
+        let day = hour.with_hour(0).unwrap();
+        let day_end = day + chrono::Duration::days(1);
+        repo.rollup_buckets(MetricsTier::Day, day, day_end)
+            .await
+            .unwrap();
+        let daily = repo
+            .query_aggregated(&distro, MetricsTier::Day, day, day_end)
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].sample_count, 4);
+        assert!((daily[0].cpu_avg - 35.0).abs() < 0.01);
+
+        let deleted = repo
+            .purge_aggregated_before(MetricsTier::Hour, end)
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+        repo.delete_by_distro(&distro).await.unwrap();
+        assert!(
+            repo.query_aggregated(&distro, MetricsTier::Day, day, day_end)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_settings_round_trip() {
+        let db = test_db().await;
+        let repo = SqliteMetricsRepository::new(db);
+        assert!(repo.load_settings().await.unwrap().is_none());
+
+        let settings = MetricsSettings {
+            collection_interval_secs: 5,
+            hour_retention_days: 60,
+            openmetrics_enabled: true,
+            openmetrics_port: 9500,
+            ..Default::default()
+        };
+        repo.save_settings(&settings).await.unwrap();
+        repo.save_settings(&settings).await.unwrap();
+        assert_eq!(repo.load_settings().await.unwrap(), Some(settings));
+    }
+
+    fn make_sample(pid: u32, command: &str, cpu: f64, rss: u64) -> ProcessSample {
+        ProcessSample {
+            pid,
+            user: "root".to_string(),
+            command: command.to_string(),
+            cpu_percent: cpu,
+            rss_bytes: rss,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_history_ranks_top_offenders() {
+        let db = test_db().await;
+        let repo = SqliteMetricsRepository::new(db);
+        let distro = DistroName::new("Ubuntu").unwrap();
+        let start = Utc::now() - chrono::Duration::minutes(10);
+
+        for (offset, build_cpu) in [(0, 5.0), (10, 95.0), (20, 10.0)] {
+            repo.store_process_samples(
+                &distro,
+                start + chrono::Duration::seconds(offset),
+                &[
+                    make_sample(42, "cargo build", build_cpu, 2_000_000),
+                    make_sample(7, "postgres", 20.0, 8_000_000),
+                ],
+            )
+            .await
+            .unwrap();
+        }
+        repo.store_process_samples(
+            &DistroName::new("Debian").unwrap(),
+            start,
+            &[make_sample(1, "busy", 100.0, 1_000)],
+        )
+        .await
+        .unwrap();
+
+        let from = start - chrono::Duration::minutes(1);
+        let to = start + chrono::Duration::minutes(1);
+        let top = repo
+            .query_top_processes_raw(&distro, from, to, 10)
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].command, "cargo build");
+        assert_eq!(top[0].sample_count, 3);
+        assert!((top[0].cpu_max - 95.0).abs() < 0.01);
+        assert!((top[0].cpu_avg - 36.67).abs() < 0.01);
+        assert_eq!(top[1].rss_max, 8_000_000);
+
+        // Every sample falls in one or two minute buckets; aggregating twice is a no-op
+        let created = repo
+            .aggregate_process_buckets(from, Utc::now())
+            .await
+            .unwrap();
+        assert!(created >= 3);
+        assert_eq!(
+            repo.aggregate_process_buckets(from, Utc::now())
+                .await
+                .unwrap(),
+            0
+        );
+        let top = repo
+            .query_top_processes_aggregated(&distro, from - chrono::Duration::minutes(1), to, 1)
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].command, "cargo build");
+        assert_eq!(top[0].sample_count, 3);
+        assert!((top[0].cpu_avg - 36.67).abs() < 0.01);
+
+        let deleted = repo
+            .purge_process_raw_before(Utc::now() + chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 7);
+        repo.delete_by_distro(&distro).await.unwrap();
+        let top = repo
+            .query_top_processes_aggregated(&distro, from - chrono::Duration::minutes(1), to, 10)
+            .await
+            .unwrap();
+        assert!(top.is_empty());
+    }
+
+    fn make_service(unit: &str, cpu: f64, memory: u64) -> ServiceMetrics {
+        ServiceMetrics {
+            unit: unit.to_string(),
+            cpu_percent: cpu,
+            memory_bytes: memory,
+            io_read_bytes_per_sec: 1_000,
+            io_write_bytes_per_sec: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_history_ranks_by_memory() {
+        let db = test_db().await;
+        let repo = SqliteMetricsRepository::new(db);
+        let distro = DistroName::new("Ubuntu").unwrap();
+        let start = Utc::now() - chrono::Duration::minutes(10);
+
+        for (offset, redis_memory) in [(0, 100_000_000), (10, 900_000_000), (20, 200_000_000)] {
+            repo.store_service_metrics(
+                &distro,
+                start + chrono::Duration::seconds(offset),
+                &[
+                    make_service("postgresql.service", 40.0, 300_000_000),
+                    make_service("redis-server.service", 1.0, redis_memory),
+                ],
+            )
+            .await
+            .unwrap();
+        }
+
+        let from = start - chrono::Duration::minutes(1);
+        let to = start + chrono::Duration::minutes(1);
+        let usage = repo
+            .query_service_usage_raw(&distro, from, to)
+            .await
+            .unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].unit, "redis-server.service");
+        assert_eq!(usage[0].sample_count, 3);
+        assert_eq!(usage[0].memory_max, 900_000_000);
+        assert_eq!(usage[0].memory_avg, 400_000_000);
+        assert!((usage[1].cpu_avg - 40.0).abs() < 0.01);
+        assert_eq!(usage[1].io_read_avg, 1_000);
+
+        // Aggregating twice is a no-op
+        let created = repo
+            .aggregate_service_buckets(from, Utc::now())
+            .await
+            .unwrap();
+        assert!(created >= 2);
+        assert_eq!(
+            repo.aggregate_service_buckets(from, Utc::now())
+                .await
+                .unwrap(),
+            0
+        );
+        let usage = repo
+            .query_service_usage_aggregated(&distro, from - chrono::Duration::minutes(1), to)
+            .await
+            .unwrap();
+        assert_eq!(usage[0].unit, "redis-server.service");
+        assert_eq!(usage[0].sample_count, 3);
+        assert_eq!(usage[0].memory_avg, 400_000_000);
+
+        let deleted = repo
+            .purge_service_raw_before(Utc::now() + chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 6);
+        repo.delete_by_distro(&distro).await.unwrap();
+        let usage = repo
+            .query_service_usage_aggregated(&distro, from - chrono::Duration::minutes(1), to)
+            .await
+            .unwrap();
+        assert!(usage.is_empty());
+    }
 }
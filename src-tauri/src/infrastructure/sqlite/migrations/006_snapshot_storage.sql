@@ -0,0 +1 @@
+ALTER TABLE snapshots ADD COLUMN storage TEXT NOT NULL DEFAULT 'file';
@@ -0,0 +1,11 @@
+-- Content-defined chunk manifests for snapshots stored in the chunk store.
+-- Chunks themselves live on disk; a chunk is garbage once no row references it.
+CREATE TABLE IF NOT EXISTS snapshot_chunks (
+    snapshot_id TEXT NOT NULL REFERENCES snapshots(id),
+    seq INTEGER NOT NULL,
+    chunk_hash TEXT NOT NULL,
+    length INTEGER NOT NULL,
+    PRIMARY KEY (snapshot_id, seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_chunks_hash ON snapshot_chunks(chunk_hash);
@@ -0,0 +1,24 @@
+-- Recurring automatic snapshots and the snapshots each schedule created.
+-- Retention only ever prunes snapshots linked here, never manual ones.
+CREATE TABLE IF NOT EXISTS snapshot_schedules (
+    id TEXT PRIMARY KEY,
+    distro_name TEXT NOT NULL,
+    frequency TEXT NOT NULL,
+    format TEXT NOT NULL,
+    storage TEXT NOT NULL DEFAULT 'file',
+    output_dir TEXT NOT NULL,
+    keep_hourly INTEGER NOT NULL DEFAULT 0,
+    keep_daily INTEGER NOT NULL DEFAULT 0,
+    keep_weekly INTEGER NOT NULL DEFAULT 0,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    last_run_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS schedule_snapshots (
+    schedule_id TEXT NOT NULL REFERENCES snapshot_schedules(id),
+    snapshot_id TEXT NOT NULL REFERENCES snapshots(id),
+    PRIMARY KEY (schedule_id, snapshot_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_schedule_snapshots_snapshot ON schedule_snapshots(snapshot_id);
@@ -0,0 +1,2 @@
+-- Pinned snapshots are skipped by retention and deletion sweeps.
+ALTER TABLE snapshots ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+-- User-defined snapshot labels ("golden", "pre-upgrade", ...), lowercase.
+CREATE TABLE IF NOT EXISTS snapshot_tags (
+    snapshot_id TEXT NOT NULL REFERENCES snapshots(id),
+    tag TEXT NOT NULL,
+    PRIMARY KEY (snapshot_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshot_tags_tag ON snapshot_tags(tag);
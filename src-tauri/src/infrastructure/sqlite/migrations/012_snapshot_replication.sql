@@ -0,0 +1,24 @@
+-- Offsite replication: the S3-compatible target (a single row) and the
+-- per-snapshot upload state, kept so interrupted multipart uploads resume.
+CREATE TABLE IF NOT EXISTS replication_target (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    endpoint TEXT NOT NULL,
+    region TEXT NOT NULL,
+    bucket TEXT NOT NULL,
+    prefix TEXT NOT NULL DEFAULT '',
+    access_key_id TEXT NOT NULL,
+    secret_access_key TEXT NOT NULL,
+    path_style INTEGER NOT NULL DEFAULT 1,
+    auto_replicate INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS snapshot_replications (
+    snapshot_id TEXT PRIMARY KEY REFERENCES snapshots(id),
+    object_key TEXT NOT NULL,
+    status TEXT NOT NULL,
+    upload_id TEXT,
+    part_size INTEGER NOT NULL,
+    bytes_uploaded INTEGER NOT NULL DEFAULT 0,
+    total_bytes INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Key-derivation parameters of encrypted snapshots (JSON), NULL for plain archives.
+ALTER TABLE snapshots ADD COLUMN encryption TEXT;
@@ -0,0 +1,11 @@
+-- Per-distro opt-in safety snapshots taken before destructive operations.
+-- `operations` is a comma-separated list such as "delete_distro,resize_vhd".
+CREATE TABLE IF NOT EXISTS safety_snapshot_policies (
+    distro_name TEXT PRIMARY KEY,
+    operations TEXT NOT NULL DEFAULT '',
+    format TEXT NOT NULL,
+    output_dir TEXT NOT NULL,
+    keep_last INTEGER NOT NULL DEFAULT 0,
+    max_age_hours INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL
+);
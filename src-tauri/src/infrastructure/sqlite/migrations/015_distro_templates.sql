@@ -0,0 +1,13 @@
+-- Templates new distros are provisioned from: a snapshot, a bootstrap script
+-- and a wsl.conf overlay. `parameters` and `wsl_config` are JSON.
+CREATE TABLE IF NOT EXISTS distro_templates (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    snapshot_id TEXT NOT NULL,
+    script TEXT NOT NULL DEFAULT '',
+    parameters TEXT NOT NULL DEFAULT '[]',
+    wsl_config TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
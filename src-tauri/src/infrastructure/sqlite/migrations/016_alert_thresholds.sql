@@ -0,0 +1,9 @@
+-- Alert thresholds: global defaults (distro_name = '') and per-distro overrides.
+-- Alert types without a stored default use the built-in one.
+CREATE TABLE IF NOT EXISTS alert_thresholds (
+    distro_name TEXT NOT NULL DEFAULT '',
+    alert_type TEXT NOT NULL,
+    threshold_percent REAL NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (distro_name, alert_type)
+);
@@ -0,0 +1,8 @@
+-- Alert lifecycle: an alert fires, then resolves once its value drops below the clear threshold
+ALTER TABLE alert_log ADD COLUMN resolved_at TEXT;
+ALTER TABLE alert_log ADD COLUMN resolved_value REAL;
+-- Alerts logged before lifecycles existed are resolved when they were logged
+UPDATE alert_log SET resolved_at = timestamp WHERE resolved_at IS NULL;
+-- Alert rules: sustained duration and hysteresis
+ALTER TABLE alert_thresholds ADD COLUMN sustained_secs INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE alert_thresholds ADD COLUMN clear_percent REAL;
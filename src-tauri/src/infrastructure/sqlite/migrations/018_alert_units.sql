@@ -0,0 +1,3 @@
+-- Alert units: thresholds and alert values may be absolute instead of percent
+ALTER TABLE alert_log ADD COLUMN unit TEXT NOT NULL DEFAULT 'percent';
+ALTER TABLE alert_thresholds ADD COLUMN unit TEXT NOT NULL DEFAULT 'percent';
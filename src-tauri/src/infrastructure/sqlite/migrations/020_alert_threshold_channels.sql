@@ -0,0 +1,2 @@
+-- Alert rules route to notification channels (JSON array of channel ids)
+ALTER TABLE alert_thresholds ADD COLUMN channels TEXT NOT NULL DEFAULT '[]';
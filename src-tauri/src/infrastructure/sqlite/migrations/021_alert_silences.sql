@@ -0,0 +1,24 @@
+-- Alert silences (one-off) and maintenance windows (recurring, local time).
+-- Empty `distro_name` / `alert_type` match every distro / alert type.
+CREATE TABLE IF NOT EXISTS alert_silences (
+    id TEXT PRIMARY KEY,
+    distro_name TEXT,
+    alert_type TEXT,
+    reason TEXT NOT NULL,
+    starts_at TEXT NOT NULL,
+    ends_at TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_silences_ends_at ON alert_silences(ends_at);
+
+CREATE TABLE IF NOT EXISTS maintenance_windows (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    distro_name TEXT,
+    alert_type TEXT,
+    days TEXT NOT NULL DEFAULT '',
+    start_time TEXT NOT NULL,
+    duration_minutes INTEGER NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1
+);
@@ -0,0 +1,2 @@
+-- Silence or maintenance window an alert fired during
+ALTER TABLE alert_log ADD COLUMN suppressed_by TEXT;
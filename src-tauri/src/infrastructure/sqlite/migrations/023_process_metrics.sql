@@ -0,0 +1,35 @@
+-- Per-process history: the top processes of each distro, sampled every 10s
+-- Retention: samples ~1h, 1min buckets ~24h
+
+CREATE TABLE IF NOT EXISTS process_metrics_raw (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    distro_name TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    user TEXT NOT NULL,
+    command TEXT NOT NULL,
+    -- CPU used since the previous sample, in percent of one core
+    cpu_percent REAL NOT NULL,
+    rss_bytes INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS process_metrics_aggregated (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    distro_name TEXT NOT NULL,
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    user TEXT NOT NULL,
+    command TEXT NOT NULL,
+    sample_count INTEGER NOT NULL,
+    cpu_avg REAL NOT NULL,
+    cpu_max REAL NOT NULL,
+    rss_avg INTEGER NOT NULL,
+    rss_max INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_process_raw_distro_ts ON process_metrics_raw(distro_name, timestamp);
+CREATE INDEX IF NOT EXISTS idx_process_raw_ts ON process_metrics_raw(timestamp);
+-- One bucket per process and minute: re-aggregating a window is a no-op
+CREATE UNIQUE INDEX IF NOT EXISTS idx_process_agg_bucket
+    ON process_metrics_aggregated(distro_name, period_start, pid, command);
@@ -0,0 +1,67 @@
+-- Multi-tier rollups: 1min buckets roll up into 1h buckets, those into 1d
+-- buckets (same columns as metrics_aggregated), plus the runtime settings
+-- of the metrics pipeline (a single row).
+
+-- Each 1min bucket was re-inserted on every aggregation pass: keep the first
+DELETE FROM metrics_aggregated WHERE id NOT IN (
+    SELECT MIN(id) FROM metrics_aggregated GROUP BY distro_name, period_start
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_metrics_agg_bucket
+    ON metrics_aggregated(distro_name, period_start);
+
+CREATE TABLE IF NOT EXISTS metrics_hourly (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    distro_name TEXT NOT NULL,
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    sample_count INTEGER NOT NULL,
+    cpu_min REAL NOT NULL,
+    cpu_avg REAL NOT NULL,
+    cpu_max REAL NOT NULL,
+    mem_used_min INTEGER NOT NULL,
+    mem_used_avg INTEGER NOT NULL,
+    mem_used_max INTEGER NOT NULL,
+    mem_total INTEGER NOT NULL,
+    disk_min REAL NOT NULL,
+    disk_avg REAL NOT NULL,
+    disk_max REAL NOT NULL,
+    net_rx_total INTEGER NOT NULL,
+    net_tx_total INTEGER NOT NULL,
+    net_rx_max_rate INTEGER NOT NULL,
+    net_tx_max_rate INTEGER NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_metrics_hourly_bucket
+    ON metrics_hourly(distro_name, period_start);
+
+CREATE TABLE IF NOT EXISTS metrics_daily (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    distro_name TEXT NOT NULL,
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    sample_count INTEGER NOT NULL,
+    cpu_min REAL NOT NULL,
+    cpu_avg REAL NOT NULL,
+    cpu_max REAL NOT NULL,
+    mem_used_min INTEGER NOT NULL,
+    mem_used_avg INTEGER NOT NULL,
+    mem_used_max INTEGER NOT NULL,
+    mem_total INTEGER NOT NULL,
+    disk_min REAL NOT NULL,
+    disk_avg REAL NOT NULL,
+    disk_max REAL NOT NULL,
+    net_rx_total INTEGER NOT NULL,
+    net_tx_total INTEGER NOT NULL,
+    net_rx_max_rate INTEGER NOT NULL,
+    net_tx_max_rate INTEGER NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_metrics_daily_bucket
+    ON metrics_daily(distro_name, period_start);
+
+CREATE TABLE IF NOT EXISTS metrics_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    collection_interval_secs INTEGER NOT NULL,
+    raw_retention_hours INTEGER NOT NULL,
+    minute_retention_hours INTEGER NOT NULL,
+    hour_retention_days INTEGER NOT NULL,
+    day_retention_days INTEGER NOT NULL
+);
@@ -0,0 +1,3 @@
+-- OpenMetrics endpoint: serve the latest metrics to scrapers on loopback
+ALTER TABLE metrics_settings ADD COLUMN openmetrics_enabled INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE metrics_settings ADD COLUMN openmetrics_port INTEGER NOT NULL DEFAULT 9469;
@@ -0,0 +1,36 @@
+-- Per-service history: the cgroup v2 usage of the systemd units of distros
+-- booted with systemd, sampled every 10s
+-- Retention: samples ~1h, 1min buckets ~24h
+
+CREATE TABLE IF NOT EXISTS service_metrics_raw (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    distro_name TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    -- CPU used since the previous sample, in percent of one core
+    cpu_percent REAL NOT NULL,
+    memory_bytes INTEGER NOT NULL,
+    io_read_bytes_per_sec INTEGER NOT NULL,
+    io_write_bytes_per_sec INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS service_metrics_aggregated (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    distro_name TEXT NOT NULL,
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    sample_count INTEGER NOT NULL,
+    cpu_avg REAL NOT NULL,
+    cpu_max REAL NOT NULL,
+    memory_avg INTEGER NOT NULL,
+    memory_max INTEGER NOT NULL,
+    io_read_avg INTEGER NOT NULL,
+    io_write_avg INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_service_raw_distro_ts ON service_metrics_raw(distro_name, timestamp);
+CREATE INDEX IF NOT EXISTS idx_service_raw_ts ON service_metrics_raw(timestamp);
+-- One bucket per unit and minute: re-aggregating a window is a no-op
+CREATE UNIQUE INDEX IF NOT EXISTS idx_service_agg_bucket
+    ON service_metrics_aggregated(distro_name, period_start, unit);
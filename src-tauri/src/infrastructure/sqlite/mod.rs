@@ -1,7 +1,12 @@
 pub mod adapter;
 pub mod alert_repository;
 pub mod metrics_repository;
+pub mod notification_repository;
 pub mod port_forwarding_repository;
+pub mod replication_repository;
+pub mod safety_policy_repository;
+pub mod schedule_repository;
+pub mod template_repository;
 
 use crate::domain::errors::DomainError;
 
@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use super::SqlxResultExt;
+use crate::domain::entities::notification_channel::{
+    DeliveryStatus, NotificationChannel, NotificationDelivery,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::notification_repository::NotificationRepositoryPort;
+
+/// Deliveries kept in the log; older ones are dropped as new ones are logged.
+const DELIVERY_LOG_SIZE: i64 = 1000;
+
+pub struct SqliteNotificationRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteNotificationRepository {
+    pub fn new(db: crate::infrastructure::sqlite::adapter::SqliteDb) -> Self {
+        Self { pool: db.pool }
+    }
+}
+
+fn parse_time(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn row_to_channel(row: &sqlx::sqlite::SqliteRow) -> Result<NotificationChannel, DomainError> {
+    let config = serde_json::from_str(row.get::<&str, _>("config")).map_err(|e| {
+        DomainError::DatabaseError(format!("invalid notification channel settings: {}", e))
+    })?;
+    Ok(NotificationChannel {
+        id: row.get("id"),
+        name: row.get("name"),
+        enabled: row.get::<i32, _>("enabled") != 0,
+        config,
+    })
+}
+
+fn row_to_delivery(row: &sqlx::sqlite::SqliteRow) -> NotificationDelivery {
+    NotificationDelivery {
+        id: row.get("id"),
+        channel_id: row.get("channel_id"),
+        channel_name: row.get("channel_name"),
+        alert_id: row.get("alert_id"),
+        distro_name: row.get("distro_name"),
+        title: row.get("title"),
+        status: row
+            .get::<&str, _>("status")
+            .parse()
+            .unwrap_or(DeliveryStatus::Failed),
+        attempts: row.get::<i64, _>("attempts") as u32,
+        error: row.get("error"),
+        timestamp: parse_time(row.get("timestamp")),
+    }
+}
+
+#[async_trait]
+impl NotificationRepositoryPort for SqliteNotificationRepository {
+    async fn list_channels(&self) -> Result<Vec<NotificationChannel>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM notification_channels ORDER BY name")
+            .fetch_all(&self.pool)
+            .await
+            .db_err()?;
+        rows.iter().map(row_to_channel).collect()
+    }
+
+    async fn save_channel(&self, channel: &NotificationChannel) -> Result<(), DomainError> {
+        let config = serde_json::to_string(&channel.config)
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO notification_channels (id, name, enabled, config)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(&channel.id)
+        .bind(&channel.name)
+        .bind(channel.enabled as i32)
+        .bind(config)
+        .execute(&self.pool)
+        .await
+        .db_err()?;
+        Ok(())
+    }
+
+    async fn delete_channel(&self, id: &str) -> Result<(), DomainError> {
+        sqlx::query("DELETE FROM notification_channels WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        Ok(())
+    }
+
+    async fn record_delivery(&self, delivery: &NotificationDelivery) -> Result<i64, DomainError> {
+        let id = sqlx::query(
+            "INSERT INTO notification_deliveries (channel_id, channel_name, alert_id, distro_name, title, status, attempts, error, timestamp)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&delivery.channel_id)
+        .bind(&delivery.channel_name)
+        .bind(delivery.alert_id)
+        .bind(&delivery.distro_name)
+        .bind(&delivery.title)
+        .bind(delivery.status.to_string())
+        .bind(delivery.attempts as i64)
+        .bind(&delivery.error)
+        .bind(delivery.timestamp.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .db_err()?
+        .last_insert_rowid();
+
+        sqlx::query("DELETE FROM notification_deliveries WHERE id <= ?")
+            .bind(id - DELIVERY_LOG_SIZE)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        Ok(id)
+    }
+
+    async fn list_deliveries(&self, limit: u32) -> Result<Vec<NotificationDelivery>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM notification_deliveries ORDER BY id DESC LIMIT ?")
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .db_err()?;
+        Ok(rows.iter().map(row_to_delivery).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::notification_channel::ChannelConfig;
+    use crate::infrastructure::sqlite::adapter::SqliteDb;
+
+    async fn test_repo() -> SqliteNotificationRepository {
+        SqliteNotificationRepository::new(SqliteDb::new("sqlite::memory:").await.unwrap())
+    }
+
+    fn delivery(status: DeliveryStatus) -> NotificationDelivery {
+        NotificationDelivery {
+            id: 0,
+            channel_id: "hook".into(),
+            channel_name: "Slack".into(),
+            alert_id: Some(3),
+            distro_name: "Ubuntu".into(),
+            title: "CPU Alert - Ubuntu".into(),
+            status,
+            attempts: 1,
+            error: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_list_delete_channels() {
+        let repo = test_repo().await;
+        let mut channel = NotificationChannel {
+            id: "hook".into(),
+            name: "Slack".into(),
+            enabled: true,
+            config: ChannelConfig::Webhook {
+                url: "https://hooks.slack.com/services/T/B/X".into(),
+            },
+        };
+        repo.save_channel(&channel).await.unwrap();
+        channel.enabled = false;
+        repo.save_channel(&channel).await.unwrap();
+
+        let channels = repo.list_channels().await.unwrap();
+        assert_eq!(channels, vec![channel]);
+
+        repo.delete_channel("hook").await.unwrap();
+        assert!(repo.list_channels().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_newest_first() {
+        let repo = test_repo().await;
+        repo.record_delivery(&delivery(DeliveryStatus::Failed))
+            .await
+            .unwrap();
+        let id = repo
+            .record_delivery(&delivery(DeliveryStatus::Delivered))
+            .await
+            .unwrap();
+
+        let deliveries = repo.list_deliveries(10).await.unwrap();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].id, id);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[1].status, DeliveryStatus::Failed);
+        assert_eq!(deliveries[1].alert_id, Some(3));
+    }
+}
@@ -0,0 +1,271 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use super::SqlxResultExt;
+use crate::domain::entities::replication::{
+    ReplicationStatus, ReplicationTarget, SnapshotReplication,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::replication_repository::ReplicationRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+pub struct SqliteReplicationRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteReplicationRepository {
+    pub fn new(db: crate::infrastructure::sqlite::adapter::SqliteDb) -> Self {
+        Self { pool: db.pool }
+    }
+}
+
+fn status_to_str(status: &ReplicationStatus) -> String {
+    match status {
+        ReplicationStatus::Uploading => "uploading".to_string(),
+        ReplicationStatus::Replicated => "replicated".to_string(),
+        ReplicationStatus::Failed(reason) => format!("failed: {}", reason),
+    }
+}
+
+fn parse_status(s: &str) -> ReplicationStatus {
+    match s {
+        "uploading" => ReplicationStatus::Uploading,
+        "replicated" => ReplicationStatus::Replicated,
+        other => ReplicationStatus::Failed(
+            other
+                .strip_prefix("failed:")
+                .unwrap_or(other)
+                .trim()
+                .to_string(),
+        ),
+    }
+}
+
+fn row_to_replication(row: &sqlx::sqlite::SqliteRow) -> SnapshotReplication {
+    let updated_at: String = row.get("updated_at");
+    SnapshotReplication {
+        snapshot_id: SnapshotId::from_string(row.get("snapshot_id")),
+        object_key: row.get("object_key"),
+        status: parse_status(row.get("status")),
+        upload_id: row.get("upload_id"),
+        part_size: row.get::<i64, _>("part_size") as u64,
+        bytes_uploaded: row.get::<i64, _>("bytes_uploaded") as u64,
+        total_bytes: row.get::<i64, _>("total_bytes") as u64,
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+#[async_trait]
+impl ReplicationRepositoryPort for SqliteReplicationRepository {
+    async fn get_target(&self) -> Result<Option<ReplicationTarget>, DomainError> {
+        let row = sqlx::query("SELECT * FROM replication_target WHERE id = 1")
+            .fetch_optional(&self.pool)
+            .await
+            .db_err()?;
+
+        Ok(row.map(|row| ReplicationTarget {
+            endpoint: row.get("endpoint"),
+            region: row.get("region"),
+            bucket: row.get("bucket"),
+            prefix: row.get("prefix"),
+            access_key_id: row.get("access_key_id"),
+            secret_access_key: row.get("secret_access_key"),
+            path_style: row.get("path_style"),
+            auto_replicate: row.get("auto_replicate"),
+        }))
+    }
+
+    async fn save_target(&self, target: Option<ReplicationTarget>) -> Result<(), DomainError> {
+        let Some(target) = target.as_ref() else {
+            sqlx::query("DELETE FROM replication_target")
+                .execute(&self.pool)
+                .await
+                .db_err()?;
+            return Ok(());
+        };
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO replication_target (id, endpoint, region, bucket, prefix, access_key_id, secret_access_key, path_style, auto_replicate)
+             VALUES (1, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&target.endpoint)
+        .bind(&target.region)
+        .bind(&target.bucket)
+        .bind(&target.prefix)
+        .bind(&target.access_key_id)
+        .bind(&target.secret_access_key)
+        .bind(target.path_style)
+        .bind(target.auto_replicate)
+        .execute(&self.pool)
+        .await
+        .db_err()?;
+
+        Ok(())
+    }
+
+    async fn save(&self, replication: &SnapshotReplication) -> Result<(), DomainError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO snapshot_replications (snapshot_id, object_key, status, upload_id, part_size, bytes_uploaded, total_bytes, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(replication.snapshot_id.as_str())
+        .bind(&replication.object_key)
+        .bind(status_to_str(&replication.status))
+        .bind(&replication.upload_id)
+        .bind(replication.part_size as i64)
+        .bind(replication.bytes_uploaded as i64)
+        .bind(replication.total_bytes as i64)
+        .bind(replication.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .db_err()?;
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &SnapshotId) -> Result<Option<SnapshotReplication>, DomainError> {
+        let row = sqlx::query("SELECT * FROM snapshot_replications WHERE snapshot_id = ?")
+            .bind(id.as_str())
+            .fetch_optional(&self.pool)
+            .await
+            .db_err()?;
+
+        Ok(row.as_ref().map(row_to_replication))
+    }
+
+    async fn list(&self) -> Result<Vec<SnapshotReplication>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM snapshot_replications ORDER BY updated_at DESC")
+            .fetch_all(&self.pool)
+            .await
+            .db_err()?;
+
+        Ok(rows.iter().map(row_to_replication).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStatus, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+    use crate::infrastructure::sqlite::adapter::{SqliteDb, SqliteSnapshotRepository};
+
+    async fn test_db() -> SqliteDb {
+        SqliteDb::new("sqlite::memory:").await.unwrap()
+    }
+
+    fn make_target() -> ReplicationTarget {
+        ReplicationTarget {
+            endpoint: "http://localhost:9000".into(),
+            region: "us-east-1".into(),
+            bucket: "snapshots".into(),
+            prefix: "laptop".into(),
+            access_key_id: "minioadmin".into(),
+            secret_access_key: "minioadmin".into(),
+            path_style: true,
+            auto_replicate: false,
+        }
+    }
+
+    fn make_snapshot(id: &str) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: format!("/backups/{id}.tar"),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    fn make_replication(id: &str, status: ReplicationStatus) -> SnapshotReplication {
+        SnapshotReplication {
+            snapshot_id: SnapshotId::from_string(id.into()),
+            object_key: format!("laptop/Ubuntu/{id}.tar"),
+            status,
+            upload_id: Some("upload-1".into()),
+            part_size: 8 * 1024 * 1024,
+            bytes_uploaded: 1024,
+            total_bytes: 4096,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_target_roundtrip_and_clear() {
+        let repo = SqliteReplicationRepository::new(test_db().await);
+        assert!(repo.get_target().await.unwrap().is_none());
+
+        repo.save_target(Some(make_target())).await.unwrap();
+        let mut changed = make_target();
+        changed.auto_replicate = true;
+        repo.save_target(Some(changed.clone())).await.unwrap();
+        assert_eq!(repo.get_target().await.unwrap(), Some(changed));
+
+        repo.save_target(None).await.unwrap();
+        assert!(repo.get_target().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replication_state_roundtrip_and_follows_snapshot_delete() {
+        let db = test_db().await;
+        let repo = SqliteReplicationRepository::new(db.clone());
+        let snapshots = SqliteSnapshotRepository::new(db);
+        for id in ["a", "b"] {
+            snapshots.save(&make_snapshot(id)).await.unwrap();
+        }
+
+        repo.save(&make_replication("a", ReplicationStatus::Uploading))
+            .await
+            .unwrap();
+        repo.save(&make_replication(
+            "b",
+            ReplicationStatus::Failed("connection reset".into()),
+        ))
+        .await
+        .unwrap();
+
+        let a = repo
+            .get(&SnapshotId::from_string("a".into()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(a.status, ReplicationStatus::Uploading);
+        assert_eq!(a.upload_id.as_deref(), Some("upload-1"));
+        assert_eq!(a.bytes_uploaded, 1024);
+        let b = repo
+            .get(&SnapshotId::from_string("b".into()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            b.status,
+            ReplicationStatus::Failed("connection reset".into())
+        );
+
+        snapshots
+            .delete(&SnapshotId::from_string("a".into()))
+            .await
+            .unwrap();
+        let left = repo.list().await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].snapshot_id.as_str(), "b");
+    }
+}
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use super::SqlxResultExt;
+use crate::domain::entities::safety_snapshot::{DestructiveOperation, SafetySnapshotPolicy};
+use crate::domain::entities::snapshot::ExportFormat;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::safety_policy_repository::SafetyPolicyRepositoryPort;
+use crate::domain::value_objects::DistroName;
+
+pub struct SqliteSafetyPolicyRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteSafetyPolicyRepository {
+    pub fn new(db: crate::infrastructure::sqlite::adapter::SqliteDb) -> Self {
+        Self { pool: db.pool }
+    }
+}
+
+fn row_to_policy(row: &sqlx::sqlite::SqliteRow) -> Result<SafetySnapshotPolicy, DomainError> {
+    let format = match row.get::<&str, _>("format") {
+        "vhdx" => ExportFormat::Vhd,
+        "tar.zst" => ExportFormat::TarZst,
+        "tar.gz" => ExportFormat::TarGz,
+        _ => ExportFormat::Tar,
+    };
+    // Unknown operations (from a newer version) are ignored rather than fatal.
+    let operations = row
+        .get::<&str, _>("operations")
+        .split(',')
+        .filter_map(DestructiveOperation::parse)
+        .collect();
+    let updated_at: String = row.get("updated_at");
+
+    Ok(SafetySnapshotPolicy {
+        distro_name: DistroName::new(row.get::<&str, _>("distro_name"))?,
+        operations,
+        format,
+        output_dir: row.get("output_dir"),
+        keep_last: row.get::<i64, _>("keep_last") as u32,
+        max_age_hours: row.get::<i64, _>("max_age_hours") as u32,
+        updated_at: DateTime::parse_from_rfc3339(&updated_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+#[async_trait]
+impl SafetyPolicyRepositoryPort for SqliteSafetyPolicyRepository {
+    async fn save(&self, policy: &SafetySnapshotPolicy) -> Result<(), DomainError> {
+        let operations: Vec<&str> = policy.operations.iter().map(|op| op.as_str()).collect();
+        sqlx::query(
+            "INSERT OR REPLACE INTO safety_snapshot_policies (distro_name, operations, format, output_dir, keep_last, max_age_hours, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(policy.distro_name.as_str())
+        .bind(operations.join(","))
+        .bind(policy.format.extension())
+        .bind(&policy.output_dir)
+        .bind(policy.keep_last as i64)
+        .bind(policy.max_age_hours as i64)
+        .bind(policy.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .db_err()?;
+        Ok(())
+    }
+
+    async fn get(&self, distro_name: &str) -> Result<Option<SafetySnapshotPolicy>, DomainError> {
+        let row = sqlx::query("SELECT * FROM safety_snapshot_policies WHERE distro_name = ?")
+            .bind(distro_name)
+            .fetch_optional(&self.pool)
+            .await
+            .db_err()?;
+        row.as_ref().map(row_to_policy).transpose()
+    }
+
+    async fn list(&self) -> Result<Vec<SafetySnapshotPolicy>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM safety_snapshot_policies ORDER BY distro_name")
+            .fetch_all(&self.pool)
+            .await
+            .db_err()?;
+        rows.iter().map(row_to_policy).collect()
+    }
+
+    async fn delete(&self, distro_name: &str) -> Result<(), DomainError> {
+        sqlx::query("DELETE FROM safety_snapshot_policies WHERE distro_name = ?")
+            .bind(distro_name)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::sqlite::adapter::SqliteDb;
+
+    async fn test_repo() -> SqliteSafetyPolicyRepository {
+        SqliteSafetyPolicyRepository::new(SqliteDb::new("sqlite::memory:").await.unwrap())
+    }
+
+    fn make_policy(distro: &str, operations: Vec<DestructiveOperation>) -> SafetySnapshotPolicy {
+        SafetySnapshotPolicy {
+            distro_name: DistroName::new(distro).unwrap(),
+            operations,
+            format: ExportFormat::TarZst,
+            output_dir: "/backups/safety".into(),
+            keep_last: 3,
+            max_age_hours: 72,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_get_and_replace() {
+        let repo = test_repo().await;
+        repo.save(&make_policy(
+            "Ubuntu",
+            vec![
+                DestructiveOperation::DeleteDistro,
+                DestructiveOperation::ResizeVhd,
+            ],
+        ))
+        .await
+        .unwrap();
+
+        let policy = repo.get("Ubuntu").await.unwrap().unwrap();
+        assert_eq!(
+            policy.operations,
+            vec![
+                DestructiveOperation::DeleteDistro,
+                DestructiveOperation::ResizeVhd
+            ]
+        );
+        assert!(matches!(policy.format, ExportFormat::TarZst));
+        assert_eq!(policy.keep_last, 3);
+        assert_eq!(policy.max_age_hours, 72);
+
+        repo.save(&make_policy("Ubuntu", Vec::new())).await.unwrap();
+        let policy = repo.get("Ubuntu").await.unwrap().unwrap();
+        assert!(policy.operations.is_empty());
+        assert!(repo.get("Debian").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_and_delete() {
+        let repo = test_repo().await;
+        repo.save(&make_policy(
+            "Ubuntu",
+            vec![DestructiveOperation::CompactVhdx],
+        ))
+        .await
+        .unwrap();
+        repo.save(&make_policy(
+            "Debian",
+            vec![DestructiveOperation::RestoreOverwrite],
+        ))
+        .await
+        .unwrap();
+
+        let names: Vec<String> = repo
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.distro_name.to_string())
+            .collect();
+        assert_eq!(names, vec!["Debian", "Ubuntu"]);
+
+        repo.delete("Ubuntu").await.unwrap();
+        assert_eq!(repo.list().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,309 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use super::SqlxResultExt;
+use crate::domain::entities::snapshot::{ExportFormat, SnapshotStorage};
+use crate::domain::entities::snapshot_schedule::{
+    RetentionPolicy, ScheduleFrequency, SnapshotSchedule,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::schedule_repository::ScheduleRepositoryPort;
+use crate::domain::value_objects::{DistroName, SnapshotId};
+
+pub struct SqliteScheduleRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteScheduleRepository {
+    pub fn new(db: crate::infrastructure::sqlite::adapter::SqliteDb) -> Self {
+        Self { pool: db.pool }
+    }
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn row_to_schedule(row: &sqlx::sqlite::SqliteRow) -> Result<SnapshotSchedule, DomainError> {
+    let frequency_str: String = row.get("frequency");
+    let format = match row.get::<&str, _>("format") {
+        "vhdx" => ExportFormat::Vhd,
+        "tar.zst" => ExportFormat::TarZst,
+        "tar.gz" => ExportFormat::TarGz,
+        _ => ExportFormat::Tar,
+    };
+    let storage = match row.get::<&str, _>("storage") {
+        "chunked" => SnapshotStorage::Chunked,
+        _ => SnapshotStorage::File,
+    };
+    let last_run_at: Option<String> = row.get("last_run_at");
+
+    Ok(SnapshotSchedule {
+        id: row.get("id"),
+        distro_name: DistroName::new(row.get::<&str, _>("distro_name"))?,
+        frequency: ScheduleFrequency::parse(&frequency_str).ok_or_else(|| {
+            DomainError::DatabaseError(format!("Unknown schedule frequency '{}'", frequency_str))
+        })?,
+        format,
+        storage,
+        output_dir: row.get("output_dir"),
+        retention: RetentionPolicy {
+            keep_hourly: row.get::<i64, _>("keep_hourly") as u32,
+            keep_daily: row.get::<i64, _>("keep_daily") as u32,
+            keep_weekly: row.get::<i64, _>("keep_weekly") as u32,
+        },
+        enabled: row.get("enabled"),
+        last_run_at: last_run_at.as_deref().and_then(parse_time),
+        created_at: parse_time(row.get("created_at")).unwrap_or_else(Utc::now),
+    })
+}
+
+#[async_trait]
+impl ScheduleRepositoryPort for SqliteScheduleRepository {
+    async fn save(&self, schedule: &SnapshotSchedule) -> Result<(), DomainError> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO snapshot_schedules (id, distro_name, frequency, format, storage, output_dir, keep_hourly, keep_daily, keep_weekly, enabled, last_run_at, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&schedule.id)
+        .bind(schedule.distro_name.as_str())
+        .bind(schedule.frequency.as_str())
+        .bind(schedule.format.extension())
+        .bind(schedule.storage.as_str())
+        .bind(&schedule.output_dir)
+        .bind(schedule.retention.keep_hourly as i64)
+        .bind(schedule.retention.keep_daily as i64)
+        .bind(schedule.retention.keep_weekly as i64)
+        .bind(schedule.enabled)
+        .bind(schedule.last_run_at.map(|t| t.to_rfc3339()))
+        .bind(schedule.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .db_err()?;
+
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        distro_name: Option<String>,
+    ) -> Result<Vec<SnapshotSchedule>, DomainError> {
+        let rows = if let Some(ref name) = distro_name {
+            sqlx::query(
+                "SELECT * FROM snapshot_schedules WHERE distro_name = ? ORDER BY created_at",
+            )
+            .bind(name)
+            .fetch_all(&self.pool)
+            .await
+        } else {
+            sqlx::query("SELECT * FROM snapshot_schedules ORDER BY created_at")
+                .fetch_all(&self.pool)
+                .await
+        }
+        .db_err()?;
+
+        rows.iter().map(row_to_schedule).collect()
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<SnapshotSchedule>, DomainError> {
+        let row = sqlx::query("SELECT * FROM snapshot_schedules WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .db_err()?;
+
+        row.as_ref().map(row_to_schedule).transpose()
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        sqlx::query("DELETE FROM schedule_snapshots WHERE schedule_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        sqlx::query("DELETE FROM snapshot_schedules WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        Ok(())
+    }
+
+    async fn delete_by_distro(&self, distro_name: &str) -> Result<(), DomainError> {
+        sqlx::query(
+            "DELETE FROM schedule_snapshots WHERE schedule_id IN \
+             (SELECT id FROM snapshot_schedules WHERE distro_name = ?)",
+        )
+        .bind(distro_name)
+        .execute(&self.pool)
+        .await
+        .db_err()?;
+        sqlx::query("DELETE FROM snapshot_schedules WHERE distro_name = ?")
+            .bind(distro_name)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        Ok(())
+    }
+
+    async fn mark_run(&self, id: &str, at: DateTime<Utc>) -> Result<(), DomainError> {
+        sqlx::query("UPDATE snapshot_schedules SET last_run_at = ? WHERE id = ?")
+            .bind(at.to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        Ok(())
+    }
+
+    async fn add_snapshot(&self, id: &str, snapshot_id: &SnapshotId) -> Result<(), DomainError> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO schedule_snapshots (schedule_id, snapshot_id) VALUES (?, ?)",
+        )
+        .bind(id)
+        .bind(snapshot_id.as_str())
+        .execute(&self.pool)
+        .await
+        .db_err()?;
+        Ok(())
+    }
+
+    async fn list_snapshots(&self, id: &str) -> Result<Vec<SnapshotId>, DomainError> {
+        let rows = sqlx::query("SELECT snapshot_id FROM schedule_snapshots WHERE schedule_id = ?")
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await
+            .db_err()?;
+        Ok(rows
+            .iter()
+            .map(|r| SnapshotId::from_string(r.get("snapshot_id")))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::snapshot::{Snapshot, SnapshotStatus, SnapshotType};
+    use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+    use crate::domain::value_objects::MemorySize;
+    use crate::infrastructure::sqlite::adapter::{SqliteDb, SqliteSnapshotRepository};
+
+    async fn test_db() -> SqliteDb {
+        SqliteDb::new("sqlite::memory:").await.unwrap()
+    }
+
+    fn make_schedule(id: &str, distro: &str) -> SnapshotSchedule {
+        SnapshotSchedule {
+            id: id.to_string(),
+            distro_name: DistroName::new(distro).unwrap(),
+            frequency: ScheduleFrequency::Daily,
+            format: ExportFormat::TarZst,
+            storage: SnapshotStorage::Chunked,
+            output_dir: "/backups".to_string(),
+            retention: RetentionPolicy {
+                keep_hourly: 24,
+                keep_daily: 7,
+                keep_weekly: 4,
+            },
+            enabled: true,
+            last_run_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn make_snapshot(id: &str) -> Snapshot {
+        Snapshot {
+            id: SnapshotId::from_string(id.into()),
+            distro_name: DistroName::new("Ubuntu").unwrap(),
+            name: id.into(),
+            description: None,
+            snapshot_type: SnapshotType::Full,
+            format: ExportFormat::Tar,
+            file_path: format!("/backups/{id}.tar"),
+            file_size: MemorySize::from_bytes(1),
+            parent_id: None,
+            created_at: Utc::now(),
+            status: SnapshotStatus::Completed,
+            default_user: None,
+            storage: SnapshotStorage::File,
+            checksum: None,
+            tags: Vec::new(),
+            pinned: false,
+            encryption: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_get_and_mark_run() {
+        let repo = SqliteScheduleRepository::new(test_db().await);
+        repo.save(&make_schedule("s1", "Ubuntu")).await.unwrap();
+
+        let s = repo.get("s1").await.unwrap().unwrap();
+        assert_eq!(s.distro_name.as_str(), "Ubuntu");
+        assert_eq!(s.frequency, ScheduleFrequency::Daily);
+        assert!(matches!(s.format, ExportFormat::TarZst));
+        assert_eq!(s.storage, SnapshotStorage::Chunked);
+        assert_eq!(s.retention.keep_weekly, 4);
+        assert!(s.last_run_at.is_none());
+
+        let at = Utc::now();
+        repo.mark_run("s1", at).await.unwrap();
+        let s = repo.get("s1").await.unwrap().unwrap();
+        assert_eq!(s.last_run_at.unwrap().timestamp(), at.timestamp());
+        assert!(repo.get("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_by_distro() {
+        let repo = SqliteScheduleRepository::new(test_db().await);
+        repo.save(&make_schedule("s1", "Ubuntu")).await.unwrap();
+        repo.save(&make_schedule("s2", "Debian")).await.unwrap();
+
+        assert_eq!(repo.list(None).await.unwrap().len(), 2);
+        let ubuntu = repo.list(Some("Ubuntu".into())).await.unwrap();
+        assert_eq!(ubuntu.len(), 1);
+        assert_eq!(ubuntu[0].id, "s1");
+
+        repo.delete_by_distro("Ubuntu").await.unwrap();
+        assert_eq!(repo.list(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_links_survive_resave_and_follow_deletes() {
+        let db = test_db().await;
+        let repo = SqliteScheduleRepository::new(db.clone());
+        let snapshots = SqliteSnapshotRepository::new(db);
+        repo.save(&make_schedule("s1", "Ubuntu")).await.unwrap();
+        for id in ["a", "b"] {
+            let snap = make_snapshot(id);
+            snapshots.save(&snap).await.unwrap();
+            repo.add_snapshot("s1", &snap.id).await.unwrap();
+        }
+
+        // Re-saving a snapshot (e.g. after verification) keeps its link.
+        snapshots.save(&make_snapshot("a")).await.unwrap();
+        assert_eq!(repo.list_snapshots("s1").await.unwrap().len(), 2);
+
+        snapshots
+            .delete(&SnapshotId::from_string("a".into()))
+            .await
+            .unwrap();
+        let linked = repo.list_snapshots("s1").await.unwrap();
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].as_str(), "b");
+
+        // Deleting the schedule keeps the snapshot itself.
+        repo.delete("s1").await.unwrap();
+        assert!(repo.get("s1").await.unwrap().is_none());
+        assert!(
+            snapshots
+                .get_by_id(&SnapshotId::from_string("b".into()))
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use super::SqlxResultExt;
+use crate::domain::entities::distro_template::DistroTemplate;
+use crate::domain::errors::DomainError;
+use crate::domain::ports::template_repository::TemplateRepositoryPort;
+use crate::domain::value_objects::SnapshotId;
+
+pub struct SqliteTemplateRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteTemplateRepository {
+    pub fn new(db: crate::infrastructure::sqlite::adapter::SqliteDb) -> Self {
+        Self { pool: db.pool }
+    }
+}
+
+fn parse_time(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn row_to_template(row: &sqlx::sqlite::SqliteRow) -> Result<DistroTemplate, DomainError> {
+    let parameters = serde_json::from_str(row.get::<&str, _>("parameters"))
+        .map_err(|e| DomainError::DatabaseError(format!("invalid template parameters: {}", e)))?;
+    let wsl_config = serde_json::from_str(row.get::<&str, _>("wsl_config"))
+        .map_err(|e| DomainError::DatabaseError(format!("invalid template wsl.conf: {}", e)))?;
+
+    Ok(DistroTemplate {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        snapshot_id: SnapshotId::from_string(row.get("snapshot_id")),
+        script: row.get("script"),
+        parameters,
+        wsl_config,
+        created_at: parse_time(row.get("created_at")),
+        updated_at: parse_time(row.get("updated_at")),
+    })
+}
+
+#[async_trait]
+impl TemplateRepositoryPort for SqliteTemplateRepository {
+    async fn save(&self, template: &DistroTemplate) -> Result<(), DomainError> {
+        let parameters = serde_json::to_string(&template.parameters)
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        let wsl_config = serde_json::to_string(&template.wsl_config)
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        sqlx::query(
+            // Not INSERT OR REPLACE: that would silently drop another
+            // template holding the same name.
+            "INSERT INTO distro_templates (id, name, description, snapshot_id, script, parameters, wsl_config, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, description = excluded.description,
+                 snapshot_id = excluded.snapshot_id, script = excluded.script, parameters = excluded.parameters,
+                 wsl_config = excluded.wsl_config, updated_at = excluded.updated_at",
+        )
+        .bind(&template.id)
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(template.snapshot_id.as_str())
+        .bind(&template.script)
+        .bind(parameters)
+        .bind(wsl_config)
+        .bind(template.created_at.to_rfc3339())
+        .bind(template.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => DomainError::ConfigError(
+                format!("A template named '{}' already exists", template.name),
+            ),
+            e => DomainError::DatabaseError(e.to_string()),
+        })?;
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<DistroTemplate>, DomainError> {
+        let row = sqlx::query("SELECT * FROM distro_templates WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .db_err()?;
+        row.as_ref().map(row_to_template).transpose()
+    }
+
+    async fn list(&self) -> Result<Vec<DistroTemplate>, DomainError> {
+        let rows = sqlx::query("SELECT * FROM distro_templates ORDER BY name COLLATE NOCASE")
+            .fetch_all(&self.pool)
+            .await
+            .db_err()?;
+        rows.iter().map(row_to_template).collect()
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        sqlx::query("DELETE FROM distro_templates WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .db_err()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::distro_template::TemplateParameter;
+    use crate::domain::entities::wsl_config::WslDistroConfig;
+    use crate::infrastructure::sqlite::adapter::SqliteDb;
+
+    async fn test_repo() -> SqliteTemplateRepository {
+        SqliteTemplateRepository::new(SqliteDb::new("sqlite::memory:").await.unwrap())
+    }
+
+    fn make_template(id: &str, name: &str) -> DistroTemplate {
+        DistroTemplate {
+            id: id.into(),
+            name: name.into(),
+            description: Some("Node dev box".into()),
+            snapshot_id: SnapshotId::from_string("snap-1".into()),
+            script: "useradd -m {{user}}".into(),
+            parameters: vec![TemplateParameter {
+                name: "user".into(),
+                description: None,
+                default: Some("dev".into()),
+                required: true,
+            }],
+            wsl_config: WslDistroConfig {
+                boot_systemd: Some(true),
+                ..Default::default()
+            },
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_get_list_and_delete() {
+        let repo = test_repo().await;
+        repo.save(&make_template("t1", "web")).await.unwrap();
+        repo.save(&make_template("t2", "Api")).await.unwrap();
+
+        let template = repo.get("t1").await.unwrap().unwrap();
+        assert_eq!(template.script, "useradd -m {{user}}");
+        assert_eq!(template.parameters[0].default.as_deref(), Some("dev"));
+        assert_eq!(template.wsl_config.boot_systemd, Some(true));
+        assert!(template.wsl_config.network_hostname.is_none());
+
+        let names: Vec<String> = repo
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Api", "web"]);
+
+        repo.delete("t1").await.unwrap();
+        assert!(repo.get("t1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_name_is_a_config_error() {
+        let repo = test_repo().await;
+        repo.save(&make_template("t1", "web")).await.unwrap();
+        let err = repo.save(&make_template("t2", "web")).await.unwrap_err();
+        assert!(matches!(err, DomainError::ConfigError(_)), "{err}");
+    }
+}
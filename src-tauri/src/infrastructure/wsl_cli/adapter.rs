@@ -1,6 +1,9 @@
 use async_trait::async_trait;
+use std::io::{Read, Write};
 use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
 use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
 #[cfg(windows)]
 use winreg::{RegKey, enums::HKEY_CURRENT_USER};
 
@@ -10,14 +13,15 @@ use crate::domain::entities::wsl_config::{WslDistroConfig, WslGlobalConfig};
 use crate::domain::entities::wsl_version::WslVersionInfo;
 use crate::domain::errors::DomainError;
 use crate::domain::ports::wsl_manager::WslManagerPort;
-use crate::domain::value_objects::DistroName;
+use crate::domain::value_objects::{DistroName, EncryptionKey};
 
 use super::encoding::decode_wsl_output;
 use super::parser::{parse_distro_list, parse_version_output};
 #[cfg(not(windows))]
 use super::path_utils::parse_reg_basepath;
 use super::path_utils::{extract_wsl_user_home, linux_to_windows_path};
-use crate::application::path_utils::windows_to_linux_path;
+use crate::application::path_utils::{existing_local_path, windows_to_linux_path};
+use crate::application::services::{compression, encryption};
 
 pub struct WslCliAdapter {
     wsl_exe: String,
@@ -114,13 +118,155 @@ impl WslCliAdapter {
         cmd
     }
 
+    /// Blocking counterpart of [`wsl_command`](Self::wsl_command), for the
+    /// streaming export/import paths that pump data on a blocking thread.
+    fn wsl_std_command(&self) -> std::process::Command {
+        #[allow(unused_mut)]
+        let mut cmd = std::process::Command::new(&self.wsl_exe);
+        cmd.env("WSL_UTF8", "1");
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(crate::infrastructure::CREATE_NO_WINDOW);
+        }
+        cmd
+    }
+
+    /// `wsl --export <name> -` with the tar stream compressed (then encrypted
+    /// with `key`) into `path` as it is produced, so the plain tar never
+    /// touches the disk.
+    async fn export_streamed(
+        &self,
+        name: &DistroName,
+        path: &str,
+        format: ExportFormat,
+        key: Option<EncryptionKey>,
+    ) -> Result<(), DomainError> {
+        let out = local_fs_path(path);
+        let mut cmd = self.wsl_std_command();
+        cmd.args(["--export", name.as_str(), "-"]);
+        tracing::info!(
+            command = %format!("wsl.exe --export {} - | {} > {}", name, format.extension(), out),
+            encrypted = key.is_some(),
+            "executing streaming export"
+        );
+
+        let abort = AbortOnDrop::default();
+        let aborted = abort.0.clone();
+        tokio::task::spawn_blocking(move || {
+            let (status, copied, stderr) = pump_wsl(cmd, None, |stdout| {
+                let file = std::io::BufWriter::new(std::fs::File::create(&out)?);
+                let mut stdout = Abortable {
+                    inner: stdout,
+                    aborted,
+                };
+                match &key {
+                    Some(key) => {
+                        let sealed = encryption::EncryptWriter::new(key, file)?;
+                        let (n, sealed) = compress_into(&format, &mut stdout, sealed)?;
+                        sealed.finish()?;
+                        Ok(n)
+                    }
+                    None => {
+                        let (n, mut file) = compress_into(&format, &mut stdout, file)?;
+                        file.flush()?;
+                        Ok(n)
+                    }
+                }
+            })?;
+            if !status.success() || copied.is_err() {
+                let _ = std::fs::remove_file(&out);
+            }
+            if !status.success() {
+                return Err(DomainError::WslCliError(wsl_failure_message(
+                    &status,
+                    &[],
+                    &stderr,
+                )));
+            }
+            let bytes = copied?;
+            tracing::info!(tar_bytes = bytes, "streaming export completed: {}", out);
+            Ok(())
+        })
+        .await
+        .map_err(|e| DomainError::Internal(format!("export task failed: {}", e)))?
+    }
+
+    /// `wsl --import <name> <location> - --version 2` fed with the archive at
+    /// `file_path`, decrypted and decompressed while it streams into wsl.exe.
+    async fn import_streamed(
+        &self,
+        name: &DistroName,
+        install_location: &str,
+        file_path: &str,
+        format: ExportFormat,
+        key: Option<EncryptionKey>,
+    ) -> Result<(), DomainError> {
+        let source = existing_local_path(file_path).ok_or_else(|| {
+            DomainError::SnapshotError(format!("Snapshot file not found: {}", file_path))
+        })?;
+        let win_loc = linux_to_windows_path(install_location);
+        let mut cmd = self.wsl_std_command();
+        cmd.args([
+            "--import",
+            name.as_str(),
+            win_loc.as_str(),
+            "-",
+            "--version",
+            "2",
+        ]);
+        tracing::info!(
+            command = %format!("{} -d < {} | wsl.exe --import {} {} - --version 2", format.extension(), source, name, win_loc),
+            "executing streaming import"
+        );
+
+        let abort = AbortOnDrop::default();
+        let aborted = abort.0.clone();
+        tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(&source)?;
+            let mut reader = Abortable {
+                inner: compression::decoder(
+                    &format,
+                    encryption::reader(key.as_ref(), std::io::BufReader::new(file))?,
+                )?,
+                aborted,
+            };
+            let (status, copied, stderr) = pump_wsl(cmd, Some(&mut reader), |stdout| {
+                std::io::copy(stdout, &mut std::io::sink())
+            })?;
+            if !status.success() {
+                return Err(DomainError::WslCliError(wsl_failure_message(
+                    &status,
+                    &[],
+                    &stderr,
+                )));
+            }
+            copied?;
+            tracing::info!("streaming import completed: {}", source);
+            Ok(())
+        })
+        .await
+        .map_err(|e| DomainError::Internal(format!("import task failed: {}", e)))?
+    }
+
     /// Run a wsl.exe command and return raw stdout bytes
     async fn run_wsl_raw(&self, args: &[&str]) -> Result<Vec<u8>, DomainError> {
+        self.run_wsl(args, false).await
+    }
+
+    /// Like [`run_wsl_raw`](Self::run_wsl_raw), but wsl.exe is killed when the
+    /// returned future is dropped. Used for cancellable exports and imports.
+    async fn run_wsl_killable(&self, args: &[&str]) -> Result<Vec<u8>, DomainError> {
+        self.run_wsl(args, true).await
+    }
+
+    async fn run_wsl(&self, args: &[&str], kill_on_drop: bool) -> Result<Vec<u8>, DomainError> {
         tracing::debug!(args = ?args, "wsl.exe");
         let start = std::time::Instant::now();
         let output = self
             .wsl_command()
             .args(args)
+            .kill_on_drop(kill_on_drop)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
@@ -133,26 +279,7 @@ impl WslCliAdapter {
         let elapsed_ms = start.elapsed().as_millis() as u64;
 
         if !output.status.success() {
-            let stderr = decode_wsl_output(&output.stderr)
-                .unwrap_or_else(|_| String::from_utf8_lossy(&output.stderr).to_string());
-            let stderr = stderr.trim();
-
-            // When stderr is empty, try stdout or include the exit code
-            let msg = if stderr.is_empty() {
-                let stdout = decode_wsl_output(&output.stdout)
-                    .unwrap_or_else(|_| String::from_utf8_lossy(&output.stdout).to_string());
-                let stdout = stdout.trim();
-                if stdout.is_empty() {
-                    format!(
-                        "wsl.exe exited with code {} (no output)",
-                        output.status.code().unwrap_or(-1)
-                    )
-                } else {
-                    stdout.to_string()
-                }
-            } else {
-                stderr.to_string()
-            };
+            let msg = wsl_failure_message(&output.status, &output.stdout, &output.stderr);
 
             tracing::error!(
                 args = ?args,
@@ -179,9 +306,24 @@ impl WslCliAdapter {
     /// The `command` parameter is passed to `sh -c`, so it is shell-interpreted.
     /// Only call this with hardcoded command strings. NEVER pass user input directly.
     async fn exec_in_distro_raw(&self, distro: &str, command: &str) -> Result<String, DomainError> {
+        self.exec_in_distro_as(distro, None, command).await
+    }
+
+    /// Run `sh -c command` in `distro` as `user` (the distro's default user if `None`).
+    async fn exec_in_distro_as(
+        &self,
+        distro: &str,
+        user: Option<&str>,
+        command: &str,
+    ) -> Result<String, DomainError> {
+        let mut args = vec!["-d", distro];
+        if let Some(user) = user {
+            args.extend(["-u", user]);
+        }
+        args.extend(["-e", "sh", "-c", command]);
         let output = self
             .wsl_command()
-            .args(["-d", distro, "-e", "sh", "-c", command])
+            .args(&args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
@@ -200,6 +342,30 @@ impl WslCliAdapter {
         String::from_utf8(output.stdout).map_err(|e| DomainError::WslCliError(e.to_string()))
     }
 
+    /// `exec_in_distro_as`, refusing distros that are being terminated.
+    async fn exec_checked(
+        &self,
+        name: &DistroName,
+        user: Option<&str>,
+        command: &str,
+    ) -> Result<String, DomainError> {
+        // Refuse to execute commands on distros being terminated — spawning
+        // `wsl -d <name>` would restart the distro immediately.
+        if let Ok(set) = self.terminating.lock()
+            && set.contains(name.as_str())
+        {
+            return Err(DomainError::DistroNotRunning(name.to_string()));
+        }
+
+        tracing::debug!(cmd = %command, ?user, "executing command in distro");
+        let result = self.exec_in_distro_as(name.as_str(), user, command).await;
+        match &result {
+            Ok(output) => tracing::debug!(output_len = output.len(), "command succeeded"),
+            Err(e) => tracing::debug!(error = %e, "command failed"),
+        }
+        result
+    }
+
     /// Resolve the Windows user profile path.
     /// On Windows, reads USERPROFILE directly. On WSL2, resolves it via cmd.exe.
     fn get_wslconfig_path() -> Result<std::path::PathBuf, DomainError> {
@@ -483,13 +649,22 @@ impl WslManagerPort for WslCliAdapter {
         Ok(())
     }
 
-    #[tracing::instrument(skip(self), fields(distro = %name, path = %path, format = ?format))]
+    #[tracing::instrument(skip(self, key), fields(distro = %name, path = %path, format = ?format))]
     async fn export_distro(
         &self,
         name: &DistroName,
         path: &str,
         format: ExportFormat,
+        key: Option<EncryptionKey>,
     ) -> Result<(), DomainError> {
+        if key.is_some() && !format.is_tar() {
+            return Err(DomainError::EncryptionError(
+                "Only tar exports can be encrypted".into(),
+            ));
+        }
+        if format.is_compressed() || key.is_some() {
+            return self.export_streamed(name, path, format, key).await;
+        }
         // Convert Linux /mnt/X/... paths to Windows X:\... for wsl.exe
         let win_path = linux_to_windows_path(path);
         if win_path != path {
@@ -504,18 +679,24 @@ impl WslManagerPort for WslCliAdapter {
             args.push(flag);
         }
         tracing::info!(command = %format!("wsl.exe {}", args.join(" ")), "executing export");
-        self.run_wsl_raw(&args).await?;
+        self.run_wsl_killable(&args).await?;
         Ok(())
     }
 
-    #[tracing::instrument(skip(self), fields(distro = %name, install = %install_location, file = %file_path, format = ?format))]
+    #[tracing::instrument(skip(self, key), fields(distro = %name, install = %install_location, file = %file_path, format = ?format))]
     async fn import_distro(
         &self,
         name: &DistroName,
         install_location: &str,
         file_path: &str,
         format: ExportFormat,
+        key: Option<EncryptionKey>,
     ) -> Result<(), DomainError> {
+        if format.is_compressed() || key.is_some() {
+            return self
+                .import_streamed(name, install_location, file_path, format, key)
+                .await;
+        }
         let win_loc = linux_to_windows_path(install_location);
         let win_file = linux_to_windows_path(file_path);
         if win_loc != install_location || win_file != file_path {
@@ -545,7 +726,7 @@ impl WslManagerPort for WslCliAdapter {
             args.push("2");
         }
         tracing::info!(command = %format!("wsl.exe {}", args.join(" ")), "executing import");
-        self.run_wsl_raw(&args).await?;
+        self.run_wsl_killable(&args).await?;
         Ok(())
     }
 
@@ -562,21 +743,93 @@ impl WslManagerPort for WslCliAdapter {
         name: &DistroName,
         command: &str,
     ) -> Result<String, DomainError> {
-        // Refuse to execute commands on distros being terminated — spawning
-        // `wsl -d <name>` would restart the distro immediately.
+        self.exec_checked(name, None, command).await
+    }
+
+    async fn exec_in_distro_as_root(
+        &self,
+        name: &DistroName,
+        command: &str,
+    ) -> Result<String, DomainError> {
+        self.exec_checked(name, Some("root"), command).await
+    }
+
+    #[tracing::instrument(skip(self, script, output), fields(distro = %name))]
+    async fn run_script(
+        &self,
+        name: &DistroName,
+        script: &str,
+        output: UnboundedSender<String>,
+    ) -> Result<(), DomainError> {
         if let Ok(set) = self.terminating.lock()
             && set.contains(name.as_str())
         {
             return Err(DomainError::DistroNotRunning(name.to_string()));
         }
 
-        tracing::debug!(cmd = %command, "executing command in distro");
-        let result = self.exec_in_distro_raw(name.as_str(), command).await;
-        match &result {
-            Ok(output) => tracing::debug!(output_len = output.len(), "command succeeded"),
-            Err(e) => tracing::debug!(error = %e, "command failed"),
+        // The script is copied to a temp file through stdin and run from
+        // there, so commands in it that read stdin cannot swallow its lines.
+        let runner = concat!(
+            "f=$(mktemp) && cat > \"$f\" && ",
+            "sh \"$f\" < /dev/null 2>&1; s=$?; rm -f \"$f\"; exit $s"
+        );
+        let mut child = self
+            .wsl_command()
+            .args(["-d", name.as_str(), "-u", "root", "-e", "sh", "-c", runner])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| DomainError::WslCliError(e.to_string()))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(script.as_bytes())
+                .await
+                .map_err(|e| DomainError::WslCliError(e.to_string()))?;
         }
-        result
+
+        let mut stdout = tokio::io::BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut tail: std::collections::VecDeque<String> = std::collections::VecDeque::new();
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let n = stdout
+                .read_until(b'\n', &mut buf)
+                .await
+                .map_err(|e| DomainError::WslCliError(e.to_string()))?;
+            if n == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&buf).trim_end().to_string();
+            if tail.len() == 5 {
+                tail.pop_front();
+            }
+            tail.push_back(line.clone());
+            let _ = output.send(line);
+        }
+
+        let result = child
+            .wait_with_output()
+            .await
+            .map_err(|e| DomainError::WslCliError(e.to_string()))?;
+        if !result.status.success() {
+            let last = Vec::from(tail).join("\n");
+            let detail = if last.trim().is_empty() {
+                String::from_utf8_lossy(&result.stderr).trim().to_string()
+            } else {
+                last
+            };
+            return Err(DomainError::WslCliError(format!(
+                "Script exited with {}: {}",
+                result
+                    .status
+                    .code()
+                    .map_or_else(|| "a signal".to_string(), |c| format!("code {}", c)),
+                detail
+            )));
+        }
+        Ok(())
     }
 
     async fn get_global_config(&self) -> Result<WslGlobalConfig, DomainError> {
@@ -646,6 +899,92 @@ impl WslManagerPort for WslCliAdapter {
         })
     }
 
+    #[tracing::instrument(skip(self, config), fields(distro = %name))]
+    async fn update_distro_config(
+        &self,
+        name: &DistroName,
+        config: WslDistroConfig,
+    ) -> Result<(), DomainError> {
+        let mut entries: Vec<(&str, &str, String)> = Vec::new();
+        macro_rules! push_opt {
+            ($field:expr, $section:literal, $key:literal) => {
+                if let Some(ref v) = $field {
+                    entries.push(($section, $key, v.to_string()));
+                }
+            };
+        }
+        push_opt!(config.automount_enabled, "automount", "enabled");
+        push_opt!(config.automount_root, "automount", "root");
+        push_opt!(config.network_hostname, "network", "hostname");
+        push_opt!(config.network_generate_hosts, "network", "generateHosts");
+        push_opt!(
+            config.network_generate_resolv_conf,
+            "network",
+            "generateResolvConf"
+        );
+        push_opt!(config.interop_enabled, "interop", "enabled");
+        push_opt!(
+            config.interop_append_windows_path,
+            "interop",
+            "appendWindowsPath"
+        );
+        push_opt!(config.user_default, "user", "default");
+        push_opt!(config.boot_systemd, "boot", "systemd");
+        push_opt!(config.boot_command, "boot", "command");
+        push_opt!(config.gpu_enabled, "gpu", "enabled");
+        push_opt!(config.use_windows_timezone, "time", "useWindowsTimezone");
+        if entries.is_empty() {
+            return Ok(());
+        }
+        if entries.iter().any(|(_, _, v)| v.contains(['\n', '\r'])) {
+            return Err(DomainError::ConfigError(
+                "wsl.conf values must be single lines".into(),
+            ));
+        }
+
+        let existing = self
+            .exec_checked(name, Some("root"), "cat /etc/wsl.conf 2>/dev/null || true")
+            .await?;
+        let content = merge_ini(&existing, &entries);
+
+        // The content goes through stdin so no value is ever shell-interpreted.
+        let mut child = self
+            .wsl_command()
+            .args([
+                "-d",
+                name.as_str(),
+                "-u",
+                "root",
+                "-e",
+                "sh",
+                "-c",
+                "cat > /etc/wsl.conf",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| DomainError::WslCliError(e.to_string()))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(content.as_bytes())
+                .await
+                .map_err(|e| DomainError::WslCliError(e.to_string()))?;
+        }
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| DomainError::WslCliError(e.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(DomainError::WslCliError(format!(
+                "Failed to write /etc/wsl.conf: {}",
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
     async fn update_global_config(&self, config: WslGlobalConfig) -> Result<(), DomainError> {
         let path = Self::get_wslconfig_path()?;
 
@@ -924,10 +1263,412 @@ impl WslManagerPort for WslCliAdapter {
     }
 }
 
+/// Set `key=value` for each `(section, key, value)` entry in INI `content`.
+/// Existing keys are replaced in place (matched case-insensitively), missing
+/// ones are added to their section, which is appended when absent. Every
+/// other line is kept as it is.
+fn merge_ini(content: &str, entries: &[(&str, &str, String)]) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut done = vec![false; entries.len()];
+
+    let mut section = String::new();
+    lines.retain_mut(|line| {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            section = trimmed[1..trimmed.len() - 1].to_lowercase();
+            return true;
+        }
+        let Some((key, _)) = trimmed.split_once('=') else {
+            return true;
+        };
+        let key = key.trim().to_lowercase();
+        match entries
+            .iter()
+            .position(|(s, k, _)| *s == section && k.to_lowercase() == key)
+        {
+            // A later duplicate of a key already written is dropped.
+            Some(i) if done[i] => false,
+            Some(i) => {
+                let (_, k, v) = &entries[i];
+                *line = format!("{}={}", k, v);
+                done[i] = true;
+                true
+            }
+            None => true,
+        }
+    });
+
+    for (i, (section, key, value)) in entries.iter().enumerate() {
+        if done[i] {
+            continue;
+        }
+        let header = lines.iter().position(|l| {
+            let t = l.trim();
+            t.starts_with('[')
+                && t.ends_with(']')
+                && t[1..t.len() - 1].eq_ignore_ascii_case(section)
+        });
+        match header {
+            Some(at) => lines.insert(at + 1, format!("{}={}", key, value)),
+            None => {
+                if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(format!("[{}]", section));
+                lines.push(format!("{}={}", key, value));
+            }
+        }
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Human-readable error of a failed wsl.exe run: stderr, else stdout, else
+/// the exit code.
+fn wsl_failure_message(status: &std::process::ExitStatus, stdout: &[u8], stderr: &[u8]) -> String {
+    let stderr =
+        decode_wsl_output(stderr).unwrap_or_else(|_| String::from_utf8_lossy(stderr).to_string());
+    let stderr = stderr.trim();
+    if !stderr.is_empty() {
+        return stderr.to_string();
+    }
+    let stdout =
+        decode_wsl_output(stdout).unwrap_or_else(|_| String::from_utf8_lossy(stdout).to_string());
+    let stdout = stdout.trim();
+    if stdout.is_empty() {
+        format!(
+            "wsl.exe exited with code {} (no output)",
+            status.code().unwrap_or(-1)
+        )
+    } else {
+        stdout.to_string()
+    }
+}
+
+/// Path under which this process can write `path` (wsl.exe paths are Windows
+/// paths; when running inside WSL they live under `/mnt/<drive>`).
+fn local_fs_path(path: &str) -> String {
+    if cfg!(windows) {
+        path.to_string()
+    } else {
+        windows_to_linux_path(path)
+    }
+}
+
+/// Raised when the async side of a streaming transfer is dropped (cancelled),
+/// so the blocking pump stops reading and wsl.exe gets killed.
+#[derive(Default)]
+struct AbortOnDrop(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Reader that fails once `aborted` is set.
+struct Abortable<R> {
+    inner: R,
+    aborted: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl<R: Read> Read for Abortable<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.aborted.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(std::io::Error::other("transfer aborted"));
+        }
+        self.inner.read(buf)
+    }
+}
+
+/// Copy `source` into `sink` compressed according to `format`; returns the
+/// uncompressed byte count and `sink`.
+fn compress_into<W: Write>(
+    format: &ExportFormat,
+    source: &mut impl Read,
+    sink: W,
+) -> std::io::Result<(u64, W)> {
+    let mut encoder = compression::Encoder::new(format, sink)?;
+    let n = std::io::copy(source, &mut encoder)?;
+    Ok((n, encoder.finish()?))
+}
+
+/// Spawn `cmd`, feed `stdin` (if any) into it and hand its stdout to
+/// `consume`, all concurrently. Returns the exit status, the result of
+/// `consume` and the captured stderr. If feeding or consuming fails, the
+/// child is killed so the wait cannot hang.
+fn pump_wsl<F>(
+    mut cmd: std::process::Command,
+    stdin: Option<&mut (dyn Read + Send)>,
+    consume: F,
+) -> Result<(std::process::ExitStatus, std::io::Result<u64>, Vec<u8>), DomainError>
+where
+    F: FnOnce(&mut std::process::ChildStdout) -> std::io::Result<u64> + Send,
+{
+    cmd.stdin(if stdin.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    })
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| DomainError::WslCliError(format!("Failed to execute wsl.exe: {}", e)))?;
+    let child_stdin = child.stdin.take();
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+
+    let (fed, copied, stderr) = std::thread::scope(|scope| {
+        let stderr_reader = scope.spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf);
+            buf
+        });
+        let feeder = scope.spawn(move || -> std::io::Result<()> {
+            if let (Some(source), Some(mut sink)) = (stdin, child_stdin) {
+                std::io::copy(source, &mut sink)?;
+                sink.flush()?;
+            }
+            Ok(())
+        });
+        let copied = consume(&mut stdout);
+        // Unblock a feeder stuck on a full pipe before joining it.
+        if copied.is_err() {
+            let _ = child.kill();
+        }
+        let fed = feeder
+            .join()
+            .unwrap_or_else(|_| Err(std::io::Error::other("stdin feeder panicked")));
+        if fed.is_err() {
+            let _ = child.kill();
+        }
+        (fed, copied, stderr_reader.join().unwrap_or_default())
+    });
+    let status = child.wait()?;
+    let copied = fed.and(copied);
+    Ok((status, copied, stderr))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Adapter whose `wsl.exe` is a shell script, for the streaming paths.
+    #[cfg(unix)]
+    fn adapter_with_script(dir: &std::path::Path, body: &str) -> WslCliAdapter {
+        use std::os::unix::fs::PermissionsExt;
+        let script = dir.join("wsl.sh");
+        std::fs::write(&script, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        WslCliAdapter {
+            wsl_exe: script.to_string_lossy().to_string(),
+            ..WslCliAdapter::new()
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_compressed_export_and_import_stream_through_wsl() {
+        let dir = std::env::temp_dir().join("wsl_cli_streaming_roundtrip");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let payload = dir.join("payload.tar");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 7) as u8).collect();
+        std::fs::write(&payload, &data).unwrap();
+        let received = dir.join("received.tar");
+        let name = DistroName::new("Ubuntu").unwrap();
+
+        for format in [ExportFormat::TarZst, ExportFormat::TarGz] {
+            // "--export <name> -" writes the payload; "--import ... -" stores stdin.
+            let adapter = adapter_with_script(
+                &dir,
+                &format!(
+                    "case \"$1\" in --export) cat '{}';; --import) cat > '{}';; esac",
+                    payload.display(),
+                    received.display()
+                ),
+            );
+            let archive = dir.join(format!("snap.{}", format.extension()));
+            let archive = archive.to_string_lossy().to_string();
+
+            adapter
+                .export_distro(&name, &archive, format.clone(), None)
+                .await
+                .unwrap();
+            assert!(compression::has_magic(
+                std::path::Path::new(&archive),
+                &format
+            ));
+            assert!(std::fs::metadata(&archive).unwrap().len() < data.len() as u64);
+
+            adapter
+                .import_distro(&name, "/tmp/install", &archive, format.clone(), None)
+                .await
+                .unwrap();
+            assert_eq!(std::fs::read(&received).unwrap(), data, "{:?}", format);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_encrypted_export_and_import_stream_through_wsl() {
+        let dir = std::env::temp_dir().join("wsl_cli_streaming_encrypted");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let payload = dir.join("payload.tar");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 7) as u8).collect();
+        std::fs::write(&payload, &data).unwrap();
+        let received = dir.join("received.tar");
+        let adapter = adapter_with_script(
+            &dir,
+            &format!(
+                "case \"$1\" in --export) cat '{}';; --import) cat > '{}';; esac",
+                payload.display(),
+                received.display()
+            ),
+        );
+        let name = DistroName::new("Ubuntu").unwrap();
+        let key = EncryptionKey::from_bytes([9; 32]);
+
+        for format in [ExportFormat::Tar, ExportFormat::TarZst] {
+            let archive = dir.join(format!("snap.{}", format.extension()));
+            let archive = archive.to_string_lossy().to_string();
+            adapter
+                .export_distro(&name, &archive, format.clone(), Some(key.clone()))
+                .await
+                .unwrap();
+            let sealed = std::fs::read(&archive).unwrap();
+            assert!(sealed.starts_with(encryption::MAGIC));
+            assert!(!sealed.windows(64).any(|w| w == &data[..64]));
+
+            adapter
+                .import_distro(
+                    &name,
+                    "/tmp/install",
+                    &archive,
+                    format.clone(),
+                    Some(key.clone()),
+                )
+                .await
+                .unwrap();
+            assert_eq!(std::fs::read(&received).unwrap(), data, "{:?}", format);
+        }
+
+        let err = adapter
+            .export_distro(
+                &name,
+                &dir.join("snap.vhdx").to_string_lossy(),
+                ExportFormat::Vhd,
+                Some(key),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DomainError::EncryptionError(_)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_compressed_export_failure_removes_partial_archive() {
+        let dir = std::env::temp_dir().join("wsl_cli_streaming_failure");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let adapter = adapter_with_script(&dir, "echo 'export exploded' >&2; exit 1");
+        let archive = dir.join("snap.tar.zst").to_string_lossy().to_string();
+
+        let err = adapter
+            .export_distro(
+                &DistroName::new("Ubuntu").unwrap(),
+                &archive,
+                ExportFormat::TarZst,
+                None,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("export exploded"), "{}", err);
+        assert!(!std::path::Path::new(&archive).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_dropping_compressed_export_kills_wsl_and_removes_archive() {
+        let dir = std::env::temp_dir().join("wsl_cli_streaming_abort");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        // Streams forever until killed.
+        let adapter = adapter_with_script(&dir, "exec yes snapshot");
+        let archive = dir.join("snap.tar.zst").to_string_lossy().to_string();
+
+        let name = DistroName::new("Ubuntu").unwrap();
+        let export = adapter.export_distro(&name, &archive, ExportFormat::TarZst, None);
+        let timed_out = tokio::time::timeout(std::time::Duration::from_millis(300), export).await;
+        assert!(timed_out.is_err());
+
+        // The blocking pump notices the abort on its next read, kills wsl.exe
+        // and removes the partial archive.
+        let gone = async {
+            while std::path::Path::new(&archive).exists() {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), gone)
+            .await
+            .expect("partial archive removed after abort");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_script_streams_lines_and_reports_exit_code() {
+        let dir = std::env::temp_dir().join("wsl_cli_run_script");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        // "-d <name> -u root -e sh -c <runner>": run the runner locally.
+        let adapter = adapter_with_script(&dir, "shift 7; exec sh -c \"$1\"");
+        let name = DistroName::new("Ubuntu").unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let err = adapter
+            .run_script(&name, "echo one\nread x || echo two >&2\nexit 3\n", tx)
+            .await
+            .unwrap_err();
+        let mut lines = Vec::new();
+        while let Ok(line) = rx.try_recv() {
+            lines.push(line);
+        }
+        assert_eq!(lines, vec!["one", "two"]);
+        assert!(err.to_string().contains("code 3"), "{err}");
+
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        assert!(adapter.run_script(&name, "true", tx).await.is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merge_ini_replaces_adds_and_keeps_other_lines() {
+        let existing =
+            "# managed by hand\n[boot]\nSystemd=false\n\n[automount]\noptions = \"metadata\"\n";
+        let merged = merge_ini(
+            existing,
+            &[
+                ("boot", "systemd", "true".into()),
+                ("automount", "root", "/mnt/".into()),
+                ("network", "hostname", "web-1".into()),
+            ],
+        );
+        assert_eq!(
+            merged,
+            "# managed by hand\n[boot]\nsystemd=true\n\n[automount]\nroot=/mnt/\n\
+             options = \"metadata\"\n\n[network]\nhostname=web-1\n"
+        );
+    }
+
     #[test]
     fn test_parse_ini_basic_section() {
         let ini = "[wsl2]\nmemory=4GB\n";
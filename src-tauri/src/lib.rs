@@ -7,14 +7,32 @@ pub mod presentation;
 use std::sync::Arc;
 
 #[cfg(not(fuzzing))]
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 #[cfg(not(fuzzing))]
-use domain::ports::alerting::AlertThreshold;
+use application::commands::verify_snapshot::VerifySnapshotHandler;
+#[cfg(not(fuzzing))]
+use application::services::replication::SnapshotReplicator;
+#[cfg(not(fuzzing))]
+use application::services::snapshot_scheduler::SnapshotScheduler;
+#[cfg(not(fuzzing))]
+use application::services::transfer::TransferRegistry;
+#[cfg(not(fuzzing))]
+use domain::entities::alert_silence::AlertSuppressions;
+#[cfg(not(fuzzing))]
+use domain::entities::notification_channel::ChannelKind;
+#[cfg(not(fuzzing))]
+use domain::ports::alerting::{AlertThresholdSet, AlertingPort};
+#[cfg(not(fuzzing))]
+use domain::ports::metrics_exporter::MetricsExporterPort;
+#[cfg(not(fuzzing))]
+use domain::ports::metrics_repository::MetricsRepositoryPort;
+#[cfg(not(fuzzing))]
+use domain::services::alert_dispatcher::AlertDispatcher;
 #[cfg(not(fuzzing))]
 use domain::services::metrics_aggregator::MetricsAggregator;
 #[cfg(not(fuzzing))]
-use domain::services::metrics_collector::MetricsCollector;
+use domain::services::metrics_collector::{LatestMetrics, MetricsCollector};
 #[cfg(not(fuzzing))]
 use infrastructure::debug_log::buffer::DebugLogBuffer;
 #[cfg(not(fuzzing))]
@@ -22,24 +40,46 @@ use infrastructure::debug_log::layer::DebugLogLayer;
 #[cfg(not(fuzzing))]
 use infrastructure::monitoring::adapter::ProcFsMonitoringAdapter;
 #[cfg(not(fuzzing))]
+use infrastructure::notifications::desktop::DesktopNotifier;
+#[cfg(not(fuzzing))]
+use infrastructure::notifications::email::EmailNotifier;
+#[cfg(not(fuzzing))]
+use infrastructure::notifications::webhook::WebhookNotifier;
+#[cfg(not(fuzzing))]
+use infrastructure::openmetrics::adapter::OpenMetricsExporter;
+#[cfg(not(fuzzing))]
 use infrastructure::port_forwarding::adapter::NetshAdapter;
 #[cfg(not(fuzzing))]
+use infrastructure::s3::adapter::S3ObjectStore;
+#[cfg(not(fuzzing))]
 use infrastructure::sqlite::adapter::{SqliteAuditLogger, SqliteDb, SqliteSnapshotRepository};
 #[cfg(not(fuzzing))]
 use infrastructure::sqlite::alert_repository::SqliteAlertRepository;
 #[cfg(not(fuzzing))]
 use infrastructure::sqlite::metrics_repository::SqliteMetricsRepository;
 #[cfg(not(fuzzing))]
+use infrastructure::sqlite::notification_repository::SqliteNotificationRepository;
+#[cfg(not(fuzzing))]
 use infrastructure::sqlite::port_forwarding_repository::SqlitePortForwardingRepository;
 #[cfg(not(fuzzing))]
+use infrastructure::sqlite::replication_repository::SqliteReplicationRepository;
+#[cfg(not(fuzzing))]
+use infrastructure::sqlite::safety_policy_repository::SqliteSafetyPolicyRepository;
+use infrastructure::sqlite::schedule_repository::SqliteScheduleRepository;
+#[cfg(not(fuzzing))]
+use infrastructure::sqlite::template_repository::SqliteTemplateRepository;
+#[cfg(not(fuzzing))]
 use infrastructure::terminal::adapter::TerminalSessionManager;
 #[cfg(not(fuzzing))]
 use infrastructure::wsl_cli::adapter::WslCliAdapter;
 use presentation::commands::{
-    audit_commands, debug_commands, distro_commands, monitoring_commands, port_forwarding_commands,
-    settings_commands, snapshot_commands, terminal_commands,
+    audit_commands, debug_commands, distro_commands, monitoring_commands, notification_commands,
+    port_forwarding_commands, replication_commands, safety_commands, schedule_commands,
+    settings_commands, silence_commands, snapshot_commands, template_commands, terminal_commands,
 };
 #[cfg(not(fuzzing))]
+use presentation::events::{EVENT_SNAPSHOT_PROGRESS, EVENT_SNAPSHOTS_VERIFIED};
+#[cfg(not(fuzzing))]
 use presentation::state::AppState;
 #[cfg(not(fuzzing))]
 use presentation::tray;
@@ -163,46 +203,148 @@ pub fn run() {
                 let alerting = Arc::new(SqliteAlertRepository::new(db.clone()));
                 let port_rules_repo = Arc::new(SqlitePortForwardingRepository::new(db.clone()));
                 let port_forwarding = Arc::new(NetshAdapter::new());
+                let schedule_repo = Arc::new(SqliteScheduleRepository::new(db.clone()));
+                let replication_repo = Arc::new(SqliteReplicationRepository::new(db.clone()));
+                let safety_policies = Arc::new(SqliteSafetyPolicyRepository::new(db.clone()));
+                let templates = Arc::new(SqliteTemplateRepository::new(db.clone()));
+                let notification_repo = Arc::new(SqliteNotificationRepository::new(db.clone()));
                 let audit_logger = Arc::new(SqliteAuditLogger::new(db));
 
                 // Shared alert thresholds (read by collector, written by Tauri commands)
-                let alert_thresholds = Arc::new(tokio::sync::RwLock::new(vec![
-                    AlertThreshold {
-                        alert_type: domain::ports::alerting::AlertType::Cpu,
-                        threshold_percent: 90.0,
-                        enabled: false,
-                    },
-                    AlertThreshold {
-                        alert_type: domain::ports::alerting::AlertType::Memory,
-                        threshold_percent: 85.0,
-                        enabled: false,
-                    },
-                    AlertThreshold {
-                        alert_type: domain::ports::alerting::AlertType::Disk,
-                        threshold_percent: 90.0,
-                        enabled: false,
-                    },
-                ]));
-
-                // Spawn background metrics collector (2s loop)
+                let stored_thresholds = alerting.list_thresholds().await.unwrap_or_else(|e| {
+                    tracing::warn!("Failed to load alert thresholds, using defaults: {e}");
+                    Vec::new()
+                });
+                let alert_thresholds = Arc::new(tokio::sync::RwLock::new(AlertThresholdSet::new(
+                    stored_thresholds,
+                )));
+
+                // Shared silences and maintenance windows (same sharing as the thresholds)
+                let alert_suppressions = AlertSuppressions {
+                    silences: alerting.list_silences().await.unwrap_or_else(|e| {
+                        tracing::warn!("Failed to load alert silences: {e}");
+                        Vec::new()
+                    }),
+                    windows: alerting
+                        .list_maintenance_windows()
+                        .await
+                        .unwrap_or_else(|e| {
+                            tracing::warn!("Failed to load maintenance windows: {e}");
+                            Vec::new()
+                        }),
+                };
+                let alert_suppressions = Arc::new(tokio::sync::RwLock::new(alert_suppressions));
+
+                // Shared metrics settings (collection interval, tier retention)
+                let metrics_settings = metrics_repo
+                    .load_settings()
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Failed to load metrics settings, using defaults: {e}");
+                        None
+                    })
+                    .unwrap_or_default();
+
+                // OpenMetrics endpoint serving the collector's latest metrics
+                // (off unless enabled in the metrics settings)
+                let latest_metrics = Arc::new(LatestMetrics::default());
+                let metrics_exporter = Arc::new(OpenMetricsExporter::new(latest_metrics.clone()));
+                if let Err(e) = metrics_exporter.apply(&metrics_settings).await {
+                    tracing::warn!("Failed to start the OpenMetrics endpoint: {e}");
+                }
+                let metrics_settings = Arc::new(tokio::sync::RwLock::new(metrics_settings));
+
+                // Alert notifications: desktop, webhook and email channels
+                let alert_dispatcher = Arc::new(
+                    AlertDispatcher::new(notification_repo.clone())
+                        .with_sender(
+                            ChannelKind::Desktop,
+                            Arc::new(DesktopNotifier::new(app_handle.clone())),
+                        )
+                        .with_sender(ChannelKind::Webhook, Arc::new(WebhookNotifier::new()))
+                        .with_sender(ChannelKind::Email, Arc::new(EmailNotifier::new())),
+                );
+
+                // Spawn background metrics collector (2s loop by default)
                 let collector = MetricsCollector::new(
                     monitoring.clone(),
                     metrics_repo.clone(),
                     alerting.clone(),
                     wsl_manager.clone(),
                     alert_thresholds.clone(),
-                );
+                    alert_suppressions.clone(),
+                    alert_dispatcher.clone(),
+                )
+                .with_settings(metrics_settings.clone())
+                .with_latest_metrics(latest_metrics);
                 let collector_handle = app_handle.clone();
                 tokio::spawn(async move {
                     collector.run(collector_handle).await;
                 });
 
                 // Spawn background metrics aggregator (60s loop)
-                let aggregator = MetricsAggregator::new(metrics_repo.clone(), alerting.clone());
+                let aggregator = MetricsAggregator::new(metrics_repo.clone(), alerting.clone())
+                    .with_settings(metrics_settings.clone());
                 tokio::spawn(async move {
                     aggregator.run().await;
                 });
 
+                // Running snapshot exports/imports report progress as events
+                let progress_handle = app_handle.clone();
+                let transfers = Arc::new(TransferRegistry::new(Arc::new(move |progress| {
+                    let _ = progress_handle.emit(EVENT_SNAPSHOT_PROGRESS, progress);
+                })));
+
+                // Spawn background snapshot scheduler (60s loop)
+                let scheduler = SnapshotScheduler::new(
+                    wsl_manager.clone(),
+                    snapshot_repo.clone(),
+                    schedule_repo.clone(),
+                    audit_logger.clone(),
+                )
+                .with_transfers(transfers.clone());
+                tokio::spawn(async move {
+                    scheduler.run().await;
+                });
+
+                // Offsite replication: connect to the saved target and spawn the
+                // automatic upload loop (5 min; idle unless auto-replicate is on)
+                let replicator = Arc::new(
+                    SnapshotReplicator::new(
+                        snapshot_repo.clone(),
+                        replication_repo,
+                        audit_logger.clone(),
+                        Arc::new(|target| {
+                            Ok(Arc::new(S3ObjectStore::new(target.clone())?) as Arc<_>)
+                        }),
+                    )
+                    .with_transfers(transfers.clone()),
+                );
+                if let Err(e) = replicator.load().await {
+                    tracing::warn!("Replication target could not be loaded: {e}");
+                }
+                tokio::spawn(replicator.clone().run());
+
+                // Spawn background snapshot verification (daily; the first pass
+                // waits 10 min so startup is not competing with re-hashing)
+                let verifier =
+                    VerifySnapshotHandler::new(snapshot_repo.clone(), audit_logger.clone());
+                let verifier_handle = app_handle.clone();
+                tokio::spawn(async move {
+                    let day = std::time::Duration::from_secs(24 * 3600);
+                    let first = tokio::time::Instant::now() + std::time::Duration::from_secs(600);
+                    let mut interval = tokio::time::interval_at(first, day);
+                    loop {
+                        interval.tick().await;
+                        match verifier.verify_all().await {
+                            Ok(report) => {
+                                let _ = verifier_handle.emit(EVENT_SNAPSHOTS_VERIFIED, report);
+                            }
+                            Err(e) => tracing::warn!("scheduled snapshot verification: {e}"),
+                        }
+                    }
+                });
+
                 let app_state = AppState {
                     wsl_manager,
                     snapshot_repo,
@@ -211,8 +353,18 @@ pub fn run() {
                     alerting,
                     audit_logger,
                     alert_thresholds,
+                    alert_suppressions,
+                    metrics_settings,
+                    metrics_exporter,
                     port_forwarding,
                     port_rules_repo,
+                    schedule_repo,
+                    transfers,
+                    replicator,
+                    safety_policies,
+                    templates,
+                    notification_repo,
+                    alert_dispatcher,
                 };
 
                 app_handle.manage(app_state);
@@ -276,13 +428,54 @@ pub fn run() {
             snapshot_commands::create_snapshot,
             snapshot_commands::delete_snapshot,
             snapshot_commands::restore_snapshot,
+            snapshot_commands::preflight_restore,
+            snapshot_commands::verify_snapshot,
+            snapshot_commands::verify_all_snapshots,
+            snapshot_commands::rescan_snapshots,
+            snapshot_commands::cancel_snapshot,
+            snapshot_commands::diff_snapshots,
+            snapshot_commands::list_snapshot_contents,
+            snapshot_commands::list_snapshot_tags,
+            snapshot_commands::update_snapshot_metadata,
+            snapshot_commands::rekey_snapshot,
+            schedule_commands::list_snapshot_schedules,
+            schedule_commands::save_snapshot_schedule,
+            schedule_commands::delete_snapshot_schedule,
+            schedule_commands::run_snapshot_schedule,
+            replication_commands::get_replication_target,
+            replication_commands::set_replication_target,
+            replication_commands::replicate_snapshot,
+            replication_commands::list_replications,
+            safety_commands::list_safety_policies,
+            safety_commands::save_safety_policy,
+            safety_commands::delete_safety_policy,
+            template_commands::list_templates,
+            template_commands::save_template,
+            template_commands::delete_template,
+            template_commands::provision_from_template,
+            notification_commands::list_notification_channels,
+            notification_commands::save_notification_channel,
+            notification_commands::delete_notification_channel,
+            notification_commands::test_notification_channel,
+            notification_commands::list_notification_deliveries,
             monitoring_commands::get_system_metrics,
             monitoring_commands::get_processes,
+            monitoring_commands::get_top_processes,
+            monitoring_commands::get_service_usage,
+            monitoring_commands::export_metrics,
             monitoring_commands::get_metrics_history,
+            monitoring_commands::get_metrics_settings,
+            monitoring_commands::set_metrics_settings,
             monitoring_commands::get_alert_thresholds,
             monitoring_commands::set_alert_thresholds,
             monitoring_commands::get_recent_alerts,
             monitoring_commands::acknowledge_alert,
+            silence_commands::list_alert_silences,
+            silence_commands::create_alert_silence,
+            silence_commands::expire_alert_silence,
+            silence_commands::list_maintenance_windows,
+            silence_commands::save_maintenance_window,
+            silence_commands::delete_maintenance_window,
             settings_commands::get_wsl_config,
             settings_commands::update_wsl_config,
             settings_commands::compact_vhdx,
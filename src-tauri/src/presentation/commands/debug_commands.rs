@@ -91,7 +91,7 @@ pub async fn debug_test_restore(
     let _ = wsl.shutdown_all().await;
     tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     if let Err(e) = wsl
-        .export_distro(&source, &source_tar_str, ExportFormat::Tar)
+        .export_distro(&source, &source_tar_str, ExportFormat::Tar, None)
         .await
     {
         cleanup(wsl, &clone_name, &temp_dir).await;
@@ -117,6 +117,7 @@ pub async fn debug_test_restore(
             &clone_dir_str,
             &source_tar_str,
             ExportFormat::Tar,
+            None,
         )
         .await
     {
@@ -153,7 +154,7 @@ pub async fn debug_test_restore(
     let snapshot_tar = temp_dir.join("snapshot.tar");
     let snapshot_tar_str = snapshot_tar.to_string_lossy().to_string();
     if let Err(e) = wsl
-        .export_distro(&clone_name, &snapshot_tar_str, ExportFormat::Tar)
+        .export_distro(&clone_name, &snapshot_tar_str, ExportFormat::Tar, None)
         .await
     {
         cleanup(wsl, &clone_name, &temp_dir).await;
@@ -225,6 +226,7 @@ pub async fn debug_test_restore(
             &clone_dir_str,
             &snapshot_tar_str,
             ExportFormat::Tar,
+            None,
         )
         .await
     {
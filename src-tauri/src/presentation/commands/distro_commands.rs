@@ -4,6 +4,8 @@ use tracing::instrument;
 use crate::application::commands::delete_distro::{DeleteDistroCommand, DeleteDistroHandler};
 use crate::application::dto::responses::DistroResponse;
 use crate::application::queries::list_distros::ListDistrosHandler;
+use crate::application::services::safety_snapshot::with_safety_note;
+use crate::domain::entities::safety_snapshot::DestructiveOperation;
 use crate::domain::errors::DomainError;
 use crate::domain::services::distro_service::DistroService;
 use crate::domain::value_objects::DistroName;
@@ -73,10 +75,18 @@ pub(crate) async fn resize_vhd_inner(
     state: &AppState,
 ) -> Result<(), DomainError> {
     let distro_name = DistroName::new(&name)?;
+    let safety = state
+        .safety_guard()
+        .protect(&distro_name, DestructiveOperation::ResizeVhd)
+        .await?;
     state.wsl_manager.resize_vhd(&distro_name, &size).await?;
     state
         .audit_logger
-        .log_with_details("vhdx.resize", &name, &format!("Resized to {size}"))
+        .log_with_details(
+            "vhdx.resize",
+            &name,
+            &with_safety_note(format!("Resized to {size}"), safety.as_ref()),
+        )
         .await?;
     Ok(())
 }
@@ -170,30 +180,45 @@ pub async fn delete_distro(
         state.alerting.clone(),
         state.port_rules_repo.clone(),
         state.audit_logger.clone(),
-    );
+    )
+    .with_safety(state.safety_guard());
     handler
         .handle(DeleteDistroCommand {
             distro_name,
             delete_snapshots,
         })
-        .await
+        .await?;
+
+    // Schedules of a deleted distro could only ever fail; its safety policy
+    // would apply to an unrelated distro reusing the name.
+    state.schedule_repo.delete_by_distro(&name).await?;
+    state.safety_policies.delete(&name).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
     use std::sync::Arc;
 
     use crate::domain::entities::distro::Distro;
     use crate::domain::ports::alerting::MockAlertingPort;
     use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
     use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
     use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
     use crate::domain::ports::port_forwarding::{
         MockPortForwardRulesRepository, MockPortForwardingPort,
     };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
     use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
     use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
     use crate::domain::value_objects::{DistroState, WslVersion};
 
     fn make_test_state(wsl: MockWslManagerPort, audit: MockAuditLoggerPort) -> AppState {
@@ -204,9 +229,30 @@ mod tests {
             metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
             alerting: Arc::new(MockAlertingPort::new()),
             audit_logger: Arc::new(audit),
-            alert_thresholds: Arc::new(tokio::sync::RwLock::new(vec![])),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
             port_forwarding: Arc::new(MockPortForwardingPort::new()),
             port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new({
+                let mut policies = MockSafetyPolicyRepositoryPort::new();
+                policies.expect_get().returning(|_| Ok(None));
+                policies
+            }),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
         }
     }
 
@@ -331,4 +377,46 @@ mod tests {
         let result = resize_vhd_inner("Ubuntu".into(), "100GB".into(), &state).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn resize_vhd_is_not_run_when_its_safety_snapshot_fails() {
+        use crate::domain::entities::safety_snapshot::SafetySnapshotPolicy;
+        use crate::domain::entities::snapshot::ExportFormat;
+
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_distro()
+            .returning(|_| Ok(make_distro("Ubuntu", DistroState::Stopped)));
+        wsl.expect_get_default_user().returning(|_| Ok(None));
+        wsl.expect_exec_in_distro()
+            .returning(|_, _| Ok(String::new()));
+        wsl.expect_terminate_distro().returning(|_| Ok(()));
+        wsl.expect_shutdown_all().returning(|| Ok(()));
+        wsl.expect_start_distro().returning(|_| Ok(()));
+        wsl.expect_export_distro()
+            .returning(|_, _, _, _| Err(DomainError::WslCliError("disk full".into())));
+        wsl.expect_resize_vhd().never();
+
+        let mut policies = MockSafetyPolicyRepositoryPort::new();
+        policies.expect_get().returning(|_| {
+            Ok(Some(SafetySnapshotPolicy {
+                distro_name: DistroName::new("Ubuntu").unwrap(),
+                operations: vec![DestructiveOperation::ResizeVhd],
+                format: ExportFormat::Tar,
+                output_dir: "/tmp".into(),
+                keep_last: 3,
+                max_age_hours: 0,
+                updated_at: chrono::Utc::now(),
+            }))
+        });
+        let mut snapshots = MockSnapshotRepositoryPort::new();
+        snapshots.expect_save().returning(|_| Ok(()));
+
+        let mut state = make_test_state(wsl, MockAuditLoggerPort::new());
+        state.safety_policies = Arc::new(policies);
+        state.snapshot_repo = Arc::new(snapshots);
+        let err = resize_vhd_inner("Ubuntu".into(), "100GB".into(), &state)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Safety snapshot"), "{err}");
+    }
 }
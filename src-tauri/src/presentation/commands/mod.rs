@@ -2,7 +2,13 @@ pub mod audit_commands;
 pub mod debug_commands;
 pub mod distro_commands;
 pub mod monitoring_commands;
+pub mod notification_commands;
 pub mod port_forwarding_commands;
+pub mod replication_commands;
+pub mod safety_commands;
+pub mod schedule_commands;
 pub mod settings_commands;
+pub mod silence_commands;
 pub mod snapshot_commands;
+pub mod template_commands;
 pub mod terminal_commands;
@@ -3,9 +3,14 @@ use serde::{Deserialize, Serialize};
 use tauri::State;
 use tracing::instrument;
 
+use crate::application::commands::export_metrics::{ExportMetricsCommand, ExportMetricsHandler};
+use crate::application::services::metrics_export::MetricsExportFormat;
+use crate::domain::entities::metrics_settings::{MetricsSettings, MetricsTier};
 use crate::domain::entities::monitoring::{ProcessInfo, SystemMetrics};
+use crate::domain::entities::notification_channel::DESKTOP_CHANNEL_ID;
 use crate::domain::errors::DomainError;
-use crate::domain::ports::alerting::{AlertRecord, AlertThreshold};
+use crate::domain::ports::alerting::{AlertRecord, AlertThreshold, AlertUnit};
+use crate::domain::ports::metrics_repository::{ProcessUsage, RawMetricsRow, ServiceUsage};
 use crate::domain::value_objects::DistroName;
 use crate::presentation::state::AppState;
 
@@ -77,13 +82,12 @@ pub struct MetricsHistoryResponse {
     pub points: Vec<MetricsHistoryPoint>,
 }
 
-#[tauri::command]
-#[instrument(skip(state), fields(cmd = "get_metrics_history", distro = %distro_name))]
-pub async fn get_metrics_history(
+/// Inner logic for get_metrics_history, testable without Tauri runtime.
+pub(crate) async fn get_metrics_history_inner(
     distro_name: String,
     from: String,
     to: String,
-    state: State<'_, AppState>,
+    state: &AppState,
 ) -> Result<MetricsHistoryResponse, DomainError> {
     let name = DistroName::new(&distro_name)?;
 
@@ -94,39 +98,73 @@ pub async fn get_metrics_history(
         .parse()
         .map_err(|e| DomainError::Internal(format!("Invalid 'to' timestamp: {e}")))?;
 
-    let duration = to_dt - from_dt;
+    let tier = state
+        .metrics_settings
+        .read()
+        .await
+        .history_tier(from_dt, to_dt, Utc::now());
+
+    if let Some(tier) = tier {
+        // Rollup tier: 1-minute buckets up to a day, then 1-hour and 1-day
+        let agg_points = state
+            .metrics_repo
+            .query_aggregated(&name, tier, from_dt, to_dt)
+            .await?;
+
+        let points: Vec<MetricsHistoryPoint> = agg_points
+            .iter()
+            .map(|p| {
+                let duration_secs = (p.period_end - p.period_start).num_seconds().max(1) as u64;
+                MetricsHistoryPoint {
+                    timestamp: p.period_start.to_rfc3339(),
+                    cpu_avg: p.cpu_avg,
+                    cpu_min: Some(p.cpu_min),
+                    cpu_max: Some(p.cpu_max),
+                    mem_used_bytes: p.mem_used_avg,
+                    mem_total_bytes: p.mem_total,
+                    disk_usage_percent: p.disk_avg,
+                    net_rx_rate: p.net_rx_total / duration_secs,
+                    net_tx_rate: p.net_tx_total / duration_secs,
+                    swap_used_bytes: None,
+                    swap_total_bytes: None,
+                    context_switches: None,
+                    disk_io_read_bytes: None,
+                    disk_io_write_bytes: None,
+                    tcp_established: None,
+                    tcp_time_wait: None,
+                    tcp_listen: None,
+                    gpu_utilization: None,
+                    gpu_vram_used: None,
+                    gpu_vram_total: None,
+                }
+            })
+            .collect();
 
-    if duration <= chrono::Duration::hours(1) {
-        // Use raw data for ranges <= 1 hour
+        Ok(MetricsHistoryResponse {
+            distro_name,
+            granularity: tier.label().to_string(),
+            points,
+        })
+    } else {
+        // Raw samples for short, recent ranges
         let rows = state.metrics_repo.query_raw(&name, from_dt, to_dt).await?;
 
         let mut points = Vec::with_capacity(rows.len());
-        let mut prev_rx: Option<u64> = None;
-        let mut prev_tx: Option<u64> = None;
+        let mut prev: Option<&RawMetricsRow> = None;
 
         for row in &rows {
-            // Calculate network rate from consecutive samples
-            let net_rx_rate = prev_rx
-                .map(|prev| {
-                    if row.net_rx_bytes >= prev {
-                        (row.net_rx_bytes - prev) / 2
-                    } else {
-                        0
-                    }
-                })
-                .unwrap_or(0);
-            let net_tx_rate = prev_tx
-                .map(|prev| {
-                    if row.net_tx_bytes >= prev {
-                        (row.net_tx_bytes - prev) / 2
-                    } else {
-                        0
-                    }
+            // Calculate network rate from consecutive samples (the collection
+            // interval is a setting, so use the actual time between them)
+            let rate = |bytes: fn(&RawMetricsRow) -> u64| {
+                prev.map(|prev| {
+                    let secs = (row.timestamp - prev.timestamp).num_seconds().max(1) as u64;
+                    bytes(row).saturating_sub(bytes(prev)) / secs
                 })
-                .unwrap_or(0);
-
-            prev_rx = Some(row.net_rx_bytes);
-            prev_tx = Some(row.net_tx_bytes);
+                .unwrap_or(0)
+            };
+            let net_rx_rate = rate(|r| r.net_rx_bytes);
+            let net_tx_rate = rate(|r| r.net_tx_bytes);
+            prev = Some(row);
 
             points.push(MetricsHistoryPoint {
                 timestamp: row.timestamp.to_rfc3339(),
@@ -157,72 +195,448 @@ pub async fn get_metrics_history(
             granularity: "raw".to_string(),
             points,
         })
+    }
+}
+
+/// History of a distro over `from`..`to`, read from the raw samples or the
+/// rollup tier suited to the range (see `MetricsSettings::history_tier`).
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "get_metrics_history", distro = %distro_name))]
+pub async fn get_metrics_history(
+    distro_name: String,
+    from: String,
+    to: String,
+    state: State<'_, AppState>,
+) -> Result<MetricsHistoryResponse, DomainError> {
+    get_metrics_history_inner(distro_name, from, to, &state).await
+}
+
+// --- New commands: Per-process history ---
+
+const DEFAULT_PROCESS_WINDOW_SECS: u32 = 60;
+const MAX_PROCESS_WINDOW_SECS: u32 = 3600;
+const DEFAULT_TOP_PROCESSES: u32 = 10;
+const MAX_TOP_PROCESSES: u32 = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopProcessesResponse {
+    pub distro_name: String,
+    pub granularity: String,
+    pub from: String,
+    pub to: String,
+    pub processes: Vec<ProcessUsage>,
+}
+
+/// Inner logic for get_top_processes, testable without Tauri runtime.
+pub(crate) async fn get_top_processes_inner(
+    distro_name: String,
+    at: String,
+    window_secs: Option<u32>,
+    limit: Option<u32>,
+    state: &AppState,
+) -> Result<TopProcessesResponse, DomainError> {
+    let name = DistroName::new(&distro_name)?;
+    let at: DateTime<Utc> = at
+        .parse()
+        .map_err(|e| DomainError::Internal(format!("Invalid 'at' timestamp: {e}")))?;
+    let window = chrono::Duration::seconds(
+        window_secs
+            .unwrap_or(DEFAULT_PROCESS_WINDOW_SECS)
+            .clamp(1, MAX_PROCESS_WINDOW_SECS)
+            .into(),
+    );
+    let limit = limit
+        .unwrap_or(DEFAULT_TOP_PROCESSES)
+        .clamp(1, MAX_TOP_PROCESSES);
+    let (from, to) = (at - window, at + window);
+
+    let raw_retention = state.metrics_settings.read().await.raw_retention();
+    let (granularity, processes) = if from >= Utc::now() - raw_retention {
+        let processes = state
+            .metrics_repo
+            .query_top_processes_raw(&name, from, to, limit)
+            .await?;
+        ("raw", processes)
     } else {
-        // Use aggregated 1-minute data for ranges > 1 hour
-        let agg_points = state
+        let processes = state
             .metrics_repo
-            .query_aggregated(&name, from_dt, to_dt)
+            .query_top_processes_aggregated(&name, from, to, limit)
             .await?;
+        ("1m", processes)
+    };
 
-        let points: Vec<MetricsHistoryPoint> = agg_points
-            .iter()
-            .map(|p| {
-                let duration_secs = (p.period_end - p.period_start).num_seconds().max(1) as u64;
-                MetricsHistoryPoint {
-                    timestamp: p.period_start.to_rfc3339(),
-                    cpu_avg: p.cpu_avg,
-                    cpu_min: Some(p.cpu_min),
-                    cpu_max: Some(p.cpu_max),
-                    mem_used_bytes: p.mem_used_avg,
-                    mem_total_bytes: p.mem_total,
-                    disk_usage_percent: p.disk_avg,
-                    net_rx_rate: p.net_rx_total / duration_secs,
-                    net_tx_rate: p.net_tx_total / duration_secs,
-                    swap_used_bytes: None,
-                    swap_total_bytes: None,
-                    context_switches: None,
-                    disk_io_read_bytes: None,
-                    disk_io_write_bytes: None,
-                    tcp_established: None,
-                    tcp_time_wait: None,
-                    tcp_listen: None,
-                    gpu_utilization: None,
-                    gpu_vram_used: None,
-                    gpu_vram_total: None,
-                }
-            })
-            .collect();
+    Ok(TopProcessesResponse {
+        distro_name,
+        granularity: granularity.to_string(),
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        processes,
+    })
+}
 
-        Ok(MetricsHistoryResponse {
-            distro_name,
-            granularity: "1m".to_string(),
-            points,
-        })
+/// The processes that used the most CPU within `window_secs` (default 60)
+/// either side of `at`, to tell which one caused a spike.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "get_top_processes", distro = %distro_name))]
+pub async fn get_top_processes(
+    distro_name: String,
+    at: String,
+    window_secs: Option<u32>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<TopProcessesResponse, DomainError> {
+    get_top_processes_inner(distro_name, at, window_secs, limit, &state).await
+}
+
+// --- New commands: Per-service history ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceUsageResponse {
+    pub distro_name: String,
+    pub granularity: String,
+    pub from: String,
+    pub to: String,
+    pub services: Vec<ServiceUsage>,
+}
+
+/// Inner logic for get_service_usage, testable without Tauri runtime.
+pub(crate) async fn get_service_usage_inner(
+    distro_name: String,
+    from: String,
+    to: String,
+    state: &AppState,
+) -> Result<ServiceUsageResponse, DomainError> {
+    let name = DistroName::new(&distro_name)?;
+    let from: DateTime<Utc> = from
+        .parse()
+        .map_err(|e| DomainError::Internal(format!("Invalid 'from' timestamp: {e}")))?;
+    let to: DateTime<Utc> = to
+        .parse()
+        .map_err(|e| DomainError::Internal(format!("Invalid 'to' timestamp: {e}")))?;
+
+    let raw_retention = state.metrics_settings.read().await.raw_retention();
+    let (granularity, services) = if from >= Utc::now() - raw_retention {
+        let services = state
+            .metrics_repo
+            .query_service_usage_raw(&name, from, to)
+            .await?;
+        ("raw", services)
+    } else {
+        let services = state
+            .metrics_repo
+            .query_service_usage_aggregated(&name, from, to)
+            .await?;
+        ("1m", services)
+    };
+
+    Ok(ServiceUsageResponse {
+        distro_name,
+        granularity: granularity.to_string(),
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        services,
+    })
+}
+
+/// CPU, memory and I/O of each systemd unit of a distro booted with systemd
+/// over a time range, by peak memory.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "get_service_usage", distro = %distro_name))]
+pub async fn get_service_usage(
+    distro_name: String,
+    from: String,
+    to: String,
+    state: State<'_, AppState>,
+) -> Result<ServiceUsageResponse, DomainError> {
+    get_service_usage_inner(distro_name, from, to, &state).await
+}
+
+// --- New commands: Metrics export ---
+
+#[derive(Debug, Deserialize)]
+pub struct ExportMetricsArgs {
+    pub distro_names: Vec<String>,
+    pub from: String,
+    pub to: String,
+    /// "raw", "1m", "1h" or "1d"; by default the tier the history chart
+    /// would read for the range.
+    pub granularity: Option<String>,
+    pub format: MetricsExportFormat,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsExportResponse {
+    pub path: String,
+    pub granularity: String,
+    pub rows: u64,
+    pub bytes: u64,
+}
+
+/// Inner logic for export_metrics, testable without Tauri runtime.
+pub(crate) async fn export_metrics_inner(
+    args: ExportMetricsArgs,
+    state: &AppState,
+) -> Result<MetricsExportResponse, DomainError> {
+    let distros = args
+        .distro_names
+        .iter()
+        .map(|name| DistroName::new(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let from: DateTime<Utc> = args
+        .from
+        .parse()
+        .map_err(|e| DomainError::Internal(format!("Invalid 'from' timestamp: {e}")))?;
+    let to: DateTime<Utc> = args
+        .to
+        .parse()
+        .map_err(|e| DomainError::Internal(format!("Invalid 'to' timestamp: {e}")))?;
+    if args.path.trim().is_empty() {
+        return Err(DomainError::ConfigError(
+            "Choose a file to export to".into(),
+        ));
     }
+    let tier = match args.granularity.as_deref() {
+        None => state
+            .metrics_settings
+            .read()
+            .await
+            .history_tier(from, to, Utc::now()),
+        Some("raw") => None,
+        Some(label) => Some(
+            MetricsTier::ALL
+                .into_iter()
+                .find(|tier| tier.label() == label)
+                .ok_or_else(|| {
+                    DomainError::ConfigError(format!("Unknown metrics granularity '{label}'"))
+                })?,
+        ),
+    };
+    let granularity = tier.map_or("raw", MetricsTier::label).to_string();
+
+    let report = ExportMetricsHandler::new(state.metrics_repo.clone())
+        .handle(ExportMetricsCommand {
+            distros,
+            from,
+            to,
+            tier,
+            format: args.format,
+            path: args.path.clone().into(),
+        })
+        .await?;
+
+    let detail = format!(
+        "{} ({}, {granularity}, {} rows) -> {}",
+        args.distro_names.join(", "),
+        args.format.label(),
+        report.rows,
+        args.path
+    );
+    state.audit("metrics.export", &detail).await?;
+
+    Ok(MetricsExportResponse {
+        path: args.path,
+        granularity,
+        rows: report.rows,
+        bytes: report.bytes,
+    })
+}
+
+/// Write the metrics history of one or more distros over a range to a CSV,
+/// JSON Lines or Arrow IPC file, reading it in batches.
+#[tauri::command]
+#[instrument(skip(state, args), fields(cmd = "export_metrics", format = args.format.label()))]
+pub async fn export_metrics(
+    args: ExportMetricsArgs,
+    state: State<'_, AppState>,
+) -> Result<MetricsExportResponse, DomainError> {
+    export_metrics_inner(args, &state).await
 }
 
 // --- New commands: Alert thresholds ---
 
+/// Inner logic for get_alert_thresholds, testable without Tauri runtime.
+pub(crate) async fn get_alert_thresholds_inner(
+    distro_name: Option<String>,
+    state: &AppState,
+) -> Result<Vec<AlertThreshold>, DomainError> {
+    let thresholds = state.alert_thresholds.read().await;
+    Ok(match distro_name {
+        Some(distro) => thresholds.effective(DistroName::new(&distro)?.as_str()),
+        None => thresholds.defaults().to_vec(),
+    })
+}
+
+/// Global defaults, or with `distro_name` the thresholds in force for that
+/// distro (overrides carry its name, inherited defaults don't).
 #[tauri::command]
 #[instrument(skip(state), fields(cmd = "get_alert_thresholds"))]
 pub async fn get_alert_thresholds(
+    distro_name: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<Vec<AlertThreshold>, DomainError> {
-    let thresholds = state.alert_thresholds.read().await;
-    Ok(thresholds.clone())
+    get_alert_thresholds_inner(distro_name, &state).await
+}
+
+/// Inner logic for set_alert_thresholds, testable without Tauri runtime.
+pub(crate) async fn set_alert_thresholds_inner(
+    thresholds: Vec<AlertThreshold>,
+    distro_name: Option<String>,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    let distro = distro_name
+        .map(|d| DistroName::new(&d).map(|n| n.as_str().to_string()))
+        .transpose()?;
+    let mut seen = Vec::new();
+    let mut thresholds = thresholds;
+    for threshold in &mut thresholds {
+        if !threshold.alert_type.supports(threshold.unit) {
+            return Err(DomainError::ConfigError(format!(
+                "The {} threshold can't be expressed in {} units",
+                threshold.alert_type, threshold.unit
+            )));
+        }
+        let in_range = match threshold.unit {
+            AlertUnit::Percent => {
+                threshold.threshold_percent > 0.0 && threshold.threshold_percent <= 100.0
+            }
+            AlertUnit::Absolute => {
+                threshold.threshold_percent.is_finite() && threshold.threshold_percent > 0.0
+            }
+        };
+        if !in_range {
+            return Err(DomainError::ConfigError(format!(
+                "The {} threshold must be above 0{}",
+                threshold.alert_type,
+                if threshold.unit == AlertUnit::Percent {
+                    " and at most 100%"
+                } else {
+                    ""
+                }
+            )));
+        }
+        if let Some(clear) = threshold.clear_percent
+            && !(0.0..=threshold.threshold_percent).contains(&clear)
+        {
+            return Err(DomainError::ConfigError(format!(
+                "The {} clear threshold must be between 0 and the alert threshold",
+                threshold.alert_type
+            )));
+        }
+        if seen.contains(&threshold.alert_type) {
+            return Err(DomainError::ConfigError(format!(
+                "The {} threshold is given twice",
+                threshold.alert_type
+            )));
+        }
+        seen.push(threshold.alert_type);
+        threshold.distro_name = distro.clone();
+    }
+
+    // Rules may only route to channels that exist
+    if thresholds.iter().any(|t| !t.channels.is_empty()) {
+        let channels = state.notification_repo.list_channels().await?;
+        let unknown = thresholds
+            .iter()
+            .flat_map(|t| &t.channels)
+            .find(|id| *id != DESKTOP_CHANNEL_ID && !channels.iter().any(|c| &c.id == *id));
+        if let Some(id) = unknown {
+            return Err(DomainError::ConfigError(format!(
+                "Notification channel '{id}' not found"
+            )));
+        }
+    }
+
+    state
+        .alerting
+        .save_thresholds(distro.clone(), thresholds.clone())
+        .await?;
+    let mut current = state.alert_thresholds.write().await;
+    match &distro {
+        Some(distro) => current.set_overrides(distro, thresholds),
+        None => current.set_defaults(thresholds),
+    }
+    drop(current);
+
+    state
+        .audit(
+            "alerts.thresholds.set",
+            distro.as_deref().unwrap_or("defaults"),
+        )
+        .await?;
+    Ok(())
 }
 
+/// Update the global defaults of the given alert types, or with
+/// `distro_name` replace that distro's overrides (an empty list goes back to
+/// the defaults).
 #[tauri::command]
 #[instrument(skip(state, thresholds), fields(cmd = "set_alert_thresholds"))]
 pub async fn set_alert_thresholds(
     thresholds: Vec<AlertThreshold>,
+    distro_name: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<(), DomainError> {
-    let mut current = state.alert_thresholds.write().await;
-    *current = thresholds;
+    set_alert_thresholds_inner(thresholds, distro_name, &state).await
+}
+
+// --- New commands: Metrics settings ---
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "get_metrics_settings"))]
+pub async fn get_metrics_settings(
+    state: State<'_, AppState>,
+) -> Result<MetricsSettings, DomainError> {
+    Ok(state.metrics_settings.read().await.clone())
+}
+
+/// Inner logic for set_metrics_settings, testable without Tauri runtime.
+pub(crate) async fn set_metrics_settings_inner(
+    settings: MetricsSettings,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    settings.validate()?;
+    // A port that cannot be bound rejects the settings before they are saved
+    state.metrics_exporter.apply(&settings).await?;
+    if let Err(e) = state.metrics_repo.save_settings(&settings).await {
+        // Serve what is still stored, and cached, rather than what a restart drops
+        let previous = state.metrics_settings.read().await.clone();
+        if let Err(revert) = state.metrics_exporter.apply(&previous).await {
+            tracing::warn!("Cannot restore the OpenMetrics endpoint: {revert}");
+        }
+        return Err(e);
+    }
+    let openmetrics = if settings.openmetrics_enabled {
+        format!("port {}", settings.openmetrics_port)
+    } else {
+        "off".to_string()
+    };
+    let detail = format!(
+        "interval {}s, raw {}h, 1m {}h, 1h {}d, 1d {}d, OpenMetrics {openmetrics}",
+        settings.collection_interval_secs,
+        settings.raw_retention_hours,
+        settings.minute_retention_hours,
+        settings.hour_retention_days,
+        settings.day_retention_days
+    );
+    *state.metrics_settings.write().await = settings;
+
+    state.audit("metrics.settings.set", &detail).await?;
     Ok(())
 }
 
+/// Save the collection interval and the retention of each history tier,
+/// which apply from the next collection and aggregation pass, and start,
+/// move or stop the OpenMetrics endpoint right away.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "set_metrics_settings"))]
+pub async fn set_metrics_settings(
+    settings: MetricsSettings,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    set_metrics_settings_inner(settings, &state).await
+}
+
 // --- New commands: Alert records ---
 
 #[tauri::command]
@@ -251,17 +665,27 @@ pub async fn acknowledge_alert(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
     use std::sync::Arc;
 
-    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::entities::notification_channel::{ChannelConfig, NotificationChannel};
+    use crate::domain::ports::alerting::{AlertType, MockAlertingPort};
     use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
     use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
     use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
     use crate::domain::ports::port_forwarding::{
         MockPortForwardRulesRepository, MockPortForwardingPort,
     };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
     use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
     use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
     use crate::presentation::state::AppState;
 
     fn make_test_state(alerting: MockAlertingPort) -> AppState {
@@ -272,9 +696,26 @@ mod tests {
             metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
             alerting: Arc::new(alerting),
             audit_logger: Arc::new(MockAuditLoggerPort::new()),
-            alert_thresholds: Arc::new(tokio::sync::RwLock::new(vec![])),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
             port_forwarding: Arc::new(MockPortForwardingPort::new()),
             port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new(MockSafetyPolicyRepositoryPort::new()),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
         }
     }
 
@@ -286,33 +727,432 @@ mod tests {
         assert!(name_result.is_err());
     }
 
+    fn cpu_threshold(percent: f64) -> AlertThreshold {
+        AlertThreshold {
+            alert_type: AlertType::Cpu,
+            threshold_percent: percent,
+            enabled: true,
+            unit: AlertUnit::Percent,
+            distro_name: None,
+            sustained_secs: 0,
+            clear_percent: None,
+            channels: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn set_alert_thresholds_persists_override_and_resolves_it() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_save_thresholds()
+            .withf(|distro, thresholds| {
+                distro.as_deref() == Some("Ubuntu")
+                    && thresholds[0].distro_name.as_deref() == Some("Ubuntu")
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let mut state = make_test_state(alerting);
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| action == "alerts.thresholds.set" && detail == "Ubuntu")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        state.audit_logger = Arc::new(audit);
+
+        set_alert_thresholds_inner(vec![cpu_threshold(97.0)], Some("Ubuntu".into()), &state)
+            .await
+            .unwrap();
+
+        let ubuntu = get_alert_thresholds_inner(Some("Ubuntu".into()), &state)
+            .await
+            .unwrap();
+        assert_eq!(ubuntu.len(), AlertType::ALL.len());
+        assert!((ubuntu[0].threshold_percent - 97.0).abs() < f64::EPSILON);
+        let defaults = get_alert_thresholds_inner(None, &state).await.unwrap();
+        assert!((defaults[0].threshold_percent - 90.0).abs() < f64::EPSILON);
+        assert!(defaults[0].distro_name.is_none());
+    }
+
     #[tokio::test]
-    async fn alert_thresholds_read_write() {
-        let alerting = MockAlertingPort::new();
+    async fn set_alert_thresholds_rejects_out_of_range_and_duplicates() {
+        let mut alerting = MockAlertingPort::new();
+        alerting.expect_save_thresholds().never();
         let state = make_test_state(alerting);
 
-        // Read initial empty thresholds
-        let thresholds = state.alert_thresholds.read().await;
-        assert!(thresholds.is_empty());
-        drop(thresholds);
+        assert!(
+            set_alert_thresholds_inner(vec![cpu_threshold(150.0)], None, &state)
+                .await
+                .is_err()
+        );
+        assert!(
+            set_alert_thresholds_inner(
+                vec![cpu_threshold(80.0), cpu_threshold(90.0)],
+                None,
+                &state
+            )
+            .await
+            .is_err()
+        );
+    }
 
-        // Write thresholds
-        let new_thresholds = vec![AlertThreshold {
-            alert_type: crate::domain::ports::alerting::AlertType::Cpu,
-            threshold_percent: 90.0,
-            enabled: true,
-        }];
-        let mut current = state.alert_thresholds.write().await;
-        *current = new_thresholds;
-        drop(current);
-
-        // Read back
-        let thresholds = state.alert_thresholds.read().await;
-        assert_eq!(thresholds.len(), 1);
+    #[tokio::test]
+    async fn set_alert_thresholds_rejects_unknown_channels() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_save_thresholds()
+            .withf(|_, thresholds| thresholds[0].channels == ["hook", "desktop"])
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let mut state = make_test_state(alerting);
+        let mut notifications = MockNotificationRepositoryPort::new();
+        notifications.expect_list_channels().returning(|| {
+            Ok(vec![NotificationChannel {
+                id: "hook".into(),
+                name: "Slack".into(),
+                enabled: true,
+                config: ChannelConfig::Webhook {
+                    url: "https://hooks.slack.com/services/T/B/X".into(),
+                },
+            }])
+        });
+        state.notification_repo = Arc::new(notifications);
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log().times(1).returning(|_, _| Ok(()));
+        state.audit_logger = Arc::new(audit);
+        let routed = |channels: &[&str]| AlertThreshold {
+            channels: channels.iter().map(|c| c.to_string()).collect(),
+            ..cpu_threshold(90.0)
+        };
+
+        assert!(
+            set_alert_thresholds_inner(vec![routed(&["deleted"])], None, &state)
+                .await
+                .is_err()
+        );
+        set_alert_thresholds_inner(vec![routed(&["hook", "desktop"])], None, &state)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn set_alert_thresholds_validates_units() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_save_thresholds()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let mut state = make_test_state(alerting);
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log().times(1).returning(|_, _| Ok(()));
+        state.audit_logger = Arc::new(audit);
+        let rule = |alert_type, value, unit| AlertThreshold {
+            unit,
+            alert_type,
+            ..cpu_threshold(value)
+        };
+
+        // CPU is percent only; TIME_WAIT is a count
+        for invalid in [
+            rule(AlertType::Cpu, 50.0, AlertUnit::Absolute),
+            rule(AlertType::TcpTimeWait, 50.0, AlertUnit::Percent),
+            rule(AlertType::Swap, 0.0, AlertUnit::Absolute),
+        ] {
+            assert!(
+                set_alert_thresholds_inner(vec![invalid], None, &state)
+                    .await
+                    .is_err()
+            );
+        }
+        // Absolute thresholds aren't capped at 100
+        set_alert_thresholds_inner(
+            vec![
+                rule(AlertType::TcpTimeWait, 5000.0, AlertUnit::Absolute),
+                rule(AlertType::Swap, 2e9, AlertUnit::Absolute),
+            ],
+            None,
+            &state,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn set_metrics_settings_validates_persists_and_caches() {
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_save_settings()
+            .withf(|settings| settings.hour_retention_days == 90)
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut state = make_test_state(MockAlertingPort::new());
+        state.metrics_repo = Arc::new(metrics_repo);
+        let mut exporter = MockMetricsExporterPort::new();
+        exporter.expect_apply().times(1).returning(|_| Ok(()));
+        state.metrics_exporter = Arc::new(exporter);
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| {
+                action == "metrics.settings.set"
+                    && detail.contains("1h 90d")
+                    && detail.ends_with("OpenMetrics off")
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        state.audit_logger = Arc::new(audit);
+
+        let invalid = MetricsSettings {
+            collection_interval_secs: 0,
+            ..Default::default()
+        };
+        assert!(set_metrics_settings_inner(invalid, &state).await.is_err());
+
+        let settings = MetricsSettings {
+            hour_retention_days: 90,
+            ..Default::default()
+        };
+        set_metrics_settings_inner(settings.clone(), &state)
+            .await
+            .unwrap();
+        assert_eq!(*state.metrics_settings.read().await, settings);
+    }
+
+    #[tokio::test]
+    async fn set_metrics_settings_keeps_the_old_settings_when_the_port_is_taken() {
+        let mut state = make_test_state(MockAlertingPort::new());
+        let mut exporter = MockMetricsExporterPort::new();
+        exporter
+            .expect_apply()
+            .withf(|settings| settings.openmetrics_enabled && settings.openmetrics_port == 9500)
+            .times(1)
+            .returning(|_| Err(DomainError::ConfigError("port in use".into())));
+        state.metrics_exporter = Arc::new(exporter);
+
+        let settings = MetricsSettings {
+            openmetrics_enabled: true,
+            openmetrics_port: 9500,
+            ..Default::default()
+        };
+        assert!(set_metrics_settings_inner(settings, &state).await.is_err());
+        assert_eq!(
+            *state.metrics_settings.read().await,
+            MetricsSettings::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn set_metrics_settings_restores_the_endpoint_when_the_save_fails() {
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_save_settings()
+            .times(1)
+            .returning(|_| Err(DomainError::DatabaseError("database is locked".into())));
+        let mut exporter = MockMetricsExporterPort::new();
+        let mut seq = mockall::Sequence::new();
+        exporter
+            .expect_apply()
+            .withf(|settings| settings.openmetrics_enabled)
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+        exporter
+            .expect_apply()
+            .withf(|settings| *settings == MetricsSettings::default())
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok(()));
+        let mut state = make_test_state(MockAlertingPort::new());
+        state.metrics_repo = Arc::new(metrics_repo);
+        state.metrics_exporter = Arc::new(exporter);
+
+        let settings = MetricsSettings {
+            openmetrics_enabled: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            set_metrics_settings_inner(settings, &state).await,
+            Err(DomainError::DatabaseError(_))
+        ));
+        assert_eq!(
+            *state.metrics_settings.read().await,
+            MetricsSettings::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_metrics_history_reads_the_tier_for_the_range() {
+        use crate::domain::entities::metrics_settings::MetricsTier;
+
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_query_raw()
+            .times(1)
+            .returning(|_, _, _| Ok(vec![]));
+        metrics_repo
+            .expect_query_aggregated()
+            .withf(|_, tier, _, _| *tier == MetricsTier::Hour)
+            .times(1)
+            .returning(|_, _, _, _| Ok(vec![]));
+        let mut state = make_test_state(MockAlertingPort::new());
+        state.metrics_repo = Arc::new(metrics_repo);
+
+        let now = Utc::now();
+        let history = |days: i64, hours: i64| {
+            let from = now - chrono::Duration::days(days) - chrono::Duration::hours(hours);
+            get_metrics_history_inner("Ubuntu".into(), from.to_rfc3339(), now.to_rfc3339(), &state)
+        };
+        assert_eq!(history(0, 1).await.unwrap().granularity, "raw");
+        assert_eq!(history(30, 0).await.unwrap().granularity, "1h");
+    }
+
+    #[tokio::test]
+    async fn get_top_processes_picks_samples_or_buckets_by_age() {
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_query_top_processes_raw()
+            .withf(|name, _, _, limit| name.as_str() == "Ubuntu" && *limit == 10)
+            .times(2)
+            .returning(|_, _, _, _| Ok(vec![]));
+        metrics_repo
+            .expect_query_top_processes_aggregated()
+            .withf(|_, _, _, limit| *limit == MAX_TOP_PROCESSES)
+            .times(1)
+            .returning(|_, _, _, _| Ok(vec![]));
+        let mut state = make_test_state(MockAlertingPort::new());
+        state.metrics_repo = Arc::new(metrics_repo);
+
+        let recent = (Utc::now() - chrono::Duration::minutes(5)).to_rfc3339();
+        let response = get_top_processes_inner("Ubuntu".into(), recent, Some(30), None, &state)
+            .await
+            .unwrap();
+        assert_eq!(response.granularity, "raw");
+
+        let hours_ago = (Utc::now() - chrono::Duration::hours(3)).to_rfc3339();
+        let response =
+            get_top_processes_inner("Ubuntu".into(), hours_ago.clone(), None, Some(500), &state)
+                .await
+                .unwrap();
+        assert_eq!(response.granularity, "1m");
+
+        // Samples are read for as long as the raw retention keeps them
+        state.metrics_settings.write().await.raw_retention_hours = 6;
+        let response = get_top_processes_inner("Ubuntu".into(), hours_ago, None, None, &state)
+            .await
+            .unwrap();
+        assert_eq!(response.granularity, "raw");
         assert_eq!(
-            thresholds[0].alert_type,
-            crate::domain::ports::alerting::AlertType::Cpu
+            chrono::DateTime::parse_from_rfc3339(&response.to).unwrap()
+                - chrono::DateTime::parse_from_rfc3339(&response.from).unwrap(),
+            chrono::Duration::seconds(120)
         );
+
+        assert!(
+            get_top_processes_inner("Ubuntu".into(), "14:32".into(), None, None, &state)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_service_usage_picks_samples_or_buckets_by_age() {
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_query_service_usage_raw()
+            .withf(|name, _, _| name.as_str() == "Ubuntu")
+            .times(2)
+            .returning(|_, from, to| {
+                Ok(vec![ServiceUsage {
+                    unit: "docker.service".to_string(),
+                    first_seen: from,
+                    last_seen: to,
+                    sample_count: 30,
+                    cpu_avg: 5.0,
+                    cpu_max: 20.0,
+                    memory_avg: 600_000_000,
+                    memory_max: 700_000_000,
+                    io_read_avg: 0,
+                    io_write_avg: 0,
+                }])
+            });
+        metrics_repo
+            .expect_query_service_usage_aggregated()
+            .times(1)
+            .returning(|_, _, _| Ok(vec![]));
+        let mut state = make_test_state(MockAlertingPort::new());
+        state.metrics_repo = Arc::new(metrics_repo);
+
+        let now = Utc::now();
+        let recent = (now - chrono::Duration::minutes(5)).to_rfc3339();
+        let response = get_service_usage_inner("Ubuntu".into(), recent, now.to_rfc3339(), &state)
+            .await
+            .unwrap();
+        assert_eq!(response.granularity, "raw");
+        assert_eq!(response.services[0].unit, "docker.service");
+
+        let hours_ago = (now - chrono::Duration::hours(3)).to_rfc3339();
+        let response =
+            get_service_usage_inner("Ubuntu".into(), hours_ago.clone(), now.to_rfc3339(), &state)
+                .await
+                .unwrap();
+        assert_eq!(response.granularity, "1m");
+
+        // Samples are read for as long as the raw retention keeps them
+        state.metrics_settings.write().await.raw_retention_hours = 6;
+        let response =
+            get_service_usage_inner("Ubuntu".into(), hours_ago, now.to_rfc3339(), &state)
+                .await
+                .unwrap();
+        assert_eq!(response.granularity, "raw");
+
+        assert!(
+            get_service_usage_inner("Ubuntu".into(), "14:32".into(), now.to_rfc3339(), &state)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn export_metrics_writes_the_requested_tier_and_audits() {
+        use crate::domain::entities::metrics_settings::MetricsTier;
+
+        let mut metrics_repo = MockMetricsRepositoryPort::new();
+        metrics_repo
+            .expect_query_aggregated()
+            .withf(|distro, tier, _, _| distro.as_str() == "Ubuntu" && *tier == MetricsTier::Hour)
+            .times(1)
+            .returning(|_, _, _, _| Ok(vec![]));
+        let mut state = make_test_state(MockAlertingPort::new());
+        state.metrics_repo = Arc::new(metrics_repo);
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| {
+                action == "metrics.export" && detail.starts_with("Ubuntu (csv, 1h, 0 rows)")
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        state.audit_logger = Arc::new(audit);
+
+        let dir = std::env::temp_dir().join("nexus_export_metrics_command");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ubuntu.csv").to_string_lossy().to_string();
+        let args = |granularity: &str| ExportMetricsArgs {
+            distro_names: vec!["Ubuntu".into()],
+            from: "2026-10-01T00:00:00Z".into(),
+            to: "2026-10-08T00:00:00Z".into(),
+            granularity: Some(granularity.into()),
+            format: MetricsExportFormat::Csv,
+            path: path.clone(),
+        };
+
+        assert!(export_metrics_inner(args("5m"), &state).await.is_err());
+        let response = export_metrics_inner(args("1h"), &state).await.unwrap();
+        assert_eq!(response.granularity, "1h");
+        assert_eq!(response.rows, 0);
+        let header = std::fs::read_to_string(&path).unwrap();
+        assert!(header.starts_with("distro_name,period_start,period_end,"));
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[tokio::test]
@@ -0,0 +1,340 @@
+use tauri::State;
+use tracing::instrument;
+
+use crate::domain::entities::notification_channel::{
+    ChannelConfig, NotificationChannel, NotificationDelivery,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::alerting::{AlertType, AlertUnit};
+use crate::domain::ports::notification_channel::AlertNotification;
+use crate::presentation::state::AppState;
+
+const DEFAULT_DELIVERY_LIMIT: u32 = 100;
+
+/// SMTP passwords are never sent back to the frontend.
+fn redact(mut channel: NotificationChannel) -> NotificationChannel {
+    if let ChannelConfig::Email { password, .. } = &mut channel.config {
+        *password = None;
+    }
+    channel
+}
+
+/// Inner logic for list_notification_channels, testable without Tauri runtime.
+pub(crate) async fn list_notification_channels_inner(
+    state: &AppState,
+) -> Result<Vec<NotificationChannel>, DomainError> {
+    let channels = state.notification_repo.list_channels().await?;
+    Ok(channels.into_iter().map(redact).collect())
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_notification_channels"))]
+pub async fn list_notification_channels(
+    state: State<'_, AppState>,
+) -> Result<Vec<NotificationChannel>, DomainError> {
+    list_notification_channels_inner(&state).await
+}
+
+/// Inner logic for save_notification_channel, testable without Tauri runtime.
+/// An email channel saved without a password keeps its current one.
+pub(crate) async fn save_notification_channel_inner(
+    channel: NotificationChannel,
+    state: &AppState,
+) -> Result<NotificationChannel, DomainError> {
+    let mut channel = channel;
+    channel.name = channel.name.trim().to_string();
+    channel.validate()?;
+
+    if channel.id.is_empty() {
+        channel.id = uuid::Uuid::new_v4().to_string();
+    } else if let ChannelConfig::Email { password, .. } = &mut channel.config
+        && password.as_deref().is_none_or(str::is_empty)
+    {
+        let stored = state.notification_repo.list_channels().await?;
+        *password = stored
+            .into_iter()
+            .find(|c| c.id == channel.id)
+            .and_then(|c| match c.config {
+                ChannelConfig::Email { password, .. } => password,
+                _ => None,
+            });
+    }
+
+    state.notification_repo.save_channel(&channel).await?;
+    state
+        .audit(
+            "notifications.channel.save",
+            &format!("{} ({})", channel.name, channel.id),
+        )
+        .await?;
+    Ok(redact(channel))
+}
+
+#[tauri::command]
+#[instrument(skip(state, channel), fields(cmd = "save_notification_channel", channel = %channel.name))]
+pub async fn save_notification_channel(
+    channel: NotificationChannel,
+    state: State<'_, AppState>,
+) -> Result<NotificationChannel, DomainError> {
+    save_notification_channel_inner(channel, &state).await
+}
+
+/// Inner logic for delete_notification_channel, testable without Tauri runtime.
+pub(crate) async fn delete_notification_channel_inner(
+    channel_id: String,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    state.notification_repo.delete_channel(&channel_id).await?;
+    state
+        .audit("notifications.channel.delete", &channel_id)
+        .await?;
+    Ok(())
+}
+
+/// Delete a channel; alert rules still routed to it skip it.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "delete_notification_channel", channel = %channel_id))]
+pub async fn delete_notification_channel(
+    channel_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    delete_notification_channel_inner(channel_id, &state).await
+}
+
+/// Inner logic for test_notification_channel, testable without Tauri runtime.
+pub(crate) async fn test_notification_channel_inner(
+    channel_id: String,
+    state: &AppState,
+) -> Result<NotificationDelivery, DomainError> {
+    let channel = if channel_id == NotificationChannel::desktop().id {
+        NotificationChannel::desktop()
+    } else {
+        state
+            .notification_repo
+            .list_channels()
+            .await?
+            .into_iter()
+            .find(|c| c.id == channel_id)
+            .ok_or_else(|| {
+                DomainError::ConfigError(format!("Notification channel '{}' not found", channel_id))
+            })?
+    };
+    let notification = AlertNotification {
+        alert_id: None,
+        distro_name: String::new(),
+        alert_type: AlertType::Cpu,
+        unit: AlertUnit::Percent,
+        threshold: 0.0,
+        actual_value: None,
+        resolved: false,
+        title: "WSL Nexus test notification".into(),
+        body: format!("Alerts routed to '{}' will arrive here.", channel.name),
+    };
+    Ok(state
+        .alert_dispatcher
+        .deliver(&channel, &notification)
+        .await)
+}
+
+/// Send a test notification through a channel (with the usual retries) and
+/// return how the delivery went.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "test_notification_channel", channel = %channel_id))]
+pub async fn test_notification_channel(
+    channel_id: String,
+    state: State<'_, AppState>,
+) -> Result<NotificationDelivery, DomainError> {
+    test_notification_channel_inner(channel_id, &state).await
+}
+
+/// Most recent notification deliveries first.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_notification_deliveries"))]
+pub async fn list_notification_deliveries(
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NotificationDelivery>, DomainError> {
+    state
+        .notification_repo
+        .list_deliveries(limit.unwrap_or(DEFAULT_DELIVERY_LIMIT))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
+    use crate::domain::entities::notification_channel::{
+        ChannelKind, DeliveryStatus, SmtpSecurity,
+    };
+    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
+    use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
+    use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_channel::MockNotificationChannelPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
+    use crate::domain::ports::port_forwarding::{
+        MockPortForwardRulesRepository, MockPortForwardingPort,
+    };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
+    use std::sync::Arc;
+
+    fn make_test_state(
+        repo: MockNotificationRepositoryPort,
+        audit: MockAuditLoggerPort,
+        webhook: MockNotificationChannelPort,
+    ) -> AppState {
+        let repo: Arc<MockNotificationRepositoryPort> = Arc::new(repo);
+        AppState {
+            wsl_manager: Arc::new(MockWslManagerPort::new()),
+            snapshot_repo: Arc::new(MockSnapshotRepositoryPort::new()),
+            monitoring: Arc::new(MockMonitoringProviderPort::new()),
+            metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
+            alerting: Arc::new(MockAlertingPort::new()),
+            audit_logger: Arc::new(audit),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
+            port_forwarding: Arc::new(MockPortForwardingPort::new()),
+            port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new(MockSafetyPolicyRepositoryPort::new()),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: repo.clone(),
+            alert_dispatcher: Arc::new(
+                AlertDispatcher::new(repo)
+                    .with_sender(ChannelKind::Webhook, Arc::new(webhook))
+                    .with_retry(1, std::time::Duration::ZERO),
+            ),
+        }
+    }
+
+    fn email_channel(id: &str, password: Option<&str>) -> NotificationChannel {
+        NotificationChannel {
+            id: id.into(),
+            name: " Ops mail ".into(),
+            enabled: true,
+            config: ChannelConfig::Email {
+                smtp_host: "smtp.example.com".into(),
+                smtp_port: 587,
+                security: SmtpSecurity::StartTls,
+                username: Some("alerts".into()),
+                password: password.map(String::from),
+                from: "alerts@example.com".into(),
+                to: vec!["ops@example.com".into()],
+            },
+        }
+    }
+
+    fn password_of(channel: &NotificationChannel) -> Option<&str> {
+        match &channel.config {
+            ChannelConfig::Email { password, .. } => password.as_deref(),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn save_keeps_the_stored_password_and_never_returns_it() {
+        let mut repo = MockNotificationRepositoryPort::new();
+        repo.expect_list_channels()
+            .returning(|| Ok(vec![email_channel("mail", Some("s3cret"))]));
+        repo.expect_save_channel()
+            .withf(|c| c.name == "Ops mail" && password_of(c) == Some("s3cret"))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, _| action == "notifications.channel.save")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let state = make_test_state(repo, audit, MockNotificationChannelPort::new());
+
+        let saved = save_notification_channel_inner(email_channel("mail", None), &state)
+            .await
+            .unwrap();
+        assert_eq!(password_of(&saved), None);
+
+        let listed = list_notification_channels_inner(&state).await.unwrap();
+        assert_eq!(password_of(&listed[0]), None);
+    }
+
+    #[tokio::test]
+    async fn save_assigns_an_id_and_rejects_invalid_channels() {
+        let mut repo = MockNotificationRepositoryPort::new();
+        repo.expect_save_channel()
+            .withf(|c| !c.id.is_empty())
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log().times(1).returning(|_, _| Ok(()));
+        let state = make_test_state(repo, audit, MockNotificationChannelPort::new());
+
+        let saved = save_notification_channel_inner(email_channel("", Some("pw")), &state)
+            .await
+            .unwrap();
+        assert!(!saved.id.is_empty());
+
+        let invalid = NotificationChannel {
+            config: ChannelConfig::Webhook {
+                url: "hooks.slack.com".into(),
+            },
+            ..email_channel("", None)
+        };
+        assert!(
+            save_notification_channel_inner(invalid, &state)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notification_reports_the_delivery() {
+        let mut repo = MockNotificationRepositoryPort::new();
+        repo.expect_list_channels().returning(|| {
+            Ok(vec![NotificationChannel {
+                id: "hook".into(),
+                name: "Teams".into(),
+                enabled: true,
+                config: ChannelConfig::Webhook {
+                    url: "http://localhost/hook".into(),
+                },
+            }])
+        });
+        repo.expect_record_delivery().times(1).returning(|_| Ok(11));
+        let mut webhook = MockNotificationChannelPort::new();
+        webhook
+            .expect_send()
+            .withf(|_, n| n.body.contains("Teams"))
+            .times(1)
+            .returning(|_, _| Err(DomainError::NotificationError("404".into())));
+        let state = make_test_state(repo, MockAuditLoggerPort::new(), webhook);
+
+        let delivery = test_notification_channel_inner("hook".into(), &state)
+            .await
+            .unwrap();
+        assert_eq!(delivery.id, 11);
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert!(
+            test_notification_channel_inner("missing".into(), &state)
+                .await
+                .is_err()
+        );
+    }
+}
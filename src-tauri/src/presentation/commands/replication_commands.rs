@@ -0,0 +1,248 @@
+use serde::Deserialize;
+use tauri::State;
+use tracing::instrument;
+
+use crate::application::dto::responses::{ReplicationTargetResponse, SnapshotReplicationResponse};
+use crate::domain::entities::replication::ReplicationTarget;
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::SnapshotId;
+use crate::presentation::state::AppState;
+
+/// Inner logic for get_replication_target, testable without Tauri runtime.
+pub(crate) fn get_replication_target_inner(state: &AppState) -> Option<ReplicationTargetResponse> {
+    state
+        .replicator
+        .target()
+        .map(ReplicationTargetResponse::from)
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "get_replication_target"))]
+pub async fn get_replication_target(
+    state: State<'_, AppState>,
+) -> Result<Option<ReplicationTargetResponse>, DomainError> {
+    Ok(get_replication_target_inner(&state))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetReplicationTargetArgs {
+    pub endpoint: String,
+    pub region: Option<String>,
+    pub bucket: String,
+    pub prefix: Option<String>,
+    pub access_key_id: String,
+    /// Left empty to keep the current secret.
+    pub secret_access_key: Option<String>,
+    pub path_style: Option<bool>,
+    pub auto_replicate: Option<bool>,
+}
+
+/// Inner logic for set_replication_target, testable without Tauri runtime.
+/// `None` turns replication off.
+pub(crate) async fn set_replication_target_inner(
+    args: Option<SetReplicationTargetArgs>,
+    state: &AppState,
+) -> Result<Option<ReplicationTargetResponse>, DomainError> {
+    let target = args.map(|args| {
+        let secret_access_key = match args.secret_access_key {
+            Some(secret) if !secret.trim().is_empty() => secret.trim().to_string(),
+            _ => state
+                .replicator
+                .target()
+                .map(|t| t.secret_access_key)
+                .unwrap_or_default(),
+        };
+        ReplicationTarget {
+            endpoint: args.endpoint.trim().to_string(),
+            region: args
+                .region
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty())
+                .unwrap_or_else(|| "us-east-1".to_string()),
+            bucket: args.bucket.trim().to_string(),
+            prefix: args.prefix.unwrap_or_default().trim().to_string(),
+            access_key_id: args.access_key_id.trim().to_string(),
+            secret_access_key,
+            path_style: args.path_style.unwrap_or(true),
+            auto_replicate: args.auto_replicate.unwrap_or(false),
+        }
+    });
+
+    state.replicator.configure(target).await?;
+    Ok(get_replication_target_inner(state))
+}
+
+#[tauri::command]
+#[instrument(skip(state, args), fields(cmd = "set_replication_target"))]
+pub async fn set_replication_target(
+    args: Option<SetReplicationTargetArgs>,
+    state: State<'_, AppState>,
+) -> Result<Option<ReplicationTargetResponse>, DomainError> {
+    set_replication_target_inner(args, &state).await
+}
+
+/// Upload a snapshot to the replication target, resuming an interrupted
+/// upload. Progress is reported as `snapshot-progress` events.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "replicate_snapshot", snapshot = %snapshot_id))]
+pub async fn replicate_snapshot(
+    snapshot_id: String,
+    state: State<'_, AppState>,
+) -> Result<SnapshotReplicationResponse, DomainError> {
+    state
+        .replicator
+        .replicate(&SnapshotId::from_string(snapshot_id))
+        .await
+        .map(SnapshotReplicationResponse::from)
+}
+
+/// Inner logic for list_replications, testable without Tauri runtime.
+pub(crate) async fn list_replications_inner(
+    state: &AppState,
+) -> Result<Vec<SnapshotReplicationResponse>, DomainError> {
+    Ok(state
+        .replicator
+        .list()
+        .await?
+        .into_iter()
+        .map(SnapshotReplicationResponse::from)
+        .collect())
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_replications"))]
+pub async fn list_replications(
+    state: State<'_, AppState>,
+) -> Result<Vec<SnapshotReplicationResponse>, DomainError> {
+    list_replications_inner(&state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::replication::{SnapshotReplicator, StoreFactory};
+    use crate::application::services::transfer::TransferRegistry;
+    use std::sync::Arc;
+
+    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
+    use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
+    use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
+    use crate::domain::ports::port_forwarding::{
+        MockPortForwardRulesRepository, MockPortForwardingPort,
+    };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
+    use crate::domain::ports::snapshot_replication::MockSnapshotReplicationPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
+
+    fn make_test_state(replications: MockReplicationRepositoryPort) -> AppState {
+        let factory: StoreFactory = Arc::new(|_| Ok(Arc::new(MockSnapshotReplicationPort::new())));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log_with_details().returning(|_, _, _| Ok(()));
+        AppState {
+            wsl_manager: Arc::new(MockWslManagerPort::new()),
+            snapshot_repo: Arc::new(MockSnapshotRepositoryPort::new()),
+            monitoring: Arc::new(MockMonitoringProviderPort::new()),
+            metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
+            alerting: Arc::new(MockAlertingPort::new()),
+            audit_logger: Arc::new(MockAuditLoggerPort::new()),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
+            port_forwarding: Arc::new(MockPortForwardingPort::new()),
+            port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(replications),
+                Arc::new(audit),
+                factory,
+            )),
+            safety_policies: Arc::new(MockSafetyPolicyRepositoryPort::new()),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
+        }
+    }
+
+    fn args(secret: Option<&str>) -> SetReplicationTargetArgs {
+        SetReplicationTargetArgs {
+            endpoint: " http://nas.local:9000 ".into(),
+            region: None,
+            bucket: "wsl".into(),
+            prefix: None,
+            access_key_id: "backup".into(),
+            secret_access_key: secret.map(str::to_string),
+            path_style: None,
+            auto_replicate: Some(true),
+        }
+    }
+
+    #[tokio::test]
+    async fn set_target_keeps_secret_when_left_empty_and_never_returns_it() {
+        let mut replications = MockReplicationRepositoryPort::new();
+        replications
+            .expect_save_target()
+            .withf(|t| t.as_ref().is_some_and(|t| t.secret_access_key == "s3cret"))
+            .times(2)
+            .returning(|_| Ok(()));
+        let state = make_test_state(replications);
+
+        let saved = set_replication_target_inner(Some(args(Some("s3cret"))), &state)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.endpoint, "http://nas.local:9000");
+        assert_eq!(saved.region, "us-east-1");
+        assert!(saved.has_secret && saved.path_style && saved.auto_replicate);
+
+        set_replication_target_inner(Some(args(Some(""))), &state)
+            .await
+            .unwrap();
+        assert_eq!(
+            state.replicator.target().unwrap().secret_access_key,
+            "s3cret"
+        );
+    }
+
+    #[tokio::test]
+    async fn set_target_without_secret_is_rejected() {
+        let mut replications = MockReplicationRepositoryPort::new();
+        replications.expect_save_target().never();
+        let state = make_test_state(replications);
+
+        let result = set_replication_target_inner(Some(args(None)), &state).await;
+        assert!(matches!(result, Err(DomainError::ConfigError(_))));
+        assert!(get_replication_target_inner(&state).is_none());
+    }
+
+    #[tokio::test]
+    async fn clearing_target_turns_replication_off() {
+        let mut replications = MockReplicationRepositoryPort::new();
+        replications.expect_save_target().returning(|_| Ok(()));
+        replications.expect_list().returning(|| Ok(vec![]));
+        let state = make_test_state(replications);
+
+        set_replication_target_inner(Some(args(Some("s3cret"))), &state)
+            .await
+            .unwrap();
+        assert!(
+            set_replication_target_inner(None, &state)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(list_replications_inner(&state).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+use serde::Deserialize;
+use tauri::State;
+use tracing::instrument;
+
+use crate::application::dto::responses::SafetyPolicyResponse;
+use crate::domain::entities::safety_snapshot::{DestructiveOperation, SafetySnapshotPolicy};
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::DistroName;
+use crate::presentation::commands::snapshot_commands::parse_export_format;
+use crate::presentation::state::AppState;
+
+/// Inner logic for list_safety_policies, testable without Tauri runtime.
+pub(crate) async fn list_safety_policies_inner(
+    state: &AppState,
+) -> Result<Vec<SafetyPolicyResponse>, DomainError> {
+    let policies = state.safety_policies.list().await?;
+    Ok(policies
+        .into_iter()
+        .map(SafetyPolicyResponse::from)
+        .collect())
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_safety_policies"))]
+pub async fn list_safety_policies(
+    state: State<'_, AppState>,
+) -> Result<Vec<SafetyPolicyResponse>, DomainError> {
+    list_safety_policies_inner(&state).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveSafetyPolicyArgs {
+    pub distro_name: String,
+    /// "delete_distro", "restore_overwrite", "resize_vhd" and/or "compact_vhdx";
+    /// empty turns safety snapshots off for the distro.
+    pub operations: Vec<String>,
+    pub format: Option<String>,
+    pub output_dir: String,
+    /// Newest safety snapshots kept (default 3, 0 keeps any number).
+    pub keep_last: Option<u32>,
+    /// Age limit in hours (default 168, 0 keeps them regardless of age).
+    pub max_age_hours: Option<u32>,
+}
+
+/// Inner logic for save_safety_policy, testable without Tauri runtime.
+pub(crate) async fn save_safety_policy_inner(
+    args: SaveSafetyPolicyArgs,
+    state: &AppState,
+) -> Result<SafetyPolicyResponse, DomainError> {
+    let distro_name = DistroName::new(&args.distro_name)?;
+    state.wsl_manager.get_distro(&distro_name).await?;
+
+    let mut operations = Vec::new();
+    for op in &args.operations {
+        let op = DestructiveOperation::parse(op).ok_or_else(|| {
+            DomainError::ConfigError(format!("Unknown destructive operation '{}'", op))
+        })?;
+        if !operations.contains(&op) {
+            operations.push(op);
+        }
+    }
+    if !operations.is_empty() && args.output_dir.trim().is_empty() {
+        return Err(DomainError::ConfigError(
+            "Safety snapshots need an output directory".into(),
+        ));
+    }
+
+    let policy = SafetySnapshotPolicy {
+        distro_name,
+        operations,
+        format: parse_export_format(args.format.as_deref()),
+        output_dir: args.output_dir,
+        keep_last: args.keep_last.unwrap_or(3),
+        max_age_hours: args.max_age_hours.unwrap_or(168),
+        updated_at: chrono::Utc::now(),
+    };
+    state.safety_policies.save(&policy).await?;
+
+    let response = SafetyPolicyResponse::from(policy);
+    state
+        .audit_logger
+        .log_with_details(
+            "safety.policy.save",
+            &response.distro_name,
+            &format!("operations=[{}]", response.operations.join(",")),
+        )
+        .await?;
+    Ok(response)
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "save_safety_policy", distro = %args.distro_name))]
+pub async fn save_safety_policy(
+    args: SaveSafetyPolicyArgs,
+    state: State<'_, AppState>,
+) -> Result<SafetyPolicyResponse, DomainError> {
+    save_safety_policy_inner(args, &state).await
+}
+
+/// Inner logic for delete_safety_policy, testable without Tauri runtime.
+pub(crate) async fn delete_safety_policy_inner(
+    distro_name: String,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    state.safety_policies.delete(&distro_name).await?;
+    state.audit("safety.policy.delete", &distro_name).await?;
+    Ok(())
+}
+
+/// Turn safety snapshots off for a distro; existing ones are kept.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "delete_safety_policy", distro = %distro_name))]
+pub async fn delete_safety_policy(
+    distro_name: String,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    delete_safety_policy_inner(distro_name, &state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
+    use std::sync::Arc;
+
+    use crate::domain::entities::distro::Distro;
+    use crate::domain::entities::snapshot::ExportFormat;
+    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
+    use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
+    use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
+    use crate::domain::ports::port_forwarding::{
+        MockPortForwardRulesRepository, MockPortForwardingPort,
+    };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
+    use crate::domain::value_objects::{DistroState, WslVersion};
+
+    fn make_test_state(
+        policies: MockSafetyPolicyRepositoryPort,
+        audit: MockAuditLoggerPort,
+    ) -> AppState {
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_distro().returning(|name| {
+            Ok(Distro::new(
+                name.clone(),
+                DistroState::Stopped,
+                WslVersion::V2,
+                false,
+            ))
+        });
+        AppState {
+            wsl_manager: Arc::new(wsl),
+            snapshot_repo: Arc::new(MockSnapshotRepositoryPort::new()),
+            monitoring: Arc::new(MockMonitoringProviderPort::new()),
+            metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
+            alerting: Arc::new(MockAlertingPort::new()),
+            audit_logger: Arc::new(audit),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
+            port_forwarding: Arc::new(MockPortForwardingPort::new()),
+            port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new(policies),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
+        }
+    }
+
+    fn make_args(operations: &[&str]) -> SaveSafetyPolicyArgs {
+        SaveSafetyPolicyArgs {
+            distro_name: "Ubuntu".into(),
+            operations: operations.iter().map(|op| op.to_string()).collect(),
+            format: Some("tar.zst".into()),
+            output_dir: "/backups/safety".into(),
+            keep_last: None,
+            max_age_hours: Some(24),
+        }
+    }
+
+    #[tokio::test]
+    async fn save_parses_operations_and_audits() {
+        let mut policies = MockSafetyPolicyRepositoryPort::new();
+        policies
+            .expect_save()
+            .withf(|p| {
+                p.operations
+                    == vec![
+                        DestructiveOperation::DeleteDistro,
+                        DestructiveOperation::ResizeVhd,
+                    ]
+                    && matches!(p.format, ExportFormat::TarZst)
+                    && p.keep_last == 3
+                    && p.max_age_hours == 24
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log_with_details()
+            .withf(|action, target, details| {
+                action == "safety.policy.save"
+                    && target == "Ubuntu"
+                    && details == "operations=[delete_distro,resize_vhd]"
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let state = make_test_state(policies, audit);
+        let resp = save_safety_policy_inner(
+            make_args(&["delete_distro", "resize_vhd", "delete_distro"]),
+            &state,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.operations, vec!["delete_distro", "resize_vhd"]);
+        assert_eq!(resp.format, "tar.zst");
+    }
+
+    #[tokio::test]
+    async fn save_rejects_unknown_operation() {
+        let mut policies = MockSafetyPolicyRepositoryPort::new();
+        policies.expect_save().never();
+
+        let state = make_test_state(policies, MockAuditLoggerPort::new());
+        let err = save_safety_policy_inner(make_args(&["wipe_disk"]), &state)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("wipe_disk"));
+    }
+
+    #[tokio::test]
+    async fn delete_removes_policy_and_audits() {
+        let mut policies = MockSafetyPolicyRepositoryPort::new();
+        policies
+            .expect_delete()
+            .withf(|distro| distro == "Ubuntu")
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| action == "safety.policy.delete" && detail == "Ubuntu")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let state = make_test_state(policies, audit);
+        assert!(
+            delete_safety_policy_inner("Ubuntu".into(), &state)
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use serde::Deserialize;
+use tauri::State;
+use tracing::instrument;
+
+use crate::application::dto::responses::{SnapshotResponse, SnapshotScheduleResponse};
+use crate::application::services::snapshot_scheduler::SnapshotScheduler;
+use crate::domain::entities::snapshot_schedule::{
+    RetentionPolicy, ScheduleFrequency, SnapshotSchedule,
+};
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::DistroName;
+use crate::presentation::commands::snapshot_commands::{parse_export_format, parse_storage};
+use crate::presentation::state::AppState;
+
+/// Inner logic for list_snapshot_schedules, testable without Tauri runtime.
+pub(crate) async fn list_snapshot_schedules_inner(
+    distro_name: Option<String>,
+    state: &AppState,
+) -> Result<Vec<SnapshotScheduleResponse>, DomainError> {
+    let schedules = state.schedule_repo.list(distro_name).await?;
+    Ok(schedules
+        .into_iter()
+        .map(SnapshotScheduleResponse::from)
+        .collect())
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_snapshot_schedules"))]
+pub async fn list_snapshot_schedules(
+    distro_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SnapshotScheduleResponse>, DomainError> {
+    list_snapshot_schedules_inner(distro_name, &state).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveScheduleArgs {
+    /// Existing schedule to update; a new one is created when absent.
+    pub id: Option<String>,
+    pub distro_name: String,
+    /// "hourly", "daily" or "weekly"
+    pub frequency: String,
+    pub format: Option<String>,
+    pub storage: Option<String>,
+    pub output_dir: String,
+    pub keep_hourly: Option<u32>,
+    pub keep_daily: Option<u32>,
+    pub keep_weekly: Option<u32>,
+    pub enabled: Option<bool>,
+}
+
+/// Inner logic for save_snapshot_schedule, testable without Tauri runtime.
+pub(crate) async fn save_snapshot_schedule_inner(
+    args: SaveScheduleArgs,
+    state: &AppState,
+) -> Result<SnapshotScheduleResponse, DomainError> {
+    let distro_name = DistroName::new(&args.distro_name)?;
+    state.wsl_manager.get_distro(&distro_name).await?;
+
+    let frequency = ScheduleFrequency::parse(&args.frequency).ok_or_else(|| {
+        DomainError::ConfigError(format!("Unknown schedule frequency '{}'", args.frequency))
+    })?;
+    if args.output_dir.trim().is_empty() {
+        return Err(DomainError::ConfigError(
+            "A schedule needs an output directory".into(),
+        ));
+    }
+
+    let existing = match &args.id {
+        Some(id) => Some(state.schedule_repo.get(id).await?.ok_or_else(|| {
+            DomainError::ConfigError(format!("Snapshot schedule '{}' not found", id))
+        })?),
+        None => None,
+    };
+
+    let schedule = SnapshotSchedule {
+        id: args.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        distro_name,
+        frequency,
+        format: parse_export_format(args.format.as_deref()),
+        storage: parse_storage(args.storage.as_deref()),
+        output_dir: args.output_dir,
+        retention: RetentionPolicy {
+            keep_hourly: args.keep_hourly.unwrap_or(0),
+            keep_daily: args.keep_daily.unwrap_or(0),
+            keep_weekly: args.keep_weekly.unwrap_or(0),
+        },
+        enabled: args.enabled.unwrap_or(true),
+        last_run_at: existing.as_ref().and_then(|s| s.last_run_at),
+        created_at: existing
+            .as_ref()
+            .map(|s| s.created_at)
+            .unwrap_or_else(chrono::Utc::now),
+    };
+
+    state.schedule_repo.save(&schedule).await?;
+    state
+        .audit(
+            "snapshot.schedule.save",
+            &format!(
+                "{} {} ({})",
+                schedule.distro_name,
+                schedule.frequency.as_str(),
+                schedule.id
+            ),
+        )
+        .await?;
+
+    Ok(SnapshotScheduleResponse::from(schedule))
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "save_snapshot_schedule", distro = %args.distro_name))]
+pub async fn save_snapshot_schedule(
+    args: SaveScheduleArgs,
+    state: State<'_, AppState>,
+) -> Result<SnapshotScheduleResponse, DomainError> {
+    save_snapshot_schedule_inner(args, &state).await
+}
+
+/// Inner logic for delete_snapshot_schedule, testable without Tauri runtime.
+pub(crate) async fn delete_snapshot_schedule_inner(
+    schedule_id: String,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    state.schedule_repo.delete(&schedule_id).await?;
+    state
+        .audit("snapshot.schedule.delete", &schedule_id)
+        .await?;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "delete_snapshot_schedule", schedule = %schedule_id))]
+pub async fn delete_snapshot_schedule(
+    schedule_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    delete_snapshot_schedule_inner(schedule_id, &state).await
+}
+
+/// Take a schedule's snapshot immediately (counts as its run for the current
+/// period) and apply its retention policy.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "run_snapshot_schedule", schedule = %schedule_id))]
+pub async fn run_snapshot_schedule(
+    schedule_id: String,
+    state: State<'_, AppState>,
+) -> Result<SnapshotResponse, DomainError> {
+    let schedule = state
+        .schedule_repo
+        .get(&schedule_id)
+        .await?
+        .ok_or_else(|| {
+            DomainError::ConfigError(format!("Snapshot schedule '{}' not found", schedule_id))
+        })?;
+    let scheduler = SnapshotScheduler::new(
+        state.wsl_manager.clone(),
+        state.snapshot_repo.clone(),
+        state.schedule_repo.clone(),
+        state.audit_logger.clone(),
+    )
+    .with_transfers(state.transfers.clone());
+    scheduler
+        .run_schedule(&schedule, chrono::Utc::now())
+        .await
+        .map(SnapshotResponse::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
+    use std::sync::Arc;
+
+    use crate::domain::entities::distro::Distro;
+    use crate::domain::entities::snapshot::{ExportFormat, SnapshotStorage};
+    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
+    use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
+    use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
+    use crate::domain::ports::port_forwarding::{
+        MockPortForwardRulesRepository, MockPortForwardingPort,
+    };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
+    use crate::domain::value_objects::{DistroState, WslVersion};
+
+    fn make_test_state(
+        wsl: MockWslManagerPort,
+        schedules: MockScheduleRepositoryPort,
+        audit: MockAuditLoggerPort,
+    ) -> AppState {
+        AppState {
+            wsl_manager: Arc::new(wsl),
+            snapshot_repo: Arc::new(MockSnapshotRepositoryPort::new()),
+            monitoring: Arc::new(MockMonitoringProviderPort::new()),
+            metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
+            alerting: Arc::new(MockAlertingPort::new()),
+            audit_logger: Arc::new(audit),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
+            port_forwarding: Arc::new(MockPortForwardingPort::new()),
+            port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(schedules),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new(MockSafetyPolicyRepositoryPort::new()),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
+        }
+    }
+
+    fn wsl_with_distro() -> MockWslManagerPort {
+        let mut wsl = MockWslManagerPort::new();
+        wsl.expect_get_distro().returning(|name| {
+            Ok(Distro::new(
+                name.clone(),
+                DistroState::Stopped,
+                WslVersion::V2,
+                false,
+            ))
+        });
+        wsl
+    }
+
+    fn make_args(id: Option<&str>, frequency: &str) -> SaveScheduleArgs {
+        SaveScheduleArgs {
+            id: id.map(str::to_string),
+            distro_name: "Ubuntu".into(),
+            frequency: frequency.into(),
+            format: Some("tar.zst".into()),
+            storage: None,
+            output_dir: "/backups".into(),
+            keep_hourly: Some(24),
+            keep_daily: Some(7),
+            keep_weekly: Some(4),
+            enabled: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn save_creates_schedule_and_audits() {
+        let mut schedules = MockScheduleRepositoryPort::new();
+        schedules
+            .expect_save()
+            .withf(|s| {
+                s.frequency == ScheduleFrequency::Daily
+                    && matches!(s.format, ExportFormat::TarZst)
+                    && s.storage == SnapshotStorage::File
+                    && s.retention.keep_weekly == 4
+                    && s.enabled
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, _| action == "snapshot.schedule.save")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let state = make_test_state(wsl_with_distro(), schedules, audit);
+        let resp = save_snapshot_schedule_inner(make_args(None, "daily"), &state)
+            .await
+            .unwrap();
+        assert_eq!(resp.frequency, "daily");
+        assert!(!resp.id.is_empty());
+    }
+
+    #[tokio::test]
+    async fn save_update_keeps_last_run() {
+        let last_run = chrono::Utc::now() - chrono::Duration::hours(3);
+        let mut schedules = MockScheduleRepositoryPort::new();
+        schedules.expect_get().returning(move |id| {
+            Ok(Some(SnapshotSchedule {
+                id: id.to_string(),
+                distro_name: DistroName::new("Ubuntu").unwrap(),
+                frequency: ScheduleFrequency::Hourly,
+                format: ExportFormat::Tar,
+                storage: SnapshotStorage::File,
+                output_dir: "/old".into(),
+                retention: RetentionPolicy::default(),
+                enabled: true,
+                last_run_at: Some(last_run),
+                created_at: last_run,
+            }))
+        });
+        schedules
+            .expect_save()
+            .withf(move |s| {
+                s.id == "sched-1" && s.last_run_at == Some(last_run) && s.output_dir == "/backups"
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log().returning(|_, _| Ok(()));
+
+        let state = make_test_state(wsl_with_distro(), schedules, audit);
+        let resp = save_snapshot_schedule_inner(make_args(Some("sched-1"), "weekly"), &state)
+            .await
+            .unwrap();
+        assert_eq!(resp.frequency, "weekly");
+    }
+
+    #[tokio::test]
+    async fn save_rejects_unknown_frequency() {
+        let mut schedules = MockScheduleRepositoryPort::new();
+        schedules.expect_save().never();
+
+        let state = make_test_state(wsl_with_distro(), schedules, MockAuditLoggerPort::new());
+        let err = save_snapshot_schedule_inner(make_args(None, "monthly"), &state)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("monthly"));
+    }
+
+    #[tokio::test]
+    async fn delete_removes_schedule_and_audits() {
+        let mut schedules = MockScheduleRepositoryPort::new();
+        schedules
+            .expect_delete()
+            .withf(|id| id == "sched-1")
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| action == "snapshot.schedule.delete" && detail == "sched-1")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let state = make_test_state(MockWslManagerPort::new(), schedules, audit);
+        delete_snapshot_schedule_inner("sched-1".into(), &state)
+            .await
+            .unwrap();
+    }
+}
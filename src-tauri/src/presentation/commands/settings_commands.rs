@@ -1,6 +1,8 @@
 use tauri::State;
 use tracing::instrument;
 
+use crate::application::services::safety_snapshot::with_safety_note;
+use crate::domain::entities::safety_snapshot::DestructiveOperation;
 use crate::domain::entities::wsl_config::WslGlobalConfig;
 use crate::domain::entities::wsl_version::WslVersionInfo;
 use crate::domain::errors::DomainError;
@@ -28,11 +30,19 @@ pub(crate) async fn compact_vhdx_inner(
     state: &AppState,
 ) -> Result<(), DomainError> {
     let name = DistroName::new(&distro_name)?;
+    let safety = state
+        .safety_guard()
+        .protect(&name, DestructiveOperation::CompactVhdx)
+        .await?;
     let _ = state.wsl_manager.terminate_distro(&name).await;
     state.wsl_manager.set_sparse(&name, true).await?;
     state
         .audit_logger
-        .log_with_details("vhdx.compact", &distro_name, "Set sparse mode enabled")
+        .log_with_details(
+            "vhdx.compact",
+            &distro_name,
+            &with_safety_note("Set sparse mode enabled".into(), safety.as_ref()),
+        )
         .await?;
     Ok(())
 }
@@ -74,17 +84,26 @@ pub async fn get_wsl_version(state: State<'_, AppState>) -> Result<WslVersionInf
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
     use std::sync::Arc;
 
     use crate::domain::ports::alerting::MockAlertingPort;
     use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
     use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
     use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
     use crate::domain::ports::port_forwarding::{
         MockPortForwardRulesRepository, MockPortForwardingPort,
     };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
     use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
     use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
 
     fn make_test_state(wsl: MockWslManagerPort, audit: MockAuditLoggerPort) -> AppState {
         AppState {
@@ -94,9 +113,30 @@ mod tests {
             metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
             alerting: Arc::new(MockAlertingPort::new()),
             audit_logger: Arc::new(audit),
-            alert_thresholds: Arc::new(tokio::sync::RwLock::new(vec![])),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
             port_forwarding: Arc::new(MockPortForwardingPort::new()),
             port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new({
+                let mut policies = MockSafetyPolicyRepositoryPort::new();
+                policies.expect_get().returning(|_| Ok(None));
+                policies
+            }),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
         }
     }
 
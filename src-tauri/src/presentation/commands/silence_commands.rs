@@ -0,0 +1,416 @@
+use chrono::Utc;
+use serde::Deserialize;
+use tauri::State;
+use tracing::instrument;
+
+use crate::domain::entities::alert_silence::{AlertMatcher, AlertSilence, MaintenanceWindow};
+use crate::domain::errors::DomainError;
+use crate::domain::ports::alerting::AlertType;
+use crate::domain::value_objects::DistroName;
+use crate::presentation::state::AppState;
+
+/// Longest silence: a week.
+const MAX_SILENCE_MINUTES: u32 = 7 * 24 * 60;
+/// Ended silences stay listed this long (the aggregator purges them after).
+const ENDED_SILENCE_HOURS: i64 = 24;
+
+/// Check the distro a matcher names and normalize it.
+fn checked_matcher(matcher: AlertMatcher) -> Result<AlertMatcher, DomainError> {
+    Ok(AlertMatcher {
+        distro_name: matcher
+            .distro_name
+            .map(|d| DistroName::new(&d).map(|n| n.as_str().to_string()))
+            .transpose()?,
+        alert_type: matcher.alert_type,
+    })
+}
+
+/// Inner logic for list_alert_silences, testable without Tauri runtime.
+pub(crate) async fn list_alert_silences_inner(
+    state: &AppState,
+) -> Result<Vec<AlertSilence>, DomainError> {
+    let shown_since = Utc::now() - chrono::Duration::hours(ENDED_SILENCE_HOURS);
+    let mut silences: Vec<AlertSilence> = state
+        .alert_suppressions
+        .read()
+        .await
+        .silences
+        .iter()
+        .filter(|s| s.ends_at >= shown_since)
+        .cloned()
+        .collect();
+    silences.sort_by_key(|s| std::cmp::Reverse(s.starts_at));
+    Ok(silences)
+}
+
+/// Active silences, plus those that ended in the last day.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_alert_silences"))]
+pub async fn list_alert_silences(
+    state: State<'_, AppState>,
+) -> Result<Vec<AlertSilence>, DomainError> {
+    list_alert_silences_inner(&state).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertSilenceArgs {
+    /// Silence one distro; every distro when absent.
+    pub distro_name: Option<String>,
+    /// Silence one alert type; every type when absent.
+    pub alert_type: Option<AlertType>,
+    pub duration_minutes: u32,
+    pub reason: String,
+}
+
+/// Inner logic for create_alert_silence, testable without Tauri runtime.
+pub(crate) async fn create_alert_silence_inner(
+    args: CreateAlertSilenceArgs,
+    state: &AppState,
+) -> Result<AlertSilence, DomainError> {
+    let reason = args.reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(DomainError::ConfigError("A silence needs a reason".into()));
+    }
+    if !(1..=MAX_SILENCE_MINUTES).contains(&args.duration_minutes) {
+        return Err(DomainError::ConfigError(format!(
+            "A silence must last between 1 and {MAX_SILENCE_MINUTES} minutes"
+        )));
+    }
+    let matcher = checked_matcher(AlertMatcher {
+        distro_name: args.distro_name,
+        alert_type: args.alert_type,
+    })?;
+
+    let now = Utc::now();
+    let silence = AlertSilence {
+        id: uuid::Uuid::new_v4().to_string(),
+        matcher,
+        reason,
+        starts_at: now,
+        ends_at: now + chrono::Duration::minutes(args.duration_minutes as i64),
+        created_at: now,
+    };
+    state.alerting.save_silence(&silence).await?;
+    let mut suppressions = state.alert_suppressions.write().await;
+    let shown_since = now - chrono::Duration::hours(ENDED_SILENCE_HOURS);
+    suppressions.silences.retain(|s| s.ends_at >= shown_since);
+    suppressions.silences.push(silence.clone());
+    drop(suppressions);
+
+    state
+        .audit(
+            "alerts.silence.create",
+            &format!(
+                "{} on {} for {} min",
+                silence.reason,
+                silence
+                    .matcher
+                    .distro_name
+                    .as_deref()
+                    .unwrap_or("all distros"),
+                args.duration_minutes
+            ),
+        )
+        .await?;
+    Ok(silence)
+}
+
+/// Silence matching alerts from now on: they are recorded as suppressed and
+/// send no notification.
+#[tauri::command]
+#[instrument(skip(state, args), fields(cmd = "create_alert_silence"))]
+pub async fn create_alert_silence(
+    args: CreateAlertSilenceArgs,
+    state: State<'_, AppState>,
+) -> Result<AlertSilence, DomainError> {
+    create_alert_silence_inner(args, &state).await
+}
+
+/// Inner logic for expire_alert_silence, testable without Tauri runtime.
+pub(crate) async fn expire_alert_silence_inner(
+    silence_id: String,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    let mut suppressions = state.alert_suppressions.write().await;
+    let silence = suppressions
+        .silences
+        .iter_mut()
+        .find(|s| s.id == silence_id)
+        .ok_or_else(|| DomainError::ConfigError(format!("Silence '{silence_id}' not found")))?;
+    let now = Utc::now();
+    if silence.ends_at <= now {
+        return Ok(());
+    }
+    let mut expired = silence.clone();
+    expired.ends_at = now.max(expired.starts_at);
+    state.alerting.save_silence(&expired).await?;
+    *silence = expired;
+    drop(suppressions);
+
+    state.audit("alerts.silence.expire", &silence_id).await?;
+    Ok(())
+}
+
+/// End a silence early.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "expire_alert_silence", silence = %silence_id))]
+pub async fn expire_alert_silence(
+    silence_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    expire_alert_silence_inner(silence_id, &state).await
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_maintenance_windows"))]
+pub async fn list_maintenance_windows(
+    state: State<'_, AppState>,
+) -> Result<Vec<MaintenanceWindow>, DomainError> {
+    Ok(state.alert_suppressions.read().await.windows.clone())
+}
+
+/// Inner logic for save_maintenance_window, testable without Tauri runtime.
+pub(crate) async fn save_maintenance_window_inner(
+    window: MaintenanceWindow,
+    state: &AppState,
+) -> Result<MaintenanceWindow, DomainError> {
+    let mut window = window;
+    window.name = window.name.trim().to_string();
+    window.validate()?;
+    window.matcher = checked_matcher(window.matcher)?;
+    window.days.sort_by_key(|d| d.num_days_from_monday());
+    window.days.dedup();
+    if window.id.is_empty() {
+        window.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    state.alerting.save_maintenance_window(&window).await?;
+    let mut suppressions = state.alert_suppressions.write().await;
+    suppressions.windows.retain(|w| w.id != window.id);
+    suppressions.windows.push(window.clone());
+    suppressions.windows.sort_by(|a, b| a.name.cmp(&b.name));
+    drop(suppressions);
+
+    state
+        .audit(
+            "alerts.maintenance.save",
+            &format!("{} ({})", window.name, window.id),
+        )
+        .await?;
+    Ok(window)
+}
+
+/// Create or update a recurring maintenance window.
+#[tauri::command]
+#[instrument(skip(state, window), fields(cmd = "save_maintenance_window", window = %window.name))]
+pub async fn save_maintenance_window(
+    window: MaintenanceWindow,
+    state: State<'_, AppState>,
+) -> Result<MaintenanceWindow, DomainError> {
+    save_maintenance_window_inner(window, &state).await
+}
+
+/// Inner logic for delete_maintenance_window, testable without Tauri runtime.
+pub(crate) async fn delete_maintenance_window_inner(
+    window_id: String,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    state.alerting.delete_maintenance_window(&window_id).await?;
+    state
+        .alert_suppressions
+        .write()
+        .await
+        .windows
+        .retain(|w| w.id != window_id);
+    state.audit("alerts.maintenance.delete", &window_id).await?;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "delete_maintenance_window", window = %window_id))]
+pub async fn delete_maintenance_window(
+    window_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    delete_maintenance_window_inner(window_id, &state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
+    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
+    use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
+    use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
+    use crate::domain::ports::port_forwarding::{
+        MockPortForwardRulesRepository, MockPortForwardingPort,
+    };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
+    use chrono::Weekday;
+    use std::sync::Arc;
+
+    fn make_test_state(alerting: MockAlertingPort, audit: MockAuditLoggerPort) -> AppState {
+        AppState {
+            wsl_manager: Arc::new(MockWslManagerPort::new()),
+            snapshot_repo: Arc::new(MockSnapshotRepositoryPort::new()),
+            monitoring: Arc::new(MockMonitoringProviderPort::new()),
+            metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
+            alerting: Arc::new(alerting),
+            audit_logger: Arc::new(audit),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
+            port_forwarding: Arc::new(MockPortForwardingPort::new()),
+            port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new(MockSafetyPolicyRepositoryPort::new()),
+            templates: Arc::new(MockTemplateRepositoryPort::new()),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
+        }
+    }
+
+    fn audit_expecting(action: &'static str, times: usize) -> MockAuditLoggerPort {
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(move |a, _| a == action)
+            .times(times)
+            .returning(|_, _| Ok(()));
+        audit
+    }
+
+    fn silence_args(duration_minutes: u32, reason: &str) -> CreateAlertSilenceArgs {
+        CreateAlertSilenceArgs {
+            distro_name: Some("Ubuntu".into()),
+            alert_type: Some(AlertType::Cpu),
+            duration_minutes,
+            reason: reason.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_silence_validates_and_takes_effect_at_once() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_save_silence()
+            .withf(|s| s.reason == "Kernel build")
+            .times(1)
+            .returning(|_| Ok(()));
+        let state = make_test_state(alerting, audit_expecting("alerts.silence.create", 1));
+
+        assert!(
+            create_alert_silence_inner(silence_args(60, "  "), &state)
+                .await
+                .is_err()
+        );
+        assert!(
+            create_alert_silence_inner(silence_args(0, "Kernel build"), &state)
+                .await
+                .is_err()
+        );
+
+        let silence = create_alert_silence_inner(silence_args(60, " Kernel build "), &state)
+            .await
+            .unwrap();
+        assert_eq!((silence.ends_at - silence.starts_at).num_minutes(), 60);
+        let suppressed = state.alert_suppressions.read().await.suppressed_by(
+            "Ubuntu",
+            AlertType::Cpu,
+            Utc::now(),
+        );
+        assert_eq!(suppressed.as_deref(), Some("Silence: Kernel build"));
+    }
+
+    #[tokio::test]
+    async fn expire_silence_ends_it_now() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_save_silence()
+            .times(2)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log().times(2).returning(|_, _| Ok(()));
+        let state = make_test_state(alerting, audit);
+
+        let silence = create_alert_silence_inner(silence_args(60, "Upgrade"), &state)
+            .await
+            .unwrap();
+        expire_alert_silence_inner(silence.id.clone(), &state)
+            .await
+            .unwrap();
+
+        let listed = list_alert_silences_inner(&state).await.unwrap();
+        assert!(!listed[0].is_active(Utc::now()));
+        assert!(
+            expire_alert_silence_inner("missing".into(), &state)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn maintenance_windows_are_saved_and_deleted() {
+        let mut alerting = MockAlertingPort::new();
+        alerting
+            .expect_save_maintenance_window()
+            .withf(|w| !w.id.is_empty() && w.days == [Weekday::Mon, Weekday::Sat])
+            .times(1)
+            .returning(|_| Ok(()));
+        alerting
+            .expect_delete_maintenance_window()
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit.expect_log().times(2).returning(|_, _| Ok(()));
+        let state = make_test_state(alerting, audit);
+
+        let window = MaintenanceWindow {
+            id: String::new(),
+            name: " Nightly backup ".into(),
+            matcher: AlertMatcher::default(),
+            days: vec![Weekday::Sat, Weekday::Mon, Weekday::Sat],
+            start_time: "02:00".into(),
+            duration_minutes: 90,
+            enabled: true,
+        };
+        let invalid = MaintenanceWindow {
+            start_time: "25:00".into(),
+            ..window.clone()
+        };
+        assert!(
+            save_maintenance_window_inner(invalid, &state)
+                .await
+                .is_err()
+        );
+
+        let saved = save_maintenance_window_inner(window, &state).await.unwrap();
+        assert_eq!(saved.name, "Nightly backup");
+        assert_eq!(state.alert_suppressions.read().await.windows.len(), 1);
+
+        delete_maintenance_window_inner(saved.id, &state)
+            .await
+            .unwrap();
+        assert!(state.alert_suppressions.read().await.windows.is_empty());
+    }
+}
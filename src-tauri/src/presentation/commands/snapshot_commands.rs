@@ -1,23 +1,75 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use tracing::instrument;
 
+use crate::application::commands::cancel_snapshot::{CancelSnapshotCommand, CancelSnapshotHandler};
 use crate::application::commands::create_snapshot::{CreateSnapshotCommand, CreateSnapshotHandler};
 use crate::application::commands::delete_snapshot::{DeleteSnapshotCommand, DeleteSnapshotHandler};
+use crate::application::commands::rekey_snapshot::{RekeySnapshotCommand, RekeySnapshotHandler};
+use crate::application::commands::rescan_snapshots::{
+    RescanSnapshotsCommand, RescanSnapshotsHandler,
+};
 use crate::application::commands::restore_snapshot::{
     RestoreSnapshotCommand, RestoreSnapshotHandler,
 };
-use crate::application::dto::responses::SnapshotResponse;
+use crate::application::commands::update_snapshot_metadata::{
+    UpdateSnapshotMetadataCommand, UpdateSnapshotMetadataHandler,
+};
+use crate::application::commands::verify_snapshot::{VerifySnapshotCommand, VerifySnapshotHandler};
+use crate::application::dto::responses::{
+    RescanReportResponse, RestorePreflightResponse, SnapshotDiffResponse, SnapshotResponse,
+    SnapshotTreeNodeResponse, VerifySnapshotResponse,
+};
+use crate::application::queries::diff_snapshots::DiffSnapshotsHandler;
+use crate::application::queries::list_snapshot_contents::ListSnapshotContentsHandler;
 use crate::application::queries::list_snapshots::ListSnapshotsHandler;
-use crate::domain::entities::snapshot::{ExportFormat, RestoreMode};
+use crate::application::queries::preflight_restore::{
+    PreflightRestoreHandler, PreflightRestoreQuery,
+};
+use crate::application::services::encryption::SnapshotSecret;
+use crate::domain::entities::snapshot::{ExportFormat, RestoreMode, SnapshotStorage};
 use crate::domain::errors::DomainError;
+use crate::domain::ports::snapshot_repository::SnapshotFilter;
 use crate::domain::value_objects::{DistroName, SnapshotId};
 use crate::infrastructure::terminal::adapter::TerminalSessionManager;
+use crate::presentation::events::EVENT_SNAPSHOTS_VERIFIED;
 use crate::presentation::state::AppState;
 
+/// Parse the `format` argument of snapshot commands; defaults to plain tar.
+pub(crate) fn parse_export_format(format: Option<&str>) -> ExportFormat {
+    match format {
+        Some("vhdx") => ExportFormat::Vhd,
+        Some("tar.zst") => ExportFormat::TarZst,
+        Some("tar.gz") => ExportFormat::TarGz,
+        _ => ExportFormat::Tar,
+    }
+}
+
+/// Parse the `storage` argument of snapshot commands; defaults to file storage.
+pub(crate) fn parse_storage(storage: Option<&str>) -> SnapshotStorage {
+    match storage {
+        Some("chunked") => SnapshotStorage::Chunked,
+        _ => SnapshotStorage::File,
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SnapshotFilterArgs {
+    /// Snapshots must carry every one of these tags.
+    pub tags: Option<Vec<String>>,
+    /// RFC 3339 bounds on the creation time, both inclusive.
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub min_size_bytes: Option<u64>,
+    pub max_size_bytes: Option<u64>,
+    pub pinned: Option<bool>,
+}
+
 /// Inner logic for list_snapshots, testable without Tauri runtime.
 pub(crate) async fn list_snapshots_inner(
     distro_name: Option<String>,
+    filter: Option<SnapshotFilterArgs>,
     state: &AppState,
 ) -> Result<Vec<SnapshotResponse>, DomainError> {
     let name = match distro_name {
@@ -25,16 +77,108 @@ pub(crate) async fn list_snapshots_inner(
         None => None,
     };
     let handler = ListSnapshotsHandler::new(state.snapshot_repo.clone());
-    handler.handle(name).await
+    match filter {
+        Some(f) => {
+            handler
+                .handle_filtered(SnapshotFilter {
+                    distro_name: name,
+                    tags: f.tags.unwrap_or_default(),
+                    created_after: f.created_after,
+                    created_before: f.created_before,
+                    min_size_bytes: f.min_size_bytes,
+                    max_size_bytes: f.max_size_bytes,
+                    pinned: f.pinned,
+                })
+                .await
+        }
+        None => handler.handle(name).await,
+    }
 }
 
 #[tauri::command]
-#[instrument(skip(state), fields(cmd = "list_snapshots"))]
+#[instrument(skip(state, filter), fields(cmd = "list_snapshots"))]
 pub async fn list_snapshots(
     distro_name: Option<String>,
+    filter: Option<SnapshotFilterArgs>,
     state: State<'_, AppState>,
 ) -> Result<Vec<SnapshotResponse>, DomainError> {
-    list_snapshots_inner(distro_name, &state).await
+    list_snapshots_inner(distro_name, filter, &state).await
+}
+
+/// Inner logic for list_snapshot_tags, testable without Tauri runtime.
+pub(crate) async fn list_snapshot_tags_inner(state: &AppState) -> Result<Vec<String>, DomainError> {
+    ListSnapshotsHandler::new(state.snapshot_repo.clone())
+        .tags()
+        .await
+}
+
+/// Every snapshot tag in use, sorted.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_snapshot_tags"))]
+pub async fn list_snapshot_tags(state: State<'_, AppState>) -> Result<Vec<String>, DomainError> {
+    list_snapshot_tags_inner(&state).await
+}
+
+/// Inner logic for list_snapshot_contents, testable without Tauri runtime.
+pub(crate) async fn list_snapshot_contents_inner(
+    snapshot_id: String,
+    path: Option<String>,
+    depth: Option<u32>,
+    secret: Option<SnapshotSecret>,
+    state: &AppState,
+) -> Result<SnapshotTreeNodeResponse, DomainError> {
+    let handler = ListSnapshotContentsHandler::new(state.snapshot_repo.clone());
+    handler
+        .handle(SnapshotId::from_string(snapshot_id), path, depth, secret)
+        .await
+}
+
+/// Browse a snapshot's files as a tree (below `path`, `depth` levels deep).
+/// Encrypted snapshots need their `passphrase` or `keyfile`.
+#[tauri::command]
+#[instrument(skip(state, passphrase), fields(cmd = "list_snapshot_contents", snapshot = %snapshot_id))]
+pub async fn list_snapshot_contents(
+    snapshot_id: String,
+    path: Option<String>,
+    depth: Option<u32>,
+    passphrase: Option<String>,
+    keyfile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SnapshotTreeNodeResponse, DomainError> {
+    let secret = SnapshotSecret::from_args(passphrase, keyfile)?;
+    list_snapshot_contents_inner(snapshot_id, path, depth, secret, &state).await
+}
+
+/// Inner logic for diff_snapshots, testable without Tauri runtime.
+pub(crate) async fn diff_snapshots_inner(
+    from_id: String,
+    to_id: String,
+    secret: Option<SnapshotSecret>,
+    state: &AppState,
+) -> Result<SnapshotDiffResponse, DomainError> {
+    let handler = DiffSnapshotsHandler::new(state.snapshot_repo.clone());
+    handler
+        .handle(
+            SnapshotId::from_string(from_id),
+            SnapshotId::from_string(to_id),
+            secret,
+        )
+        .await
+}
+
+/// Compare the filesystems of two snapshots of the same distro. Encrypted
+/// snapshots need their `passphrase` or `keyfile`.
+#[tauri::command]
+#[instrument(skip(state, passphrase), fields(cmd = "diff_snapshots", from = %from_id, to = %to_id))]
+pub async fn diff_snapshots(
+    from_id: String,
+    to_id: String,
+    passphrase: Option<String>,
+    keyfile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SnapshotDiffResponse, DomainError> {
+    let secret = SnapshotSecret::from_args(passphrase, keyfile)?;
+    diff_snapshots_inner(from_id, to_id, secret, &state).await
 }
 
 #[derive(Debug, Deserialize)]
@@ -44,6 +188,13 @@ pub struct CreateSnapshotArgs {
     pub description: Option<String>,
     pub format: Option<String>,
     pub output_dir: String,
+    /// Parent snapshot id; when set, only the delta against it is stored.
+    pub parent_id: Option<String>,
+    /// "chunked" stores the archive in the deduplicated chunk store.
+    pub storage: Option<String>,
+    /// Encrypt the archive with this passphrase or keyfile (at most one).
+    pub passphrase: Option<String>,
+    pub keyfile: Option<String>,
 }
 
 /// Inner logic for create_snapshot, testable without Tauri runtime.
@@ -51,10 +202,7 @@ pub(crate) async fn create_snapshot_inner(
     args: CreateSnapshotArgs,
     state: &AppState,
 ) -> Result<SnapshotResponse, DomainError> {
-    let format = match args.format.as_deref() {
-        Some("vhdx") => ExportFormat::Vhd,
-        _ => ExportFormat::Tar,
-    };
+    let format = parse_export_format(args.format.as_deref());
 
     tracing::info!(
         "create_snapshot command received: distro={} name={} format={:?} output_dir={} desc={:?}",
@@ -69,7 +217,8 @@ pub(crate) async fn create_snapshot_inner(
         state.wsl_manager.clone(),
         state.snapshot_repo.clone(),
         state.audit_logger.clone(),
-    );
+    )
+    .with_transfers(state.transfers.clone());
 
     let cmd = CreateSnapshotCommand {
         distro_name: DistroName::new(&args.distro_name)?,
@@ -77,6 +226,9 @@ pub(crate) async fn create_snapshot_inner(
         description: args.description,
         format,
         output_dir: args.output_dir,
+        parent_id: args.parent_id.map(SnapshotId::from_string),
+        storage: parse_storage(args.storage.as_deref()),
+        encryption: SnapshotSecret::from_args(args.passphrase, args.keyfile)?,
     };
 
     match handler.handle(cmd).await {
@@ -97,7 +249,7 @@ pub(crate) async fn create_snapshot_inner(
 }
 
 #[tauri::command]
-#[instrument(skip(state), fields(cmd = "create_snapshot", distro = %args.distro_name))]
+#[instrument(skip(state, args), fields(cmd = "create_snapshot", distro = %args.distro_name))]
 pub async fn create_snapshot(
     args: CreateSnapshotArgs,
     state: State<'_, AppState>,
@@ -135,6 +287,30 @@ pub struct RestoreSnapshotArgs {
     pub mode: String,
     pub new_name: Option<String>,
     pub install_location: Option<String>,
+    /// Paths to copy back for `mode: "files"`.
+    pub paths: Option<Vec<String>>,
+    /// Restore even if the archive fails its checksum verification.
+    pub force: Option<bool>,
+    /// Passphrase or keyfile of an encrypted snapshot.
+    pub passphrase: Option<String>,
+    pub keyfile: Option<String>,
+}
+
+/// Restore mode selected by `args.mode`: "clone", "files" or overwrite.
+fn restore_mode(args: &RestoreSnapshotArgs) -> Result<RestoreMode, DomainError> {
+    Ok(match args.mode.as_str() {
+        "clone" => {
+            let new_name = args
+                .new_name
+                .clone()
+                .ok_or_else(|| DomainError::Internal("Clone mode requires a new_name".into()))?;
+            RestoreMode::Clone { new_name }
+        }
+        "files" => RestoreMode::Files {
+            paths: args.paths.clone().unwrap_or_default(),
+        },
+        _ => RestoreMode::Overwrite,
+    })
 }
 
 /// Inner logic for restore_snapshot, testable without Tauri runtime.
@@ -143,14 +319,8 @@ pub(crate) async fn restore_snapshot_inner(
     state: &AppState,
     terminal_mgr: &TerminalSessionManager,
 ) -> Result<(), DomainError> {
-    let mode = if args.mode == "clone" {
-        let new_name = args
-            .new_name
-            .ok_or_else(|| DomainError::Internal("Clone mode requires a new_name".into()))?;
-        RestoreMode::Clone { new_name }
-    } else {
-        RestoreMode::Overwrite
-    };
+    let mode = restore_mode(&args)?;
+    let secret = SnapshotSecret::from_args(args.passphrase, args.keyfile)?;
 
     // Resolve the snapshot to get distro_name for terminal cleanup
     let snapshot = state
@@ -168,6 +338,8 @@ pub(crate) async fn restore_snapshot_inner(
                 .get_distro_install_path(&snapshot.distro_name)
                 .await?
         }
+        // Files are copied into the running distro, which stays registered.
+        (RestoreMode::Files { .. }, _) => String::new(),
         _ => {
             return Err(DomainError::Internal(
                 "Clone mode requires an install_location".into(),
@@ -177,7 +349,11 @@ pub(crate) async fn restore_snapshot_inner(
 
     // Close all terminal sessions for this distro before restore —
     // the old PTY sessions would be stale after unregister + import.
-    let closed = terminal_mgr.close_sessions_by_distro(&distro_name).await;
+    let closed = if matches!(mode, RestoreMode::Files { .. }) {
+        Vec::new()
+    } else {
+        terminal_mgr.close_sessions_by_distro(&distro_name).await
+    };
     if !closed.is_empty() {
         tracing::info!(
             "closed {} terminal session(s) for '{}' before restore",
@@ -190,19 +366,24 @@ pub(crate) async fn restore_snapshot_inner(
         state.wsl_manager.clone(),
         state.snapshot_repo.clone(),
         state.audit_logger.clone(),
-    );
+    )
+    .with_transfers(state.transfers.clone())
+    .with_replicator(state.replicator.clone())
+    .with_safety(state.safety_guard());
 
     handler
         .handle(RestoreSnapshotCommand {
             snapshot_id: SnapshotId::from_string(args.snapshot_id),
             mode,
             install_location,
+            force: args.force.unwrap_or(false),
+            secret,
         })
         .await
 }
 
 #[tauri::command]
-#[instrument(skip(state, terminal_mgr), fields(cmd = "restore_snapshot", snapshot = %args.snapshot_id))]
+#[instrument(skip(state, terminal_mgr, args), fields(cmd = "restore_snapshot", snapshot = %args.snapshot_id))]
 pub async fn restore_snapshot(
     args: RestoreSnapshotArgs,
     state: State<'_, AppState>,
@@ -210,3 +391,214 @@ pub async fn restore_snapshot(
 ) -> Result<(), DomainError> {
     restore_snapshot_inner(args, &state, &terminal_mgr).await
 }
+
+/// Inner logic for preflight_restore, testable without Tauri runtime.
+pub(crate) async fn preflight_restore_inner(
+    args: RestoreSnapshotArgs,
+    state: &AppState,
+) -> Result<RestorePreflightResponse, DomainError> {
+    let mode = restore_mode(&args)?;
+    let secret = SnapshotSecret::from_args(args.passphrase, args.keyfile)?;
+    let handler =
+        PreflightRestoreHandler::new(state.wsl_manager.clone(), state.snapshot_repo.clone())
+            .with_replicator(state.replicator.clone());
+    handler
+        .handle(PreflightRestoreQuery {
+            snapshot_id: SnapshotId::from_string(args.snapshot_id),
+            mode,
+            install_location: args.install_location.unwrap_or_default(),
+            force: args.force.unwrap_or(false),
+            secret,
+        })
+        .await
+}
+
+/// Dry run of `restore_snapshot` with the same arguments: reports free
+/// space, name conflicts, running distros that would be stopped, the default
+/// user and format compatibility without changing anything.
+#[tauri::command]
+#[instrument(skip(state, args), fields(cmd = "preflight_restore", snapshot = %args.snapshot_id))]
+pub async fn preflight_restore(
+    args: RestoreSnapshotArgs,
+    state: State<'_, AppState>,
+) -> Result<RestorePreflightResponse, DomainError> {
+    preflight_restore_inner(args, &state).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSnapshotMetadataArgs {
+    pub snapshot_id: String,
+    pub name: Option<String>,
+    /// Notes; an empty string clears them.
+    pub description: Option<String>,
+    /// Replaces the whole tag set.
+    pub tags: Option<Vec<String>>,
+    pub pinned: Option<bool>,
+}
+
+/// Inner logic for update_snapshot_metadata, testable without Tauri runtime.
+pub(crate) async fn update_snapshot_metadata_inner(
+    args: UpdateSnapshotMetadataArgs,
+    state: &AppState,
+) -> Result<SnapshotResponse, DomainError> {
+    let handler =
+        UpdateSnapshotMetadataHandler::new(state.snapshot_repo.clone(), state.audit_logger.clone());
+    handler
+        .handle(UpdateSnapshotMetadataCommand {
+            snapshot_id: SnapshotId::from_string(args.snapshot_id),
+            name: args.name,
+            description: args.description,
+            tags: args.tags,
+            pinned: args.pinned,
+        })
+        .await
+        .map(SnapshotResponse::from)
+}
+
+/// Rename a snapshot or change its notes, tags or pinned flag.
+#[tauri::command]
+#[instrument(skip(state, args), fields(cmd = "update_snapshot_metadata", snapshot = %args.snapshot_id))]
+pub async fn update_snapshot_metadata(
+    args: UpdateSnapshotMetadataArgs,
+    state: State<'_, AppState>,
+) -> Result<SnapshotResponse, DomainError> {
+    update_snapshot_metadata_inner(args, &state).await
+}
+
+#[derive(Deserialize)]
+pub struct RekeySnapshotArgs {
+    pub snapshot_id: String,
+    /// Secret the snapshot is encrypted with now; omit for a plain snapshot.
+    pub current_passphrase: Option<String>,
+    pub current_keyfile: Option<String>,
+    /// Secret to encrypt with; omit both to decrypt the snapshot.
+    pub new_passphrase: Option<String>,
+    pub new_keyfile: Option<String>,
+}
+
+/// Inner logic for rekey_snapshot, testable without Tauri runtime.
+pub(crate) async fn rekey_snapshot_inner(
+    args: RekeySnapshotArgs,
+    state: &AppState,
+) -> Result<SnapshotResponse, DomainError> {
+    let handler =
+        RekeySnapshotHandler::new(state.snapshot_repo.clone(), state.audit_logger.clone())
+            .with_replicator(state.replicator.clone());
+    handler
+        .handle(RekeySnapshotCommand {
+            snapshot_id: SnapshotId::from_string(args.snapshot_id),
+            current: SnapshotSecret::from_args(args.current_passphrase, args.current_keyfile)?,
+            new: SnapshotSecret::from_args(args.new_passphrase, args.new_keyfile)?,
+        })
+        .await
+        .map(SnapshotResponse::from)
+}
+
+/// Encrypt a snapshot, change its passphrase/keyfile, or decrypt it.
+#[tauri::command]
+#[instrument(skip(state, args), fields(cmd = "rekey_snapshot", snapshot = %args.snapshot_id))]
+pub async fn rekey_snapshot(
+    args: RekeySnapshotArgs,
+    state: State<'_, AppState>,
+) -> Result<SnapshotResponse, DomainError> {
+    rekey_snapshot_inner(args, &state).await
+}
+
+/// Inner logic for cancel_snapshot, testable without Tauri runtime.
+pub(crate) async fn cancel_snapshot_inner(
+    snapshot_id: String,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    let handler = CancelSnapshotHandler::new(
+        state.snapshot_repo.clone(),
+        state.transfers.clone(),
+        state.audit_logger.clone(),
+    );
+    handler
+        .handle(CancelSnapshotCommand {
+            snapshot_id: SnapshotId::from_string(snapshot_id),
+        })
+        .await
+}
+
+/// Cancel a running snapshot export (or restore import). Progress of both is
+/// emitted as `snapshot-progress` events.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "cancel_snapshot", snapshot = %snapshot_id))]
+pub async fn cancel_snapshot(
+    snapshot_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    cancel_snapshot_inner(snapshot_id, &state).await
+}
+
+/// Inner logic for verify_snapshot, testable without Tauri runtime.
+pub(crate) async fn verify_snapshot_inner(
+    snapshot_id: String,
+    state: &AppState,
+) -> Result<VerifySnapshotResponse, DomainError> {
+    let handler =
+        VerifySnapshotHandler::new(state.snapshot_repo.clone(), state.audit_logger.clone());
+    handler
+        .handle(VerifySnapshotCommand {
+            snapshot_id: SnapshotId::from_string(snapshot_id),
+        })
+        .await
+        .map(VerifySnapshotResponse::from)
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "verify_snapshot", snapshot = %snapshot_id))]
+pub async fn verify_snapshot(
+    snapshot_id: String,
+    state: State<'_, AppState>,
+) -> Result<VerifySnapshotResponse, DomainError> {
+    verify_snapshot_inner(snapshot_id, &state).await
+}
+
+/// Inner logic for rescan_snapshots, testable without Tauri runtime.
+pub(crate) async fn rescan_snapshots_inner(
+    directories: Option<Vec<String>>,
+    state: &AppState,
+) -> Result<RescanReportResponse, DomainError> {
+    let handler =
+        RescanSnapshotsHandler::new(state.snapshot_repo.clone(), state.audit_logger.clone());
+    handler
+        .handle(RescanSnapshotsCommand {
+            directories: directories.unwrap_or_default(),
+        })
+        .await
+        .map(RescanReportResponse::from)
+}
+
+/// Reconcile the catalog with the snapshot folders (plus `directories`):
+/// import snapshots from sidecar manifests and report missing and orphan files.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "rescan_snapshots"))]
+pub async fn rescan_snapshots(
+    directories: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<RescanReportResponse, DomainError> {
+    rescan_snapshots_inner(directories, &state).await
+}
+
+/// Start a verify-all pass in the background. The report is emitted as
+/// `snapshots-verified` when the pass completes.
+#[tauri::command]
+#[instrument(skip(app_handle, state), fields(cmd = "verify_all_snapshots"))]
+pub async fn verify_all_snapshots(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    let handler =
+        VerifySnapshotHandler::new(state.snapshot_repo.clone(), state.audit_logger.clone());
+    tauri::async_runtime::spawn(async move {
+        match handler.verify_all().await {
+            Ok(report) => {
+                let _ = app_handle.emit(EVENT_SNAPSHOTS_VERIFIED, report);
+            }
+            Err(e) => tracing::warn!("verify_all_snapshots: {}", e),
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, State};
+use tracing::instrument;
+
+use crate::application::commands::provision_from_template::{
+    ProvisionFromTemplateCommand, ProvisionFromTemplateHandler, ProvisionSink,
+};
+use crate::application::dto::responses::{DistroResponse, DistroTemplateResponse};
+use crate::application::services::encryption::SnapshotSecret;
+use crate::domain::entities::distro_template::{DistroTemplate, TemplateParameter};
+use crate::domain::entities::snapshot::SnapshotStatus;
+use crate::domain::entities::wsl_config::WslDistroConfig;
+use crate::domain::errors::DomainError;
+use crate::domain::value_objects::SnapshotId;
+use crate::presentation::events::EVENT_PROVISION_OUTPUT;
+use crate::presentation::state::AppState;
+
+/// Inner logic for list_templates, testable without Tauri runtime.
+pub(crate) async fn list_templates_inner(
+    state: &AppState,
+) -> Result<Vec<DistroTemplateResponse>, DomainError> {
+    let templates = state.templates.list().await?;
+    Ok(templates
+        .into_iter()
+        .map(DistroTemplateResponse::from)
+        .collect())
+}
+
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "list_templates"))]
+pub async fn list_templates(
+    state: State<'_, AppState>,
+) -> Result<Vec<DistroTemplateResponse>, DomainError> {
+    list_templates_inner(&state).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveTemplateArgs {
+    /// Existing template to update; a new one is created when absent.
+    pub id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub snapshot_id: String,
+    /// Bootstrap script run as root; `{{param}}` expands to a quoted value.
+    pub script: Option<String>,
+    pub parameters: Option<Vec<TemplateParameter>>,
+    /// Fields written over the clone's /etc/wsl.conf.
+    pub wsl_config: Option<WslDistroConfig>,
+}
+
+/// Inner logic for save_template, testable without Tauri runtime.
+pub(crate) async fn save_template_inner(
+    args: SaveTemplateArgs,
+    state: &AppState,
+) -> Result<DistroTemplateResponse, DomainError> {
+    let snapshot_id = SnapshotId::from_string(args.snapshot_id);
+    let snapshot = state.snapshot_repo.get_by_id(&snapshot_id).await?;
+    if !matches!(snapshot.status, SnapshotStatus::Completed) {
+        return Err(DomainError::ConfigError(format!(
+            "Snapshot '{}' is not a completed snapshot",
+            snapshot.name
+        )));
+    }
+
+    let existing =
+        match &args.id {
+            Some(id) => {
+                Some(state.templates.get(id).await?.ok_or_else(|| {
+                    DomainError::ConfigError(format!("Template '{}' not found", id))
+                })?)
+            }
+            None => None,
+        };
+
+    let now = chrono::Utc::now();
+    let template = DistroTemplate {
+        id: args.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name: args.name.trim().to_string(),
+        description: args.description.filter(|d| !d.trim().is_empty()),
+        snapshot_id,
+        script: args.script.unwrap_or_default(),
+        parameters: args.parameters.unwrap_or_default(),
+        wsl_config: args.wsl_config.unwrap_or_default(),
+        created_at: existing.as_ref().map(|t| t.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+    template.validate()?;
+
+    state.templates.save(&template).await?;
+    state
+        .audit(
+            "template.save",
+            &format!("{} ({})", template.name, template.id),
+        )
+        .await?;
+
+    Ok(DistroTemplateResponse::from(template))
+}
+
+#[tauri::command]
+#[instrument(skip(state, args), fields(cmd = "save_template", template = %args.name))]
+pub async fn save_template(
+    args: SaveTemplateArgs,
+    state: State<'_, AppState>,
+) -> Result<DistroTemplateResponse, DomainError> {
+    save_template_inner(args, &state).await
+}
+
+/// Inner logic for delete_template, testable without Tauri runtime.
+pub(crate) async fn delete_template_inner(
+    template_id: String,
+    state: &AppState,
+) -> Result<(), DomainError> {
+    state.templates.delete(&template_id).await?;
+    state.audit("template.delete", &template_id).await?;
+    Ok(())
+}
+
+/// Delete a template; its snapshot and the distros provisioned from it are kept.
+#[tauri::command]
+#[instrument(skip(state), fields(cmd = "delete_template", template = %template_id))]
+pub async fn delete_template(
+    template_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), DomainError> {
+    delete_template_inner(template_id, &state).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProvisionFromTemplateArgs {
+    pub template_id: String,
+    pub distro_name: String,
+    pub install_location: String,
+    pub parameters: Option<HashMap<String, String>>,
+    /// Passphrase or keyfile of an encrypted template snapshot.
+    pub passphrase: Option<String>,
+    pub keyfile: Option<String>,
+}
+
+/// Inner logic for provision_from_template, testable without Tauri runtime.
+pub(crate) async fn provision_from_template_inner(
+    args: ProvisionFromTemplateArgs,
+    state: &AppState,
+    output: ProvisionSink,
+) -> Result<DistroResponse, DomainError> {
+    let secret = SnapshotSecret::from_args(args.passphrase, args.keyfile)?;
+    let handler = ProvisionFromTemplateHandler::new(
+        state.wsl_manager.clone(),
+        state.snapshot_repo.clone(),
+        state.templates.clone(),
+        state.audit_logger.clone(),
+    )
+    .with_transfers(state.transfers.clone())
+    .with_replicator(state.replicator.clone())
+    .with_output(output);
+    handler
+        .handle(ProvisionFromTemplateCommand {
+            template_id: args.template_id,
+            distro_name: args.distro_name,
+            install_location: args.install_location,
+            parameters: args.parameters.unwrap_or_default(),
+            secret,
+        })
+        .await
+        .map(DistroResponse::from)
+}
+
+/// Create a distro from a template: clone its snapshot, write its wsl.conf
+/// overlay and run its bootstrap script. Phases and script output are
+/// emitted as `provision-output` events; the clone's import reports
+/// `snapshot-progress` like a restore.
+#[tauri::command]
+#[instrument(
+    skip(app_handle, state, args),
+    fields(cmd = "provision_from_template", template = %args.template_id, distro = %args.distro_name)
+)]
+pub async fn provision_from_template(
+    args: ProvisionFromTemplateArgs,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DistroResponse, DomainError> {
+    let output: ProvisionSink = std::sync::Arc::new(move |out| {
+        let _ = app_handle.emit(EVENT_PROVISION_OUTPUT, out);
+    });
+    provision_from_template_inner(args, &state, output).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::replication::SnapshotReplicator;
+    use crate::application::services::transfer::TransferRegistry;
+    use std::sync::Arc;
+
+    use crate::domain::entities::snapshot::{
+        ExportFormat, Snapshot, SnapshotStorage, SnapshotType,
+    };
+    use crate::domain::ports::alerting::MockAlertingPort;
+    use crate::domain::ports::audit_logger::MockAuditLoggerPort;
+    use crate::domain::ports::metrics_exporter::MockMetricsExporterPort;
+    use crate::domain::ports::metrics_repository::MockMetricsRepositoryPort;
+    use crate::domain::ports::monitoring_provider::MockMonitoringProviderPort;
+    use crate::domain::ports::notification_repository::MockNotificationRepositoryPort;
+    use crate::domain::ports::port_forwarding::{
+        MockPortForwardRulesRepository, MockPortForwardingPort,
+    };
+    use crate::domain::ports::replication_repository::MockReplicationRepositoryPort;
+    use crate::domain::ports::safety_policy_repository::MockSafetyPolicyRepositoryPort;
+    use crate::domain::ports::schedule_repository::MockScheduleRepositoryPort;
+    use crate::domain::ports::snapshot_repository::MockSnapshotRepositoryPort;
+    use crate::domain::ports::template_repository::MockTemplateRepositoryPort;
+    use crate::domain::ports::wsl_manager::MockWslManagerPort;
+    use crate::domain::services::alert_dispatcher::AlertDispatcher;
+    use crate::domain::value_objects::{DistroName, MemorySize};
+
+    fn make_test_state(
+        templates: MockTemplateRepositoryPort,
+        audit: MockAuditLoggerPort,
+    ) -> AppState {
+        let mut snapshots = MockSnapshotRepositoryPort::new();
+        snapshots.expect_get_by_id().returning(|id| {
+            Ok(Snapshot {
+                id: id.clone(),
+                distro_name: DistroName::new("Ubuntu").unwrap(),
+                name: "golden".into(),
+                description: None,
+                snapshot_type: SnapshotType::Full,
+                format: ExportFormat::Tar,
+                file_path: "/tmp/golden.tar".into(),
+                file_size: MemorySize::from_bytes(1),
+                parent_id: None,
+                created_at: chrono::Utc::now(),
+                status: if id.as_str() == "snap-failed" {
+                    SnapshotStatus::Failed("disk full".into())
+                } else {
+                    SnapshotStatus::Completed
+                },
+                default_user: None,
+                storage: SnapshotStorage::File,
+                checksum: None,
+                tags: Vec::new(),
+                pinned: false,
+                encryption: None,
+            })
+        });
+        AppState {
+            wsl_manager: Arc::new(MockWslManagerPort::new()),
+            snapshot_repo: Arc::new(snapshots),
+            monitoring: Arc::new(MockMonitoringProviderPort::new()),
+            metrics_repo: Arc::new(MockMetricsRepositoryPort::new()),
+            alerting: Arc::new(MockAlertingPort::new()),
+            audit_logger: Arc::new(audit),
+            alert_thresholds: Default::default(),
+            alert_suppressions: Default::default(),
+            metrics_settings: Default::default(),
+            metrics_exporter: Arc::new(MockMetricsExporterPort::new()),
+            port_forwarding: Arc::new(MockPortForwardingPort::new()),
+            port_rules_repo: Arc::new(MockPortForwardRulesRepository::new()),
+            schedule_repo: Arc::new(MockScheduleRepositoryPort::new()),
+            transfers: Arc::new(TransferRegistry::default()),
+            replicator: Arc::new(SnapshotReplicator::new(
+                Arc::new(MockSnapshotRepositoryPort::new()),
+                Arc::new(MockReplicationRepositoryPort::new()),
+                Arc::new(MockAuditLoggerPort::new()),
+                Arc::new(|_| Err(DomainError::Internal("no object store".into()))),
+            )),
+            safety_policies: Arc::new(MockSafetyPolicyRepositoryPort::new()),
+            templates: Arc::new(templates),
+            notification_repo: Arc::new(MockNotificationRepositoryPort::new()),
+            alert_dispatcher: Arc::new(AlertDispatcher::new(Arc::new(
+                MockNotificationRepositoryPort::new(),
+            ))),
+        }
+    }
+
+    fn make_args(snapshot_id: &str, script: &str) -> SaveTemplateArgs {
+        SaveTemplateArgs {
+            id: None,
+            name: " node-dev ".into(),
+            description: Some(String::new()),
+            snapshot_id: snapshot_id.into(),
+            script: Some(script.into()),
+            parameters: Some(vec![TemplateParameter {
+                name: "user".into(),
+                description: None,
+                default: Some("dev".into()),
+                required: true,
+            }]),
+            wsl_config: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn save_creates_template_and_audits() {
+        let mut templates = MockTemplateRepositoryPort::new();
+        templates
+            .expect_save()
+            .withf(|t| t.name == "node-dev" && t.description.is_none())
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| action == "template.save" && detail.starts_with("node-dev ("))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let state = make_test_state(templates, audit);
+        let resp = save_template_inner(make_args("snap-1", "useradd -m {{user}}"), &state)
+            .await
+            .unwrap();
+        assert_eq!(resp.snapshot_id, "snap-1");
+        assert!(!resp.id.is_empty());
+    }
+
+    #[tokio::test]
+    async fn save_rejects_unknown_placeholder_and_unfinished_snapshot() {
+        let mut templates = MockTemplateRepositoryPort::new();
+        templates.expect_save().never();
+        let state = make_test_state(templates, MockAuditLoggerPort::new());
+
+        let err = save_template_inner(make_args("snap-1", "echo {{shell}}"), &state)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("shell"), "{err}");
+
+        let err = save_template_inner(make_args("snap-failed", "true"), &state)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("not a completed snapshot"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_removes_template_and_audits() {
+        let mut templates = MockTemplateRepositoryPort::new();
+        templates
+            .expect_delete()
+            .withf(|id| id == "tpl-1")
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut audit = MockAuditLoggerPort::new();
+        audit
+            .expect_log()
+            .withf(|action, detail| action == "template.delete" && detail == "tpl-1")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let state = make_test_state(templates, audit);
+        assert!(delete_template_inner("tpl-1".into(), &state).await.is_ok());
+    }
+}
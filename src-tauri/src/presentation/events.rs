@@ -1,2 +1,6 @@
 pub const EVENT_SYSTEM_METRICS: &str = "system-metrics";
 pub const EVENT_ALERT_TRIGGERED: &str = "alert-triggered";
+pub const EVENT_ALERT_RESOLVED: &str = "alert-resolved";
+pub const EVENT_SNAPSHOTS_VERIFIED: &str = "snapshots-verified";
+pub const EVENT_SNAPSHOT_PROGRESS: &str = "snapshot-progress";
+pub const EVENT_PROVISION_OUTPUT: &str = "provision-output";
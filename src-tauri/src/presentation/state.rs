@@ -1,12 +1,23 @@
 use std::sync::Arc;
 
-use crate::domain::ports::alerting::{AlertThreshold, AlertingPort};
+use crate::application::services::replication::SnapshotReplicator;
+use crate::application::services::safety_snapshot::SafetySnapshotGuard;
+use crate::application::services::transfer::TransferRegistry;
+use crate::domain::entities::alert_silence::AlertSuppressions;
+use crate::domain::entities::metrics_settings::MetricsSettings;
+use crate::domain::ports::alerting::{AlertThresholdSet, AlertingPort};
 use crate::domain::ports::audit_logger::AuditLoggerPort;
+use crate::domain::ports::metrics_exporter::MetricsExporterPort;
 use crate::domain::ports::metrics_repository::MetricsRepositoryPort;
 use crate::domain::ports::monitoring_provider::MonitoringProviderPort;
+use crate::domain::ports::notification_repository::NotificationRepositoryPort;
 use crate::domain::ports::port_forwarding::{PortForwardRulesRepository, PortForwardingPort};
+use crate::domain::ports::safety_policy_repository::SafetyPolicyRepositoryPort;
+use crate::domain::ports::schedule_repository::ScheduleRepositoryPort;
 use crate::domain::ports::snapshot_repository::SnapshotRepositoryPort;
+use crate::domain::ports::template_repository::TemplateRepositoryPort;
 use crate::domain::ports::wsl_manager::WslManagerPort;
+use crate::domain::services::alert_dispatcher::AlertDispatcher;
 
 use crate::domain::errors::DomainError;
 
@@ -19,9 +30,31 @@ pub struct AppState {
     pub metrics_repo: Arc<dyn MetricsRepositoryPort>,
     pub alerting: Arc<dyn AlertingPort>,
     pub audit_logger: Arc<dyn AuditLoggerPort>,
-    pub alert_thresholds: Arc<tokio::sync::RwLock<Vec<AlertThreshold>>>,
+    /// Cache of the stored alert thresholds, read by the metrics collector.
+    pub alert_thresholds: Arc<tokio::sync::RwLock<AlertThresholdSet>>,
+    /// Cache of the stored silences and maintenance windows, read by the
+    /// metrics collector.
+    pub alert_suppressions: Arc<tokio::sync::RwLock<AlertSuppressions>>,
+    /// Cache of the stored metrics settings, read by the metrics collector
+    /// and aggregator.
+    pub metrics_settings: Arc<tokio::sync::RwLock<MetricsSettings>>,
+    /// Local OpenMetrics endpoint, started and stopped by the metrics settings.
+    pub metrics_exporter: Arc<dyn MetricsExporterPort>,
     pub port_forwarding: Arc<dyn PortForwardingPort>,
     pub port_rules_repo: Arc<dyn PortForwardRulesRepository>,
+    pub schedule_repo: Arc<dyn ScheduleRepositoryPort>,
+    /// Running snapshot exports/imports: progress events and cancellation.
+    pub transfers: Arc<TransferRegistry>,
+    /// Offsite copies of snapshot archives (S3-compatible object storage).
+    pub replicator: Arc<SnapshotReplicator>,
+    /// Per-distro policies for safety snapshots before destructive operations.
+    pub safety_policies: Arc<dyn SafetyPolicyRepositoryPort>,
+    /// Templates new distros are provisioned from.
+    pub templates: Arc<dyn TemplateRepositoryPort>,
+    /// Alert notification channels and their delivery log.
+    pub notification_repo: Arc<dyn NotificationRepositoryPort>,
+    /// Sends alert notifications to the channels of their rule.
+    pub alert_dispatcher: Arc<AlertDispatcher>,
 }
 
 impl AppState {
@@ -29,4 +62,18 @@ impl AppState {
     pub async fn audit(&self, action: &str, detail: &str) -> Result<(), DomainError> {
         self.audit_logger.log(action, detail).await
     }
+
+    /// Guard taking the safety snapshots the distros' policies ask for
+    /// before destructive operations.
+    pub fn safety_guard(&self) -> Arc<SafetySnapshotGuard> {
+        Arc::new(
+            SafetySnapshotGuard::new(
+                self.wsl_manager.clone(),
+                self.snapshot_repo.clone(),
+                self.safety_policies.clone(),
+                self.audit_logger.clone(),
+            )
+            .with_transfers(self.transfers.clone()),
+        )
+    }
 }
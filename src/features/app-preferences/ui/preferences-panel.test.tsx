@@ -9,12 +9,21 @@ const mockSetMetricsInterval = vi.fn();
 const mockSetProcessesInterval = vi.fn();
 const mockSetDefaultSnapshotDir = vi.fn();
 const mockSetDefaultInstallLocation = vi.fn();
-const mockSetAlertThresholds = vi.fn();
+const mockSaveThresholds = vi.fn();
 
 const defaultThresholds: AlertThreshold[] = [
   { alert_type: "cpu", threshold_percent: 90, enabled: false },
   { alert_type: "memory", threshold_percent: 85, enabled: false },
   { alert_type: "disk", threshold_percent: 90, enabled: false },
+  {
+    alert_type: "swap",
+    threshold_percent: 512,
+    enabled: true,
+    unit: "absolute",
+    sustained_secs: 60,
+    clear_percent: 256,
+    channels: ["ops-webhook"],
+  },
 ];
 
 vi.mock("@/shared/hooks/use-theme", () => ({
@@ -32,12 +41,10 @@ vi.mock("@/shared/stores/use-preferences-store", () => ({
       processesInterval: 3000,
       defaultSnapshotDir: "",
       defaultInstallLocation: "",
-      alertThresholds: defaultThresholds,
       setMetricsInterval: mockSetMetricsInterval,
       setProcessesInterval: mockSetProcessesInterval,
       setDefaultSnapshotDir: mockSetDefaultSnapshotDir,
       setDefaultInstallLocation: mockSetDefaultInstallLocation,
-      setAlertThresholds: mockSetAlertThresholds,
       developerMode: false,
       setDeveloperMode: vi.fn(),
     };
@@ -46,7 +53,8 @@ vi.mock("@/shared/stores/use-preferences-store", () => ({
 }));
 
 vi.mock("@/features/monitoring-dashboard/api/queries", () => ({
-  useSetAlertThresholds: () => ({ mutate: vi.fn() }),
+  useAlertThresholds: () => ({ data: defaultThresholds }),
+  useSetAlertThresholds: () => ({ mutate: mockSaveThresholds }),
 }));
 
 vi.mock("@tauri-apps/plugin-dialog", () => ({
@@ -158,6 +166,17 @@ describe("PreferencesPanel", () => {
     expect(toggles.length).toBeGreaterThanOrEqual(3);
   });
 
+  it("saves every loaded threshold when one is edited", () => {
+    renderWithProviders(<PreferencesPanel />);
+    fireEvent.click(screen.getAllByRole("switch")[0]);
+    expect(mockSaveThresholds).toHaveBeenCalledWith({
+      thresholds: [
+        { ...defaultThresholds[0], enabled: true },
+        ...defaultThresholds.slice(1),
+      ],
+    });
+  });
+
   it("renders monitoring section description", () => {
     renderWithProviders(<PreferencesPanel />);
     expect(screen.getByText(/Adjust how often metrics are polled/)).toBeInTheDocument();
@@ -1,4 +1,4 @@
-import { useEffect, useMemo, useRef } from "react";
+import { useMemo, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { Moon, Sun, Timer, Archive, FolderOpen, Bell, Code } from "lucide-react";
 import { Select } from "@/shared/ui/select";
@@ -9,8 +9,11 @@ import { usePreferencesStore } from "@/shared/stores/use-preferences-store";
 import { useShallow } from "zustand/react/shallow";
 import { useLocaleStore } from "@/shared/stores/use-locale-store";
 import { supportedLocales, localeLabels, type Locale } from "@/shared/config/i18n";
-import { useSetAlertThresholds } from "@/features/monitoring-dashboard/api/queries";
-import type { AlertThreshold } from "@/shared/types/monitoring";
+import {
+  useAlertThresholds,
+  useSetAlertThresholds,
+} from "@/features/monitoring-dashboard/api/queries";
+import type { AlertThreshold, AlertType } from "@/shared/types/monitoring";
 import { cn } from "@/shared/lib/utils";
 
 const INTERVAL_OPTIONS = [
@@ -24,6 +27,9 @@ const INTERVAL_OPTIONS = [
 
 const inputClass = "focus-ring glass-input text-text w-full rounded-lg px-3 py-2 text-sm";
 
+/** Percentage thresholds edited here; the other alert types are set from the dashboard. */
+const PANEL_ALERT_TYPES: AlertType[] = ["cpu", "memory", "disk"];
+
 export function PreferencesPanel() {
   const { t } = useTranslation();
   const { theme, toggleTheme } = useThemeStore();
@@ -42,13 +48,11 @@ export function PreferencesPanel() {
     processesInterval,
     defaultSnapshotDir,
     defaultInstallLocation,
-    alertThresholds,
     developerMode,
     setMetricsInterval,
     setProcessesInterval,
     setDefaultSnapshotDir,
     setDefaultInstallLocation,
-    setAlertThresholds,
     setDeveloperMode,
   } = usePreferencesStore(
     useShallow((s) => ({
@@ -56,34 +60,32 @@ export function PreferencesPanel() {
       processesInterval: s.processesInterval,
       defaultSnapshotDir: s.defaultSnapshotDir,
       defaultInstallLocation: s.defaultInstallLocation,
-      alertThresholds: s.alertThresholds,
       developerMode: s.developerMode,
       setMetricsInterval: s.setMetricsInterval,
       setProcessesInterval: s.setProcessesInterval,
       setDefaultSnapshotDir: s.setDefaultSnapshotDir,
       setDefaultInstallLocation: s.setDefaultInstallLocation,
-      setAlertThresholds: s.setAlertThresholds,
       setDeveloperMode: s.setDeveloperMode,
     })),
   );
 
-  // Push localStorage thresholds to backend on mount (localStorage is the source of truth)
+  // The global defaults stored in SQLite; edits keep a local copy so sliders
+  // don't snap back while the save is in flight, and send back every row
+  // loaded so the lifecycle fields and other alert types are kept
+  const { data: savedThresholds } = useAlertThresholds();
   const setBackendThresholds = useSetAlertThresholds();
-  const hasSynced = useRef(false);
-
-  useEffect(() => {
-    if (!hasSynced.current && alertThresholds.length > 0) {
-      hasSynced.current = true;
-      setBackendThresholds.mutate(alertThresholds);
-    }
-  }, [alertThresholds, setBackendThresholds]);
+  const [draftThresholds, setDraftThresholds] = useState<AlertThreshold[] | null>(null);
+  const alertThresholds = draftThresholds ?? savedThresholds ?? [];
+  const panelThresholds = alertThresholds.filter(
+    (t) => PANEL_ALERT_TYPES.includes(t.alert_type) && (t.unit ?? "percent") === "percent",
+  );
 
-  const updateThreshold = (alertType: string, updates: Partial<AlertThreshold>) => {
+  const updateThreshold = (alertType: AlertType, updates: Partial<AlertThreshold>) => {
     const next = alertThresholds.map((t) =>
       t.alert_type === alertType ? { ...t, ...updates } : t,
     );
-    setAlertThresholds(next);
-    setBackendThresholds.mutate(next);
+    setDraftThresholds(next);
+    setBackendThresholds.mutate({ thresholds: next });
   };
 
   return (
@@ -246,7 +248,7 @@ export function PreferencesPanel() {
         </div>
         <p className="text-subtext-0 mb-4 text-xs">{t("preferences.alertsDesc")}</p>
         <div className="space-y-4">
-          {alertThresholds.map((threshold) => {
+          {panelThresholds.map((threshold) => {
             const label =
               threshold.alert_type === "cpu"
                 ? t("preferences.alertCpu")
@@ -4,6 +4,12 @@ import { tauriInvoke } from "@/shared/api/tauri-client";
 import { useTauriMutation } from "@/shared/api/use-tauri-mutation";
 import { toast } from "@/shared/ui/toast-store";
 import { distroKeys } from "@/shared/api/distro-queries";
+import type {
+  Distro,
+  DistroTemplate,
+  ProvisionFromTemplateArgs,
+  SaveTemplateArgs,
+} from "@/shared/types/distro";
 
 export function useStartDistro() {
   return useTauriMutation<void, string>({
@@ -66,6 +72,35 @@ export function useDeleteDistro() {
   });
 }
 
+export function useSaveTemplate() {
+  return useTauriMutation<DistroTemplate, SaveTemplateArgs>({
+    mutationFn: (args) => tauriInvoke<DistroTemplate>("save_template", { args }),
+    invalidateKeys: [distroKeys.templates()],
+    successMessage: (_data, args) => `Template ${args.name} saved`,
+    errorMessage: (err) => `Failed to save template: ${err.message}`,
+  });
+}
+
+export function useDeleteTemplate() {
+  return useTauriMutation<void, string>({
+    mutationFn: (templateId) => tauriInvoke("delete_template", { templateId }),
+    invalidateKeys: [distroKeys.templates()],
+  });
+}
+
+/**
+ * Clone a template's snapshot into a new distro, apply its wsl.conf overlay and
+ * run its bootstrap script; progress arrives through `useProvisionOutput`.
+ */
+export function useProvisionFromTemplate() {
+  return useTauriMutation<Distro, ProvisionFromTemplateArgs>({
+    mutationFn: (args) => tauriInvoke<Distro>("provision_from_template", { args }),
+    invalidateKeys: [distroKeys.all],
+    successMessage: (_data, args) => `${args.distro_name} provisioned`,
+    errorMessage: (err, args) => `Failed to provision ${args.distro_name}: ${err.message}`,
+  });
+}
+
 export function useShutdownAll() {
   return useTauriMutation({
     mutationFn: () => tauriInvoke("shutdown_all"),
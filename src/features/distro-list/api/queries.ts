@@ -1 +1 @@
-export { distroKeys, useDistros } from "@/shared/api/distro-queries";
+export { distroKeys, useDistros, useTemplates } from "@/shared/api/distro-queries";
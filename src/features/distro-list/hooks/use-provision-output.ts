@@ -0,0 +1,37 @@
+import { useCallback, useState } from "react";
+import { useTauriEvent } from "@/shared/hooks/use-tauri-event";
+import { EVENTS } from "@/shared/api/events";
+import type { ProvisionOutput, ProvisionPhase } from "@/shared/types/distro";
+
+/**
+ * Phase and script output of a distro being provisioned from a template,
+ * collected from `provision-output` events.
+ */
+export function useProvisionOutput(distroName: string) {
+  const [output, setOutput] = useState<{
+    distroName: string;
+    phase: ProvisionPhase | null;
+    lines: string[];
+  }>({ distroName, phase: null, lines: [] });
+
+  const handler = useCallback(
+    (e: ProvisionOutput) => {
+      if (e.distro_name !== distroName) return;
+      setOutput((prev) => {
+        const lines = prev.distroName === distroName ? prev.lines : [];
+        return {
+          distroName,
+          phase: e.phase,
+          lines: e.line === null ? lines : [...lines, e.line],
+        };
+      });
+    },
+    [distroName],
+  );
+
+  useTauriEvent<ProvisionOutput>(EVENTS.PROVISION_OUTPUT, handler);
+
+  return output.distroName === distroName
+    ? { phase: output.phase, lines: output.lines }
+    : { phase: null, lines: [] as string[] };
+}
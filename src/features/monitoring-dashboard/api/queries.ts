@@ -2,7 +2,21 @@ import { useQuery } from "@tanstack/react-query";
 import { tauriInvoke } from "@/shared/api/tauri-client";
 import { useTauriMutation } from "@/shared/api/use-tauri-mutation";
 import { usePreferencesStore } from "@/shared/stores/use-preferences-store";
-import type { TimeRange, MetricsHistoryResponse, AlertThreshold } from "@/shared/types/monitoring";
+import type {
+  TimeRange,
+  MetricsHistoryResponse,
+  MetricsSettings,
+  TopProcessesResponse,
+  ServiceUsageResponse,
+  ExportMetricsArgs,
+  MetricsExportResponse,
+  AlertThreshold,
+  NotificationChannel,
+  NotificationDelivery,
+  AlertSilence,
+  CreateAlertSilenceArgs,
+  MaintenanceWindow,
+} from "@/shared/types/monitoring";
 
 export interface ProcessInfo {
   pid: number;
@@ -13,6 +27,7 @@ export interface ProcessInfo {
   rss_bytes: number;
   command: string;
   state: string;
+  cpu_time_secs: number;
 }
 
 export const monitoringKeys = {
@@ -21,8 +36,20 @@ export const monitoringKeys = {
   processes: (distro: string) => [...monitoringKeys.all, "processes", distro] as const,
   history: (distro: string, range: string) =>
     [...monitoringKeys.all, "history", distro, range] as const,
-  alertThresholds: () => [...monitoringKeys.all, "alertThresholds"] as const,
+  metricsSettings: () => [...monitoringKeys.all, "metricsSettings"] as const,
+  topProcesses: (distro: string, at: string) =>
+    [...monitoringKeys.all, "topProcesses", distro, at] as const,
+  serviceUsage: (distro: string, from: string, to: string) =>
+    [...monitoringKeys.all, "serviceUsage", distro, from, to] as const,
+  alertThresholds: (distroName?: string) =>
+    distroName
+      ? ([...monitoringKeys.all, "alertThresholds", distroName] as const)
+      : ([...monitoringKeys.all, "alertThresholds"] as const),
   alerts: (distro: string) => [...monitoringKeys.all, "alerts", distro] as const,
+  notificationChannels: () => [...monitoringKeys.all, "notificationChannels"] as const,
+  notificationDeliveries: () => [...monitoringKeys.all, "notificationDeliveries"] as const,
+  silences: () => [...monitoringKeys.all, "silences"] as const,
+  maintenanceWindows: () => [...monitoringKeys.all, "maintenanceWindows"] as const,
 };
 
 export function useProcesses(distroName: string | null, enabled = true) {
@@ -48,6 +75,10 @@ function getTimeRangeMs(range: TimeRange): number {
       return 6 * 60 * 60 * 1000;
     case "24h":
       return 24 * 60 * 60 * 1000;
+    case "7d":
+      return 7 * 24 * 60 * 60 * 1000;
+    case "30d":
+      return 30 * 24 * 60 * 60 * 1000;
     default:
       return 60 * 60 * 1000;
   }
@@ -66,22 +97,181 @@ export function useMetricsHistory(distroName: string | null, timeRange: TimeRang
       });
     },
     enabled: !!distroName && timeRange !== "live",
-    refetchInterval: timeRange === "1h" ? 30_000 : 60_000,
+    refetchInterval:
+      timeRange === "1h" ? 30_000 : timeRange === "6h" || timeRange === "24h" ? 60_000 : 300_000,
+  });
+}
+
+// --- Metrics settings ---
+
+export function useMetricsSettings() {
+  return useQuery({
+    queryKey: monitoringKeys.metricsSettings(),
+    queryFn: () => tauriInvoke<MetricsSettings>("get_metrics_settings"),
+  });
+}
+
+/** Collection interval and tier retention; applied from the next collection pass. */
+export function useSetMetricsSettings() {
+  return useTauriMutation<void, MetricsSettings>({
+    mutationFn: (settings) => tauriInvoke("set_metrics_settings", { settings }),
+    invalidateKeys: [monitoringKeys.metricsSettings()],
+  });
+}
+
+/**
+ * Processes that used the most CPU around `at` (an ISO timestamp, e.g. the
+ * time of a spike on a chart or of a CPU alert).
+ */
+export function useTopProcesses(distroName: string | null, at: string | null, windowSecs?: number) {
+  return useQuery({
+    queryKey: monitoringKeys.topProcesses(distroName ?? "", at ?? ""),
+    queryFn: () =>
+      tauriInvoke<TopProcessesResponse>("get_top_processes", {
+        distroName: distroName!,
+        at: at!,
+        windowSecs,
+      }),
+    enabled: !!distroName && !!at,
+  });
+}
+
+/**
+ * CPU, memory and I/O of each systemd unit of a distro booted with systemd
+ * between two ISO timestamps, by peak memory.
+ */
+export function useServiceUsage(distroName: string | null, from: string | null, to: string | null) {
+  return useQuery({
+    queryKey: monitoringKeys.serviceUsage(distroName ?? "", from ?? "", to ?? ""),
+    queryFn: () =>
+      tauriInvoke<ServiceUsageResponse>("get_service_usage", {
+        distroName: distroName!,
+        from: from!,
+        to: to!,
+      }),
+    enabled: !!distroName && !!from && !!to,
+  });
+}
+
+// --- Metrics export ---
+
+/**
+ * Write the history of one or more distros to a CSV, JSON Lines or Arrow IPC
+ * file, e.g. to attach to a performance ticket instead of a chart screenshot.
+ */
+export function useExportMetrics() {
+  return useTauriMutation<MetricsExportResponse, ExportMetricsArgs>({
+    mutationFn: (args) => tauriInvoke("export_metrics", { args }),
   });
 }
 
 // --- Alert thresholds ---
 
-export function useAlertThresholds() {
+/**
+ * Global alert thresholds, or with `distroName` the ones in force for that
+ * distro (its overrides carry `distro_name`, inherited defaults don't).
+ */
+export function useAlertThresholds(distroName?: string) {
   return useQuery({
-    queryKey: monitoringKeys.alertThresholds(),
-    queryFn: () => tauriInvoke<AlertThreshold[]>("get_alert_thresholds", {}),
+    queryKey: monitoringKeys.alertThresholds(distroName),
+    queryFn: () => tauriInvoke<AlertThreshold[]>("get_alert_thresholds", { distroName }),
   });
 }
 
+/**
+ * Save global defaults (only the alert types given change), or with `distroName`
+ * replace that distro's overrides ([] clears them).
+ */
 export function useSetAlertThresholds() {
-  return useTauriMutation<void, AlertThreshold[]>({
-    mutationFn: (thresholds) => tauriInvoke("set_alert_thresholds", { thresholds }),
+  return useTauriMutation<void, { thresholds: AlertThreshold[]; distroName?: string }>({
+    mutationFn: ({ thresholds, distroName }) =>
+      tauriInvoke("set_alert_thresholds", { thresholds, distroName }),
     invalidateKeys: [monitoringKeys.alertThresholds()],
   });
 }
+
+// --- Notification channels ---
+
+export function useNotificationChannels() {
+  return useQuery({
+    queryKey: monitoringKeys.notificationChannels(),
+    queryFn: () => tauriInvoke<NotificationChannel[]>("list_notification_channels"),
+  });
+}
+
+/** Create (empty `id`) or update a channel. */
+export function useSaveNotificationChannel() {
+  return useTauriMutation<NotificationChannel, NotificationChannel>({
+    mutationFn: (channel) => tauriInvoke("save_notification_channel", { channel }),
+    invalidateKeys: [monitoringKeys.notificationChannels()],
+  });
+}
+
+export function useDeleteNotificationChannel() {
+  return useTauriMutation<void, string>({
+    mutationFn: (channelId) => tauriInvoke("delete_notification_channel", { channelId }),
+    invalidateKeys: [monitoringKeys.notificationChannels()],
+  });
+}
+
+/** Send a test notification through a channel; resolves with the delivery. */
+export function useTestNotificationChannel() {
+  return useTauriMutation<NotificationDelivery, string>({
+    mutationFn: (channelId) => tauriInvoke("test_notification_channel", { channelId }),
+    invalidateKeys: [monitoringKeys.notificationDeliveries()],
+  });
+}
+
+export function useNotificationDeliveries(limit?: number) {
+  return useQuery({
+    queryKey: monitoringKeys.notificationDeliveries(),
+    queryFn: () => tauriInvoke<NotificationDelivery[]>("list_notification_deliveries", { limit }),
+  });
+}
+
+// --- Silences and maintenance windows ---
+
+export function useAlertSilences() {
+  return useQuery({
+    queryKey: monitoringKeys.silences(),
+    queryFn: () => tauriInvoke<AlertSilence[]>("list_alert_silences"),
+  });
+}
+
+export function useCreateAlertSilence() {
+  return useTauriMutation<AlertSilence, CreateAlertSilenceArgs>({
+    mutationFn: (args) => tauriInvoke("create_alert_silence", { args }),
+    invalidateKeys: [monitoringKeys.silences()],
+    successMessage: (_data, args) => `Alerts silenced for ${args.duration_minutes} min`,
+  });
+}
+
+/** End a silence early. */
+export function useExpireAlertSilence() {
+  return useTauriMutation<void, string>({
+    mutationFn: (silenceId) => tauriInvoke("expire_alert_silence", { silenceId }),
+    invalidateKeys: [monitoringKeys.silences()],
+  });
+}
+
+export function useMaintenanceWindows() {
+  return useQuery({
+    queryKey: monitoringKeys.maintenanceWindows(),
+    queryFn: () => tauriInvoke<MaintenanceWindow[]>("list_maintenance_windows"),
+  });
+}
+
+/** Create (empty `id`) or update a maintenance window. */
+export function useSaveMaintenanceWindow() {
+  return useTauriMutation<MaintenanceWindow, MaintenanceWindow>({
+    mutationFn: (window) => tauriInvoke("save_maintenance_window", { window }),
+    invalidateKeys: [monitoringKeys.maintenanceWindows()],
+  });
+}
+
+export function useDeleteMaintenanceWindow() {
+  return useTauriMutation<void, string>({
+    mutationFn: (windowId) => tauriInvoke("delete_maintenance_window", { windowId }),
+    invalidateKeys: [monitoringKeys.maintenanceWindows()],
+  });
+}
@@ -2,10 +2,12 @@ import { useState, useCallback } from "react";
 import { useTauriEvent } from "@/shared/hooks/use-tauri-event";
 
 interface AlertEvent {
+  alert_id: number | null;
   distro_name: string;
   alert_type: string;
+  unit: "percent" | "absolute";
   threshold: number;
-  actual_value: number;
+  actual_value: number | null;
 }
 
 /**
@@ -4,12 +4,14 @@ import { renderWithProviders, createWrapper } from "@/test/test-utils";
 import { TimeRangePicker } from "./time-range-picker";
 
 describe("TimeRangePicker", () => {
-  it("renders all four time range options", () => {
+  it("renders all six time range options", () => {
     renderWithProviders(<TimeRangePicker value="live" onChange={vi.fn()} />);
     expect(screen.getByText("Live")).toBeInTheDocument();
     expect(screen.getByText("1h")).toBeInTheDocument();
     expect(screen.getByText("6h")).toBeInTheDocument();
     expect(screen.getByText("24h")).toBeInTheDocument();
+    expect(screen.getByText("7d")).toBeInTheDocument();
+    expect(screen.getByText("30d")).toBeInTheDocument();
   });
 
   it("calls onChange with the clicked option value", () => {
@@ -24,6 +26,9 @@ describe("TimeRangePicker", () => {
 
     fireEvent.click(screen.getByText("24h"));
     expect(onChange).toHaveBeenCalledWith("24h");
+
+    fireEvent.click(screen.getByText("30d"));
+    expect(onChange).toHaveBeenCalledWith("30d");
   });
 
   it("shows pulse indicator only when live is selected", () => {
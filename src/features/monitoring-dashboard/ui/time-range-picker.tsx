@@ -16,6 +16,8 @@ export function TimeRangePicker({ value, onChange }: TimeRangePickerProps) {
       { value: "1h", label: t("monitoring.timeRange1h") },
       { value: "6h", label: t("monitoring.timeRange6h") },
       { value: "24h", label: t("monitoring.timeRange24h") },
+      { value: "7d", label: t("monitoring.timeRange7d") },
+      { value: "30d", label: t("monitoring.timeRange30d") },
     ],
     [t],
   );
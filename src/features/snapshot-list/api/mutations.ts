@@ -2,7 +2,22 @@ import { tauriInvoke } from "@/shared/api/tauri-client";
 import { useTauriMutation } from "@/shared/api/use-tauri-mutation";
 import { snapshotKeys } from "./queries";
 import { distroKeys } from "@/shared/api/distro-queries";
-import type { Snapshot, CreateSnapshotArgs, RestoreSnapshotArgs } from "@/shared/types/snapshot";
+import type {
+  Snapshot,
+  CreateSnapshotArgs,
+  RestoreSnapshotArgs,
+  VerifySnapshotResult,
+  RescanReport,
+  SnapshotSchedule,
+  SaveScheduleArgs,
+  UpdateSnapshotMetadataArgs,
+  RekeySnapshotArgs,
+  ReplicationTarget,
+  SetReplicationTargetArgs,
+  SnapshotReplication,
+  SafetyPolicy,
+  SaveSafetyPolicyArgs,
+} from "@/shared/types/snapshot";
 
 export function useCreateSnapshot() {
   return useTauriMutation<Snapshot, CreateSnapshotArgs>({
@@ -20,6 +35,23 @@ export function useDeleteSnapshot() {
   });
 }
 
+export function useUpdateSnapshotMetadata() {
+  return useTauriMutation<Snapshot, UpdateSnapshotMetadataArgs>({
+    mutationFn: (args) => tauriInvoke<Snapshot>("update_snapshot_metadata", { args }),
+    invalidateKeys: [snapshotKeys.all],
+    errorMessage: (err) => `Failed to update snapshot: ${err.message}`,
+  });
+}
+
+export function useRekeySnapshot() {
+  return useTauriMutation<Snapshot, RekeySnapshotArgs>({
+    mutationFn: (args) => tauriInvoke<Snapshot>("rekey_snapshot", { args }),
+    invalidateKeys: [snapshotKeys.all],
+    successMessage: "Snapshot encryption updated",
+    errorMessage: (err) => `Rekey failed: ${err.message}`,
+  });
+}
+
 export function useRestoreSnapshot() {
   return useTauriMutation<void, RestoreSnapshotArgs>({
     mutationFn: (args) => tauriInvoke("restore_snapshot", { args }),
@@ -28,3 +60,100 @@ export function useRestoreSnapshot() {
     errorMessage: (err) => `Restore failed: ${err.message}`,
   });
 }
+
+export function useVerifySnapshot() {
+  return useTauriMutation<VerifySnapshotResult, string>({
+    mutationFn: (snapshotId) =>
+      tauriInvoke<VerifySnapshotResult>("verify_snapshot", { snapshotId }),
+    invalidateKeys: [snapshotKeys.all],
+    errorMessage: (err) => `Verification failed: ${err.message}`,
+  });
+}
+
+export function useVerifyAllSnapshots() {
+  return useTauriMutation<void, void>({
+    mutationFn: () => tauriInvoke("verify_all_snapshots"),
+    successMessage: "Snapshot verification started",
+  });
+}
+
+export function useRescanSnapshots() {
+  return useTauriMutation<RescanReport, string[] | undefined>({
+    mutationFn: (directories) => tauriInvoke<RescanReport>("rescan_snapshots", { directories }),
+    invalidateKeys: [snapshotKeys.all],
+    successMessage: (report) =>
+      `Rescan: ${report.imported.length} imported, ${report.missing.length} missing, ${report.orphan_files.length} orphan file(s)`,
+    errorMessage: (err) => `Rescan failed: ${err.message}`,
+  });
+}
+
+export function useCancelSnapshot() {
+  return useTauriMutation<void, string>({
+    mutationFn: (snapshotId) => tauriInvoke("cancel_snapshot", { snapshotId }),
+    invalidateKeys: [snapshotKeys.all],
+    successMessage: "Snapshot cancelled",
+    errorMessage: (err) => `Failed to cancel snapshot: ${err.message}`,
+  });
+}
+
+export function useSaveSnapshotSchedule() {
+  return useTauriMutation<SnapshotSchedule, SaveScheduleArgs>({
+    mutationFn: (args) => tauriInvoke<SnapshotSchedule>("save_snapshot_schedule", { args }),
+    invalidateKeys: [snapshotKeys.all],
+    successMessage: "Snapshot schedule saved",
+    errorMessage: (err) => `Failed to save schedule: ${err.message}`,
+  });
+}
+
+export function useDeleteSnapshotSchedule() {
+  return useTauriMutation<void, string>({
+    mutationFn: (scheduleId) => tauriInvoke("delete_snapshot_schedule", { scheduleId }),
+    invalidateKeys: [snapshotKeys.all],
+  });
+}
+
+export function useRunSnapshotSchedule() {
+  return useTauriMutation<Snapshot, string>({
+    mutationFn: (scheduleId) => tauriInvoke<Snapshot>("run_snapshot_schedule", { scheduleId }),
+    invalidateKeys: [snapshotKeys.all],
+    errorMessage: (err) => `Scheduled snapshot failed: ${err.message}`,
+  });
+}
+
+export function useSaveSafetyPolicy() {
+  return useTauriMutation<SafetyPolicy, SaveSafetyPolicyArgs>({
+    mutationFn: (args) => tauriInvoke<SafetyPolicy>("save_safety_policy", { args }),
+    invalidateKeys: [snapshotKeys.all],
+    successMessage: (_data, args) => `Safety snapshots updated for ${args.distro_name}`,
+    errorMessage: (err) => `Failed to save safety policy: ${err.message}`,
+  });
+}
+
+/** Turn safety snapshots off for a distro; existing ones are kept. */
+export function useDeleteSafetyPolicy() {
+  return useTauriMutation<void, string>({
+    mutationFn: (distroName) => tauriInvoke("delete_safety_policy", { distroName }),
+    invalidateKeys: [snapshotKeys.all],
+  });
+}
+
+/** Save the replication target; `null` turns replication off. */
+export function useSetReplicationTarget() {
+  return useTauriMutation<ReplicationTarget | null, SetReplicationTargetArgs | null>({
+    mutationFn: (args) =>
+      tauriInvoke<ReplicationTarget | null>("set_replication_target", { args }),
+    invalidateKeys: [snapshotKeys.all],
+    successMessage: "Replication settings saved",
+    errorMessage: (err) => `Failed to save replication settings: ${err.message}`,
+  });
+}
+
+export function useReplicateSnapshot() {
+  return useTauriMutation<SnapshotReplication, string>({
+    mutationFn: (snapshotId) =>
+      tauriInvoke<SnapshotReplication>("replicate_snapshot", { snapshotId }),
+    invalidateKeys: [snapshotKeys.all],
+    successMessage: "Snapshot replicated",
+    errorMessage: (err) => `Replication failed: ${err.message}`,
+  });
+}
@@ -1,23 +1,141 @@
 import { useMemo } from "react";
 import { useQuery } from "@tanstack/react-query";
 import { tauriInvoke } from "@/shared/api/tauri-client";
-import type { Snapshot } from "@/shared/types/snapshot";
+import type {
+  ReplicationTarget,
+  RestorePreflight,
+  RestoreSnapshotArgs,
+  SafetyPolicy,
+  Snapshot,
+  SnapshotFilter,
+  SnapshotDiff,
+  SnapshotReplication,
+  SnapshotSchedule,
+  SnapshotSecret,
+  SnapshotTreeNode,
+} from "@/shared/types/snapshot";
 
 export const snapshotKeys = {
   all: ["snapshots"] as const,
-  list: (distroName?: string) => [...snapshotKeys.all, "list", distroName ?? "all"] as const,
+  list: (distroName?: string, filter?: SnapshotFilter) =>
+    filter
+      ? ([...snapshotKeys.all, "list", distroName ?? "all", filter] as const)
+      : ([...snapshotKeys.all, "list", distroName ?? "all"] as const),
+  tags: () => [...snapshotKeys.all, "tags"] as const,
+  schedules: (distroName?: string) =>
+    [...snapshotKeys.all, "schedules", distroName ?? "all"] as const,
+  diff: (fromId: string, toId: string) => [...snapshotKeys.all, "diff", fromId, toId] as const,
+  contents: (snapshotId: string, path: string) =>
+    [...snapshotKeys.all, "contents", snapshotId, path] as const,
+  preflight: (args: Omit<RestoreSnapshotArgs, "passphrase" | "keyfile">) =>
+    [...snapshotKeys.all, "preflight", args] as const,
+  replicationTarget: () => [...snapshotKeys.all, "replication-target"] as const,
+  replications: () => [...snapshotKeys.all, "replications"] as const,
+  safetyPolicies: () => [...snapshotKeys.all, "safety-policies"] as const,
 };
 
-export function useSnapshots(distroName?: string) {
+export function useSnapshots(distroName?: string, filter?: SnapshotFilter) {
   return useQuery({
-    queryKey: snapshotKeys.list(distroName),
+    queryKey: snapshotKeys.list(distroName, filter),
     queryFn: () =>
       tauriInvoke<Snapshot[]>("list_snapshots", {
         distroName: distroName ?? null,
+        ...(filter ? { filter } : {}),
       }),
   });
 }
 
+/** Every tag in use, for filter suggestions. */
+export function useSnapshotTags() {
+  return useQuery({
+    queryKey: snapshotKeys.tags(),
+    queryFn: () => tauriInvoke<string[]>("list_snapshot_tags"),
+  });
+}
+
+export function useSnapshotSchedules(distroName?: string) {
+  return useQuery({
+    queryKey: snapshotKeys.schedules(distroName),
+    queryFn: () =>
+      tauriInvoke<SnapshotSchedule[]>("list_snapshot_schedules", {
+        distroName: distroName ?? null,
+      }),
+  });
+}
+
+/** Offsite replication target; null when replication is off. */
+export function useReplicationTarget() {
+  return useQuery({
+    queryKey: snapshotKeys.replicationTarget(),
+    queryFn: () => tauriInvoke<ReplicationTarget | null>("get_replication_target"),
+  });
+}
+
+/** Replication state of every snapshot that was (or is being) uploaded. */
+export function useReplications() {
+  return useQuery({
+    queryKey: snapshotKeys.replications(),
+    queryFn: () => tauriInvoke<SnapshotReplication[]>("list_replications"),
+  });
+}
+
+/** Safety snapshot policies of every distro that has one. */
+export function useSafetyPolicies() {
+  return useQuery({
+    queryKey: snapshotKeys.safetyPolicies(),
+    queryFn: () => tauriInvoke<SafetyPolicy[]>("list_safety_policies"),
+  });
+}
+
+/**
+ * Filesystem diff between two snapshots; disabled until both are picked.
+ * `secret` unlocks encrypted snapshots and is kept out of the query key.
+ */
+export function useSnapshotDiff(fromId?: string, toId?: string, secret?: SnapshotSecret) {
+  return useQuery({
+    queryKey: snapshotKeys.diff(fromId ?? "", toId ?? ""),
+    queryFn: () => tauriInvoke<SnapshotDiff>("diff_snapshots", { fromId, toId, ...secret }),
+    enabled: !!fromId && !!toId && fromId !== toId,
+    // Snapshots are immutable: a diff never goes stale.
+    staleTime: Infinity,
+  });
+}
+
+/**
+ * Dry run of a restore with `args`; disabled until a snapshot is picked.
+ * The passphrase or keyfile is kept out of the query key.
+ */
+export function useRestorePreflight(args?: RestoreSnapshotArgs) {
+  const {
+    passphrase: _passphrase,
+    keyfile: _keyfile,
+    ...keyArgs
+  } = args ?? { snapshot_id: "", mode: "overwrite" as const };
+  return useQuery({
+    queryKey: snapshotKeys.preflight(keyArgs),
+    queryFn: () => tauriInvoke<RestorePreflight>("preflight_restore", { args }),
+    enabled: !!args?.snapshot_id,
+    // Free space and running distros change: always re-check.
+    staleTime: 0,
+  });
+}
+
+/** One level of a snapshot's file tree below `path`; expand by querying a child path. */
+export function useSnapshotContents(snapshotId?: string, path = "/", secret?: SnapshotSecret) {
+  return useQuery({
+    queryKey: snapshotKeys.contents(snapshotId ?? "", path),
+    queryFn: () =>
+      tauriInvoke<SnapshotTreeNode>("list_snapshot_contents", {
+        snapshotId,
+        path,
+        depth: 1,
+        ...secret,
+      }),
+    enabled: !!snapshotId,
+    staleTime: Infinity,
+  });
+}
+
 export function useSnapshotCounts(): Record<string, number> {
   const { data: snapshots } = useSnapshots();
   return useMemo(() => {
@@ -0,0 +1,28 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import type { SnapshotProgress } from "@/shared/types/snapshot";
+
+/**
+ * Latest `snapshot-progress` sample for a running export or restore,
+ * or null until the first sample arrives.
+ */
+export function useSnapshotProgress(snapshotId: string): SnapshotProgress | null {
+  const [progress, setProgress] = useState<SnapshotProgress | null>(null);
+
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+    listen<SnapshotProgress>("snapshot-progress", (e) => {
+      if (e.payload.snapshot_id === snapshotId) setProgress(e.payload);
+    }).then((fn) => {
+      if (cancelled) fn();
+      else unlisten = fn;
+    });
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [snapshotId]);
+
+  return progress;
+}
@@ -31,7 +31,7 @@ export function CreateSnapshotDialog({ open, onClose, defaultDistro }: CreateSna
   }
 
   const [description, setDescription] = useState("");
-  const [format, setFormat] = useState<"tar" | "vhdx">("tar");
+  const [format, setFormat] = useState<"tar" | "tar.zst" | "tar.gz" | "vhdx">("tar");
   const [outputDir, setOutputDir] = useState(defaultSnapshotDir);
 
   const handleSubmit = (e: React.FormEvent) => {
@@ -137,6 +137,8 @@ export function CreateSnapshotDialog({ open, onClose, defaultDistro }: CreateSna
               onChange={(v) => setFormat(v as typeof format)}
               options={[
                 { value: "tar", label: t("snapshots.create.formatTar") },
+                { value: "tar.zst", label: t("snapshots.create.formatTarZst") },
+                { value: "tar.gz", label: t("snapshots.create.formatTarGz") },
                 { value: "vhdx", label: t("snapshots.create.formatVhdx") },
               ]}
               placeholder=""
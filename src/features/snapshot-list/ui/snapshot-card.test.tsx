@@ -17,6 +17,9 @@ function makeSnapshot(overrides: Partial<Snapshot> = {}): Snapshot {
     parent_id: null,
     created_at: new Date().toISOString(),
     status: "completed",
+    tags: [],
+    pinned: false,
+    encryption: null,
     ...overrides,
   };
 }
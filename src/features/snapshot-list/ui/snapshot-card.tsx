@@ -1,6 +1,6 @@
 import { memo } from "react";
 import { useTranslation } from "react-i18next";
-import { Trash2, RotateCw, Archive, Clock, HardDrive, Loader2 } from "lucide-react";
+import { Trash2, RotateCw, Archive, Clock, HardDrive, Loader2, Pin, Lock } from "lucide-react";
 import type { Snapshot } from "@/shared/types/snapshot";
 import { formatBytes, formatRelativeTime } from "@/shared/lib/formatters";
 import { cn } from "@/shared/lib/utils";
@@ -35,6 +35,16 @@ export const SnapshotCard = memo(function SnapshotCard({
               <Archive className="text-mauve h-4 w-4 shrink-0" />
             )}
             <h4 className="text-text truncate font-semibold">{snapshot.name}</h4>
+            {snapshot.pinned && (
+              <Tooltip content={t("snapshots.pinned")}>
+                <Pin className="text-peach h-3.5 w-3.5 shrink-0" data-testid="snapshot-pinned" />
+              </Tooltip>
+            )}
+            {snapshot.encryption && (
+              <Tooltip content={t("snapshots.encrypted", { source: snapshot.encryption })}>
+                <Lock className="text-green h-3.5 w-3.5 shrink-0" data-testid="snapshot-encrypted" />
+              </Tooltip>
+            )}
           </div>
           {snapshot.description && (
             <p className="text-subtext-0 mt-1 line-clamp-2 text-sm">{snapshot.description}</p>
@@ -70,6 +80,11 @@ export const SnapshotCard = memo(function SnapshotCard({
         {snapshot.file_size_bytes > 0 && <span>{formatBytes(snapshot.file_size_bytes)}</span>}
         <span className="rounded-md bg-white/5 px-1.5 py-0.5">{snapshot.format}</span>
         <span className="rounded-md bg-white/5 px-1.5 py-0.5">{snapshot.snapshot_type}</span>
+        {snapshot.tags.map((tag) => (
+          <span key={tag} className="bg-mauve/15 text-mauve rounded-md px-1.5 py-0.5">
+            #{tag}
+          </span>
+        ))}
       </div>
 
       <div className="mt-3 flex justify-end gap-1">
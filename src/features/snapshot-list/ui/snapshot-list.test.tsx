@@ -27,6 +27,9 @@ const mockSnapshots: Snapshot[] = [
     parent_id: null,
     created_at: "2024-01-01T00:00:00Z",
     status: "completed",
+    tags: [],
+    pinned: false,
+    encryption: null,
   },
   {
     id: "2",
@@ -40,6 +43,9 @@ const mockSnapshots: Snapshot[] = [
     parent_id: null,
     created_at: "2024-01-02T00:00:00Z",
     status: "completed",
+    tags: [],
+    pinned: false,
+    encryption: null,
   },
 ];
 
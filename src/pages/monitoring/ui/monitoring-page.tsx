@@ -74,7 +74,10 @@ export function MonitoringPage() {
   const historicalChartData: MetricsPoint[] = useMemo(() => {
     if (timeRange === "live" || !historyData?.points) return [];
     return historyData.points.map((p: MetricsHistoryPoint) => ({
-      time: new Date(p.timestamp).toLocaleTimeString(),
+      time:
+        timeRange === "7d" || timeRange === "30d"
+          ? new Date(p.timestamp).toLocaleString()
+          : new Date(p.timestamp).toLocaleTimeString(),
       cpu: p.cpu_avg,
       memUsed: p.mem_used_bytes,
       memTotal: p.mem_total_bytes,
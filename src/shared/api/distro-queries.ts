@@ -1,10 +1,11 @@
 import { useQuery } from "@tanstack/react-query";
 import { tauriInvoke } from "@/shared/api/tauri-client";
-import type { Distro } from "@/shared/types/distro";
+import type { Distro, DistroTemplate } from "@/shared/types/distro";
 
 export const distroKeys = {
   all: ["distros"] as const,
   list: () => [...distroKeys.all, "list"] as const,
+  templates: () => [...distroKeys.all, "templates"] as const,
 };
 
 export function useDistros(fastPolling = false) {
@@ -14,3 +15,11 @@ export function useDistros(fastPolling = false) {
     refetchInterval: fastPolling ? 2_000 : 10_000,
   });
 }
+
+/** Distro templates, sorted by name. */
+export function useTemplates() {
+  return useQuery({
+    queryKey: distroKeys.templates(),
+    queryFn: () => tauriInvoke<DistroTemplate[]>("list_templates"),
+  });
+}
@@ -1,3 +1,4 @@
 export const EVENTS = {
   DISTRO_STATE_CHANGED: "distro-state-changed",
+  PROVISION_OUTPUT: "provision-output",
 } as const;
@@ -1,6 +1,5 @@
 import { describe, it, expect, beforeEach } from "vitest";
 import { usePreferencesStore } from "./use-preferences-store";
-import type { AlertThreshold } from "@/shared/types/monitoring";
 
 describe("usePreferencesStore", () => {
   beforeEach(() => {
@@ -13,11 +12,6 @@ describe("usePreferencesStore", () => {
       defaultInstallLocation: "",
       sortKey: "name-asc",
       viewMode: "grid",
-      alertThresholds: [
-        { alert_type: "cpu", threshold_percent: 90, enabled: false },
-        { alert_type: "memory", threshold_percent: 85, enabled: false },
-        { alert_type: "disk", threshold_percent: 90, enabled: false },
-      ],
     });
   });
 
@@ -45,18 +39,6 @@ describe("usePreferencesStore", () => {
     it("defaults to grid view", () => {
       expect(usePreferencesStore.getState().viewMode).toBe("grid");
     });
-
-    it("has 3 default alert thresholds", () => {
-      const thresholds = usePreferencesStore.getState().alertThresholds;
-      expect(thresholds).toHaveLength(3);
-      expect(thresholds[0]).toEqual({ alert_type: "cpu", threshold_percent: 90, enabled: false });
-      expect(thresholds[1]).toEqual({
-        alert_type: "memory",
-        threshold_percent: 85,
-        enabled: false,
-      });
-      expect(thresholds[2]).toEqual({ alert_type: "disk", threshold_percent: 90, enabled: false });
-    });
   });
 
   describe("setters", () => {
@@ -91,14 +73,6 @@ describe("usePreferencesStore", () => {
       usePreferencesStore.getState().setViewMode("grid");
       expect(usePreferencesStore.getState().viewMode).toBe("grid");
     });
-
-    it("setAlertThresholds replaces all thresholds", () => {
-      const newThresholds: AlertThreshold[] = [
-        { alert_type: "cpu", threshold_percent: 95, enabled: false },
-      ];
-      usePreferencesStore.getState().setAlertThresholds(newThresholds);
-      expect(usePreferencesStore.getState().alertThresholds).toEqual(newThresholds);
-    });
   });
 
   describe("state independence", () => {
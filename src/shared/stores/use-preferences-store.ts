@@ -1,6 +1,5 @@
 import { create } from "zustand";
 import { persist } from "zustand/middleware";
-import type { AlertThreshold } from "@/shared/types/monitoring";
 
 export type SortKey =
   | "name-asc"
@@ -19,7 +18,6 @@ interface PreferencesStore {
   defaultInstallLocation: string;
   sortKey: SortKey;
   viewMode: ViewMode;
-  alertThresholds: AlertThreshold[];
   developerMode: boolean;
   setMetricsInterval: (ms: number) => void;
   setProcessesInterval: (ms: number) => void;
@@ -27,7 +25,6 @@ interface PreferencesStore {
   setDefaultInstallLocation: (path: string) => void;
   setSortKey: (key: SortKey) => void;
   setViewMode: (mode: ViewMode) => void;
-  setAlertThresholds: (thresholds: AlertThreshold[]) => void;
   setDeveloperMode: (enabled: boolean) => void;
 }
 
@@ -41,18 +38,12 @@ export const usePreferencesStore = create<PreferencesStore>()(
       sortKey: "name-asc",
       viewMode: "grid",
       developerMode: false,
-      alertThresholds: [
-        { alert_type: "cpu", threshold_percent: 90, enabled: false },
-        { alert_type: "memory", threshold_percent: 85, enabled: false },
-        { alert_type: "disk", threshold_percent: 90, enabled: false },
-      ],
       setMetricsInterval: (ms) => set({ metricsInterval: ms }),
       setProcessesInterval: (ms) => set({ processesInterval: ms }),
       setDefaultSnapshotDir: (path) => set({ defaultSnapshotDir: path }),
       setDefaultInstallLocation: (path) => set({ defaultInstallLocation: path }),
       setSortKey: (key) => set({ sortKey: key }),
       setViewMode: (mode) => set({ viewMode: mode }),
-      setAlertThresholds: (thresholds) => set({ alertThresholds: thresholds }),
       setDeveloperMode: (enabled) => set({ developerMode: enabled }),
     }),
     { name: "wsl-nexus-preferences" },
@@ -9,3 +9,73 @@ export interface Distro {
 }
 
 export type DistroState = "Running" | "Stopped" | "Installing" | "Converting" | "Uninstalling";
+
+/** Fields written over a distro's `/etc/wsl.conf`; unset ones are left alone. */
+export interface WslDistroConfig {
+  automount_enabled?: boolean | null;
+  automount_root?: string | null;
+  network_hostname?: string | null;
+  network_generate_hosts?: boolean | null;
+  network_generate_resolv_conf?: boolean | null;
+  interop_enabled?: boolean | null;
+  interop_append_windows_path?: boolean | null;
+  user_default?: string | null;
+  boot_systemd?: boolean | null;
+  boot_command?: string | null;
+  gpu_enabled?: boolean | null;
+  use_windows_timezone?: boolean | null;
+}
+
+export interface TemplateParameter {
+  name: string;
+  description: string | null;
+  default: string | null;
+  required: boolean;
+}
+
+/**
+ * Snapshot new distros are cloned from, plus a bootstrap script and wsl.conf
+ * overlay. Both may use `{{param}}` placeholders, including `{{distro_name}}`.
+ */
+export interface DistroTemplate {
+  id: string;
+  name: string;
+  description: string | null;
+  snapshot_id: string;
+  script: string;
+  parameters: TemplateParameter[];
+  wsl_config: WslDistroConfig;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SaveTemplateArgs {
+  /** Omit to create a new template. */
+  id?: string;
+  name: string;
+  description?: string;
+  snapshot_id: string;
+  script?: string;
+  parameters?: TemplateParameter[];
+  wsl_config?: WslDistroConfig;
+}
+
+export interface ProvisionFromTemplateArgs {
+  template_id: string;
+  distro_name: string;
+  install_location: string;
+  parameters?: Record<string, string>;
+  /** Unlocks an encrypted template snapshot. */
+  passphrase?: string;
+  keyfile?: string;
+}
+
+export type ProvisionPhase = "clone" | "configure" | "script" | "done";
+
+/** Payload of the `provision-output` event; `line` is set for script output. */
+export interface ProvisionOutput {
+  template_id: string;
+  distro_name: string;
+  phase: ProvisionPhase;
+  line: string | null;
+}
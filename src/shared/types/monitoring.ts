@@ -64,7 +64,7 @@ export interface InterfaceStats {
 
 // --- Historical metrics types ---
 
-export type TimeRange = "live" | "1h" | "6h" | "24h";
+export type TimeRange = "live" | "1h" | "6h" | "24h" | "7d" | "30d";
 
 export interface MetricsHistoryPoint {
   timestamp: string;
@@ -91,18 +91,170 @@ export interface MetricsHistoryPoint {
 
 export interface MetricsHistoryResponse {
   distro_name: string;
-  granularity: "raw" | "1m";
+  granularity: "raw" | "1m" | "1h" | "1d";
   points: MetricsHistoryPoint[];
 }
 
+/** Collection interval, retention of each tier of the metrics history and the OpenMetrics endpoint. */
+export interface MetricsSettings {
+  collection_interval_secs: number;
+  raw_retention_hours: number;
+  minute_retention_hours: number;
+  hour_retention_days: number;
+  day_retention_days: number;
+  /** Serve `http://127.0.0.1:<openmetrics_port>/metrics` to scrapers. */
+  openmetrics_enabled: boolean;
+  openmetrics_port: number;
+}
+
+/** Resource usage of one process over a time range of the per-process history. */
+export interface ProcessUsage {
+  pid: number;
+  user: string;
+  command: string;
+  first_seen: string;
+  last_seen: string;
+  sample_count: number;
+  /** CPU in percent of one core, measured between samples. */
+  cpu_avg: number;
+  cpu_max: number;
+  rss_avg: number;
+  rss_max: number;
+}
+
+export interface TopProcessesResponse {
+  distro_name: string;
+  granularity: "raw" | "1m";
+  from: string;
+  to: string;
+  processes: ProcessUsage[];
+}
+
+/** Resource usage of one systemd unit over a time range of the per-service history. */
+export interface ServiceUsage {
+  unit: string;
+  first_seen: string;
+  last_seen: string;
+  sample_count: number;
+  /** CPU in percent of one core, measured between samples. */
+  cpu_avg: number;
+  cpu_max: number;
+  memory_avg: number;
+  memory_max: number;
+  /** Bytes per second. */
+  io_read_avg: number;
+  io_write_avg: number;
+}
+
+export interface ServiceUsageResponse {
+  distro_name: string;
+  granularity: "raw" | "1m";
+  from: string;
+  to: string;
+  /** By peak memory; empty for distros not booted with systemd. */
+  services: ServiceUsage[];
+}
+
+export type MetricsExportFormat = "csv" | "jsonl" | "arrow";
+
+export interface ExportMetricsArgs {
+  distro_names: string[];
+  from: string;
+  to: string;
+  /** Defaults to the tier the history chart reads for the range. */
+  granularity?: "raw" | "1m" | "1h" | "1d";
+  format: MetricsExportFormat;
+  path: string;
+}
+
+export interface MetricsExportResponse {
+  path: string;
+  granularity: "raw" | "1m" | "1h" | "1d";
+  rows: number;
+  bytes: number;
+}
+
 // --- Alert types ---
 
-export type AlertType = "cpu" | "memory" | "disk";
+export type AlertType =
+  | "cpu"
+  | "memory"
+  | "disk"
+  | "swap"
+  | "load_per_core"
+  | "tcp_time_wait"
+  | "disk_write"
+  | "gpu_vram";
+
+/**
+ * Unit of a threshold and its alert values. `absolute` is the metric's own unit:
+ * bytes (memory, disk, swap, VRAM), bytes/s (disk write), sockets (TIME_WAIT)
+ * or the raw ratio (load per core).
+ */
+export type AlertUnit = "percent" | "absolute";
 
 export interface AlertThreshold {
   alert_type: AlertType;
+  /** Threshold value, in `unit`. */
   threshold_percent: number;
   enabled: boolean;
+  /** Defaults to `percent`. */
+  unit?: AlertUnit;
+  /** Distro this threshold overrides the global default for; absent for a default. */
+  distro_name?: string | null;
+  /** Seconds the value must stay at or above the threshold before the alert fires (0 = at once). */
+  sustained_secs?: number;
+  /** A firing alert resolves once the value drops below this; defaults to `threshold_percent`. */
+  clear_percent?: number | null;
+  /** Notification channel ids the alert is sent to; empty sends a desktop notification. */
+  channels?: string[];
+}
+
+// --- Notification channels ---
+
+/** Id of the built-in desktop channel. */
+export const DESKTOP_CHANNEL_ID = "desktop";
+
+export type SmtpSecurity = "start_tls" | "tls" | "none";
+
+export type ChannelConfig =
+  | { kind: "desktop" }
+  /** JSON POST with a Slack/Teams-compatible `text` field plus the structured alert. */
+  | { kind: "webhook"; url: string }
+  | {
+      kind: "email";
+      smtp_host: string;
+      smtp_port: number;
+      security?: SmtpSecurity;
+      username?: string | null;
+      /** Never returned by the backend; leave empty to keep the stored one. */
+      password?: string | null;
+      from: string;
+      to: string[];
+    };
+
+export interface NotificationChannel {
+  /** Empty for a new channel; assigned on save. */
+  id: string;
+  name: string;
+  enabled: boolean;
+  config: ChannelConfig;
+}
+
+export type DeliveryStatus = "delivered" | "failed";
+
+export interface NotificationDelivery {
+  id: number;
+  channel_id: string;
+  channel_name: string;
+  alert_id: number | null;
+  distro_name: string;
+  title: string;
+  status: DeliveryStatus;
+  attempts: number;
+  /** Error of the last attempt, when the delivery failed. */
+  error: string | null;
+  timestamp: string;
 }
 
 export interface AlertRecord {
@@ -111,6 +263,48 @@ export interface AlertRecord {
   alert_type: AlertType;
   threshold: number;
   actual_value: number;
+  unit: AlertUnit;
   timestamp: string;
   acknowledged: boolean;
+  /** Set once the value dropped below the clear threshold; null while firing. */
+  resolved_at: string | null;
+  /** Value that resolved the alert; null while firing or when the distro stopped. */
+  resolved_value: number | null;
+  /** Silence or maintenance window the alert fired during; suppressed alerts send no notification. */
+  suppressed_by?: string | null;
+}
+
+/** Which alerts a silence or maintenance window covers; null matches everything. */
+export interface AlertMatcher {
+  distro_name?: string | null;
+  alert_type?: AlertType | null;
+}
+
+export interface AlertSilence extends AlertMatcher {
+  id: string;
+  reason: string;
+  starts_at: string;
+  ends_at: string;
+  created_at: string;
+}
+
+export interface CreateAlertSilenceArgs extends AlertMatcher {
+  duration_minutes: number;
+  reason: string;
+}
+
+export type Weekday = "Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun";
+
+/** Recurring window in local time during which matching alerts are suppressed. */
+export interface MaintenanceWindow extends AlertMatcher {
+  /** Empty for a new window; assigned on save. */
+  id: string;
+  name: string;
+  /** Days the window starts on; empty for every day. */
+  days: Weekday[];
+  /** Local start time, `HH:MM`. */
+  start_time: string;
+  /** At most 1440 (a day); a window may run past midnight. */
+  duration_minutes: number;
+  enabled: boolean;
 }
@@ -10,19 +10,261 @@ export interface Snapshot {
   parent_id: string | null;
   created_at: string;
   status: string;
+  checksum?: string | null;
+  tags: string[];
+  /** Pinned snapshots are skipped by retention and deletion sweeps. */
+  pinned: boolean;
+  /** What unlocks an encrypted archive; `null` for a plain one. */
+  encryption: "passphrase" | "keyfile" | null;
+}
+
+/** Passphrase or keyfile path of an encrypted snapshot; give at most one. */
+export interface SnapshotSecret {
+  passphrase?: string;
+  keyfile?: string;
+}
+
+/** Filters of `list_snapshots`; omitted fields match everything. */
+export interface SnapshotFilter {
+  /** A snapshot must carry all of these tags. */
+  tags?: string[];
+  /** RFC 3339 bounds, both inclusive. */
+  created_after?: string;
+  created_before?: string;
+  min_size_bytes?: number;
+  max_size_bytes?: number;
+  pinned?: boolean;
+}
+
+export interface UpdateSnapshotMetadataArgs {
+  snapshot_id: string;
+  name?: string;
+  /** Notes; an empty string clears them. */
+  description?: string;
+  /** Replaces the whole tag set. */
+  tags?: string[];
+  pinned?: boolean;
 }
 
 export interface CreateSnapshotArgs {
   distro_name: string;
   name: string;
   description?: string;
-  format?: "tar" | "vhdx";
+  format?: "tar" | "tar.zst" | "tar.gz" | "vhdx";
   output_dir: string;
+  parent_id?: string;
+  storage?: "file" | "chunked";
+  /** Encrypt the archive (tar formats, non-incremental only). */
+  passphrase?: string;
+  keyfile?: string;
 }
 
 export interface RestoreSnapshotArgs {
   snapshot_id: string;
-  mode: "clone" | "overwrite";
+  mode: "clone" | "overwrite" | "files";
   new_name?: string;
   install_location?: string;
+  /** Files or directories to copy back into the running distro (`files` mode). */
+  paths?: string[];
+  force?: boolean;
+  /** Required for encrypted snapshots. */
+  passphrase?: string;
+  keyfile?: string;
+}
+
+export interface PreflightCheck {
+  check:
+    | "status"
+    | "archive"
+    | "encryption"
+    | "format"
+    | "target"
+    | "install_location"
+    | "running"
+    | "disk_space"
+    | "default_user";
+  status: "pass" | "warn" | "fail";
+  message: string;
+}
+
+/** What `restore_snapshot` with the same args would run into; nothing is changed. */
+export interface RestorePreflight {
+  snapshot_id: string;
+  mode: RestoreSnapshotArgs["mode"];
+  target_name: string;
+  install_location: string;
+  /** No check failed. */
+  ready: boolean;
+  archive_bytes: number;
+  required_bytes: number | null;
+  available_bytes: number | null;
+  default_user: string | null;
+  /** Running distros stopped by the restore's `wsl --shutdown`. */
+  stopped_distros: string[];
+  checks: PreflightCheck[];
+}
+
+/** Encrypt, re-encrypt or (without a new secret) decrypt a snapshot. */
+export interface RekeySnapshotArgs {
+  snapshot_id: string;
+  current_passphrase?: string;
+  current_keyfile?: string;
+  new_passphrase?: string;
+  new_keyfile?: string;
+}
+
+export interface VerifySnapshotResult {
+  snapshot: Snapshot;
+  outcome: "intact" | "recorded" | "unavailable" | "corrupted";
+  detail: string | null;
+}
+
+export interface RescanReport {
+  imported: Snapshot[];
+  relocated: Snapshot[];
+  missing: Snapshot[];
+  orphan_files: string[];
+  sidecars_written: number;
+}
+
+export interface SnapshotEntry {
+  path: string;
+  kind: "file" | "directory" | "symlink" | "hardlink" | "other";
+  size_bytes: number;
+  /** Permission bits, e.g. 0o644. */
+  mode: number;
+  uid: number;
+  gid: number;
+  user: string | null;
+  group: string | null;
+  link_target: string | null;
+}
+
+export interface SnapshotTreeNode extends SnapshotEntry {
+  name: string;
+  children: SnapshotTreeNode[];
+  /** Children omitted because of the requested depth. */
+  truncated: boolean;
+}
+
+export interface ModifiedEntry {
+  path: string;
+  before: SnapshotEntry;
+  after: SnapshotEntry;
+  content_changed: boolean;
+  mode_changed: boolean;
+  owner_changed: boolean;
+}
+
+export interface SnapshotDiff {
+  from_id: string;
+  to_id: string;
+  distro_name: string;
+  added: SnapshotEntry[];
+  removed: SnapshotEntry[];
+  modified: ModifiedEntry[];
+  unchanged: number;
+}
+
+/** Payload of the `snapshot-progress` event, emitted about once a second. */
+export interface SnapshotProgress {
+  snapshot_id: string;
+  distro_name: string;
+  phase: "export" | "import" | "upload" | "download";
+  bytes_done: number;
+  /** Estimated final size; null when unknown (e.g. compressed restores). */
+  bytes_total: number | null;
+}
+
+export type ScheduleFrequency = "hourly" | "daily" | "weekly";
+
+export interface SnapshotSchedule {
+  id: string;
+  distro_name: string;
+  frequency: ScheduleFrequency;
+  format: string;
+  storage: "file" | "chunked";
+  output_dir: string;
+  keep_hourly: number;
+  keep_daily: number;
+  keep_weekly: number;
+  enabled: boolean;
+  last_run_at: string | null;
+  created_at: string;
+}
+
+export interface SaveScheduleArgs {
+  id?: string;
+  distro_name: string;
+  frequency: ScheduleFrequency;
+  format?: "tar" | "tar.zst" | "tar.gz" | "vhdx";
+  storage?: "file" | "chunked";
+  output_dir: string;
+  keep_hourly?: number;
+  keep_daily?: number;
+  keep_weekly?: number;
+  enabled?: boolean;
+}
+
+/** S3-compatible bucket snapshots are replicated to. The secret is never returned. */
+export interface ReplicationTarget {
+  endpoint: string;
+  region: string;
+  bucket: string;
+  prefix: string;
+  access_key_id: string;
+  has_secret: boolean;
+  path_style: boolean;
+  auto_replicate: boolean;
+}
+
+export interface SetReplicationTargetArgs {
+  endpoint: string;
+  region?: string;
+  bucket: string;
+  prefix?: string;
+  access_key_id: string;
+  /** Omit (or leave empty) to keep the current secret. */
+  secret_access_key?: string;
+  path_style?: boolean;
+  auto_replicate?: boolean;
+}
+
+export interface SnapshotReplication {
+  snapshot_id: string;
+  object_key: string;
+  status: "uploading" | "replicated" | "failed";
+  error: string | null;
+  bytes_uploaded: number;
+  total_bytes: number;
+  updated_at: string;
+}
+
+export type DestructiveOperation =
+  | "delete_distro"
+  | "restore_overwrite"
+  | "resize_vhd"
+  | "compact_vhdx";
+
+/** Opt-in snapshot taken before destructive operations on a distro. */
+export interface SafetyPolicy {
+  distro_name: string;
+  operations: DestructiveOperation[];
+  format: string;
+  output_dir: string;
+  /** 0 keeps any number of safety snapshots. */
+  keep_last: number;
+  /** 0 keeps safety snapshots regardless of age. */
+  max_age_hours: number;
+  updated_at: string;
+}
+
+export interface SaveSafetyPolicyArgs {
+  distro_name: string;
+  /** Empty turns safety snapshots off for the distro. */
+  operations: DestructiveOperation[];
+  format?: string;
+  output_dir: string;
+  keep_last?: number;
+  max_age_hours?: number;
 }